@@ -64,6 +64,7 @@ port = 5433
 ssl = on
 ssl_cert_file = 'server.crt'
 ssl_key_file = 'server.key'
+wal_level = logical
 EOCONF
 
 cat > "$PGDATA/pg_hba.conf" <<-EOCONF
@@ -84,6 +85,9 @@ host    all             ssl_user        ::0/0                reject
 host    all             postgres        0.0.0.0/0            trust
 # IPv6 local connections:
 host    all             postgres        ::0/0                trust
+# Replication connections:
+host    replication     postgres        0.0.0.0/0            trust
+host    replication     postgres        ::0/0                trust
 # Unix socket connections:
 local   all             postgres                             trust
 EOCONF
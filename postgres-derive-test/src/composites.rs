@@ -163,6 +163,72 @@ fn wrong_name() {
     assert!(err.source().unwrap().is::<WrongType>());
 }
 
+#[test]
+fn default_field() {
+    #[derive(FromSql, ToSql, Debug, PartialEq)]
+    #[postgres(name = "inventory_item")]
+    struct InventoryItem {
+        name: String,
+        supplier_id: i32,
+        #[postgres(default)]
+        price: Option<f64>,
+    }
+
+    #[derive(FromSql, Debug, PartialEq)]
+    #[postgres(name = "priced_item")]
+    struct PricedItem {
+        name: String,
+        supplier_id: i32,
+        #[postgres(default)]
+        price: Option<f64>,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+    conn.batch_execute(
+        "CREATE TYPE pg_temp.inventory_item AS (
+            name TEXT,
+            supplier_id INT
+        );
+        CREATE TYPE pg_temp.priced_item AS (
+            name TEXT,
+            supplier_id INT,
+            price DOUBLE PRECISION
+        );",
+    )
+    .unwrap();
+
+    let item = conn
+        .query_one("SELECT ROW('foobar', 100)::inventory_item", &[])
+        .unwrap()
+        .get::<_, InventoryItem>(0);
+    assert_eq!(
+        item,
+        InventoryItem {
+            name: "foobar".to_owned(),
+            supplier_id: 100,
+            price: None,
+        }
+    );
+
+    let err = conn
+        .execute("SELECT $1::inventory_item", &[&item])
+        .unwrap_err();
+    assert!(err.source().unwrap().is::<WrongType>());
+
+    let item = conn
+        .query_one("SELECT ROW('foobar', 100, 15.50)::priced_item", &[])
+        .unwrap()
+        .get::<_, PricedItem>(0);
+    assert_eq!(
+        item,
+        PricedItem {
+            name: "foobar".to_owned(),
+            supplier_id: 100,
+            price: Some(15.50),
+        }
+    );
+}
+
 #[test]
 fn extra_field() {
     #[derive(FromSql, ToSql, Debug, PartialEq)]
@@ -294,7 +360,6 @@ fn generics() {
         price: Option<U>,
     }
 
-    // doesn't make sense to implement derived FromSql on a type with borrows
     #[derive(ToSql, Debug, PartialEq)]
     #[postgres(name = "InventoryItem")]
     struct InventoryItemRef<'a, T: 'a + Clone, U>
@@ -346,3 +411,179 @@ fn generics() {
         },
     );
 }
+
+#[test]
+fn nested() {
+    #[derive(FromSql, ToSql, Debug, PartialEq)]
+    #[postgres(name = "supplier")]
+    struct Supplier {
+        id: i32,
+        name: String,
+    }
+
+    #[derive(FromSql, ToSql, Debug, PartialEq)]
+    #[postgres(name = "inventory_item")]
+    struct InventoryItem {
+        name: String,
+        supplier: Supplier,
+        alternates: Vec<Supplier>,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+    conn.batch_execute(
+        "CREATE TYPE pg_temp.supplier AS (
+            id INT,
+            name TEXT
+        );
+        CREATE TYPE pg_temp.inventory_item AS (
+            name TEXT,
+            supplier supplier,
+            alternates supplier[]
+        );",
+    )
+    .unwrap();
+
+    let item = InventoryItem {
+        name: "foobar".to_owned(),
+        supplier: Supplier {
+            id: 100,
+            name: "acme".to_owned(),
+        },
+        alternates: vec![
+            Supplier {
+                id: 101,
+                name: "globex".to_owned(),
+            },
+            Supplier {
+                id: 102,
+                name: "initech".to_owned(),
+            },
+        ],
+    };
+
+    let item_empty = InventoryItem {
+        name: "foobar".to_owned(),
+        supplier: Supplier {
+            id: 100,
+            name: "acme".to_owned(),
+        },
+        alternates: vec![],
+    };
+
+    test_type(
+        &mut conn,
+        "inventory_item",
+        &[
+            (
+                item,
+                "ROW('foobar', ROW(100, 'acme'), \
+                 ARRAY[ROW(101, 'globex'), ROW(102, 'initech')]::supplier[])",
+            ),
+            (
+                item_empty,
+                "ROW('foobar', ROW(100, 'acme'), ARRAY[]::supplier[])",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn array() {
+    #[derive(FromSql, ToSql, Debug, PartialEq)]
+    #[postgres(name = "inventory_item")]
+    struct InventoryItem {
+        name: String,
+        supplier_id: i32,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+    conn.batch_execute(
+        "CREATE TYPE pg_temp.inventory_item AS (
+            name TEXT,
+            supplier_id INT
+        );",
+    )
+    .unwrap();
+
+    let items = vec![
+        InventoryItem {
+            name: "foo".to_owned(),
+            supplier_id: 100,
+        },
+        InventoryItem {
+            name: "bar".to_owned(),
+            supplier_id: 101,
+        },
+    ];
+
+    test_type(
+        &mut conn,
+        "inventory_item[]",
+        &[
+            (
+                items,
+                "ARRAY[ROW('foo', 100), ROW('bar', 101)]::inventory_item[]",
+            ),
+            (vec![], "ARRAY[]::inventory_item[]"),
+        ],
+    );
+
+    let items = conn
+        .query_one(
+            "SELECT array_agg(ROW(name, 100)::inventory_item ORDER BY name) \
+             FROM (VALUES ('foo'), ('bar')) AS t (name)",
+            &[],
+        )
+        .unwrap()
+        .get::<_, Vec<Option<InventoryItem>>>(0);
+    assert_eq!(
+        items,
+        vec![
+            Some(InventoryItem {
+                name: "bar".to_owned(),
+                supplier_id: 100,
+            }),
+            Some(InventoryItem {
+                name: "foo".to_owned(),
+                supplier_id: 100,
+            }),
+        ]
+    );
+}
+
+#[test]
+fn borrowed() {
+    #[derive(FromSql, Debug, PartialEq)]
+    #[postgres(name = "inventory_item")]
+    struct InventoryItem<'a> {
+        name: &'a str,
+        tags: Vec<&'a str>,
+        data: Option<&'a [u8]>,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+    conn.batch_execute(
+        "CREATE TYPE pg_temp.inventory_item AS (
+            name TEXT,
+            tags TEXT[],
+            data BYTEA
+        );",
+    )
+    .unwrap();
+
+    let row = conn
+        .query_one(
+            "SELECT ROW('foobar', ARRAY['a', 'b'], '\\x0102')::inventory_item",
+            &[],
+        )
+        .unwrap();
+    let item = row.get::<_, InventoryItem<'_>>(0);
+    assert_eq!(
+        item,
+        InventoryItem {
+            name: "foobar",
+            tags: vec!["a", "b"],
+            data: Some(&[1, 2]),
+        }
+    );
+}
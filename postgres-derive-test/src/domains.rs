@@ -119,3 +119,46 @@ fn domain_in_composite() {
         )],
     );
 }
+
+#[test]
+fn domain_base_type_in_composite() {
+    #[derive(FromSql, ToSql, Debug, PartialEq)]
+    #[postgres(name = "point_2d")]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(FromSql, ToSql, Debug, PartialEq)]
+    #[postgres(name = "composite")]
+    struct Composite {
+        id: i32,
+        point: Point,
+    }
+
+    let mut conn = Client::connect("user=postgres host=localhost port=5433", NoTls).unwrap();
+    conn.batch_execute(
+        "
+            CREATE DOMAIN pg_temp.positive AS INT CHECK (VALUE > 0);\
+            CREATE TYPE pg_temp.point_2d AS (x INT, y INT);\
+            CREATE DOMAIN pg_temp.origin_point AS point_2d;\
+            CREATE TYPE pg_temp.composite AS (
+                id positive,
+                point origin_point
+            );
+        ",
+    )
+    .unwrap();
+
+    test_type(
+        &mut conn,
+        "composite",
+        &[(
+            Composite {
+                id: 1,
+                point: Point { x: 2, y: 3 },
+            },
+            "ROW(1, ROW(2, 3))",
+        )],
+    );
+}
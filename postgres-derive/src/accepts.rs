@@ -68,6 +68,26 @@ pub fn enum_body(name: &str, variants: &[Variant], allow_mismatch: bool) -> Toke
 
 pub fn composite_body(name: &str, trait_: &str, fields: &[Field]) -> TokenStream {
     let num_fields = fields.len();
+    // fields with a default may be missing from the Postgres type when deserializing
+    let required_names = fields
+        .iter()
+        .filter(|f| !f.default || trait_ != "FromSql")
+        .map(|f| &f.name)
+        .collect::<Vec<_>>();
+    let fields_check = if required_names.len() == num_fields {
+        quote! {
+            if fields.len() != #num_fields {
+                return false;
+            }
+        }
+    } else {
+        quote! {
+            let required: &[&str] = &[#(#required_names),*];
+            if !required.iter().all(|name| fields.iter().any(|f| f.name() == *name)) {
+                return false;
+            }
+        }
+    };
     let trait_ = Ident::new(trait_, Span::call_site());
     let traits = iter::repeat(&trait_);
     let field_names = fields.iter().map(|f| &f.name);
@@ -80,15 +100,17 @@ pub fn composite_body(name: &str, trait_: &str, fields: &[Field]) -> TokenStream
 
         match *type_.kind() {
             ::postgres_types::Kind::Composite(ref fields) => {
-                if fields.len() != #num_fields {
-                    return false;
-                }
+                #fields_check
 
                 fields.iter().all(|f| {
                     match f.name() {
                         #(
                             #field_names => {
-                                <#field_types as ::postgres_types::#traits>::accepts(f.type_())
+                                ::postgres_types::private::accepted_type(
+                                    f.type_(),
+                                    <#field_types as ::postgres_types::#traits>::accepts,
+                                )
+                                .is_some()
                             }
                         )*
                         _ => false,
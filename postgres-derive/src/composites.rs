@@ -10,11 +10,18 @@ pub struct Field {
     pub name: String,
     pub ident: Ident,
     pub type_: Type,
+    pub default: bool,
 }
 
 impl Field {
     pub fn parse(raw: &syn::Field, rename_all: Option<RenameRule>) -> Result<Field, Error> {
         let overrides = Overrides::extract(&raw.attrs, false)?;
+        if overrides.flatten {
+            return Err(Error::new_spanned(
+                raw,
+                "#[postgres(flatten)] is only supported by #[derive(FromRow)]",
+            ));
+        }
         let ident = raw.ident.as_ref().unwrap().clone();
 
         // field level name override takes precendence over container level rename_all override
@@ -35,6 +42,7 @@ impl Field {
             name,
             ident,
             type_: raw.ty.clone(),
+            default: overrides.default,
         })
     }
 }
@@ -19,6 +19,12 @@ impl Variant {
             }
         }
         let overrides = Overrides::extract(&raw.attrs, false)?;
+        if overrides.flatten || overrides.default {
+            return Err(Error::new_spanned(
+                raw,
+                "#[postgres(flatten)] and #[postgres(default)] are only supported by #[derive(FromRow)]",
+            ));
+        }
 
         // variant level name override takes precendence over container level rename_all override
         let name = overrides.name.unwrap_or_else(|| match rename_all {
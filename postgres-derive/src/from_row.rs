@@ -0,0 +1,105 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DataStruct, DeriveInput, Error, Fields};
+
+use crate::fromsql::build_generics;
+use crate::overrides::Overrides;
+
+pub fn expand_derive_fromrow(input: DeriveInput) -> Result<TokenStream, Error> {
+    let overrides = Overrides::extract(&input.attrs, true)?;
+
+    if overrides.name.is_some() || overrides.transparent || overrides.allow_mismatch {
+        return Err(Error::new_spanned(
+            &input,
+            "#[derive(FromRow)] only supports the #[postgres(rename_all = \"...\")] container attribute",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "#[derive(FromRow)] may only be applied to structs with named fields",
+            ))
+        }
+    };
+
+    let (generics, lifetime) = build_generics(&input.generics);
+
+    let mut field_idents = vec![];
+    let mut field_values = vec![];
+    for field in fields {
+        let field_overrides = Overrides::extract(&field.attrs, false)?;
+        let ident = field.ident.as_ref().unwrap();
+        let type_ = &field.ty;
+
+        if field_overrides.flatten {
+            if field_overrides.name.is_some() || field_overrides.default {
+                return Err(Error::new_spanned(
+                    field,
+                    "#[postgres(flatten)] is not allowed with #[postgres(name = \"...\")] or #[postgres(default)]",
+                ));
+            }
+
+            field_idents.push(ident);
+            field_values.push(quote! {
+                <#type_ as postgres_types::FromRow<#lifetime>>::from_row(row)?
+            });
+            continue;
+        }
+
+        // field level name override takes precendence over container level rename_all override
+        let name = match field_overrides.name {
+            Some(name) => name,
+            None => {
+                let name = ident.to_string();
+                let stripped = name.strip_prefix("r#").map(String::from).unwrap_or(name);
+
+                match overrides.rename_all {
+                    Some(rule) => rule.apply_to_field(&stripped),
+                    None => stripped,
+                }
+            }
+        };
+
+        let value = quote! {
+            postgres_types::NamedSqlRow::try_get_named(row, #name)?
+        };
+        field_idents.push(ident);
+        if field_overrides.default {
+            field_values.push(quote! {
+                if postgres_types::NamedSqlRow::contains(row, #name) {
+                    #value
+                } else {
+                    std::default::Default::default()
+                }
+            });
+        } else {
+            field_values.push(value);
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let out = quote! {
+        impl #impl_generics postgres_types::FromRow<#lifetime> for #ident #ty_generics #where_clause {
+            fn from_row<R>(row: &#lifetime R) -> std::result::Result<Self, R::Error>
+            where
+                R: postgres_types::NamedSqlRow,
+            {
+                std::result::Result::Ok(#ident {
+                    #(
+                        #field_idents: #field_values,
+                    )*
+                })
+            }
+        }
+    };
+
+    Ok(out)
+}
@@ -190,6 +190,13 @@ fn composite_body(ident: &Ident, fields: &[Field]) -> TokenStream {
         .collect::<Vec<_>>();
     let field_names = &fields.iter().map(|f| &f.name).collect::<Vec<_>>();
     let field_idents = &fields.iter().map(|f| &f.ident).collect::<Vec<_>>();
+    let field_values = fields.iter().zip(temp_vars).map(|(f, temp_var)| {
+        if f.default {
+            quote! { #temp_var.unwrap_or_else(std::default::Default::default) }
+        } else {
+            quote! { #temp_var.unwrap() }
+        }
+    });
 
     quote! {
         let fields = match *_type.kind() {
@@ -227,14 +234,30 @@ fn composite_body(ident: &Ident, fields: &[Field]) -> TokenStream {
 
         std::result::Result::Ok(#ident {
             #(
-                #field_idents: #temp_vars.unwrap(),
+                #field_idents: #field_values,
             )*
         })
     }
 }
 
-fn build_generics(source: &Generics) -> (Generics, Lifetime) {
-    // don't worry about lifetime name collisions, it doesn't make sense to derive FromSql on a struct with a lifetime
+pub(crate) fn build_generics(source: &Generics) -> (Generics, Lifetime) {
+    // a struct with lifetimes borrows from the buffer through its first one, which must outlive the others
+    if let Some(first) = source.lifetimes().next() {
+        let lifetime = first.lifetime.clone();
+        let others = source
+            .lifetimes()
+            .skip(1)
+            .map(|param| param.lifetime.clone())
+            .collect::<Vec<_>>();
+
+        let mut out = append_generic_bound(source.to_owned(), &new_fromsql_bound(&lifetime));
+        if let Some(first) = out.lifetimes_mut().next() {
+            first.bounds.extend(others);
+        }
+
+        return (out, lifetime);
+    }
+
     let lifetime = Lifetime::new("'a", Span::call_site());
 
     let mut out = append_generic_bound(source.to_owned(), &new_fromsql_bound(&lifetime));
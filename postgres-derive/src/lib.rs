@@ -10,8 +10,10 @@ mod accepts;
 mod case;
 mod composites;
 mod enums;
+mod from_row;
 mod fromsql;
 mod overrides;
+mod rows;
 mod tosql;
 
 #[proc_macro_derive(ToSql, attributes(postgres))]
@@ -31,3 +33,30 @@ pub fn derive_fromsql(input: TokenStream) -> TokenStream {
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
+
+#[proc_macro_derive(ToSqlRow)]
+pub fn derive_tosqlrow(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input);
+
+    rows::expand_derive_tosqlrow(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(FromSqlRow)]
+pub fn derive_fromsqlrow(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input);
+
+    rows::expand_derive_fromsqlrow(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(FromRow, attributes(postgres))]
+pub fn derive_fromrow(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input);
+
+    from_row::expand_derive_fromrow(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
@@ -8,6 +8,8 @@ pub struct Overrides {
     pub rename_all: Option<RenameRule>,
     pub transparent: bool,
     pub allow_mismatch: bool,
+    pub flatten: bool,
+    pub default: bool,
 }
 
 impl Overrides {
@@ -17,6 +19,8 @@ impl Overrides {
             rename_all: None,
             transparent: false,
             allow_mismatch: false,
+            flatten: false,
+            default: false,
         };
 
         for attr in attrs {
@@ -92,6 +96,18 @@ impl Overrides {
                                 ));
                             }
                             overrides.allow_mismatch = true;
+                        } else if path.is_ident("flatten") || path.is_ident("default") {
+                            if container_attr {
+                                return Err(Error::new_spanned(
+                                    &path,
+                                    format!("{} is a field attribute", path.get_ident().unwrap()),
+                                ));
+                            }
+                            if path.is_ident("flatten") {
+                                overrides.flatten = true;
+                            } else {
+                                overrides.default = true;
+                            }
                         } else {
                             return Err(Error::new_spanned(path, "unknown override"));
                         }
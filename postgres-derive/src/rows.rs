@@ -0,0 +1,90 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DataStruct, DeriveInput, Error, Fields, Index, Member};
+
+use crate::composites::append_generic_bound;
+use crate::fromsql::build_generics;
+
+pub fn expand_derive_tosqlrow(input: DeriveInput) -> Result<TokenStream, Error> {
+    let members = struct_members(&input, "ToSqlRow")?;
+
+    let ident = &input.ident;
+    let generics = append_generic_bound(
+        input.generics.to_owned(),
+        &parse_quote!(postgres_types::ToSql),
+    );
+    let generics = append_generic_bound(generics, &parse_quote!(std::marker::Sync));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let out = quote! {
+        impl #impl_generics postgres_types::ToSqlRow for #ident #ty_generics #where_clause {
+            fn to_sql_row(&self) -> std::vec::Vec<&(dyn postgres_types::ToSql + std::marker::Sync)> {
+                std::vec![
+                    #(
+                        &self.#members as &(dyn postgres_types::ToSql + std::marker::Sync),
+                    )*
+                ]
+            }
+        }
+    };
+
+    Ok(out)
+}
+
+pub fn expand_derive_fromsqlrow(input: DeriveInput) -> Result<TokenStream, Error> {
+    let members = struct_members(&input, "FromSqlRow")?;
+    let indices = 0..members.len();
+    let temp_vars = (0..members.len())
+        .map(|i| format_ident!("__field{}", i))
+        .collect::<Vec<_>>();
+
+    let ident = &input.ident;
+    let (generics, lifetime) = build_generics(&input.generics);
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let out = quote! {
+        impl #impl_generics postgres_types::FromSqlRow<#lifetime> for #ident #ty_generics #where_clause {
+            fn from_sql_row<R>(row: &#lifetime R) -> std::result::Result<Self, R::Error>
+            where
+                R: postgres_types::SqlRow,
+            {
+                #(
+                    let #temp_vars = postgres_types::SqlRow::try_get(row, #indices)?;
+                )*
+
+                std::result::Result::Ok(#ident {
+                    #(
+                        #members: #temp_vars,
+                    )*
+                })
+            }
+        }
+    };
+
+    Ok(out)
+}
+
+fn struct_members(input: &DeriveInput, derive: &str) -> Result<Vec<Member>, Error> {
+    match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => Ok(fields
+            .named
+            .iter()
+            .map(|field| Member::Named(field.ident.clone().unwrap()))
+            .collect()),
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(fields),
+            ..
+        }) => Ok((0..fields.unnamed.len())
+            .map(|i| Member::Unnamed(Index::from(i)))
+            .collect()),
+        _ => Err(Error::new_spanned(
+            input,
+            format!(
+                "#[derive({})] may only be applied to structs with fields",
+                derive
+            ),
+        )),
+    }
+}
@@ -187,7 +187,7 @@ fn composite_body(fields: &[Field]) -> TokenStream {
             buf.extend_from_slice(&[0; 4]);
             let r = match field.name() {
                 #(
-                    #field_names => postgres_types::ToSql::to_sql(&self.#field_idents, field.type_(), buf),
+                    #field_names => postgres_types::private::write_value(&self.#field_idents, field.type_(), buf),
                 )*
                 _ => unreachable!(),
             };
@@ -60,7 +60,7 @@ use std::pin::Pin;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite, BufReader, ReadBuf};
 use tokio_postgres::tls;
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 use tokio_postgres::tls::MakeTlsConnect;
 use tokio_postgres::tls::{ChannelBinding, TlsConnect};
 
@@ -69,12 +69,12 @@ mod test;
 
 /// A `MakeTlsConnect` implementation using the `native-tls` crate.
 ///
-/// Requires the `runtime` Cargo feature (enabled by default).
-#[cfg(feature = "runtime")]
+/// Requires the `runtime-core` Cargo feature (enabled by default).
+#[cfg(feature = "runtime-core")]
 #[derive(Clone)]
 pub struct MakeTlsConnector(native_tls::TlsConnector);
 
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 impl MakeTlsConnector {
     /// Creates a new connector.
     pub fn new(connector: native_tls::TlsConnector) -> MakeTlsConnector {
@@ -82,7 +82,7 @@ impl MakeTlsConnector {
     }
 }
 
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 impl<S> MakeTlsConnect<S> for MakeTlsConnector
 where
     S: AsyncRead + AsyncWrite + Unpin + 'static + Send,
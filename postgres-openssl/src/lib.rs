@@ -45,30 +45,48 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! The hashing and randomness used during password authentication can also be delegated to OpenSSL, for example when
+//! it has been configured to use a FIPS validated provider:
+//!
+//! ```no_run
+//! use postgres_openssl::OpensslCrypto;
+//! use postgres_protocol::authentication::crypto;
+//!
+//! crypto::install_default(&OpensslCrypto).unwrap();
+//! ```
 #![warn(rust_2018_idioms, clippy::all, missing_docs)]
 
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 use openssl::error::ErrorStack;
-use openssl::hash::MessageDigest;
+use openssl::hash::{self, MessageDigest};
 use openssl::nid::Nid;
-#[cfg(feature = "runtime")]
-use openssl::ssl::SslConnector;
+use openssl::pkcs5;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
 use openssl::ssl::{self, ConnectConfiguration, SslConnectorBuilder, SslRef};
+#[cfg(feature = "runtime-core")]
+use openssl::ssl::{SslConnector, SslFiletype};
+#[cfg(feature = "runtime-core")]
+use openssl::x509::store::{X509Lookup, X509StoreBuilder};
 use openssl::x509::X509VerifyResult;
+use postgres_protocol::authentication::crypto::CryptoProvider;
 use std::error::Error;
 use std::fmt::{self, Debug};
 use std::future::Future;
 use std::io;
 use std::pin::Pin;
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite, BufReader, ReadBuf};
 use tokio_openssl::SslStream;
 use tokio_postgres::tls;
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 use tokio_postgres::tls::MakeTlsConnect;
 use tokio_postgres::tls::{ChannelBinding, TlsConnect};
+#[cfg(feature = "runtime-core")]
+use tokio_postgres::SocketConfig;
 
 #[cfg(test)]
 mod test;
@@ -78,15 +96,15 @@ type ConfigCallback =
 
 /// A `MakeTlsConnect` implementation using the `openssl` crate.
 ///
-/// Requires the `runtime` Cargo feature (enabled by default).
-#[cfg(feature = "runtime")]
+/// Requires the `runtime-core` Cargo feature (enabled by default).
+#[cfg(feature = "runtime-core")]
 #[derive(Clone)]
 pub struct MakeTlsConnector {
     connector: SslConnector,
     config: Arc<ConfigCallback>,
 }
 
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 impl MakeTlsConnector {
     /// Creates a new connector.
     pub fn new(connector: SslConnector) -> MakeTlsConnector {
@@ -107,7 +125,7 @@ impl MakeTlsConnector {
     }
 }
 
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 impl<S> MakeTlsConnect<S> for MakeTlsConnector
 where
     S: AsyncRead + AsyncWrite + Unpin + Debug + 'static + Sync + Send,
@@ -121,6 +139,30 @@ where
         (self.config)(&mut ssl, domain)?;
         Ok(TlsConnector::new(ssl, domain))
     }
+
+    /// Like `make_tls_connect`, but also loads the files set with the `sslrootcert`, `sslcert`, and `sslkey` options.
+    ///
+    /// The certificate authorities in `sslrootcert` replace those of the `SslConnector`. The files are read in PEM
+    /// format before the callback set with `set_callback` is run.
+    fn make_tls_connect_for(&mut self, config: &SocketConfig) -> Result<TlsConnector, ErrorStack> {
+        let domain = config.hostname().unwrap_or("");
+        let mut ssl = self.connector.configure()?;
+        if let Some(path) = config.ssl_root_cert() {
+            let mut store = X509StoreBuilder::new()?;
+            store
+                .add_lookup(X509Lookup::file())?
+                .load_cert_file(path, SslFiletype::PEM)?;
+            ssl.set_verify_cert_store(store.build())?;
+        }
+        if let Some(path) = config.ssl_cert() {
+            ssl.set_certificate_chain_file(path)?;
+        }
+        if let Some(path) = config.ssl_key() {
+            ssl.set_private_key_file(path, SslFiletype::PEM)?;
+        }
+        (self.config)(&mut ssl, domain)?;
+        Ok(TlsConnector::new(ssl, domain))
+    }
 }
 
 /// A `TlsConnect` implementation using the `openssl` crate.
@@ -257,3 +299,63 @@ fn tls_server_end_point(ssl: &SslRef) -> Option<Vec<u8>> {
 pub fn set_postgresql_alpn(builder: &mut SslConnectorBuilder) -> Result<(), ErrorStack> {
     builder.set_alpn_protos(b"\x0apostgresql")
 }
+
+/// A `CryptoProvider` implementation using the `openssl` crate.
+///
+/// The primitives are taken from whichever OpenSSL providers are active in the process, so this can be used to
+/// restrict password authentication to a FIPS validated module.
+///
+/// # Panics
+///
+/// The `CryptoProvider` methods panic if OpenSSL refuses to perform the operation, such as MD5 hashing when only the
+/// FIPS provider is loaded. MD5 password authentication should be disabled on the server in that case.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct OpensslCrypto;
+
+impl CryptoProvider for OpensslCrypto {
+    fn md5(&self, parts: &[&[u8]]) -> [u8; 16] {
+        let mut hasher = hash::Hasher::new(MessageDigest::md5()).expect("MD5 is not available");
+        for part in parts {
+            hasher.update(part).expect("MD5 is not available");
+        }
+        let digest = hasher.finish().expect("MD5 is not available");
+        let mut out = [0; 16];
+        out.copy_from_slice(&digest);
+        out
+    }
+
+    fn sha256(&self, data: &[u8]) -> [u8; 32] {
+        openssl::sha::sha256(data)
+    }
+
+    fn hmac_sha256(&self, key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+        let key = PKey::hmac(key).expect("HMAC-SHA-256 is not available");
+        let mut signer =
+            Signer::new(MessageDigest::sha256(), &key).expect("HMAC-SHA-256 is not available");
+        for part in parts {
+            signer.update(part).expect("HMAC-SHA-256 is not available");
+        }
+        let mut out = [0; 32];
+        signer
+            .sign(&mut out)
+            .expect("HMAC-SHA-256 is not available");
+        out
+    }
+
+    fn pbkdf2_hmac_sha256(&self, password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+        let mut out = [0; 32];
+        pkcs5::pbkdf2_hmac(
+            password,
+            salt,
+            iterations as usize,
+            MessageDigest::sha256(),
+            &mut out,
+        )
+        .expect("PBKDF2-HMAC-SHA-256 is not available");
+        out
+    }
+
+    fn fill_random(&self, buf: &mut [u8]) {
+        openssl::rand::rand_bytes(buf).expect("random number generation failed");
+    }
+}
@@ -1,5 +1,6 @@
 use futures_util::FutureExt;
 use openssl::ssl::{SslConnector, SslMethod};
+use postgres_protocol::authentication::crypto::RustCrypto;
 use tokio::net::TcpStream;
 use tokio_postgres::tls::TlsConnect;
 
@@ -122,3 +123,57 @@ async fn runtime() {
     assert_eq!(rows.len(), 1);
     assert_eq!(rows[0].get::<_, i32>(0), 1);
 }
+
+#[tokio::test]
+#[cfg(feature = "runtime")]
+async fn runtime_ssl_root_cert() {
+    // the connector itself trusts nothing, so the server's certificate is only verified through sslrootcert
+    let builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    let connector = MakeTlsConnector::new(builder.build());
+
+    let (client, connection) = tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres sslmode=require sslrootcert=../test/server.crt",
+        connector,
+    )
+    .await
+    .unwrap();
+    let connection = connection.map(|r| r.unwrap());
+    tokio::spawn(connection);
+
+    client.batch_execute("SELECT 1").await.unwrap();
+}
+
+#[tokio::test]
+#[cfg(feature = "runtime")]
+async fn runtime_ssl_root_cert_missing() {
+    let builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    let connector = MakeTlsConnector::new(builder.build());
+
+    let err = tokio_postgres::connect(
+        "host=localhost port=5433 user=postgres sslmode=require sslrootcert=../test/missing.crt",
+        connector,
+    )
+    .await
+    .err()
+    .unwrap();
+    assert!(err.to_string().contains("TLS"), "{}", err);
+}
+
+#[test]
+fn crypto_provider() {
+    let parts: &[&[u8]] = &[b"password", b"md5_user"];
+    assert_eq!(OpensslCrypto.md5(parts), RustCrypto.md5(parts));
+    assert_eq!(OpensslCrypto.sha256(b"foo"), RustCrypto.sha256(b"foo"));
+    assert_eq!(
+        OpensslCrypto.hmac_sha256(b"key", parts),
+        RustCrypto.hmac_sha256(b"key", parts)
+    );
+    assert_eq!(
+        OpensslCrypto.pbkdf2_hmac_sha256(b"pencil", b"salt", 4096),
+        RustCrypto.pbkdf2_hmac_sha256(b"pencil", b"salt", 4096)
+    );
+
+    let mut buf = [0; 32];
+    OpensslCrypto.fill_random(&mut buf);
+    assert_ne!(buf, [0; 32]);
+}
@@ -0,0 +1,64 @@
+use bytes::BytesMut;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::message::backend::{self, Message};
+
+fn data_rows(rows: usize, columns: usize) -> BytesMut {
+    let value = b"the quick brown fox jumps over the lazy dog";
+    let mut buf = BytesMut::new();
+    for _ in 0..rows {
+        backend::data_row((0..columns).map(|_| Some(&value[..])), &mut buf).unwrap();
+    }
+    backend::command_complete(&format!("SELECT {}", rows), &mut buf).unwrap();
+    backend::ready_for_query(b'I', &mut buf);
+    buf
+}
+
+fn copy_data(chunks: usize, size: usize) -> BytesMut {
+    let chunk = vec![b'x'; size];
+    let mut buf = BytesMut::new();
+    for _ in 0..chunks {
+        backend::copy_data(&chunk, &mut buf).unwrap();
+    }
+    backend::copy_done(&mut buf);
+    buf
+}
+
+fn parse_all(mut buf: BytesMut) -> usize {
+    let mut fields = 0;
+    while let Some(message) = Message::parse(&mut buf).unwrap() {
+        match message {
+            Message::DataRow(body) => fields += body.ranges().count().unwrap(),
+            Message::CopyData(body) => fields += body.data().len(),
+            _ => {}
+        }
+    }
+    fields
+}
+
+fn parse_data_rows(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_data_rows");
+    for &columns in &[1, 10, 100] {
+        let buf = data_rows(1000, columns);
+        group.throughput(Throughput::Bytes(buf.len() as u64));
+        group.bench_function(format!("{}_columns", columns), |b| {
+            b.iter_batched(|| buf.clone(), parse_all, BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+fn parse_copy_data(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_copy_data");
+    for &size in &[64, 8192] {
+        let buf = copy_data(1000, size);
+        group.throughput(Throughput::Bytes(buf.len() as u64));
+        group.bench_function(format!("{}_bytes", size), |b| {
+            b.iter_batched(|| buf.clone(), parse_all, BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse_data_rows, parse_copy_data);
+criterion_main!(benches);
@@ -0,0 +1,145 @@
+//! Pluggable implementations of the cryptographic primitives used during authentication.
+//!
+//! MD5 password hashing, SCRAM-SHA-256 and the functions in [`password`](crate::password) perform all of their
+//! hashing and random number generation through a [`CryptoProvider`]. By default this is [`RustCrypto`], which is
+//! built on pure-Rust implementations. Deployments which must use a particular implementation, such as a FIPS 140
+//! validated module, can call [`install_default`] once at startup, before any connections are made.
+use hmac::{Hmac, Mac};
+use md5::{Digest, Md5};
+use rand::RngCore;
+use sha2::Sha256;
+use std::fmt;
+use std::sync::OnceLock;
+
+static DEFAULT: OnceLock<&'static dyn CryptoProvider> = OnceLock::new();
+
+/// A source of the hash functions and randomness used for authentication.
+pub trait CryptoProvider: fmt::Debug + Send + Sync {
+    /// Returns the MD5 digest of the concatenation of `parts`.
+    fn md5(&self, parts: &[&[u8]]) -> [u8; 16];
+
+    /// Returns the SHA-256 digest of `data`.
+    fn sha256(&self, data: &[u8]) -> [u8; 32];
+
+    /// Returns the HMAC-SHA-256 of the concatenation of `parts`, keyed with `key`.
+    fn hmac_sha256(&self, key: &[u8], parts: &[&[u8]]) -> [u8; 32];
+
+    /// Returns the first block of PBKDF2-HMAC-SHA-256, which is the `Hi` function of [RFC 5802].
+    ///
+    /// The default implementation is built on [`CryptoProvider::hmac_sha256`].
+    ///
+    /// [RFC 5802]: https://tools.ietf.org/html/rfc5802#section-2.2
+    fn pbkdf2_hmac_sha256(&self, password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+        let mut prev = self.hmac_sha256(password, &[salt, &[0, 0, 0, 1]]);
+        let mut hi = prev;
+
+        for _ in 1..iterations {
+            prev = self.hmac_sha256(password, &[&prev]);
+            for (hi, prev) in hi.iter_mut().zip(prev) {
+                *hi ^= prev;
+            }
+        }
+
+        hi
+    }
+
+    /// Fills `buf` with cryptographically secure random bytes.
+    ///
+    /// This is used to generate SCRAM nonces and password salts.
+    fn fill_random(&self, buf: &mut [u8]);
+}
+
+/// The default provider, built on the pure-Rust `md-5`, `sha2`, `hmac` and `rand` crates.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct RustCrypto;
+
+impl CryptoProvider for RustCrypto {
+    fn md5(&self, parts: &[&[u8]]) -> [u8; 16] {
+        let mut md5 = Md5::new();
+        for part in parts {
+            md5.update(part);
+        }
+        md5.finalize().into()
+    }
+
+    fn sha256(&self, data: &[u8]) -> [u8; 32] {
+        Sha256::digest(data).into()
+    }
+
+    fn hmac_sha256(&self, key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+        let mut hmac =
+            Hmac::<Sha256>::new_from_slice(key).expect("HMAC is able to accept all key sizes");
+        for part in parts {
+            hmac.update(part);
+        }
+        hmac.finalize().into_bytes().into()
+    }
+
+    fn fill_random(&self, buf: &mut [u8]) {
+        // rand's ThreadRng is cryptographically secure
+        rand::rng().fill_bytes(buf);
+    }
+}
+
+/// Installs the provider used by authentication for the rest of the process.
+///
+/// This can only be done once, and only before the default provider has first been used. If a provider is already in
+/// place, the provider passed in is returned as an error.
+pub fn install_default(
+    provider: &'static dyn CryptoProvider,
+) -> Result<(), &'static dyn CryptoProvider> {
+    let mut installed = false;
+    DEFAULT.get_or_init(|| {
+        installed = true;
+        provider
+    });
+    if installed {
+        Ok(())
+    } else {
+        Err(provider)
+    }
+}
+
+/// Returns the provider used by authentication.
+///
+/// This is the provider passed to [`install_default`], or [`RustCrypto`] if none has been installed.
+pub fn default_provider() -> &'static dyn CryptoProvider {
+    *DEFAULT.get_or_init(|| &RustCrypto)
+}
+
+/// Compares two byte strings in time independent of where they first differ.
+///
+/// Servers should use this to check passwords and password hashes, so that the time taken does not reveal how much of
+/// a guess was correct. The time taken does depend on whether the lengths are equal.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pbkdf2() {
+        // RFC 7677 test vector
+        let salt = b"\x5b\x6d\x99\x68\x9d\x12\x35\x8e\xec\xa0\x4b\x14\x12\x36\xfa\x81";
+        assert_eq!(
+            RustCrypto.pbkdf2_hmac_sha256(b"pencil", salt, 4096),
+            [
+                0xc4, 0xa4, 0x95, 0x10, 0x32, 0x3a, 0xb4, 0xf9, 0x52, 0xca, 0xc1, 0xfa, 0x99, 0x44,
+                0x19, 0x39, 0xe7, 0x8e, 0xa7, 0x4d, 0x6b, 0xe8, 0x1d, 0xdf, 0x70, 0x96, 0xe8, 0x75,
+                0x13, 0xdc, 0x61, 0x5d,
+            ]
+        );
+    }
+
+    #[test]
+    fn constant_time() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+}
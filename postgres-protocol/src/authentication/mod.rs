@@ -1,6 +1,9 @@
 //! Authentication protocol support.
-use md5::{Digest, Md5};
+use std::fmt::Write;
 
+use crate::authentication::crypto::CryptoProvider;
+
+pub mod crypto;
 pub mod sasl;
 
 /// Hashes authentication information in a way suitable for use in response
@@ -10,13 +13,26 @@ pub mod sasl;
 /// `PasswordMessage` message.
 #[inline]
 pub fn md5_hash(username: &[u8], password: &[u8], salt: [u8; 4]) -> String {
-    let mut md5 = Md5::new();
-    md5.update(password);
-    md5.update(username);
-    let output = md5.finalize_reset();
-    md5.update(format!("{:x}", output));
-    md5.update(salt);
-    format!("md5{:x}", md5.finalize())
+    md5_hash_with_provider(username, password, salt, crypto::default_provider())
+}
+
+/// Like `md5_hash`, but computes the hashes with the specified crypto provider.
+pub fn md5_hash_with_provider(
+    username: &[u8],
+    password: &[u8],
+    salt: [u8; 4],
+    provider: &dyn CryptoProvider,
+) -> String {
+    let output = hex(&provider.md5(&[password, username]));
+    format!("md5{}", hex(&provider.md5(&[output.as_bytes(), &salt])))
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        write!(s, "{:02x}", b).unwrap();
+    }
+    s
 }
 
 #[cfg(test)]
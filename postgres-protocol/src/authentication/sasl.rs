@@ -3,16 +3,17 @@
 use base64::display::Base64Display;
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
-use hmac::{Hmac, Mac};
-use rand::{self, Rng};
-use sha2::digest::FixedOutput;
-use sha2::{Digest, Sha256};
 use std::fmt::Write;
+use std::hint;
 use std::io;
 use std::iter;
 use std::mem;
+use std::ops::Deref;
 use std::str;
 
+use crate::authentication::crypto::{self, constant_time_eq, CryptoProvider};
+use crate::password::{SCRAM_DEFAULT_ITERATIONS, SCRAM_DEFAULT_SALT_LEN};
+
 const NONCE_LENGTH: usize = 24;
 
 /// The identifier of the SCRAM-SHA-256 SASL authentication mechanism.
@@ -20,6 +21,22 @@ pub const SCRAM_SHA_256: &str = "SCRAM-SHA-256";
 /// The identifier of the SCRAM-SHA-256-PLUS SASL authentication mechanism.
 pub const SCRAM_SHA_256_PLUS: &str = "SCRAM-SHA-256-PLUS";
 
+fn nonce(provider: &dyn CryptoProvider) -> String {
+    let mut bytes = [0; NONCE_LENGTH];
+    provider.fill_random(&mut bytes);
+    bytes
+        .iter()
+        .map(|b| {
+            // map onto the printable characters other than ','
+            let mut v = 0x21 + b % (0x7e - 0x21);
+            if v == 0x2c {
+                v = 0x7e
+            }
+            v as char
+        })
+        .collect()
+}
+
 // since postgres passwords are not required to exclude saslprep-prohibited
 // characters or even be valid UTF8, we run saslprep if possible and otherwise
 // return the raw password.
@@ -35,28 +52,6 @@ fn normalize(pass: &[u8]) -> Vec<u8> {
     }
 }
 
-pub(crate) fn hi(str: &[u8], salt: &[u8], i: u32) -> [u8; 32] {
-    let mut hmac =
-        Hmac::<Sha256>::new_from_slice(str).expect("HMAC is able to accept all key sizes");
-    hmac.update(salt);
-    hmac.update(&[0, 0, 0, 1]);
-    let mut prev = hmac.finalize().into_bytes();
-
-    let mut hi = prev;
-
-    for _ in 1..i {
-        let mut hmac = Hmac::<Sha256>::new_from_slice(str).expect("already checked above");
-        hmac.update(&prev);
-        prev = hmac.finalize().into_bytes();
-
-        for (hi, prev) in hi.iter_mut().zip(prev) {
-            *hi ^= prev;
-        }
-    }
-
-    hi.into()
-}
-
 enum ChannelBindingInner {
     Unrequested,
     Unsupported,
@@ -99,14 +94,34 @@ impl ChannelBinding {
     }
 }
 
+// Holds key material derived from a password, overwriting it with zeros when dropped.
+struct Zeroizing<T: AsMut<[u8]>>(T);
+
+impl<T: AsMut<[u8]>> Deref for Zeroizing<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: AsMut<[u8]>> Drop for Zeroizing<T> {
+    fn drop(&mut self) {
+        let buf = self.0.as_mut();
+        buf.fill(0);
+        // keep the writes from being optimized away as dead stores
+        hint::black_box(buf);
+    }
+}
+
 enum State {
     Update {
         nonce: String,
-        password: Vec<u8>,
+        password: Zeroizing<Vec<u8>>,
         channel_binding: ChannelBinding,
     },
     Finish {
-        salted_password: [u8; 32],
+        salted_password: Zeroizing<[u8; 32]>,
         auth_message: String,
     },
     Done,
@@ -130,34 +145,38 @@ enum State {
 pub struct ScramSha256 {
     message: String,
     state: State,
+    provider: &'static dyn CryptoProvider,
 }
 
 impl ScramSha256 {
     /// Constructs a new instance which will use the provided password for authentication.
     pub fn new(password: &[u8], channel_binding: ChannelBinding) -> ScramSha256 {
-        // rand 0.5's ThreadRng is cryptographically secure
-        let mut rng = rand::rng();
-        let nonce = (0..NONCE_LENGTH)
-            .map(|_| {
-                let mut v = rng.random_range(0x21u8..0x7e);
-                if v == 0x2c {
-                    v = 0x7e
-                }
-                v as char
-            })
-            .collect::<String>();
+        ScramSha256::with_provider(password, channel_binding, crypto::default_provider())
+    }
 
-        ScramSha256::new_inner(password, channel_binding, nonce)
+    /// Like `new`, but generates the nonce and computes the proofs with the specified crypto provider.
+    pub fn with_provider(
+        password: &[u8],
+        channel_binding: ChannelBinding,
+        provider: &'static dyn CryptoProvider,
+    ) -> ScramSha256 {
+        ScramSha256::new_inner(password, channel_binding, nonce(provider), provider)
     }
 
-    fn new_inner(password: &[u8], channel_binding: ChannelBinding, nonce: String) -> ScramSha256 {
+    fn new_inner(
+        password: &[u8],
+        channel_binding: ChannelBinding,
+        nonce: String,
+        provider: &'static dyn CryptoProvider,
+    ) -> ScramSha256 {
         ScramSha256 {
             message: format!("{}n=,r={}", channel_binding.gs2_header(), nonce),
             state: State::Update {
                 nonce,
-                password: normalize(password),
+                password: Zeroizing(normalize(password)),
                 channel_binding,
             },
+            provider,
         }
     }
 
@@ -197,16 +216,16 @@ impl ScramSha256 {
             Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidInput, e)),
         };
 
-        let salted_password = hi(&password, &salt, parsed.iteration_count);
-
-        let mut hmac = Hmac::<Sha256>::new_from_slice(&salted_password)
-            .expect("HMAC is able to accept all key sizes");
-        hmac.update(b"Client Key");
-        let client_key = hmac.finalize().into_bytes();
+        let salted_password = Zeroizing(self.provider.pbkdf2_hmac_sha256(
+            &password,
+            &salt,
+            parsed.iteration_count,
+        ));
 
-        let mut hash = Sha256::default();
-        hash.update(client_key.as_slice());
-        let stored_key = hash.finalize_fixed();
+        let client_key = self
+            .provider
+            .hmac_sha256(&*salted_password, &[b"Client Key"]);
+        let stored_key = self.provider.sha256(&client_key);
 
         let mut cbind_input = vec![];
         cbind_input.extend(channel_binding.gs2_header().as_bytes());
@@ -218,10 +237,9 @@ impl ScramSha256 {
 
         let auth_message = format!("n=,r={},{},{}", client_nonce, message, self.message);
 
-        let mut hmac = Hmac::<Sha256>::new_from_slice(&stored_key)
-            .expect("HMAC is able to accept all key sizes");
-        hmac.update(auth_message.as_bytes());
-        let client_signature = hmac.finalize().into_bytes();
+        let client_signature = self
+            .provider
+            .hmac_sha256(&stored_key, &[auth_message.as_bytes()]);
 
         let mut client_proof = client_key;
         for (proof, signature) in client_proof.iter_mut().zip(client_signature) {
@@ -272,17 +290,309 @@ impl ScramSha256 {
             Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidInput, e)),
         };
 
-        let mut hmac = Hmac::<Sha256>::new_from_slice(&salted_password)
-            .expect("HMAC is able to accept all key sizes");
-        hmac.update(b"Server Key");
-        let server_key = hmac.finalize().into_bytes();
+        let server_key = self
+            .provider
+            .hmac_sha256(&*salted_password, &[b"Server Key"]);
+        let server_signature = self
+            .provider
+            .hmac_sha256(&server_key, &[auth_message.as_bytes()]);
+
+        if constant_time_eq(&server_signature, &verifier) {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "SCRAM verification error",
+            ))
+        }
+    }
+}
+
+// The contents of a SCRAM verifier, which is everything the server needs to know about a password.
+struct ServerKeys {
+    iterations: u32,
+    salt: Vec<u8>,
+    stored_key: [u8; 32],
+    server_key: Zeroizing<[u8; 32]>,
+}
+
+enum ServerState {
+    Update {
+        nonce: String,
+        keys: ServerKeys,
+    },
+    Finish {
+        nonce: String,
+        gs2_header: String,
+        keys: ServerKeys,
+        auth_message: String,
+    },
+    Done,
+}
+
+/// A type which handles the server side of the SCRAM-SHA-256 authentication process.
+///
+/// This is the counterpart of `ScramSha256`, for use by servers and test backends. Channel binding is not supported,
+/// so only the `SCRAM-SHA-256` mechanism should be offered to the client in the `AuthenticationSASL` message.
+///
+/// The data of the client's `SASLInitialResponse` message should be passed to the `update()` method, after which the
+/// buffer returned by the `message()` method should be sent to the client in an `AuthenticationSASLContinue` message.
+///
+/// The client will reply with a `SASLResponse` message. Its contents should be passed to the `finish()` method, after
+/// which, if it succeeded, the buffer returned by the `message()` method should be sent to the client in an
+/// `AuthenticationSASLFinal` message.
+pub struct ScramSha256Server {
+    message: String,
+    state: ServerState,
+    provider: &'static dyn CryptoProvider,
+}
+
+impl ScramSha256Server {
+    /// Constructs a new instance which will authenticate clients against the provided password.
+    pub fn new(password: &[u8]) -> ScramSha256Server {
+        ScramSha256Server::with_provider(password, crypto::default_provider())
+    }
+
+    /// Like `new`, but generates the salt and nonce and computes the proofs with the specified crypto provider.
+    pub fn with_provider(
+        password: &[u8],
+        provider: &'static dyn CryptoProvider,
+    ) -> ScramSha256Server {
+        let mut salt = vec![0; SCRAM_DEFAULT_SALT_LEN];
+        provider.fill_random(&mut salt);
+        ScramSha256Server::new_inner(password, salt, nonce(provider), provider)
+    }
+
+    fn new_inner(
+        password: &[u8],
+        salt: Vec<u8>,
+        nonce: String,
+        provider: &'static dyn CryptoProvider,
+    ) -> ScramSha256Server {
+        let salted_password = Zeroizing(provider.pbkdf2_hmac_sha256(
+            &Zeroizing(normalize(password)),
+            &salt,
+            SCRAM_DEFAULT_ITERATIONS,
+        ));
+        let client_key = Zeroizing(provider.hmac_sha256(&*salted_password, &[b"Client Key"]));
+        let keys = ServerKeys {
+            iterations: SCRAM_DEFAULT_ITERATIONS,
+            salt,
+            stored_key: provider.sha256(&*client_key),
+            server_key: Zeroizing(provider.hmac_sha256(&*salted_password, &[b"Server Key"])),
+        };
+        ScramSha256Server::from_keys(keys, nonce, provider)
+    }
+
+    /// Constructs a new instance which will authenticate clients against a stored SCRAM verifier.
+    ///
+    /// The verifier has the format produced by `password::scram_sha_256` and stored by Postgres in
+    /// `pg_authid.rolpassword`: `SCRAM-SHA-256$<iterations>:<salt>$<StoredKey>:<ServerKey>`. The password itself is not
+    /// needed.
+    pub fn from_verifier(verifier: &str) -> io::Result<ScramSha256Server> {
+        let provider = crypto::default_provider();
+        let keys = parse_verifier(verifier)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid SCRAM verifier"))?;
+        Ok(ScramSha256Server::from_keys(
+            keys,
+            nonce(provider),
+            provider,
+        ))
+    }
+
+    fn from_keys(
+        keys: ServerKeys,
+        nonce: String,
+        provider: &'static dyn CryptoProvider,
+    ) -> ScramSha256Server {
+        ScramSha256Server {
+            message: String::new(),
+            state: ServerState::Update { nonce, keys },
+            provider,
+        }
+    }
+
+    /// Returns the message which should be sent to the client.
+    pub fn message(&self) -> &[u8] {
+        self.message.as_bytes()
+    }
+
+    /// Updates the state machine with the client's first message.
+    ///
+    /// This should be called when a `SASLInitialResponse` message is received.
+    pub fn update(&mut self, message: &[u8]) -> io::Result<()> {
+        let (server_nonce, keys) = match mem::replace(&mut self.state, ServerState::Done) {
+            ServerState::Update { nonce, keys } => (nonce, keys),
+            _ => return Err(io::Error::other("invalid SCRAM state")),
+        };
+
+        let message =
+            str::from_utf8(message).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        let mut parts = message.splitn(3, ',');
+        match parts.next() {
+            Some("n") | Some("y") => {}
+            Some(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "channel binding is not supported",
+                ))
+            }
+            None => unreachable!(),
+        }
+        let client_first_bare = match (parts.next(), parts.next()) {
+            (Some(_), Some(bare)) => bare,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "invalid SCRAM client first message",
+                ))
+            }
+        };
+        let gs2_header = &message[..message.len() - client_first_bare.len()];
+
+        let mut parser = Parser::new(client_first_bare);
+        parser.eat('n')?;
+        parser.eat('=')?;
+        parser.take_while(|c| c != ',')?;
+        parser.eat(',')?;
+        let client_nonce = parser.nonce()?;
+
+        let nonce = format!("{}{}", client_nonce, server_nonce);
+        self.message.clear();
+        write!(
+            &mut self.message,
+            "r={},s={},i={}",
+            nonce,
+            Base64Display::new(&keys.salt, &STANDARD),
+            keys.iterations
+        )
+        .unwrap();
+
+        self.state = ServerState::Finish {
+            nonce,
+            gs2_header: gs2_header.to_string(),
+            keys,
+            auth_message: format!("{},{}", client_first_bare, self.message),
+        };
+        Ok(())
+    }
+
+    /// Finalizes the authentication process with the client's final message.
+    ///
+    /// This should be called when a `SASLResponse` message is received. The client has only authenticated if this
+    /// method returns `Ok(())`.
+    pub fn finish(&mut self, message: &[u8]) -> io::Result<()> {
+        let (nonce, gs2_header, keys, auth_message) =
+            match mem::replace(&mut self.state, ServerState::Done) {
+                ServerState::Finish {
+                    nonce,
+                    gs2_header,
+                    keys,
+                    auth_message,
+                } => (nonce, gs2_header, keys, auth_message),
+                _ => return Err(io::Error::other("invalid SCRAM state")),
+            };
+
+        let message =
+            str::from_utf8(message).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+        let (without_proof, proof) = match message.rfind(",p=") {
+            Some(i) => (&message[..i], &message[i + 3..]),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "missing SCRAM client proof",
+                ))
+            }
+        };
+
+        let mut parser = Parser::new(without_proof);
+        parser.eat('c')?;
+        parser.eat('=')?;
+        let cbind_input = parser.base64()?;
+        parser.eat(',')?;
+        let client_nonce = parser.nonce()?;
+
+        if STANDARD.decode(cbind_input).ok().as_deref() != Some(gs2_header.as_bytes()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid channel binding",
+            ));
+        }
+        if client_nonce != nonce {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid nonce"));
+        }
+
+        let proof = STANDARD
+            .decode(proof)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let auth_message = format!("{},{}", auth_message, without_proof);
+
+        let client_signature = self
+            .provider
+            .hmac_sha256(&keys.stored_key, &[auth_message.as_bytes()]);
+
+        if proof.len() != client_signature.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "SCRAM verification error",
+            ));
+        }
+        let mut proof_key = client_signature;
+        for (key, proof) in proof_key.iter_mut().zip(proof) {
+            *key ^= proof;
+        }
+
+        if !constant_time_eq(&self.provider.sha256(&proof_key), &keys.stored_key) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "SCRAM verification error",
+            ));
+        }
+
+        let server_signature = self
+            .provider
+            .hmac_sha256(&*keys.server_key, &[auth_message.as_bytes()]);
+
+        self.message.clear();
+        write!(
+            &mut self.message,
+            "v={}",
+            Base64Display::new(&server_signature, &STANDARD)
+        )
+        .unwrap();
+        Ok(())
+    }
+}
+
+// Parses a verifier of the form `SCRAM-SHA-256$<iterations>:<salt>$<StoredKey>:<ServerKey>`.
+fn parse_verifier(verifier: &str) -> Option<ServerKeys> {
+    let (params, keys) = verifier.strip_prefix("SCRAM-SHA-256$")?.split_once('$')?;
+    let (iterations, salt) = params.split_once(':')?;
+    let (stored_key, server_key) = keys.split_once(':')?;
+
+    let iterations = iterations.parse().ok().filter(|i| *i > 0)?;
+    let salt = STANDARD.decode(salt).ok()?;
+    let stored_key = decode_key(stored_key)?;
+    let server_key = Zeroizing(decode_key(server_key)?);
+
+    Some(ServerKeys {
+        iterations,
+        salt,
+        stored_key,
+        server_key,
+    })
+}
 
-        let mut hmac = Hmac::<Sha256>::new_from_slice(&server_key)
-            .expect("HMAC is able to accept all key sizes");
-        hmac.update(auth_message.as_bytes());
-        hmac.verify_slice(&verifier)
-            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "SCRAM verification error"))
+fn decode_key(s: &str) -> Option<[u8; 32]> {
+    let decoded = Zeroizing(STANDARD.decode(s).ok()?);
+    if decoded.len() != 32 {
+        return None;
     }
+    let mut key = [0; 32];
+    key.copy_from_slice(&decoded);
+    Some(key)
 }
 
 struct Parser<'a> {
@@ -466,6 +776,7 @@ mod test {
             password.as_bytes(),
             ChannelBinding::unsupported(),
             nonce.to_string(),
+            &crypto::RustCrypto,
         );
         assert_eq!(str::from_utf8(scram.message()).unwrap(), client_first);
 
@@ -474,4 +785,134 @@ mod test {
 
         scram.finish(server_final.as_bytes()).unwrap();
     }
+
+    #[test]
+    fn server_exchange() {
+        let password = "foobar";
+        let salt = STANDARD.decode("fs3IXBy7U7+IvVjZ").unwrap();
+
+        let client_first = "n,,n=,r=9IZ2O01zb9IgiIZ1WJ/zgpJB";
+        let server_first =
+            "r=9IZ2O01zb9IgiIZ1WJ/zgpJBjx/oIRLs02gGSHcw1KEty3eY,s=fs3IXBy7U7+IvVjZ,i\
+             =4096";
+        let client_final =
+            "c=biws,r=9IZ2O01zb9IgiIZ1WJ/zgpJBjx/oIRLs02gGSHcw1KEty3eY,p=AmNKosjJzS3\
+             1NTlQYNs5BTeQjdHdk7lOflDo5re2an8=";
+        let server_final = "v=U+ppxD5XUKtradnv8e2MkeupiA8FU87Sg8CXzXHDAzw=";
+
+        let mut scram = ScramSha256Server::new_inner(
+            password.as_bytes(),
+            salt,
+            "jx/oIRLs02gGSHcw1KEty3eY".to_string(),
+            &crypto::RustCrypto,
+        );
+        scram.update(client_first.as_bytes()).unwrap();
+        assert_eq!(str::from_utf8(scram.message()).unwrap(), server_first);
+
+        scram.finish(client_final.as_bytes()).unwrap();
+        assert_eq!(str::from_utf8(scram.message()).unwrap(), server_final);
+    }
+
+    #[test]
+    fn server_verifier_exchange() {
+        let verifier =
+            "SCRAM-SHA-256$4096:fs3IXBy7U7+IvVjZ$tmgbRn9qfDg3ip++wAxsFIk0Zl9PF0NDB5npDjVeECM=:\
+                        bSU5hgS4vu9S/BceyyM0+b0RxdaRtdH5LRjmCPcHxhg=";
+
+        let client_first = "n,,n=,r=9IZ2O01zb9IgiIZ1WJ/zgpJB";
+        let server_first =
+            "r=9IZ2O01zb9IgiIZ1WJ/zgpJBjx/oIRLs02gGSHcw1KEty3eY,s=fs3IXBy7U7+IvVjZ,i\
+             =4096";
+        let client_final =
+            "c=biws,r=9IZ2O01zb9IgiIZ1WJ/zgpJBjx/oIRLs02gGSHcw1KEty3eY,p=AmNKosjJzS3\
+             1NTlQYNs5BTeQjdHdk7lOflDo5re2an8=";
+        let server_final = "v=U+ppxD5XUKtradnv8e2MkeupiA8FU87Sg8CXzXHDAzw=";
+
+        let mut scram = ScramSha256Server::from_keys(
+            parse_verifier(verifier).unwrap(),
+            "jx/oIRLs02gGSHcw1KEty3eY".to_string(),
+            &crypto::RustCrypto,
+        );
+        scram.update(client_first.as_bytes()).unwrap();
+        assert_eq!(str::from_utf8(scram.message()).unwrap(), server_first);
+
+        scram.finish(client_final.as_bytes()).unwrap();
+        assert_eq!(str::from_utf8(scram.message()).unwrap(), server_final);
+    }
+
+    #[test]
+    fn server_verifier() {
+        let verifier = crate::password::scram_sha_256(b"foobar");
+        let mut server = ScramSha256Server::from_verifier(&verifier).unwrap();
+        let mut client = ScramSha256::new(b"foobar", ChannelBinding::unrequested());
+
+        server.update(client.message()).unwrap();
+        client.update(server.message()).unwrap();
+        server.finish(client.message()).unwrap();
+        client.finish(server.message()).unwrap();
+
+        let mut server = ScramSha256Server::from_verifier(&verifier).unwrap();
+        let mut client = ScramSha256::new(b"barfoo", ChannelBinding::unrequested());
+
+        server.update(client.message()).unwrap();
+        client.update(server.message()).unwrap();
+        server.finish(client.message()).unwrap_err();
+
+        for verifier in [
+            "md5abcdef",
+            "SCRAM-SHA-256$4096:fs3IXBy7U7+IvVjZ",
+            "SCRAM-SHA-256$0:fs3IXBy7U7+IvVjZ$tmgbRn9qfDg3ip++wAxsFIk0Zl9PF0NDB5npDjVeECM=:\
+             bSU5hgS4vu9S/BceyyM0+b0RxdaRtdH5LRjmCPcHxhg=",
+            "SCRAM-SHA-256$4096:fs3IXBy7U7+IvVjZ$dG9vIHNob3J0:bSU5hgS4vu9S/BceyyM0+b0RxdaRtdH5LRjmCPcHxhg=",
+        ] {
+            assert!(ScramSha256Server::from_verifier(verifier).is_err());
+        }
+    }
+
+    #[test]
+    fn server_wrong_password() {
+        let mut server = ScramSha256Server::new(b"foobar");
+        let mut client = ScramSha256::new(b"barfoo", ChannelBinding::unrequested());
+
+        server.update(client.message()).unwrap();
+        client.update(server.message()).unwrap();
+        server.finish(client.message()).unwrap_err();
+    }
+
+    #[test]
+    fn custom_provider() {
+        #[derive(Debug)]
+        struct Fixed;
+
+        impl CryptoProvider for Fixed {
+            fn md5(&self, parts: &[&[u8]]) -> [u8; 16] {
+                crypto::RustCrypto.md5(parts)
+            }
+
+            fn sha256(&self, data: &[u8]) -> [u8; 32] {
+                crypto::RustCrypto.sha256(data)
+            }
+
+            fn hmac_sha256(&self, key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+                crypto::RustCrypto.hmac_sha256(key, parts)
+            }
+
+            fn fill_random(&self, buf: &mut [u8]) {
+                buf.fill(0);
+            }
+        }
+
+        let mut server = ScramSha256Server::with_provider(b"foobar", &Fixed);
+        let mut client =
+            ScramSha256::with_provider(b"foobar", ChannelBinding::unrequested(), &Fixed);
+        assert_eq!(
+            str::from_utf8(client.message()).unwrap(),
+            "y,,n=,r=!!!!!!!!!!!!!!!!!!!!!!!!"
+        );
+
+        server.update(client.message()).unwrap();
+        client.update(server.message()).unwrap();
+        server.finish(client.message()).unwrap();
+        client.finish(server.message()).unwrap();
+    }
 }
@@ -19,6 +19,7 @@ pub mod authentication;
 pub mod escape;
 pub mod message;
 pub mod password;
+pub mod trace;
 pub mod types;
 
 /// A Postgres OID.
@@ -75,3 +76,4 @@ macro_rules! from_usize {
 
 from_usize!(i16);
 from_usize!(i32);
+from_usize!(u16);
@@ -1,15 +1,17 @@
 #![allow(missing_docs)]
 
 use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
-use bytes::{Bytes, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use fallible_iterator::FallibleIterator;
 use memchr::memchr;
 use std::cmp;
 use std::io::{self, Read};
+use std::mem;
 use std::ops::Range;
 use std::str;
 
-use crate::Oid;
+use crate::message::frontend::{write_body, write_counted, write_cstr};
+use crate::{FromUsize, Oid};
 
 pub const PARSE_COMPLETE_TAG: u8 = b'1';
 pub const BIND_COMPLETE_TAG: u8 = b'2';
@@ -22,6 +24,7 @@ pub const DATA_ROW_TAG: u8 = b'D';
 pub const ERROR_RESPONSE_TAG: u8 = b'E';
 pub const COPY_IN_RESPONSE_TAG: u8 = b'G';
 pub const COPY_OUT_RESPONSE_TAG: u8 = b'H';
+pub const COPY_BOTH_RESPONSE_TAG: u8 = b'W';
 pub const EMPTY_QUERY_RESPONSE_TAG: u8 = b'I';
 pub const BACKEND_KEY_DATA_TAG: u8 = b'K';
 pub const NO_DATA_TAG: u8 = b'n';
@@ -93,6 +96,7 @@ pub enum Message {
     CopyDone,
     CopyInResponse(CopyInResponseBody),
     CopyOutResponse(CopyOutResponseBody),
+    CopyBothResponse(CopyBothResponseBody),
     DataRow(DataRowBody),
     EmptyQueryResponse,
     ErrorResponse(ErrorResponseBody),
@@ -133,10 +137,7 @@ impl Message {
             return Ok(None);
         }
 
-        let mut buf = Buffer {
-            bytes: buf.split_to(total_len).freeze(),
-            idx: 5,
-        };
+        let mut buf = Buffer::new(buf.split_to(total_len).freeze(), 5);
 
         let message = match tag {
             PARSE_COMPLETE_TAG => Message::ParseComplete,
@@ -190,6 +191,16 @@ impl Message {
                     storage,
                 })
             }
+            COPY_BOTH_RESPONSE_TAG => {
+                let format = buf.read_u8()?;
+                let len = buf.read_u16::<BigEndian>()?;
+                let storage = buf.read_all();
+                Message::CopyBothResponse(CopyBothResponseBody {
+                    format,
+                    len,
+                    storage,
+                })
+            }
             EMPTY_QUERY_RESPONSE_TAG => Message::EmptyQueryResponse,
             BACKEND_KEY_DATA_TAG => {
                 let process_id = buf.read_i32::<BigEndian>()?;
@@ -278,30 +289,35 @@ impl Message {
     }
 }
 
-struct Buffer {
+// A cursor over the body of a message. Bytes are split off the front of the buffer as they are read, so the final
+// field of a message can take ownership of the remaining bytes without touching the reference count.
+pub(crate) struct Buffer {
     bytes: Bytes,
-    idx: usize,
 }
 
 impl Buffer {
+    #[inline]
+    pub(crate) fn new(mut bytes: Bytes, idx: usize) -> Buffer {
+        bytes.advance(idx);
+        Buffer { bytes }
+    }
+
     #[inline]
     fn slice(&self) -> &[u8] {
-        &self.bytes[self.idx..]
+        &self.bytes
     }
 
     #[inline]
-    fn is_empty(&self) -> bool {
-        self.slice().is_empty()
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
     }
 
     #[inline]
-    fn read_cstr(&mut self) -> io::Result<Bytes> {
+    pub(crate) fn read_cstr(&mut self) -> io::Result<Bytes> {
         match memchr(0, self.slice()) {
             Some(pos) => {
-                let start = self.idx;
-                let end = start + pos;
-                let cstr = self.bytes.slice(start..end);
-                self.idx = end + 1;
+                let cstr = self.bytes.split_to(pos);
+                self.bytes.advance(1);
                 Ok(cstr)
             }
             None => Err(io::Error::new(
@@ -312,23 +328,28 @@ impl Buffer {
     }
 
     #[inline]
-    fn read_all(&mut self) -> Bytes {
-        let buf = self.bytes.slice(self.idx..);
-        self.idx = self.bytes.len();
-        buf
+    pub(crate) fn read_all(&mut self) -> Bytes {
+        mem::take(&mut self.bytes)
+    }
+
+    #[inline]
+    pub(crate) fn read_bytes(&mut self, len: usize) -> io::Result<Bytes> {
+        if self.bytes.len() < len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "unexpected EOF",
+            ));
+        }
+        Ok(self.bytes.split_to(len))
     }
 }
 
 impl Read for Buffer {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        let len = {
-            let slice = self.slice();
-            let len = cmp::min(slice.len(), buf.len());
-            buf[..len].copy_from_slice(&slice[..len]);
-            len
-        };
-        self.idx += len;
+        let len = cmp::min(self.bytes.len(), buf.len());
+        buf[..len].copy_from_slice(&self.bytes[..len]);
+        self.bytes.advance(len);
         Ok(len)
     }
 }
@@ -524,6 +545,27 @@ impl CopyOutResponseBody {
     }
 }
 
+pub struct CopyBothResponseBody {
+    format: u8,
+    len: u16,
+    storage: Bytes,
+}
+
+impl CopyBothResponseBody {
+    #[inline]
+    pub fn format(&self) -> u8 {
+        self.format
+    }
+
+    #[inline]
+    pub fn column_formats(&self) -> ColumnFormats<'_> {
+        ColumnFormats {
+            remaining: self.len,
+            buf: &self.storage,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DataRowBody {
     storage: Bytes,
@@ -540,6 +582,15 @@ impl DataRowBody {
         }
     }
 
+    /// Returns an iterator over the row's values, borrowed from the message buffer, with `None` representing `NULL`.
+    #[inline]
+    pub fn values(&self) -> DataRowValues<'_> {
+        DataRowValues {
+            buf: &self.storage,
+            ranges: self.ranges(),
+        }
+    }
+
     #[inline]
     pub fn buffer(&self) -> &[u8] {
         &self.storage
@@ -599,6 +650,29 @@ impl FallibleIterator for DataRowRanges<'_> {
     }
 }
 
+pub struct DataRowValues<'a> {
+    buf: &'a [u8],
+    ranges: DataRowRanges<'a>,
+}
+
+impl<'a> FallibleIterator for DataRowValues<'a> {
+    type Item = Option<&'a [u8]>;
+    type Error = io::Error;
+
+    #[inline]
+    fn next(&mut self) -> io::Result<Option<Option<&'a [u8]>>> {
+        let buf = self.buf;
+        self.ranges
+            .next()
+            .map(|range| range.map(|range| range.map(|range| &buf[range])))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ranges.size_hint()
+    }
+}
+
 pub struct ErrorResponseBody {
     storage: Bytes,
 }
@@ -844,6 +918,28 @@ pub struct Field<'a> {
 }
 
 impl<'a> Field<'a> {
+    /// Creates a field description, for use with `row_description`.
+    #[inline]
+    pub fn new(
+        name: &'a str,
+        table_oid: Oid,
+        column_id: i16,
+        type_oid: Oid,
+        type_size: i16,
+        type_modifier: i32,
+        format: i16,
+    ) -> Field<'a> {
+        Field {
+            name,
+            table_oid,
+            column_id,
+            type_oid,
+            type_size,
+            type_modifier,
+            format,
+        }
+    }
+
     #[inline]
     pub fn name(&self) -> &'a str {
         self.name
@@ -881,7 +977,301 @@ impl<'a> Field<'a> {
 }
 
 #[inline]
-fn find_null(buf: &[u8], start: usize) -> io::Result<usize> {
+fn write_empty(tag: u8, buf: &mut BytesMut) {
+    buf.put_u8(tag);
+    write_body(buf, |_| Ok::<(), io::Error>(())).unwrap();
+}
+
+#[inline]
+fn write_authentication(code: i32, data: &[u8], buf: &mut BytesMut) -> io::Result<()> {
+    buf.put_u8(AUTHENTICATION_TAG);
+    write_body(buf, |buf| {
+        buf.put_i32(code);
+        buf.put_slice(data);
+        Ok(())
+    })
+}
+
+#[inline]
+pub fn authentication_ok(buf: &mut BytesMut) {
+    write_authentication(0, &[], buf).unwrap();
+}
+
+#[inline]
+pub fn authentication_cleartext_password(buf: &mut BytesMut) {
+    write_authentication(3, &[], buf).unwrap();
+}
+
+#[inline]
+pub fn authentication_md5_password(salt: [u8; 4], buf: &mut BytesMut) {
+    write_authentication(5, &salt, buf).unwrap();
+}
+
+#[inline]
+pub fn authentication_sasl<'a, I>(mechanisms: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    buf.put_u8(AUTHENTICATION_TAG);
+    write_body(buf, |buf| {
+        buf.put_i32(10);
+        for mechanism in mechanisms {
+            write_cstr(mechanism.as_bytes(), buf)?;
+        }
+        buf.put_u8(0);
+        Ok(())
+    })
+}
+
+#[inline]
+pub fn authentication_sasl_continue(data: &[u8], buf: &mut BytesMut) -> io::Result<()> {
+    write_authentication(11, data, buf)
+}
+
+#[inline]
+pub fn authentication_sasl_final(data: &[u8], buf: &mut BytesMut) -> io::Result<()> {
+    write_authentication(12, data, buf)
+}
+
+#[inline]
+pub fn backend_key_data(process_id: i32, secret_key: i32, buf: &mut BytesMut) {
+    buf.put_u8(BACKEND_KEY_DATA_TAG);
+    write_body(buf, |buf| {
+        buf.put_i32(process_id);
+        buf.put_i32(secret_key);
+        Ok::<_, io::Error>(())
+    })
+    .unwrap();
+}
+
+#[inline]
+pub fn bind_complete(buf: &mut BytesMut) {
+    write_empty(BIND_COMPLETE_TAG, buf);
+}
+
+#[inline]
+pub fn close_complete(buf: &mut BytesMut) {
+    write_empty(CLOSE_COMPLETE_TAG, buf);
+}
+
+#[inline]
+pub fn command_complete(tag: &str, buf: &mut BytesMut) -> io::Result<()> {
+    buf.put_u8(COMMAND_COMPLETE_TAG);
+    write_body(buf, |buf| write_cstr(tag.as_bytes(), buf))
+}
+
+#[inline]
+pub fn copy_data(data: &[u8], buf: &mut BytesMut) -> io::Result<()> {
+    buf.put_u8(COPY_DATA_TAG);
+    write_body(buf, |buf| {
+        buf.put_slice(data);
+        Ok(())
+    })
+}
+
+#[inline]
+pub fn copy_done(buf: &mut BytesMut) {
+    write_empty(COPY_DONE_TAG, buf);
+}
+
+#[inline]
+fn write_copy_response<I>(
+    tag: u8,
+    format: u8,
+    column_formats: I,
+    buf: &mut BytesMut,
+) -> io::Result<()>
+where
+    I: IntoIterator<Item = i16>,
+{
+    buf.put_u8(tag);
+    write_body(buf, |buf| {
+        buf.put_u8(format);
+        write_counted(
+            column_formats,
+            |f, buf| {
+                buf.put_i16(f);
+                Ok::<_, io::Error>(())
+            },
+            buf,
+        )
+    })
+}
+
+#[inline]
+pub fn copy_in_response<I>(format: u8, column_formats: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = i16>,
+{
+    write_copy_response(COPY_IN_RESPONSE_TAG, format, column_formats, buf)
+}
+
+#[inline]
+pub fn copy_out_response<I>(format: u8, column_formats: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = i16>,
+{
+    write_copy_response(COPY_OUT_RESPONSE_TAG, format, column_formats, buf)
+}
+
+#[inline]
+pub fn data_row<'a, I>(values: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = Option<&'a [u8]>>,
+{
+    buf.put_u8(DATA_ROW_TAG);
+    write_body(buf, |buf| {
+        write_counted(
+            values,
+            |value, buf| {
+                match value {
+                    Some(value) => {
+                        buf.put_i32(i32::from_usize(value.len())?);
+                        buf.put_slice(value);
+                    }
+                    None => buf.put_i32(-1),
+                }
+                Ok::<_, io::Error>(())
+            },
+            buf,
+        )
+    })
+}
+
+#[inline]
+pub fn empty_query_response(buf: &mut BytesMut) {
+    write_empty(EMPTY_QUERY_RESPONSE_TAG, buf);
+}
+
+#[inline]
+fn write_fields<'a, I>(tag: u8, fields: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = (u8, &'a str)>,
+{
+    buf.put_u8(tag);
+    write_body(buf, |buf| {
+        for (type_, value) in fields {
+            buf.put_u8(type_);
+            write_cstr(value.as_bytes(), buf)?;
+        }
+        buf.put_u8(0);
+        Ok(())
+    })
+}
+
+/// Serializes an `ErrorResponse` message from pairs of field type codes and values, such as `(b'C', "42601")`.
+#[inline]
+pub fn error_response<'a, I>(fields: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = (u8, &'a str)>,
+{
+    write_fields(ERROR_RESPONSE_TAG, fields, buf)
+}
+
+#[inline]
+pub fn no_data(buf: &mut BytesMut) {
+    write_empty(NO_DATA_TAG, buf);
+}
+
+/// Serializes a `NoticeResponse` message from pairs of field type codes and values, such as `(b'C', "01000")`.
+#[inline]
+pub fn notice_response<'a, I>(fields: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = (u8, &'a str)>,
+{
+    write_fields(NOTICE_RESPONSE_TAG, fields, buf)
+}
+
+#[inline]
+pub fn notification_response(
+    process_id: i32,
+    channel: &str,
+    message: &str,
+    buf: &mut BytesMut,
+) -> io::Result<()> {
+    buf.put_u8(NOTIFICATION_RESPONSE_TAG);
+    write_body(buf, |buf| {
+        buf.put_i32(process_id);
+        write_cstr(channel.as_bytes(), buf)?;
+        write_cstr(message.as_bytes(), buf)
+    })
+}
+
+#[inline]
+pub fn parameter_description<I>(types: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = Oid>,
+{
+    buf.put_u8(PARAMETER_DESCRIPTION_TAG);
+    write_body(buf, |buf| {
+        write_counted(
+            types,
+            |ty, buf| {
+                buf.put_u32(ty);
+                Ok::<_, io::Error>(())
+            },
+            buf,
+        )
+    })
+}
+
+#[inline]
+pub fn parameter_status(name: &str, value: &str, buf: &mut BytesMut) -> io::Result<()> {
+    buf.put_u8(PARAMETER_STATUS_TAG);
+    write_body(buf, |buf| {
+        write_cstr(name.as_bytes(), buf)?;
+        write_cstr(value.as_bytes(), buf)
+    })
+}
+
+#[inline]
+pub fn parse_complete(buf: &mut BytesMut) {
+    write_empty(PARSE_COMPLETE_TAG, buf);
+}
+
+#[inline]
+pub fn portal_suspended(buf: &mut BytesMut) {
+    write_empty(PORTAL_SUSPENDED_TAG, buf);
+}
+
+/// Serializes a `ReadyForQuery` message with the provided transaction status: `b'I'` when idle, `b'T'` in a
+/// transaction, or `b'E'` in a failed transaction.
+#[inline]
+pub fn ready_for_query(status: u8, buf: &mut BytesMut) {
+    buf.put_u8(READY_FOR_QUERY_TAG);
+    write_body(buf, |buf| {
+        buf.put_u8(status);
+        Ok::<_, io::Error>(())
+    })
+    .unwrap();
+}
+
+#[inline]
+pub fn row_description<'a, I>(fields: I, buf: &mut BytesMut) -> io::Result<()>
+where
+    I: IntoIterator<Item = Field<'a>>,
+{
+    buf.put_u8(ROW_DESCRIPTION_TAG);
+    write_body(buf, |buf| {
+        write_counted(
+            fields,
+            |field, buf| {
+                write_cstr(field.name.as_bytes(), buf)?;
+                buf.put_u32(field.table_oid);
+                buf.put_i16(field.column_id);
+                buf.put_u32(field.type_oid);
+                buf.put_i16(field.type_size);
+                buf.put_i32(field.type_modifier);
+                buf.put_i16(field.format);
+                Ok::<_, io::Error>(())
+            },
+            buf,
+        )
+    })
+}
+
+#[inline]
+pub(crate) fn find_null(buf: &[u8], start: usize) -> io::Result<usize> {
     match memchr(0, &buf[start..]) {
         Some(pos) => Ok(pos + start),
         None => Err(io::Error::new(
@@ -892,6 +1282,99 @@ fn find_null(buf: &[u8], start: usize) -> io::Result<usize> {
 }
 
 #[inline]
-fn get_str(buf: &[u8]) -> io::Result<&str> {
+pub(crate) fn get_str(buf: &[u8]) -> io::Result<&str> {
     str::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn serialize_round_trip() {
+        let mut buf = BytesMut::new();
+        authentication_md5_password([1, 2, 3, 4], &mut buf);
+        authentication_sasl(["SCRAM-SHA-256"], &mut buf).unwrap();
+        parameter_status("server_version", "16.0", &mut buf).unwrap();
+        backend_key_data(1, 2, &mut buf);
+        row_description([Field::new("id", 0, 0, 23, 4, -1, 1)], &mut buf).unwrap();
+        data_row([Some(&[0, 0, 0, 1][..]), None], &mut buf).unwrap();
+        command_complete("SELECT 1", &mut buf).unwrap();
+        error_response([(b'S', "ERROR"), (b'C', "42601")], &mut buf).unwrap();
+        ready_for_query(b'T', &mut buf);
+
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::AuthenticationMd5Password(body)) => assert_eq!(body.salt(), [1, 2, 3, 4]),
+            _ => panic!("expected AuthenticationMd5Password"),
+        }
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::AuthenticationSasl(body)) => {
+                let mechanisms = body.mechanisms().collect::<Vec<_>>().unwrap();
+                assert_eq!(mechanisms, ["SCRAM-SHA-256"]);
+            }
+            _ => panic!("expected AuthenticationSasl"),
+        }
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::ParameterStatus(body)) => {
+                assert_eq!(body.name().unwrap(), "server_version");
+                assert_eq!(body.value().unwrap(), "16.0");
+            }
+            _ => panic!("expected ParameterStatus"),
+        }
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::BackendKeyData(body)) => {
+                assert_eq!(body.process_id(), 1);
+                assert_eq!(body.secret_key(), 2);
+            }
+            _ => panic!("expected BackendKeyData"),
+        }
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::RowDescription(body)) => {
+                let fields = body.fields().collect::<Vec<_>>().unwrap();
+                assert_eq!(fields.len(), 1);
+                assert_eq!(fields[0].name(), "id");
+                assert_eq!(fields[0].type_oid(), 23);
+                assert_eq!(fields[0].format(), 1);
+            }
+            _ => panic!("expected RowDescription"),
+        }
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::DataRow(body)) => {
+                let ranges = body.ranges().collect::<Vec<_>>().unwrap();
+                assert_eq!(ranges, [Some(4..8), None]);
+                let values = body.values().collect::<Vec<_>>().unwrap();
+                assert_eq!(values, [Some(&[0, 0, 0, 1][..]), None]);
+                assert_eq!(&body.buffer()[4..8], [0, 0, 0, 1]);
+            }
+            _ => panic!("expected DataRow"),
+        }
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::CommandComplete(body)) => assert_eq!(body.tag().unwrap(), "SELECT 1"),
+            _ => panic!("expected CommandComplete"),
+        }
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::ErrorResponse(body)) => {
+                let fields = body
+                    .fields()
+                    .map(|f| {
+                        Ok((
+                            f.type_(),
+                            String::from_utf8(f.value_bytes().to_vec()).unwrap(),
+                        ))
+                    })
+                    .collect::<Vec<_>>()
+                    .unwrap();
+                assert_eq!(
+                    fields,
+                    [(b'S', "ERROR".to_string()), (b'C', "42601".to_string())]
+                );
+            }
+            _ => panic!("expected ErrorResponse"),
+        }
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::ReadyForQuery(body)) => assert_eq!(body.status(), b'T'),
+            _ => panic!("expected ReadyForQuery"),
+        }
+        assert!(buf.is_empty());
+    }
+}
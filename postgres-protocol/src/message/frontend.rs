@@ -1,17 +1,40 @@
 //! Frontend message serialization.
 #![allow(missing_docs)]
 
-use byteorder::{BigEndian, ByteOrder};
-use bytes::{Buf, BufMut, BytesMut};
+use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
+use bytes::buf::Chain;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use fallible_iterator::FallibleIterator;
 use std::convert::TryFrom;
 use std::error::Error;
 use std::io;
 use std::marker;
 
+use crate::message::backend::{find_null, get_str, Buffer};
 use crate::{write_nullable, FromUsize, IsNull, Oid};
 
+pub const BIND_TAG: u8 = b'B';
+pub const CLOSE_TAG: u8 = b'C';
+pub const COPY_DATA_TAG: u8 = b'd';
+pub const COPY_DONE_TAG: u8 = b'c';
+pub const COPY_FAIL_TAG: u8 = b'f';
+pub const DESCRIBE_TAG: u8 = b'D';
+pub const EXECUTE_TAG: u8 = b'E';
+pub const FLUSH_TAG: u8 = b'H';
+pub const FUNCTION_CALL_TAG: u8 = b'F';
+pub const PARSE_TAG: u8 = b'P';
+pub const PASSWORD_MESSAGE_TAG: u8 = b'p';
+pub const QUERY_TAG: u8 = b'Q';
+pub const SYNC_TAG: u8 = b'S';
+pub const TERMINATE_TAG: u8 = b'X';
+
+pub const PROTOCOL_VERSION: i32 = 0x00_03_00_00;
+pub const CANCEL_REQUEST_CODE: i32 = 80_877_102;
+pub const SSL_REQUEST_CODE: i32 = 80_877_103;
+pub const GSS_ENC_REQUEST_CODE: i32 = 80_877_104;
+
 #[inline]
-fn write_body<F, E>(buf: &mut BytesMut, f: F) -> Result<(), E>
+pub(crate) fn write_body<F, E>(buf: &mut BytesMut, f: F) -> Result<(), E>
 where
     F: FnOnce(&mut BytesMut) -> Result<(), E>,
     E: From<io::Error>,
@@ -93,7 +116,11 @@ where
 }
 
 #[inline]
-fn write_counted<I, T, F, E>(items: I, mut serializer: F, buf: &mut BytesMut) -> Result<(), E>
+pub(crate) fn write_counted<I, T, F, E>(
+    items: I,
+    mut serializer: F,
+    buf: &mut BytesMut,
+) -> Result<(), E>
 where
     I: IntoIterator<Item = T>,
     F: FnMut(T, &mut BytesMut) -> Result<(), E>,
@@ -115,7 +142,7 @@ where
 #[inline]
 pub fn cancel_request(process_id: i32, secret_key: i32, buf: &mut BytesMut) {
     write_body(buf, |buf| {
-        buf.put_i32(80_877_102);
+        buf.put_i32(CANCEL_REQUEST_CODE);
         buf.put_i32(process_id);
         buf.put_i32(secret_key);
         Ok::<_, io::Error>(())
@@ -154,10 +181,34 @@ where
     }
 
     pub fn write(self, out: &mut BytesMut) {
-        out.put_u8(b'd');
+        out.put_u8(COPY_DATA_TAG);
         out.put_i32(self.len);
         out.put(self.buf);
     }
+
+    /// Returns the message header chained with the data buffer.
+    ///
+    /// Unlike `write`, this does not copy the data, so it can be handed directly to a vectored writer.
+    pub fn into_buf(self) -> Chain<Bytes, T> {
+        let mut header = BytesMut::with_capacity(5);
+        header.put_u8(COPY_DATA_TAG);
+        header.put_i32(self.len);
+        header.freeze().chain(self.buf)
+    }
+}
+
+/// Writes a `CopyData` message whose contents are produced by `f` directly into `buf`.
+///
+/// The header is written before `f` is called and its length is filled in afterwards, so the data does not need to be
+/// staged in a separate buffer.
+#[inline]
+pub fn copy_data<F, E>(buf: &mut BytesMut, f: F) -> Result<(), E>
+where
+    F: FnOnce(&mut BytesMut) -> Result<(), E>,
+    E: From<io::Error>,
+{
+    buf.put_u8(COPY_DATA_TAG);
+    write_body(buf, f)
 }
 
 #[inline]
@@ -236,6 +287,15 @@ pub fn sasl_initial_response(mechanism: &str, data: &[u8], buf: &mut BytesMut) -
     })
 }
 
+#[inline]
+pub fn gss_response(data: &[u8], buf: &mut BytesMut) -> io::Result<()> {
+    buf.put_u8(b'p');
+    write_body(buf, |buf| {
+        buf.put_slice(data);
+        Ok(())
+    })
+}
+
 #[inline]
 pub fn sasl_response(data: &[u8], buf: &mut BytesMut) -> io::Result<()> {
     buf.put_u8(b'p');
@@ -248,7 +308,16 @@ pub fn sasl_response(data: &[u8], buf: &mut BytesMut) -> io::Result<()> {
 #[inline]
 pub fn ssl_request(buf: &mut BytesMut) {
     write_body(buf, |buf| {
-        buf.put_i32(80_877_103);
+        buf.put_i32(SSL_REQUEST_CODE);
+        Ok::<_, io::Error>(())
+    })
+    .unwrap();
+}
+
+#[inline]
+pub fn gss_enc_request(buf: &mut BytesMut) {
+    write_body(buf, |buf| {
+        buf.put_i32(GSS_ENC_REQUEST_CODE);
         Ok::<_, io::Error>(())
     })
     .unwrap();
@@ -261,7 +330,7 @@ where
 {
     write_body(buf, |buf| {
         // postgres protocol version 3.0(196608) in bigger-endian
-        buf.put_i32(0x00_03_00_00);
+        buf.put_i32(PROTOCOL_VERSION);
         for (key, value) in parameters {
             write_cstr(key.as_bytes(), buf)?;
             write_cstr(value.as_bytes(), buf)?;
@@ -290,7 +359,7 @@ pub fn terminate(buf: &mut BytesMut) {
 }
 
 #[inline]
-fn write_cstr(s: &[u8], buf: &mut BytesMut) -> Result<(), io::Error> {
+pub(crate) fn write_cstr(s: &[u8], buf: &mut BytesMut) -> Result<(), io::Error> {
     if s.contains(&0) {
         return Err(io::Error::new(
             io::ErrorKind::InvalidInput,
@@ -301,3 +370,630 @@ fn write_cstr(s: &[u8], buf: &mut BytesMut) -> Result<(), io::Error> {
     buf.put_u8(0);
     Ok(())
 }
+
+/// An enum representing Postgres frontend messages.
+///
+/// The untagged messages which may be sent at the start of a connection are represented separately by
+/// [`StartupMessage`].
+#[non_exhaustive]
+pub enum Message {
+    Bind(BindBody),
+    Close(CloseBody),
+    CopyData(CopyDataBody),
+    CopyDone,
+    CopyFail(CopyFailBody),
+    Describe(DescribeBody),
+    Execute(ExecuteBody),
+    Flush,
+    FunctionCall(FunctionCallBody),
+    Parse(ParseBody),
+    PasswordMessage(PasswordMessageBody),
+    Query(QueryBody),
+    Sync,
+    Terminate,
+}
+
+impl Message {
+    /// Parses a message from the front of `buf`, returning `None` if it does not yet hold a complete message.
+    ///
+    /// No limit is placed on the length of the message. Callers parsing messages from an untrusted peer should use
+    /// [`Message::parse_with_max_len`] instead, so that a peer cannot make them buffer an arbitrarily large message.
+    #[inline]
+    pub fn parse(buf: &mut BytesMut) -> io::Result<Option<Message>> {
+        Message::parse_with_max_len(buf, usize::MAX)
+    }
+
+    /// Like [`Message::parse`], but returns an error if the length of the message, as given in its header, is greater
+    /// than `max_len`.
+    ///
+    /// The length counts the length field itself but not the message's tag, as in the protocol.
+    pub fn parse_with_max_len(buf: &mut BytesMut, max_len: usize) -> io::Result<Option<Message>> {
+        if buf.len() < 5 {
+            return Ok(None);
+        }
+
+        let tag = buf[0];
+        let len = (&buf[1..5]).read_u32::<BigEndian>().unwrap();
+
+        if len < 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: parsing u32",
+            ));
+        }
+
+        check_len(len, max_len)?;
+
+        let total_len = len as usize + 1;
+        if buf.len() < total_len {
+            return Ok(None);
+        }
+
+        let mut buf = Buffer::new(buf.split_to(total_len).freeze(), 5);
+
+        let message = match tag {
+            BIND_TAG => {
+                let portal = buf.read_cstr()?;
+                let statement = buf.read_cstr()?;
+                let formats = read_formats(&mut buf)?;
+                let values = read_values(&mut buf)?;
+                let result_formats = read_formats(&mut buf)?;
+                Message::Bind(BindBody {
+                    portal,
+                    statement,
+                    formats,
+                    values,
+                    result_formats,
+                })
+            }
+            CLOSE_TAG => {
+                let variant = buf.read_u8()?;
+                let name = buf.read_cstr()?;
+                Message::Close(CloseBody { variant, name })
+            }
+            COPY_DATA_TAG => {
+                let storage = buf.read_all();
+                Message::CopyData(CopyDataBody { storage })
+            }
+            COPY_DONE_TAG => Message::CopyDone,
+            COPY_FAIL_TAG => {
+                let message = buf.read_cstr()?;
+                Message::CopyFail(CopyFailBody { message })
+            }
+            DESCRIBE_TAG => {
+                let variant = buf.read_u8()?;
+                let name = buf.read_cstr()?;
+                Message::Describe(DescribeBody { variant, name })
+            }
+            EXECUTE_TAG => {
+                let portal = buf.read_cstr()?;
+                let max_rows = buf.read_i32::<BigEndian>()?;
+                Message::Execute(ExecuteBody { portal, max_rows })
+            }
+            FLUSH_TAG => Message::Flush,
+            FUNCTION_CALL_TAG => {
+                let function_oid = buf.read_u32::<BigEndian>()?;
+                let formats = read_formats(&mut buf)?;
+                let values = read_values(&mut buf)?;
+                let result_format = buf.read_i16::<BigEndian>()?;
+                Message::FunctionCall(FunctionCallBody {
+                    function_oid,
+                    formats,
+                    values,
+                    result_format,
+                })
+            }
+            PARSE_TAG => {
+                let name = buf.read_cstr()?;
+                let query = buf.read_cstr()?;
+                let len = buf.read_u16::<BigEndian>()?;
+                let mut parameter_types = Vec::with_capacity(len as usize);
+                for _ in 0..len {
+                    parameter_types.push(buf.read_u32::<BigEndian>()?);
+                }
+                Message::Parse(ParseBody {
+                    name,
+                    query,
+                    parameter_types,
+                })
+            }
+            PASSWORD_MESSAGE_TAG => {
+                let storage = buf.read_all();
+                Message::PasswordMessage(PasswordMessageBody { storage })
+            }
+            QUERY_TAG => {
+                let query = buf.read_cstr()?;
+                Message::Query(QueryBody { query })
+            }
+            SYNC_TAG => Message::Sync,
+            TERMINATE_TAG => Message::Terminate,
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown message tag `{}`", tag),
+                ));
+            }
+        };
+
+        if !buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: expected buffer to be empty",
+            ));
+        }
+
+        Ok(Some(message))
+    }
+}
+
+fn read_formats(buf: &mut Buffer) -> io::Result<Vec<i16>> {
+    let len = buf.read_u16::<BigEndian>()?;
+    let mut formats = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        formats.push(buf.read_i16::<BigEndian>()?);
+    }
+    Ok(formats)
+}
+
+fn read_values(buf: &mut Buffer) -> io::Result<Vec<Option<Bytes>>> {
+    let len = buf.read_u16::<BigEndian>()?;
+    let mut values = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        let len = buf.read_i32::<BigEndian>()?;
+        if len < 0 {
+            values.push(None);
+        } else {
+            values.push(Some(buf.read_bytes(len as usize)?));
+        }
+    }
+    Ok(values)
+}
+
+// Rejects a message whose length header exceeds the caller's limit, before any of its body is buffered.
+fn check_len(len: u32, max_len: usize) -> io::Result<()> {
+    if len as usize > max_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message length {} exceeds the maximum of {}", len, max_len),
+        ));
+    }
+    Ok(())
+}
+
+/// An enum representing the untagged messages a client may send at the start of a connection.
+#[non_exhaustive]
+pub enum StartupMessage {
+    CancelRequest(CancelRequestBody),
+    GssEncRequest,
+    SslRequest,
+    Startup(StartupBody),
+}
+
+impl StartupMessage {
+    /// Parses a message from the front of `buf`, returning `None` if it does not yet hold a complete message.
+    ///
+    /// No limit is placed on the length of the message. Callers parsing messages from an untrusted peer should use
+    /// [`StartupMessage::parse_with_max_len`] instead, so that a peer cannot make them buffer an arbitrarily large
+    /// message.
+    #[inline]
+    pub fn parse(buf: &mut BytesMut) -> io::Result<Option<StartupMessage>> {
+        StartupMessage::parse_with_max_len(buf, usize::MAX)
+    }
+
+    /// Like [`StartupMessage::parse`], but returns an error if the length of the message, as given in its header, is
+    /// greater than `max_len`.
+    pub fn parse_with_max_len(
+        buf: &mut BytesMut,
+        max_len: usize,
+    ) -> io::Result<Option<StartupMessage>> {
+        if buf.len() < 4 {
+            return Ok(None);
+        }
+
+        let len = (&buf[..4]).read_u32::<BigEndian>().unwrap();
+
+        if len < 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: startup message length < 8",
+            ));
+        }
+
+        check_len(len, max_len)?;
+
+        let total_len = len as usize;
+        if buf.len() < total_len {
+            return Ok(None);
+        }
+
+        let mut buf = Buffer::new(buf.split_to(total_len).freeze(), 4);
+
+        let message = match buf.read_i32::<BigEndian>()? {
+            CANCEL_REQUEST_CODE => {
+                let process_id = buf.read_i32::<BigEndian>()?;
+                let secret_key = buf.read_i32::<BigEndian>()?;
+                StartupMessage::CancelRequest(CancelRequestBody {
+                    process_id,
+                    secret_key,
+                })
+            }
+            GSS_ENC_REQUEST_CODE => StartupMessage::GssEncRequest,
+            SSL_REQUEST_CODE => StartupMessage::SslRequest,
+            version => {
+                let storage = buf.read_all();
+                if storage.last() != Some(&0) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "invalid message length: startup parameters are not terminated",
+                    ));
+                }
+                StartupMessage::Startup(StartupBody { version, storage })
+            }
+        };
+
+        if !buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: expected buffer to be empty",
+            ));
+        }
+
+        Ok(Some(message))
+    }
+}
+
+pub struct BindBody {
+    portal: Bytes,
+    statement: Bytes,
+    formats: Vec<i16>,
+    values: Vec<Option<Bytes>>,
+    result_formats: Vec<i16>,
+}
+
+impl BindBody {
+    #[inline]
+    pub fn portal(&self) -> io::Result<&str> {
+        get_str(&self.portal)
+    }
+
+    #[inline]
+    pub fn statement(&self) -> io::Result<&str> {
+        get_str(&self.statement)
+    }
+
+    #[inline]
+    pub fn formats(&self) -> &[i16] {
+        &self.formats
+    }
+
+    #[inline]
+    pub fn values(&self) -> &[Option<Bytes>] {
+        &self.values
+    }
+
+    #[inline]
+    pub fn result_formats(&self) -> &[i16] {
+        &self.result_formats
+    }
+}
+
+pub struct CancelRequestBody {
+    process_id: i32,
+    secret_key: i32,
+}
+
+impl CancelRequestBody {
+    #[inline]
+    pub fn process_id(&self) -> i32 {
+        self.process_id
+    }
+
+    #[inline]
+    pub fn secret_key(&self) -> i32 {
+        self.secret_key
+    }
+}
+
+pub struct CloseBody {
+    variant: u8,
+    name: Bytes,
+}
+
+impl CloseBody {
+    #[inline]
+    pub fn variant(&self) -> u8 {
+        self.variant
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+}
+
+pub struct CopyDataBody {
+    storage: Bytes,
+}
+
+impl CopyDataBody {
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.storage
+    }
+
+    #[inline]
+    pub fn into_bytes(self) -> Bytes {
+        self.storage
+    }
+}
+
+pub struct CopyFailBody {
+    message: Bytes,
+}
+
+impl CopyFailBody {
+    #[inline]
+    pub fn message(&self) -> io::Result<&str> {
+        get_str(&self.message)
+    }
+}
+
+pub struct DescribeBody {
+    variant: u8,
+    name: Bytes,
+}
+
+impl DescribeBody {
+    #[inline]
+    pub fn variant(&self) -> u8 {
+        self.variant
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+}
+
+pub struct ExecuteBody {
+    portal: Bytes,
+    max_rows: i32,
+}
+
+impl ExecuteBody {
+    #[inline]
+    pub fn portal(&self) -> io::Result<&str> {
+        get_str(&self.portal)
+    }
+
+    #[inline]
+    pub fn max_rows(&self) -> i32 {
+        self.max_rows
+    }
+}
+
+pub struct FunctionCallBody {
+    function_oid: Oid,
+    formats: Vec<i16>,
+    values: Vec<Option<Bytes>>,
+    result_format: i16,
+}
+
+impl FunctionCallBody {
+    #[inline]
+    pub fn function_oid(&self) -> Oid {
+        self.function_oid
+    }
+
+    #[inline]
+    pub fn formats(&self) -> &[i16] {
+        &self.formats
+    }
+
+    #[inline]
+    pub fn values(&self) -> &[Option<Bytes>] {
+        &self.values
+    }
+
+    #[inline]
+    pub fn result_format(&self) -> i16 {
+        self.result_format
+    }
+}
+
+pub struct ParseBody {
+    name: Bytes,
+    query: Bytes,
+    parameter_types: Vec<Oid>,
+}
+
+impl ParseBody {
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+
+    #[inline]
+    pub fn query(&self) -> io::Result<&str> {
+        get_str(&self.query)
+    }
+
+    #[inline]
+    pub fn parameter_types(&self) -> &[Oid] {
+        &self.parameter_types
+    }
+}
+
+/// The body of a `p` message.
+///
+/// The same tag is used for cleartext and MD5 passwords as well as the SASL and GSSAPI exchanges, so the payload
+/// can only be interpreted with knowledge of the authentication request the server sent.
+pub struct PasswordMessageBody {
+    storage: Bytes,
+}
+
+impl PasswordMessageBody {
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.storage
+    }
+
+    /// Interprets the payload as a cleartext or MD5-hashed password.
+    #[inline]
+    pub fn password(&self) -> io::Result<&[u8]> {
+        let end = find_null(&self.storage, 0)?;
+        if end + 1 != self.storage.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: password is not drained",
+            ));
+        }
+        Ok(&self.storage[..end])
+    }
+
+    /// Interprets the payload as a SASLInitialResponse.
+    #[inline]
+    pub fn sasl_initial_response(&self) -> io::Result<SaslInitialResponse<'_>> {
+        let mut buf = &self.storage[..];
+        let end = find_null(buf, 0)?;
+        let mechanism = get_str(&buf[..end])?;
+        buf = &buf[end + 1..];
+        let len = buf.read_i32::<BigEndian>()?;
+        let data = if len < 0 {
+            None
+        } else if buf.len() == len as usize {
+            Some(buf)
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid message length: SASL data does not match its length",
+            ));
+        };
+        Ok(SaslInitialResponse { mechanism, data })
+    }
+}
+
+pub struct SaslInitialResponse<'a> {
+    mechanism: &'a str,
+    data: Option<&'a [u8]>,
+}
+
+impl<'a> SaslInitialResponse<'a> {
+    #[inline]
+    pub fn mechanism(&self) -> &'a str {
+        self.mechanism
+    }
+
+    #[inline]
+    pub fn data(&self) -> Option<&'a [u8]> {
+        self.data
+    }
+}
+
+pub struct QueryBody {
+    query: Bytes,
+}
+
+impl QueryBody {
+    #[inline]
+    pub fn query(&self) -> io::Result<&str> {
+        get_str(&self.query)
+    }
+}
+
+pub struct StartupBody {
+    version: i32,
+    storage: Bytes,
+}
+
+impl StartupBody {
+    /// Returns the requested protocol version, with the major version in the high 16 bits.
+    #[inline]
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    #[inline]
+    pub fn parameters(&self) -> StartupParameters<'_> {
+        StartupParameters { buf: &self.storage }
+    }
+}
+
+pub struct StartupParameters<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> FallibleIterator for StartupParameters<'a> {
+    type Item = (&'a str, &'a str);
+    type Error = io::Error;
+
+    #[inline]
+    fn next(&mut self) -> io::Result<Option<(&'a str, &'a str)>> {
+        let name_end = find_null(self.buf, 0)?;
+        if name_end == 0 {
+            if self.buf.len() != 1 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "invalid message length: startup parameters are not drained",
+                ));
+            }
+            return Ok(None);
+        }
+
+        let name = get_str(&self.buf[..name_end])?;
+        let value_end = find_null(self.buf, name_end + 1)?;
+        let value = get_str(&self.buf[name_end + 1..value_end])?;
+        self.buf = &self.buf[value_end + 1..];
+
+        Ok(Some((name, value)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn copy_data_paths() {
+        let mut expected = BytesMut::new();
+        CopyData::new(&b"1\thello\n"[..])
+            .unwrap()
+            .write(&mut expected);
+
+        let mut buf = BytesMut::new();
+        copy_data(&mut buf, |buf| {
+            buf.extend_from_slice(b"1\thello\n");
+            Ok::<_, io::Error>(())
+        })
+        .unwrap();
+        assert_eq!(buf, expected);
+
+        let mut chained = CopyData::new(&b"1\thello\n"[..]).unwrap().into_buf();
+        assert_eq!(chained.copy_to_bytes(chained.remaining()), expected);
+
+        match Message::parse(&mut buf).unwrap() {
+            Some(Message::CopyData(body)) => assert_eq!(body.data(), b"1\thello\n"),
+            _ => panic!("expected CopyData"),
+        }
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn max_len() {
+        let mut buf = BytesMut::from(&b"Q\x7f\xff\xff\xff"[..]);
+        let capacity = buf.capacity();
+        let err = Message::parse_with_max_len(&mut buf, 8192).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(buf.capacity(), capacity);
+
+        let mut buf = BytesMut::from(&b"\xff\xff\xff\xff"[..]);
+        let err = StartupMessage::parse_with_max_len(&mut buf, 10000)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // a message within the limit whose body has not arrived yet is not an error
+        let mut buf = BytesMut::from(&b"Q\x00\x00\x00\x10"[..]);
+        assert!(Message::parse_with_max_len(&mut buf, 16).unwrap().is_none());
+    }
+}
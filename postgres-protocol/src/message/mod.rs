@@ -2,7 +2,12 @@
 //!
 //! See [Postgres's documentation][docs] for more information on message flow.
 //!
+//! Parsed messages implement `serde::Serialize` if the `with-serde-1` Cargo feature is enabled.
+//!
 //! [docs]: https://www.postgresql.org/docs/9.5/static/protocol-flow.html
 
 pub mod backend;
 pub mod frontend;
+pub mod replication;
+#[cfg(feature = "with-serde-1")]
+mod serde_1;
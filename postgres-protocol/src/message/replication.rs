@@ -0,0 +1,834 @@
+//! Streaming replication protocol support.
+//!
+//! Once a replication connection has entered `CopyBoth` mode, the messages in this module are exchanged as the
+//! contents of `CopyData` messages. See [Postgres's documentation][docs] for details.
+//!
+//! The `LogicalReplicationMessage` type parses the output of the built-in `pgoutput` plugin, protocol version 1.
+//!
+//! [docs]: https://www.postgresql.org/docs/current/protocol-replication.html
+#![allow(missing_docs)]
+
+use byteorder::{BigEndian, ReadBytesExt};
+use bytes::{BufMut, Bytes, BytesMut};
+use std::io;
+use std::str;
+
+use crate::message::backend::Buffer;
+use crate::{Lsn, Oid};
+
+pub const XLOG_DATA_TAG: u8 = b'w';
+pub const PRIMARY_KEEPALIVE_TAG: u8 = b'k';
+pub const STANDBY_STATUS_UPDATE_TAG: u8 = b'r';
+pub const HOT_STANDBY_FEEDBACK_TAG: u8 = b'h';
+
+pub const BEGIN_TAG: u8 = b'B';
+pub const COMMIT_TAG: u8 = b'C';
+pub const ORIGIN_TAG: u8 = b'O';
+pub const RELATION_TAG: u8 = b'R';
+pub const TYPE_TAG: u8 = b'Y';
+pub const INSERT_TAG: u8 = b'I';
+pub const UPDATE_TAG: u8 = b'U';
+pub const DELETE_TAG: u8 = b'D';
+pub const TRUNCATE_TAG: u8 = b'T';
+pub const MESSAGE_TAG: u8 = b'M';
+
+const TUPLE_NEW_TAG: u8 = b'N';
+const TUPLE_KEY_TAG: u8 = b'K';
+const TUPLE_OLD_TAG: u8 = b'O';
+
+const TUPLE_DATA_NULL_TAG: u8 = b'n';
+const TUPLE_DATA_TOAST_TAG: u8 = b'u';
+const TUPLE_DATA_TEXT_TAG: u8 = b't';
+const TUPLE_DATA_BINARY_TAG: u8 = b'b';
+
+const TRUNCATE_CASCADE: u8 = 1;
+const TRUNCATE_RESTART_IDENTITY: u8 = 2;
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn ensure_empty(buf: &Buffer) -> io::Result<()> {
+    if buf.is_empty() {
+        Ok(())
+    } else {
+        Err(invalid(
+            "invalid message length: expected buffer to be empty".to_string(),
+        ))
+    }
+}
+
+#[inline]
+fn get_str(buf: &[u8]) -> io::Result<&str> {
+    str::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// A message sent by the server in `CopyBoth` mode.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ReplicationMessage<D> {
+    XLogData(XLogDataBody<D>),
+    PrimaryKeepAlive(PrimaryKeepAliveBody),
+}
+
+impl ReplicationMessage<Bytes> {
+    /// Parses the contents of a `CopyData` message.
+    pub fn parse(buf: &Bytes) -> io::Result<ReplicationMessage<Bytes>> {
+        let mut buf = Buffer::new(buf.clone(), 0);
+        let tag = buf.read_u8()?;
+
+        let message = match tag {
+            XLOG_DATA_TAG => {
+                let wal_start = buf.read_u64::<BigEndian>()?;
+                let wal_end = buf.read_u64::<BigEndian>()?;
+                let timestamp = buf.read_i64::<BigEndian>()?;
+                let data = buf.read_all();
+                ReplicationMessage::XLogData(XLogDataBody {
+                    wal_start,
+                    wal_end,
+                    timestamp,
+                    data,
+                })
+            }
+            PRIMARY_KEEPALIVE_TAG => {
+                let wal_end = buf.read_u64::<BigEndian>()?;
+                let timestamp = buf.read_i64::<BigEndian>()?;
+                let reply = buf.read_u8()?;
+                ReplicationMessage::PrimaryKeepAlive(PrimaryKeepAliveBody {
+                    wal_end,
+                    timestamp,
+                    reply,
+                })
+            }
+            tag => {
+                return Err(invalid(format!(
+                    "unknown replication message tag `{}`",
+                    tag
+                )))
+            }
+        };
+
+        ensure_empty(&buf)?;
+        Ok(message)
+    }
+}
+
+#[derive(Debug)]
+pub struct XLogDataBody<D> {
+    wal_start: Lsn,
+    wal_end: Lsn,
+    timestamp: i64,
+    data: D,
+}
+
+impl<D> XLogDataBody<D> {
+    /// The starting point of the WAL data in this message.
+    #[inline]
+    pub fn wal_start(&self) -> Lsn {
+        self.wal_start
+    }
+
+    /// The current end of WAL on the server.
+    #[inline]
+    pub fn wal_end(&self) -> Lsn {
+        self.wal_end
+    }
+
+    /// The server's clock at the time of transmission, in microseconds since midnight on 2000-01-01.
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    #[inline]
+    pub fn data(&self) -> &D {
+        &self.data
+    }
+
+    #[inline]
+    pub fn into_data(self) -> D {
+        self.data
+    }
+
+    /// Replaces the data of the message with the result of `f`, such as a decoded `LogicalReplicationMessage`.
+    pub fn map_data<F, D2, E>(self, f: F) -> Result<XLogDataBody<D2>, E>
+    where
+        F: FnOnce(D) -> Result<D2, E>,
+    {
+        Ok(XLogDataBody {
+            wal_start: self.wal_start,
+            wal_end: self.wal_end,
+            timestamp: self.timestamp,
+            data: f(self.data)?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PrimaryKeepAliveBody {
+    wal_end: Lsn,
+    timestamp: i64,
+    reply: u8,
+}
+
+impl PrimaryKeepAliveBody {
+    /// The current end of WAL on the server.
+    #[inline]
+    pub fn wal_end(&self) -> Lsn {
+        self.wal_end
+    }
+
+    /// The server's clock at the time of transmission, in microseconds since midnight on 2000-01-01.
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    /// 1 if the server requests a reply as soon as possible, 0 otherwise.
+    #[inline]
+    pub fn reply(&self) -> u8 {
+        self.reply
+    }
+}
+
+/// A message produced by the `pgoutput` logical decoding plugin.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum LogicalReplicationMessage {
+    Begin(BeginBody),
+    Commit(CommitBody),
+    Origin(OriginBody),
+    Relation(RelationBody),
+    Type(TypeBody),
+    Insert(InsertBody),
+    Update(UpdateBody),
+    Delete(DeleteBody),
+    Truncate(TruncateBody),
+    Message(MessageBody),
+}
+
+impl LogicalReplicationMessage {
+    /// Parses the data of an `XLogData` message.
+    pub fn parse(buf: &Bytes) -> io::Result<LogicalReplicationMessage> {
+        let mut buf = Buffer::new(buf.clone(), 0);
+        let tag = buf.read_u8()?;
+
+        let message = match tag {
+            BEGIN_TAG => LogicalReplicationMessage::Begin(BeginBody {
+                final_lsn: buf.read_u64::<BigEndian>()?,
+                timestamp: buf.read_i64::<BigEndian>()?,
+                xid: buf.read_u32::<BigEndian>()?,
+            }),
+            COMMIT_TAG => LogicalReplicationMessage::Commit(CommitBody {
+                flags: buf.read_u8()?,
+                commit_lsn: buf.read_u64::<BigEndian>()?,
+                end_lsn: buf.read_u64::<BigEndian>()?,
+                timestamp: buf.read_i64::<BigEndian>()?,
+            }),
+            ORIGIN_TAG => LogicalReplicationMessage::Origin(OriginBody {
+                commit_lsn: buf.read_u64::<BigEndian>()?,
+                name: buf.read_cstr()?,
+            }),
+            RELATION_TAG => {
+                let rel_id = buf.read_u32::<BigEndian>()?;
+                let namespace = buf.read_cstr()?;
+                let name = buf.read_cstr()?;
+                let replica_identity = match buf.read_u8()? {
+                    b'd' => ReplicaIdentity::Default,
+                    b'n' => ReplicaIdentity::Nothing,
+                    b'f' => ReplicaIdentity::Full,
+                    b'i' => ReplicaIdentity::Index,
+                    tag => return Err(invalid(format!("unknown replica identity `{}`", tag))),
+                };
+                let len = buf.read_i16::<BigEndian>()?;
+                let mut columns = Vec::with_capacity(len.max(0) as usize);
+                for _ in 0..len {
+                    columns.push(Column {
+                        flags: buf.read_i8()?,
+                        name: buf.read_cstr()?,
+                        type_id: buf.read_u32::<BigEndian>()?,
+                        type_modifier: buf.read_i32::<BigEndian>()?,
+                    });
+                }
+                LogicalReplicationMessage::Relation(RelationBody {
+                    rel_id,
+                    namespace,
+                    name,
+                    replica_identity,
+                    columns,
+                })
+            }
+            TYPE_TAG => LogicalReplicationMessage::Type(TypeBody {
+                id: buf.read_u32::<BigEndian>()?,
+                namespace: buf.read_cstr()?,
+                name: buf.read_cstr()?,
+            }),
+            INSERT_TAG => {
+                let rel_id = buf.read_u32::<BigEndian>()?;
+                match buf.read_u8()? {
+                    TUPLE_NEW_TAG => {}
+                    tag => return Err(invalid(format!("unexpected tuple tag `{}`", tag))),
+                }
+                let tuple = Tuple::parse(&mut buf)?;
+                LogicalReplicationMessage::Insert(InsertBody { rel_id, tuple })
+            }
+            UPDATE_TAG => {
+                let rel_id = buf.read_u32::<BigEndian>()?;
+                let mut key_tuple = None;
+                let mut old_tuple = None;
+                let new_tuple = loop {
+                    match buf.read_u8()? {
+                        TUPLE_KEY_TAG if key_tuple.is_none() && old_tuple.is_none() => {
+                            key_tuple = Some(Tuple::parse(&mut buf)?)
+                        }
+                        TUPLE_OLD_TAG if key_tuple.is_none() && old_tuple.is_none() => {
+                            old_tuple = Some(Tuple::parse(&mut buf)?)
+                        }
+                        TUPLE_NEW_TAG => break Tuple::parse(&mut buf)?,
+                        tag => return Err(invalid(format!("unexpected tuple tag `{}`", tag))),
+                    }
+                };
+                LogicalReplicationMessage::Update(UpdateBody {
+                    rel_id,
+                    key_tuple,
+                    old_tuple,
+                    new_tuple,
+                })
+            }
+            DELETE_TAG => {
+                let rel_id = buf.read_u32::<BigEndian>()?;
+                let (key_tuple, old_tuple) = match buf.read_u8()? {
+                    TUPLE_KEY_TAG => (Some(Tuple::parse(&mut buf)?), None),
+                    TUPLE_OLD_TAG => (None, Some(Tuple::parse(&mut buf)?)),
+                    tag => return Err(invalid(format!("unexpected tuple tag `{}`", tag))),
+                };
+                LogicalReplicationMessage::Delete(DeleteBody {
+                    rel_id,
+                    key_tuple,
+                    old_tuple,
+                })
+            }
+            TRUNCATE_TAG => {
+                let len = buf.read_u32::<BigEndian>()?;
+                let options = buf.read_u8()?;
+                let mut rel_ids = Vec::with_capacity(len.min(1024) as usize);
+                for _ in 0..len {
+                    rel_ids.push(buf.read_u32::<BigEndian>()?);
+                }
+                LogicalReplicationMessage::Truncate(TruncateBody { options, rel_ids })
+            }
+            MESSAGE_TAG => {
+                let flags = buf.read_u8()?;
+                let lsn = buf.read_u64::<BigEndian>()?;
+                let prefix = buf.read_cstr()?;
+                let len = buf.read_u32::<BigEndian>()?;
+                let content = buf.read_bytes(len as usize)?;
+                LogicalReplicationMessage::Message(MessageBody {
+                    flags,
+                    lsn,
+                    prefix,
+                    content,
+                })
+            }
+            tag => {
+                return Err(invalid(format!(
+                    "unknown logical replication message tag `{}`",
+                    tag
+                )))
+            }
+        };
+
+        ensure_empty(&buf)?;
+        Ok(message)
+    }
+}
+
+#[derive(Debug)]
+pub struct BeginBody {
+    final_lsn: Lsn,
+    timestamp: i64,
+    xid: u32,
+}
+
+impl BeginBody {
+    /// The final LSN of the transaction.
+    #[inline]
+    pub fn final_lsn(&self) -> Lsn {
+        self.final_lsn
+    }
+
+    /// The commit timestamp of the transaction, in microseconds since midnight on 2000-01-01.
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    #[inline]
+    pub fn xid(&self) -> u32 {
+        self.xid
+    }
+}
+
+#[derive(Debug)]
+pub struct CommitBody {
+    flags: u8,
+    commit_lsn: Lsn,
+    end_lsn: Lsn,
+    timestamp: i64,
+}
+
+impl CommitBody {
+    #[inline]
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// The LSN of the commit.
+    #[inline]
+    pub fn commit_lsn(&self) -> Lsn {
+        self.commit_lsn
+    }
+
+    /// The end LSN of the transaction.
+    #[inline]
+    pub fn end_lsn(&self) -> Lsn {
+        self.end_lsn
+    }
+
+    /// The commit timestamp of the transaction, in microseconds since midnight on 2000-01-01.
+    #[inline]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+}
+
+#[derive(Debug)]
+pub struct OriginBody {
+    commit_lsn: Lsn,
+    name: Bytes,
+}
+
+impl OriginBody {
+    /// The LSN of the commit on the origin server.
+    #[inline]
+    pub fn commit_lsn(&self) -> Lsn {
+        self.commit_lsn
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+}
+
+/// The replica identity setting of a relation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReplicaIdentity {
+    /// The primary key, if any.
+    Default,
+    /// Nothing.
+    Nothing,
+    /// All columns.
+    Full,
+    /// The columns of a specific index.
+    Index,
+}
+
+#[derive(Debug)]
+pub struct RelationBody {
+    rel_id: Oid,
+    namespace: Bytes,
+    name: Bytes,
+    replica_identity: ReplicaIdentity,
+    columns: Vec<Column>,
+}
+
+impl RelationBody {
+    #[inline]
+    pub fn rel_id(&self) -> Oid {
+        self.rel_id
+    }
+
+    /// The namespace of the relation, or an empty string for `pg_catalog`.
+    #[inline]
+    pub fn namespace(&self) -> io::Result<&str> {
+        get_str(&self.namespace)
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+
+    #[inline]
+    pub fn replica_identity(&self) -> ReplicaIdentity {
+        self.replica_identity
+    }
+
+    #[inline]
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+}
+
+#[derive(Debug)]
+pub struct Column {
+    flags: i8,
+    name: Bytes,
+    type_id: Oid,
+    type_modifier: i32,
+}
+
+impl Column {
+    /// 1 if the column is part of the key, 0 otherwise.
+    #[inline]
+    pub fn flags(&self) -> i8 {
+        self.flags
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+
+    #[inline]
+    pub fn type_id(&self) -> Oid {
+        self.type_id
+    }
+
+    #[inline]
+    pub fn type_modifier(&self) -> i32 {
+        self.type_modifier
+    }
+}
+
+#[derive(Debug)]
+pub struct TypeBody {
+    id: Oid,
+    namespace: Bytes,
+    name: Bytes,
+}
+
+impl TypeBody {
+    #[inline]
+    pub fn id(&self) -> Oid {
+        self.id
+    }
+
+    /// The namespace of the type, or an empty string for `pg_catalog`.
+    #[inline]
+    pub fn namespace(&self) -> io::Result<&str> {
+        get_str(&self.namespace)
+    }
+
+    #[inline]
+    pub fn name(&self) -> io::Result<&str> {
+        get_str(&self.name)
+    }
+}
+
+#[derive(Debug)]
+pub struct InsertBody {
+    rel_id: Oid,
+    tuple: Tuple,
+}
+
+impl InsertBody {
+    #[inline]
+    pub fn rel_id(&self) -> Oid {
+        self.rel_id
+    }
+
+    #[inline]
+    pub fn tuple(&self) -> &Tuple {
+        &self.tuple
+    }
+}
+
+#[derive(Debug)]
+pub struct UpdateBody {
+    rel_id: Oid,
+    key_tuple: Option<Tuple>,
+    old_tuple: Option<Tuple>,
+    new_tuple: Tuple,
+}
+
+impl UpdateBody {
+    #[inline]
+    pub fn rel_id(&self) -> Oid {
+        self.rel_id
+    }
+
+    /// The old values of the key columns, if the update changed them.
+    #[inline]
+    pub fn key_tuple(&self) -> Option<&Tuple> {
+        self.key_tuple.as_ref()
+    }
+
+    /// The old row, if the relation's replica identity is `Full`.
+    #[inline]
+    pub fn old_tuple(&self) -> Option<&Tuple> {
+        self.old_tuple.as_ref()
+    }
+
+    #[inline]
+    pub fn new_tuple(&self) -> &Tuple {
+        &self.new_tuple
+    }
+}
+
+#[derive(Debug)]
+pub struct DeleteBody {
+    rel_id: Oid,
+    key_tuple: Option<Tuple>,
+    old_tuple: Option<Tuple>,
+}
+
+impl DeleteBody {
+    #[inline]
+    pub fn rel_id(&self) -> Oid {
+        self.rel_id
+    }
+
+    /// The old values of the key columns, if the relation's replica identity is `Default` or `Index`.
+    #[inline]
+    pub fn key_tuple(&self) -> Option<&Tuple> {
+        self.key_tuple.as_ref()
+    }
+
+    /// The old row, if the relation's replica identity is `Full`.
+    #[inline]
+    pub fn old_tuple(&self) -> Option<&Tuple> {
+        self.old_tuple.as_ref()
+    }
+}
+
+#[derive(Debug)]
+pub struct TruncateBody {
+    options: u8,
+    rel_ids: Vec<Oid>,
+}
+
+impl TruncateBody {
+    #[inline]
+    pub fn rel_ids(&self) -> &[Oid] {
+        &self.rel_ids
+    }
+
+    #[inline]
+    pub fn options(&self) -> u8 {
+        self.options
+    }
+
+    /// Determines if the truncate was `CASCADE`.
+    #[inline]
+    pub fn cascade(&self) -> bool {
+        self.options & TRUNCATE_CASCADE != 0
+    }
+
+    /// Determines if the truncate was `RESTART IDENTITY`.
+    #[inline]
+    pub fn restart_identity(&self) -> bool {
+        self.options & TRUNCATE_RESTART_IDENTITY != 0
+    }
+}
+
+#[derive(Debug)]
+pub struct MessageBody {
+    flags: u8,
+    lsn: Lsn,
+    prefix: Bytes,
+    content: Bytes,
+}
+
+impl MessageBody {
+    /// Determines if the message was emitted transactionally.
+    #[inline]
+    pub fn transactional(&self) -> bool {
+        self.flags & 1 != 0
+    }
+
+    #[inline]
+    pub fn lsn(&self) -> Lsn {
+        self.lsn
+    }
+
+    #[inline]
+    pub fn prefix(&self) -> io::Result<&str> {
+        get_str(&self.prefix)
+    }
+
+    #[inline]
+    pub fn content(&self) -> &[u8] {
+        &self.content
+    }
+}
+
+/// The column values of a row.
+#[derive(Debug)]
+pub struct Tuple(Vec<TupleData>);
+
+impl Tuple {
+    fn parse(buf: &mut Buffer) -> io::Result<Tuple> {
+        let len = buf.read_i16::<BigEndian>()?;
+        let mut data = Vec::with_capacity(len.max(0) as usize);
+        for _ in 0..len {
+            let value = match buf.read_u8()? {
+                TUPLE_DATA_NULL_TAG => TupleData::Null,
+                TUPLE_DATA_TOAST_TAG => TupleData::UnchangedToast,
+                TUPLE_DATA_TEXT_TAG => {
+                    let len = buf.read_u32::<BigEndian>()?;
+                    TupleData::Text(buf.read_bytes(len as usize)?)
+                }
+                TUPLE_DATA_BINARY_TAG => {
+                    let len = buf.read_u32::<BigEndian>()?;
+                    TupleData::Binary(buf.read_bytes(len as usize)?)
+                }
+                tag => return Err(invalid(format!("unknown tuple data tag `{}`", tag))),
+            };
+            data.push(value);
+        }
+        Ok(Tuple(data))
+    }
+
+    #[inline]
+    pub fn tuple_data(&self) -> &[TupleData] {
+        &self.0
+    }
+}
+
+/// The value of a column.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TupleData {
+    /// A null value.
+    Null,
+    /// An unchanged TOASTed value, whose actual value is not sent.
+    UnchangedToast,
+    /// A value in the text format.
+    Text(Bytes),
+    /// A value in the binary format.
+    Binary(Bytes),
+}
+
+/// Writes a standby status update message.
+///
+/// The timestamp is the client's clock, in microseconds since midnight on 2000-01-01.
+#[inline]
+pub fn standby_status_update(
+    write_lsn: Lsn,
+    flush_lsn: Lsn,
+    apply_lsn: Lsn,
+    timestamp: i64,
+    reply: u8,
+    buf: &mut BytesMut,
+) {
+    buf.put_u8(STANDBY_STATUS_UPDATE_TAG);
+    buf.put_u64(write_lsn);
+    buf.put_u64(flush_lsn);
+    buf.put_u64(apply_lsn);
+    buf.put_i64(timestamp);
+    buf.put_u8(reply);
+}
+
+/// Writes a hot standby feedback message.
+///
+/// The timestamp is the client's clock, in microseconds since midnight on 2000-01-01.
+#[inline]
+pub fn hot_standby_feedback(
+    timestamp: i64,
+    global_xmin: u32,
+    global_xmin_epoch: u32,
+    catalog_xmin: u32,
+    catalog_xmin_epoch: u32,
+    buf: &mut BytesMut,
+) {
+    buf.put_u8(HOT_STANDBY_FEEDBACK_TAG);
+    buf.put_i64(timestamp);
+    buf.put_u32(global_xmin);
+    buf.put_u32(global_xmin_epoch);
+    buf.put_u32(catalog_xmin);
+    buf.put_u32(catalog_xmin_epoch);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::BufMut;
+
+    #[test]
+    fn xlog_data() {
+        let mut buf = BytesMut::new();
+        buf.put_u8(XLOG_DATA_TAG);
+        buf.put_u64(1);
+        buf.put_u64(2);
+        buf.put_i64(3);
+        buf.put_u8(BEGIN_TAG);
+        buf.put_u64(4);
+        buf.put_i64(5);
+        buf.put_u32(6);
+
+        let body = match ReplicationMessage::parse(&buf.freeze()).unwrap() {
+            ReplicationMessage::XLogData(body) => body,
+            m => panic!("unexpected message {:?}", m),
+        };
+        assert_eq!(body.wal_start(), 1);
+        assert_eq!(body.wal_end(), 2);
+        assert_eq!(body.timestamp(), 3);
+
+        let body = body
+            .map_data(|d| LogicalReplicationMessage::parse(&d))
+            .unwrap();
+        match body.data() {
+            LogicalReplicationMessage::Begin(begin) => {
+                assert_eq!(begin.final_lsn(), 4);
+                assert_eq!(begin.timestamp(), 5);
+                assert_eq!(begin.xid(), 6);
+            }
+            m => panic!("unexpected message {:?}", m),
+        }
+    }
+
+    #[test]
+    fn update() {
+        let mut buf = BytesMut::new();
+        buf.put_u8(UPDATE_TAG);
+        buf.put_u32(16384);
+        buf.put_u8(TUPLE_KEY_TAG);
+        buf.put_i16(1);
+        buf.put_u8(TUPLE_DATA_TEXT_TAG);
+        buf.put_u32(1);
+        buf.put_slice(b"1");
+        buf.put_u8(TUPLE_NEW_TAG);
+        buf.put_i16(2);
+        buf.put_u8(TUPLE_DATA_TEXT_TAG);
+        buf.put_u32(1);
+        buf.put_slice(b"2");
+        buf.put_u8(TUPLE_DATA_NULL_TAG);
+
+        let body = match LogicalReplicationMessage::parse(&buf.freeze()).unwrap() {
+            LogicalReplicationMessage::Update(body) => body,
+            m => panic!("unexpected message {:?}", m),
+        };
+        assert_eq!(body.rel_id(), 16384);
+        assert!(body.old_tuple().is_none());
+        match body.key_tuple().unwrap().tuple_data() {
+            [TupleData::Text(v)] => assert_eq!(&v[..], b"1"),
+            d => panic!("unexpected tuple {:?}", d),
+        }
+        match body.new_tuple().tuple_data() {
+            [TupleData::Text(v), TupleData::Null] => assert_eq!(&v[..], b"2"),
+            d => panic!("unexpected tuple {:?}", d),
+        }
+    }
+
+    #[test]
+    fn standby_status_update_encoding() {
+        let mut buf = BytesMut::new();
+        standby_status_update(1, 2, 3, 4, 1, &mut buf);
+        assert_eq!(buf.len(), 34);
+        assert_eq!(buf[0], STANDBY_STATUS_UPDATE_TAG);
+        assert_eq!(buf[33], 1);
+    }
+}
@@ -0,0 +1,830 @@
+//! `Serialize` implementations for parsed messages.
+//!
+//! Message bodies are lazily parsed views over the raw message buffer, so these are written by hand in terms of the
+//! bodies' accessors. The output has the same shape as a derived implementation would: enums are externally tagged
+//! and bodies are structs of their decoded fields. Binary payloads are serialized as bytes.
+//!
+//! Credentials are redacted so that the output can be logged: the data of password messages (which also carry SASL
+//! responses) and the secret keys of `BackendKeyData` and `CancelRequest` are serialized as `"_"`.
+
+use fallible_iterator::FallibleIterator;
+use serde_1::ser::{Error, Serialize, SerializeMap, SerializeStruct, Serializer};
+use std::io;
+
+use crate::message::backend::{self, ErrorFields};
+use crate::message::frontend;
+
+struct Binary<'a>(&'a [u8]);
+
+impl Serialize for Binary<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+struct Redacted;
+
+impl Serialize for Redacted {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str("_")
+    }
+}
+
+struct Values<'a>(Vec<Option<&'a [u8]>>);
+
+impl Serialize for Values<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.0.iter().map(|v| v.map(Binary)))
+    }
+}
+
+struct Fields<'a, T>(&'a T);
+
+trait HasFields {
+    fn fields(&self) -> ErrorFields<'_>;
+}
+
+impl HasFields for backend::ErrorResponseBody {
+    fn fields(&self) -> ErrorFields<'_> {
+        self.fields()
+    }
+}
+
+impl HasFields for backend::NoticeResponseBody {
+    fn fields(&self) -> ErrorFields<'_> {
+        self.fields()
+    }
+}
+
+impl<T> Serialize for Fields<'_, T>
+where
+    T: HasFields,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let fields = self
+            .0
+            .fields()
+            .collect::<Vec<_>>()
+            .map_err(S::Error::custom)?;
+        serializer.collect_seq(fields.iter().map(|f| ErrorField {
+            type_: f.type_() as char,
+            value: String::from_utf8_lossy(f.value_bytes()).into_owned(),
+        }))
+    }
+}
+
+struct ErrorField {
+    type_: char,
+    value: String,
+}
+
+impl Serialize for ErrorField {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("ErrorField", 2)?;
+        s.serialize_field("type", &self.type_)?;
+        s.serialize_field("value", &self.value)?;
+        s.end()
+    }
+}
+
+fn ser<T, E>(r: io::Result<T>) -> Result<T, E>
+where
+    E: Error,
+{
+    r.map_err(E::custom)
+}
+
+impl Serialize for backend::Message {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use backend::Message::*;
+
+        const NAME: &str = "Message";
+        match self {
+            AuthenticationCleartextPassword => {
+                serializer.serialize_unit_variant(NAME, 0, "AuthenticationCleartextPassword")
+            }
+            AuthenticationGss => serializer.serialize_unit_variant(NAME, 1, "AuthenticationGss"),
+            AuthenticationKerberosV5 => {
+                serializer.serialize_unit_variant(NAME, 2, "AuthenticationKerberosV5")
+            }
+            AuthenticationMd5Password(body) => {
+                serializer.serialize_newtype_variant(NAME, 3, "AuthenticationMd5Password", body)
+            }
+            AuthenticationOk => serializer.serialize_unit_variant(NAME, 4, "AuthenticationOk"),
+            AuthenticationScmCredential => {
+                serializer.serialize_unit_variant(NAME, 5, "AuthenticationScmCredential")
+            }
+            AuthenticationSspi => serializer.serialize_unit_variant(NAME, 6, "AuthenticationSspi"),
+            AuthenticationGssContinue(body) => {
+                serializer.serialize_newtype_variant(NAME, 7, "AuthenticationGssContinue", body)
+            }
+            AuthenticationSasl(body) => {
+                serializer.serialize_newtype_variant(NAME, 8, "AuthenticationSasl", body)
+            }
+            AuthenticationSaslContinue(body) => {
+                serializer.serialize_newtype_variant(NAME, 9, "AuthenticationSaslContinue", body)
+            }
+            AuthenticationSaslFinal(body) => {
+                serializer.serialize_newtype_variant(NAME, 10, "AuthenticationSaslFinal", body)
+            }
+            BackendKeyData(body) => {
+                serializer.serialize_newtype_variant(NAME, 11, "BackendKeyData", body)
+            }
+            BindComplete => serializer.serialize_unit_variant(NAME, 12, "BindComplete"),
+            CloseComplete => serializer.serialize_unit_variant(NAME, 13, "CloseComplete"),
+            CommandComplete(body) => {
+                serializer.serialize_newtype_variant(NAME, 14, "CommandComplete", body)
+            }
+            CopyData(body) => serializer.serialize_newtype_variant(NAME, 15, "CopyData", body),
+            CopyDone => serializer.serialize_unit_variant(NAME, 16, "CopyDone"),
+            CopyInResponse(body) => {
+                serializer.serialize_newtype_variant(NAME, 17, "CopyInResponse", body)
+            }
+            CopyOutResponse(body) => {
+                serializer.serialize_newtype_variant(NAME, 18, "CopyOutResponse", body)
+            }
+            CopyBothResponse(body) => {
+                serializer.serialize_newtype_variant(NAME, 19, "CopyBothResponse", body)
+            }
+            DataRow(body) => serializer.serialize_newtype_variant(NAME, 20, "DataRow", body),
+            EmptyQueryResponse => serializer.serialize_unit_variant(NAME, 21, "EmptyQueryResponse"),
+            ErrorResponse(body) => {
+                serializer.serialize_newtype_variant(NAME, 22, "ErrorResponse", body)
+            }
+            NoData => serializer.serialize_unit_variant(NAME, 23, "NoData"),
+            NoticeResponse(body) => {
+                serializer.serialize_newtype_variant(NAME, 24, "NoticeResponse", body)
+            }
+            NotificationResponse(body) => {
+                serializer.serialize_newtype_variant(NAME, 25, "NotificationResponse", body)
+            }
+            ParameterDescription(body) => {
+                serializer.serialize_newtype_variant(NAME, 26, "ParameterDescription", body)
+            }
+            ParameterStatus(body) => {
+                serializer.serialize_newtype_variant(NAME, 27, "ParameterStatus", body)
+            }
+            ParseComplete => serializer.serialize_unit_variant(NAME, 28, "ParseComplete"),
+            PortalSuspended => serializer.serialize_unit_variant(NAME, 29, "PortalSuspended"),
+            ReadyForQuery(body) => {
+                serializer.serialize_newtype_variant(NAME, 30, "ReadyForQuery", body)
+            }
+            RowDescription(body) => {
+                serializer.serialize_newtype_variant(NAME, 31, "RowDescription", body)
+            }
+        }
+    }
+}
+
+impl Serialize for backend::AuthenticationMd5PasswordBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("AuthenticationMd5PasswordBody", 1)?;
+        s.serialize_field("salt", &Binary(&self.salt()))?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::AuthenticationGssContinueBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("AuthenticationGssContinueBody", 1)?;
+        s.serialize_field("data", &Binary(self.data()))?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::AuthenticationSaslBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mechanisms = ser::<_, S::Error>(self.mechanisms().collect::<Vec<_>>())?;
+        let mut s = serializer.serialize_struct("AuthenticationSaslBody", 1)?;
+        s.serialize_field("mechanisms", &mechanisms)?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::AuthenticationSaslContinueBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("AuthenticationSaslContinueBody", 1)?;
+        s.serialize_field("data", &Binary(self.data()))?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::AuthenticationSaslFinalBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("AuthenticationSaslFinalBody", 1)?;
+        s.serialize_field("data", &Binary(self.data()))?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::BackendKeyDataBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("BackendKeyDataBody", 2)?;
+        s.serialize_field("process_id", &self.process_id())?;
+        s.serialize_field("secret_key", &Redacted)?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::CommandCompleteBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("CommandCompleteBody", 1)?;
+        s.serialize_field("tag", ser::<_, S::Error>(self.tag())?)?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::CopyDataBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("CopyDataBody", 1)?;
+        s.serialize_field("data", &Binary(self.data()))?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::CopyInResponseBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let column_formats = ser::<_, S::Error>(self.column_formats().collect::<Vec<_>>())?;
+        let mut s = serializer.serialize_struct("CopyInResponseBody", 2)?;
+        s.serialize_field("format", &self.format())?;
+        s.serialize_field("column_formats", &column_formats)?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::CopyOutResponseBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let column_formats = ser::<_, S::Error>(self.column_formats().collect::<Vec<_>>())?;
+        let mut s = serializer.serialize_struct("CopyOutResponseBody", 2)?;
+        s.serialize_field("format", &self.format())?;
+        s.serialize_field("column_formats", &column_formats)?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::CopyBothResponseBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let column_formats = ser::<_, S::Error>(self.column_formats().collect::<Vec<_>>())?;
+        let mut s = serializer.serialize_struct("CopyBothResponseBody", 2)?;
+        s.serialize_field("format", &self.format())?;
+        s.serialize_field("column_formats", &column_formats)?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::DataRowBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let buf = self.buffer();
+        let values = ser::<_, S::Error>(
+            self.ranges()
+                .map(|r| Ok(r.map(|r| &buf[r])))
+                .collect::<Vec<_>>(),
+        )?;
+        let mut s = serializer.serialize_struct("DataRowBody", 1)?;
+        s.serialize_field("values", &Values(values))?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::ErrorResponseBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("ErrorResponseBody", 1)?;
+        s.serialize_field("fields", &Fields(self))?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::NoticeResponseBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("NoticeResponseBody", 1)?;
+        s.serialize_field("fields", &Fields(self))?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::NotificationResponseBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("NotificationResponseBody", 3)?;
+        s.serialize_field("process_id", &self.process_id())?;
+        s.serialize_field("channel", ser::<_, S::Error>(self.channel())?)?;
+        s.serialize_field("message", ser::<_, S::Error>(self.message())?)?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::ParameterDescriptionBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let parameters = ser::<_, S::Error>(self.parameters().collect::<Vec<_>>())?;
+        let mut s = serializer.serialize_struct("ParameterDescriptionBody", 1)?;
+        s.serialize_field("parameters", &parameters)?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::ParameterStatusBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("ParameterStatusBody", 2)?;
+        s.serialize_field("name", ser::<_, S::Error>(self.name())?)?;
+        s.serialize_field("value", ser::<_, S::Error>(self.value())?)?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::ReadyForQueryBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("ReadyForQueryBody", 1)?;
+        s.serialize_field("status", &(self.status() as char))?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::RowDescriptionBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let fields = ser::<_, S::Error>(self.fields().collect::<Vec<_>>())?;
+        let mut s = serializer.serialize_struct("RowDescriptionBody", 1)?;
+        s.serialize_field("fields", &fields)?;
+        s.end()
+    }
+}
+
+impl Serialize for backend::Field<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("Field", 7)?;
+        s.serialize_field("name", self.name())?;
+        s.serialize_field("table_oid", &self.table_oid())?;
+        s.serialize_field("column_id", &self.column_id())?;
+        s.serialize_field("type_oid", &self.type_oid())?;
+        s.serialize_field("type_size", &self.type_size())?;
+        s.serialize_field("type_modifier", &self.type_modifier())?;
+        s.serialize_field("format", &self.format())?;
+        s.end()
+    }
+}
+
+impl Serialize for frontend::Message {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use frontend::Message::*;
+
+        const NAME: &str = "Message";
+        match self {
+            Bind(body) => serializer.serialize_newtype_variant(NAME, 0, "Bind", body),
+            Close(body) => serializer.serialize_newtype_variant(NAME, 1, "Close", body),
+            CopyData(body) => serializer.serialize_newtype_variant(NAME, 2, "CopyData", body),
+            CopyDone => serializer.serialize_unit_variant(NAME, 3, "CopyDone"),
+            CopyFail(body) => serializer.serialize_newtype_variant(NAME, 4, "CopyFail", body),
+            Describe(body) => serializer.serialize_newtype_variant(NAME, 5, "Describe", body),
+            Execute(body) => serializer.serialize_newtype_variant(NAME, 6, "Execute", body),
+            Flush => serializer.serialize_unit_variant(NAME, 7, "Flush"),
+            FunctionCall(body) => {
+                serializer.serialize_newtype_variant(NAME, 8, "FunctionCall", body)
+            }
+            Parse(body) => serializer.serialize_newtype_variant(NAME, 9, "Parse", body),
+            PasswordMessage(body) => {
+                serializer.serialize_newtype_variant(NAME, 10, "PasswordMessage", body)
+            }
+            Query(body) => serializer.serialize_newtype_variant(NAME, 11, "Query", body),
+            Sync => serializer.serialize_unit_variant(NAME, 12, "Sync"),
+            Terminate => serializer.serialize_unit_variant(NAME, 13, "Terminate"),
+        }
+    }
+}
+
+impl Serialize for frontend::StartupMessage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use frontend::StartupMessage::*;
+
+        const NAME: &str = "StartupMessage";
+        match self {
+            CancelRequest(body) => {
+                serializer.serialize_newtype_variant(NAME, 0, "CancelRequest", body)
+            }
+            GssEncRequest => serializer.serialize_unit_variant(NAME, 1, "GssEncRequest"),
+            SslRequest => serializer.serialize_unit_variant(NAME, 2, "SslRequest"),
+            Startup(body) => serializer.serialize_newtype_variant(NAME, 3, "Startup", body),
+        }
+    }
+}
+
+impl Serialize for frontend::BindBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let values = Values(self.values().iter().map(|v| v.as_deref()).collect());
+        let mut s = serializer.serialize_struct("BindBody", 5)?;
+        s.serialize_field("portal", ser::<_, S::Error>(self.portal())?)?;
+        s.serialize_field("statement", ser::<_, S::Error>(self.statement())?)?;
+        s.serialize_field("formats", self.formats())?;
+        s.serialize_field("values", &values)?;
+        s.serialize_field("result_formats", self.result_formats())?;
+        s.end()
+    }
+}
+
+impl Serialize for frontend::CancelRequestBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("CancelRequestBody", 2)?;
+        s.serialize_field("process_id", &self.process_id())?;
+        s.serialize_field("secret_key", &Redacted)?;
+        s.end()
+    }
+}
+
+impl Serialize for frontend::CloseBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("CloseBody", 2)?;
+        s.serialize_field("variant", &(self.variant() as char))?;
+        s.serialize_field("name", ser::<_, S::Error>(self.name())?)?;
+        s.end()
+    }
+}
+
+impl Serialize for frontend::CopyDataBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("CopyDataBody", 1)?;
+        s.serialize_field("data", &Binary(self.data()))?;
+        s.end()
+    }
+}
+
+impl Serialize for frontend::CopyFailBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("CopyFailBody", 1)?;
+        s.serialize_field("message", ser::<_, S::Error>(self.message())?)?;
+        s.end()
+    }
+}
+
+impl Serialize for frontend::DescribeBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("DescribeBody", 2)?;
+        s.serialize_field("variant", &(self.variant() as char))?;
+        s.serialize_field("name", ser::<_, S::Error>(self.name())?)?;
+        s.end()
+    }
+}
+
+impl Serialize for frontend::ExecuteBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("ExecuteBody", 2)?;
+        s.serialize_field("portal", ser::<_, S::Error>(self.portal())?)?;
+        s.serialize_field("max_rows", &self.max_rows())?;
+        s.end()
+    }
+}
+
+impl Serialize for frontend::FunctionCallBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let values = Values(self.values().iter().map(|v| v.as_deref()).collect());
+        let mut s = serializer.serialize_struct("FunctionCallBody", 4)?;
+        s.serialize_field("function_oid", &self.function_oid())?;
+        s.serialize_field("formats", self.formats())?;
+        s.serialize_field("values", &values)?;
+        s.serialize_field("result_format", &self.result_format())?;
+        s.end()
+    }
+}
+
+impl Serialize for frontend::ParseBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("ParseBody", 3)?;
+        s.serialize_field("name", ser::<_, S::Error>(self.name())?)?;
+        s.serialize_field("query", ser::<_, S::Error>(self.query())?)?;
+        s.serialize_field("parameter_types", self.parameter_types())?;
+        s.end()
+    }
+}
+
+impl Serialize for frontend::PasswordMessageBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("PasswordMessageBody", 1)?;
+        s.serialize_field("data", &Redacted)?;
+        s.end()
+    }
+}
+
+impl Serialize for frontend::QueryBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("QueryBody", 1)?;
+        s.serialize_field("query", ser::<_, S::Error>(self.query())?)?;
+        s.end()
+    }
+}
+
+impl Serialize for frontend::StartupBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("StartupBody", 2)?;
+        s.serialize_field("version", &self.version())?;
+        s.serialize_field("parameters", &Parameters(self))?;
+        s.end()
+    }
+}
+
+struct Parameters<'a>(&'a frontend::StartupBody);
+
+impl Serialize for Parameters<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut parameters = self.0.parameters();
+        let mut map = serializer.serialize_map(None)?;
+        while let Some((name, value)) = ser::<_, S::Error>(parameters.next())? {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::BytesMut;
+    use serde_json_1::json;
+
+    use crate::message::{backend, frontend};
+    use crate::IsNull;
+
+    #[test]
+    fn backend() {
+        let mut buf =
+            BytesMut::from(&b"D\0\0\0\x0f\0\x02\0\0\0\x01a\xff\xff\xff\xffZ\0\0\0\x05I"[..]);
+
+        let message = backend::Message::parse(&mut buf).unwrap().unwrap();
+        assert_eq!(
+            serde_json_1::to_value(&message).unwrap(),
+            json!({"DataRow": {"values": [[97], null]}}),
+        );
+
+        let message = backend::Message::parse(&mut buf).unwrap().unwrap();
+        assert_eq!(
+            serde_json_1::to_value(&message).unwrap(),
+            json!({"ReadyForQuery": {"status": "I"}}),
+        );
+    }
+
+    #[test]
+    fn frontend() {
+        let mut buf = BytesMut::new();
+        frontend::startup_message(vec![("user", "postgres")], &mut buf).unwrap();
+        let message = frontend::StartupMessage::parse(&mut buf).unwrap().unwrap();
+        assert_eq!(
+            serde_json_1::to_value(&message).unwrap(),
+            json!({"Startup": {"version": 196608, "parameters": {"user": "postgres"}}}),
+        );
+
+        frontend::query("SELECT 1", &mut buf).unwrap();
+        frontend::sync(&mut buf);
+        let message = frontend::Message::parse(&mut buf).unwrap().unwrap();
+        assert_eq!(
+            serde_json_1::to_value(&message).unwrap(),
+            json!({"Query": {"query": "SELECT 1"}}),
+        );
+        let message = frontend::Message::parse(&mut buf).unwrap().unwrap();
+        assert_eq!(serde_json_1::to_value(&message).unwrap(), json!("Sync"));
+    }
+
+    #[test]
+    fn backend_round_trip() {
+        let mut buf = BytesMut::new();
+        backend::authentication_md5_password([1, 2, 3, 4], &mut buf);
+        backend::authentication_sasl(["SCRAM-SHA-256"], &mut buf).unwrap();
+        backend::parameter_status("server_version", "16.0", &mut buf).unwrap();
+        backend::row_description(
+            [backend::Field::new("id", 16384, 1, 23, 4, -1, 1)],
+            &mut buf,
+        )
+        .unwrap();
+        backend::command_complete("SELECT 1", &mut buf).unwrap();
+        backend::error_response([(b'S', "ERROR"), (b'C', "42601")], &mut buf).unwrap();
+        backend::notification_response(1, "channel", "payload", &mut buf).unwrap();
+        backend::copy_data(b"1\n", &mut buf).unwrap();
+
+        let mut messages = vec![];
+        while let Some(message) = backend::Message::parse(&mut buf).unwrap() {
+            messages.push(serde_json_1::to_value(&message).unwrap());
+        }
+        assert_eq!(
+            messages,
+            [
+                json!({"AuthenticationMd5Password": {"salt": [1, 2, 3, 4]}}),
+                json!({"AuthenticationSasl": {"mechanisms": ["SCRAM-SHA-256"]}}),
+                json!({"ParameterStatus": {"name": "server_version", "value": "16.0"}}),
+                json!({"RowDescription": {"fields": [{
+                    "name": "id",
+                    "table_oid": 16384,
+                    "column_id": 1,
+                    "type_oid": 23,
+                    "type_size": 4,
+                    "type_modifier": -1,
+                    "format": 1,
+                }]}}),
+                json!({"CommandComplete": {"tag": "SELECT 1"}}),
+                json!({"ErrorResponse": {"fields": [
+                    {"type": "S", "value": "ERROR"},
+                    {"type": "C", "value": "42601"},
+                ]}}),
+                json!({"NotificationResponse": {
+                    "process_id": 1,
+                    "channel": "channel",
+                    "message": "payload",
+                }}),
+                json!({"CopyData": {"data": [49, 10]}}),
+            ],
+        );
+    }
+
+    #[test]
+    fn frontend_round_trip() {
+        let mut buf = BytesMut::new();
+        frontend::parse("s0", "SELECT $1", [23], &mut buf).unwrap();
+        let bind = frontend::bind(
+            "p0",
+            "s0",
+            [1],
+            [Some(1i32), None],
+            |v, buf| match v {
+                Some(v) => {
+                    buf.extend_from_slice(&v.to_be_bytes());
+                    Ok(IsNull::No)
+                }
+                None => Ok(IsNull::Yes),
+            },
+            [1],
+            &mut buf,
+        );
+        assert!(bind.is_ok());
+        frontend::describe(b'P', "p0", &mut buf).unwrap();
+        frontend::execute("p0", 10, &mut buf).unwrap();
+        frontend::close(b'S', "s0", &mut buf).unwrap();
+        frontend::copy_fail("bad row", &mut buf).unwrap();
+        frontend::terminate(&mut buf);
+
+        let mut messages = vec![];
+        while let Some(message) = frontend::Message::parse(&mut buf).unwrap() {
+            messages.push(serde_json_1::to_value(&message).unwrap());
+        }
+        assert_eq!(
+            messages,
+            [
+                json!({"Parse": {"name": "s0", "query": "SELECT $1", "parameter_types": [23]}}),
+                json!({"Bind": {
+                    "portal": "p0",
+                    "statement": "s0",
+                    "formats": [1],
+                    "values": [[0, 0, 0, 1], null],
+                    "result_formats": [1],
+                }}),
+                json!({"Describe": {"variant": "P", "name": "p0"}}),
+                json!({"Execute": {"portal": "p0", "max_rows": 10}}),
+                json!({"Close": {"variant": "S", "name": "s0"}}),
+                json!({"CopyFail": {"message": "bad row"}}),
+                json!("Terminate"),
+            ],
+        );
+    }
+
+    #[test]
+    fn redaction() {
+        let mut buf = BytesMut::new();
+        frontend::password_message(b"hunter2", &mut buf).unwrap();
+        frontend::sasl_initial_response("SCRAM-SHA-256", b"n,,n=,r=nonce", &mut buf).unwrap();
+        let mut messages = vec![];
+        while let Some(message) = frontend::Message::parse(&mut buf).unwrap() {
+            messages.push(serde_json_1::to_value(&message).unwrap());
+        }
+
+        frontend::cancel_request(1234, 5678, &mut buf);
+        let message = frontend::StartupMessage::parse(&mut buf).unwrap().unwrap();
+        messages.push(serde_json_1::to_value(&message).unwrap());
+
+        backend::backend_key_data(1234, 5678, &mut buf);
+        let message = backend::Message::parse(&mut buf).unwrap().unwrap();
+        messages.push(serde_json_1::to_value(&message).unwrap());
+        assert_eq!(
+            messages,
+            [
+                json!({"PasswordMessage": {"data": "_"}}),
+                json!({"PasswordMessage": {"data": "_"}}),
+                json!({"CancelRequest": {"process_id": 1234, "secret_key": "_"}}),
+                json!({"BackendKeyData": {"process_id": 1234, "secret_key": "_"}}),
+            ],
+        );
+    }
+}
@@ -6,20 +6,16 @@
 //! side. This is good because it ensures the cleartext password won't
 //! end up in logs pg_stat displays, etc.
 
-use crate::authentication::sasl;
+use crate::authentication::crypto::{self, CryptoProvider};
+use crate::authentication::hex;
 use base64::display::Base64Display;
 use base64::engine::general_purpose::STANDARD;
-use hmac::{Hmac, Mac};
-use md5::Md5;
-use rand::RngCore;
-use sha2::digest::FixedOutput;
-use sha2::{Digest, Sha256};
 
 #[cfg(test)]
 mod test;
 
-const SCRAM_DEFAULT_ITERATIONS: u32 = 4096;
-const SCRAM_DEFAULT_SALT_LEN: usize = 16;
+pub(crate) const SCRAM_DEFAULT_ITERATIONS: u32 = 4096;
+pub(crate) const SCRAM_DEFAULT_SALT_LEN: usize = 16;
 
 /// Hash password using SCRAM-SHA-256 with a randomly-generated
 /// salt.
@@ -27,15 +23,24 @@ const SCRAM_DEFAULT_SALT_LEN: usize = 16;
 /// The client may assume the returned string doesn't contain any
 /// special characters that would require escaping in an SQL command.
 pub fn scram_sha_256(password: &[u8]) -> String {
+    scram_sha_256_with_provider(password, crypto::default_provider())
+}
+
+/// Like `scram_sha_256`, but generates the salt and computes the hashes
+/// with the specified crypto provider.
+pub fn scram_sha_256_with_provider(password: &[u8], provider: &dyn CryptoProvider) -> String {
     let mut salt: [u8; SCRAM_DEFAULT_SALT_LEN] = [0; SCRAM_DEFAULT_SALT_LEN];
-    let mut rng = rand::rng();
-    rng.fill_bytes(&mut salt);
-    scram_sha_256_salt(password, salt)
+    provider.fill_random(&mut salt);
+    scram_sha_256_salt(password, salt, provider)
 }
 
 // Internal implementation of scram_sha_256 with a caller-provided
 // salt. This is useful for testing.
-pub(crate) fn scram_sha_256_salt(password: &[u8], salt: [u8; SCRAM_DEFAULT_SALT_LEN]) -> String {
+pub(crate) fn scram_sha_256_salt(
+    password: &[u8],
+    salt: [u8; SCRAM_DEFAULT_SALT_LEN],
+    provider: &dyn CryptoProvider,
+) -> String {
     // Prepare the password, per [RFC
     // 4013](https://tools.ietf.org/html/rfc4013), if possible.
     //
@@ -60,24 +65,16 @@ pub(crate) fn scram_sha_256_salt(password: &[u8], salt: [u8; SCRAM_DEFAULT_SALT_
     };
 
     // salt password
-    let salted_password = sasl::hi(&prepared, &salt, SCRAM_DEFAULT_ITERATIONS);
+    let salted_password = provider.pbkdf2_hmac_sha256(&prepared, &salt, SCRAM_DEFAULT_ITERATIONS);
 
     // client key
-    let mut hmac = Hmac::<Sha256>::new_from_slice(&salted_password)
-        .expect("HMAC is able to accept all key sizes");
-    hmac.update(b"Client Key");
-    let client_key = hmac.finalize().into_bytes();
+    let client_key = provider.hmac_sha256(&salted_password, &[b"Client Key"]);
 
     // stored key
-    let mut hash = Sha256::default();
-    hash.update(client_key.as_slice());
-    let stored_key = hash.finalize_fixed();
+    let stored_key = provider.sha256(&client_key);
 
     // server key
-    let mut hmac = Hmac::<Sha256>::new_from_slice(&salted_password)
-        .expect("HMAC is able to accept all key sizes");
-    hmac.update(b"Server Key");
-    let server_key = hmac.finalize().into_bytes();
+    let server_key = provider.hmac_sha256(&salted_password, &[b"Server Key"]);
 
     format!(
         "SCRAM-SHA-256${}:{}${}:{}",
@@ -95,12 +92,14 @@ pub(crate) fn scram_sha_256_salt(password: &[u8], salt: [u8; SCRAM_DEFAULT_SALT_
 /// The client may assume the returned string doesn't contain any
 /// special characters that would require escaping.
 pub fn md5(password: &[u8], username: &str) -> String {
-    // salt password with username
-    let mut salted_password = Vec::from(password);
-    salted_password.extend_from_slice(username.as_bytes());
+    md5_with_provider(password, username, crypto::default_provider())
+}
 
-    let mut hash = Md5::new();
-    hash.update(&salted_password);
-    let digest = hash.finalize();
-    format!("md5{:x}", digest)
+/// **Not recommended, as MD5 is not considered to be secure.**
+///
+/// Like `md5`, but computes the hash with the specified crypto provider.
+pub fn md5_with_provider(password: &[u8], username: &str, provider: &dyn CryptoProvider) -> String {
+    // salt password with username
+    let digest = provider.md5(&[password, username.as_bytes()]);
+    format!("md5{}", hex(&digest))
 }
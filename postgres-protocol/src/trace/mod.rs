@@ -0,0 +1,497 @@
+//! Human-readable dumps of protocol traffic.
+//!
+//! A [`Tracer`] consumes the raw bytes flowing in one direction of a connection and renders each complete message
+//! as a single line of text. Message lengths are validated as they are parsed, so truncated or malformed traffic is
+//! reported as an error rather than silently misinterpreted. This is primarily intended for debugging interop
+//! issues with other clients and servers.
+//!
+//! # Examples
+//!
+//! ```
+//! use fallible_iterator::FallibleIterator;
+//! use postgres_protocol::trace::{Direction, Tracer};
+//!
+//! let mut tracer = Tracer::new(Direction::Backend);
+//! tracer.push(b"Z\0\0\0\x05I");
+//! assert_eq!(tracer.next().unwrap().unwrap(), "B ReadyForQuery len=5 status=I");
+//! ```
+use bytes::BytesMut;
+use fallible_iterator::FallibleIterator;
+use std::fmt::Write;
+use std::io;
+use std::str;
+
+use crate::message::backend;
+use crate::message::frontend;
+
+#[cfg(test)]
+mod test;
+
+/// The direction of traffic traced by a [`Tracer`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    /// Messages sent by the client to the server.
+    Frontend,
+    /// Messages sent by the server to the client.
+    Backend,
+}
+
+/// A pretty-printer for one direction of a protocol stream.
+///
+/// Bytes are added with [`push`](Tracer::push), and complete messages are rendered by the `FallibleIterator`
+/// implementation. `Ok(None)` is returned when more data is needed to complete the next message, after which more
+/// bytes may be pushed and iteration resumed.
+///
+/// A frontend tracer expects the stream to begin with an untagged startup packet, and remains in that state across
+/// `SSLRequest` and `GSSENCRequest` packets. Encrypted traffic can't be traced, and the single-byte response to
+/// those requests should not be pushed into a backend tracer.
+///
+/// By default, authentication payloads (passwords, SASL and GSSAPI exchanges, and the cancellation secret key) are
+/// redacted from the output.
+pub struct Tracer {
+    direction: Direction,
+    buf: BytesMut,
+    startup: bool,
+    redact_auth: bool,
+}
+
+impl Tracer {
+    /// Creates a new tracer for traffic in the specified direction.
+    pub fn new(direction: Direction) -> Tracer {
+        Tracer {
+            direction,
+            buf: BytesMut::new(),
+            startup: direction == Direction::Frontend,
+            redact_auth: true,
+        }
+    }
+
+    /// Controls the redaction of authentication payloads.
+    ///
+    /// Defaults to `true`.
+    pub fn redact_auth(&mut self, redact_auth: bool) -> &mut Tracer {
+        self.redact_auth = redact_auth;
+        self
+    }
+
+    /// Controls whether the next frontend message is expected to be an untagged startup packet.
+    ///
+    /// Defaults to `true` for frontend tracers. This should be disabled when tracing a connection which has already
+    /// completed startup.
+    pub fn startup(&mut self, startup: bool) -> &mut Tracer {
+        self.startup = startup && self.direction == Direction::Frontend;
+        self
+    }
+
+    /// Adds bytes read from the stream.
+    pub fn push(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Returns the number of buffered bytes which have not yet formed a complete message.
+    pub fn pending(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn next_frontend(&mut self) -> io::Result<Option<String>> {
+        let len = self.message_len(if self.startup { 0 } else { 1 });
+
+        if self.startup {
+            let message = match frontend::StartupMessage::parse(&mut self.buf)? {
+                Some(message) => message,
+                None => return Ok(None),
+            };
+            let mut out = String::new();
+            self.render_startup(&mut out, len, message)?;
+            return Ok(Some(out));
+        }
+
+        let message = match frontend::Message::parse(&mut self.buf)? {
+            Some(message) => message,
+            None => return Ok(None),
+        };
+        let mut out = String::new();
+        self.render_frontend(&mut out, len, message)?;
+        Ok(Some(out))
+    }
+
+    fn next_backend(&mut self) -> io::Result<Option<String>> {
+        let len = self.message_len(1);
+
+        let message = match backend::Message::parse(&mut self.buf)? {
+            Some(message) => message,
+            None => return Ok(None),
+        };
+        let mut out = String::new();
+        self.render_backend(&mut out, len, message)?;
+        Ok(Some(out))
+    }
+
+    fn message_len(&self, offset: usize) -> u32 {
+        match self.buf.get(offset..offset + 4) {
+            Some(len) => u32::from_be_bytes([len[0], len[1], len[2], len[3]]),
+            None => 0,
+        }
+    }
+
+    fn render_startup(
+        &mut self,
+        out: &mut String,
+        len: u32,
+        message: frontend::StartupMessage,
+    ) -> io::Result<()> {
+        match message {
+            frontend::StartupMessage::CancelRequest(body) => {
+                self.startup = false;
+                header(out, "F", "CancelRequest", len);
+                let _ = write!(out, " process_id={}", body.process_id());
+                self.write_secret(out, " secret_key=", &body.secret_key().to_be_bytes());
+            }
+            frontend::StartupMessage::GssEncRequest => header(out, "F", "GSSENCRequest", len),
+            frontend::StartupMessage::SslRequest => header(out, "F", "SSLRequest", len),
+            frontend::StartupMessage::Startup(body) => {
+                self.startup = false;
+                header(out, "F", "StartupMessage", len);
+                let _ = write!(
+                    out,
+                    " version={}.{}",
+                    body.version() >> 16,
+                    body.version() & 0xffff
+                );
+                let mut parameters = body.parameters();
+                while let Some((name, value)) = parameters.next()? {
+                    let _ = write!(out, " {}={:?}", name, value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn render_frontend(
+        &self,
+        out: &mut String,
+        len: u32,
+        message: frontend::Message,
+    ) -> io::Result<()> {
+        match message {
+            frontend::Message::Bind(body) => {
+                header(out, "F", "Bind", len);
+                let _ = write!(
+                    out,
+                    " portal={:?} statement={:?} formats={:?} values=",
+                    body.portal()?,
+                    body.statement()?,
+                    body.formats()
+                );
+                write_values(out, body.values().iter().map(|v| v.as_deref()));
+                let _ = write!(out, " result_formats={:?}", body.result_formats());
+            }
+            frontend::Message::Close(body) => {
+                header(out, "F", "Close", len);
+                let _ = write!(
+                    out,
+                    " variant={} name={:?}",
+                    body.variant() as char,
+                    body.name()?
+                );
+            }
+            frontend::Message::CopyData(body) => {
+                header(out, "F", "CopyData", len);
+                out.push(' ');
+                write_bytes(out, body.data());
+            }
+            frontend::Message::CopyDone => header(out, "F", "CopyDone", len),
+            frontend::Message::CopyFail(body) => {
+                header(out, "F", "CopyFail", len);
+                let _ = write!(out, " message={:?}", body.message()?);
+            }
+            frontend::Message::Describe(body) => {
+                header(out, "F", "Describe", len);
+                let _ = write!(
+                    out,
+                    " variant={} name={:?}",
+                    body.variant() as char,
+                    body.name()?
+                );
+            }
+            frontend::Message::Execute(body) => {
+                header(out, "F", "Execute", len);
+                let _ = write!(
+                    out,
+                    " portal={:?} max_rows={}",
+                    body.portal()?,
+                    body.max_rows()
+                );
+            }
+            frontend::Message::Flush => header(out, "F", "Flush", len),
+            frontend::Message::FunctionCall(body) => {
+                header(out, "F", "FunctionCall", len);
+                let _ = write!(
+                    out,
+                    " function_oid={} formats={:?} values=",
+                    body.function_oid(),
+                    body.formats()
+                );
+                write_values(out, body.values().iter().map(|v| v.as_deref()));
+                let _ = write!(out, " result_format={}", body.result_format());
+            }
+            frontend::Message::Parse(body) => {
+                header(out, "F", "Parse", len);
+                let _ = write!(
+                    out,
+                    " name={:?} query={:?} parameter_types={:?}",
+                    body.name()?,
+                    body.query()?,
+                    body.parameter_types()
+                );
+            }
+            frontend::Message::PasswordMessage(body) => {
+                // The payload's meaning depends on the server's authentication request, which isn't visible to
+                // this side of the stream, so only the SASL mechanism name (if any) is decoded.
+                header(out, "F", "PasswordMessage", len);
+                if let Ok(response) = body.sasl_initial_response() {
+                    if response.mechanism().starts_with("SCRAM-") {
+                        let _ = write!(out, " mechanism={}", response.mechanism());
+                    }
+                }
+                self.write_secret(out, " data=", body.data());
+            }
+            frontend::Message::Query(body) => {
+                header(out, "F", "Query", len);
+                let _ = write!(out, " query={:?}", body.query()?);
+            }
+            frontend::Message::Sync => header(out, "F", "Sync", len),
+            frontend::Message::Terminate => header(out, "F", "Terminate", len),
+        }
+
+        Ok(())
+    }
+
+    fn render_backend(
+        &self,
+        out: &mut String,
+        len: u32,
+        message: backend::Message,
+    ) -> io::Result<()> {
+        match message {
+            backend::Message::AuthenticationCleartextPassword => {
+                header(out, "B", "AuthenticationCleartextPassword", len)
+            }
+            backend::Message::AuthenticationGss => header(out, "B", "AuthenticationGSS", len),
+            backend::Message::AuthenticationKerberosV5 => {
+                header(out, "B", "AuthenticationKerberosV5", len)
+            }
+            backend::Message::AuthenticationMd5Password(body) => {
+                header(out, "B", "AuthenticationMD5Password", len);
+                let _ = write!(out, " salt={:02x?}", body.salt());
+            }
+            backend::Message::AuthenticationOk => header(out, "B", "AuthenticationOk", len),
+            backend::Message::AuthenticationScmCredential => {
+                header(out, "B", "AuthenticationSCMCredential", len)
+            }
+            backend::Message::AuthenticationSspi => header(out, "B", "AuthenticationSSPI", len),
+            backend::Message::AuthenticationGssContinue(body) => {
+                header(out, "B", "AuthenticationGSSContinue", len);
+                self.write_secret(out, " data=", body.data());
+            }
+            backend::Message::AuthenticationSasl(body) => {
+                header(out, "B", "AuthenticationSASL", len);
+                let mechanisms = body.mechanisms().collect::<Vec<_>>()?;
+                let _ = write!(out, " mechanisms={:?}", mechanisms);
+            }
+            backend::Message::AuthenticationSaslContinue(body) => {
+                header(out, "B", "AuthenticationSASLContinue", len);
+                self.write_secret(out, " data=", body.data());
+            }
+            backend::Message::AuthenticationSaslFinal(body) => {
+                header(out, "B", "AuthenticationSASLFinal", len);
+                self.write_secret(out, " data=", body.data());
+            }
+            backend::Message::BackendKeyData(body) => {
+                header(out, "B", "BackendKeyData", len);
+                let _ = write!(out, " process_id={}", body.process_id());
+                self.write_secret(out, " secret_key=", &body.secret_key().to_be_bytes());
+            }
+            backend::Message::BindComplete => header(out, "B", "BindComplete", len),
+            backend::Message::CloseComplete => header(out, "B", "CloseComplete", len),
+            backend::Message::CommandComplete(body) => {
+                header(out, "B", "CommandComplete", len);
+                let _ = write!(out, " tag={:?}", body.tag()?);
+            }
+            backend::Message::CopyData(body) => {
+                header(out, "B", "CopyData", len);
+                out.push(' ');
+                write_bytes(out, body.data());
+            }
+            backend::Message::CopyDone => header(out, "B", "CopyDone", len),
+            backend::Message::CopyInResponse(body) => {
+                header(out, "B", "CopyInResponse", len);
+                let formats = body.column_formats().collect::<Vec<_>>()?;
+                let _ = write!(
+                    out,
+                    " format={} column_formats={:?}",
+                    body.format(),
+                    formats
+                );
+            }
+            backend::Message::CopyOutResponse(body) => {
+                header(out, "B", "CopyOutResponse", len);
+                let formats = body.column_formats().collect::<Vec<_>>()?;
+                let _ = write!(
+                    out,
+                    " format={} column_formats={:?}",
+                    body.format(),
+                    formats
+                );
+            }
+            backend::Message::CopyBothResponse(body) => {
+                header(out, "B", "CopyBothResponse", len);
+                let formats = body.column_formats().collect::<Vec<_>>()?;
+                let _ = write!(
+                    out,
+                    " format={} column_formats={:?}",
+                    body.format(),
+                    formats
+                );
+            }
+            backend::Message::DataRow(body) => {
+                header(out, "B", "DataRow", len);
+                let buf = body.buffer();
+                let values = body
+                    .ranges()
+                    .map(|r| Ok(r.map(|r| &buf[r])))
+                    .collect::<Vec<_>>()?;
+                out.push(' ');
+                write_values(out, values.into_iter());
+            }
+            backend::Message::EmptyQueryResponse => header(out, "B", "EmptyQueryResponse", len),
+            backend::Message::ErrorResponse(body) => {
+                header(out, "B", "ErrorResponse", len);
+                write_fields(out, body.fields())?;
+            }
+            backend::Message::NoData => header(out, "B", "NoData", len),
+            backend::Message::NoticeResponse(body) => {
+                header(out, "B", "NoticeResponse", len);
+                write_fields(out, body.fields())?;
+            }
+            backend::Message::NotificationResponse(body) => {
+                header(out, "B", "NotificationResponse", len);
+                let _ = write!(
+                    out,
+                    " process_id={} channel={:?} payload={:?}",
+                    body.process_id(),
+                    body.channel()?,
+                    body.message()?
+                );
+            }
+            backend::Message::ParameterDescription(body) => {
+                header(out, "B", "ParameterDescription", len);
+                let parameters = body.parameters().collect::<Vec<_>>()?;
+                let _ = write!(out, " types={:?}", parameters);
+            }
+            backend::Message::ParameterStatus(body) => {
+                header(out, "B", "ParameterStatus", len);
+                let _ = write!(out, " {}={:?}", body.name()?, body.value()?);
+            }
+            backend::Message::ParseComplete => header(out, "B", "ParseComplete", len),
+            backend::Message::PortalSuspended => header(out, "B", "PortalSuspended", len),
+            backend::Message::ReadyForQuery(body) => {
+                header(out, "B", "ReadyForQuery", len);
+                let _ = write!(out, " status={}", body.status() as char);
+            }
+            backend::Message::RowDescription(body) => {
+                header(out, "B", "RowDescription", len);
+                out.push_str(" [");
+                let mut fields = body.fields();
+                let mut first = true;
+                while let Some(field) = fields.next()? {
+                    if !first {
+                        out.push_str(", ");
+                    }
+                    first = false;
+                    let _ = write!(
+                        out,
+                        "{:?} table={} column={} type={} size={} modifier={} format={}",
+                        field.name(),
+                        field.table_oid(),
+                        field.column_id(),
+                        field.type_oid(),
+                        field.type_size(),
+                        field.type_modifier(),
+                        field.format()
+                    );
+                }
+                out.push(']');
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_secret(&self, out: &mut String, prefix: &str, data: &[u8]) {
+        out.push_str(prefix);
+        if self.redact_auth {
+            let _ = write!(out, "<redacted {} bytes>", data.len());
+        } else {
+            write_bytes(out, data);
+        }
+    }
+}
+
+impl FallibleIterator for Tracer {
+    type Item = String;
+    type Error = io::Error;
+
+    fn next(&mut self) -> io::Result<Option<String>> {
+        match self.direction {
+            Direction::Frontend => self.next_frontend(),
+            Direction::Backend => self.next_backend(),
+        }
+    }
+}
+
+fn header(out: &mut String, direction: &str, name: &str, len: u32) {
+    let _ = write!(out, "{} {} len={}", direction, name, len);
+}
+
+fn write_fields(out: &mut String, mut fields: backend::ErrorFields<'_>) -> io::Result<()> {
+    while let Some(field) = fields.next()? {
+        let _ = write!(out, " {}=", field.type_() as char);
+        write_bytes(out, field.value_bytes());
+    }
+    Ok(())
+}
+
+fn write_values<'a, I>(out: &mut String, values: I)
+where
+    I: Iterator<Item = Option<&'a [u8]>>,
+{
+    out.push('[');
+    for (i, value) in values.enumerate() {
+        if i != 0 {
+            out.push_str(", ");
+        }
+        match value {
+            Some(value) => write_bytes(out, value),
+            None => out.push_str("NULL"),
+        }
+    }
+    out.push(']');
+}
+
+// Text is written as a quoted, escaped string, and anything else as hex.
+fn write_bytes(out: &mut String, data: &[u8]) {
+    match str::from_utf8(data) {
+        Ok(s) if !s.chars().any(|c| c.is_control() && c != '\n' && c != '\t') => {
+            let _ = write!(out, "{:?}", s);
+        }
+        _ => {
+            out.push_str("x'");
+            for b in data {
+                let _ = write!(out, "{:02x}", b);
+            }
+            out.push('\'');
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use bytes::BytesMut;
+use fallible_iterator::FallibleIterator;
+
+use super::*;
+use crate::message::frontend;
+use crate::IsNull;
+
+fn trace(direction: Direction, data: &[u8]) -> Vec<String> {
+    let mut tracer = Tracer::new(direction);
+    tracer.push(data);
+    let lines = tracer.by_ref().collect::<Vec<_>>().unwrap();
+    assert_eq!(tracer.pending(), 0);
+    lines
+}
+
+#[test]
+fn startup() {
+    let mut buf = BytesMut::new();
+    frontend::ssl_request(&mut buf);
+    frontend::startup_message(vec![("user", "postgres"), ("database", "db")], &mut buf).unwrap();
+    frontend::query("SELECT 1", &mut buf).unwrap();
+
+    assert_eq!(
+        trace(Direction::Frontend, &buf),
+        [
+            "F SSLRequest len=8",
+            "F StartupMessage len=35 version=3.0 user=\"postgres\" database=\"db\"",
+            "F Query len=13 query=\"SELECT 1\"",
+        ]
+    );
+}
+
+#[test]
+fn extended_query() {
+    let mut buf = BytesMut::new();
+    frontend::parse("s1", "SELECT $1", Some(25), &mut buf).unwrap();
+    frontend::bind(
+        "",
+        "s1",
+        Some(1),
+        vec![Some(&b"hi"[..]), None],
+        |v, buf| match v {
+            Some(v) => {
+                buf.extend_from_slice(v);
+                Ok(IsNull::No)
+            }
+            None => Ok(IsNull::Yes),
+        },
+        Some(1),
+        &mut buf,
+    )
+    .map_err(|_| ())
+    .unwrap();
+    frontend::execute("", 0, &mut buf).unwrap();
+    frontend::sync(&mut buf);
+
+    let mut tracer = Tracer::new(Direction::Frontend);
+    tracer.startup(false);
+    tracer.push(&buf);
+    assert_eq!(
+        tracer.collect::<Vec<_>>().unwrap(),
+        [
+            "F Parse len=23 name=\"s1\" query=\"SELECT $1\" parameter_types=[25]",
+            "F Bind len=28 portal=\"\" statement=\"s1\" formats=[1] values=[\"hi\", NULL] result_formats=[1]",
+            "F Execute len=9 portal=\"\" max_rows=0",
+            "F Sync len=4",
+        ]
+    );
+}
+
+#[test]
+fn backend() {
+    let data = b"R\0\0\0\x08\0\0\0\0\
+        S\0\0\0\x15TimeZone\0Etc/UTC\0\
+        K\0\0\0\x0c\0\0\0\x2a\x01\x02\x03\x04\
+        D\0\0\0\x0f\0\x02\0\0\0\x01a\xff\xff\xff\xff\
+        Z\0\0\0\x05I";
+
+    assert_eq!(
+        trace(Direction::Backend, data),
+        [
+            "B AuthenticationOk len=8",
+            "B ParameterStatus len=21 TimeZone=\"Etc/UTC\"",
+            "B BackendKeyData len=12 process_id=42 secret_key=<redacted 4 bytes>",
+            "B DataRow len=15 [\"a\", NULL]",
+            "B ReadyForQuery len=5 status=I",
+        ]
+    );
+}
+
+#[test]
+fn redaction() {
+    let mut buf = BytesMut::new();
+    frontend::password_message(b"hunter2", &mut buf).unwrap();
+
+    let mut tracer = Tracer::new(Direction::Frontend);
+    tracer.startup(false);
+    tracer.push(&buf);
+    assert_eq!(
+        tracer.next().unwrap().unwrap(),
+        "F PasswordMessage len=12 data=<redacted 8 bytes>"
+    );
+
+    let mut tracer = Tracer::new(Direction::Frontend);
+    tracer.startup(false);
+    tracer.redact_auth(false);
+    tracer.push(&buf);
+    assert_eq!(
+        tracer.next().unwrap().unwrap(),
+        "F PasswordMessage len=12 data=x'68756e7465723200'"
+    );
+}
+
+#[test]
+fn partial() {
+    let mut buf = BytesMut::new();
+    frontend::sync(&mut buf);
+
+    let mut tracer = Tracer::new(Direction::Frontend);
+    tracer.startup(false);
+    tracer.push(&buf[..3]);
+    assert_eq!(tracer.next().unwrap(), None);
+    tracer.push(&buf[3..]);
+    assert_eq!(tracer.next().unwrap().unwrap(), "F Sync len=4");
+    assert_eq!(tracer.next().unwrap(), None);
+}
+
+#[test]
+fn bad_length() {
+    let mut tracer = Tracer::new(Direction::Backend);
+    tracer.push(b"Z\0\0\0\x06I\0");
+    tracer.next().unwrap_err();
+}
@@ -2,6 +2,7 @@
 use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
 use bytes::{BufMut, BytesMut};
 use fallible_iterator::FallibleIterator;
+use memchr::memchr;
 use std::boxed::Box as StdBox;
 use std::error::Error;
 use std::io::Read;
@@ -421,6 +422,58 @@ pub fn time_from_sql(mut buf: &[u8]) -> Result<i64, StdBox<dyn Error + Sync + Se
     Ok(v)
 }
 
+/// Serializes an `INTERVAL` value.
+#[inline]
+pub fn interval_to_sql(microseconds: i64, days: i32, months: i32, buf: &mut BytesMut) {
+    buf.put_i64(microseconds);
+    buf.put_i32(days);
+    buf.put_i32(months);
+}
+
+/// Deserializes an `INTERVAL` value.
+#[inline]
+pub fn interval_from_sql(mut buf: &[u8]) -> Result<Interval, StdBox<dyn Error + Sync + Send>> {
+    let microseconds = buf.read_i64::<BigEndian>()?;
+    let days = buf.read_i32::<BigEndian>()?;
+    let months = buf.read_i32::<BigEndian>()?;
+    if !buf.is_empty() {
+        return Err("invalid message length: interval not drained".into());
+    }
+    Ok(Interval {
+        microseconds,
+        days,
+        months,
+    })
+}
+
+/// A Postgres interval.
+#[derive(Copy, Clone)]
+pub struct Interval {
+    microseconds: i64,
+    days: i32,
+    months: i32,
+}
+
+impl Interval {
+    /// Returns the time component of the interval, in microseconds.
+    #[inline]
+    pub fn microseconds(&self) -> i64 {
+        self.microseconds
+    }
+
+    /// Returns the days component of the interval.
+    #[inline]
+    pub fn days(&self) -> i32 {
+        self.days
+    }
+
+    /// Returns the months component of the interval.
+    #[inline]
+    pub fn months(&self) -> i32 {
+        self.months
+    }
+}
+
 /// Serializes a `MACADDR` value.
 #[inline]
 pub fn macaddr_to_sql(v: [u8; 6], buf: &mut BytesMut) {
@@ -438,6 +491,23 @@ pub fn macaddr_from_sql(buf: &[u8]) -> Result<[u8; 6], StdBox<dyn Error + Sync +
     Ok(out)
 }
 
+/// Serializes a `MACADDR8` value.
+#[inline]
+pub fn macaddr8_to_sql(v: [u8; 8], buf: &mut BytesMut) {
+    buf.put_slice(&v);
+}
+
+/// Deserializes a `MACADDR8` value.
+#[inline]
+pub fn macaddr8_from_sql(buf: &[u8]) -> Result<[u8; 8], StdBox<dyn Error + Sync + Send>> {
+    if buf.len() != 8 {
+        return Err("invalid message length: macaddr8 length mismatch".into());
+    }
+    let mut out = [0; 8];
+    out.copy_from_slice(buf);
+    Ok(out)
+}
+
 /// Serializes a `UUID` value.
 #[inline]
 pub fn uuid_to_sql(v: [u8; 16], buf: &mut BytesMut) {
@@ -657,6 +727,85 @@ impl<'a> FallibleIterator for ArrayValues<'a> {
     }
 }
 
+/// Deserializes a `RECORD` or composite value.
+#[inline]
+pub fn record_from_sql(
+    mut buf: &[u8],
+) -> Result<RecordFields<'_>, StdBox<dyn Error + Sync + Send>> {
+    let count = buf.read_i32::<BigEndian>()?;
+    if count < 0 {
+        return Err("invalid field count".into());
+    }
+
+    Ok(RecordFields {
+        remaining: count,
+        buf,
+    })
+}
+
+/// An iterator over the fields of a record.
+pub struct RecordFields<'a> {
+    remaining: i32,
+    buf: &'a [u8],
+}
+
+impl<'a> FallibleIterator for RecordFields<'a> {
+    type Item = RecordField<'a>;
+    type Error = StdBox<dyn Error + Sync + Send>;
+
+    #[inline]
+    fn next(&mut self) -> Result<Option<RecordField<'a>>, StdBox<dyn Error + Sync + Send>> {
+        if self.remaining == 0 {
+            if !self.buf.is_empty() {
+                return Err("invalid message length: record not drained".into());
+            }
+            return Ok(None);
+        }
+        self.remaining -= 1;
+
+        let type_oid = self.buf.read_u32::<BigEndian>()?;
+        let len = self.buf.read_i32::<BigEndian>()?;
+        let value = if len < 0 {
+            None
+        } else {
+            if self.buf.len() < len as usize {
+                return Err("invalid value length".into());
+            }
+
+            let (value, buf) = self.buf.split_at(len as usize);
+            self.buf = buf;
+            Some(value)
+        };
+
+        Ok(Some(RecordField { type_oid, value }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining as usize;
+        (len, Some(len))
+    }
+}
+
+/// A field of a record.
+pub struct RecordField<'a> {
+    type_oid: Oid,
+    value: Option<&'a [u8]>,
+}
+
+impl<'a> RecordField<'a> {
+    /// Returns the OID of the field's type.
+    #[inline]
+    pub fn type_oid(&self) -> Oid {
+        self.type_oid
+    }
+
+    /// Returns the field's value, or `None` if it is `NULL`.
+    #[inline]
+    pub fn value(&self) -> Option<&'a [u8]> {
+        self.value
+    }
+}
+
 /// Serializes an empty range.
 #[inline]
 pub fn empty_range_to_sql(buf: &mut BytesMut) {
@@ -795,6 +944,116 @@ pub enum Range<'a> {
     Nonempty(RangeBound<Option<&'a [u8]>>, RangeBound<Option<&'a [u8]>>),
 }
 
+/// Serializes a multirange value.
+///
+/// Each range is serialized with `serializer`, which should write the range with `range_to_sql` or
+/// `empty_range_to_sql`.
+pub fn multirange_to_sql<T, I, F>(
+    ranges: I,
+    mut serializer: F,
+    buf: &mut BytesMut,
+) -> Result<(), StdBox<dyn Error + Sync + Send>>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(T, &mut BytesMut) -> Result<(), StdBox<dyn Error + Sync + Send>>,
+{
+    let count_idx = buf.len();
+    buf.put_i32(0);
+
+    let mut count = 0;
+    for range in ranges {
+        let base = buf.len();
+        buf.put_i32(0);
+        serializer(range, buf)?;
+        let len = i32::from_usize(buf.len() - base - 4)?;
+        BigEndian::write_i32(&mut buf[base..], len);
+        count += 1;
+    }
+
+    let count = i32::from_usize(count)?;
+    BigEndian::write_i32(&mut buf[count_idx..], count);
+
+    Ok(())
+}
+
+/// Deserializes a multirange value.
+#[inline]
+pub fn multirange_from_sql(
+    mut buf: &[u8],
+) -> Result<Multirange<'_>, StdBox<dyn Error + Sync + Send>> {
+    let count = buf.read_i32::<BigEndian>()?;
+    if count < 0 {
+        return Err("invalid range count".into());
+    }
+
+    Ok(Multirange { count, buf })
+}
+
+/// A Postgres multirange.
+pub struct Multirange<'a> {
+    count: i32,
+    buf: &'a [u8],
+}
+
+impl<'a> Multirange<'a> {
+    /// Returns the number of ranges in the multirange.
+    #[inline]
+    pub fn len(&self) -> i32 {
+        self.count
+    }
+
+    /// Determines if the multirange contains no ranges.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns an iterator over the ranges of the multirange.
+    #[inline]
+    pub fn ranges(&self) -> MultirangeRanges<'a> {
+        MultirangeRanges {
+            remaining: self.count,
+            buf: self.buf,
+        }
+    }
+}
+
+/// An iterator over the ranges of a multirange.
+pub struct MultirangeRanges<'a> {
+    remaining: i32,
+    buf: &'a [u8],
+}
+
+impl<'a> FallibleIterator for MultirangeRanges<'a> {
+    type Item = Range<'a>;
+    type Error = StdBox<dyn Error + Sync + Send>;
+
+    #[inline]
+    fn next(&mut self) -> Result<Option<Range<'a>>, StdBox<dyn Error + Sync + Send>> {
+        if self.remaining == 0 {
+            if !self.buf.is_empty() {
+                return Err("invalid message length: multirange not drained".into());
+            }
+            return Ok(None);
+        }
+        self.remaining -= 1;
+
+        let len = self.buf.read_i32::<BigEndian>()?;
+        if len < 0 || self.buf.len() < len as usize {
+            return Err("invalid range length".into());
+        }
+
+        let (range, buf) = self.buf.split_at(len as usize);
+        self.buf = buf;
+        range_from_sql(range).map(Some)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining as usize;
+        (len, Some(len))
+    }
+}
+
 /// Serializes a point value.
 #[inline]
 pub fn point_to_sql(x: f64, y: f64, buf: &mut BytesMut) {
@@ -977,6 +1236,197 @@ impl FallibleIterator for PathPoints<'_> {
     }
 }
 
+/// Serializes a Postgres line, the set of points satisfying `a * x + b * y + c = 0`.
+#[inline]
+pub fn line_to_sql(a: f64, b: f64, c: f64, buf: &mut BytesMut) {
+    buf.put_f64(a);
+    buf.put_f64(b);
+    buf.put_f64(c);
+}
+
+/// Deserializes a Postgres line.
+#[inline]
+pub fn line_from_sql(mut buf: &[u8]) -> Result<Line, StdBox<dyn Error + Sync + Send>> {
+    let a = buf.read_f64::<BigEndian>()?;
+    let b = buf.read_f64::<BigEndian>()?;
+    let c = buf.read_f64::<BigEndian>()?;
+    if !buf.is_empty() {
+        return Err("invalid buffer size".into());
+    }
+    Ok(Line { a, b, c })
+}
+
+/// A Postgres line.
+#[derive(Copy, Clone)]
+pub struct Line {
+    a: f64,
+    b: f64,
+    c: f64,
+}
+
+impl Line {
+    /// Returns the `a` coefficient of the line's equation.
+    #[inline]
+    pub fn a(&self) -> f64 {
+        self.a
+    }
+
+    /// Returns the `b` coefficient of the line's equation.
+    #[inline]
+    pub fn b(&self) -> f64 {
+        self.b
+    }
+
+    /// Returns the `c` coefficient of the line's equation.
+    #[inline]
+    pub fn c(&self) -> f64 {
+        self.c
+    }
+}
+
+/// Serializes a Postgres line segment.
+#[inline]
+pub fn lseg_to_sql(x1: f64, y1: f64, x2: f64, y2: f64, buf: &mut BytesMut) {
+    buf.put_f64(x1);
+    buf.put_f64(y1);
+    buf.put_f64(x2);
+    buf.put_f64(y2);
+}
+
+/// Deserializes a Postgres line segment.
+#[inline]
+pub fn lseg_from_sql(mut buf: &[u8]) -> Result<LineSegment, StdBox<dyn Error + Sync + Send>> {
+    let x1 = buf.read_f64::<BigEndian>()?;
+    let y1 = buf.read_f64::<BigEndian>()?;
+    let x2 = buf.read_f64::<BigEndian>()?;
+    let y2 = buf.read_f64::<BigEndian>()?;
+    if !buf.is_empty() {
+        return Err("invalid buffer size".into());
+    }
+    Ok(LineSegment {
+        start: Point { x: x1, y: y1 },
+        end: Point { x: x2, y: y2 },
+    })
+}
+
+/// A Postgres line segment.
+#[derive(Copy, Clone)]
+pub struct LineSegment {
+    start: Point,
+    end: Point,
+}
+
+impl LineSegment {
+    /// Returns the start point of the segment.
+    #[inline]
+    pub fn start(&self) -> Point {
+        self.start
+    }
+
+    /// Returns the end point of the segment.
+    #[inline]
+    pub fn end(&self) -> Point {
+        self.end
+    }
+}
+
+/// Serializes a Postgres polygon.
+#[inline]
+pub fn polygon_to_sql<I>(
+    points: I,
+    buf: &mut BytesMut,
+) -> Result<(), StdBox<dyn Error + Sync + Send>>
+where
+    I: IntoIterator<Item = (f64, f64)>,
+{
+    let points_idx = buf.len();
+    buf.put_i32(0);
+
+    let mut num_points = 0;
+    for (x, y) in points {
+        num_points += 1;
+        buf.put_f64(x);
+        buf.put_f64(y);
+    }
+
+    let num_points = i32::from_usize(num_points)?;
+    BigEndian::write_i32(&mut buf[points_idx..], num_points);
+
+    Ok(())
+}
+
+/// Deserializes a Postgres polygon.
+#[inline]
+pub fn polygon_from_sql(mut buf: &[u8]) -> Result<Polygon<'_>, StdBox<dyn Error + Sync + Send>> {
+    let points = buf.read_i32::<BigEndian>()?;
+    if points < 0 {
+        return Err("invalid point count".into());
+    }
+
+    Ok(Polygon { points, buf })
+}
+
+/// A Postgres polygon.
+pub struct Polygon<'a> {
+    points: i32,
+    buf: &'a [u8],
+}
+
+impl<'a> Polygon<'a> {
+    /// Returns an iterator over the vertices of the polygon.
+    #[inline]
+    pub fn points(&self) -> PathPoints<'a> {
+        PathPoints {
+            remaining: self.points,
+            buf: self.buf,
+        }
+    }
+}
+
+/// Serializes a Postgres circle.
+#[inline]
+pub fn circle_to_sql(x: f64, y: f64, radius: f64, buf: &mut BytesMut) {
+    buf.put_f64(x);
+    buf.put_f64(y);
+    buf.put_f64(radius);
+}
+
+/// Deserializes a Postgres circle.
+#[inline]
+pub fn circle_from_sql(mut buf: &[u8]) -> Result<Circle, StdBox<dyn Error + Sync + Send>> {
+    let x = buf.read_f64::<BigEndian>()?;
+    let y = buf.read_f64::<BigEndian>()?;
+    let radius = buf.read_f64::<BigEndian>()?;
+    if !buf.is_empty() {
+        return Err("invalid buffer size".into());
+    }
+    Ok(Circle {
+        center: Point { x, y },
+        radius,
+    })
+}
+
+/// A Postgres circle.
+#[derive(Copy, Clone)]
+pub struct Circle {
+    center: Point,
+    radius: f64,
+}
+
+impl Circle {
+    /// Returns the center of the circle.
+    #[inline]
+    pub fn center(&self) -> Point {
+        self.center
+    }
+
+    /// Returns the radius of the circle.
+    #[inline]
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+}
+
 /// Serializes a Postgres inet.
 #[inline]
 pub fn inet_to_sql(addr: IpAddr, netmask: u8, buf: &mut BytesMut) {
@@ -1116,3 +1566,311 @@ pub fn ltxtquery_from_sql(buf: &[u8]) -> Result<&str, StdBox<dyn Error + Sync +
         _ => Err("ltxtquery version 1 only supported".into()),
     }
 }
+
+/// Serializes a `TSVECTOR` value.
+///
+/// Each lexeme is provided along with its positions, each of which packs a weight into its top two bits and a
+/// position into its bottom 14 bits.
+pub fn tsvector_to_sql<'a, I, P>(
+    lexemes: I,
+    buf: &mut BytesMut,
+) -> Result<(), StdBox<dyn Error + Sync + Send>>
+where
+    I: IntoIterator<Item = (&'a str, P)>,
+    P: IntoIterator<Item = u16>,
+{
+    let count_idx = buf.len();
+    buf.put_i32(0);
+
+    let mut count = 0;
+    for (lexeme, positions) in lexemes {
+        write_cstr(lexeme, buf)?;
+
+        let positions_idx = buf.len();
+        buf.put_u16(0);
+        let mut positions_count = 0;
+        for position in positions {
+            buf.put_u16(position);
+            positions_count += 1;
+        }
+        let positions_count = u16::from_usize(positions_count)?;
+        BigEndian::write_u16(&mut buf[positions_idx..], positions_count);
+
+        count += 1;
+    }
+
+    let count = i32::from_usize(count)?;
+    BigEndian::write_i32(&mut buf[count_idx..], count);
+
+    Ok(())
+}
+
+/// Deserializes a `TSVECTOR` value.
+#[inline]
+pub fn tsvector_from_sql(
+    mut buf: &[u8],
+) -> Result<TsVectorEntries<'_>, StdBox<dyn Error + Sync + Send>> {
+    let count = buf.read_i32::<BigEndian>()?;
+    if count < 0 {
+        return Err("invalid lexeme count".into());
+    }
+
+    Ok(TsVectorEntries {
+        remaining: count,
+        buf,
+    })
+}
+
+/// A fallible iterator over the lexemes of a `TSVECTOR`.
+pub struct TsVectorEntries<'a> {
+    remaining: i32,
+    buf: &'a [u8],
+}
+
+impl<'a> FallibleIterator for TsVectorEntries<'a> {
+    type Item = TsVectorEntry<'a>;
+    type Error = StdBox<dyn Error + Sync + Send>;
+
+    #[inline]
+    fn next(&mut self) -> Result<Option<TsVectorEntry<'a>>, StdBox<dyn Error + Sync + Send>> {
+        if self.remaining == 0 {
+            if !self.buf.is_empty() {
+                return Err("invalid buffer size".into());
+            }
+            return Ok(None);
+        }
+        self.remaining -= 1;
+
+        let lexeme = read_cstr(&mut self.buf)?;
+        let count = self.buf.read_u16::<BigEndian>()? as usize;
+        if self.buf.len() < count * 2 {
+            return Err("invalid buffer size".into());
+        }
+        let (positions, buf) = self.buf.split_at(count * 2);
+        self.buf = buf;
+
+        Ok(Some(TsVectorEntry { lexeme, positions }))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining as usize;
+        (len, Some(len))
+    }
+}
+
+/// A lexeme of a `TSVECTOR`.
+pub struct TsVectorEntry<'a> {
+    lexeme: &'a str,
+    positions: &'a [u8],
+}
+
+impl<'a> TsVectorEntry<'a> {
+    /// Returns the lexeme.
+    #[inline]
+    pub fn lexeme(&self) -> &'a str {
+        self.lexeme
+    }
+
+    /// Returns an iterator over the positions of the lexeme.
+    ///
+    /// Each position packs a weight into its top two bits and a position into its bottom 14 bits.
+    #[inline]
+    pub fn positions(&self) -> TsVectorPositions<'a> {
+        TsVectorPositions(self.positions)
+    }
+}
+
+/// An iterator over the positions of a `TSVECTOR` lexeme.
+pub struct TsVectorPositions<'a>(&'a [u8]);
+
+impl Iterator for TsVectorPositions<'_> {
+    type Item = u16;
+
+    #[inline]
+    fn next(&mut self) -> Option<u16> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        let position = BigEndian::read_u16(self.0);
+        self.0 = &self.0[2..];
+        Some(position)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.len() / 2;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for TsVectorPositions<'_> {}
+
+const TSQUERY_VALUE: u8 = 1;
+const TSQUERY_OPERATOR: u8 = 2;
+
+const TSQUERY_NOT: u8 = 1;
+const TSQUERY_AND: u8 = 2;
+const TSQUERY_OR: u8 = 3;
+const TSQUERY_PHRASE: u8 = 4;
+
+/// An item of a `TSQUERY`.
+///
+/// A query is a sequence of items in prefix order, with each operator followed by its right operand and then, for
+/// binary operators, its left operand.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TsQueryItem<'a> {
+    /// A lexeme to match.
+    Operand {
+        /// A bitmask of the weights the lexeme matches, with `A` as `0b1000` and `D` as `0b0001`, or `0` to match
+        /// any weight.
+        weights: u8,
+        /// Whether the lexeme matches as a prefix.
+        prefix: bool,
+        /// The lexeme.
+        lexeme: &'a str,
+    },
+    /// The `!` operator.
+    Not,
+    /// The `&` operator.
+    And,
+    /// The `|` operator.
+    Or,
+    /// The `<N>` operator, with its distance.
+    Phrase(u16),
+}
+
+/// Serializes a `TSQUERY` value.
+pub fn tsquery_to_sql<'a, I>(
+    items: I,
+    buf: &mut BytesMut,
+) -> Result<(), StdBox<dyn Error + Sync + Send>>
+where
+    I: IntoIterator<Item = TsQueryItem<'a>>,
+{
+    let count_idx = buf.len();
+    buf.put_i32(0);
+
+    let mut count = 0;
+    for item in items {
+        match item {
+            TsQueryItem::Operand {
+                weights,
+                prefix,
+                lexeme,
+            } => {
+                buf.put_u8(TSQUERY_VALUE);
+                buf.put_u8(weights);
+                buf.put_u8(prefix as u8);
+                write_cstr(lexeme, buf)?;
+            }
+            TsQueryItem::Not => {
+                buf.put_u8(TSQUERY_OPERATOR);
+                buf.put_u8(TSQUERY_NOT);
+            }
+            TsQueryItem::And => {
+                buf.put_u8(TSQUERY_OPERATOR);
+                buf.put_u8(TSQUERY_AND);
+            }
+            TsQueryItem::Or => {
+                buf.put_u8(TSQUERY_OPERATOR);
+                buf.put_u8(TSQUERY_OR);
+            }
+            TsQueryItem::Phrase(distance) => {
+                buf.put_u8(TSQUERY_OPERATOR);
+                buf.put_u8(TSQUERY_PHRASE);
+                buf.put_u16(distance);
+            }
+        }
+        count += 1;
+    }
+
+    let count = i32::from_usize(count)?;
+    BigEndian::write_i32(&mut buf[count_idx..], count);
+
+    Ok(())
+}
+
+/// Deserializes a `TSQUERY` value.
+#[inline]
+pub fn tsquery_from_sql(
+    mut buf: &[u8],
+) -> Result<TsQueryItems<'_>, StdBox<dyn Error + Sync + Send>> {
+    let count = buf.read_i32::<BigEndian>()?;
+    if count < 0 {
+        return Err("invalid item count".into());
+    }
+
+    Ok(TsQueryItems {
+        remaining: count,
+        buf,
+    })
+}
+
+/// A fallible iterator over the items of a `TSQUERY`, in prefix order.
+pub struct TsQueryItems<'a> {
+    remaining: i32,
+    buf: &'a [u8],
+}
+
+impl<'a> FallibleIterator for TsQueryItems<'a> {
+    type Item = TsQueryItem<'a>;
+    type Error = StdBox<dyn Error + Sync + Send>;
+
+    #[inline]
+    fn next(&mut self) -> Result<Option<TsQueryItem<'a>>, StdBox<dyn Error + Sync + Send>> {
+        if self.remaining == 0 {
+            if !self.buf.is_empty() {
+                return Err("invalid buffer size".into());
+            }
+            return Ok(None);
+        }
+        self.remaining -= 1;
+
+        let item = match self.buf.read_u8()? {
+            TSQUERY_VALUE => {
+                let weights = self.buf.read_u8()?;
+                let prefix = self.buf.read_u8()? != 0;
+                let lexeme = read_cstr(&mut self.buf)?;
+                TsQueryItem::Operand {
+                    weights,
+                    prefix,
+                    lexeme,
+                }
+            }
+            TSQUERY_OPERATOR => match self.buf.read_u8()? {
+                TSQUERY_NOT => TsQueryItem::Not,
+                TSQUERY_AND => TsQueryItem::And,
+                TSQUERY_OR => TsQueryItem::Or,
+                TSQUERY_PHRASE => TsQueryItem::Phrase(self.buf.read_u16::<BigEndian>()?),
+                _ => return Err("invalid tsquery operator".into()),
+            },
+            _ => return Err("invalid tsquery item type".into()),
+        };
+
+        Ok(Some(item))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining as usize;
+        (len, Some(len))
+    }
+}
+
+fn write_cstr(s: &str, buf: &mut BytesMut) -> Result<(), StdBox<dyn Error + Sync + Send>> {
+    if s.as_bytes().contains(&0) {
+        return Err("string contains embedded null".into());
+    }
+    buf.put_slice(s.as_bytes());
+    buf.put_u8(0);
+    Ok(())
+}
+
+fn read_cstr<'a>(buf: &mut &'a [u8]) -> Result<&'a str, StdBox<dyn Error + Sync + Send>> {
+    let end = memchr(0, buf).ok_or("unterminated string")?;
+    let s = str::from_utf8(&buf[..end])?;
+    *buf = &buf[end + 1..];
+    Ok(s)
+}
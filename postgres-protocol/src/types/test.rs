@@ -1,4 +1,4 @@
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, BufMut, BytesMut};
 use fallible_iterator::FallibleIterator;
 use std::collections::HashMap;
 
@@ -157,6 +157,45 @@ fn non_null_array() {
     assert_eq!(array.values().collect::<Vec<_>>().unwrap(), values);
 }
 
+#[test]
+fn multirange() {
+    let mut buf = BytesMut::new();
+    multirange_to_sql(
+        [Some((1, 5)), None],
+        |range, buf| match range {
+            Some((lower, upper)) => range_to_sql(
+                |buf| {
+                    int4_to_sql(lower, buf);
+                    Ok(RangeBound::Inclusive(IsNull::No))
+                },
+                |buf| {
+                    int4_to_sql(upper, buf);
+                    Ok(RangeBound::Exclusive(IsNull::No))
+                },
+                buf,
+            ),
+            None => {
+                empty_range_to_sql(buf);
+                Ok(())
+            }
+        },
+        &mut buf,
+    )
+    .unwrap();
+
+    let multirange = multirange_from_sql(&buf).unwrap();
+    assert_eq!(multirange.len(), 2);
+    let ranges = multirange.ranges().collect::<Vec<_>>().unwrap();
+    match ranges[0] {
+        Range::Nonempty(RangeBound::Inclusive(Some(lower)), RangeBound::Exclusive(Some(upper))) => {
+            assert_eq!(int4_from_sql(lower).unwrap(), 1);
+            assert_eq!(int4_from_sql(upper).unwrap(), 5);
+        }
+        _ => panic!("unexpected range"),
+    }
+    assert!(matches!(ranges[1], Range::Empty));
+}
+
 #[test]
 fn ltree_sql() {
     let mut query = vec![1u8];
@@ -240,3 +279,78 @@ fn ltxtquery_wrong_version() {
 
     assert!(ltree_from_sql(query.as_slice()).is_err())
 }
+
+#[test]
+fn tsvector() {
+    let lexemes = [("cat", vec![1, 0xC003]), ("fat", vec![])];
+
+    let mut buf = BytesMut::new();
+    tsvector_to_sql(
+        lexemes
+            .iter()
+            .map(|(lexeme, positions)| (*lexeme, positions.iter().copied())),
+        &mut buf,
+    )
+    .unwrap();
+
+    let entries = tsvector_from_sql(&buf)
+        .unwrap()
+        .map(|e| Ok((e.lexeme(), e.positions().collect::<Vec<_>>())))
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(entries, lexemes);
+}
+
+#[test]
+fn tsquery() {
+    let items = [
+        TsQueryItem::And,
+        TsQueryItem::Not,
+        TsQueryItem::Operand {
+            weights: 0b1100,
+            prefix: true,
+            lexeme: "rat",
+        },
+        TsQueryItem::Phrase(2),
+        TsQueryItem::Operand {
+            weights: 0,
+            prefix: false,
+            lexeme: "cat",
+        },
+        TsQueryItem::Operand {
+            weights: 0,
+            prefix: false,
+            lexeme: "fat",
+        },
+    ];
+
+    let mut buf = BytesMut::new();
+    tsquery_to_sql(items.iter().copied(), &mut buf).unwrap();
+
+    let decoded = tsquery_from_sql(&buf).unwrap().collect::<Vec<_>>().unwrap();
+    assert_eq!(decoded, items);
+}
+
+#[test]
+fn record() {
+    let mut buf = BytesMut::new();
+    buf.put_i32(2);
+    buf.put_u32(23);
+    buf.put_i32(4);
+    buf.put_i32(10);
+    buf.put_u32(25);
+    buf.put_i32(-1);
+
+    let fields = record_from_sql(&buf)
+        .unwrap()
+        .map(|f| Ok((f.type_oid(), f.value())))
+        .collect::<Vec<_>>()
+        .unwrap();
+    assert_eq!(fields, vec![(23, Some(&[0, 0, 0, 10][..])), (25, None)]);
+
+    buf.put_u8(0);
+    let mut fields = record_from_sql(&buf).unwrap();
+    assert!(fields.next().unwrap().is_some());
+    assert!(fields.next().unwrap().is_some());
+    assert!(fields.next().is_err());
+}
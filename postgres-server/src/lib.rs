@@ -0,0 +1,866 @@
+//! A framework for implementing the server side of the PostgreSQL protocol.
+//!
+//! The [`serve`] function runs a single client connection over any stream. It handles the framing of messages, the
+//! startup and authentication exchange, the error recovery rules of the extended query protocol, and the reporting of
+//! the transaction status, and calls into a [`Handler`] to answer the client's statements. Handlers write their
+//! responses with the methods of a [`Responder`]. [`Config`] adjusts the limits applied to clients.
+//!
+//! TLS and GSSAPI encryption requests are declined, so clients must be configured to allow unencrypted connections.
+//!
+//! # Examples
+//!
+//! A server which answers every simple query with a single row containing the query's text:
+//!
+//! ```no_run
+//! use async_trait::async_trait;
+//! use postgres_server::{ErrorResponse, Field, Handler, Responder};
+//! use tokio::net::TcpListener;
+//!
+//! struct Echo;
+//!
+//! #[async_trait]
+//! impl Handler for Echo {
+//!     async fn on_query(&mut self, query: &str, out: &mut Responder) -> Result<(), ErrorResponse> {
+//!         out.row_description([Field::new("query", 0, 0, 25, -1, -1, 0)])?;
+//!         out.data_row([Some(query.as_bytes())])?;
+//!         out.command_complete("SELECT 1")?;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # async fn run() -> std::io::Result<()> {
+//! let listener = TcpListener::bind("127.0.0.1:5432").await?;
+//! loop {
+//!     let (stream, _) = listener.accept().await?;
+//!     tokio::spawn(async move { postgres_server::serve(stream, &mut Echo).await });
+//! }
+//! # }
+//! ```
+#![warn(missing_docs, rust_2018_idioms, clippy::all)]
+
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::authentication::crypto::{self, constant_time_eq};
+use postgres_protocol::authentication::md5_hash;
+use postgres_protocol::authentication::sasl::{ScramSha256Server, SCRAM_SHA_256};
+use postgres_protocol::message::backend;
+use postgres_protocol::message::frontend::{Message, PasswordMessageBody, StartupMessage};
+use std::error;
+use std::fmt;
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+#[doc(inline)]
+pub use postgres_protocol::message::backend::Field;
+pub use postgres_protocol::Oid;
+
+#[cfg(test)]
+mod test;
+
+const INTERNAL_ERROR: &str = "XX000";
+const FEATURE_NOT_SUPPORTED: &str = "0A000";
+const INVALID_PASSWORD: &str = "28P01";
+
+// The largest startup packet accepted, as in the Postgres server.
+const MAX_STARTUP_PACKET_LEN: usize = 10_000;
+
+// The largest message accepted during authentication, as in the Postgres server.
+const MAX_AUTH_MESSAGE_LEN: usize = 65_535;
+
+/// The default value of [`Config::max_message_len`], which matches the Postgres server's own limit of just under 1 GiB.
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 0x3fff_fffe;
+
+/// The authentication method required of a client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Auth {
+    /// The client is accepted without a password.
+    Trust,
+    /// The client must send the provided password in cleartext.
+    Cleartext(String),
+    /// The client must send the MD5 hash of the provided password.
+    Md5(String),
+    /// The client must authenticate with the provided password using SCRAM-SHA-256.
+    ScramSha256(String),
+    /// The client must authenticate using SCRAM-SHA-256 against the provided stored verifier, in the
+    /// `SCRAM-SHA-256$<iterations>:<salt>$<StoredKey>:<ServerKey>` format used by `pg_authid.rolpassword`.
+    ///
+    /// Clients are rejected if the verifier is malformed.
+    ScramSha256Verifier(String),
+}
+
+/// The parameters sent by a client in its startup message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Startup {
+    parameters: Vec<(String, String)>,
+}
+
+impl Startup {
+    /// Returns the value of a parameter, if it was sent.
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| &**v)
+    }
+
+    /// Returns all of the parameters, in the order they were sent.
+    pub fn parameters(&self) -> &[(String, String)] {
+        &self.parameters
+    }
+
+    /// Returns the name of the user the client is connecting as.
+    pub fn user(&self) -> Option<&str> {
+        self.parameter("user")
+    }
+
+    /// Returns the name of the database the client is connecting to.
+    ///
+    /// Like the server, this defaults to the name of the user.
+    pub fn database(&self) -> Option<&str> {
+        self.parameter("database").or_else(|| self.user())
+    }
+}
+
+/// An error reported to a client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorResponse {
+    severity: String,
+    code: String,
+    message: String,
+    detail: Option<String>,
+    hint: Option<String>,
+}
+
+impl ErrorResponse {
+    /// Creates an `ERROR` with the provided SQLSTATE code and message.
+    pub fn new(code: &str, message: &str) -> ErrorResponse {
+        ErrorResponse::with_severity("ERROR", code, message)
+    }
+
+    /// Creates a `FATAL` error with the provided SQLSTATE code and message.
+    ///
+    /// The connection is closed after a fatal error is reported.
+    pub fn fatal(code: &str, message: &str) -> ErrorResponse {
+        ErrorResponse::with_severity("FATAL", code, message)
+    }
+
+    fn with_severity(severity: &str, code: &str, message: &str) -> ErrorResponse {
+        ErrorResponse {
+            severity: severity.to_string(),
+            code: code.to_string(),
+            message: message.to_string(),
+            detail: None,
+            hint: None,
+        }
+    }
+
+    /// Sets the detail of the error.
+    pub fn detail(mut self, detail: &str) -> ErrorResponse {
+        self.detail = Some(detail.to_string());
+        self
+    }
+
+    /// Sets the hint of the error.
+    pub fn hint(mut self, hint: &str) -> ErrorResponse {
+        self.hint = Some(hint.to_string());
+        self
+    }
+
+    /// Returns the severity of the error.
+    pub fn severity(&self) -> &str {
+        &self.severity
+    }
+
+    /// Returns the SQLSTATE code of the error.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Returns the message of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.severity == "FATAL" || self.severity == "PANIC"
+    }
+
+    fn write(&self, buf: &mut BytesMut) -> io::Result<()> {
+        let mut fields = vec![
+            (b'S', &*self.severity),
+            (b'V', &*self.severity),
+            (b'C', &*self.code),
+            (b'M', &*self.message),
+        ];
+        if let Some(detail) = &self.detail {
+            fields.push((b'D', detail));
+        }
+        if let Some(hint) = &self.hint {
+            fields.push((b'H', hint));
+        }
+        backend::error_response(fields, buf)
+    }
+}
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}: {}", self.severity, self.message)
+    }
+}
+
+impl error::Error for ErrorResponse {}
+
+impl From<io::Error> for ErrorResponse {
+    fn from(e: io::Error) -> ErrorResponse {
+        ErrorResponse::new(INTERNAL_ERROR, &e.to_string())
+    }
+}
+
+/// The transaction status of a connection, reported to the client when it is ready for a new query.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// Not in a transaction.
+    Idle,
+    /// In a transaction.
+    InTransaction,
+    /// In a failed transaction, which must be rolled back.
+    Failed,
+}
+
+impl TransactionStatus {
+    fn byte(self) -> u8 {
+        match self {
+            TransactionStatus::Idle => b'I',
+            TransactionStatus::InTransaction => b'T',
+            TransactionStatus::Failed => b'E',
+        }
+    }
+}
+
+/// The contents of a client's `Bind` message.
+#[derive(Debug)]
+pub struct Bind<'a> {
+    portal: &'a str,
+    statement: &'a str,
+    param_formats: &'a [i16],
+    params: &'a [Option<Bytes>],
+    result_formats: &'a [i16],
+}
+
+impl<'a> Bind<'a> {
+    /// Returns the name of the portal to create, which is empty for the unnamed portal.
+    pub fn portal(&self) -> &'a str {
+        self.portal
+    }
+
+    /// Returns the name of the prepared statement to bind, which is empty for the unnamed statement.
+    pub fn statement(&self) -> &'a str {
+        self.statement
+    }
+
+    /// Returns the format codes of the parameters.
+    ///
+    /// As in the protocol, an empty list means that all parameters are in the text format, and a single code applies
+    /// to all parameters.
+    pub fn param_formats(&self) -> &'a [i16] {
+        self.param_formats
+    }
+
+    /// Returns the values of the parameters, with `None` representing `NULL`.
+    pub fn params(&self) -> &'a [Option<Bytes>] {
+        self.params
+    }
+
+    /// Returns the format codes requested for the result columns.
+    ///
+    /// As in the protocol, an empty list means that all columns are in the text format, and a single code applies to
+    /// all columns.
+    pub fn result_formats(&self) -> &'a [i16] {
+        self.result_formats
+    }
+}
+
+/// A buffer of messages to be sent to a client in response to one of its messages.
+pub struct Responder {
+    buf: BytesMut,
+    status: TransactionStatus,
+}
+
+impl Responder {
+    /// Returns the transaction status of the connection.
+    pub fn transaction_status(&self) -> TransactionStatus {
+        self.status
+    }
+
+    /// Sets the transaction status of the connection.
+    ///
+    /// The status is tracked automatically from the command tags passed to `command_complete` and from errors, so this
+    /// is only needed by handlers which implement transactions with other commands.
+    pub fn set_transaction_status(&mut self, status: TransactionStatus) {
+        self.status = status;
+    }
+
+    /// Writes a `ParameterStatus` message, reporting the value of a runtime parameter.
+    pub fn parameter_status(&mut self, name: &str, value: &str) -> io::Result<()> {
+        backend::parameter_status(name, value, &mut self.buf)
+    }
+
+    /// Writes a `BackendKeyData` message, providing the key the client uses to cancel queries.
+    pub fn backend_key_data(&mut self, process_id: i32, secret_key: i32) {
+        backend::backend_key_data(process_id, secret_key, &mut self.buf);
+    }
+
+    /// Writes a `ParameterDescription` message, describing the parameters of a prepared statement.
+    pub fn parameter_description<I>(&mut self, types: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = Oid>,
+    {
+        backend::parameter_description(types, &mut self.buf)
+    }
+
+    /// Writes a `RowDescription` message, describing the columns of the rows which follow.
+    pub fn row_description<'a, I>(&mut self, fields: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = Field<'a>>,
+    {
+        backend::row_description(fields, &mut self.buf)
+    }
+
+    /// Writes a `NoData` message, indicating that a statement or portal returns no rows.
+    pub fn no_data(&mut self) {
+        backend::no_data(&mut self.buf);
+    }
+
+    /// Writes a `DataRow` message, with `None` representing `NULL`.
+    pub fn data_row<'a, I>(&mut self, values: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = Option<&'a [u8]>>,
+    {
+        backend::data_row(values, &mut self.buf)
+    }
+
+    /// Writes a `CommandComplete` message with the provided command tag, such as `SELECT 2` or `INSERT 0 1`.
+    ///
+    /// The `BEGIN`, `COMMIT`, and `ROLLBACK` tags update the transaction status.
+    pub fn command_complete(&mut self, tag: &str) -> io::Result<()> {
+        match tag {
+            "BEGIN" => self.status = TransactionStatus::InTransaction,
+            "COMMIT" | "ROLLBACK" => self.status = TransactionStatus::Idle,
+            _ => {}
+        }
+        backend::command_complete(tag, &mut self.buf)
+    }
+
+    /// Writes an `EmptyQueryResponse` message, in place of `command_complete` for an empty query.
+    pub fn empty_query_response(&mut self) {
+        backend::empty_query_response(&mut self.buf);
+    }
+
+    /// Writes a `PortalSuspended` message, in place of `command_complete` when an execution's row limit is reached
+    /// before the portal completes.
+    pub fn portal_suspended(&mut self) {
+        backend::portal_suspended(&mut self.buf);
+    }
+
+    /// Writes a `NoticeResponse` message with the provided SQLSTATE code and message.
+    pub fn notice(&mut self, code: &str, message: &str) -> io::Result<()> {
+        backend::notice_response(
+            [
+                (b'S', "NOTICE"),
+                (b'V', "NOTICE"),
+                (b'C', code),
+                (b'M', message),
+            ],
+            &mut self.buf,
+        )
+    }
+
+    /// Writes a `NotificationResponse` message, delivering a notification to a listening client.
+    pub fn notification(
+        &mut self,
+        process_id: i32,
+        channel: &str,
+        payload: &str,
+    ) -> io::Result<()> {
+        backend::notification_response(process_id, channel, payload, &mut self.buf)
+    }
+
+    fn error(&mut self, error: &ErrorResponse) -> io::Result<()> {
+        if self.status == TransactionStatus::InTransaction {
+            self.status = TransactionStatus::Failed;
+        }
+        error.write(&mut self.buf)
+    }
+}
+
+fn unsupported(message: &str) -> ErrorResponse {
+    ErrorResponse::new(
+        FEATURE_NOT_SUPPORTED,
+        &format!("{} is not supported", message),
+    )
+}
+
+/// The callbacks through which a server answers a client.
+///
+/// Each callback which receives a `Responder` writes its response to it. If a callback returns an error, it is
+/// reported to the client after anything already written; in the extended query protocol the client's remaining
+/// messages are then discarded until its next `Sync`, as the server does.
+///
+/// Only `on_query` is required. The extended query protocol callbacks default to rejecting the messages.
+#[async_trait]
+pub trait Handler: Send {
+    /// Called with the client's startup message, returning the authentication method the client must complete.
+    ///
+    /// Defaults to `Auth::Trust`.
+    async fn on_startup(&mut self, startup: &Startup) -> Result<Auth, ErrorResponse> {
+        let _ = startup;
+        Ok(Auth::Trust)
+    }
+
+    /// Called once the client has authenticated, before it is told that the server is ready for queries.
+    ///
+    /// This is the place to report runtime parameters with `Responder::parameter_status` and the cancellation key
+    /// with `Responder::backend_key_data`.
+    async fn on_authenticated(&mut self, out: &mut Responder) -> Result<(), ErrorResponse> {
+        let _ = out;
+        Ok(())
+    }
+
+    /// Called with a query string sent with the simple query protocol.
+    ///
+    /// For each statement, the handler should write an optional `RowDescription` and the rows, followed by a
+    /// `CommandComplete`. Empty query strings are answered without calling the handler.
+    async fn on_query(&mut self, query: &str, out: &mut Responder) -> Result<(), ErrorResponse>;
+
+    /// Called with a `Parse` message, to prepare a statement.
+    ///
+    /// The parameter types given by the client may be empty or contain `0` for parameters whose types are to be
+    /// inferred. A `ParseComplete` is written on success.
+    async fn on_parse(
+        &mut self,
+        name: &str,
+        query: &str,
+        param_types: &[Oid],
+    ) -> Result<(), ErrorResponse> {
+        let _ = (name, query, param_types);
+        Err(unsupported("the extended query protocol"))
+    }
+
+    /// Called with a `Bind` message, to create a portal from a prepared statement.
+    ///
+    /// A `BindComplete` is written on success.
+    async fn on_bind(&mut self, bind: &Bind<'_>) -> Result<(), ErrorResponse> {
+        let _ = bind;
+        Err(unsupported("the extended query protocol"))
+    }
+
+    /// Called with a `Describe` message for a prepared statement.
+    ///
+    /// The handler should write a `ParameterDescription`, followed by either a `RowDescription` or `NoData`.
+    async fn on_describe_statement(
+        &mut self,
+        name: &str,
+        out: &mut Responder,
+    ) -> Result<(), ErrorResponse> {
+        let _ = (name, out);
+        Err(unsupported("the extended query protocol"))
+    }
+
+    /// Called with a `Describe` message for a portal.
+    ///
+    /// The handler should write either a `RowDescription`, using the result formats the portal was bound with, or
+    /// `NoData`.
+    async fn on_describe_portal(
+        &mut self,
+        name: &str,
+        out: &mut Responder,
+    ) -> Result<(), ErrorResponse> {
+        let _ = (name, out);
+        Err(unsupported("the extended query protocol"))
+    }
+
+    /// Called with an `Execute` message, to run a portal.
+    ///
+    /// The handler should write at most `max_rows` rows, or all rows if it is `0`, followed by either a
+    /// `CommandComplete` or, if rows remain, a `PortalSuspended`.
+    async fn on_execute(
+        &mut self,
+        portal: &str,
+        max_rows: i32,
+        out: &mut Responder,
+    ) -> Result<(), ErrorResponse> {
+        let _ = (portal, max_rows, out);
+        Err(unsupported("the extended query protocol"))
+    }
+
+    /// Called with a `Close` message for a prepared statement.
+    ///
+    /// A `CloseComplete` is written on success.
+    async fn on_close_statement(&mut self, name: &str) -> Result<(), ErrorResponse> {
+        let _ = name;
+        Ok(())
+    }
+
+    /// Called with a `Close` message for a portal.
+    ///
+    /// A `CloseComplete` is written on success.
+    async fn on_close_portal(&mut self, name: &str) -> Result<(), ErrorResponse> {
+        let _ = name;
+        Ok(())
+    }
+
+    /// Called with a cancellation request, which is sent on a new connection in place of a startup message.
+    ///
+    /// The connection is closed once this returns.
+    async fn on_cancel(&mut self, process_id: i32, secret_key: i32) {
+        let _ = (process_id, secret_key);
+    }
+}
+
+/// Serves a single client connection over the provided stream, with the default [`Config`].
+///
+/// Returns once the client terminates the connection or closes the stream, once a fatal error has been reported to
+/// it, or if it fails to authenticate. An error is returned if the stream fails or the client violates the protocol.
+pub async fn serve<S, H>(stream: S, handler: &mut H) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    H: Handler + ?Sized,
+{
+    Config::new().serve(stream, handler).await
+}
+
+/// Configuration for serving client connections.
+#[derive(Clone, Debug)]
+pub struct Config {
+    max_message_len: usize,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config::new()
+    }
+}
+
+impl Config {
+    /// Creates a new configuration with default values.
+    pub fn new() -> Config {
+        Config {
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+        }
+    }
+
+    /// Sets the largest message, in bytes, accepted from a client once it has authenticated.
+    ///
+    /// Larger messages are rejected with an [`InvalidData`](io::ErrorKind::InvalidData) error, which closes the
+    /// connection. Before authentication, startup packets are limited to 10000 bytes and other messages to 65535
+    /// bytes regardless of this setting. Defaults to [`DEFAULT_MAX_MESSAGE_LEN`].
+    pub fn max_message_len(&mut self, max_message_len: usize) -> &mut Config {
+        self.max_message_len = max_message_len;
+        self
+    }
+
+    /// Gets the largest message accepted from a client once it has authenticated.
+    pub fn get_max_message_len(&self) -> usize {
+        self.max_message_len
+    }
+
+    /// Serves a single client connection over the provided stream.
+    ///
+    /// See [`serve`] for details.
+    pub async fn serve<S, H>(&self, stream: S, handler: &mut H) -> io::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+        H: Handler + ?Sized,
+    {
+        serve_inner(stream, handler, self.max_message_len).await
+    }
+}
+
+async fn serve_inner<S, H>(stream: S, handler: &mut H, max_message_len: usize) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    H: Handler + ?Sized,
+{
+    let mut connection = Connection {
+        stream,
+        buf: BytesMut::new(),
+        out: Responder {
+            buf: BytesMut::new(),
+            status: TransactionStatus::Idle,
+        },
+    };
+
+    let startup = loop {
+        match connection
+            .read(StartupMessage::parse_with_max_len, MAX_STARTUP_PACKET_LEN)
+            .await?
+        {
+            Some(StartupMessage::SslRequest) | Some(StartupMessage::GssEncRequest) => {
+                connection.stream.write_all(b"N").await?;
+                connection.stream.flush().await?;
+            }
+            Some(StartupMessage::Startup(body)) => {
+                let parameters = body
+                    .parameters()
+                    .map(|(name, value)| Ok((name.to_string(), value.to_string())))
+                    .collect()?;
+                break Startup { parameters };
+            }
+            Some(StartupMessage::CancelRequest(body)) => {
+                handler
+                    .on_cancel(body.process_id(), body.secret_key())
+                    .await;
+                return Ok(());
+            }
+            Some(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "unsupported startup message",
+                ))
+            }
+            None => return Ok(()),
+        }
+    };
+
+    let result = match handler.on_startup(&startup).await {
+        Ok(auth) => connection.authenticate(&startup, &auth).await?,
+        Err(e) => Err(e),
+    };
+    let result = match result {
+        Ok(()) => {
+            backend::authentication_ok(&mut connection.out.buf);
+            handler.on_authenticated(&mut connection.out).await
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        ErrorResponse {
+            severity: "FATAL".to_string(),
+            ..e
+        }
+        .write(&mut connection.out.buf)?;
+        return connection.flush().await;
+    }
+    connection.ready_for_query();
+    connection.flush().await?;
+
+    let mut skipping = false;
+    while let Some(message) = connection
+        .read(Message::parse_with_max_len, max_message_len)
+        .await?
+    {
+        if skipping {
+            if let Message::Sync = message {
+                skipping = false;
+                connection.ready_for_query();
+                connection.flush().await?;
+            }
+            continue;
+        }
+
+        let out = &mut connection.out;
+        let result = match message {
+            Message::Query(body) => {
+                let query = body.query()?;
+                let result = if query.trim().is_empty() {
+                    out.empty_query_response();
+                    Ok(())
+                } else {
+                    handler.on_query(query, out).await
+                };
+                if let Err(e) = result {
+                    out.error(&e)?;
+                    if e.is_fatal() {
+                        return connection.flush().await;
+                    }
+                }
+                connection.ready_for_query();
+                Ok(())
+            }
+            Message::Parse(body) => handler
+                .on_parse(body.name()?, body.query()?, body.parameter_types())
+                .await
+                .map(|()| backend::parse_complete(&mut out.buf)),
+            Message::Bind(body) => {
+                let bind = Bind {
+                    portal: body.portal()?,
+                    statement: body.statement()?,
+                    param_formats: body.formats(),
+                    params: body.values(),
+                    result_formats: body.result_formats(),
+                };
+                handler
+                    .on_bind(&bind)
+                    .await
+                    .map(|()| backend::bind_complete(&mut out.buf))
+            }
+            Message::Describe(body) => match body.variant() {
+                b'S' => handler.on_describe_statement(body.name()?, out).await,
+                _ => handler.on_describe_portal(body.name()?, out).await,
+            },
+            Message::Execute(body) => {
+                handler
+                    .on_execute(body.portal()?, body.max_rows(), out)
+                    .await
+            }
+            Message::Close(body) => {
+                let result = match body.variant() {
+                    b'S' => handler.on_close_statement(body.name()?).await,
+                    _ => handler.on_close_portal(body.name()?).await,
+                };
+                result.map(|()| backend::close_complete(&mut out.buf))
+            }
+            Message::Sync => {
+                connection.ready_for_query();
+                Ok(())
+            }
+            Message::Flush => Ok(()),
+            Message::Terminate => break,
+            Message::FunctionCall(_) => Err(unsupported("the function call protocol")),
+            _ => Err(unsupported("COPY")),
+        };
+
+        if let Err(e) = result {
+            connection.out.error(&e)?;
+            if e.is_fatal() {
+                return connection.flush().await;
+            }
+            skipping = true;
+        }
+        if !connection.out.buf.is_empty() {
+            connection.flush().await?;
+        }
+    }
+
+    Ok(())
+}
+
+struct Connection<S> {
+    stream: S,
+    buf: BytesMut,
+    out: Responder,
+}
+
+impl<S> Connection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // Runs the authentication exchange, returning an error to report to the client if it fails.
+    async fn authenticate(
+        &mut self,
+        startup: &Startup,
+        auth: &Auth,
+    ) -> io::Result<Result<(), ErrorResponse>> {
+        let user = startup.user().unwrap_or("");
+        let authenticated = match auth {
+            Auth::Trust => true,
+            Auth::Cleartext(password) => {
+                backend::authentication_cleartext_password(&mut self.out.buf);
+                self.flush().await?;
+                let body = self.read_password().await?;
+                constant_time_eq(body.password()?, password.as_bytes())
+            }
+            Auth::Md5(password) => {
+                let mut salt = [0; 4];
+                crypto::default_provider().fill_random(&mut salt);
+                backend::authentication_md5_password(salt, &mut self.out.buf);
+                self.flush().await?;
+                let body = self.read_password().await?;
+                let hash = md5_hash(user.as_bytes(), password.as_bytes(), salt);
+                constant_time_eq(body.password()?, hash.as_bytes())
+            }
+            Auth::ScramSha256(password) => {
+                self.authenticate_scram(ScramSha256Server::new(password.as_bytes()))
+                    .await?
+            }
+            Auth::ScramSha256Verifier(verifier) => match ScramSha256Server::from_verifier(verifier)
+            {
+                Ok(scram) => self.authenticate_scram(scram).await?,
+                Err(_) => false,
+            },
+        };
+
+        if authenticated {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(ErrorResponse::fatal(
+                INVALID_PASSWORD,
+                &format!("password authentication failed for user \"{}\"", user),
+            )))
+        }
+    }
+
+    async fn authenticate_scram(&mut self, mut scram: ScramSha256Server) -> io::Result<bool> {
+        backend::authentication_sasl([SCRAM_SHA_256], &mut self.out.buf)?;
+        self.flush().await?;
+        let body = self.read_password().await?;
+        let initial = body.sasl_initial_response()?;
+        let data = match initial.data() {
+            Some(data) if initial.mechanism() == SCRAM_SHA_256 => data,
+            _ => return Ok(false),
+        };
+        if scram.update(data).is_err() {
+            return Ok(false);
+        }
+
+        backend::authentication_sasl_continue(scram.message(), &mut self.out.buf)?;
+        self.flush().await?;
+        let body = self.read_password().await?;
+        if scram.finish(body.data()).is_err() {
+            return Ok(false);
+        }
+
+        backend::authentication_sasl_final(scram.message(), &mut self.out.buf)?;
+        Ok(true)
+    }
+
+    fn ready_for_query(&mut self) {
+        backend::ready_for_query(self.out.status.byte(), &mut self.out.buf);
+    }
+
+    async fn read<T>(
+        &mut self,
+        parse: fn(&mut BytesMut, usize) -> io::Result<Option<T>>,
+        max_len: usize,
+    ) -> io::Result<Option<T>> {
+        loop {
+            if let Some(message) = parse(&mut self.buf, max_len)? {
+                return Ok(Some(message));
+            }
+            if self.stream.read_buf(&mut self.buf).await? == 0 {
+                if self.buf.is_empty() {
+                    return Ok(None);
+                }
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "unexpected EOF",
+                ));
+            }
+        }
+    }
+
+    async fn read_password(&mut self) -> io::Result<PasswordMessageBody> {
+        match self
+            .read(Message::parse_with_max_len, MAX_AUTH_MESSAGE_LEN)
+            .await?
+        {
+            Some(Message::PasswordMessage(body)) => Ok(body),
+            Some(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "expected a password message",
+            )),
+            None => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "unexpected EOF",
+            )),
+        }
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        self.stream.write_all(&self.out.buf).await?;
+        self.out.buf.clear();
+        self.stream.flush().await
+    }
+}
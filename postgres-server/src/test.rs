@@ -0,0 +1,211 @@
+use super::*;
+use postgres_protocol::message::backend::Message as BackendMessage;
+use postgres_protocol::message::frontend;
+use tokio::io::DuplexStream;
+
+struct Echo;
+
+#[async_trait]
+impl Handler for Echo {
+    async fn on_startup(&mut self, startup: &Startup) -> Result<Auth, ErrorResponse> {
+        assert_eq!(startup.user(), Some("ferris"));
+        assert_eq!(startup.database(), Some("ferris"));
+        Ok(Auth::Cleartext("password".to_string()))
+    }
+
+    async fn on_authenticated(&mut self, out: &mut Responder) -> Result<(), ErrorResponse> {
+        out.parameter_status("server_version", "16.0")?;
+        Ok(())
+    }
+
+    async fn on_query(&mut self, query: &str, out: &mut Responder) -> Result<(), ErrorResponse> {
+        if query == "fail" {
+            return Err(ErrorResponse::new("42601", "syntax error"));
+        }
+        out.row_description([Field::new("query", 0, 0, 25, -1, -1, 0)])?;
+        out.data_row([Some(query.as_bytes())])?;
+        out.command_complete("SELECT 1")?;
+        Ok(())
+    }
+}
+
+async fn send(stream: &mut DuplexStream, f: impl FnOnce(&mut BytesMut)) {
+    let mut buf = BytesMut::new();
+    f(&mut buf);
+    stream.write_all(&buf).await.unwrap();
+}
+
+async fn recv(stream: &mut DuplexStream, buf: &mut BytesMut) -> BackendMessage {
+    loop {
+        if let Some(message) = BackendMessage::parse(buf).unwrap() {
+            return message;
+        }
+        assert_ne!(stream.read_buf(buf).await.unwrap(), 0);
+    }
+}
+
+async fn ready_for_query(stream: &mut DuplexStream, buf: &mut BytesMut) -> u8 {
+    loop {
+        if let BackendMessage::ReadyForQuery(body) = recv(stream, buf).await {
+            return body.status();
+        }
+    }
+}
+
+#[tokio::test]
+async fn simple_query() {
+    let (mut client, server) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move { serve(server, &mut Echo).await });
+    let mut buf = BytesMut::new();
+
+    send(&mut client, |buf| {
+        frontend::startup_message([("user", "ferris")], buf).unwrap()
+    })
+    .await;
+    match recv(&mut client, &mut buf).await {
+        BackendMessage::AuthenticationCleartextPassword => {}
+        _ => panic!("expected a password request"),
+    }
+    send(&mut client, |buf| {
+        frontend::password_message(b"password", buf).unwrap()
+    })
+    .await;
+    match recv(&mut client, &mut buf).await {
+        BackendMessage::AuthenticationOk => {}
+        _ => panic!("expected authentication to succeed"),
+    }
+    match recv(&mut client, &mut buf).await {
+        BackendMessage::ParameterStatus(body) => assert_eq!(body.value().unwrap(), "16.0"),
+        _ => panic!("expected a parameter status"),
+    }
+    assert_eq!(ready_for_query(&mut client, &mut buf).await, b'I');
+
+    send(&mut client, |buf| frontend::query("hello", buf).unwrap()).await;
+    match recv(&mut client, &mut buf).await {
+        BackendMessage::RowDescription(_) => {}
+        _ => panic!("expected a row description"),
+    }
+    match recv(&mut client, &mut buf).await {
+        BackendMessage::DataRow(body) => assert_eq!(body.buffer(), b"\0\0\0\x05hello"),
+        _ => panic!("expected a row"),
+    }
+    match recv(&mut client, &mut buf).await {
+        BackendMessage::CommandComplete(body) => assert_eq!(body.tag().unwrap(), "SELECT 1"),
+        _ => panic!("expected a command complete"),
+    }
+    assert_eq!(ready_for_query(&mut client, &mut buf).await, b'I');
+
+    send(&mut client, |buf| frontend::query("fail", buf).unwrap()).await;
+    match recv(&mut client, &mut buf).await {
+        BackendMessage::ErrorResponse(_) => {}
+        _ => panic!("expected an error"),
+    }
+    assert_eq!(ready_for_query(&mut client, &mut buf).await, b'I');
+
+    send(&mut client, frontend::terminate).await;
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn extended_query_unsupported() {
+    let (mut client, server) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move { serve(server, &mut Echo).await });
+    let mut buf = BytesMut::new();
+
+    send(&mut client, |buf| {
+        frontend::startup_message([("user", "ferris")], buf).unwrap();
+    })
+    .await;
+    recv(&mut client, &mut buf).await;
+    send(&mut client, |buf| {
+        frontend::password_message(b"password", buf).unwrap()
+    })
+    .await;
+    ready_for_query(&mut client, &mut buf).await;
+
+    send(&mut client, |buf| {
+        frontend::parse("", "SELECT 1", [], buf).unwrap();
+        frontend::describe(b'S', "", buf).unwrap();
+        frontend::sync(buf);
+    })
+    .await;
+    match recv(&mut client, &mut buf).await {
+        BackendMessage::ErrorResponse(body) => {
+            let code = body
+                .fields()
+                .find(|f| Ok(f.type_() == b'C'))
+                .unwrap()
+                .unwrap();
+            assert_eq!(code.value_bytes(), b"0A000");
+        }
+        _ => panic!("expected an error"),
+    }
+    match recv(&mut client, &mut buf).await {
+        BackendMessage::ReadyForQuery(_) => {}
+        _ => panic!("expected the describe to be skipped"),
+    }
+
+    drop(client);
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn wrong_password() {
+    let (mut client, server) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move { serve(server, &mut Echo).await });
+    let mut buf = BytesMut::new();
+
+    send(&mut client, |buf| {
+        frontend::startup_message([("user", "ferris")], buf).unwrap();
+    })
+    .await;
+    recv(&mut client, &mut buf).await;
+    send(&mut client, |buf| {
+        frontend::password_message(b"wrong", buf).unwrap()
+    })
+    .await;
+    match recv(&mut client, &mut buf).await {
+        BackendMessage::ErrorResponse(_) => {}
+        _ => panic!("expected an error"),
+    }
+
+    server.await.unwrap().unwrap();
+    assert_eq!(client.read_buf(&mut buf).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn oversized_startup_packet() {
+    let (mut client, server) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move { serve(server, &mut Echo).await });
+
+    client.write_all(b"\x7f\xff\xff\xff").await.unwrap();
+    let err = server.await.unwrap().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[tokio::test]
+async fn oversized_message() {
+    let (mut client, server) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move {
+        Config::new()
+            .max_message_len(100)
+            .serve(server, &mut Echo)
+            .await
+    });
+    let mut buf = BytesMut::new();
+
+    send(&mut client, |buf| {
+        frontend::startup_message([("user", "ferris")], buf).unwrap();
+    })
+    .await;
+    recv(&mut client, &mut buf).await;
+    send(&mut client, |buf| {
+        frontend::password_message(b"password", buf).unwrap()
+    })
+    .await;
+    ready_for_query(&mut client, &mut buf).await;
+
+    client.write_all(b"Q\xff\xff\xff\xff").await.unwrap();
+    let err = server.await.unwrap().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,37 @@
+use bigdecimal_04::num_bigint::{BigInt, Sign};
+use bigdecimal_04::BigDecimal;
+use bytes::BytesMut;
+use std::error::Error;
+
+use crate::numeric::{numeric_from_sql, numeric_to_sql};
+use crate::{FromSql, IsNull, ToSql, Type};
+
+impl<'a> FromSql<'a> for BigDecimal {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<BigDecimal, Box<dyn Error + Sync + Send>> {
+        let numeric = numeric_from_sql(raw)?;
+        let mut digits =
+            BigInt::parse_bytes(numeric.digits.as_bytes(), 10).ok_or("invalid numeric value")?;
+        if numeric.negative {
+            digits = -digits;
+        }
+        Ok(BigDecimal::new(digits, i64::from(numeric.scale)))
+    }
+
+    accepts!(NUMERIC);
+}
+
+impl ToSql for BigDecimal {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let (digits, scale) = self.as_bigint_and_exponent();
+        numeric_to_sql(
+            digits.sign() == Sign::Minus,
+            &digits.magnitude().to_string(),
+            scale,
+            w,
+        )?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(NUMERIC);
+    to_sql_checked!();
+}
@@ -3,9 +3,10 @@ use chrono_04::{
     DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
 };
 use postgres_protocol::types;
+use std::convert::TryFrom;
 use std::error::Error;
 
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{FromSql, IntervalConversionError, IsNull, PgInterval, ToSql, Type};
 
 fn base() -> NaiveDateTime {
     NaiveDate::from_ymd_opt(2000, 1, 1)
@@ -158,3 +159,21 @@ impl ToSql for NaiveTime {
     accepts!(TIME);
     to_sql_checked!();
 }
+
+impl TryFrom<PgInterval> for Duration {
+    type Error = IntervalConversionError;
+
+    fn try_from(interval: PgInterval) -> Result<Duration, IntervalConversionError> {
+        Ok(Duration::microseconds(interval.to_microseconds()?))
+    }
+}
+
+impl TryFrom<Duration> for PgInterval {
+    type Error = IntervalConversionError;
+
+    fn try_from(duration: Duration) -> Result<PgInterval, IntervalConversionError> {
+        let nanoseconds = i128::from(duration.num_seconds()) * 1_000_000_000
+            + i128::from(duration.subsec_nanos());
+        PgInterval::from_nanoseconds(nanoseconds)
+    }
+}
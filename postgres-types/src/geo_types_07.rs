@@ -1,6 +1,6 @@
 use bytes::BytesMut;
 use fallible_iterator::FallibleIterator;
-use geo_types_0_7::{Coord, LineString, Point, Rect};
+use geo_types_0_7::{Coord, Line, LineString, Point, Polygon, Rect};
 use postgres_protocol::types;
 use std::error::Error;
 
@@ -70,3 +70,61 @@ impl ToSql for LineString<f64> {
     accepts!(PATH);
     to_sql_checked!();
 }
+
+impl<'a> FromSql<'a> for Line<f64> {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let lseg = types::lseg_from_sql(raw)?;
+        Ok(Line::new(
+            (lseg.start().x(), lseg.start().y()),
+            (lseg.end().x(), lseg.end().y()),
+        ))
+    }
+
+    accepts!(LSEG);
+}
+
+impl ToSql for Line<f64> {
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::lseg_to_sql(self.start.x, self.start.y, self.end.x, self.end.y, out);
+        Ok(IsNull::No)
+    }
+
+    accepts!(LSEG);
+    to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for Polygon<f64> {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let polygon = types::polygon_from_sql(raw)?;
+        let points = polygon
+            .points()
+            .map(|p| Ok(Coord { x: p.x(), y: p.y() }))
+            .collect()?;
+        // Polygon::new closes the exterior ring
+        Ok(Polygon::new(LineString(points), vec![]))
+    }
+
+    accepts!(POLYGON);
+}
+
+impl ToSql for Polygon<f64> {
+    fn to_sql(&self, _: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        if !self.interiors().is_empty() {
+            return Err("Postgres polygons cannot have interior rings".into());
+        }
+
+        // Postgres polygons are implicitly closed, so the closing point of the ring is dropped
+        let mut points = &self.exterior().0[..];
+        if points.len() > 1 && points.first() == points.last() {
+            points = &points[..points.len() - 1];
+        }
+        if points.is_empty() {
+            return Err("a polygon must have at least one point".into());
+        }
+        types::polygon_to_sql(points.iter().map(|p| (p.x, p.y)), out)?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(POLYGON);
+    to_sql_checked!();
+}
@@ -0,0 +1,243 @@
+//! Geometric types.
+
+use bytes::BytesMut;
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::types;
+use std::error::Error;
+
+use crate::{FromSql, IsNull, ToSql, Type};
+
+/// A Postgres `POINT`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Point {
+    /// The x coordinate.
+    pub x: f64,
+    /// The y coordinate.
+    pub y: f64,
+}
+
+impl Point {
+    /// Creates a point from its coordinates.
+    pub fn new(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+}
+
+impl From<types::Point> for Point {
+    fn from(point: types::Point) -> Point {
+        Point::new(point.x(), point.y())
+    }
+}
+
+impl<'a> FromSql<'a> for Point {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Point, Box<dyn Error + Sync + Send>> {
+        Ok(types::point_from_sql(raw)?.into())
+    }
+
+    accepts!(POINT);
+}
+
+impl ToSql for Point {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::point_to_sql(self.x, self.y, w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(POINT);
+    to_sql_checked!();
+}
+
+/// A Postgres `LINE`, the infinite line of points satisfying `a * x + b * y + c = 0`.
+///
+/// `a` and `b` cannot both be zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Line {
+    /// The `a` coefficient.
+    pub a: f64,
+    /// The `b` coefficient.
+    pub b: f64,
+    /// The `c` coefficient.
+    pub c: f64,
+}
+
+impl<'a> FromSql<'a> for Line {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Line, Box<dyn Error + Sync + Send>> {
+        let line = types::line_from_sql(raw)?;
+        Ok(Line {
+            a: line.a(),
+            b: line.b(),
+            c: line.c(),
+        })
+    }
+
+    accepts!(LINE);
+}
+
+impl ToSql for Line {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::line_to_sql(self.a, self.b, self.c, w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(LINE);
+    to_sql_checked!();
+}
+
+/// A Postgres `LSEG`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineSegment {
+    /// The start of the segment.
+    pub start: Point,
+    /// The end of the segment.
+    pub end: Point,
+}
+
+impl<'a> FromSql<'a> for LineSegment {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<LineSegment, Box<dyn Error + Sync + Send>> {
+        let lseg = types::lseg_from_sql(raw)?;
+        Ok(LineSegment {
+            start: lseg.start().into(),
+            end: lseg.end().into(),
+        })
+    }
+
+    accepts!(LSEG);
+}
+
+impl ToSql for LineSegment {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::lseg_to_sql(self.start.x, self.start.y, self.end.x, self.end.y, w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(LSEG);
+    to_sql_checked!();
+}
+
+/// A Postgres `BOX`, named to avoid conflicting with `std::boxed::Box`.
+///
+/// Postgres reorders the corners of a box so that `high` is the upper right corner and `low` the lower left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PgBox {
+    /// The upper right corner of the box.
+    pub high: Point,
+    /// The lower left corner of the box.
+    pub low: Point,
+}
+
+impl<'a> FromSql<'a> for PgBox {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<PgBox, Box<dyn Error + Sync + Send>> {
+        let pg_box = types::box_from_sql(raw)?;
+        Ok(PgBox {
+            high: pg_box.upper_right().into(),
+            low: pg_box.lower_left().into(),
+        })
+    }
+
+    accepts!(BOX);
+}
+
+impl ToSql for PgBox {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::box_to_sql(self.high.x, self.high.y, self.low.x, self.low.y, w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(BOX);
+    to_sql_checked!();
+}
+
+/// A Postgres `PATH`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    /// Whether the path is closed, connecting its last point back to its first.
+    pub closed: bool,
+    /// The points of the path.
+    pub points: Vec<Point>,
+}
+
+impl<'a> FromSql<'a> for Path {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Path, Box<dyn Error + Sync + Send>> {
+        let path = types::path_from_sql(raw)?;
+        Ok(Path {
+            closed: path.closed(),
+            points: path.points().map(|p| Ok(p.into())).collect()?,
+        })
+    }
+
+    accepts!(PATH);
+}
+
+impl ToSql for Path {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::path_to_sql(self.closed, self.points.iter().map(|p| (p.x, p.y)), w)?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(PATH);
+    to_sql_checked!();
+}
+
+/// A Postgres `POLYGON`.
+///
+/// A polygon is implicitly closed, so its last point should not repeat its first. It must have at least one point.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Polygon {
+    /// The vertices of the polygon.
+    pub points: Vec<Point>,
+}
+
+impl<'a> FromSql<'a> for Polygon {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Polygon, Box<dyn Error + Sync + Send>> {
+        let polygon = types::polygon_from_sql(raw)?;
+        Ok(Polygon {
+            points: polygon.points().map(|p| Ok(p.into())).collect()?,
+        })
+    }
+
+    accepts!(POLYGON);
+}
+
+impl ToSql for Polygon {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        if self.points.is_empty() {
+            return Err("a polygon must have at least one point".into());
+        }
+        types::polygon_to_sql(self.points.iter().map(|p| (p.x, p.y)), w)?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(POLYGON);
+    to_sql_checked!();
+}
+
+/// A Postgres `CIRCLE`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    /// The center of the circle.
+    pub center: Point,
+    /// The radius of the circle, which cannot be negative.
+    pub radius: f64,
+}
+
+impl<'a> FromSql<'a> for Circle {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Circle, Box<dyn Error + Sync + Send>> {
+        let circle = types::circle_from_sql(raw)?;
+        Ok(Circle {
+            center: circle.center().into(),
+            radius: circle.radius(),
+        })
+    }
+
+    accepts!(CIRCLE);
+}
+
+impl ToSql for Circle {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::circle_to_sql(self.center.x, self.center.y, self.radius, w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(CIRCLE);
+    to_sql_checked!();
+}
@@ -0,0 +1,121 @@
+//! The `INTERVAL` type.
+
+use bytes::BytesMut;
+use postgres_protocol::types;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use crate::{FromSql, IsNull, ToSql, Type};
+
+const USEC_PER_DAY: i64 = 86_400_000_000;
+
+/// Postgres `INTERVAL` type.
+///
+/// An interval is stored as separate months, days and microseconds components, because the length of a month in days
+/// and of a day in hours depends on the date the interval is applied to. Postgres does not normalize the components
+/// against each other, so `1 day` and `24 hours` are different values.
+///
+/// Conversions to and from duration types are checked. An interval converts to a duration only if its months
+/// component is zero, with each day taken as 24 hours. A duration converts to an interval only if it has no
+/// sub-microsecond precision, and is stored entirely in the microseconds component.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PgInterval {
+    /// The months component.
+    pub months: i32,
+    /// The days component.
+    pub days: i32,
+    /// The time component, in microseconds.
+    pub microseconds: i64,
+}
+
+impl PgInterval {
+    /// Creates an interval from its components.
+    pub fn new(months: i32, days: i32, microseconds: i64) -> PgInterval {
+        PgInterval {
+            months,
+            days,
+            microseconds,
+        }
+    }
+
+    /// Returns the length of the interval in microseconds, if it has no months component and the length fits in an
+    /// `i64`.
+    pub(crate) fn to_microseconds(self) -> Result<i64, IntervalConversionError> {
+        if self.months != 0 {
+            return Err(IntervalConversionError(()));
+        }
+        i64::from(self.days)
+            .checked_mul(USEC_PER_DAY)
+            .and_then(|days| days.checked_add(self.microseconds))
+            .ok_or(IntervalConversionError(()))
+    }
+
+    /// Creates an interval from a length in nanoseconds, failing if it has sub-microsecond precision.
+    pub(crate) fn from_nanoseconds(
+        nanoseconds: i128,
+    ) -> Result<PgInterval, IntervalConversionError> {
+        if nanoseconds % 1000 != 0 {
+            return Err(IntervalConversionError(()));
+        }
+        let microseconds =
+            i64::try_from(nanoseconds / 1000).map_err(|_| IntervalConversionError(()))?;
+        Ok(PgInterval::new(0, 0, microseconds))
+    }
+}
+
+/// Error converting between a [`PgInterval`] and a duration type.
+#[derive(Debug)]
+pub struct IntervalConversionError(());
+
+impl fmt::Display for IntervalConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("interval cannot be represented in the target type")
+    }
+}
+
+impl Error for IntervalConversionError {}
+
+impl TryFrom<PgInterval> for Duration {
+    type Error = IntervalConversionError;
+
+    fn try_from(interval: PgInterval) -> Result<Duration, IntervalConversionError> {
+        let microseconds =
+            u64::try_from(interval.to_microseconds()?).map_err(|_| IntervalConversionError(()))?;
+        Ok(Duration::from_micros(microseconds))
+    }
+}
+
+impl TryFrom<Duration> for PgInterval {
+    type Error = IntervalConversionError;
+
+    fn try_from(duration: Duration) -> Result<PgInterval, IntervalConversionError> {
+        let nanoseconds =
+            i128::try_from(duration.as_nanos()).map_err(|_| IntervalConversionError(()))?;
+        PgInterval::from_nanoseconds(nanoseconds)
+    }
+}
+
+impl<'a> FromSql<'a> for PgInterval {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<PgInterval, Box<dyn Error + Sync + Send>> {
+        let interval = types::interval_from_sql(raw)?;
+        Ok(PgInterval::new(
+            interval.months(),
+            interval.days(),
+            interval.microseconds(),
+        ))
+    }
+
+    accepts!(INTERVAL);
+}
+
+impl ToSql for PgInterval {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::interval_to_sql(self.microseconds, self.days, self.months, w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(INTERVAL);
+    to_sql_checked!();
+}
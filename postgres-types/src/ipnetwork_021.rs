@@ -0,0 +1,71 @@
+use bytes::BytesMut;
+use ipnetwork_021::{IpNetwork, Ipv4Network, Ipv6Network};
+use postgres_protocol::types;
+use std::error::Error;
+use std::net::IpAddr;
+
+use crate::{FromSql, IsNull, ToSql, Type};
+
+impl<'a> FromSql<'a> for IpNetwork {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<IpNetwork, Box<dyn Error + Sync + Send>> {
+        let inet = types::inet_from_sql(raw)?;
+        Ok(IpNetwork::new(inet.addr(), inet.netmask())?)
+    }
+
+    accepts!(INET, CIDR);
+}
+
+impl ToSql for IpNetwork {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        // CIDR values with bits set to the right of the netmask are rejected by the server
+        types::inet_to_sql(self.ip(), self.prefix(), w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(INET, CIDR);
+    to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for Ipv4Network {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<Ipv4Network, Box<dyn Error + Sync + Send>> {
+        let inet = types::inet_from_sql(raw)?;
+        match inet.addr() {
+            IpAddr::V4(addr) => Ok(Ipv4Network::new(addr, inet.netmask())?),
+            IpAddr::V6(_) => Err("expected an IPv4 network, got an IPv6 network".into()),
+        }
+    }
+
+    accepts!(INET, CIDR);
+}
+
+impl ToSql for Ipv4Network {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::inet_to_sql(IpAddr::V4(self.ip()), self.prefix(), w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(INET, CIDR);
+    to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for Ipv6Network {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<Ipv6Network, Box<dyn Error + Sync + Send>> {
+        let inet = types::inet_from_sql(raw)?;
+        match inet.addr() {
+            IpAddr::V6(addr) => Ok(Ipv6Network::new(addr, inet.netmask())?),
+            IpAddr::V4(_) => Err("expected an IPv6 network, got an IPv4 network".into()),
+        }
+    }
+
+    accepts!(INET, CIDR);
+}
+
+impl ToSql for Ipv6Network {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        types::inet_to_sql(IpAddr::V6(self.ip()), self.prefix(), w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(INET, CIDR);
+    to_sql_checked!();
+}
@@ -95,6 +95,14 @@
 //! }
 //! ```
 //!
+//! Fields may themselves be derived composites, or arrays of them. A field declared in Postgres with a domain type may
+//! use either a derived domain type or the domain's base type in Rust. A struct with a lifetime parameter may borrow
+//! its fields, such as `&'a str` or `Vec<&'a str>`, from the row instead of allocating them; this also applies to
+//! `FromRow`.
+//!
+//! A field marked `#[postgres(default)]` may be missing from the Postgres type, in which case `FromSql` sets it to its
+//! `Default` value. This only applies when deserializing; `ToSql` still requires every field to be present.
+//!
 //! ## Naming
 //!
 //! The derived implementations will enforce exact matches of type, field, and variant names between the Rust and
@@ -181,12 +189,61 @@
 //!    Meh,
 //! }
 //! ```
+//!
+//! ## Rows
+//!
+//! The `ToSqlRow` and `FromSqlRow` derives map the fields of a struct to the columns of a row, in declaration order,
+//! rather than to a single composite value. This is used by the binary copy support in `tokio-postgres`:
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")]
+//! use postgres_types::{FromSqlRow, ToSqlRow};
+//!
+//! # #[cfg(feature = "derive")]
+//! #[derive(Debug, ToSqlRow, FromSqlRow)]
+//! struct Person {
+//!     id: i32,
+//!     name: String,
+//!     email: Option<String>,
+//! }
+//! ```
+//!
+//! The `FromRow` derive instead matches fields to columns by name, which can be adjusted with the
+//! `#[postgres(name = "...")]` and `#[postgres(rename_all = "...")]` attributes. A field marked
+//! `#[postgres(default)]` is set to its `Default` value if the column is missing from the row, and a field marked
+//! `#[postgres(flatten)]` is itself deserialized from the same row via `FromRow`:
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")]
+//! use postgres_types::FromRow;
+//!
+//! # #[cfg(feature = "derive")]
+//! #[derive(Debug, FromRow)]
+//! struct Address {
+//!     street: String,
+//!     city: String,
+//! }
+//!
+//! # #[cfg(feature = "derive")]
+//! #[derive(Debug, FromRow)]
+//! #[postgres(rename_all = "camelCase")]
+//! struct Person {
+//!     #[postgres(name = "person_id")]
+//!     id: i32,
+//!     display_name: String, // displayName
+//!     #[postgres(default)]
+//!     nicknames: Vec<String>,
+//!     #[postgres(flatten)]
+//!     address: Address,
+//! }
+//! ```
 #![warn(clippy::all, rust_2018_idioms, missing_docs)]
 use fallible_iterator::FallibleIterator;
 use postgres_protocol::types::{self, ArrayDimension};
 use std::any::type_name;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
 use std::hash::BuildHasher;
@@ -195,7 +252,7 @@ use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[cfg(feature = "derive")]
-pub use postgres_derive::{FromSql, ToSql};
+pub use postgres_derive::{FromRow, FromSql, FromSqlRow, ToSql, ToSqlRow};
 
 #[cfg(feature = "with-serde_json-1")]
 pub use crate::serde_json_1::Json;
@@ -204,10 +261,15 @@ use crate::type_gen::{Inner, Other};
 #[doc(inline)]
 pub use postgres_protocol::Oid;
 
+pub use crate::geometric::{Circle, Line, LineSegment, Path, PgBox, Point, Polygon};
+pub use crate::interval::{IntervalConversionError, PgInterval};
 #[doc(inline)]
 pub use pg_lsn::PgLsn;
 
+pub use crate::range::{Multirange, Range, RangeBound};
+
 pub use crate::special::{Date, Timestamp};
+pub use crate::text_search::{TsLexeme, TsPosition, TsQuery, TsQueryNode, TsVector, TsWeight};
 use bytes::BytesMut;
 
 // Number of seconds from 1970-01-01 to 2000-01-01
@@ -262,6 +324,8 @@ where
     v.to_sql(ty, out)
 }
 
+#[cfg(feature = "with-bigdecimal-0_4")]
+mod bigdecimal_04;
 #[cfg(feature = "with-bit-vec-0_6")]
 mod bit_vec_06;
 #[cfg(feature = "with-chrono-0_4")]
@@ -278,10 +342,16 @@ mod eui48_1;
 mod geo_types_06;
 #[cfg(feature = "with-geo-types-0_7")]
 mod geo_types_07;
+#[cfg(feature = "with-ipnetwork-0_21")]
+mod ipnetwork_021;
 #[cfg(feature = "with-jiff-0_1")]
 mod jiff_01;
 #[cfg(feature = "with-jiff-0_2")]
 mod jiff_02;
+#[cfg(feature = "with-macaddr-1")]
+mod macaddr_1;
+#[cfg(feature = "with-rust_decimal-1")]
+mod rust_decimal_1;
 #[cfg(feature = "with-serde_json-1")]
 mod serde_json_1;
 #[cfg(feature = "with-smol_str-01")]
@@ -299,10 +369,17 @@ mod uuid_1;
 #[cfg(feature = "with-time-0_2")]
 extern crate time_02 as time;
 
+mod geometric;
+mod interval;
+#[cfg(any(feature = "with-bigdecimal-0_4", feature = "with-rust_decimal-1"))]
+mod numeric;
 mod pg_lsn;
 #[doc(hidden)]
 pub mod private;
+mod range;
+mod record;
 mod special;
+mod text_search;
 mod type_gen;
 
 /// A Postgres type.
@@ -478,6 +555,16 @@ impl WrongType {
 /// | `HashMap<String, Option<String>>` | HSTORE                                        |
 /// | `SystemTime`                      | TIMESTAMP, TIMESTAMP WITH TIME ZONE           |
 /// | `IpAddr`                          | INET                                          |
+/// | `PgInterval`                      | INTERVAL                                      |
+/// | `TsVector`                        | TSVECTOR                                      |
+/// | `TsQuery`                         | TSQUERY                                       |
+/// | `Point`                           | POINT                                         |
+/// | `Line`                            | LINE                                          |
+/// | `LineSegment`                     | LSEG                                          |
+/// | `PgBox`                           | BOX                                           |
+/// | `Path`                            | PATH                                          |
+/// | `Polygon`                         | POLYGON                                       |
+/// | `Circle`                          | CIRCLE                                        |
 ///
 /// In addition, some implementations are provided for types in third party
 /// crates. These are disabled by default; to opt into one of these
@@ -507,12 +594,21 @@ impl WrongType {
 /// | `geo_types::Point<f64>`         | POINT                               |
 /// | `geo_types::Rect<f64>`          | BOX                                 |
 /// | `geo_types::LineString<f64>`    | PATH                                |
+/// | `geo_types::Line<f64>`          | LSEG                                |
+/// | `geo_types::Polygon<f64>`       | POLYGON                             |
 /// | `serde_json::Value`             | JSON, JSONB                         |
+/// | `&RawValue`/`Box<RawValue>`     | JSON, JSONB                         |
 /// | `uuid::Uuid`                    | UUID                                |
 /// | `bit_vec::BitVec`               | BIT, VARBIT                         |
+/// | `rust_decimal::Decimal`         | NUMERIC                             |
+/// | `bigdecimal::BigDecimal`        | NUMERIC                             |
 /// | `eui48::MacAddress`             | MACADDR                             |
 /// | `cidr::InetCidr`                | CIDR                                |
 /// | `cidr::InetAddr`                | INET                                |
+/// | `ipnetwork::IpNetwork`          | INET, CIDR                          |
+/// | `macaddr::MacAddr6`             | MACADDR, MACADDR8                   |
+/// | `macaddr::MacAddr8`             | MACADDR8                            |
+/// | `macaddr::MacAddr`              | MACADDR, MACADDR8                   |
 /// | `smol_str::SmolStr`             | VARCHAR, CHAR(n), TEXT, CITEXT,     |
 /// |                                 | NAME, UNKNOWN, LTREE, LQUERY,       |
 /// |                                 | LTXTQUERY                           |
@@ -530,6 +626,19 @@ impl WrongType {
 ///
 /// **Note:** the impl for arrays only exist when the Cargo feature `array-impls`
 /// is enabled.
+///
+/// # Ranges
+///
+/// `FromSql` is implemented for `Range<T>` and `Multirange<T>` where `T`
+/// implements `FromSql`, and corresponds to Postgres range and multirange
+/// types over elements of `T`'s type.
+///
+/// # Records
+///
+/// `FromSql` is implemented for tuples of up to 12 elements which each
+/// implement `FromSql`, and corresponds to anonymous `RECORD` values, such as
+/// those produced by `SELECT (1, 'foo')`, as well as composite types with the
+/// same number of fields. The fields of a `RECORD` must have built-in types.
 pub trait FromSql<'a>: Sized {
     /// Creates a new value of this type from a buffer of data of the specified
     /// Postgres `Type` in its binary format.
@@ -770,6 +879,11 @@ where
 impl<'a> FromSql<'a> for SystemTime {
     fn from_sql(_: &Type, raw: &'a [u8]) -> Result<SystemTime, Box<dyn Error + Sync + Send>> {
         let time = types::timestamp_from_sql(raw)?;
+        // infinite timestamps can only be represented through the `Timestamp` wrapper
+        if time == i64::MAX || time == i64::MIN {
+            return Err("value too large to decode".into());
+        }
+
         let epoch = UNIX_EPOCH + Duration::from_secs(TIME_SEC_CONVERSION);
 
         let negative = time < 0;
@@ -780,12 +894,12 @@ impl<'a> FromSql<'a> for SystemTime {
         let offset = Duration::new(secs, nsec as u32);
 
         let time = if negative {
-            epoch - offset
+            epoch.checked_sub(offset)
         } else {
-            epoch + offset
+            epoch.checked_add(offset)
         };
 
-        Ok(time)
+        time.ok_or_else(|| "value too large to decode".into())
     }
 
     accepts!(TIMESTAMP, TIMESTAMPTZ);
@@ -831,6 +945,16 @@ pub enum IsNull {
 /// | `HashMap<String, Option<String>>` | HSTORE                               |
 /// | `SystemTime`                      | TIMESTAMP, TIMESTAMP WITH TIME ZONE  |
 /// | `IpAddr`                          | INET                                 |
+/// | `PgInterval`                      | INTERVAL                             |
+/// | `TsVector`                        | TSVECTOR                             |
+/// | `TsQuery`                         | TSQUERY                              |
+/// | `Point`                           | POINT                                |
+/// | `Line`                            | LINE                                 |
+/// | `LineSegment`                     | LSEG                                 |
+/// | `PgBox`                           | BOX                                  |
+/// | `Path`                            | PATH                                 |
+/// | `Polygon`                         | POLYGON                              |
+/// | `Circle`                          | CIRCLE                               |
 ///
 /// In addition, some implementations are provided for types in third party
 /// crates. These are disabled by default; to opt into one of these
@@ -856,10 +980,19 @@ pub enum IsNull {
 /// | `geo_types::Point<f64>`         | POINT                               |
 /// | `geo_types::Rect<f64>`          | BOX                                 |
 /// | `geo_types::LineString<f64>`    | PATH                                |
+/// | `geo_types::Line<f64>`          | LSEG                                |
+/// | `geo_types::Polygon<f64>`       | POLYGON                             |
 /// | `serde_json::Value`             | JSON, JSONB                         |
+/// | `&RawValue`/`Box<RawValue>`     | JSON, JSONB                         |
 /// | `uuid::Uuid`                    | UUID                                |
 /// | `bit_vec::BitVec`               | BIT, VARBIT                         |
+/// | `rust_decimal::Decimal`         | NUMERIC                             |
+/// | `bigdecimal::BigDecimal`        | NUMERIC                             |
 /// | `eui48::MacAddress`             | MACADDR                             |
+/// | `ipnetwork::IpNetwork`          | INET, CIDR                          |
+/// | `macaddr::MacAddr6`             | MACADDR, MACADDR8                   |
+/// | `macaddr::MacAddr8`             | MACADDR8                            |
+/// | `macaddr::MacAddr`              | MACADDR, MACADDR8                   |
 ///
 /// # Nullability
 ///
@@ -875,6 +1008,12 @@ pub enum IsNull {
 ///
 /// **Note:** the impl for arrays only exist when the Cargo feature `array-impls`
 /// is enabled.
+///
+/// # Ranges
+///
+/// `ToSql` is implemented for `Range<T>` and `Multirange<T>` where `T`
+/// implements `ToSql`, and corresponds to Postgres range and multirange
+/// types over elements of `T`'s type.
 pub trait ToSql: fmt::Debug {
     /// Converts the value of `self` into the binary format of the specified
     /// Postgres `Type`, appending it to `out`.
@@ -1203,13 +1342,16 @@ impl ToSql for SystemTime {
     fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
         let epoch = UNIX_EPOCH + Duration::from_secs(TIME_SEC_CONVERSION);
 
-        let to_usec =
-            |d: Duration| d.as_secs() * USEC_PER_SEC + u64::from(d.subsec_nanos()) / NSEC_PER_USEC;
+        let to_usec = |d: Duration| i64::try_from(d.as_micros()).ok();
 
         let time = match self.duration_since(epoch) {
-            Ok(duration) => to_usec(duration) as i64,
-            Err(e) => -(to_usec(e.duration()) as i64),
+            Ok(duration) => to_usec(duration),
+            Err(e) => to_usec(e.duration()).map(|usec| -usec),
         };
+        // the extreme values are reserved for infinity, which is written with the `Timestamp` wrapper
+        let time = time
+            .filter(|time| *time != i64::MAX && *time != i64::MIN)
+            .ok_or("value too large to transmit")?;
 
         types::timestamp_to_sql(time, w);
         Ok(IsNull::No)
@@ -1305,3 +1447,60 @@ where
         self
     }
 }
+
+/// A trait for types which can be converted into a row of Postgres values, one per field.
+///
+/// This can be derived for structs with the `derive` Cargo feature.
+pub trait ToSqlRow {
+    /// Returns the values making up the row, in column order.
+    fn to_sql_row(&self) -> Vec<&(dyn ToSql + Sync)>;
+}
+
+/// A row of Postgres values which can be deserialized by column index.
+pub trait SqlRow {
+    /// The error returned when a value cannot be deserialized.
+    type Error;
+
+    /// Deserializes the value of the column at the provided index.
+    fn try_get<'a, T>(&'a self, idx: usize) -> Result<T, Self::Error>
+    where
+        T: FromSql<'a>;
+}
+
+/// A row of Postgres values which can also be deserialized by column name.
+pub trait NamedSqlRow: SqlRow {
+    /// Determines if the row contains a column with the provided name.
+    fn contains(&self, name: &str) -> bool;
+
+    /// Deserializes the value of the column with the provided name.
+    fn try_get_named<'a, T>(&'a self, name: &str) -> Result<T, Self::Error>
+    where
+        T: FromSql<'a>;
+}
+
+/// A trait for types which can be created from a row of Postgres values, one per field.
+///
+/// This can be derived for structs with the `derive` Cargo feature.
+pub trait FromSqlRow<'a>: Sized {
+    /// Creates a new value of this type from the columns of a row, in order.
+    fn from_sql_row<R>(row: &'a R) -> Result<Self, R::Error>
+    where
+        R: SqlRow;
+}
+
+/// A trait for types which can be created from a row of Postgres values, matching fields to columns by name.
+///
+/// This can be derived for structs with the `derive` Cargo feature.
+pub trait FromRow<'a>: Sized {
+    /// Creates a new value of this type from the columns of a row.
+    fn from_row<R>(row: &'a R) -> Result<Self, R::Error>
+    where
+        R: NamedSqlRow;
+}
+
+/// A trait for types which can be created from a row of Postgres values without borrowing any data.
+///
+/// This is primarily useful for trait bounds on functions.
+pub trait FromRowOwned: for<'a> FromRow<'a> {}
+
+impl<T> FromRowOwned for T where T: for<'a> FromRow<'a> {}
@@ -0,0 +1,82 @@
+use bytes::BytesMut;
+use macaddr_1::{MacAddr, MacAddr6, MacAddr8};
+use postgres_protocol::types;
+use std::error::Error;
+
+use crate::{FromSql, IsNull, ToSql, Type};
+
+fn macaddr6_to_sql(addr: &MacAddr6, w: &mut BytesMut) {
+    let mut bytes = [0; 6];
+    bytes.copy_from_slice(addr.as_bytes());
+    types::macaddr_to_sql(bytes, w);
+}
+
+fn macaddr8_to_sql(addr: &MacAddr8, w: &mut BytesMut) {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(addr.as_bytes());
+    types::macaddr8_to_sql(bytes, w);
+}
+
+impl<'a> FromSql<'a> for MacAddr6 {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<MacAddr6, Box<dyn Error + Sync + Send>> {
+        Ok(types::macaddr_from_sql(raw)?.into())
+    }
+
+    accepts!(MACADDR);
+}
+
+impl ToSql for MacAddr6 {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        // the server converts 6 byte addresses to the 8 byte EUI-64 form when receiving a MACADDR8
+        macaddr6_to_sql(self, w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(MACADDR, MACADDR8);
+    to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for MacAddr8 {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<MacAddr8, Box<dyn Error + Sync + Send>> {
+        Ok(types::macaddr8_from_sql(raw)?.into())
+    }
+
+    accepts!(MACADDR8);
+}
+
+impl ToSql for MacAddr8 {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        macaddr8_to_sql(self, w);
+        Ok(IsNull::No)
+    }
+
+    accepts!(MACADDR8);
+    to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for MacAddr {
+    fn from_sql(ty: &Type, raw: &[u8]) -> Result<MacAddr, Box<dyn Error + Sync + Send>> {
+        match *ty {
+            Type::MACADDR8 => MacAddr8::from_sql(ty, raw).map(MacAddr::V8),
+            _ => MacAddr6::from_sql(ty, raw).map(MacAddr::V6),
+        }
+    }
+
+    accepts!(MACADDR, MACADDR8);
+}
+
+impl ToSql for MacAddr {
+    fn to_sql(&self, ty: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match (self, ty) {
+            (MacAddr::V6(addr), _) => macaddr6_to_sql(addr, w),
+            (MacAddr::V8(addr), &Type::MACADDR8) => macaddr8_to_sql(addr, w),
+            (MacAddr::V8(_), _) => {
+                return Err("an 8 byte MAC address cannot be stored in a MACADDR".into())
+            }
+        }
+        Ok(IsNull::No)
+    }
+
+    accepts!(MACADDR, MACADDR8);
+    to_sql_checked!();
+}
@@ -0,0 +1,176 @@
+//! Conversions between the binary `NUMERIC` format and decimal digits, shared by the arbitrary precision decimal
+//! integrations.
+
+use bytes::{BufMut, BytesMut};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::Write;
+
+const NUMERIC_POS: u16 = 0x0000;
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+
+// The largest display scale the server accepts in the binary format.
+const NUMERIC_DSCALE_MAX: i64 = 0x3FFF;
+// The most digits a `NUMERIC` may have before the decimal point.
+const NUMERIC_INTEGER_DIGITS_MAX: i128 = 131_072;
+
+/// A finite `NUMERIC` value, equal to `digits * 10^-scale`, negated if `negative` is set.
+pub(crate) struct Numeric {
+    pub negative: bool,
+    /// The decimal digits of the unscaled value, without leading zeros.
+    pub digits: String,
+    pub scale: u16,
+}
+
+fn read_i16(buf: &mut &[u8]) -> Result<i16, Box<dyn Error + Sync + Send>> {
+    if buf.len() < 2 {
+        return Err("invalid buffer size".into());
+    }
+    let value = i16::from_be_bytes([buf[0], buf[1]]);
+    *buf = &buf[2..];
+    Ok(value)
+}
+
+/// Deserializes a `NUMERIC` value, failing for `NaN` and infinite values, which decimal types cannot represent.
+pub(crate) fn numeric_from_sql(mut raw: &[u8]) -> Result<Numeric, Box<dyn Error + Sync + Send>> {
+    let ndigits = read_i16(&mut raw)?;
+    let weight = read_i16(&mut raw)?;
+    let sign = read_i16(&mut raw)? as u16;
+    let dscale = read_i16(&mut raw)?;
+    let groups = (0..ndigits)
+        .map(|_| read_i16(&mut raw))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !raw.is_empty() {
+        return Err("invalid buffer size".into());
+    }
+
+    let negative = match sign {
+        NUMERIC_POS => false,
+        NUMERIC_NEG => true,
+        NUMERIC_NAN => return Err("NaN cannot be represented as a decimal".into()),
+        NUMERIC_PINF | NUMERIC_NINF => {
+            return Err("infinity cannot be represented as a decimal".into())
+        }
+        _ => return Err("invalid numeric sign".into()),
+    };
+    if dscale < 0 || groups.iter().any(|g| !(0..10000).contains(g)) {
+        return Err("invalid numeric value".into());
+    }
+
+    // each group holds 4 digits, and the group at index `i` is scaled by 10000^(weight - i)
+    let group = |exponent: i32| {
+        let idx = i32::from(weight) - exponent;
+        if idx < 0 {
+            0
+        } else {
+            groups.get(idx as usize).copied().unwrap_or(0)
+        }
+    };
+
+    let mut digits = String::new();
+    for exponent in (0..=i32::from(weight).max(0)).rev() {
+        write!(digits, "{:04}", group(exponent)).unwrap();
+    }
+    let fraction_groups = (i32::from(dscale) + 3) / 4;
+    for exponent in 1..=fraction_groups {
+        write!(digits, "{:04}", group(-exponent)).unwrap();
+    }
+    digits.truncate(digits.len() - (fraction_groups * 4 - i32::from(dscale)) as usize);
+
+    let digits = match digits.trim_start_matches('0') {
+        "" => "0".to_string(),
+        digits => digits.to_string(),
+    };
+
+    Ok(Numeric {
+        negative,
+        digits,
+        scale: dscale as u16,
+    })
+}
+
+/// Serializes the value `digits * 10^-scale`, negated if `negative` is set, as a `NUMERIC`.
+///
+/// A negative scale denotes trailing zeros of an integer, which is represented with a display scale of zero.
+pub(crate) fn numeric_to_sql(
+    negative: bool,
+    digits: &str,
+    scale: i64,
+    buf: &mut BytesMut,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    if scale > NUMERIC_DSCALE_MAX {
+        return Err("value has too many digits after the decimal point for NUMERIC".into());
+    }
+
+    let digits = digits.trim_start_matches('0');
+    // checked before any padding is allocated, since the scale of a zero or an integer can be arbitrarily negative
+    if !digits.is_empty() && digits.len() as i128 - i128::from(scale) > NUMERIC_INTEGER_DIGITS_MAX {
+        return Err("value has too many digits before the decimal point for NUMERIC".into());
+    }
+
+    let (integer, fraction) = if digits.is_empty() && scale < 0 {
+        (String::new(), String::new())
+    } else if scale < 0 {
+        let mut integer = digits.to_string();
+        integer.extend((0..scale.unsigned_abs()).map(|_| '0'));
+        (integer, String::new())
+    } else {
+        let scale = scale as usize;
+        if digits.len() > scale {
+            let (integer, fraction) = digits.split_at(digits.len() - scale);
+            (integer.to_string(), fraction.to_string())
+        } else {
+            let mut fraction = "0".repeat(scale - digits.len());
+            fraction.push_str(digits);
+            (String::new(), fraction)
+        }
+    };
+
+    // align both parts to the 4 digit groups on either side of the decimal point
+    let mut padded = "0".repeat((4 - integer.len() % 4) % 4);
+    padded.push_str(&integer);
+    let integer_groups = padded.len() / 4;
+    padded.push_str(&fraction);
+    padded.extend((0..(4 - fraction.len() % 4) % 4).map(|_| '0'));
+
+    let mut groups = padded
+        .as_bytes()
+        .chunks(4)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .fold(0, |acc, b| acc * 10 + i16::from(b - b'0'))
+        })
+        .collect::<Vec<_>>();
+    let mut weight = integer_groups as i64 - 1;
+
+    let leading = groups.iter().take_while(|g| **g == 0).count();
+    groups.drain(..leading);
+    weight -= leading as i64;
+    while groups.last() == Some(&0) {
+        groups.pop();
+    }
+
+    let (weight, sign) = if groups.is_empty() {
+        (0, NUMERIC_POS)
+    } else if negative {
+        (weight, NUMERIC_NEG)
+    } else {
+        (weight, NUMERIC_POS)
+    };
+    let weight = i16::try_from(weight).map_err(|_| "value too large to transmit")?;
+    let ndigits = i16::try_from(groups.len()).map_err(|_| "value too large to transmit")?;
+
+    buf.put_i16(ndigits);
+    buf.put_i16(weight);
+    buf.put_u16(sign);
+    buf.put_i16(scale.max(0) as i16);
+    for group in groups {
+        buf.put_i16(group);
+    }
+
+    Ok(())
+}
@@ -5,18 +5,48 @@ use bytes::BytesMut;
 use postgres_protocol::types;
 use std::error::Error;
 use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
 use std::str::FromStr;
 
 use crate::{FromSql, IsNull, ToSql, Type};
 
 /// Postgres `PG_LSN` type.
-#[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
+///
+/// An LSN is a byte offset into the WAL. Adding or subtracting a number of bytes moves the LSN forwards or backwards,
+/// and subtracting one LSN from another gives the number of bytes between them, as with the server's `pg_lsn`
+/// operators.
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct PgLsn(u64);
 
+impl PgLsn {
+    /// Moves the LSN forwards by `bytes`, returning `None` on overflow.
+    pub fn checked_add(self, bytes: u64) -> Option<PgLsn> {
+        self.0.checked_add(bytes).map(PgLsn)
+    }
+
+    /// Moves the LSN backwards by `bytes`, returning `None` on underflow.
+    pub fn checked_sub(self, bytes: u64) -> Option<PgLsn> {
+        self.0.checked_sub(bytes).map(PgLsn)
+    }
+
+    /// Returns the number of bytes from `earlier` to this LSN, or `None` if `earlier` is after it.
+    pub fn checked_distance_from(self, earlier: PgLsn) -> Option<u64> {
+        self.0.checked_sub(earlier.0)
+    }
+}
+
 /// Error parsing LSN.
 #[derive(Debug)]
 pub struct ParseLsnError(());
 
+impl fmt::Display for ParseLsnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid LSN")
+    }
+}
+
+impl Error for ParseLsnError {}
+
 impl From<u64> for PgLsn {
     fn from(lsn_u64: u64) -> Self {
         PgLsn(lsn_u64)
@@ -37,10 +67,10 @@ impl FromStr for PgLsn {
             return Err(ParseLsnError(()));
         };
         let (hi, lo) = (
-            u64::from_str_radix(split_hi, 16).map_err(|_| ParseLsnError(()))?,
-            u64::from_str_radix(split_lo, 16).map_err(|_| ParseLsnError(()))?,
+            u32::from_str_radix(split_hi, 16).map_err(|_| ParseLsnError(()))?,
+            u32::from_str_radix(split_lo, 16).map_err(|_| ParseLsnError(()))?,
         );
-        Ok(PgLsn((hi << 32) | lo))
+        Ok(PgLsn((u64::from(hi) << 32) | u64::from(lo)))
     }
 }
 
@@ -56,6 +86,50 @@ impl fmt::Debug for PgLsn {
     }
 }
 
+impl Add<u64> for PgLsn {
+    type Output = PgLsn;
+
+    fn add(self, bytes: u64) -> PgLsn {
+        self.checked_add(bytes)
+            .expect("overflow when adding to LSN")
+    }
+}
+
+impl AddAssign<u64> for PgLsn {
+    fn add_assign(&mut self, bytes: u64) {
+        *self = *self + bytes;
+    }
+}
+
+impl Sub<u64> for PgLsn {
+    type Output = PgLsn;
+
+    fn sub(self, bytes: u64) -> PgLsn {
+        self.checked_sub(bytes)
+            .expect("overflow when subtracting from LSN")
+    }
+}
+
+impl SubAssign<u64> for PgLsn {
+    fn sub_assign(&mut self, bytes: u64) {
+        *self = *self - bytes;
+    }
+}
+
+impl Sub for PgLsn {
+    type Output = u64;
+
+    /// Returns the number of bytes between two LSNs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rhs` is after `self`.
+    fn sub(self, rhs: PgLsn) -> u64 {
+        self.checked_distance_from(rhs)
+            .expect("overflow when subtracting LSNs")
+    }
+}
+
 impl<'a> FromSql<'a> for PgLsn {
     fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
         let v = types::lsn_from_sql(raw)?;
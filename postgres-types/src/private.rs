@@ -1,4 +1,4 @@
-use crate::{FromSql, Type};
+use crate::{FromSql, IsNull, Kind, ToSql, Type};
 pub use bytes::BytesMut;
 use std::error::Error;
 
@@ -30,5 +30,34 @@ where
         *buf = tail;
         Some(head)
     };
-    T::from_sql_nullable(type_, value)
+    T::from_sql_nullable(accepted_type(type_, T::accepts).unwrap_or(type_), value)
+}
+
+pub fn write_value<T>(
+    value: &T,
+    type_: &Type,
+    buf: &mut BytesMut,
+) -> Result<IsNull, Box<dyn Error + Sync + Send>>
+where
+    T: ToSql,
+{
+    value.to_sql(accepted_type(type_, T::accepts).unwrap_or(type_), buf)
+}
+
+/// Returns the first type accepted by `accepts` in the chain of domains starting at `type_`.
+///
+/// Fields of composite types may be declared with a domain type, while the Rust field uses the domain's base type.
+pub fn accepted_type<F>(mut type_: &Type, accepts: F) -> Option<&Type>
+where
+    F: Fn(&Type) -> bool,
+{
+    loop {
+        if accepts(type_) {
+            return Some(type_);
+        }
+        match *type_.kind() {
+            Kind::Domain(ref base) => type_ = base,
+            _ => return None,
+        }
+    }
 }
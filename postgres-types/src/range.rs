@@ -0,0 +1,221 @@
+//! Range and multirange types.
+
+use bytes::BytesMut;
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::types;
+use std::error::Error;
+
+use crate::{FromSql, IsNull, Kind, ToSql, Type};
+
+/// One side of a [`Range`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RangeBound<T> {
+    /// An inclusive bound.
+    Inclusive(T),
+    /// An exclusive bound.
+    Exclusive(T),
+    /// No bound.
+    Unbounded,
+}
+
+/// A Postgres range, such as an `INT4RANGE` or a `TSTZRANGE`.
+///
+/// Postgres canonicalizes the bounds of discrete ranges, so a range of integers or dates is always returned with an
+/// inclusive lower bound and an exclusive upper bound: `[1,5]` is read back as `[1,6)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Range<T> {
+    /// An empty range.
+    Empty,
+    /// A nonempty range, with its lower and upper bounds.
+    Nonempty(RangeBound<T>, RangeBound<T>),
+}
+
+impl<'a, T: FromSql<'a>> FromSql<'a> for Range<T> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Range<T>, Box<dyn Error + Sync + Send>> {
+        let member_type = match *ty.kind() {
+            Kind::Range(ref member) => member,
+            _ => panic!("expected range type"),
+        };
+
+        range_from_sql(member_type, types::range_from_sql(raw)?)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty.kind() {
+            Kind::Range(ref member) => T::accepts(member),
+            _ => false,
+        }
+    }
+}
+
+impl<T: ToSql> ToSql for Range<T> {
+    fn to_sql(&self, ty: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let member_type = match *ty.kind() {
+            Kind::Range(ref member) => member,
+            _ => panic!("expected range type"),
+        };
+
+        range_to_sql(self, member_type, w)?;
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty.kind() {
+            Kind::Range(ref member) => T::accepts(member),
+            _ => false,
+        }
+    }
+
+    to_sql_checked!();
+}
+
+/// A Postgres multirange, such as an `INT4MULTIRANGE` or a `TSTZMULTIRANGE`.
+///
+/// Multiranges were added in Postgres 14. Postgres stores a multirange as a sorted list of non-overlapping,
+/// non-adjacent, nonempty ranges, so the ranges of a multirange read from the database may differ from those it was
+/// written with: empty ranges are dropped, and overlapping or adjacent ranges are merged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Multirange<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> Multirange<T> {
+    /// Creates a multirange from its ranges.
+    pub fn new(ranges: Vec<Range<T>>) -> Multirange<T> {
+        Multirange { ranges }
+    }
+
+    /// Returns the ranges of the multirange.
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    /// Consumes the multirange, returning its ranges.
+    pub fn into_ranges(self) -> Vec<Range<T>> {
+        self.ranges
+    }
+}
+
+impl<T> Default for Multirange<T> {
+    fn default() -> Multirange<T> {
+        Multirange::new(vec![])
+    }
+}
+
+impl<T> From<Vec<Range<T>>> for Multirange<T> {
+    fn from(ranges: Vec<Range<T>>) -> Multirange<T> {
+        Multirange::new(ranges)
+    }
+}
+
+impl<'a, T: FromSql<'a>> FromSql<'a> for Multirange<T> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Multirange<T>, Box<dyn Error + Sync + Send>> {
+        let member_type = match *ty.kind() {
+            Kind::Multirange(ref member) => member,
+            _ => panic!("expected multirange type"),
+        };
+
+        let ranges = types::multirange_from_sql(raw)?
+            .ranges()
+            .map(|range| range_from_sql(member_type, range))
+            .collect()?;
+        Ok(Multirange::new(ranges))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty.kind() {
+            Kind::Multirange(ref member) => T::accepts(member),
+            _ => false,
+        }
+    }
+}
+
+impl<T: ToSql> ToSql for Multirange<T> {
+    fn to_sql(&self, ty: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let member_type = match *ty.kind() {
+            Kind::Multirange(ref member) => member,
+            _ => panic!("expected multirange type"),
+        };
+
+        types::multirange_to_sql(
+            &self.ranges,
+            |range, w| range_to_sql(range, member_type, w),
+            w,
+        )?;
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty.kind() {
+            Kind::Multirange(ref member) => T::accepts(member),
+            _ => false,
+        }
+    }
+
+    to_sql_checked!();
+}
+
+fn range_from_sql<'a, T: FromSql<'a>>(
+    member_type: &Type,
+    range: types::Range<'a>,
+) -> Result<Range<T>, Box<dyn Error + Sync + Send>> {
+    match range {
+        types::Range::Empty => Ok(Range::Empty),
+        types::Range::Nonempty(lower, upper) => Ok(Range::Nonempty(
+            bound_from_sql(member_type, lower)?,
+            bound_from_sql(member_type, upper)?,
+        )),
+    }
+}
+
+fn bound_from_sql<'a, T: FromSql<'a>>(
+    member_type: &Type,
+    bound: types::RangeBound<Option<&'a [u8]>>,
+) -> Result<RangeBound<T>, Box<dyn Error + Sync + Send>> {
+    match bound {
+        types::RangeBound::Inclusive(value) => Ok(RangeBound::Inclusive(T::from_sql_nullable(
+            member_type,
+            value,
+        )?)),
+        types::RangeBound::Exclusive(value) => Ok(RangeBound::Exclusive(T::from_sql_nullable(
+            member_type,
+            value,
+        )?)),
+        types::RangeBound::Unbounded => Ok(RangeBound::Unbounded),
+    }
+}
+
+fn range_to_sql<T: ToSql>(
+    range: &Range<T>,
+    member_type: &Type,
+    w: &mut BytesMut,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    match range {
+        Range::Empty => {
+            types::empty_range_to_sql(w);
+            Ok(())
+        }
+        Range::Nonempty(lower, upper) => types::range_to_sql(
+            |w| bound_to_sql(lower, member_type, w),
+            |w| bound_to_sql(upper, member_type, w),
+            w,
+        ),
+    }
+}
+
+fn bound_to_sql<T: ToSql>(
+    bound: &RangeBound<T>,
+    member_type: &Type,
+    w: &mut BytesMut,
+) -> Result<types::RangeBound<postgres_protocol::IsNull>, Box<dyn Error + Sync + Send>> {
+    let value_to_sql = |value: &T, w: &mut BytesMut| match value.to_sql(member_type, w)? {
+        IsNull::No => Ok(postgres_protocol::IsNull::No),
+        IsNull::Yes => Ok::<_, Box<dyn Error + Sync + Send>>(postgres_protocol::IsNull::Yes),
+    };
+
+    match bound {
+        RangeBound::Inclusive(value) => Ok(types::RangeBound::Inclusive(value_to_sql(value, w)?)),
+        RangeBound::Exclusive(value) => Ok(types::RangeBound::Exclusive(value_to_sql(value, w)?)),
+        RangeBound::Unbounded => Ok(types::RangeBound::Unbounded),
+    }
+}
@@ -0,0 +1,94 @@
+//! Tuples, read from anonymous records and composite types.
+
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::types::{self, RecordFields};
+use std::error::Error;
+
+use crate::{Field, FromSql, Kind, Type, WrongType};
+
+macro_rules! tuple_from_sql {
+    ($len:expr; $($t:ident $idx:tt),+) => {
+        impl<'a, $($t),+> FromSql<'a> for ($($t,)+)
+        where
+            $($t: FromSql<'a>,)+
+        {
+            fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+                let members = match *ty.kind() {
+                    Kind::Composite(ref fields) => Some(&fields[..]),
+                    _ => None,
+                };
+
+                let mut fields = types::record_from_sql(raw)?;
+                if fields.size_hint().0 != $len {
+                    return Err(format!(
+                        "invalid field count: {} vs {}",
+                        fields.size_hint().0,
+                        $len
+                    )
+                    .into());
+                }
+
+                let value = ($(read_field::<$t>(&mut fields, members.map(|m| &m[$idx]))?,)+);
+                if fields.next()?.is_some() {
+                    return Err("invalid field count".into());
+                }
+                Ok(value)
+            }
+
+            fn accepts(ty: &Type) -> bool {
+                match *ty.kind() {
+                    Kind::Composite(ref fields) => {
+                        fields.len() == $len $(&& $t::accepts(fields[$idx].type_()))+
+                    }
+                    _ => *ty == Type::RECORD,
+                }
+            }
+        }
+    };
+}
+
+tuple_from_sql!(1; T0 0);
+tuple_from_sql!(2; T0 0, T1 1);
+tuple_from_sql!(3; T0 0, T1 1, T2 2);
+tuple_from_sql!(4; T0 0, T1 1, T2 2, T3 3);
+tuple_from_sql!(5; T0 0, T1 1, T2 2, T3 3, T4 4);
+tuple_from_sql!(6; T0 0, T1 1, T2 2, T3 3, T4 4, T5 5);
+tuple_from_sql!(7; T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6);
+tuple_from_sql!(8; T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7);
+tuple_from_sql!(9; T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8);
+tuple_from_sql!(10; T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9);
+tuple_from_sql!(11; T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10);
+tuple_from_sql!(12; T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11);
+
+// Composite types provide the types of their fields, but a `RECORD` only carries their OIDs, which must then refer to
+// built-in types.
+fn read_field<'a, T>(
+    fields: &mut RecordFields<'a>,
+    member: Option<&Field>,
+) -> Result<T, Box<dyn Error + Sync + Send>>
+where
+    T: FromSql<'a>,
+{
+    let field = fields.next()?.ok_or("invalid field count")?;
+
+    let ty = match member {
+        Some(member) => {
+            if field.type_oid() != member.type_().oid() {
+                return Err("unexpected OID".into());
+            }
+            member.type_().clone()
+        }
+        None => Type::from_oid(field.type_oid()).ok_or_else(|| {
+            format!(
+                "record field has non-built-in type with OID {}",
+                field.type_oid()
+            )
+        })?,
+    };
+
+    if !T::accepts(&ty) {
+        return Err(Box::new(WrongType::new::<T>(ty)));
+    }
+
+    T::from_sql_nullable(&ty, field.value())
+}
@@ -0,0 +1,51 @@
+use bytes::BytesMut;
+use rust_decimal_1::Decimal;
+use std::error::Error;
+
+use crate::numeric::{numeric_from_sql, numeric_to_sql};
+use crate::{FromSql, IsNull, ToSql, Type};
+
+impl<'a> FromSql<'a> for Decimal {
+    fn from_sql(_: &Type, raw: &[u8]) -> Result<Decimal, Box<dyn Error + Sync + Send>> {
+        let numeric = numeric_from_sql(raw)?;
+
+        // trailing zeros beyond the maximum scale can be dropped without losing precision
+        let mut digits = numeric.digits.as_str();
+        let mut scale = u32::from(numeric.scale);
+        while scale > Decimal::MAX_SCALE && digits.ends_with('0') {
+            digits = &digits[..digits.len() - 1];
+            scale -= 1;
+        }
+        if scale > Decimal::MAX_SCALE {
+            return Err("value has too many digits after the decimal point for Decimal".into());
+        }
+
+        let mantissa = digits
+            .parse::<i128>()
+            .map_err(|_| "value too large for Decimal")?;
+        let mantissa = if numeric.negative {
+            -mantissa
+        } else {
+            mantissa
+        };
+        Ok(Decimal::try_from_i128_with_scale(mantissa, scale)?)
+    }
+
+    accepts!(NUMERIC);
+}
+
+impl ToSql for Decimal {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let mantissa = self.mantissa();
+        numeric_to_sql(
+            mantissa < 0,
+            &mantissa.unsigned_abs().to_string(),
+            i64::from(self.scale()),
+            w,
+        )?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(NUMERIC);
+    to_sql_checked!();
+}
@@ -1,6 +1,7 @@
 use crate::{FromSql, IsNull, ToSql, Type};
 use bytes::{BufMut, BytesMut};
 use serde_1::{Deserialize, Serialize};
+use serde_json_1::value::RawValue;
 use serde_json_1::Value;
 use std::error::Error;
 use std::fmt::Debug;
@@ -71,3 +72,50 @@ impl ToSql for Value {
     accepts!(JSON, JSONB);
     to_sql_checked!();
 }
+
+impl<'a> FromSql<'a> for &'a RawValue {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<&'a RawValue, Box<dyn Error + Sync + Send>> {
+        Json::<&'a RawValue>::from_sql(ty, raw).map(|json| json.0)
+    }
+
+    accepts!(JSON, JSONB);
+}
+
+impl<'a> FromSql<'a> for Box<RawValue> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Box<RawValue>, Box<dyn Error + Sync + Send>> {
+        Json::<Box<RawValue>>::from_sql(ty, raw).map(|json| json.0)
+    }
+
+    accepts!(JSON, JSONB);
+}
+
+impl ToSql for &RawValue {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        if *ty == Type::JSONB {
+            out.put_u8(1);
+        }
+        // the text of a raw value is already valid JSON, so it can be written without serializing
+        out.put_slice(self.get().as_bytes());
+        Ok(IsNull::No)
+    }
+
+    accepts!(JSON, JSONB);
+    to_sql_checked!();
+}
+
+impl ToSql for Box<RawValue> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        <&RawValue as ToSql>::to_sql(&&**self, ty, out)
+    }
+
+    accepts!(JSON, JSONB);
+    to_sql_checked!();
+}
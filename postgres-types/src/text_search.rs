@@ -0,0 +1,320 @@
+//! Full text search types.
+
+use bytes::BytesMut;
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::types::{self, TsQueryItem};
+use std::error::Error;
+
+use crate::{FromSql, IsNull, ToSql, Type};
+
+// The largest position the server stores for a lexeme.
+const MAX_POSITION: u16 = 0x3FFF;
+
+/// The weight of a lexeme, from the highest, `A`, to the lowest and default, `D`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TsWeight {
+    /// The `A` weight.
+    A,
+    /// The `B` weight.
+    B,
+    /// The `C` weight.
+    C,
+    /// The `D` weight.
+    D,
+}
+
+impl TsWeight {
+    fn from_position(position: u16) -> TsWeight {
+        match position >> 14 {
+            3 => TsWeight::A,
+            2 => TsWeight::B,
+            1 => TsWeight::C,
+            _ => TsWeight::D,
+        }
+    }
+
+    fn to_position(self) -> u16 {
+        match self {
+            TsWeight::A => 3 << 14,
+            TsWeight::B => 2 << 14,
+            TsWeight::C => 1 << 14,
+            TsWeight::D => 0,
+        }
+    }
+
+    fn mask(self) -> u8 {
+        match self {
+            TsWeight::A => 0b1000,
+            TsWeight::B => 0b0100,
+            TsWeight::C => 0b0010,
+            TsWeight::D => 0b0001,
+        }
+    }
+}
+
+/// A position of a lexeme in a [`TsVector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TsPosition {
+    /// The position, between 1 and 16383.
+    pub position: u16,
+    /// The weight of the lexeme at this position.
+    pub weight: TsWeight,
+}
+
+/// A lexeme of a [`TsVector`], along with the positions it occurs at.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TsLexeme {
+    /// The lexeme.
+    pub lexeme: String,
+    /// The positions of the lexeme, which may be empty.
+    pub positions: Vec<TsPosition>,
+}
+
+/// A Postgres `TSVECTOR`.
+///
+/// Postgres sorts and deduplicates the lexemes of a vector and their positions, so a vector read from the database may
+/// differ from the one it was written with.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TsVector {
+    lexemes: Vec<TsLexeme>,
+}
+
+impl TsVector {
+    /// Creates a vector from its lexemes.
+    pub fn new(lexemes: Vec<TsLexeme>) -> TsVector {
+        TsVector { lexemes }
+    }
+
+    /// Returns the lexemes of the vector.
+    pub fn lexemes(&self) -> &[TsLexeme] {
+        &self.lexemes
+    }
+
+    /// Consumes the vector, returning its lexemes.
+    pub fn into_lexemes(self) -> Vec<TsLexeme> {
+        self.lexemes
+    }
+}
+
+impl<'a> FromSql<'a> for TsVector {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<TsVector, Box<dyn Error + Sync + Send>> {
+        let lexemes = types::tsvector_from_sql(raw)?
+            .map(|entry| {
+                let positions = entry
+                    .positions()
+                    .map(|position| TsPosition {
+                        position: position & MAX_POSITION,
+                        weight: TsWeight::from_position(position),
+                    })
+                    .collect();
+                Ok(TsLexeme {
+                    lexeme: entry.lexeme().to_string(),
+                    positions,
+                })
+            })
+            .collect()?;
+        Ok(TsVector::new(lexemes))
+    }
+
+    accepts!(TS_VECTOR);
+}
+
+impl ToSql for TsVector {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        for lexeme in &self.lexemes {
+            if lexeme
+                .positions
+                .iter()
+                .any(|p| p.position == 0 || p.position > MAX_POSITION)
+            {
+                return Err(format!(
+                    "lexeme position out of range (must be between 1 and {})",
+                    MAX_POSITION
+                )
+                .into());
+            }
+        }
+
+        types::tsvector_to_sql(
+            self.lexemes.iter().map(|lexeme| {
+                let positions = lexeme
+                    .positions
+                    .iter()
+                    .map(|p| p.position | p.weight.to_position());
+                (&*lexeme.lexeme, positions)
+            }),
+            w,
+        )?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(TS_VECTOR);
+
+    to_sql_checked!();
+}
+
+/// A node of a [`TsQuery`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TsQueryNode {
+    /// A lexeme to match.
+    Lexeme {
+        /// The lexeme.
+        lexeme: String,
+        /// The weights the lexeme matches, or all weights if empty.
+        weights: Vec<TsWeight>,
+        /// Whether the lexeme matches as a prefix, as in `lex:*`.
+        prefix: bool,
+    },
+    /// Negation, as in `!a`.
+    Not(Box<TsQueryNode>),
+    /// Conjunction, as in `a & b`.
+    And(Box<TsQueryNode>, Box<TsQueryNode>),
+    /// Disjunction, as in `a | b`.
+    Or(Box<TsQueryNode>, Box<TsQueryNode>),
+    /// A phrase, as in `a <N> b`, matching when the right lexeme follows the left at the given distance.
+    Phrase(Box<TsQueryNode>, Box<TsQueryNode>, u16),
+}
+
+/// A Postgres `TSQUERY`.
+///
+/// A query is either empty, as produced by `to_tsquery` for input made up entirely of stop words, or a tree of
+/// [`TsQueryNode`]s. Postgres can't receive an empty query in the binary format, so only nonempty queries can be used
+/// as parameters.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TsQuery {
+    root: Option<TsQueryNode>,
+}
+
+impl TsQuery {
+    /// Creates a query from its root node.
+    pub fn new(root: TsQueryNode) -> TsQuery {
+        TsQuery { root: Some(root) }
+    }
+
+    /// Creates an empty query.
+    pub fn empty() -> TsQuery {
+        TsQuery { root: None }
+    }
+
+    /// Returns the root node of the query, or `None` if it is empty.
+    pub fn root(&self) -> Option<&TsQueryNode> {
+        self.root.as_ref()
+    }
+
+    /// Consumes the query, returning its root node.
+    pub fn into_root(self) -> Option<TsQueryNode> {
+        self.root
+    }
+}
+
+impl From<TsQueryNode> for TsQuery {
+    fn from(root: TsQueryNode) -> TsQuery {
+        TsQuery::new(root)
+    }
+}
+
+impl<'a> FromSql<'a> for TsQuery {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<TsQuery, Box<dyn Error + Sync + Send>> {
+        let mut items = types::tsquery_from_sql(raw)?.peekable();
+        if items.peek()?.is_none() {
+            return Ok(TsQuery::empty());
+        }
+
+        let root = read_node(&mut items)?;
+        if items.next()?.is_some() {
+            return Err("invalid tsquery: trailing items".into());
+        }
+        Ok(TsQuery::new(root))
+    }
+
+    accepts!(TSQUERY);
+}
+
+impl ToSql for TsQuery {
+    fn to_sql(&self, _: &Type, w: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let root = self
+            .root
+            .as_ref()
+            .ok_or("an empty tsquery cannot be sent in the binary format")?;
+        let mut items = vec![];
+        write_node(root, &mut items);
+        types::tsquery_to_sql(items, w)?;
+        Ok(IsNull::No)
+    }
+
+    accepts!(TSQUERY);
+
+    to_sql_checked!();
+}
+
+fn read_node<'a, I>(items: &mut I) -> Result<TsQueryNode, Box<dyn Error + Sync + Send>>
+where
+    I: FallibleIterator<Item = TsQueryItem<'a>, Error = Box<dyn Error + Sync + Send>>,
+{
+    let item = items.next()?.ok_or("invalid tsquery: missing operand")?;
+
+    // binary operators are followed by their right operand, and then their left
+    let node = match item {
+        TsQueryItem::Operand {
+            weights,
+            prefix,
+            lexeme,
+        } => TsQueryNode::Lexeme {
+            lexeme: lexeme.to_string(),
+            weights: [TsWeight::A, TsWeight::B, TsWeight::C, TsWeight::D]
+                .iter()
+                .copied()
+                .filter(|weight| weights & weight.mask() != 0)
+                .collect(),
+            prefix,
+        },
+        TsQueryItem::Not => TsQueryNode::Not(Box::new(read_node(items)?)),
+        TsQueryItem::And => {
+            let right = read_node(items)?;
+            TsQueryNode::And(Box::new(read_node(items)?), Box::new(right))
+        }
+        TsQueryItem::Or => {
+            let right = read_node(items)?;
+            TsQueryNode::Or(Box::new(read_node(items)?), Box::new(right))
+        }
+        TsQueryItem::Phrase(distance) => {
+            let right = read_node(items)?;
+            TsQueryNode::Phrase(Box::new(read_node(items)?), Box::new(right), distance)
+        }
+    };
+
+    Ok(node)
+}
+
+fn write_node<'a>(node: &'a TsQueryNode, items: &mut Vec<TsQueryItem<'a>>) {
+    match node {
+        TsQueryNode::Lexeme {
+            lexeme,
+            weights,
+            prefix,
+        } => items.push(TsQueryItem::Operand {
+            weights: weights.iter().fold(0, |mask, weight| mask | weight.mask()),
+            prefix: *prefix,
+            lexeme,
+        }),
+        TsQueryNode::Not(operand) => {
+            items.push(TsQueryItem::Not);
+            write_node(operand, items);
+        }
+        TsQueryNode::And(left, right) => {
+            items.push(TsQueryItem::And);
+            write_node(right, items);
+            write_node(left, items);
+        }
+        TsQueryNode::Or(left, right) => {
+            items.push(TsQueryItem::Or);
+            write_node(right, items);
+            write_node(left, items);
+        }
+        TsQueryNode::Phrase(left, right, distance) => {
+            items.push(TsQueryItem::Phrase(*distance));
+            write_node(right, items);
+            write_node(left, items);
+        }
+    }
+}
@@ -4,7 +4,7 @@ use std::convert::TryFrom;
 use std::error::Error;
 use time_03::{Date, Duration, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
 
-use crate::{FromSql, IsNull, ToSql, Type};
+use crate::{FromSql, IntervalConversionError, IsNull, PgInterval, ToSql, Type};
 
 fn base() -> PrimitiveDateTime {
     PrimitiveDateTime::new(Date::from_ordinal_date(2000, 1).unwrap(), Time::MIDNIGHT)
@@ -111,3 +111,19 @@ impl ToSql for Time {
     accepts!(TIME);
     to_sql_checked!();
 }
+
+impl TryFrom<PgInterval> for Duration {
+    type Error = IntervalConversionError;
+
+    fn try_from(interval: PgInterval) -> Result<Duration, IntervalConversionError> {
+        Ok(Duration::microseconds(interval.to_microseconds()?))
+    }
+}
+
+impl TryFrom<Duration> for PgInterval {
+    type Error = IntervalConversionError;
+
+    fn try_from(duration: Duration) -> Result<PgInterval, IntervalConversionError> {
+        PgInterval::from_nanoseconds(duration.whole_nanoseconds())
+    }
+}
@@ -1,8 +1,8 @@
 //! Utilities for working with the PostgreSQL binary copy format.
 
 use crate::connection::ConnectionRef;
-use crate::types::{BorrowToSql, ToSql, Type};
-use crate::{CopyInWriter, CopyOutReader, Error};
+use crate::types::{BorrowToSql, ToSql, ToSqlRow, Type};
+use crate::{CopyInWriter, CopyOutReader, CopyProgress, Error};
 use fallible_iterator::FallibleIterator;
 use futures_util::StreamExt;
 use std::pin::Pin;
@@ -32,6 +32,17 @@ impl<'a> BinaryCopyInWriter<'a> {
         }
     }
 
+    /// Registers a callback which is invoked with the progress of the copy each time a chunk of rows is sent to the
+    /// connection.
+    ///
+    /// Rows are buffered and sent in chunks of a few kilobytes, so the callback is not invoked for every row.
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(CopyProgress) + Send + Sync + 'static,
+    {
+        self.sink.as_mut().set_progress_callback(callback);
+    }
+
     /// Writes a single row.
     ///
     /// # Panics
@@ -41,6 +52,22 @@ impl<'a> BinaryCopyInWriter<'a> {
         self.connection.block_on(self.sink.as_mut().write(values))
     }
 
+    /// Writes a single row from the fields of a value.
+    ///
+    /// The `ToSqlRow` trait can be derived for structs with the `derive` feature of `postgres-types`. Similarly, rows
+    /// read with a `BinaryCopyOutIter` can be deserialized into structs with `BinaryCopyOutRow::get_struct`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of fields does not match the number of values expected.
+    pub fn write_struct<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ToSqlRow,
+    {
+        self.connection
+            .block_on(self.sink.as_mut().write_struct(value))
+    }
+
     /// A maximally-flexible version of `write`.
     ///
     /// # Panics
@@ -83,6 +110,14 @@ impl<'a> BinaryCopyOutIter<'a> {
             stream: Box::pin(BinaryCopyOutStream::new(stream, types)),
         }
     }
+
+    /// Registers a callback which is invoked with the progress of the copy each time a row is received.
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(CopyProgress) + Send + Sync + 'static,
+    {
+        self.stream.as_mut().set_progress_callback(callback);
+    }
 }
 
 impl FallibleIterator for BinaryCopyOutIter<'_> {
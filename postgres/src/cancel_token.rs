@@ -1,3 +1,4 @@
+use std::time::Duration;
 use tokio::runtime;
 use tokio_postgres::tls::MakeTlsConnect;
 use tokio_postgres::{Error, Socket};
@@ -32,4 +33,18 @@ impl CancelToken {
             .unwrap() // FIXME don't unwrap
             .block_on(self.0.cancel_query(tls))
     }
+
+    /// Like `cancel_query`, but returns an error if the cancellation request has not been sent within `timeout`.
+    ///
+    /// See [`tokio_postgres::CancelToken::cancel_query_with_timeout`] for details.
+    pub fn cancel_query_with_timeout<T>(&self, tls: T, timeout: Duration) -> Result<(), Error>
+    where
+        T: MakeTlsConnect<Socket>,
+    {
+        runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap() // FIXME don't unwrap
+            .block_on(self.0.cancel_query_with_timeout(tls, timeout))
+    }
 }
@@ -1,12 +1,19 @@
 use crate::connection::Connection;
+use crate::replication::{
+    BaseBackup, BlockingWriter, IdentifySystem, LogicalReplicationIter, ReplicationIter,
+    TimelineHistory,
+};
 use crate::{
     CancelToken, Config, CopyInWriter, CopyOutReader, Notifications, RowIter, Statement,
     ToStatement, Transaction, TransactionBuilder,
 };
+use bytes::Bytes;
+use std::io::Write;
 use std::task::Poll;
 use std::time::Duration;
+use tokio_postgres::replication::{LogicalReplicationStream, ReplicationStream};
 use tokio_postgres::tls::{MakeTlsConnect, TlsConnect};
-use tokio_postgres::types::{BorrowToSql, ToSql, Type};
+use tokio_postgres::types::{BorrowToSql, PgLsn, ToSql, Type};
 use tokio_postgres::{Error, Row, SimpleQueryMessage, Socket};
 
 /// A synchronous PostgreSQL client.
@@ -83,6 +90,28 @@ impl Client {
         self.connection.block_on(self.client.execute(query, params))
     }
 
+    /// Like `execute`, but cancels the statement if it does not complete within `timeout`.
+    ///
+    /// When the deadline passes, a cancellation request is sent to the server using the `tls` implementation (see
+    /// [`CancelToken::cancel_query`]) and an error is returned for which [`Error::is_timeout`] returns `true`. The
+    /// connection remains usable afterwards.
+    pub fn execute_with_timeout<T, U>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+        timeout: Duration,
+        tls: U,
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+        U: MakeTlsConnect<Socket>,
+    {
+        self.connection.block_on(
+            self.client
+                .execute_with_timeout(query, params, timeout, tls),
+        )
+    }
+
     /// Executes a statement, returning the resulting rows.
     ///
     /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
@@ -115,6 +144,42 @@ impl Client {
         self.connection.block_on(self.client.query(query, params))
     }
 
+    /// Like `query`, but cancels the query if it does not complete within `timeout`.
+    ///
+    /// See `execute_with_timeout` for details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use postgres::{Client, NoTls};
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), postgres::Error> {
+    /// let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+    ///
+    /// match client.query_with_timeout("SELECT * FROM big_table", &[], Duration::from_secs(5), NoTls) {
+    ///     Ok(rows) => println!("{} rows", rows.len()),
+    ///     Err(e) if e.is_timeout() => println!("query timed out"),
+    ///     Err(e) => return Err(e),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_with_timeout<T, U>(
+        &mut self,
+        query: &T,
+        params: &[&(dyn ToSql + Sync)],
+        timeout: Duration,
+        tls: U,
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+        U: MakeTlsConnect<Socket>,
+    {
+        self.connection
+            .block_on(self.client.query_with_timeout(query, params, timeout, tls))
+    }
+
     /// Executes a statement which returns a single row, returning it.
     ///
     /// Returns an error if the query does not return exactly one row.
@@ -440,6 +505,89 @@ impl Client {
         Ok(CopyOutReader::new(self.connection.as_ref(), stream))
     }
 
+    /// Executes a `START_REPLICATION` command using the simple query protocol, returning an iterator over the
+    /// resulting replication messages.
+    ///
+    /// The client must have been opened as a replication connection. See the [`replication`](crate::replication)
+    /// module for details.
+    pub fn replication_stream(&mut self, query: &str) -> Result<ReplicationIter<'_>, Error> {
+        let stream = self
+            .connection
+            .block_on(self.client.copy_both_simple::<Bytes>(query))?;
+        Ok(ReplicationIter::new(
+            self.connection.as_ref(),
+            ReplicationStream::new(stream),
+        ))
+    }
+
+    /// Like `replication_stream`, but decodes the messages produced by the `pgoutput` logical decoding plugin.
+    ///
+    /// The client must have been opened as a logical replication connection.
+    pub fn logical_replication_stream(
+        &mut self,
+        query: &str,
+    ) -> Result<LogicalReplicationIter<'_>, Error> {
+        let stream = self
+            .connection
+            .block_on(self.client.copy_both_simple::<Bytes>(query))?;
+        Ok(LogicalReplicationIter::new(
+            self.connection.as_ref(),
+            LogicalReplicationStream::new(stream),
+        ))
+    }
+
+    /// Requests the server to identify itself, returning its system identifier, timeline, and WAL position.
+    ///
+    /// The client must have been opened as a replication connection.
+    pub fn identify_system(&mut self) -> Result<IdentifySystem, Error> {
+        self.connection.block_on(self.client.identify_system())
+    }
+
+    /// Requests the timeline history file for the specified timeline.
+    ///
+    /// The client must have been opened as a physical replication connection.
+    pub fn timeline_history(&mut self, timeline: u32) -> Result<TimelineHistory, Error> {
+        self.connection
+            .block_on(self.client.timeline_history(timeline))
+    }
+
+    /// Starts streaming WAL from the specified location, returning an iterator over replication messages.
+    ///
+    /// If a slot is provided, the server retains the WAL required by the slot until the client reports it as flushed.
+    /// If a timeline is not provided, the server's current timeline is used. The iterator ends when the server switches
+    /// away from the timeline, after which [`ReplicationIter::close`] must be called before the client can be used
+    /// again.
+    ///
+    /// The client must have been opened as a physical replication connection.
+    pub fn start_physical_replication(
+        &mut self,
+        slot: Option<&str>,
+        lsn: PgLsn,
+        timeline: Option<u32>,
+    ) -> Result<ReplicationIter<'_>, Error> {
+        let stream = self
+            .connection
+            .block_on(self.client.start_physical_replication(slot, lsn, timeline))?;
+        Ok(ReplicationIter::new(self.connection.as_ref(), stream))
+    }
+
+    /// Takes a base backup of the server, writing the archive data to `writer`.
+    ///
+    /// The `options` are passed to the `BASE_BACKUP` command as its parenthesized option list, for example
+    /// `LABEL 'nightly', CHECKPOINT 'fast', MANIFEST 'no'`. The server sends a tar archive of the data directory, followed
+    /// by one for each additional tablespace; all of them are written to `writer` one after another. The backup
+    /// manifest, if any, is not written.
+    ///
+    /// The client must have been opened as a physical replication connection to a server running PostgreSQL 15 or
+    /// later.
+    pub fn base_backup<W>(&mut self, options: &str, writer: W) -> Result<BaseBackup, Error>
+    where
+        W: Write + Unpin,
+    {
+        self.connection
+            .block_on(self.client.base_backup(options, BlockingWriter(writer)))
+    }
+
     /// Executes a sequence of SQL statements using the simple query protocol.
     ///
     /// Statements should be separated by semicolons. If an error occurs, execution of the sequence will stop at that
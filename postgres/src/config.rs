@@ -2,7 +2,8 @@
 
 #![allow(clippy::doc_overindented_list_items)]
 
-use crate::connection::Connection;
+use crate::connection::{ClientRuntime, Connection};
+use crate::gss::MakeGssContext;
 use crate::Client;
 use log::info;
 use std::fmt;
@@ -11,14 +12,15 @@ use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::runtime;
+use tokio::runtime::{self, Handle, RuntimeFlavor};
 #[doc(inline)]
 pub use tokio_postgres::config::{
-    ChannelBinding, Host, LoadBalanceHosts, SslMode, SslNegotiation, TargetSessionAttrs,
+    AuthMethod, ChannelBinding, GssEncMode, Host, LoadBalanceHosts, Proxy, ProxyKind,
+    ReplicationMode, SslMode, SslNegotiation, TargetSessionAttrs,
 };
 use tokio_postgres::error::DbError;
 use tokio_postgres::tls::{MakeTlsConnect, TlsConnect};
-use tokio_postgres::{Error, Socket};
+use tokio_postgres::{Error, Resolver, Socket};
 
 /// Connection configuration.
 ///
@@ -38,6 +40,15 @@ use tokio_postgres::{Error, Socket};
 /// * `application_name` - Sets the `application_name` parameter on the server.
 /// * `sslmode` - Controls usage of TLS. If set to `disable`, TLS will not be used. If set to `prefer`, TLS will be used
 ///     if available, but not used otherwise. If set to `require`, TLS will be forced to be used. Defaults to `prefer`.
+///     Multiple modes can be specified, separated by commas, to use a different mode for each host. In that case
+///     there must be the same number of modes as hosts. Certificates are verified by the
+///     [`MakeTlsConnect`](tokio_postgres::tls::MakeTlsConnect) implementation, which is given each host's name and so
+///     can apply its own policy per host.
+/// * `gssencmode` - Controls usage of GSSAPI encryption. If set to `disable`, GSSAPI encryption will not be used. If
+///     set to `prefer`, GSSAPI encryption will be used if a GSSAPI implementation has been set with the `gss` method
+///     and the server supports it, in which case TLS is not used. If set to `require`, GSSAPI encryption will be
+///     forced to be used. It is never used over Unix domain sockets. Defaults to `prefer`.
+/// * `krbsrvname` - The Kerberos service name to use when authenticating with GSSAPI. Defaults to `postgres`.
 /// * `host` - The host to connect to. On Unix platforms, if the host starts with a `/` character it is treated as the
 ///     path to the directory containing Unix domain sockets. Otherwise, it is treated as a hostname. Multiple hosts
 ///     can be specified, separated by commas. Each host will be tried in turn when connecting. Required if connecting
@@ -45,6 +56,13 @@ use tokio_postgres::{Error, Socket};
 /// * `sslnegotiation` - TLS negotiation method. If set to `direct`, the client will perform direct TLS handshake, this only works for PostgreSQL 17 and newer.
 ///     Note that you will need to setup ALPN of TLS client configuration to `postgresql` when using direct TLS.
 ///     If set to `postgres`, the default value, it follows original postgres wire protocol to perform the negotiation.
+/// * `sslrootcert` - The path to a file containing the certificate authorities used to verify the server's
+///     certificate.
+/// * `sslcert` - The path to a file containing the client certificate to present to the server.
+/// * `sslkey` - The path to a file containing the private key of the client certificate.
+///
+///     These three paths are passed on to the `MakeTlsConnect` implementation, which is responsible for loading the
+///     files. `postgres-openssl` supports them; other implementations may ignore them.
 /// * `hostaddr` - Numeric IP address of host to connect to. This should be in the standard IPv4 address format,
 ///     e.g., 172.28.40.9. If your machine supports IPv6, you can also use those addresses.
 ///     If this parameter is not specified, the value of `host` will be looked up to find the corresponding IP address,
@@ -80,12 +98,46 @@ use tokio_postgres::{Error, Socket};
 /// * `channel_binding` - Controls usage of channel binding in the authentication process. If set to `disable`, channel
 ///     binding will not be used. If set to `prefer`, channel binding will be used if available, but not used otherwise.
 ///     If set to `require`, the authentication process will fail if channel binding is not used. Defaults to `prefer`.
+/// * `require_auth` - A comma-separated list of the authentication methods the server may request, from `password`,
+///     `md5`, `gss`, `sspi`, `scram-sha-256`, and `none` (no authentication). If the methods are each prefixed with
+///     `!`, any method but those listed may be requested instead. The connection fails if the server requests a
+///     method which is not allowed. Defaults to allowing any method.
 /// * `load_balance_hosts` - Controls the order in which the client tries to connect to the available hosts and
 ///     addresses. Once a connection attempt is successful no other hosts and addresses will be tried. This parameter
 ///     is typically used in combination with multiple host names or a DNS record that returns multiple IPs. If set to
 ///     `disable`, hosts and addresses will be tried in the order provided. If set to `random`, hosts will be tried
-///     in a random order, and the IP addresses resolved from a hostname will also be tried in a random order. Defaults
-///     to `disable`.
+///     in a random order, and the IP addresses resolved from a hostname will also be tried in a random order. If set
+///     to `least-latency`, hosts will be tried in order of how quickly connections to them have recently been
+///     established with the configuration, with hosts that have not been tried recently going first. Defaults to
+///     `disable`.
+/// * `random_seed` - An integer seed for the random choices made by the client: the order hosts and addresses are
+///     tried in when `load_balance_hosts` is `random`, and the delays between attempts of
+///     `Client::transaction_retry` in `tokio-postgres`. Setting a seed makes these reproducible, for example in tests. Defaults to seeding
+///     from the operating system.
+/// * `proxy` - The URL of a proxy to establish TCP connections through, of the form
+///     `socks5://[user[:password]@]host[:port]` or `http://[user[:password]@]host[:port]`. Host names are resolved by
+///     the proxy.
+/// * `local_address` - The local IP address to bind outgoing TCP connections to, for hosts with more than one
+///     address. Defaults to letting the operating system choose.
+/// * `bind_interface` - The name of a network interface to bind outgoing TCP connections to. This is only supported
+///     on Linux, Android, and Fuchsia.
+/// * `row_buffer_size` - The maximum number of rows the connection reads from the socket into a single batch for a
+///     statement. The connection stops reading from the socket while a statement's batches are waiting to be consumed,
+///     so this bounds the memory used when rows are produced faster than they are processed. Must be positive.
+///     Defaults to no limit.
+/// * `read_buffer_size` - The capacity in bytes of the buffer the connection reads messages from the socket into.
+///     Defaults to 8 KiB.
+/// * `write_buffer_size` - The capacity in bytes of the buffer the connection writes messages to the socket from.
+///     Defaults to 8 KiB.
+/// * `service` - The name of a service defined in a connection service file, whose parameters are used for any that
+///     are not given in the connection string itself. The per-user service file is `~/.pg_service.conf` (or
+///     `%APPDATA%\postgresql\.pg_service.conf` on Windows) unless overridden by `servicefile` or the `PGSERVICEFILE`
+///     environment variable, followed by `pg_service.conf` in the directory named by the `PGSYSCONFDIR` environment
+///     variable.
+/// * `servicefile` - The path to the per-user connection service file searched for `service`.
+/// * `replication` - Opens a replication connection. If set to `true`, `on`, `yes`, or `1`, the connection can be used
+///     for physical replication. If set to `database`, the connection can be used for logical replication from the
+///     database named by `dbname`. Defaults to a normal connection.
 ///
 /// ## Examples
 ///
@@ -133,6 +185,7 @@ use tokio_postgres::{Error, Socket};
 pub struct Config {
     config: tokio_postgres::Config,
     notice_callback: Arc<dyn Fn(DbError) + Send + Sync>,
+    runtime_handle: Option<Handle>,
 }
 
 impl fmt::Debug for Config {
@@ -155,6 +208,47 @@ impl Config {
         tokio_postgres::Config::new().into()
     }
 
+    /// Creates a configuration from the libpq environment variables, such as `PGHOST`, `PGPORT`, `PGUSER`,
+    /// `PGPASSWORD`, `PGDATABASE`, and `PGSSLMODE`.
+    ///
+    /// See [`tokio_postgres::Config::from_env`] for details.
+    pub fn from_env() -> Result<Config, Error> {
+        tokio_postgres::Config::from_env().map(Config::from)
+    }
+
+    /// Parses a connection string, using the libpq environment variables as defaults for parameters it does not
+    /// specify.
+    ///
+    /// See [`tokio_postgres::Config::parse_with_env`] for details.
+    pub fn parse_with_env(s: &str) -> Result<Config, Error> {
+        tokio_postgres::Config::parse_with_env(s).map(Config::from)
+    }
+
+    /// Like `from_env`, but reads the libpq environment variables from `vars` rather than the process environment.
+    ///
+    /// See [`tokio_postgres::Config::from_env_vars`] for details.
+    pub fn from_env_vars<I, K, V>(vars: I) -> Result<Config, Error>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        tokio_postgres::Config::from_env_vars(vars).map(Config::from)
+    }
+
+    /// Like `parse_with_env`, but reads the libpq environment variables from `vars` rather than the process
+    /// environment.
+    ///
+    /// See [`tokio_postgres::Config::parse_with_env_vars`] for details.
+    pub fn parse_with_env_vars<I, K, V>(s: &str, vars: I) -> Result<Config, Error>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        tokio_postgres::Config::parse_with_env_vars(s, vars).map(Config::from)
+    }
+
     /// Sets the user to authenticate with.
     ///
     /// If the user is not set, then this defaults to the user executing this process.
@@ -222,6 +316,34 @@ impl Config {
         self.config.get_application_name()
     }
 
+    /// Sets a runtime parameter to be sent to the server when connecting, such as `search_path` or
+    /// `statement_timeout`.
+    ///
+    /// The parameter is included in the startup message, and takes effect as if it had been set with `SET` at the start
+    /// of the session. Setting a parameter again replaces its previous value. Parameters which have a dedicated method,
+    /// such as `application_name`, should be set with it instead.
+    pub fn param(&mut self, key: &str, value: &str) -> &mut Config {
+        self.config.param(key, value);
+        self
+    }
+
+    /// Gets the runtime parameters which have been set with the `param` method, in the order they were first set.
+    pub fn get_params(&self) -> &[(String, String)] {
+        self.config.get_params()
+    }
+
+    /// Gets the name of the connection service the configuration was loaded from, if one was specified with the
+    /// `service` key when parsing it.
+    pub fn get_service(&self) -> Option<&str> {
+        self.config.get_service()
+    }
+
+    /// Gets the path to the connection service file, if one was specified with the `servicefile` key when parsing
+    /// the configuration.
+    pub fn get_service_file(&self) -> Option<&Path> {
+        self.config.get_service_file()
+    }
+
     /// Sets the SSL configuration.
     ///
     /// Defaults to `prefer`.
@@ -235,6 +357,21 @@ impl Config {
         self.config.get_ssl_mode()
     }
 
+    /// Adds an SSL configuration for a single host, overriding the one set with `ssl_mode`.
+    ///
+    /// Multiple modes can be specified by calling this method multiple times, in which case they are matched with
+    /// hosts in order. There must either be no per-host modes, in which case `ssl_mode` is used for all hosts, or the
+    /// same number of modes as hosts.
+    pub fn host_ssl_mode(&mut self, ssl_mode: SslMode) -> &mut Config {
+        self.config.host_ssl_mode(ssl_mode);
+        self
+    }
+
+    /// Gets the per-host SSL configurations that have been added with `host_ssl_mode`.
+    pub fn get_host_ssl_modes(&self) -> &[SslMode] {
+        self.config.get_host_ssl_modes()
+    }
+
     /// Sets the SSL negotiation method
     pub fn ssl_negotiation(&mut self, ssl_negotiation: SslNegotiation) -> &mut Config {
         self.config.ssl_negotiation(ssl_negotiation);
@@ -246,6 +383,101 @@ impl Config {
         self.config.get_ssl_negotiation()
     }
 
+    /// Sets the path to the file containing the certificate authorities used to verify the server's certificate.
+    ///
+    /// The path is passed on to the `MakeTlsConnect` implementation, which is responsible for loading the file.
+    pub fn ssl_root_cert<T>(&mut self, ssl_root_cert: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        self.config.ssl_root_cert(ssl_root_cert.as_ref());
+        self
+    }
+
+    /// Gets the path to the file containing the certificate authorities used to verify the server's certificate, if
+    /// one has been set with the `ssl_root_cert` method.
+    pub fn get_ssl_root_cert(&self) -> Option<&Path> {
+        self.config.get_ssl_root_cert()
+    }
+
+    /// Sets the path to the file containing the client certificate to present to the server.
+    ///
+    /// The path is passed on to the `MakeTlsConnect` implementation, which is responsible for loading the file.
+    pub fn ssl_cert<T>(&mut self, ssl_cert: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        self.config.ssl_cert(ssl_cert.as_ref());
+        self
+    }
+
+    /// Gets the path to the file containing the client certificate, if one has been set with the `ssl_cert` method.
+    pub fn get_ssl_cert(&self) -> Option<&Path> {
+        self.config.get_ssl_cert()
+    }
+
+    /// Sets the path to the file containing the private key of the client certificate.
+    ///
+    /// The path is passed on to the `MakeTlsConnect` implementation, which is responsible for loading the file.
+    pub fn ssl_key<T>(&mut self, ssl_key: T) -> &mut Config
+    where
+        T: AsRef<Path>,
+    {
+        self.config.ssl_key(ssl_key.as_ref());
+        self
+    }
+
+    /// Gets the path to the file containing the private key of the client certificate, if one has been set with the
+    /// `ssl_key` method.
+    pub fn get_ssl_key(&self) -> Option<&Path> {
+        self.config.get_ssl_key()
+    }
+
+    /// Sets the GSSAPI encryption configuration.
+    ///
+    /// GSSAPI encryption requires an implementation set with the `gss` method. Without one, it is not used if this is
+    /// set to `prefer`, and connecting fails if this is set to `require`.
+    ///
+    /// Defaults to `prefer`.
+    pub fn gss_enc_mode(&mut self, gss_enc_mode: GssEncMode) -> &mut Config {
+        self.config.gss_enc_mode(gss_enc_mode);
+        self
+    }
+
+    /// Gets the GSSAPI encryption configuration.
+    pub fn get_gss_enc_mode(&self) -> GssEncMode {
+        self.config.get_gss_enc_mode()
+    }
+
+    /// Sets the Kerberos service name used to authenticate with GSSAPI.
+    ///
+    /// Defaults to `postgres`.
+    pub fn krb_srv_name(&mut self, krb_srv_name: &str) -> &mut Config {
+        self.config.krb_srv_name(krb_srv_name);
+        self
+    }
+
+    /// Gets the Kerberos service name used to authenticate with GSSAPI.
+    pub fn get_krb_srv_name(&self) -> &str {
+        self.config.get_krb_srv_name()
+    }
+
+    /// Sets the GSSAPI implementation used for GSSAPI encryption and authentication.
+    ///
+    /// Neither is available without one.
+    pub fn gss<G>(&mut self, gss: G) -> &mut Config
+    where
+        G: MakeGssContext + 'static,
+    {
+        self.config.gss(gss);
+        self
+    }
+
+    /// Gets the GSSAPI implementation, if one has been set with the `gss` method.
+    pub fn get_gss(&self) -> Option<&dyn MakeGssContext> {
+        self.config.get_gss()
+    }
+
     /// Adds a host to the configuration.
     ///
     /// Multiple hosts can be specified by calling this method multiple times, and each will be tried in order. On Unix
@@ -333,6 +565,64 @@ impl Config {
         self.config.get_tcp_user_timeout()
     }
 
+    /// Sets a proxy through which TCP connections are established.
+    ///
+    /// This is ignored for Unix domain socket connections.
+    pub fn proxy(&mut self, proxy: Proxy) -> &mut Config {
+        self.config.proxy(proxy);
+        self
+    }
+
+    /// Gets the proxy, if one has been set with the `proxy` method.
+    pub fn get_proxy(&self) -> Option<&Proxy> {
+        self.config.get_proxy()
+    }
+
+    /// Sets the local IP address that TCP connections are bound to.
+    ///
+    /// This is useful on hosts with multiple addresses, where the server only accepts connections from some of them.
+    /// Hosts resolving to addresses of a different family than the local address cannot be connected to. This is
+    /// ignored for Unix domain socket connections.
+    pub fn local_address(&mut self, local_address: IpAddr) -> &mut Config {
+        self.config.local_address(local_address);
+        self
+    }
+
+    /// Gets the local IP address, if one has been set with the `local_address` method.
+    pub fn get_local_address(&self) -> Option<IpAddr> {
+        self.config.get_local_address()
+    }
+
+    /// Sets the name of the network interface that TCP connections are bound to.
+    ///
+    /// This is only supported on Linux, Android, and Fuchsia, and typically requires the `CAP_NET_RAW` capability;
+    /// connecting fails on other systems. This is ignored for Unix domain socket connections.
+    pub fn bind_interface(&mut self, bind_interface: &str) -> &mut Config {
+        self.config.bind_interface(bind_interface);
+        self
+    }
+
+    /// Gets the name of the network interface, if one has been set with the `bind_interface` method.
+    pub fn get_bind_interface(&self) -> Option<&str> {
+        self.config.get_bind_interface()
+    }
+
+    /// Sets the resolver used to look up the addresses of hosts and of the proxy.
+    ///
+    /// Defaults to the system resolver.
+    pub fn resolver<R>(&mut self, resolver: R) -> &mut Config
+    where
+        R: Resolver + 'static,
+    {
+        self.config.resolver(resolver);
+        self
+    }
+
+    /// Gets the resolver, if one has been set with the `resolver` method.
+    pub fn get_resolver(&self) -> Option<&dyn Resolver> {
+        self.config.get_resolver()
+    }
+
     /// Controls the use of TCP keepalive.
     ///
     /// This is ignored for Unix domain socket connections. Defaults to `true`.
@@ -417,6 +707,21 @@ impl Config {
         self.config.get_channel_binding()
     }
 
+    /// Adds an authentication method the server is allowed to request.
+    ///
+    /// Once a method has been added, connecting fails if the server requests any method which has not been. Defaults
+    /// to allowing any method.
+    pub fn require_auth(&mut self, method: AuthMethod) -> &mut Config {
+        self.config.require_auth(method);
+        self
+    }
+
+    /// Gets the authentication methods the server is allowed to request, if any have been added with the
+    /// `require_auth` method.
+    pub fn get_require_auth(&self) -> Option<&[AuthMethod]> {
+        self.config.get_require_auth()
+    }
+
     /// Sets the host load balancing behavior.
     ///
     /// Defaults to `disable`.
@@ -430,6 +735,91 @@ impl Config {
         self.config.get_load_balance_hosts()
     }
 
+    /// Sets the seed for the random choices made by the client.
+    ///
+    /// With a seed set, hosts and addresses are tried in the same order every time the configuration is used with
+    /// `LoadBalanceHosts::Random`. Defaults to seeding from the operating system.
+    pub fn random_seed(&mut self, random_seed: u64) -> &mut Config {
+        self.config.random_seed(random_seed);
+        self
+    }
+
+    /// Gets the seed for the random choices made by the client, if one has been set with the `random_seed` method.
+    pub fn get_random_seed(&self) -> Option<u64> {
+        self.config.get_random_seed()
+    }
+
+    /// Opens the connection in replication mode.
+    ///
+    /// Replication connections accept the commands of the streaming replication protocol rather than normal SQL.
+    /// Logical replication connections can additionally run simple queries against the database.
+    pub fn replication_mode(&mut self, replication_mode: ReplicationMode) -> &mut Config {
+        self.config.replication_mode(replication_mode);
+        self
+    }
+
+    /// Gets the replication mode of the connection, if one has been configured with the `replication_mode` method.
+    pub fn get_replication_mode(&self) -> Option<ReplicationMode> {
+        self.config.get_replication_mode()
+    }
+
+    /// Sets the maximum number of rows the connection reads from the socket into a single batch for a statement.
+    ///
+    /// Defaults to no limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row_buffer_size` is 0.
+    pub fn row_buffer_size(&mut self, row_buffer_size: usize) -> &mut Config {
+        self.config.row_buffer_size(row_buffer_size);
+        self
+    }
+
+    /// Gets the maximum number of rows in a single batch, if one has been set with the `row_buffer_size` method.
+    pub fn get_row_buffer_size(&self) -> Option<usize> {
+        self.config.get_row_buffer_size()
+    }
+
+    /// Sets the capacity in bytes of the buffer the connection reads messages from the socket into.
+    ///
+    /// A value of 0 is treated as 1. Defaults to 8 KiB.
+    pub fn read_buffer_size(&mut self, read_buffer_size: usize) -> &mut Config {
+        self.config.read_buffer_size(read_buffer_size);
+        self
+    }
+
+    /// Gets the capacity of the buffer the connection reads messages into.
+    pub fn get_read_buffer_size(&self) -> usize {
+        self.config.get_read_buffer_size()
+    }
+
+    /// Sets the capacity in bytes of the buffer the connection writes messages to the socket from.
+    ///
+    /// A value of 0 is treated as 1. Defaults to 8 KiB.
+    pub fn write_buffer_size(&mut self, write_buffer_size: usize) -> &mut Config {
+        self.config.write_buffer_size(write_buffer_size);
+        self
+    }
+
+    /// Gets the capacity of the buffer the connection writes messages from.
+    pub fn get_write_buffer_size(&self) -> usize {
+        self.config.get_write_buffer_size()
+    }
+
+    /// Adds a statement to run on each new connection before it is returned.
+    ///
+    /// Multiple statements can be added by calling this method multiple times, and are run in order. If a statement
+    /// fails, the connection attempt fails with its error.
+    pub fn init_statement(&mut self, statement: &str) -> &mut Config {
+        self.config.init_statement(statement);
+        self
+    }
+
+    /// Gets the statements that have been added with `init_statement`.
+    pub fn get_init_statements(&self) -> &[String] {
+        self.config.get_init_statements()
+    }
+
     /// Sets the notice callback.
     ///
     /// This callback will be invoked with the contents of every
@@ -449,6 +839,22 @@ impl Config {
         self
     }
 
+    /// Sets a handle to an existing Tokio runtime on which connections will run.
+    ///
+    /// By default, each connection creates its own single-threaded runtime. With a handle, connections instead share
+    /// the I/O and timer drivers of the provided runtime, which must be a multi-threaded runtime so that those drivers
+    /// are run by its worker threads. Connecting fails if the handle is to a current-thread runtime. As with the
+    /// default runtime, the client's methods must not be called from within an asynchronous context.
+    pub fn runtime_handle(&mut self, handle: Handle) -> &mut Config {
+        self.runtime_handle = Some(handle);
+        self
+    }
+
+    /// Gets the runtime handle, if one has been set with the `runtime_handle` method.
+    pub fn get_runtime_handle(&self) -> Option<&Handle> {
+        self.runtime_handle.as_ref()
+    }
+
     /// Opens a connection to a PostgreSQL database.
     pub fn connect<T>(&self, tls: T) -> Result<Client, Error>
     where
@@ -457,10 +863,21 @@ impl Config {
         T::Stream: Send,
         <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
     {
-        let runtime = runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .unwrap(); // FIXME don't unwrap
+        let runtime = match &self.runtime_handle {
+            // nothing would drive the connection between calls on a current-thread runtime
+            Some(handle) if handle.runtime_flavor() == RuntimeFlavor::CurrentThread => {
+                return Err(Error::__private_api_config(
+                    "the runtime handle must be to a multi-threaded runtime".into(),
+                ))
+            }
+            Some(handle) => ClientRuntime::Shared(handle.clone()),
+            None => ClientRuntime::Owned(
+                runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap(), // FIXME don't unwrap
+            ),
+        };
 
         let (client, connection) = runtime.block_on(self.config.connect(tls))?;
 
@@ -484,6 +901,7 @@ impl From<tokio_postgres::Config> for Config {
             notice_callback: Arc::new(|notice| {
                 info!("{}: {}", notice.severity(), notice.message())
             }),
+            runtime_handle: None,
         }
     }
 }
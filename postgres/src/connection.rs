@@ -7,12 +7,37 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite};
-use tokio::runtime::Runtime;
+use tokio::runtime::{EnterGuard, Handle, Runtime};
 use tokio_postgres::error::DbError;
 use tokio_postgres::AsyncMessage;
 
+// The runtime driving a connection, either created for it or provided by the caller.
+pub enum ClientRuntime {
+    Owned(Runtime),
+    Shared(Handle),
+}
+
+impl ClientRuntime {
+    pub fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        match self {
+            ClientRuntime::Owned(runtime) => runtime.block_on(future),
+            ClientRuntime::Shared(handle) => handle.block_on(future),
+        }
+    }
+
+    fn enter(&self) -> EnterGuard<'_> {
+        match self {
+            ClientRuntime::Owned(runtime) => runtime.enter(),
+            ClientRuntime::Shared(handle) => handle.enter(),
+        }
+    }
+}
+
 pub struct Connection {
-    runtime: Runtime,
+    runtime: ClientRuntime,
     connection: Pin<Box<dyn Stream<Item = Result<AsyncMessage, Error>> + Send>>,
     notifications: VecDeque<Notification>,
     notice_callback: Arc<dyn Fn(DbError) + Sync + Send>,
@@ -20,7 +45,7 @@ pub struct Connection {
 
 impl Connection {
     pub fn new<S, T>(
-        runtime: Runtime,
+        runtime: ClientRuntime,
         connection: tokio_postgres::Connection<S, T>,
         notice_callback: Arc<dyn Fn(DbError) + Sync + Send>,
     ) -> Connection
@@ -4,7 +4,7 @@ use bytes::{Bytes, BytesMut};
 use futures_util::SinkExt;
 use std::io;
 use std::io::Write;
-use tokio_postgres::{CopyInSink, Error};
+use tokio_postgres::{CopyInSink, CopyProgress, Error};
 
 /// The writer returned by the `copy_in` method.
 ///
@@ -13,6 +13,8 @@ pub struct CopyInWriter<'a> {
     pub(crate) connection: ConnectionRef<'a>,
     pub(crate) sink: LazyPin<CopyInSink<Bytes>>,
     buf: BytesMut,
+    bytes: u64,
+    progress_callback: Option<Box<dyn FnMut(CopyProgress) + Send + Sync>>,
 }
 
 impl<'a> CopyInWriter<'a> {
@@ -21,9 +23,22 @@ impl<'a> CopyInWriter<'a> {
             connection,
             sink: LazyPin::new(sink),
             buf: BytesMut::new(),
+            bytes: 0,
+            progress_callback: None,
         }
     }
 
+    /// Registers a callback which is invoked with the progress of the copy each time a chunk of data is sent to the
+    /// connection.
+    ///
+    /// Data is buffered and sent in chunks of a few kilobytes, so the callback is not invoked for every write.
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(CopyProgress) + Send + Sync + 'static,
+    {
+        self.progress_callback = Some(Box::new(callback));
+    }
+
     /// Completes the copy, returning the number of rows written.
     ///
     /// If this is not called, the copy will be aborted.
@@ -37,8 +52,15 @@ impl<'a> CopyInWriter<'a> {
             return Ok(());
         }
 
+        let len = self.buf.len();
         self.connection
-            .block_on(self.sink.pinned().send(self.buf.split().freeze()))
+            .block_on(self.sink.pinned().send(self.buf.split().freeze()))?;
+
+        self.bytes += len as u64;
+        if let Some(callback) = &mut self.progress_callback {
+            callback(CopyProgress::__private_api_new(self.bytes, None));
+        }
+        Ok(())
     }
 }
 
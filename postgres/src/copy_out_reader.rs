@@ -3,13 +3,15 @@ use crate::lazy_pin::LazyPin;
 use bytes::{Buf, Bytes};
 use futures_util::StreamExt;
 use std::io::{self, BufRead, Read};
-use tokio_postgres::CopyOutStream;
+use tokio_postgres::{CopyOutStream, CopyProgress};
 
 /// The reader returned by the `copy_out` method.
 pub struct CopyOutReader<'a> {
     pub(crate) connection: ConnectionRef<'a>,
     pub(crate) stream: LazyPin<CopyOutStream>,
     cur: Bytes,
+    bytes: u64,
+    progress_callback: Option<Box<dyn FnMut(CopyProgress) + Send + Sync>>,
 }
 
 impl<'a> CopyOutReader<'a> {
@@ -18,8 +20,18 @@ impl<'a> CopyOutReader<'a> {
             connection,
             stream: LazyPin::new(stream),
             cur: Bytes::new(),
+            bytes: 0,
+            progress_callback: None,
         }
     }
+
+    /// Registers a callback which is invoked with the progress of the copy each time a chunk of data is received.
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(CopyProgress) + Send + Sync + 'static,
+    {
+        self.progress_callback = Some(Box::new(callback));
+    }
 }
 
 impl Read for CopyOutReader<'_> {
@@ -40,7 +52,13 @@ impl BufRead for CopyOutReader<'_> {
                 .connection
                 .block_on(async { stream.next().await.transpose() })
             {
-                Ok(Some(cur)) => self.cur = cur,
+                Ok(Some(cur)) => {
+                    self.bytes += cur.len() as u64;
+                    if let Some(callback) = &mut self.progress_callback {
+                        callback(CopyProgress::__private_api_new(self.bytes, None));
+                    }
+                    self.cur = cur;
+                }
                 Err(e) => return Err(io::Error::other(e)),
                 Ok(None) => break,
             };
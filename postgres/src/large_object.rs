@@ -0,0 +1,187 @@
+//! Large object support.
+//!
+//! Large objects store binary data out of line in the `pg_largeobject` catalog, and are read and written in chunks
+//! through a descriptor rather than as a single value. Descriptors are only valid until the end of the transaction
+//! which opened them, so large objects are accessed through a [`Transaction`](crate::Transaction).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use postgres::large_object::Mode;
+//! use postgres::{Client, NoTls};
+//! use std::fs::File;
+//! use std::io;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut client = Client::connect("host=localhost user=postgres", NoTls)?;
+//! let mut transaction = client.transaction()?;
+//!
+//! let oid = transaction.create_large_object()?;
+//! let mut object = transaction.open_large_object(oid, Mode::Write)?;
+//! io::copy(&mut File::open("backup.tar")?, &mut object)?;
+//! object.close()?;
+//!
+//! transaction.commit()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::connection::ConnectionRef;
+use std::convert::TryFrom;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use tokio_postgres::types::{Oid, ToSql, Type};
+use tokio_postgres::Error;
+
+// Bounds the size of the `bytea` values exchanged with the server, which are limited to 1GB.
+const MAX_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+
+const INV_WRITE: i32 = 0x0002_0000;
+const INV_READ: i32 = 0x0004_0000;
+
+const SEEK_SET: i32 = 0;
+const SEEK_CUR: i32 = 1;
+const SEEK_END: i32 = 2;
+
+/// The mode a large object is opened in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Mode {
+    /// The object can only be read.
+    ///
+    /// Reads see the contents of the object as of the start of the transaction's snapshot, ignoring later writes.
+    Read,
+    /// The object can only be written.
+    Write,
+    /// The object can be both read and written.
+    ///
+    /// Reads see the current contents of the object, including writes made through this descriptor.
+    ReadWrite,
+}
+
+impl Mode {
+    fn flags(self) -> i32 {
+        match self {
+            Mode::Read => INV_READ,
+            Mode::Write => INV_WRITE,
+            Mode::ReadWrite => INV_READ | INV_WRITE,
+        }
+    }
+}
+
+/// An open large object.
+///
+/// It implements `Read`, `Write` and `Seek`, with each call making a round trip to the server, so it should be
+/// wrapped in a `BufReader` or `BufWriter` when accessed in small pieces.
+///
+/// The descriptor is closed by the server at the end of the transaction. Use the `close` method to close it earlier.
+pub struct LargeObject<'a> {
+    connection: ConnectionRef<'a>,
+    transaction: &'a tokio_postgres::Transaction<'a>,
+    oid: Oid,
+    fd: i32,
+}
+
+impl<'a> LargeObject<'a> {
+    pub(crate) fn open(
+        mut connection: ConnectionRef<'a>,
+        transaction: &'a tokio_postgres::Transaction<'a>,
+        oid: Oid,
+        mode: Mode,
+    ) -> Result<LargeObject<'a>, Error> {
+        let row = connection.block_on(transaction.query_typed(
+            "SELECT lo_open($1, $2)",
+            &[(&oid, Type::OID), (&mode.flags(), Type::INT4)],
+        ))?;
+        let fd = row[0].try_get(0)?;
+
+        Ok(LargeObject {
+            connection,
+            transaction,
+            oid,
+            fd,
+        })
+    }
+
+    /// Returns the OID of the large object.
+    pub fn oid(&self) -> Oid {
+        self.oid
+    }
+
+    /// Truncates or extends the large object to `len` bytes.
+    ///
+    /// The object is extended with zero bytes.
+    pub fn truncate(&mut self, len: u64) -> Result<(), Error> {
+        let len = i64::try_from(len).unwrap_or(i64::MAX);
+        self.query("SELECT lo_truncate64($1, $2)", &[(&len, Type::INT8)])?;
+        Ok(())
+    }
+
+    /// Closes the large object's descriptor.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.query("SELECT lo_close($1)", &[])?;
+        Ok(())
+    }
+
+    // runs a query against the object's descriptor, which is always passed as the first parameter
+    fn query(
+        &mut self,
+        query: &str,
+        params: &[(&(dyn ToSql + Sync), Type)],
+    ) -> Result<tokio_postgres::Row, Error> {
+        let mut all = vec![(&self.fd as &(dyn ToSql + Sync), Type::INT4)];
+        all.extend_from_slice(params);
+        let mut rows = self
+            .connection
+            .block_on(self.transaction.query_typed(query, &all))?;
+        Ok(rows.remove(0))
+    }
+}
+
+impl Read for LargeObject<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(MAX_CHUNK_SIZE) as i32;
+        let row = self
+            .query("SELECT loread($1, $2)", &[(&len, Type::INT4)])
+            .map_err(io::Error::other)?;
+        let data = row.try_get::<_, &[u8]>(0).map_err(io::Error::other)?;
+        buf[..data.len()].copy_from_slice(data);
+        Ok(data.len())
+    }
+}
+
+impl Write for LargeObject<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let data = &buf[..buf.len().min(MAX_CHUNK_SIZE)];
+        let row = self
+            .query("SELECT lowrite($1, $2)", &[(&data, Type::BYTEA)])
+            .map_err(io::Error::other)?;
+        let len = row.try_get::<_, i32>(0).map_err(io::Error::other)?;
+        Ok(len as usize)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for LargeObject<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (offset, whence) = match pos {
+            SeekFrom::Start(offset) => (
+                i64::try_from(offset)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek"))?,
+                SEEK_SET,
+            ),
+            SeekFrom::Current(offset) => (offset, SEEK_CUR),
+            SeekFrom::End(offset) => (offset, SEEK_END),
+        };
+        let row = self
+            .query(
+                "SELECT lo_lseek64($1, $2, $3)",
+                &[(&offset, Type::INT8), (&whence, Type::INT4)],
+            )
+            .map_err(io::Error::other)?;
+        let pos = row.try_get::<_, i64>(0).map_err(io::Error::other)?;
+        Ok(pos as u64)
+    }
+}
@@ -38,7 +38,8 @@
 //!
 //! This crate is a lightweight wrapper over tokio-postgres. The `postgres::Client` is simply a wrapper around a
 //! `tokio_postgres::Client` along side a tokio `Runtime`. The client simply blocks on the futures provided by the async
-//! client.
+//! client. By default each client creates its own single-threaded runtime, but applications which already run a
+//! multi-threaded runtime can share it between clients with `Config::runtime_handle`.
 //!
 //! # SSL/TLS support
 //!
@@ -53,12 +54,16 @@
 //!
 //! | Feature | Description | Extra dependencies | Default |
 //! | ------- | ----------- | ------------------ | ------- |
+//! | `with-bigdecimal-0_4` | Enable support for the 0.4 version of the `bigdecimal` crate. | [bigdecimal](https://crates.io/crates/bigdecimal/0.4.0) 0.4 | no |
 //! | `with-bit-vec-0_6` | Enable support for the `bit-vec` crate. | [bit-vec](https://crates.io/crates/bit-vec) 0.6 | no |
 //! | `with-chrono-0_4` | Enable support for the `chrono` crate. | [chrono](https://crates.io/crates/chrono) 0.4 | no |
 //! | `with-eui48-0_4` | Enable support for the 0.4 version of the `eui48` crate. This is deprecated and will be removed. | [eui48](https://crates.io/crates/eui48) 0.4 | no |
 //! | `with-eui48-1` | Enable support for the 1.0 version of the `eui48` crate. | [eui48](https://crates.io/crates/eui48) 1.0 | no |
 //! | `with-geo-types-0_6` | Enable support for the 0.6 version of the `geo-types` crate. | [geo-types](https://crates.io/crates/geo-types/0.6.0) 0.6 | no |
 //! | `with-geo-types-0_7` | Enable support for the 0.7 version of the `geo-types` crate. | [geo-types](https://crates.io/crates/geo-types/0.7.0) 0.7 | no |
+//! | `with-ipnetwork-0_21` | Enable support for the 0.21 version of the `ipnetwork` crate. | [ipnetwork](https://crates.io/crates/ipnetwork/0.21.0) 0.21 | no |
+//! | `with-macaddr-1` | Enable support for the `macaddr` crate. | [macaddr](https://crates.io/crates/macaddr) 1.0 | no |
+//! | `with-rust_decimal-1` | Enable support for the `rust_decimal` crate. | [rust_decimal](https://crates.io/crates/rust_decimal) 1.0 | no |
 //! | `with-serde_json-1` | Enable support for the `serde_json` crate. | [serde_json](https://crates.io/crates/serde_json) 1.0 | no |
 //! | `with-uuid-0_8` | Enable support for the `uuid` crate. | [uuid](https://crates.io/crates/uuid) 0.8 | no |
 //! | `with-uuid-1` | Enable support for the `uuid` crate. | [uuid](https://crates.io/crates/uuid) 1.0 | no |
@@ -68,8 +73,8 @@
 
 pub use fallible_iterator;
 pub use tokio_postgres::{
-    error, row, tls, types, Column, IsolationLevel, Notification, Portal, SimpleQueryMessage,
-    Socket, Statement, ToStatement,
+    error, gss, row, tls, types, Column, CopyProgress, IsolationLevel, Notification, Portal,
+    Resolver, SimpleQueryMessage, Socket, Statement, ToStatement,
 };
 
 pub use crate::cancel_token::CancelToken;
@@ -98,8 +103,11 @@ mod connection;
 mod copy_in_writer;
 mod copy_out_reader;
 mod generic_client;
+pub mod large_object;
 mod lazy_pin;
 pub mod notifications;
+pub mod pool;
+pub mod replication;
 mod row_iter;
 mod transaction;
 mod transaction_builder;
@@ -70,6 +70,35 @@ impl<'a> Notifications<'a> {
             connection: self.connection.as_ref(),
         }
     }
+
+    /// Waits for a single notification until the provided deadline.
+    ///
+    /// A buffered pending notification is returned immediately. Otherwise, this blocks waiting on the PostgreSQL backend
+    /// server to send one, without issuing a query, until the deadline passes. A deadline in the past polls the
+    /// connection once without blocking. A return value of `None` either indicates that no notification arrived in
+    /// time or that the server has disconnected.
+    pub fn poll_with_deadline(
+        &mut self,
+        deadline: std::time::Instant,
+    ) -> Result<Option<Notification>, Error> {
+        if let Some(notification) = self.connection.notifications_mut().pop_front() {
+            return Ok(Some(notification));
+        }
+
+        let mut delay = Box::pin(
+            self.connection
+                .enter(|| time::sleep_until(Instant::from_std(deadline))),
+        );
+        self.connection
+            .poll_block_on(|cx, notifications, done| match notifications.pop_front() {
+                Some(notification) => Poll::Ready(Ok(Some(notification))),
+                None if done => Poll::Ready(Ok(None)),
+                None => {
+                    ready!(delay.poll_unpin(cx));
+                    Poll::Ready(Ok(None))
+                }
+            })
+    }
 }
 
 /// A nonblocking iterator over pending notifications.
@@ -0,0 +1,348 @@
+//! A connection pool.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use postgres::pool::Pool;
+//! use postgres::NoTls;
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<(), postgres::Error> {
+//! let config = "host=localhost user=postgres".parse()?;
+//! let pool = Pool::builder(config, NoTls)
+//!     .max_size(16)
+//!     .checkout_timeout(Duration::from_secs(5))
+//!     .build();
+//!
+//! let mut client = pool.get()?;
+//! client.execute("UPDATE foo SET bar = 1", &[])?;
+//! // The connection is returned to the pool when `client` is dropped.
+//! # Ok(())
+//! # }
+//! ```
+use crate::{Client, Config, Error};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio_postgres::tls::{MakeTlsConnect, TlsConnect};
+use tokio_postgres::Socket;
+
+/// A builder for [`Pool`]s.
+pub struct PoolBuilder<T> {
+    config: Config,
+    tls: T,
+    max_size: usize,
+    idle_timeout: Option<Duration>,
+    checkout_timeout: Option<Duration>,
+    test_on_checkout: bool,
+}
+
+impl<T> PoolBuilder<T> {
+    /// Sets the maximum number of connections managed by the pool.
+    ///
+    /// Defaults to 10.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is 0.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        assert!(max_size > 0, "max_size must be positive");
+        self.max_size = max_size;
+        self
+    }
+
+    /// Sets the amount of time a connection may sit idle in the pool before it is closed.
+    ///
+    /// The pool has no background thread, so expired connections are closed the next time a connection is checked out
+    /// or returned. Defaults to no timeout.
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Sets the maximum amount of time [`Pool::get`] will wait for a connection to be returned when the pool is at its
+    /// maximum size.
+    ///
+    /// Defaults to waiting indefinitely.
+    pub fn checkout_timeout(mut self, checkout_timeout: Duration) -> Self {
+        self.checkout_timeout = Some(checkout_timeout);
+        self
+    }
+
+    /// Determines if idle connections are checked with an empty query before they are handed out.
+    ///
+    /// Connections which fail the check are discarded and replaced. Defaults to `true`.
+    pub fn test_on_checkout(mut self, test_on_checkout: bool) -> Self {
+        self.test_on_checkout = test_on_checkout;
+        self
+    }
+
+    /// Creates the pool.
+    ///
+    /// No connections are opened until the first call to [`Pool::get`].
+    pub fn build(self) -> Pool<T> {
+        Pool {
+            inner: Arc::new(Inner {
+                config: self.config,
+                tls: self.tls,
+                max_size: self.max_size,
+                idle_timeout: self.idle_timeout,
+                checkout_timeout: self.checkout_timeout,
+                test_on_checkout: self.test_on_checkout,
+                state: Mutex::new(State {
+                    idle: vec![],
+                    size: 0,
+                }),
+                returned: Condvar::new(),
+            }),
+        }
+    }
+}
+
+/// Information about the state of a [`Pool`].
+#[derive(Debug, Copy, Clone)]
+#[non_exhaustive]
+pub struct Status {
+    /// The maximum number of connections managed by the pool.
+    pub max_size: usize,
+    /// The number of open connections, both idle and checked out.
+    pub size: usize,
+    /// The number of idle connections.
+    pub idle: usize,
+}
+
+/// A blocking pool of database connections.
+///
+/// Connections are opened on demand up to the configured maximum size. Once that limit is reached, [`Pool::get`] blocks
+/// until a connection is returned or the checkout timeout expires.
+///
+/// The pool is reference counted and can be shared between threads; clones refer to the same set of connections.
+pub struct Pool<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Pool<T> {
+        Pool {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("status", &self.status())
+            .finish()
+    }
+}
+
+impl<T> Pool<T> {
+    /// Returns a builder for a pool which connects with `config` and `tls`.
+    pub fn builder(config: Config, tls: T) -> PoolBuilder<T> {
+        PoolBuilder {
+            config,
+            tls,
+            max_size: 10,
+            idle_timeout: None,
+            checkout_timeout: None,
+            test_on_checkout: true,
+        }
+    }
+
+    /// Returns information about the current state of the pool.
+    pub fn status(&self) -> Status {
+        let state = self.inner.lock();
+        Status {
+            max_size: self.inner.max_size,
+            size: state.size,
+            idle: state.idle.len(),
+        }
+    }
+}
+
+impl<T> Pool<T>
+where
+    T: MakeTlsConnect<Socket> + Clone + 'static + Send,
+    T::TlsConnect: Send,
+    T::Stream: Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    /// Checks out a connection from the pool, opening a new one if none are idle.
+    ///
+    /// If the pool is at its maximum size, this blocks until a connection is returned. An error for which
+    /// [`Error::is_timeout`] returns `true` is returned if the checkout timeout expires first.
+    ///
+    /// The connection is returned to the pool when the [`PooledClient`] is dropped.
+    pub fn get(&self) -> Result<PooledClient<T>, Error> {
+        let deadline = self
+            .inner
+            .checkout_timeout
+            .map(|timeout| Instant::now() + timeout);
+
+        let mut state = self.inner.lock();
+        loop {
+            let expired = self.inner.prune(&mut state);
+            if !expired.is_empty() {
+                // closing a connection talks to the server, so don't hold the lock while doing it
+                drop(state);
+                drop(expired);
+                state = self.inner.lock();
+                continue;
+            }
+
+            if let Some(idle) = state.idle.pop() {
+                drop(state);
+
+                let mut client = idle.client;
+                if !self.inner.test_on_checkout || client.simple_query("").is_ok() {
+                    return Ok(PooledClient {
+                        client: Some(client),
+                        pool: self.inner.clone(),
+                    });
+                }
+
+                drop(client);
+                state = self.inner.lock();
+                state.size -= 1;
+                continue;
+            }
+
+            if state.size < self.inner.max_size {
+                state.size += 1;
+                drop(state);
+
+                return match self.inner.config.connect(self.inner.tls.clone()) {
+                    Ok(client) => Ok(PooledClient {
+                        client: Some(client),
+                        pool: self.inner.clone(),
+                    }),
+                    Err(e) => {
+                        self.inner.lock().size -= 1;
+                        self.inner.returned.notify_one();
+                        Err(e)
+                    }
+                };
+            }
+
+            state = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(Error::__private_api_timeout());
+                    }
+                    self.inner
+                        .returned
+                        .wait_timeout(state, deadline - now)
+                        .unwrap()
+                        .0
+                }
+                None => self.inner.returned.wait(state).unwrap(),
+            };
+        }
+    }
+}
+
+struct IdleClient {
+    client: Client,
+    since: Instant,
+}
+
+struct State {
+    idle: Vec<IdleClient>,
+    size: usize,
+}
+
+struct Inner<T> {
+    config: Config,
+    tls: T,
+    max_size: usize,
+    idle_timeout: Option<Duration>,
+    checkout_timeout: Option<Duration>,
+    test_on_checkout: bool,
+    state: Mutex<State>,
+    returned: Condvar,
+}
+
+impl<T> Inner<T> {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    // removes idle connections which have expired or been closed by the server
+    fn prune(&self, state: &mut State) -> Vec<IdleClient> {
+        let (expired, live) = state.idle.drain(..).partition::<Vec<_>, _>(|idle| {
+            let expired = match self.idle_timeout {
+                Some(idle_timeout) => idle.since.elapsed() >= idle_timeout,
+                None => false,
+            };
+            expired || idle.client.is_closed()
+        });
+        state.idle = live;
+        state.size -= expired.len();
+        expired
+    }
+}
+
+/// A connection checked out from a [`Pool`].
+///
+/// It dereferences to a [`Client`], and returns the connection to the pool when dropped.
+pub struct PooledClient<T> {
+    client: Option<Client>,
+    pool: Arc<Inner<T>>,
+}
+
+impl<T> PooledClient<T> {
+    /// Removes the connection from the pool.
+    ///
+    /// The pool will open a new connection in its place when needed.
+    pub fn detach(mut self) -> Client {
+        self.pool.lock().size -= 1;
+        self.pool.returned.notify_one();
+        self.client.take().unwrap()
+    }
+}
+
+impl<T> Deref for PooledClient<T> {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for PooledClient<T> {
+    fn deref_mut(&mut self) -> &mut Client {
+        self.client.as_mut().unwrap()
+    }
+}
+
+impl<T> fmt::Debug for PooledClient<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledClient").finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for PooledClient<T> {
+    fn drop(&mut self) {
+        let client = match self.client.take() {
+            Some(client) => client,
+            None => return,
+        };
+
+        let mut state = self.pool.lock();
+        if client.is_closed() {
+            state.size -= 1;
+        } else {
+            state.idle.push(IdleClient {
+                client,
+                since: Instant::now(),
+            });
+        }
+        let expired = self.pool.prune(&mut state);
+        drop(state);
+        self.pool.returned.notify_one();
+        drop(expired);
+    }
+}
@@ -0,0 +1,243 @@
+//! Utilities for working with the PostgreSQL replication copy both format.
+//!
+//! Replication connections are opened by setting a [`ReplicationMode`] on the connection's configuration. Once the
+//! client has issued `START_REPLICATION` with [`Client::replication_stream`] or
+//! [`Client::logical_replication_stream`], the returned iterator yields replication messages from the server and can
+//! send progress reports back to it.
+//!
+//! The server does not advance a replication slot until the client reports its progress, so clients should
+//! periodically send standby status updates, and must respond promptly to keepalive messages which request a reply.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fallible_iterator::FallibleIterator;
+//! use postgres::config::{Config, ReplicationMode};
+//! use postgres::types::PgLsn;
+//! use postgres::{Error, NoTls};
+//! use postgres_protocol::message::replication::{LogicalReplicationMessage, ReplicationMessage};
+//! use std::time::SystemTime;
+//!
+//! # fn main() -> Result<(), Error> {
+//! let mut config = "host=localhost user=postgres dbname=postgres".parse::<Config>()?;
+//! config.replication_mode(ReplicationMode::Logical);
+//! let mut client = config.connect(NoTls)?;
+//!
+//! client.simple_query("CREATE_REPLICATION_SLOT my_slot TEMPORARY LOGICAL pgoutput")?;
+//! let mut stream = client.logical_replication_stream(
+//!     "START_REPLICATION SLOT my_slot LOGICAL 0/0 \
+//!      (proto_version '1', publication_names 'my_publication')",
+//! )?;
+//!
+//! while let Some(message) = stream.next()? {
+//!     match message {
+//!         ReplicationMessage::XLogData(body) => {
+//!             if let LogicalReplicationMessage::Commit(commit) = body.data() {
+//!                 let lsn = PgLsn::from(commit.end_lsn());
+//!                 stream.standby_status_update(lsn, lsn, lsn, SystemTime::now(), false)?;
+//!             }
+//!         }
+//!         ReplicationMessage::PrimaryKeepAlive(body) => {
+//!             if body.reply() == 1 {
+//!                 let lsn = PgLsn::from(body.wal_end());
+//!                 stream.standby_status_update(lsn, lsn, lsn, SystemTime::now(), false)?;
+//!             }
+//!         }
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ReplicationMode`]: crate::config::ReplicationMode
+//! [`Client::replication_stream`]: crate::Client::replication_stream
+//! [`Client::logical_replication_stream`]: crate::Client::logical_replication_stream
+
+use crate::connection::ConnectionRef;
+use bytes::Bytes;
+use fallible_iterator::FallibleIterator;
+use futures_util::StreamExt;
+use postgres_protocol::message::replication::{LogicalReplicationMessage, ReplicationMessage};
+use std::io::{self, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::SystemTime;
+use tokio::io::AsyncWrite;
+use tokio_postgres::replication::{LogicalReplicationStream, ReplicationStream};
+use tokio_postgres::types::PgLsn;
+use tokio_postgres::Error;
+
+#[doc(inline)]
+pub use tokio_postgres::replication::{BaseBackup, IdentifySystem, TimelineHistory};
+
+/// A blocking iterator over streaming replication messages.
+///
+/// The data of `XLogData` messages is returned undecoded.
+pub struct ReplicationIter<'a> {
+    connection: ConnectionRef<'a>,
+    stream: Pin<Box<ReplicationStream>>,
+}
+
+impl<'a> ReplicationIter<'a> {
+    pub(crate) fn new(
+        connection: ConnectionRef<'a>,
+        stream: ReplicationStream,
+    ) -> ReplicationIter<'a> {
+        ReplicationIter {
+            connection,
+            stream: Box::pin(stream),
+        }
+    }
+
+    /// Sends a standby status update to the server, reporting the client's progress.
+    ///
+    /// The server may discard WAL up to the flushed location. If `reply` is set, the server will immediately respond
+    /// with a keepalive message.
+    pub fn standby_status_update(
+        &mut self,
+        write_lsn: PgLsn,
+        flush_lsn: PgLsn,
+        apply_lsn: PgLsn,
+        ts: SystemTime,
+        reply: bool,
+    ) -> Result<(), Error> {
+        self.connection.block_on(
+            self.stream
+                .as_mut()
+                .standby_status_update(write_lsn, flush_lsn, apply_lsn, ts, reply),
+        )
+    }
+
+    /// Sends a hot standby feedback message to the server.
+    ///
+    /// A transaction ID of 0 indicates that the corresponding xmin is not being reported.
+    pub fn hot_standby_feedback(
+        &mut self,
+        ts: SystemTime,
+        global_xmin: u32,
+        global_xmin_epoch: u32,
+        catalog_xmin: u32,
+        catalog_xmin_epoch: u32,
+    ) -> Result<(), Error> {
+        self.connection
+            .block_on(self.stream.as_mut().hot_standby_feedback(
+                ts,
+                global_xmin,
+                global_xmin_epoch,
+                catalog_xmin,
+                catalog_xmin_epoch,
+            ))
+    }
+
+    /// Finishes the client's side of the replication stream, waiting for the server to complete the command.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.connection.block_on(self.stream.as_mut().close())
+    }
+}
+
+impl FallibleIterator for ReplicationIter<'_> {
+    type Item = ReplicationMessage<Bytes>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<ReplicationMessage<Bytes>>, Error> {
+        let stream = &mut self.stream;
+        self.connection
+            .block_on(async { stream.next().await.transpose() })
+    }
+}
+
+/// A blocking iterator over logical replication messages produced by the `pgoutput` plugin.
+///
+/// The data of `XLogData` messages is decoded into `LogicalReplicationMessage`s.
+pub struct LogicalReplicationIter<'a> {
+    connection: ConnectionRef<'a>,
+    stream: Pin<Box<LogicalReplicationStream>>,
+}
+
+impl<'a> LogicalReplicationIter<'a> {
+    pub(crate) fn new(
+        connection: ConnectionRef<'a>,
+        stream: LogicalReplicationStream,
+    ) -> LogicalReplicationIter<'a> {
+        LogicalReplicationIter {
+            connection,
+            stream: Box::pin(stream),
+        }
+    }
+
+    /// Like `ReplicationIter::standby_status_update`.
+    pub fn standby_status_update(
+        &mut self,
+        write_lsn: PgLsn,
+        flush_lsn: PgLsn,
+        apply_lsn: PgLsn,
+        ts: SystemTime,
+        reply: bool,
+    ) -> Result<(), Error> {
+        self.connection.block_on(
+            self.stream
+                .as_mut()
+                .standby_status_update(write_lsn, flush_lsn, apply_lsn, ts, reply),
+        )
+    }
+
+    /// Like `ReplicationIter::hot_standby_feedback`.
+    pub fn hot_standby_feedback(
+        &mut self,
+        ts: SystemTime,
+        global_xmin: u32,
+        global_xmin_epoch: u32,
+        catalog_xmin: u32,
+        catalog_xmin_epoch: u32,
+    ) -> Result<(), Error> {
+        self.connection
+            .block_on(self.stream.as_mut().hot_standby_feedback(
+                ts,
+                global_xmin,
+                global_xmin_epoch,
+                catalog_xmin,
+                catalog_xmin_epoch,
+            ))
+    }
+
+    /// Like `ReplicationIter::close`.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.connection.block_on(self.stream.as_mut().close())
+    }
+}
+
+impl FallibleIterator for LogicalReplicationIter<'_> {
+    type Item = ReplicationMessage<LogicalReplicationMessage>;
+    type Error = Error;
+
+    fn next(&mut self) -> Result<Option<ReplicationMessage<LogicalReplicationMessage>>, Error> {
+        let stream = &mut self.stream;
+        self.connection
+            .block_on(async { stream.next().await.transpose() })
+    }
+}
+
+// Adapts a blocking writer for the async base backup, which runs on the client's own runtime.
+pub(crate) struct BlockingWriter<W>(pub W);
+
+impl<W> AsyncWrite for BlockingWriter<W>
+where
+    W: Write + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(self.get_mut().0.write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.get_mut().0.flush())
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
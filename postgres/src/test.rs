@@ -1,15 +1,20 @@
-use std::io::{Read, Write};
+use postgres_protocol::message::replication::{
+    LogicalReplicationMessage, ReplicationMessage, TupleData,
+};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::str::FromStr;
 use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 use tokio_postgres::error::SqlState;
-use tokio_postgres::types::Type;
+use tokio_postgres::types::{PgLsn, Type};
 use tokio_postgres::NoTls;
 
 use super::*;
 use crate::binary_copy::{BinaryCopyInWriter, BinaryCopyOutIter};
+use crate::pool::Pool;
 use fallible_iterator::FallibleIterator;
+use postgres_types::{FromSqlRow, ToSqlRow};
 
 #[test]
 fn prepare() {
@@ -296,6 +301,92 @@ fn binary_copy_in() {
     assert_eq!(rows[1].get::<_, &str>(1), "timothy");
 }
 
+#[test]
+fn binary_copy_struct() {
+    #[derive(Debug, PartialEq, ToSqlRow, FromSqlRow)]
+    struct Person {
+        id: i32,
+        name: Option<String>,
+    }
+
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client
+        .simple_query("CREATE TEMPORARY TABLE foo (id INT, name TEXT)")
+        .unwrap();
+
+    let expected = vec![
+        Person {
+            id: 1,
+            name: Some("steven".to_string()),
+        },
+        Person { id: 2, name: None },
+    ];
+
+    let writer = client.copy_in("COPY foo FROM stdin BINARY").unwrap();
+    let mut writer = BinaryCopyInWriter::new(writer, &[Type::INT4, Type::TEXT]);
+    for person in &expected {
+        writer.write_struct(person).unwrap();
+    }
+    assert_eq!(writer.finish().unwrap(), 2);
+
+    let reader = client
+        .copy_out("COPY (SELECT id, name FROM foo ORDER BY id) TO STDOUT BINARY")
+        .unwrap();
+    let actual = BinaryCopyOutIter::new(reader, &[Type::INT4, Type::TEXT])
+        .map(|row| row.try_get_struct())
+        .collect::<Vec<Person>>()
+        .unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn copy_progress() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client
+        .simple_query("CREATE TEMPORARY TABLE foo (id INT, name TEXT)")
+        .unwrap();
+
+    let mut data = String::new();
+    for i in 0..1000 {
+        data.push_str(&format!("{0}\tname{0}\n", i));
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut writer = client.copy_in("COPY foo FROM stdin").unwrap();
+    writer.set_progress_callback({
+        let tx = tx.clone();
+        move |p| tx.send(p).unwrap()
+    });
+    for line in data.lines() {
+        writeln!(writer, "{}", line).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let reports = rx.try_iter().collect::<Vec<CopyProgress>>();
+    assert!(reports.len() > 1);
+    assert_eq!(reports.last().unwrap().bytes(), data.len() as u64);
+
+    let mut reader = client.copy_out("COPY foo TO stdout").unwrap();
+    reader.set_progress_callback({
+        let tx = tx.clone();
+        move |p| tx.send(p).unwrap()
+    });
+    io::copy(&mut reader, &mut io::sink()).unwrap();
+    drop(reader);
+    assert_eq!(rx.try_iter().last().unwrap().bytes(), data.len() as u64);
+
+    let writer = client.copy_in("COPY foo FROM stdin BINARY").unwrap();
+    let mut writer = BinaryCopyInWriter::new(writer, &[Type::INT4, Type::TEXT]);
+    writer.set_progress_callback(move |p| tx.send(p).unwrap());
+    for i in 0..1000 {
+        writer.write(&[&i, &"steven"]).unwrap();
+    }
+    writer.finish().unwrap();
+    assert_eq!(rx.try_iter().last().unwrap().rows(), Some(1000));
+}
+
 #[test]
 fn copy_out() {
     let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
@@ -478,6 +569,51 @@ fn notifications_timeout_iter() {
     assert_eq!(notifications[1].payload(), "world");
 }
 
+#[test]
+fn notifications_poll_with_deadline() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    client
+        .batch_execute(
+            "\
+        LISTEN notifications_poll_with_deadline;
+        NOTIFY notifications_poll_with_deadline, 'hello';
+    ",
+        )
+        .unwrap();
+
+    let notification = client
+        .notifications()
+        .poll_with_deadline(Instant::now())
+        .unwrap()
+        .unwrap();
+    assert_eq!(notification.payload(), "hello");
+
+    let start = Instant::now();
+    let notification = client
+        .notifications()
+        .poll_with_deadline(start + Duration::from_millis(100))
+        .unwrap();
+    assert!(notification.is_none());
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    thread::spawn(|| {
+        let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+        thread::sleep(Duration::from_millis(200));
+        client
+            .batch_execute("NOTIFY notifications_poll_with_deadline, 'world'")
+            .unwrap();
+    });
+
+    let notification = client
+        .notifications()
+        .poll_with_deadline(Instant::now() + Duration::from_secs(5))
+        .unwrap()
+        .unwrap();
+    assert_eq!(notification.payload(), "world");
+}
+
 #[test]
 fn notice_callback() {
     let (notice_tx, notice_rx) = mpsc::sync_channel(64);
@@ -505,6 +641,363 @@ fn check_send() {
     fn is_send<T: Send>() {}
 
     is_send::<Client>();
+    is_send::<Pool<NoTls>>();
     is_send::<Statement>();
     is_send::<Transaction<'_>>();
 }
+
+fn pool(max_size: usize) -> Pool<NoTls> {
+    let config = "host=localhost port=5433 user=postgres".parse().unwrap();
+    Pool::builder(config, NoTls).max_size(max_size).build()
+}
+
+fn backend_pid(client: &mut Client) -> i32 {
+    client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .unwrap()
+        .get(0)
+}
+
+#[test]
+fn pool_reuse() {
+    let pool = pool(2);
+
+    let mut client = pool.get().unwrap();
+    let pid = backend_pid(&mut client);
+    drop(client);
+
+    let status = pool.status();
+    assert_eq!(status.size, 1);
+    assert_eq!(status.idle, 1);
+
+    let mut client = pool.get().unwrap();
+    assert_eq!(backend_pid(&mut client), pid);
+}
+
+#[test]
+fn pool_checkout_timeout() {
+    let config = "host=localhost port=5433 user=postgres".parse().unwrap();
+    let pool = Pool::builder(config, NoTls)
+        .max_size(1)
+        .checkout_timeout(Duration::from_millis(100))
+        .build();
+
+    let client = pool.get().unwrap();
+    assert!(pool.get().unwrap_err().is_timeout());
+
+    let (tx, rx) = mpsc::channel();
+    let other = pool.clone();
+    let handle = thread::spawn(move || {
+        tx.send(()).unwrap();
+        let mut client = other.get().unwrap();
+        client.batch_execute("SELECT 1").unwrap();
+    });
+    rx.recv().unwrap();
+    thread::sleep(Duration::from_millis(20));
+    drop(client);
+    handle.join().unwrap();
+}
+
+#[test]
+fn pool_discard_broken() {
+    let pool = pool(1);
+
+    let mut client = pool.get().unwrap();
+    let pid = backend_pid(&mut client);
+    drop(client);
+
+    let mut other = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    other
+        .execute("SELECT pg_terminate_backend($1)", &[&pid])
+        .unwrap();
+
+    let mut client = pool.get().unwrap();
+    assert_ne!(backend_pid(&mut client), pid);
+    assert_eq!(pool.status().size, 1);
+}
+
+#[test]
+fn pool_detach() {
+    let pool = pool(1);
+
+    let mut client = pool.get().unwrap().detach();
+    assert_eq!(pool.status().size, 0);
+    client.batch_execute("SELECT 1").unwrap();
+
+    drop(pool.get().unwrap());
+    assert_eq!(pool.status().size, 1);
+}
+
+#[test]
+fn pool_idle_timeout() {
+    let config = "host=localhost port=5433 user=postgres".parse().unwrap();
+    let pool = Pool::builder(config, NoTls)
+        .idle_timeout(Duration::from_millis(100))
+        .build();
+
+    let mut client = pool.get().unwrap();
+    let pid = backend_pid(&mut client);
+    drop(client);
+    assert_eq!(pool.status().idle, 1);
+
+    thread::sleep(Duration::from_millis(200));
+    let mut client = pool.get().unwrap();
+    assert_ne!(backend_pid(&mut client), pid);
+    assert_eq!(pool.status().size, 1);
+}
+
+#[test]
+fn logical_replication() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    client
+        .batch_execute(
+            "DROP TABLE IF EXISTS sync_replication_test;
+             DROP PUBLICATION IF EXISTS sync_replication_test_pub;
+             CREATE TABLE sync_replication_test (id SERIAL PRIMARY KEY, name TEXT);
+             CREATE PUBLICATION sync_replication_test_pub FOR TABLE sync_replication_test;",
+        )
+        .unwrap();
+
+    let mut config = Config::from_str("host=localhost port=5433 user=postgres").unwrap();
+    config.replication_mode(config::ReplicationMode::Logical);
+    let mut repl_client = config.connect(NoTls).unwrap();
+    let slot = repl_client
+        .simple_query(
+            "CREATE_REPLICATION_SLOT sync_replication_test_slot TEMPORARY LOGICAL pgoutput",
+        )
+        .unwrap();
+    let lsn = match &slot[1] {
+        SimpleQueryMessage::Row(row) => row.get("consistent_point").unwrap().to_string(),
+        _ => panic!("unexpected message"),
+    };
+
+    client
+        .execute(
+            "INSERT INTO sync_replication_test (name) VALUES ('steven')",
+            &[],
+        )
+        .unwrap();
+
+    let query = format!(
+        "START_REPLICATION SLOT sync_replication_test_slot LOGICAL {} \
+         (proto_version '1', publication_names 'sync_replication_test_pub')",
+        lsn
+    );
+    let mut stream = repl_client.logical_replication_stream(&query).unwrap();
+
+    loop {
+        let body = match stream.next().unwrap().unwrap() {
+            ReplicationMessage::XLogData(body) => body.into_data(),
+            _ => continue,
+        };
+        match body {
+            LogicalReplicationMessage::Insert(insert) => match insert.tuple().tuple_data() {
+                [TupleData::Text(_), TupleData::Text(name)] => assert_eq!(&name[..], b"steven"),
+                data => panic!("unexpected tuple {:?}", data),
+            },
+            LogicalReplicationMessage::Commit(commit) => {
+                let lsn = PgLsn::from(commit.end_lsn());
+                stream
+                    .standby_status_update(lsn, lsn, lsn, SystemTime::now(), false)
+                    .unwrap();
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    stream.close().unwrap();
+    repl_client.simple_query("SELECT 1").unwrap();
+
+    client
+        .batch_execute(
+            "DROP PUBLICATION sync_replication_test_pub;
+             DROP TABLE sync_replication_test;",
+        )
+        .unwrap();
+}
+
+#[test]
+fn physical_replication() {
+    let mut repl_client = Client::connect(
+        "host=localhost port=5433 user=postgres replication=true",
+        NoTls,
+    )
+    .unwrap();
+    let system = repl_client.identify_system().unwrap();
+
+    let mut stream = repl_client
+        .start_physical_replication(None, system.xlogpos(), Some(system.timeline()))
+        .unwrap();
+
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    client.batch_execute("SELECT txid_current()").unwrap();
+
+    loop {
+        match stream.next().unwrap().unwrap() {
+            ReplicationMessage::XLogData(body) => {
+                assert!(u64::from(system.xlogpos()) <= body.wal_start());
+                break;
+            }
+            ReplicationMessage::PrimaryKeepAlive(body) => {
+                let lsn = PgLsn::from(body.wal_end());
+                stream
+                    .standby_status_update(lsn, lsn, lsn, SystemTime::now(), false)
+                    .unwrap();
+            }
+            _ => {}
+        }
+    }
+
+    stream.close().unwrap();
+    repl_client.identify_system().unwrap();
+}
+
+#[test]
+fn base_backup() {
+    let mut repl_client = Client::connect(
+        "host=localhost port=5433 user=postgres replication=true",
+        NoTls,
+    )
+    .unwrap();
+
+    let mut archive = vec![];
+    let backup = repl_client
+        .base_backup(
+            "LABEL 'sync_base_backup_test', CHECKPOINT 'fast', MANIFEST 'no'",
+            &mut archive,
+        )
+        .unwrap();
+    assert!(backup.start_lsn() <= backup.end_lsn());
+    assert!(archive
+        .chunks(512)
+        .any(|block| block.starts_with(b"PG_VERSION\0")));
+}
+
+#[test]
+fn large_object() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+    let mut transaction = client.transaction().unwrap();
+
+    let oid = transaction.create_large_object().unwrap();
+    let mut object = transaction
+        .open_large_object(oid, large_object::Mode::ReadWrite)
+        .unwrap();
+    assert_eq!(object.oid(), oid);
+    object.write_all(b"hello world").unwrap();
+
+    assert_eq!(object.seek(SeekFrom::Start(6)).unwrap(), 6);
+    let mut buf = String::new();
+    object.read_to_string(&mut buf).unwrap();
+    assert_eq!(buf, "world");
+
+    object.truncate(5).unwrap();
+    assert_eq!(object.seek(SeekFrom::End(0)).unwrap(), 5);
+    object.close().unwrap();
+
+    let mut object = transaction
+        .open_large_object(oid, large_object::Mode::Read)
+        .unwrap();
+    let mut buf = vec![];
+    object.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, b"hello");
+    assert!(object.write(b"!").is_err());
+    drop(object);
+
+    // the failed write aborted the transaction
+    transaction.rollback().unwrap();
+
+    let mut transaction = client.transaction().unwrap();
+    assert!(transaction
+        .open_large_object(oid, large_object::Mode::Read)
+        .is_err());
+}
+
+#[test]
+fn unlink_large_object() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let mut transaction = client.transaction().unwrap();
+    let oid = transaction.create_large_object().unwrap();
+    transaction.commit().unwrap();
+
+    let mut transaction = client.transaction().unwrap();
+    transaction.unlink_large_object(oid).unwrap();
+    assert!(transaction
+        .open_large_object(oid, large_object::Mode::Read)
+        .is_err());
+}
+
+#[test]
+fn query_with_timeout() {
+    let mut client = Client::connect("host=localhost port=5433 user=postgres", NoTls).unwrap();
+
+    let err = client
+        .query_with_timeout(
+            "SELECT pg_sleep(100)",
+            &[],
+            Duration::from_millis(100),
+            NoTls,
+        )
+        .unwrap_err();
+    assert!(err.is_timeout());
+
+    let err = client
+        .execute_with_timeout(
+            "SELECT pg_sleep(100)",
+            &[],
+            Duration::from_millis(100),
+            NoTls,
+        )
+        .unwrap_err();
+    assert!(err.is_timeout());
+
+    // the cancelled queries no longer block the connection
+    let start = Instant::now();
+    let rows = client
+        .query_with_timeout("SELECT 1", &[], Duration::from_secs(5), NoTls)
+        .unwrap();
+    assert_eq!(rows[0].get::<_, i32>(0), 1);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn runtime_handle() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let mut config = Config::from_str("host=localhost port=5433 user=postgres").unwrap();
+    config.runtime_handle(runtime.handle().clone());
+    let mut clients = (0..2)
+        .map(|_| config.connect(NoTls).unwrap())
+        .collect::<Vec<_>>();
+
+    for client in &mut clients {
+        let row = client.query_one("SELECT 1::INT", &[]).unwrap();
+        assert_eq!(row.get::<_, i32>(0), 1);
+    }
+
+    // timers are driven by the shared runtime as well
+    let mut notifications = clients[0].notifications();
+    assert!(notifications
+        .timeout_iter(Duration::from_millis(10))
+        .next()
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn runtime_handle_current_thread() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let mut config = Config::from_str("host=localhost port=5433 user=postgres").unwrap();
+    config.runtime_handle(runtime.handle().clone());
+    let err = config.connect(NoTls).err().unwrap();
+    assert!(err.to_string().contains("multi-threaded"), "{}", err);
+}
@@ -1,6 +1,7 @@
 use crate::connection::ConnectionRef;
+use crate::large_object::{LargeObject, Mode};
 use crate::{CancelToken, CopyInWriter, CopyOutReader, Portal, RowIter, Statement, ToStatement};
-use tokio_postgres::types::{BorrowToSql, ToSql, Type};
+use tokio_postgres::types::{BorrowToSql, Oid, ToSql, Type};
 use tokio_postgres::{Error, Row, SimpleQueryMessage};
 
 /// A representation of a PostgreSQL database transaction.
@@ -212,6 +213,30 @@ impl<'a> Transaction<'a> {
         Ok(CopyOutReader::new(self.connection.as_ref(), stream))
     }
 
+    /// Creates a new, empty large object, returning its OID.
+    pub fn create_large_object(&mut self) -> Result<Oid, Error> {
+        let row = self.query_one("SELECT lo_create(0)", &[])?;
+        row.try_get(0)
+    }
+
+    /// Opens the large object with the specified OID.
+    ///
+    /// See the [`large_object`](crate::large_object) module for details.
+    pub fn open_large_object(&mut self, oid: Oid, mode: Mode) -> Result<LargeObject<'_>, Error> {
+        LargeObject::open(
+            self.connection.as_ref(),
+            self.transaction.as_ref().unwrap(),
+            oid,
+            mode,
+        )
+    }
+
+    /// Deletes the large object with the specified OID.
+    pub fn unlink_large_object(&mut self, oid: Oid) -> Result<(), Error> {
+        self.query_one("SELECT lo_unlink($1)", &[&oid])?;
+        Ok(())
+    }
+
     /// Like `Client::simple_query`.
     pub fn simple_query(&mut self, query: &str) -> Result<Vec<SimpleQueryMessage>, Error> {
         self.connection
@@ -54,5 +54,19 @@ fn query_prepared(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, query_prepared);
+fn query_large_row(c: &mut Criterion) {
+    let (client, runtime) = setup();
+    let statement = runtime
+        .block_on(client.prepare("SELECT repeat('x', $1)"))
+        .unwrap();
+    c.bench_function("large_row", move |b| {
+        b.iter(|| {
+            runtime
+                .block_on(client.query_one(&statement, &[&(1i32 << 20)]))
+                .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, query_prepared, query_large_row);
 criterion_main!(benches);
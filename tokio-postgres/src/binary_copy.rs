@@ -1,6 +1,7 @@
 //! Utilities for working with the PostgreSQL binary copy format.
 
-use crate::types::{FromSql, IsNull, ToSql, Type, WrongType};
+use crate::copy_progress::{CopyProgress, Progress};
+use crate::types::{FromSql, FromSqlRow, IsNull, SqlRow, ToSql, ToSqlRow, Type, WrongType};
 use crate::{slice_iter, CopyInSink, CopyOutStream, Error};
 use byteorder::{BigEndian, ByteOrder};
 use bytes::{Buf, BufMut, Bytes, BytesMut};
@@ -27,6 +28,7 @@ pin_project! {
         sink: CopyInSink<Bytes>,
         types: Vec<Type>,
         buf: BytesMut,
+        progress: Progress,
     }
 }
 
@@ -42,9 +44,21 @@ impl BinaryCopyInWriter {
             sink,
             types: types.to_vec(),
             buf,
+            progress: Progress::rows(),
         }
     }
 
+    /// Registers a callback which is invoked with the progress of the copy each time a chunk of rows is sent to the
+    /// connection.
+    ///
+    /// Rows are buffered and sent in chunks of a few kilobytes, so the callback is not invoked for every row.
+    pub fn set_progress_callback<F>(self: Pin<&mut Self>, callback: F)
+    where
+        F: FnMut(CopyProgress) + Send + Sync + 'static,
+    {
+        self.project().progress.set_callback(callback);
+    }
+
     /// Writes a single row.
     ///
     /// # Panics
@@ -54,6 +68,20 @@ impl BinaryCopyInWriter {
         self.write_raw(slice_iter(values)).await
     }
 
+    /// Writes a single row from the fields of a value.
+    ///
+    /// The `ToSqlRow` trait can be derived for structs with the `derive` feature of `postgres-types`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of fields does not match the number of values expected.
+    pub async fn write_struct<T>(self: Pin<&mut Self>, value: &T) -> Result<(), Error>
+    where
+        T: ToSqlRow,
+    {
+        self.write_raw(value.to_sql_row()).await
+    }
+
     /// A maximally-flexible version of `write`.
     ///
     /// # Panics
@@ -92,8 +120,11 @@ impl BinaryCopyInWriter {
             BigEndian::write_i32(&mut this.buf[idx..], len);
         }
 
+        this.progress.add_row();
         if this.buf.len() > 4096 {
+            this.progress.add_bytes(this.buf.len());
             this.sink.send(this.buf.split().freeze()).await?;
+            this.progress.report();
         }
 
         Ok(())
@@ -106,7 +137,9 @@ impl BinaryCopyInWriter {
         let mut this = self.project();
 
         this.buf.put_i16(-1);
+        this.progress.add_bytes(this.buf.len());
         this.sink.send(this.buf.split().freeze()).await?;
+        this.progress.report();
         this.sink.finish().await
     }
 }
@@ -122,6 +155,7 @@ pin_project! {
         stream: CopyOutStream,
         types: Arc<Vec<Type>>,
         header: Option<Header>,
+        progress: Progress,
     }
 }
 
@@ -132,8 +166,17 @@ impl BinaryCopyOutStream {
             stream,
             types: Arc::new(types.to_vec()),
             header: None,
+            progress: Progress::rows(),
         }
     }
+
+    /// Registers a callback which is invoked with the progress of the copy each time a row is received.
+    pub fn set_progress_callback<F>(self: Pin<&mut Self>, callback: F)
+    where
+        F: FnMut(CopyProgress) + Send + Sync + 'static,
+    {
+        self.project().progress.set_callback(callback);
+    }
 }
 
 impl Stream for BinaryCopyOutStream {
@@ -147,6 +190,7 @@ impl Stream for BinaryCopyOutStream {
             Some(Err(e)) => return Poll::Ready(Some(Err(e))),
             None => return Poll::Ready(Some(Err(Error::closed()))),
         };
+        let chunk_len = chunk.len();
         let mut chunk = Cursor::new(chunk);
 
         let has_oids = match &this.header {
@@ -204,6 +248,10 @@ impl Stream for BinaryCopyOutStream {
             }
         }
 
+        this.progress.add_bytes(chunk_len);
+        this.progress.add_row();
+        this.progress.report();
+
         Poll::Ready(Some(Ok(BinaryCopyOutRow {
             buf: chunk.into_inner(),
             ranges,
@@ -270,4 +318,41 @@ impl BinaryCopyOutRow {
             Err(e) => panic!("error retrieving column {}: {}", idx, e),
         }
     }
+
+    /// Like `get_struct`, but returns a `Result` rather than panicking.
+    pub fn try_get_struct<'a, T>(&'a self) -> Result<T, Error>
+    where
+        T: FromSqlRow<'a>,
+    {
+        T::from_sql_row(self)
+    }
+
+    /// Deserializes the row into a value, one column per field.
+    ///
+    /// The `FromSqlRow` trait can be derived for structs with the `derive` feature of `postgres-types`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row has fewer columns than the value has fields, or if a column cannot be converted to the type
+    /// of its field.
+    pub fn get_struct<'a, T>(&'a self) -> T
+    where
+        T: FromSqlRow<'a>,
+    {
+        match self.try_get_struct() {
+            Ok(value) => value,
+            Err(e) => panic!("error deserializing row: {}", e),
+        }
+    }
+}
+
+impl SqlRow for BinaryCopyOutRow {
+    type Error = Error;
+
+    fn try_get<'a, T>(&'a self, idx: usize) -> Result<T, Error>
+    where
+        T: FromSql<'a>,
+    {
+        BinaryCopyOutRow::try_get(self, idx)
+    }
 }
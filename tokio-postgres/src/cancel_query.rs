@@ -1,5 +1,5 @@
-use crate::client::SocketConfig;
 use crate::config::{SslMode, SslNegotiation};
+use crate::socket_config::SocketConfig;
 use crate::tls::MakeTlsConnect;
 use crate::{cancel_query_raw, connect_socket, Error, Socket};
 use std::io;
@@ -26,18 +26,11 @@ where
     };
 
     let tls = tls
-        .make_tls_connect(config.hostname.as_deref().unwrap_or(""))
+        .make_tls_connect_for(&config)
         .map_err(|e| Error::tls(e.into()))?;
     let has_hostname = config.hostname.is_some();
 
-    let socket = connect_socket::connect_socket(
-        &config.addr,
-        config.port,
-        config.connect_timeout,
-        config.tcp_user_timeout,
-        config.keepalive.as_ref(),
-    )
-    .await?;
+    let socket = connect_socket::connect_socket(&config).await?;
 
     cancel_query_raw::cancel_query_raw(
         socket,
@@ -1,15 +1,25 @@
 use crate::config::{SslMode, SslNegotiation};
 use crate::tls::TlsConnect;
-#[cfg(feature = "runtime")]
-use crate::{cancel_query, client::SocketConfig, tls::MakeTlsConnect, Socket};
+#[cfg(feature = "runtime-core")]
+use crate::{cancel_query, rt, socket_config::SocketConfig, tls::MakeTlsConnect, Socket};
 use crate::{cancel_query_raw, Error};
+#[cfg(feature = "runtime-core")]
+use std::time::Duration;
 use tokio::io::{AsyncRead, AsyncWrite};
 
 /// The capability to request cancellation of in-progress queries on a
 /// connection.
+///
+/// With the `with-serde-1` Cargo feature, the token can be serialized so that a query can be cancelled from another
+/// process.
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "with-serde-1",
+    derive(serde_1::Serialize, serde_1::Deserialize),
+    serde(crate = "serde_1")
+)]
 pub struct CancelToken {
-    #[cfg(feature = "runtime")]
+    #[cfg(feature = "runtime-core")]
     pub(crate) socket_config: Option<SocketConfig>,
     pub(crate) ssl_mode: SslMode,
     pub(crate) ssl_negotiation: SslNegotiation,
@@ -18,6 +28,46 @@ pub struct CancelToken {
 }
 
 impl CancelToken {
+    /// Creates a token from the process ID and secret key of a connection.
+    ///
+    /// The token does not know the address of the server, so `cancel_query_raw` must be used to cancel queries with
+    /// it.
+    pub fn from_parts(
+        process_id: i32,
+        secret_key: i32,
+        ssl_mode: SslMode,
+        ssl_negotiation: SslNegotiation,
+    ) -> CancelToken {
+        CancelToken {
+            #[cfg(feature = "runtime-core")]
+            socket_config: None,
+            ssl_mode,
+            ssl_negotiation,
+            process_id,
+            secret_key,
+        }
+    }
+
+    /// Returns the process ID of the backend serving the connection.
+    pub fn process_id(&self) -> i32 {
+        self.process_id
+    }
+
+    /// Returns the secret key used to authenticate cancellation requests for the connection.
+    pub fn secret_key(&self) -> i32 {
+        self.secret_key
+    }
+
+    /// Returns the TLS mode used when connecting to cancel queries.
+    pub fn ssl_mode(&self) -> SslMode {
+        self.ssl_mode
+    }
+
+    /// Returns the TLS negotiation mode used when connecting to cancel queries.
+    pub fn ssl_negotiation(&self) -> SslNegotiation {
+        self.ssl_negotiation
+    }
+
     /// Attempts to cancel the in-progress query on the connection associated
     /// with this `CancelToken`.
     ///
@@ -29,8 +79,8 @@ impl CancelToken {
     /// normally, or that the connection associated with this token is still
     /// active.
     ///
-    /// Requires the `runtime` Cargo feature (enabled by default).
-    #[cfg(feature = "runtime")]
+    /// Requires the `runtime-core` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime-core")]
     pub async fn cancel_query<T>(&self, tls: T) -> Result<(), Error>
     where
         T: MakeTlsConnect<Socket>,
@@ -46,6 +96,28 @@ impl CancelToken {
         .await
     }
 
+    /// Like `cancel_query`, but returns an error if the cancellation request has not been sent within `timeout`.
+    ///
+    /// The deadline covers connecting to the server as well as sending the request, so an unreachable server does not
+    /// hold up the caller for the full connect timeout. When it passes, an error is returned for which
+    /// [`Error::is_timeout`] returns `true`.
+    ///
+    /// Requires the `runtime-core` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime-core")]
+    pub async fn cancel_query_with_timeout<T>(&self, tls: T, timeout: Duration) -> Result<(), Error>
+    where
+        T: MakeTlsConnect<Socket>,
+    {
+        let runtime = self
+            .socket_config
+            .as_ref()
+            .and_then(|config| config.runtime.as_ref());
+        match rt::timeout(runtime, timeout, self.cancel_query(tls)).await {
+            Some(r) => r,
+            None => Err(Error::timeout()),
+        }
+    }
+
     /// Like `cancel_query`, but uses a stream which is already connected to the server rather than opening a new
     /// connection itself.
     pub async fn cancel_query_raw<S, T>(&self, stream: S, tls: T) -> Result<(), Error>
@@ -2,36 +2,55 @@ use crate::codec::BackendMessages;
 use crate::config::{SslMode, SslNegotiation};
 use crate::connection::{Request, RequestMessages};
 use crate::copy_out::CopyOutStream;
-#[cfg(feature = "runtime")]
-use crate::keepalive::KeepaliveConfig;
+use crate::error::SqlState;
+use crate::hook::HookGuard;
+use crate::insert_many;
+use crate::listen::Listeners;
 use crate::query::RowStream;
+use crate::replication::{self, BaseBackup, IdentifySystem, ReplicationStream, TimelineHistory};
+#[cfg(feature = "runtime-core")]
+use crate::rt::{self, RuntimeHandle};
 use crate::simple_query::SimpleQueryStream;
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
+use crate::socket_config::SocketConfig;
+use crate::stats::Stats;
+use crate::time::Instant;
+#[cfg(feature = "runtime-core")]
 use crate::tls::MakeTlsConnect;
 use crate::tls::TlsConnect;
-use crate::types::{Oid, ToSql, Type};
-#[cfg(feature = "runtime")]
+use crate::types::{FromRowOwned, Oid, PgLsn, ToSql, Type};
+#[cfg(feature = "with-serde_json-1")]
+use crate::JsonNotificationStream;
+#[cfg(feature = "runtime-core")]
 use crate::Socket;
 use crate::{
-    copy_in, copy_out, prepare, query, simple_query, slice_iter, CancelToken, CopyInSink, Error,
-    Row, SimpleQueryMessage, Statement, ToStatement, Transaction, TransactionBuilder,
+    copy_both, copy_in, copy_out, cursor, prepare, query, simple_query, slice_iter, BatchResult,
+    CancelToken, ClientStats, CopyBothDuplex, CopyInSink, CursorStream, Error, NoticeStream,
+    NotificationStream, ParameterStream, Pipeline, QueryOptions, Row, RowStreamAs,
+    SimpleQueryMessage, Statement, StatementHook, TextRowStream, ToStatement, Transaction,
+    TransactionBuilder,
 };
 use bytes::{Buf, BytesMut};
 use fallible_iterator::FallibleIterator;
 use futures_channel::mpsc;
+#[cfg(feature = "runtime-core")]
+use futures_util::future::BoxFuture;
 use futures_util::{future, pin_mut, ready, StreamExt, TryStreamExt};
+#[cfg(feature = "runtime-core")]
+use log::info;
 use parking_lot::Mutex;
+use postgres_protocol::escape::escape_identifier;
 use postgres_protocol::message::backend::Message;
 use postgres_types::BorrowToSql;
+#[cfg(feature = "runtime-core")]
+use rand::rngs::StdRng;
+#[cfg(feature = "runtime-core")]
+use rand::{Rng, SeedableRng};
 use std::collections::HashMap;
 use std::fmt;
-#[cfg(feature = "runtime")]
-use std::net::IpAddr;
-#[cfg(feature = "runtime")]
-use std::path::PathBuf;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 use std::time::Duration;
 use tokio::io::{AsyncRead, AsyncWrite};
 
@@ -76,6 +95,9 @@ struct CachedTypeInfo {
     /// Corresponds to [TYPEINFO_QUERY](prepare::TYPEINFO_COMPOSITE_QUERY) (or
     /// its fallback).
     typeinfo_enum: Option<Statement>,
+    /// A statement for getting the element type of a multirange type from its OID.
+    /// Corresponds to [TYPEINFO_MULTIRANGE_QUERY](prepare::TYPEINFO_MULTIRANGE_QUERY).
+    typeinfo_multirange: Option<Statement>,
 
     /// Cache of types already looked up.
     types: HashMap<Oid, Type>,
@@ -84,15 +106,34 @@ struct CachedTypeInfo {
 pub struct InnerClient {
     sender: mpsc::UnboundedSender<Request>,
     cached_typeinfo: Mutex<CachedTypeInfo>,
+    listeners: Arc<Listeners>,
+    stats: Arc<Stats>,
 
     /// A buffer to use when writing out postgres commands.
     buffer: Mutex<BytesMut>,
 }
 
 impl InnerClient {
+    pub fn listeners(&self) -> &Listeners {
+        &self.listeners
+    }
+
     pub fn send(&self, messages: RequestMessages) -> Result<Responses, Error> {
+        self.send_inner(messages, false)
+    }
+
+    /// Like `send`, but also forwards the notices raised while processing the request as `NoticeResponse` messages.
+    pub fn send_with_notices(&self, messages: RequestMessages) -> Result<Responses, Error> {
+        self.send_inner(messages, true)
+    }
+
+    fn send_inner(&self, messages: RequestMessages, notices: bool) -> Result<Responses, Error> {
         let (sender, receiver) = mpsc::channel(1);
-        let request = Request { messages, sender };
+        let request = Request {
+            messages,
+            sender,
+            notices,
+        };
         self.sender
             .unbounded_send(request)
             .map_err(|_| Error::closed())?;
@@ -127,6 +168,14 @@ impl InnerClient {
         self.cached_typeinfo.lock().typeinfo_enum = Some(statement.clone());
     }
 
+    pub fn typeinfo_multirange(&self) -> Option<Statement> {
+        self.cached_typeinfo.lock().typeinfo_multirange.clone()
+    }
+
+    pub fn set_typeinfo_multirange(&self, statement: &Statement) {
+        self.cached_typeinfo.lock().typeinfo_multirange = Some(statement.clone());
+    }
+
     pub fn type_(&self, oid: Oid) -> Option<Type> {
         self.cached_typeinfo.lock().types.get(&oid).cloned()
     }
@@ -152,42 +201,31 @@ impl InnerClient {
     }
 }
 
-#[cfg(feature = "runtime")]
-#[derive(Clone)]
-pub(crate) struct SocketConfig {
-    pub addr: Addr,
-    pub hostname: Option<String>,
-    pub port: u16,
-    pub connect_timeout: Option<Duration>,
-    pub tcp_user_timeout: Option<Duration>,
-    pub keepalive: Option<KeepaliveConfig>,
-}
-
-#[cfg(feature = "runtime")]
-#[derive(Clone)]
-pub(crate) enum Addr {
-    Tcp(IpAddr),
-    #[cfg(unix)]
-    Unix(PathBuf),
-}
-
 /// An asynchronous PostgreSQL client.
 ///
 /// The client is one half of what is returned when a connection is established. Users interact with the database
 /// through this client object.
 pub struct Client {
     inner: Arc<InnerClient>,
-    #[cfg(feature = "runtime")]
+    #[cfg(feature = "runtime-core")]
     socket_config: Option<SocketConfig>,
     ssl_mode: SslMode,
     ssl_negotiation: SslNegotiation,
     process_id: i32,
     secret_key: i32,
+    hook: Option<Arc<dyn StatementHook>>,
+    retry_invalidated_plans: bool,
+    #[cfg(feature = "runtime-core")]
+    rng: Option<StdRng>,
+    #[cfg(feature = "runtime-core")]
+    runtime: Option<RuntimeHandle>,
 }
 
 impl Client {
     pub(crate) fn new(
         sender: mpsc::UnboundedSender<Request>,
+        listeners: Arc<Listeners>,
+        stats: Arc<Stats>,
         ssl_mode: SslMode,
         ssl_negotiation: SslNegotiation,
         process_id: i32,
@@ -197,14 +235,22 @@ impl Client {
             inner: Arc::new(InnerClient {
                 sender,
                 cached_typeinfo: Default::default(),
+                listeners,
+                stats,
                 buffer: Default::default(),
             }),
-            #[cfg(feature = "runtime")]
+            #[cfg(feature = "runtime-core")]
             socket_config: None,
             ssl_mode,
             ssl_negotiation,
             process_id,
             secret_key,
+            hook: None,
+            retry_invalidated_plans: false,
+            #[cfg(feature = "runtime-core")]
+            rng: None,
+            #[cfg(feature = "runtime-core")]
+            runtime: None,
         }
     }
 
@@ -212,11 +258,21 @@ impl Client {
         &self.inner
     }
 
-    #[cfg(feature = "runtime")]
+    #[cfg(feature = "runtime-core")]
     pub(crate) fn set_socket_config(&mut self, socket_config: SocketConfig) {
         self.socket_config = Some(socket_config);
     }
 
+    #[cfg(feature = "runtime-core")]
+    pub(crate) fn set_random_seed(&mut self, seed: u64) {
+        self.rng = Some(StdRng::seed_from_u64(seed));
+    }
+
+    #[cfg(feature = "runtime-core")]
+    pub(crate) fn set_runtime(&mut self, runtime: Option<RuntimeHandle>) {
+        self.runtime = runtime;
+    }
+
     /// Creates a new prepared statement.
     ///
     /// Prepared statements can be executed repeatedly, and may contain query parameters (indicated by `$1`, `$2`, etc),
@@ -253,12 +309,92 @@ impl Client {
     where
         T: ?Sized + ToStatement,
     {
-        self.query_raw(statement, slice_iter(params))
+        self.query_slice(statement, params)
             .await?
             .try_collect()
             .await
     }
 
+    /// Like `query`, but deserializes each row into a value via its `FromRow` implementation.
+    ///
+    /// `FromRow` can be derived for structs with the `derive` feature of `postgres-types`, matching fields to columns
+    /// by name.
+    pub async fn query_as<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, Error>
+    where
+        R: FromRowOwned,
+    {
+        self.query(statement, params)
+            .await?
+            .iter()
+            .map(R::from_row)
+            .collect()
+    }
+
+    /// Like `query_as`, but returns a stream of the deserialized values rather than collecting them.
+    ///
+    /// Rows are deserialized as they are received, so only a bounded number of rows are buffered at a time (see
+    /// [`Config::row_buffer_size`](crate::Config::row_buffer_size)) regardless of the size of the result set.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn async_main(client: &tokio_postgres::Client) -> Result<(), tokio_postgres::Error> {
+    /// use futures_util::{pin_mut, TryStreamExt};
+    /// use postgres_types::FromRow;
+    ///
+    /// #[derive(FromRow)]
+    /// struct Event {
+    ///     id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// let events = client.query_stream_as::<Event>("SELECT id, name FROM events", &[]).await?;
+    ///
+    /// pin_mut!(events);
+    /// while let Some(event) = events.try_next().await? {
+    ///     println!("{}: {}", event.id, event.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_stream_as<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<RowStreamAs<R>, Error>
+    where
+        R: FromRowOwned,
+    {
+        self.query_raw(statement, slice_iter(params))
+            .await
+            .map(RowStreamAs::new)
+    }
+
+    /// Inserts many rows into a table with a single statement, returning the number of rows inserted.
+    ///
+    /// Each row contains one value per column, in order. The table may be schema-qualified as `schema.table`. Each part
+    /// of the table name and each column name is quoted, so they are case sensitive, and a table name cannot itself
+    /// contain a `.`.
+    ///
+    /// Small batches are inserted with a multi-row `VALUES` list. Larger batches look up the column types and pass one
+    /// array per column to `UNNEST`, which keeps the statement size constant. Batches too large for a single `VALUES`
+    /// list that include array-typed columns are split across multiple statements, which are not executed atomically
+    /// unless the client is in a transaction.
+    ///
+    /// Returns an error if `columns` is empty or if the length of a row does not match the number of columns.
+    pub async fn insert_many(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[&[&(dyn ToSql + Sync)]],
+    ) -> Result<u64, Error> {
+        insert_many::insert_many(self, table, columns, rows).await
+    }
+
     /// Executes a statement which returns a single row, returning it.
     ///
     /// Returns an error if the query does not return exactly one row.
@@ -300,7 +436,7 @@ impl Client {
     where
         T: ?Sized + ToStatement,
     {
-        let stream = self.query_raw(statement, slice_iter(params)).await?;
+        let stream = self.query_slice(statement, params).await?;
         pin_mut!(stream);
 
         let mut first = None;
@@ -321,6 +457,89 @@ impl Client {
         Ok(first)
     }
 
+    /// Like [`query`], but cancels the query if it does not complete within `timeout`.
+    ///
+    /// When the deadline passes, a cancellation request is sent to the server using the `tls` implementation (see
+    /// [`CancelToken::cancel_query`]) and an error is returned for which [`Error::is_timeout`] returns `true`. The
+    /// cancellation request is itself given up to `timeout` to complete, so the call returns within about twice
+    /// `timeout`. The timeout error is returned even if the cancellation request fails or is abandoned.
+    ///
+    /// Requires the `runtime-core` Cargo feature (enabled by default).
+    ///
+    /// [`query`]: #method.query
+    #[cfg(feature = "runtime-core")]
+    pub async fn query_with_timeout<T, U>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        timeout: Duration,
+        tls: U,
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+        U: MakeTlsConnect<Socket>,
+    {
+        match rt::timeout(
+            self.runtime.as_ref(),
+            timeout,
+            self.query(statement, params),
+        )
+        .await
+        {
+            Some(r) => r,
+            None => {
+                self.cancel_after_timeout(timeout, tls).await;
+                Err(Error::timeout())
+            }
+        }
+    }
+
+    // The timeout is reported whether or not the cancellation request succeeds. The request is bounded by the same
+    // timeout as the statement, so that an unreachable server cannot hold up the caller for a TCP connect timeout.
+    #[cfg(feature = "runtime-core")]
+    async fn cancel_after_timeout<U>(&self, timeout: Duration, tls: U)
+    where
+        U: MakeTlsConnect<Socket>,
+    {
+        let cancel_token = self.cancel_token();
+        match rt::timeout(
+            self.runtime.as_ref(),
+            timeout,
+            cancel_token.cancel_query(tls),
+        )
+        .await
+        {
+            Some(Ok(())) => {}
+            Some(Err(e)) => info!("failed to cancel timed out statement: {}", e),
+            None => info!("timed out cancelling timed out statement"),
+        }
+    }
+
+    /// Like [`query`], but applies per-statement settings from `options`.
+    ///
+    /// See the documentation of [`QueryOptions`] for details.
+    ///
+    /// [`query`]: #method.query
+    pub async fn query_with_options<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        options: &QueryOptions,
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        let (statement, params, hook) = self.start_statement(statement, slice_iter(params)).await?;
+        let result = query::query_with_options(&self.inner, statement, params, options).await;
+        if let Some(hook) = hook {
+            match &result {
+                Ok((_, rows)) => hook.finish(Some(*rows)),
+                Err(e) => hook.fail(e),
+            }
+        }
+        result.map(|(rows, _)| rows)
+    }
+
     /// The maximally flexible version of [`query`].
     ///
     /// A statement may contain parameters, specified by `$n`, where `n` is the index of the parameter of the list
@@ -361,9 +580,139 @@ impl Client {
         P: BorrowToSql,
         I: IntoIterator<Item = P>,
         I::IntoIter: ExactSizeIterator,
+    {
+        let (statement, params, hook) = self.start_statement(statement, params).await?;
+        let result = query::query(&self.inner, statement, params).await;
+        Client::attach_hook(result, hook)
+    }
+
+    /// Like [`query_raw`], but requests results in text format.
+    ///
+    /// Rows are returned as [`SimpleQueryRow`]s, exposing each value as the `&str` the server would send for it in a
+    /// simple query. This avoids decoding every column's binary representation, which is useful when results are
+    /// forwarded or exported generically.
+    ///
+    /// [`query_raw`]: #method.query_raw
+    /// [`SimpleQueryRow`]: crate::SimpleQueryRow
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn async_main(client: &tokio_postgres::Client) -> Result<(), tokio_postgres::Error> {
+    /// use futures_util::{pin_mut, TryStreamExt};
+    ///
+    /// let it = client.query_raw_text("SELECT foo FROM bar WHERE biz = $1", &[&1i32]).await?;
+    ///
+    /// pin_mut!(it);
+    /// while let Some(row) = it.try_next().await? {
+    ///     let foo: Option<&str> = row.get("foo");
+    ///     println!("foo: {:?}", foo);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_raw_text<T, P, I>(
+        &self,
+        statement: &T,
+        params: I,
+    ) -> Result<TextRowStream, Error>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let (statement, params, hook) = self.start_statement(statement, params).await?;
+        match (
+            query::query_text(&self.inner, statement, params).await,
+            hook,
+        ) {
+            (Ok(mut stream), Some(hook)) => {
+                stream.set_hook(hook);
+                Ok(stream)
+            }
+            (Err(e), Some(hook)) => {
+                hook.fail(&e);
+                Err(e)
+            }
+            (result, None) => result,
+        }
+    }
+
+    async fn query_slice<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<RowStream, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        match self.query_raw(statement, slice_iter(params)).await {
+            Ok(stream) => Ok(stream),
+            Err(e) => {
+                let statement = self.replan(statement, e).await?;
+                self.query_raw(&statement, slice_iter(params)).await
+            }
+        }
+    }
+
+    // Re-prepares the statement if `error` was caused by its cached plan being invalidated and retrying is enabled,
+    // returning `error` otherwise.
+    async fn replan<T>(&self, statement: &T, error: Error) -> Result<Statement, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        let invalidated = error.code() == Some(&SqlState::FEATURE_NOT_SUPPORTED)
+            && error
+                .as_db_error()
+                .and_then(|e| e.routine())
+                .is_some_and(|r| r == "RevalidateCachedQuery");
+        if !self.retry_invalidated_plans || !invalidated {
+            return Err(error);
+        }
+
+        // keep the parameter types of the original statement, which may have been given to `prepare_typed`
+        let statement = statement.__convert();
+        match self
+            .prepare_typed(statement.query(), statement.param_types())
+            .await
+        {
+            Ok(statement) => Ok(statement),
+            // the error aborted the enclosing transaction, so the statement can't be retried
+            Err(e) if e.code() == Some(&SqlState::IN_FAILED_SQL_TRANSACTION) => Err(error),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Executes a statement, returning a stream of the resulting rows which are fetched from the server in batches of
+    /// `fetch_size` rows.
+    ///
+    /// Unlike `query_raw`, at most one batch of rows is requested from the server at a time, and the next batch is only
+    /// requested once the previous one has been consumed. This bounds the memory used by queries returning huge result
+    /// sets without requiring the statement to be bound to a portal inside of a transaction.
+    ///
+    /// The connection is reserved for the stream until it has been exhausted or dropped; requests made on the client
+    /// in the meantime are queued until then, so do not wait on them while holding the stream.
+    ///
+    /// The `statement` argument can either be a `Statement`, or a raw query string. If the same statement will be
+    /// repeatedly executed (perhaps with different query parameters), consider preparing the statement up front
+    /// with the `prepare` method.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fetch_size` is not positive, or if the number of parameters provided does not match the number
+    /// expected.
+    pub async fn query_cursor<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        fetch_size: i32,
+    ) -> Result<CursorStream, Error>
+    where
+        T: ?Sized + ToStatement,
     {
         let statement = statement.__convert().into_statement(self).await?;
-        query::query(&self.inner, statement, params).await
+        cursor::query_cursor(&self.inner, statement, slice_iter(params), fetch_size).await
     }
 
     /// Like `query`, but requires the types of query parameters to be explicitly specified.
@@ -427,7 +776,20 @@ impl Client {
         P: BorrowToSql,
         I: IntoIterator<Item = (P, Type)>,
     {
-        query::query_typed(&self.inner, query, params).await
+        let start = Instant::now();
+        let mut param_count = 0;
+        let buf = query::encode_typed(
+            &self.inner,
+            query,
+            params.into_iter().inspect(|_| param_count += 1),
+        );
+        let hook = self.start_hook(query, param_count, start);
+
+        let result = match buf {
+            Ok(buf) => query::query_typed(&self.inner, query, buf).await,
+            Err(e) => Err(e),
+        };
+        Client::attach_hook(result, hook)
     }
 
     /// Executes a statement, returning the number of rows modified.
@@ -448,7 +810,48 @@ impl Client {
     where
         T: ?Sized + ToStatement,
     {
-        self.execute_raw(statement, slice_iter(params)).await
+        match self.execute_raw(statement, slice_iter(params)).await {
+            Ok(rows) => Ok(rows),
+            Err(e) => {
+                let statement = self.replan(statement, e).await?;
+                self.execute_raw(&statement, slice_iter(params)).await
+            }
+        }
+    }
+
+    /// Like [`execute`], but cancels the statement if it does not complete within `timeout`.
+    ///
+    /// See [`query_with_timeout`] for details.
+    ///
+    /// Requires the `runtime-core` Cargo feature (enabled by default).
+    ///
+    /// [`execute`]: #method.execute
+    /// [`query_with_timeout`]: #method.query_with_timeout
+    #[cfg(feature = "runtime-core")]
+    pub async fn execute_with_timeout<T, U>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        timeout: Duration,
+        tls: U,
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+        U: MakeTlsConnect<Socket>,
+    {
+        match rt::timeout(
+            self.runtime.as_ref(),
+            timeout,
+            self.execute(statement, params),
+        )
+        .await
+        {
+            Some(r) => r,
+            None => {
+                self.cancel_after_timeout(timeout, tls).await;
+                Err(Error::timeout())
+            }
+        }
     }
 
     /// The maximally flexible version of [`execute`].
@@ -468,8 +871,40 @@ impl Client {
         I: IntoIterator<Item = P>,
         I::IntoIter: ExactSizeIterator,
     {
-        let statement = statement.__convert().into_statement(self).await?;
-        query::execute(self.inner(), statement, params).await
+        let (statement, params, hook) = self.start_statement(statement, params).await?;
+        let result = query::execute(self.inner(), statement, params).await;
+        if let Some(hook) = hook {
+            match &result {
+                Ok(rows) => hook.finish(Some(*rows)),
+                Err(e) => hook.fail(e),
+            }
+        }
+        result
+    }
+
+    /// Like [`execute`], but applies per-statement settings from `options`.
+    ///
+    /// See the documentation of [`QueryOptions`] for details.
+    ///
+    /// [`execute`]: #method.execute
+    pub async fn execute_with_options<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        options: &QueryOptions,
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        let (statement, params, hook) = self.start_statement(statement, slice_iter(params)).await?;
+        let result = query::query_with_options(&self.inner, statement, params, options).await;
+        if let Some(hook) = hook {
+            match &result {
+                Ok((_, rows)) => hook.finish(Some(*rows)),
+                Err(e) => hook.fail(e),
+            }
+        }
+        result.map(|(_, rows)| rows)
     }
 
     /// Executes a `COPY FROM STDIN` statement, returning a sink used to write the copy data.
@@ -496,6 +931,95 @@ impl Client {
         copy_out::copy_out(self.inner(), statement).await
     }
 
+    /// Executes a `COPY FROM STDIN` statement, reading the copy data from `reader`, and returns the number of rows
+    /// inserted.
+    ///
+    /// The data is sent to the server in chunks as it is read. If reading fails, or the returned future is dropped
+    /// before it completes, the copy is aborted.
+    pub async fn copy_in_from<T, R>(&self, statement: &T, reader: R) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+        R: AsyncRead + Unpin,
+    {
+        let statement = statement.__convert().into_statement(self).await?;
+        copy_in::copy_in_from(self.inner(), statement, reader).await
+    }
+
+    /// Executes a `COPY TO STDOUT` statement, writing the copy data to `writer`, and returns the number of rows
+    /// copied.
+    ///
+    /// The writer is flushed once all of the data has been written.
+    pub async fn copy_out_to<T, W>(&self, statement: &T, writer: W) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+        W: AsyncWrite + Unpin,
+    {
+        let statement = statement.__convert().into_statement(self).await?;
+        copy_out::copy_out_to(self.inner(), statement, writer).await
+    }
+
+    /// Executes a command which enters `CopyBoth` mode using the simple query protocol, returning a duplex stream of
+    /// the copy data.
+    ///
+    /// This is used by the streaming replication protocol, where commands like `START_REPLICATION` must be sent on a
+    /// connection opened with a [`ReplicationMode`](crate::config::ReplicationMode). See the [`replication`] module
+    /// for typed wrappers around the returned stream.
+    ///
+    /// [`replication`]: crate::replication
+    pub async fn copy_both_simple<T>(&self, query: &str) -> Result<CopyBothDuplex<T>, Error>
+    where
+        T: Buf + 'static + Send,
+    {
+        copy_both::copy_both_simple(self.inner(), query).await
+    }
+
+    /// Requests the server to identify itself, returning its system identifier, timeline, and WAL position.
+    ///
+    /// The client must have been opened as a replication connection.
+    pub async fn identify_system(&self) -> Result<IdentifySystem, Error> {
+        replication::identify_system(self).await
+    }
+
+    /// Requests the timeline history file for the specified timeline.
+    ///
+    /// The client must have been opened as a physical replication connection.
+    pub async fn timeline_history(&self, timeline: u32) -> Result<TimelineHistory, Error> {
+        replication::timeline_history(self, timeline).await
+    }
+
+    /// Starts streaming WAL from the specified location, returning a stream of replication messages.
+    ///
+    /// If a slot is provided, the server retains the WAL required by the slot until the client reports it as flushed.
+    /// If a timeline is not provided, the server's current timeline is used. The stream ends when the server switches
+    /// away from the timeline, after which [`ReplicationStream::close`] must be called before the client can be used
+    /// again.
+    ///
+    /// The client must have been opened as a physical replication connection.
+    pub async fn start_physical_replication(
+        &self,
+        slot: Option<&str>,
+        lsn: PgLsn,
+        timeline: Option<u32>,
+    ) -> Result<ReplicationStream, Error> {
+        replication::start_physical_replication(self, slot, lsn, timeline).await
+    }
+
+    /// Takes a base backup of the server, writing the archive data to `writer`.
+    ///
+    /// The `options` are passed to the `BASE_BACKUP` command as its parenthesized option list, for example
+    /// `LABEL 'nightly', CHECKPOINT 'fast', MANIFEST 'no'`. The server sends a tar archive of the data directory, followed
+    /// by one for each additional tablespace; all of them are written to `writer` one after another. The backup
+    /// manifest, if any, is not written.
+    ///
+    /// The client must have been opened as a physical replication connection to a server running PostgreSQL 15 or
+    /// later.
+    pub async fn base_backup<W>(&self, options: &str, writer: W) -> Result<BaseBackup, Error>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        replication::base_backup(self, options, writer).await
+    }
+
     /// Executes a sequence of SQL statements using the simple query protocol, returning the resulting rows.
     ///
     /// Statements should be separated by semicolons. If an error occurs, execution of the sequence will stop at that
@@ -510,7 +1034,34 @@ impl Client {
     /// functionality to safely embed that data in the request. Do not form statements via string concatenation and pass
     /// them to this method!
     pub async fn simple_query(&self, query: &str) -> Result<Vec<SimpleQueryMessage>, Error> {
-        self.simple_query_raw(query).await?.try_collect().await
+        let hook = self.start_hook(query, 0, Instant::now());
+
+        let result = async {
+            self.simple_query_raw(query)
+                .await?
+                .try_collect::<Vec<_>>()
+                .await
+        }
+        .await;
+        if let Some(mut hook) = hook {
+            match &result {
+                Ok(messages) => {
+                    let mut rows_affected = None;
+                    for message in messages {
+                        match message {
+                            SimpleQueryMessage::Row(_) => hook.add_row(),
+                            SimpleQueryMessage::CommandComplete(rows) => {
+                                rows_affected = Some(*rows)
+                            }
+                            _ => {}
+                        }
+                    }
+                    hook.finish(rows_affected);
+                }
+                Err(e) => hook.fail(e),
+            }
+        }
+        result
     }
 
     pub(crate) async fn simple_query_raw(&self, query: &str) -> Result<SimpleQueryStream, Error> {
@@ -528,7 +1079,44 @@ impl Client {
     /// functionality to safely embed that data in the request. Do not form statements via string concatenation and pass
     /// them to this method!
     pub async fn batch_execute(&self, query: &str) -> Result<(), Error> {
-        simple_query::batch_execute(self.inner(), query).await
+        let hook = self.start_hook(query, 0, Instant::now());
+
+        let result = simple_query::batch_execute(self.inner(), query).await;
+        if let Some(hook) = hook {
+            match &result {
+                Ok(()) => hook.finish(None),
+                Err(e) => hook.fail(e),
+            }
+        }
+        result
+    }
+
+    /// Like [`batch_execute`], but returns the outcome of each statement in the sequence.
+    ///
+    /// The returned vector contains one entry per statement executed, in order, with its command tag, the number of
+    /// rows it affected, and the notices the server raised while executing it. This is intended for use when, for
+    /// example, a migration runner needs to report what each statement of a script did. Notices are still also
+    /// delivered to [`notices`] streams and returned from `poll_message`.
+    ///
+    /// # Warning
+    ///
+    /// Prepared statements should be use for any query which contains user-specified data, as they provided the
+    /// functionality to safely embed that data in the request. Do not form statements via string concatenation and pass
+    /// them to this method!
+    ///
+    /// [`batch_execute`]: #method.batch_execute
+    /// [`notices`]: #method.notices
+    pub async fn batch_execute_results(&self, query: &str) -> Result<Vec<BatchResult>, Error> {
+        let hook = self.start_hook(query, 0, Instant::now());
+
+        let result = simple_query::batch_execute_results(self.inner(), query).await;
+        if let Some(hook) = hook {
+            match &result {
+                Ok(results) => hook.finish(results.last().map(BatchResult::rows_affected)),
+                Err(e) => hook.fail(e),
+            }
+        }
+        result
     }
 
     /// Begins a new database transaction.
@@ -538,6 +1126,130 @@ impl Client {
         self.build_transaction().start().await
     }
 
+    /// Runs `f` in a new transaction, retrying it if the transaction fails with a serialization failure or deadlock.
+    ///
+    /// The transaction is committed if `f` returns `Ok`, and rolled back otherwise. If `f` or the commit fails with
+    /// SQLSTATE `40001` (serialization failure) or `40P01` (deadlock detected), the transaction is retried after an
+    /// exponentially increasing delay, starting at 10 milliseconds and capped at 1 second. At most 10 attempts are made,
+    /// after which the last error is returned. All other errors are returned immediately.
+    ///
+    /// Since `f` may be called multiple times, it should not have side effects outside of the transaction.
+    ///
+    /// Requires the `runtime-core` Cargo feature (enabled by default).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn example(client: &mut tokio_postgres::Client) -> Result<(), tokio_postgres::Error> {
+    /// client
+    ///     .transaction_retry(|transaction| {
+    ///         Box::pin(async move {
+    ///             transaction
+    ///                 .execute("UPDATE accounts SET balance = balance - 10 WHERE id = 1", &[])
+    ///                 .await?;
+    ///             transaction
+    ///                 .execute("UPDATE accounts SET balance = balance + 10 WHERE id = 2", &[])
+    ///                 .await?;
+    ///             Ok(())
+    ///         })
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "runtime-core")]
+    pub async fn transaction_retry<F, R>(&mut self, mut f: F) -> Result<R, Error>
+    where
+        F: for<'a, 'b> FnMut(&'a mut Transaction<'b>) -> BoxFuture<'a, Result<R, Error>>,
+    {
+        const MAX_ATTEMPTS: u32 = 10;
+        const MAX_BACKOFF: Duration = Duration::from_secs(1);
+
+        // derived up front, since the transaction borrows the client for the rest of the loop
+        let mut rng = self.rng.as_mut().map(StdRng::from_rng);
+        let runtime = self.runtime.clone();
+        let mut backoff = Duration::from_millis(10);
+        let mut attempt = 1;
+        loop {
+            let mut transaction = self.transaction().await?;
+            let r = match f(&mut transaction).await {
+                Ok(r) => transaction.commit().await.map(|()| r),
+                Err(e) => Err(e),
+            };
+
+            match r {
+                Err(e) if attempt < MAX_ATTEMPTS && is_retryable(&e) => {}
+                r => return r,
+            }
+
+            let delay = match &mut rng {
+                Some(rng) => rng.random_range(backoff / 2..=backoff),
+                None => rand::rng().random_range(backoff / 2..=backoff),
+            };
+            rt::sleep(runtime.as_ref(), delay).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            attempt += 1;
+        }
+    }
+
+    /// Returns the current value of a runtime parameter reported by the server, such as `server_version` or `TimeZone`.
+    ///
+    /// The value reflects the `ParameterStatus` messages processed by the `Connection` so far.
+    pub fn parameter(&self, name: &str) -> Option<String> {
+        self.inner.listeners().parameter(name)
+    }
+
+    /// Returns a stream of changes to the runtime parameters reported by the server.
+    ///
+    /// The server reports changes to parameters such as `TimeZone` or `standard_conforming_strings` when they are set
+    /// by a query, and a connection pooler may report new values after switching to a different server. Only changes
+    /// received after the stream was created are returned.
+    pub fn parameter_changes(&self) -> ParameterStream {
+        ParameterStream::new(&self.inner)
+    }
+
+    /// Returns a stream of the notices sent by the server, such as the output of `RAISE NOTICE`.
+    ///
+    /// The stream receives every notice sent after it was created, including those raised while executing other
+    /// requests. As with notifications, notices are only delivered while the `Connection` is being polled, and are
+    /// also still returned from `poll_message`.
+    pub fn notices(&self) -> NoticeStream {
+        NoticeStream::new(&self.inner)
+    }
+
+    /// Starts listening for notifications sent to a channel.
+    ///
+    /// This issues a `LISTEN` command for the channel, and returns a stream of the notifications sent to it. The
+    /// channel name is quoted, so it is case sensitive. The client issues an `UNLISTEN` command once every stream for
+    /// the channel has been dropped.
+    ///
+    /// Notifications are only delivered while the `Connection` is being polled, whether through its `Future`
+    /// implementation or `poll_message`. They are also still returned from `poll_message`.
+    pub async fn listen(&self, channel: &str) -> Result<NotificationStream, Error> {
+        let stream = NotificationStream::new(&self.inner, channel);
+        self.batch_execute(&format!("LISTEN {}", escape_identifier(channel)))
+            .await?;
+        Ok(stream)
+    }
+
+    /// Like `listen`, but deserializes the payload of each notification sent to the channel as JSON.
+    ///
+    /// Requires the `with-serde_json-1` Cargo feature.
+    #[cfg(feature = "with-serde_json-1")]
+    pub async fn listen_json<T>(&self, channel: &str) -> Result<JsonNotificationStream<T>, Error>
+    where
+        T: serde_1::de::DeserializeOwned,
+    {
+        self.listen(channel).await.map(NotificationStream::json)
+    }
+
+    /// Returns a builder for a pipeline of statements which are sent to the server together.
+    ///
+    /// See the documentation of [`Pipeline`] for details.
+    pub fn pipeline(&self) -> Pipeline<'_> {
+        Pipeline::new(self)
+    }
+
     /// Returns a builder for a transaction with custom settings.
     ///
     /// Unlike the `transaction` method, the builder can be used to control the transaction's isolation level and other
@@ -546,11 +1258,21 @@ impl Client {
         TransactionBuilder::new(self)
     }
 
+    /// Returns the configuration of the socket the client is connected over.
+    ///
+    /// This is `None` for clients created with [`Config::connect_raw`](crate::Config::connect_raw).
+    ///
+    /// Requires the `runtime-core` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime-core")]
+    pub fn socket_config(&self) -> Option<&SocketConfig> {
+        self.socket_config.as_ref()
+    }
+
     /// Constructs a cancellation token that can later be used to request cancellation of a query running on the
     /// connection associated with this client.
     pub fn cancel_token(&self) -> CancelToken {
         CancelToken {
-            #[cfg(feature = "runtime")]
+            #[cfg(feature = "runtime-core")]
             socket_config: self.socket_config.clone(),
             ssl_mode: self.ssl_mode,
             ssl_negotiation: self.ssl_negotiation,
@@ -564,8 +1286,8 @@ impl Client {
     /// The server provides no information about whether a cancellation attempt was successful or not. An error will
     /// only be returned if the client was unable to connect to the database.
     ///
-    /// Requires the `runtime` Cargo feature (enabled by default).
-    #[cfg(feature = "runtime")]
+    /// Requires the `runtime-core` Cargo feature (enabled by default).
+    #[cfg(feature = "runtime-core")]
     #[deprecated(since = "0.6.0", note = "use Client::cancel_token() instead")]
     pub async fn cancel_query<T>(&self, tls: T) -> Result<(), Error>
     where
@@ -585,6 +1307,95 @@ impl Client {
         self.cancel_token().cancel_query_raw(stream, tls).await
     }
 
+    /// Determines if statements whose cached plans have been invalidated are automatically re-prepared and retried.
+    ///
+    /// Changing the schema of a table, for example with `ALTER TABLE`, can cause a previously prepared statement
+    /// which reads from it to fail with "cached plan must not change result type". If enabled, `query`, `query_one`,
+    /// `query_opt`, and `execute` respond to that error by preparing the statement's query again and retrying it once.
+    /// The `Statement` passed by the caller is not updated, so it should be replaced with a freshly prepared one to
+    /// avoid repeating the retry on every execution.
+    ///
+    /// Statements cannot be retried within a transaction, since the error aborts it. Defaults to `false`.
+    pub fn set_retry_invalidated_plans(&mut self, retry_invalidated_plans: bool) {
+        self.retry_invalidated_plans = retry_invalidated_plans;
+    }
+
+    /// Registers a hook to be invoked around the execution of each statement, replacing any previously registered one.
+    ///
+    /// See [`StatementHook`] for details.
+    pub fn set_statement_hook<H>(&mut self, hook: H)
+    where
+        H: StatementHook + 'static,
+    {
+        self.hook = Some(Arc::new(hook));
+    }
+
+    /// Removes the hook registered with `set_statement_hook`, if any.
+    pub fn clear_statement_hook(&mut self) {
+        self.hook = None;
+    }
+
+    fn start_hook(&self, query: &str, param_count: usize, start: Instant) -> Option<HookGuard> {
+        self.hook
+            .as_ref()
+            .map(|hook| HookGuard::start(hook, query, param_count, start))
+    }
+
+    // Prepares the statement if necessary, and starts the hook for its execution. A failure to prepare the statement
+    // is reported to the hook as the outcome of the statement.
+    async fn start_statement<T, I>(
+        &self,
+        statement: &T,
+        params: I,
+    ) -> Result<(Statement, I, Option<HookGuard>), Error>
+    where
+        T: ?Sized + ToStatement,
+        I: IntoIterator,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let start = Instant::now();
+        let statement = statement.__convert();
+        let query = statement.query();
+
+        match statement.into_statement(self).await {
+            Ok(statement) => {
+                let hook = self.start_hook(query, statement.params().len(), start);
+                Ok((statement, params, hook))
+            }
+            Err(e) => {
+                if let Some(hook) = self.start_hook(query, params.into_iter().len(), start) {
+                    hook.fail(&e);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn attach_hook(
+        result: Result<RowStream, Error>,
+        hook: Option<HookGuard>,
+    ) -> Result<RowStream, Error> {
+        match (result, hook) {
+            (Ok(mut stream), Some(hook)) => {
+                stream.set_hook(hook);
+                Ok(stream)
+            }
+            (Err(e), Some(hook)) => {
+                hook.fail(&e);
+                Err(e)
+            }
+            (result, None) => result,
+        }
+    }
+
+    /// Returns a snapshot of the execution statistics of the connection.
+    ///
+    /// The counters are updated by the `Connection` as it processes messages, so requests which are still in flight
+    /// may only be partially accounted for.
+    pub fn stats(&self) -> ClientStats {
+        self.inner.stats.snapshot()
+    }
+
     /// Clears the client's type information cache.
     ///
     /// When user-defined types are used in a query, the client loads their definitions from the database and caches
@@ -601,12 +1412,43 @@ impl Client {
         self.inner.sender.is_closed()
     }
 
+    /// Gracefully closes the connection to the server.
+    ///
+    /// Unlike dropping the client, this waits for requests already sent to complete, sends a `Terminate` message to
+    /// the server, and then resolves once the `Connection` has flushed and shut down its stream. Servers log abruptly
+    /// closed connections as errors, so this should be preferred where that matters.
+    ///
+    /// The `Connection` must be polled for this to complete.
+    pub async fn close(self) {
+        let (sender, mut receiver) = mpsc::channel(1);
+        let request = Request {
+            messages: RequestMessages::Close,
+            sender,
+            notices: false,
+        };
+        if self.inner.sender.unbounded_send(request).is_err() {
+            return;
+        }
+        self.inner.sender.close_channel();
+
+        // the connection drops the sender once it has finished
+        while receiver.next().await.is_some() {}
+    }
+
     #[doc(hidden)]
     pub fn __private_api_close(&mut self) {
         self.inner.sender.close_channel()
     }
 }
 
+#[cfg(feature = "runtime-core")]
+fn is_retryable(e: &Error) -> bool {
+    matches!(
+        e.code(),
+        Some(&SqlState::T_R_SERIALIZATION_FAILURE) | Some(&SqlState::T_R_DEADLOCK_DETECTED)
+    )
+}
+
 impl fmt::Debug for Client {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Client").finish()
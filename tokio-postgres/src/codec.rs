@@ -1,13 +1,17 @@
-use bytes::{Buf, Bytes, BytesMut};
+use crate::stats::Stats;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use fallible_iterator::FallibleIterator;
 use postgres_protocol::message::backend;
-use postgres_protocol::message::frontend::CopyData;
 use std::io;
+use std::sync::Arc;
 use tokio_util::codec::{Decoder, Encoder};
 
 pub enum FrontendMessage {
     Raw(Bytes),
-    CopyData(CopyData<Box<dyn Buf + Send>>),
+    /// Encoded messages which are moved into the write buffer rather than copied if it is empty.
+    Buffer(BytesMut),
+    /// Encoded messages split across buffers, such as a `CopyData` header chained with caller-provided data.
+    Buf(Box<dyn Buf + Send>),
 }
 
 pub enum BackendMessage {
@@ -16,8 +20,10 @@ pub enum BackendMessage {
         request_complete: bool,
     },
     Async(backend::Message),
+    Notice(BackendMessages),
 }
 
+#[derive(Clone)]
 pub struct BackendMessages(BytesMut);
 
 impl BackendMessages {
@@ -35,16 +41,40 @@ impl FallibleIterator for BackendMessages {
     }
 }
 
-pub struct PostgresCodec;
+pub struct PostgresCodec {
+    stats: Arc<Stats>,
+    max_rows: Option<usize>,
+}
+
+impl PostgresCodec {
+    pub fn new(stats: Arc<Stats>) -> PostgresCodec {
+        PostgresCodec {
+            stats,
+            max_rows: None,
+        }
+    }
+
+    /// Limits the number of `DataRow` messages decoded into a single batch.
+    pub fn set_max_rows(&mut self, max_rows: Option<usize>) {
+        self.max_rows = max_rows;
+    }
+
+    pub fn stats(&self) -> &Arc<Stats> {
+        &self.stats
+    }
+}
 
 impl Encoder<FrontendMessage> for PostgresCodec {
     type Error = io::Error;
 
     fn encode(&mut self, item: FrontendMessage, dst: &mut BytesMut) -> io::Result<()> {
+        let start = dst.len();
         match item {
             FrontendMessage::Raw(buf) => dst.extend_from_slice(&buf),
-            FrontendMessage::CopyData(data) => data.write(dst),
+            FrontendMessage::Buffer(buf) => dst.unsplit(buf),
+            FrontendMessage::Buf(buf) => dst.put(buf),
         }
+        self.stats.add_bytes_sent(dst.len() - start);
 
         Ok(())
     }
@@ -56,6 +86,7 @@ impl Decoder for PostgresCodec {
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<BackendMessage>, io::Error> {
         let mut idx = 0;
+        let mut rows = 0;
         let mut request_complete = false;
 
         while let Some(header) = backend::Header::parse(&src[idx..])? {
@@ -65,16 +96,33 @@ impl Decoder for PostgresCodec {
             }
 
             match header.tag() {
-                backend::NOTICE_RESPONSE_TAG
-                | backend::NOTIFICATION_RESPONSE_TAG
-                | backend::PARAMETER_STATUS_TAG => {
+                // notices are kept in their raw form so that they can be forwarded along with a request's responses
+                backend::NOTICE_RESPONSE_TAG => {
                     if idx == 0 {
+                        self.stats.add_bytes_received(len);
+                        return Ok(Some(BackendMessage::Notice(BackendMessages(
+                            src.split_to(len),
+                        ))));
+                    } else {
+                        break;
+                    }
+                }
+                backend::NOTIFICATION_RESPONSE_TAG | backend::PARAMETER_STATUS_TAG => {
+                    if idx == 0 {
+                        self.stats.add_bytes_received(len);
                         let message = backend::Message::parse(src)?.unwrap();
                         return Ok(Some(BackendMessage::Async(message)));
                     } else {
                         break;
                     }
                 }
+                backend::DATA_ROW_TAG => {
+                    self.stats.add_row();
+                    rows += 1;
+                }
+                backend::COMMAND_COMPLETE_TAG => self.stats.add_statement(),
+                backend::PARSE_COMPLETE_TAG => self.stats.add_prepare(),
+                backend::ERROR_RESPONSE_TAG => self.stats.add_error(),
                 _ => {}
             }
 
@@ -84,11 +132,17 @@ impl Decoder for PostgresCodec {
                 request_complete = true;
                 break;
             }
+
+            // leave the remaining rows in the read buffer until the client has caught up
+            if self.max_rows.is_some_and(|max| rows >= max) {
+                break;
+            }
         }
 
         if idx == 0 {
             Ok(None)
         } else {
+            self.stats.add_bytes_received(idx);
             Ok(Some(BackendMessage::Normal {
                 messages: BackendMessages(src.split_to(idx)),
                 request_complete,
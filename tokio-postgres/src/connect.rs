@@ -1,14 +1,18 @@
-use crate::client::{Addr, SocketConfig};
-use crate::config::{Host, LoadBalanceHosts, TargetSessionAttrs};
+use crate::config::{GssEncMode, Host, LoadBalanceHosts, TargetSessionAttrs};
+use crate::connect_gss;
 use crate::connect_raw::connect_raw;
 use crate::connect_socket::connect_socket;
+use crate::resolver;
+use crate::socket_config::{Addr, SocketConfig};
 use crate::tls::MakeTlsConnect;
 use crate::{Client, Config, Connection, Error, SimpleQueryMessage, Socket};
 use futures_util::{future, pin_mut, Future, FutureExt, Stream};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use std::borrow::Cow;
 use std::task::Poll;
+use std::time::Instant;
 use std::{cmp, io};
-use tokio::net;
 
 pub async fn connect<T>(
     mut tls: T,
@@ -42,39 +46,71 @@ where
         return Err(Error::config("invalid number of ports".into()));
     }
 
-    let mut indices = (0..num_hosts).collect::<Vec<_>>();
-    if config.load_balance_hosts == LoadBalanceHosts::Random {
-        indices.shuffle(&mut rand::rng());
+    if !config.host_ssl_mode.is_empty() && config.host_ssl_mode.len() != num_hosts {
+        return Err(Error::config("invalid number of sslmodes".into()));
+    }
+
+    let mut targets = (0..num_hosts)
+        .map(|i| {
+            let host = config.host.get(i);
+            let hostaddr = config.hostaddr.get(i);
+            let port = config
+                .port
+                .get(i)
+                .or_else(|| config.port.first())
+                .copied()
+                .unwrap_or(5432);
+
+            // The value of host is used as the hostname for TLS validation,
+            let hostname = match host {
+                Some(Host::Tcp(host)) => Some(host.clone()),
+                // postgres doesn't support TLS over unix sockets, so the choice here doesn't matter
+                #[cfg(unix)]
+                Some(Host::Unix(_)) => None,
+                None => None,
+            };
+
+            // Try to use the value of hostaddr to establish the TCP connection,
+            // fallback to host if hostaddr is not present.
+            let addr = match hostaddr {
+                Some(ipaddr) => Host::Tcp(ipaddr.to_string()),
+                None => host.cloned().unwrap(),
+            };
+
+            (i, addr, hostname, port)
+        })
+        .collect::<Vec<_>>();
+    let mut rng = config.rng();
+    match config.load_balance_hosts {
+        LoadBalanceHosts::Random => targets.shuffle(&mut rng),
+        LoadBalanceHosts::LeastLatency => targets.sort_by_cached_key(|(_, addr, _, port)| {
+            config.latency.estimate(&host_key(addr), *port)
+        }),
+        LoadBalanceHosts::Disable => {}
     }
 
     let mut error = None;
-    for i in indices {
-        let host = config.host.get(i);
-        let hostaddr = config.hostaddr.get(i);
-        let port = config
-            .port
-            .get(i)
-            .or_else(|| config.port.first())
-            .copied()
-            .unwrap_or(5432);
-
-        // The value of host is used as the hostname for TLS validation,
-        let hostname = match host {
-            Some(Host::Tcp(host)) => Some(host.clone()),
-            // postgres doesn't support TLS over unix sockets, so the choice here doesn't matter
-            #[cfg(unix)]
-            Some(Host::Unix(_)) => None,
-            None => None,
+    for (i, addr, hostname, port) in targets {
+        let host_config = match config.host_ssl_mode.get(i) {
+            Some(&ssl_mode) if ssl_mode != config.ssl_mode => {
+                let mut host_config = config.clone();
+                host_config.ssl_mode = ssl_mode;
+                Cow::Owned(host_config)
+            }
+            _ => Cow::Borrowed(config),
         };
 
-        // Try to use the value of hostaddr to establish the TCP connection,
-        // fallback to host if hostaddr is not present.
-        let addr = match hostaddr {
-            Some(ipaddr) => Host::Tcp(ipaddr.to_string()),
-            None => host.cloned().unwrap(),
-        };
+        let key = host_key(&addr).into_owned();
+        let start = Instant::now();
+        let result = connect_host(addr, hostname, port, &mut tls, &host_config, &mut rng).await;
+        if config.load_balance_hosts == LoadBalanceHosts::LeastLatency {
+            match &result {
+                Ok(_) => config.latency.record(&key, port, start.elapsed()),
+                Err(_) => config.latency.record_failure(&key, port),
+            }
+        }
 
-        match connect_host(addr, hostname, port, &mut tls, config).await {
+        match result {
             Ok((client, connection)) => return Ok((client, connection)),
             Err(e) => error = Some(e),
         }
@@ -83,25 +119,45 @@ where
     Err(error.unwrap())
 }
 
+fn host_key(host: &Host) -> Cow<'_, str> {
+    match host {
+        Host::Tcp(host) => Cow::Borrowed(host),
+        #[cfg(unix)]
+        Host::Unix(path) => path.to_string_lossy(),
+    }
+}
+
 async fn connect_host<T>(
     host: Host,
     hostname: Option<String>,
     port: u16,
     tls: &mut T,
     config: &Config,
+    rng: &mut StdRng,
 ) -> Result<(Client, Connection<Socket, T::Stream>), Error>
 where
     T: MakeTlsConnect<Socket>,
 {
     match host {
+        Host::Tcp(host) if config.proxy.is_some() => {
+            let addr = match host.parse() {
+                Ok(ip) => Addr::Tcp(ip),
+                Err(_) => Addr::Proxied(host),
+            };
+            connect_once(addr, hostname.as_deref(), port, tls, config).await
+        }
         Host::Tcp(host) => {
-            let mut addrs = net::lookup_host((&*host, port))
-                .await
-                .map_err(Error::connect)?
-                .collect::<Vec<_>>();
+            let mut addrs = resolver::resolve(
+                config.resolver.as_ref(),
+                config.runtime.as_ref(),
+                &host,
+                port,
+            )
+            .await
+            .map_err(Error::connect)?;
 
             if config.load_balance_hosts == LoadBalanceHosts::Random {
-                addrs.shuffle(&mut rand::rng());
+                addrs.shuffle(rng);
             }
 
             let mut last_err = None;
@@ -131,6 +187,23 @@ where
     }
 }
 
+async fn connect_startup<T>(
+    socket_config: &SocketConfig,
+    tls: &mut T,
+    config: &Config,
+) -> Result<(Client, Connection<Socket, T::Stream>), Error>
+where
+    T: MakeTlsConnect<Socket>,
+{
+    let socket = connect_socket(socket_config).await?;
+
+    let tls = tls
+        .make_tls_connect_for(socket_config)
+        .map_err(|e| Error::tls(e.into()))?;
+    let hostname = socket_config.hostname.as_deref();
+    connect_raw(socket, tls, hostname.is_some(), hostname, config).await
+}
+
 async fn connect_once<T>(
     addr: Addr,
     hostname: Option<&str>,
@@ -141,24 +214,37 @@ async fn connect_once<T>(
 where
     T: MakeTlsConnect<Socket>,
 {
-    let socket = connect_socket(
-        &addr,
+    let socket_config = SocketConfig {
+        addr,
+        hostname: hostname.map(|s| s.to_string()),
         port,
-        config.connect_timeout,
-        config.tcp_user_timeout,
-        if config.keepalives {
-            Some(&config.keepalive_config)
+        connect_timeout: config.connect_timeout,
+        tcp_user_timeout: config.tcp_user_timeout,
+        keepalive: if config.keepalives {
+            Some(config.keepalive_config.clone())
         } else {
             None
         },
-    )
-    .await?;
-
-    let tls = tls
-        .make_tls_connect(hostname.unwrap_or(""))
-        .map_err(|e| Error::tls(e.into()))?;
-    let has_hostname = hostname.is_some();
-    let (mut client, mut connection) = connect_raw(socket, tls, has_hostname, config).await?;
+        proxy: config.proxy.clone(),
+        local_address: config.local_address,
+        bind_interface: config.bind_interface.clone(),
+        ssl_root_cert: config.ssl_root_cert.clone(),
+        ssl_cert: config.ssl_cert.clone(),
+        ssl_key: config.ssl_key.clone(),
+        resolver: config.resolver.clone(),
+        runtime: config.runtime.clone(),
+    };
+    let (mut client, mut connection) = match connect_startup(&socket_config, tls, config).await {
+        Err(e)
+            if config.gss_enc_mode == GssEncMode::Prefer && connect_gss::is_prefer_failure(&e) =>
+        {
+            // as with libpq, start over with a new connection that does not attempt GSSAPI encryption
+            let mut config = config.clone();
+            config.gss_enc_mode = GssEncMode::Disable;
+            connect_startup(&socket_config, tls, &config).await?
+        }
+        result => result?,
+    };
 
     if config.target_session_attrs != TargetSessionAttrs::Any {
         let rows = client.simple_query_raw("SHOW transaction_read_only");
@@ -210,18 +296,7 @@ where
         }
     }
 
-    client.set_socket_config(SocketConfig {
-        addr,
-        hostname: hostname.map(|s| s.to_string()),
-        port,
-        connect_timeout: config.connect_timeout,
-        tcp_user_timeout: config.tcp_user_timeout,
-        keepalive: if config.keepalives {
-            Some(config.keepalive_config.clone())
-        } else {
-            None
-        },
-    });
+    client.set_socket_config(socket_config);
 
     Ok((client, connection))
 }
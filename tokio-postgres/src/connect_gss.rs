@@ -0,0 +1,168 @@
+use crate::config::{Config, GssEncMode};
+use crate::gss::{GssContext, GssStream, MAX_PACKET_SIZE};
+use crate::Error;
+use bytes::{Buf, BufMut, BytesMut};
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+use std::{error, fmt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+// A failure to establish GSSAPI encryption after the server agreed to it, which `prefer` recovers from by connecting
+// again without it.
+#[derive(Debug)]
+struct PreferFailure(Error);
+
+impl fmt::Display for PreferFailure {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "GSSAPI encryption could not be established: {}",
+            self.0
+        )
+    }
+}
+
+impl error::Error for PreferFailure {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+// Returns whether an error is a failure to establish GSSAPI encryption with `gssencmode=prefer`.
+#[cfg_attr(not(feature = "runtime-core"), allow(dead_code))]
+pub fn is_prefer_failure(e: &Error) -> bool {
+    error::Error::source(e).is_some_and(|e| e.is::<PreferFailure>())
+}
+
+// Negotiates GSSAPI encryption with the server, returning the stream unchanged if it is not to be used.
+pub async fn connect_gss<S>(
+    mut stream: S,
+    config: &Config,
+    hostname: Option<&str>,
+) -> Result<Result<GssStream<S>, S>, Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mode = config.gss_enc_mode;
+    if mode == GssEncMode::Disable {
+        return Ok(Err(stream));
+    }
+
+    let context = match (&config.gss, hostname) {
+        (Some(gss), Some(hostname)) => gss.0.make_gss_context(config.get_krb_srv_name(), hostname),
+        (None, _) => Err("no GSSAPI implementation is configured".into()),
+        (_, None) => Err("no hostname provided for GSSAPI".into()),
+    };
+    let context = match context {
+        Ok(context) => context,
+        Err(e) if mode == GssEncMode::Require => return Err(Error::authentication(e)),
+        Err(_) => return Ok(Err(stream)),
+    };
+
+    let mut buf = BytesMut::new();
+    frontend::gss_enc_request(&mut buf);
+    stream.write_all(&buf).await.map_err(Error::io)?;
+
+    let mut buf = [0];
+    stream.read_exact(&mut buf).await.map_err(Error::io)?;
+
+    match buf[0] {
+        b'G' => {}
+        b'N' if mode == GssEncMode::Require => {
+            return Err(Error::authentication(
+                "server does not support GSSAPI encryption".into(),
+            ))
+        }
+        b'N' => return Ok(Err(stream)),
+        _ => return Err(Error::unexpected_message()),
+    }
+
+    match establish(stream, context).await {
+        Ok(stream) => Ok(Ok(stream)),
+        Err(e) if mode == GssEncMode::Prefer => {
+            Err(Error::authentication(Box::new(PreferFailure(e))))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn establish<S>(
+    mut stream: S,
+    mut context: Box<dyn GssContext>,
+) -> Result<GssStream<S>, Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut read_buf = BytesMut::new();
+    let mut token = None;
+    loop {
+        let output = context
+            .step(token.as_deref())
+            .map_err(Error::authentication)?;
+        if let Some(output) = output.filter(|output| !output.is_empty()) {
+            if output.len() > MAX_PACKET_SIZE - 4 {
+                return Err(Error::authentication(
+                    "GSSAPI token is too large to send to the server".into(),
+                ));
+            }
+            let mut buf = BytesMut::with_capacity(output.len() + 4);
+            buf.put_u32(output.len() as u32);
+            buf.put_slice(&output);
+            stream.write_all(&buf).await.map_err(Error::io)?;
+        }
+
+        if context.is_complete() {
+            break;
+        }
+
+        token = Some(read_packet(&mut stream, &mut read_buf).await?);
+    }
+
+    GssStream::new(stream, context, read_buf).map_err(Error::io)
+}
+
+// The largest error response read during the handshake, which like libpq's is read until the server closes the
+// connection.
+const MAX_ERROR_RESPONSE_SIZE: usize = 8192;
+
+// Reads a length-prefixed packet sent by the server during the handshake.
+async fn read_packet<S>(stream: &mut S, buf: &mut BytesMut) -> Result<Vec<u8>, Error>
+where
+    S: AsyncRead + Unpin,
+{
+    loop {
+        // the server reports a failure to establish the context with a normal, unencrypted, ErrorResponse
+        if buf.first() == Some(&b'E') {
+            while buf.len() <= MAX_ERROR_RESPONSE_SIZE {
+                if stream.read_buf(buf).await.map_err(Error::io)? == 0 {
+                    return match Message::parse(buf).map_err(Error::parse)? {
+                        Some(Message::ErrorResponse(body)) => Err(Error::db(body)),
+                        _ => Err(Error::unexpected_message()),
+                    };
+                }
+            }
+            return Err(Error::authentication(
+                "oversize error response sent by the server during GSSAPI negotiation".into(),
+            ));
+        }
+
+        if buf.len() >= 4 {
+            let len = (&buf[..4]).get_u32() as usize;
+            if len > MAX_PACKET_SIZE - 4 {
+                return Err(Error::authentication(
+                    format!("oversize GSSAPI packet sent by the server ({} bytes)", len).into(),
+                ));
+            }
+            if buf.len() >= len + 4 {
+                buf.advance(4);
+                return Ok(buf.split_to(len).to_vec());
+            }
+        }
+
+        if stream.read_buf(buf).await.map_err(Error::io)? == 0 {
+            return Err(Error::authentication(
+                "server closed the connection during GSSAPI negotiation".into(),
+            ));
+        }
+    }
+}
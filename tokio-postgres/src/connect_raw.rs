@@ -1,13 +1,19 @@
 use crate::codec::{BackendMessage, BackendMessages, FrontendMessage, PostgresCodec};
-use crate::config::{self, Config};
+use crate::config::{self, AuthMethod, Config, ReplicationMode};
+use crate::connect_gss::connect_gss;
 use crate::connect_tls::connect_tls;
+use crate::error::DbError;
+use crate::listen::Listeners;
 use crate::maybe_tls_stream::MaybeTlsStream;
+use crate::raw_connection::RawConnection;
+use crate::secret::Secret;
+use crate::stats::Stats;
 use crate::tls::{TlsConnect, TlsStream};
 use crate::{Client, Connection, Error};
 use bytes::BytesMut;
 use fallible_iterator::FallibleIterator;
 use futures_channel::mpsc;
-use futures_util::{ready, Sink, SinkExt, Stream, TryStreamExt};
+use futures_util::{future, pin_mut, ready, FutureExt, Sink, SinkExt, Stream, TryStreamExt};
 use postgres_protocol::authentication;
 use postgres_protocol::authentication::sasl;
 use postgres_protocol::authentication::sasl::ScramSha256;
@@ -15,11 +21,13 @@ use postgres_protocol::message::backend::{AuthenticationSaslBody, Message};
 use postgres_protocol::message::frontend;
 use std::borrow::Cow;
 use std::collections::{HashMap, VecDeque};
+use std::future::Future;
 use std::io;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
-use tokio::io::{AsyncRead, AsyncWrite};
-use tokio_util::codec::Framed;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio_util::codec::{Framed, FramedParts};
 
 pub struct StartupStream<S, T> {
     inner: Framed<MaybeTlsStream<S, T>, PostgresCodec>,
@@ -70,7 +78,8 @@ where
             }
 
             match ready!(Pin::new(&mut self.inner).poll_next(cx)) {
-                Some(Ok(BackendMessage::Normal { messages, .. })) => self.buf = messages,
+                Some(Ok(BackendMessage::Normal { messages, .. }))
+                | Some(Ok(BackendMessage::Notice(messages))) => self.buf = messages,
                 Some(Ok(BackendMessage::Async(message))) => return Poll::Ready(Some(Ok(message))),
                 Some(Err(e)) => return Poll::Ready(Some(Err(e))),
                 None => return Poll::Ready(None),
@@ -83,23 +92,149 @@ pub async fn connect_raw<S, T>(
     stream: S,
     tls: T,
     has_hostname: bool,
+    hostname: Option<&str>,
     config: &Config,
 ) -> Result<(Client, Connection<S, T::Stream>), Error>
 where
     S: AsyncRead + AsyncWrite + Unpin,
     T: TlsConnect<S>,
 {
-    let stream = connect_tls(
-        stream,
+    let (mut stream, process_id, secret_key, parameters) =
+        handshake(stream, tls, has_hostname, hostname, config).await?;
+    stream
+        .inner
+        .codec_mut()
+        .set_max_rows(config.row_buffer_size);
+
+    let (sender, receiver) = mpsc::unbounded();
+    let listeners = Arc::new(Listeners::default());
+    for (name, value) in &parameters {
+        listeners.set_parameter(name, value);
+    }
+    #[cfg_attr(not(feature = "runtime-core"), allow(unused_mut))]
+    let mut client = Client::new(
+        sender,
+        listeners.clone(),
+        stream.inner.codec().stats().clone(),
         config.ssl_mode,
         config.ssl_negotiation,
-        tls,
-        has_hostname,
-    )
-    .await?;
+        process_id,
+        secret_key,
+    );
+    #[cfg(feature = "runtime-core")]
+    {
+        if let Some(seed) = config.random_seed {
+            client.set_random_seed(seed);
+        }
+        client.set_runtime(config.runtime.clone());
+    }
+    let mut connection = Connection::new(
+        stream.inner,
+        stream.delayed,
+        parameters,
+        receiver,
+        listeners,
+    );
+
+    if !config.init_statements.is_empty() || config.session_setup.is_some() {
+        let setup = setup_session(&client, config);
+        pin_mut!(setup);
+        future::poll_fn(|cx| {
+            if connection.poll_unpin(cx)?.is_ready() {
+                return Poll::Ready(Err(Error::closed()));
+            }
+
+            setup.as_mut().poll(cx)
+        })
+        .await?;
+    }
+
+    Ok((client, connection))
+}
+
+async fn setup_session(client: &Client, config: &Config) -> Result<(), Error> {
+    for statement in &config.init_statements {
+        client.batch_execute(statement).await?;
+    }
+
+    if let Some(setup) = &config.session_setup {
+        (setup.0)(client).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn connect_raw_stream<S, T>(
+    stream: S,
+    tls: T,
+    has_hostname: bool,
+    hostname: Option<&str>,
+    config: &Config,
+) -> Result<RawConnection<S, T::Stream>, Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: TlsConnect<S>,
+{
+    let (stream, process_id, secret_key, parameters) =
+        handshake(stream, tls, has_hostname, hostname, config).await?;
+
+    let mut notices = vec![];
+    for message in stream.delayed {
+        if let BackendMessage::Async(Message::NoticeResponse(body)) = message {
+            notices.push(DbError::parse(&mut body.fields()).map_err(Error::parse)?);
+        }
+    }
+
+    // the startup process ends with ReadyForQuery, so nothing after it has been decoded
+    let parts = stream.inner.into_parts();
+    Ok(RawConnection {
+        stream: parts.io,
+        buf: parts.read_buf,
+        process_id,
+        secret_key,
+        parameters,
+        notices,
+        ssl_mode: config.ssl_mode,
+        ssl_negotiation: config.ssl_negotiation,
+    })
+}
+
+async fn handshake<S, T>(
+    stream: S,
+    tls: T,
+    has_hostname: bool,
+    hostname: Option<&str>,
+    config: &Config,
+) -> Result<
+    (
+        StartupStream<S, T::Stream>,
+        i32,
+        i32,
+        HashMap<String, String>,
+    ),
+    Error,
+>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: TlsConnect<S>,
+{
+    // as with libpq, TLS is only attempted if GSSAPI encryption is not used
+    let stream = match connect_gss(stream, config, hostname).await? {
+        Ok(stream) => MaybeTlsStream::Gss(stream),
+        Err(stream) => {
+            connect_tls(
+                stream,
+                config.ssl_mode,
+                config.ssl_negotiation,
+                tls,
+                has_hostname,
+            )
+            .await?
+        }
+    };
 
     let mut stream = StartupStream {
-        inner: Framed::new(stream, PostgresCodec),
+        inner: framed(stream, config),
         buf: BackendMessages::empty(),
         delayed: VecDeque::new(),
     };
@@ -110,20 +245,23 @@ where
         .map_or_else(|| Cow::Owned(whoami::username()), Cow::Borrowed);
 
     startup(&mut stream, config, &user).await?;
-    authenticate(&mut stream, config, &user).await?;
+    authenticate(&mut stream, config, &user, hostname).await?;
     let (process_id, secret_key, parameters) = read_info(&mut stream).await?;
 
-    let (sender, receiver) = mpsc::unbounded();
-    let client = Client::new(
-        sender,
-        config.ssl_mode,
-        config.ssl_negotiation,
-        process_id,
-        secret_key,
-    );
-    let connection = Connection::new(stream.inner, stream.delayed, parameters, receiver);
+    Ok((stream, process_id, secret_key, parameters))
+}
 
-    Ok((client, connection))
+fn framed<S>(stream: S, config: &Config) -> Framed<S, PostgresCodec>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    let mut parts =
+        FramedParts::new::<FrontendMessage>(stream, PostgresCodec::new(Arc::new(Stats::default())));
+    parts.read_buf = BytesMut::with_capacity(config.read_buffer_size);
+    parts.write_buf = BytesMut::with_capacity(config.write_buffer_size);
+    let mut framed = Framed::from_parts(parts);
+    framed.set_backpressure_boundary(config.write_buffer_size);
+    framed
 }
 
 async fn startup<S, T>(
@@ -146,6 +284,14 @@ where
     if let Some(application_name) = &config.application_name {
         params.push(("application_name", &**application_name));
     }
+    for (key, value) in &config.params {
+        params.push((&**key, &**value));
+    }
+    match config.replication_mode {
+        Some(ReplicationMode::Physical) => params.push(("replication", "true")),
+        Some(ReplicationMode::Logical) => params.push(("replication", "database")),
+        None => {}
+    }
 
     let mut buf = BytesMut::new();
     frontend::startup_message(params, &mut buf).map_err(Error::encode)?;
@@ -160,18 +306,44 @@ async fn authenticate<S, T>(
     stream: &mut StartupStream<S, T>,
     config: &Config,
     user: &str,
+    hostname: Option<&str>,
 ) -> Result<(), Error>
 where
     S: AsyncRead + AsyncWrite + Unpin,
     T: TlsStream + Unpin,
 {
-    match stream.try_next().await.map_err(Error::io)? {
+    let message = stream.try_next().await.map_err(Error::io)?;
+    let method = match &message {
+        Some(Message::AuthenticationOk) => Some(AuthMethod::None),
+        Some(Message::AuthenticationCleartextPassword) => Some(AuthMethod::Password),
+        Some(Message::AuthenticationMd5Password(_)) => Some(AuthMethod::Md5),
+        Some(Message::AuthenticationSasl(_)) => Some(AuthMethod::ScramSha256),
+        Some(Message::AuthenticationGss) => Some(AuthMethod::Gss),
+        Some(Message::AuthenticationSspi) => Some(AuthMethod::Sspi),
+        _ => None,
+    };
+    if let (Some(method), Some(allowed)) = (method, &config.require_auth) {
+        if !allowed.contains(&method) {
+            return Err(Error::authentication(
+                format!(
+                    "server requested {} authentication, which is not allowed by require_auth",
+                    method.name()
+                )
+                .into(),
+            ));
+        }
+    }
+
+    match message {
         Some(Message::AuthenticationOk) => {
-            can_skip_channel_binding(config)?;
+            can_skip_channel_binding(config, "the server did not request authentication")?;
             return Ok(());
         }
         Some(Message::AuthenticationCleartextPassword) => {
-            can_skip_channel_binding(config)?;
+            can_skip_channel_binding(
+                config,
+                "the server requested password authentication, which does not support it",
+            )?;
 
             let pass = config
                 .password
@@ -181,23 +353,34 @@ where
             authenticate_password(stream, pass).await?;
         }
         Some(Message::AuthenticationMd5Password(body)) => {
-            can_skip_channel_binding(config)?;
+            can_skip_channel_binding(
+                config,
+                "the server requested password authentication, which does not support it",
+            )?;
 
             let pass = config
                 .password
                 .as_ref()
                 .ok_or_else(|| Error::config("password missing".into()))?;
 
-            let output = authentication::md5_hash(user.as_bytes(), pass, body.salt());
-            authenticate_password(stream, output.as_bytes()).await?;
+            let output = Secret::new(
+                authentication::md5_hash(user.as_bytes(), pass, body.salt()).into_bytes(),
+            );
+            authenticate_password(stream, &output).await?;
         }
         Some(Message::AuthenticationSasl(body)) => {
             authenticate_sasl(stream, body, config).await?;
         }
-        Some(Message::AuthenticationKerberosV5)
-        | Some(Message::AuthenticationScmCredential)
-        | Some(Message::AuthenticationGss)
-        | Some(Message::AuthenticationSspi) => {
+        // as with libpq on platforms without SSPI, SSPI is negotiated with GSSAPI
+        Some(Message::AuthenticationGss) | Some(Message::AuthenticationSspi) => {
+            can_skip_channel_binding(
+                config,
+                "the server requested GSSAPI authentication, which does not support it",
+            )?;
+
+            authenticate_gss(stream, config, hostname).await?;
+        }
+        Some(Message::AuthenticationKerberosV5) | Some(Message::AuthenticationScmCredential) => {
             return Err(Error::authentication(
                 "unsupported authentication method".into(),
             ))
@@ -215,11 +398,11 @@ where
     }
 }
 
-fn can_skip_channel_binding(config: &Config) -> Result<(), Error> {
+fn can_skip_channel_binding(config: &Config, reason: &str) -> Result<(), Error> {
     match config.channel_binding {
         config::ChannelBinding::Disable | config::ChannelBinding::Prefer => Ok(()),
         config::ChannelBinding::Require => Err(Error::authentication(
-            "server did not use channel binding".into(),
+            format!("channel binding is required, but {}", reason).into(),
         )),
     }
 }
@@ -232,13 +415,67 @@ where
     S: AsyncRead + AsyncWrite + Unpin,
     T: AsyncRead + AsyncWrite + Unpin,
 {
-    let mut buf = BytesMut::new();
+    // sized up front so that growing the buffer does not leave stray copies of the password behind
+    let mut buf = BytesMut::with_capacity(password.len() + 6);
     frontend::password_message(password, &mut buf).map_err(Error::encode)?;
+    let buf = Secret::new(Vec::from(buf));
 
-    stream
-        .send(FrontendMessage::Raw(buf.freeze()))
-        .await
-        .map_err(Error::io)
+    // the message is written directly to the stream rather than through the codec so that no copy of it outlives
+    // this function
+    let stats = stream.inner.codec().stats().clone();
+    let io = stream.inner.get_mut();
+    io.write_all(&buf).await.map_err(Error::io)?;
+    io.flush().await.map_err(Error::io)?;
+    stats.add_bytes_sent(buf.len());
+
+    Ok(())
+}
+
+async fn authenticate_gss<S, T>(
+    stream: &mut StartupStream<S, T>,
+    config: &Config,
+    hostname: Option<&str>,
+) -> Result<(), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let gss = config
+        .gss
+        .as_ref()
+        .ok_or_else(|| Error::authentication("no GSSAPI implementation is configured".into()))?;
+    let hostname =
+        hostname.ok_or_else(|| Error::authentication("no hostname provided for GSSAPI".into()))?;
+    let mut context = gss
+        .0
+        .make_gss_context(&config.krb_srv_name, hostname)
+        .map_err(Error::authentication)?;
+
+    let mut token = None;
+    loop {
+        let output = context
+            .step(token.as_deref())
+            .map_err(Error::authentication)?;
+        if let Some(output) = output.filter(|output| !output.is_empty()) {
+            let mut buf = BytesMut::new();
+            frontend::gss_response(&output, &mut buf).map_err(Error::encode)?;
+            stream
+                .send(FrontendMessage::Raw(buf.freeze()))
+                .await
+                .map_err(Error::io)?;
+        }
+
+        if context.is_complete() {
+            return Ok(());
+        }
+
+        match stream.try_next().await.map_err(Error::io)? {
+            Some(Message::AuthenticationGssContinue(body)) => token = Some(body.data().to_vec()),
+            Some(Message::ErrorResponse(body)) => return Err(Error::db(body)),
+            Some(_) => return Err(Error::unexpected_message()),
+            None => return Err(Error::closed()),
+        }
+    }
 }
 
 async fn authenticate_sasl<S, T>(
@@ -289,7 +526,14 @@ where
     };
 
     if mechanism != sasl::SCRAM_SHA_256_PLUS {
-        can_skip_channel_binding(config)?;
+        let reason = if !matches!(stream.inner.get_ref(), MaybeTlsStream::Tls(_)) {
+            "the connection does not use TLS"
+        } else if !has_scram_plus {
+            "the server does not support SCRAM-SHA-256-PLUS"
+        } else {
+            "the TLS implementation does not support it"
+        };
+        can_skip_channel_binding(config, reason)?;
     }
 
     let mut scram = ScramSha256::new(password, channel_binding);
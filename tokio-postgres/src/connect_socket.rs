@@ -1,66 +1,135 @@
-use crate::client::Addr;
-use crate::keepalive::KeepaliveConfig;
-use crate::{Error, Socket};
+use crate::socket_config::{Addr, SocketConfig};
+use crate::{proxy, rt, Error, Socket};
+#[cfg(feature = "runtime")]
 use socket2::{SockRef, TcpKeepalive};
 use std::future::Future;
 use std::io;
-use std::time::Duration;
-use tokio::net::TcpStream;
+use std::net::SocketAddr;
 #[cfg(unix)]
+use std::path::Path;
+#[cfg(all(unix, feature = "runtime"))]
 use tokio::net::UnixStream;
-use tokio::time;
-
-pub(crate) async fn connect_socket(
-    addr: &Addr,
-    port: u16,
-    connect_timeout: Option<Duration>,
-    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))] tcp_user_timeout: Option<
-        Duration,
-    >,
-    keepalive_config: Option<&KeepaliveConfig>,
-) -> Result<Socket, Error> {
-    match addr {
-        Addr::Tcp(ip) => {
-            let stream =
-                connect_with_timeout(TcpStream::connect((*ip, port)), connect_timeout).await?;
-
-            stream.set_nodelay(true).map_err(Error::connect)?;
-
-            let sock_ref = SockRef::from(&stream);
-
-            #[cfg(target_os = "linux")]
-            if let Some(tcp_user_timeout) = tcp_user_timeout {
-                sock_ref
-                    .set_tcp_user_timeout(Some(tcp_user_timeout))
-                    .map_err(Error::connect)?;
-            }
+#[cfg(feature = "runtime")]
+use tokio::net::{TcpSocket, TcpStream};
 
-            if let Some(keepalive_config) = keepalive_config {
-                sock_ref
-                    .set_tcp_keepalive(&TcpKeepalive::from(keepalive_config))
-                    .map_err(Error::connect)?;
-            }
+pub(crate) async fn connect_socket(config: &SocketConfig) -> Result<Socket, Error> {
+    #[cfg(not(feature = "runtime"))]
+    if config.runtime.is_none() {
+        return Err(Error::connect(rt::no_runtime_error()));
+    }
 
-            Ok(Socket::new_tcp(stream))
-        }
+    match &config.addr {
+        Addr::Tcp(_) | Addr::Proxied(_) => match (&config.proxy, &config.addr) {
+            (Some(proxy), _) => connect_with_timeout(proxy::connect(proxy, config), config).await,
+            (None, Addr::Tcp(ip)) => {
+                connect_with_timeout(
+                    connect_tcp(SocketAddr::new(*ip, config.port), config),
+                    config,
+                )
+                .await
+            }
+            (None, _) => Err(Error::connect(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "host names can only be resolved by a proxy",
+            ))),
+        },
         #[cfg(unix)]
         Addr::Unix(dir) => {
-            let path = dir.join(format!(".s.PGSQL.{}", port));
-            let socket = connect_with_timeout(UnixStream::connect(path), connect_timeout).await?;
-            Ok(Socket::new_unix(socket))
+            let path = dir.join(format!(".s.PGSQL.{}", config.port));
+            connect_with_timeout(connect_unix(&path, config), config).await
         }
     }
 }
 
-async fn connect_with_timeout<F, T>(connect: F, timeout: Option<Duration>) -> Result<T, Error>
+/// Opens a TCP connection to `addr` with the runtime of the configuration, applying its socket options.
+pub(crate) async fn connect_tcp(addr: SocketAddr, config: &SocketConfig) -> io::Result<Socket> {
+    match &config.runtime {
+        Some(runtime) => runtime
+            .0
+            .connect_tcp(addr, config)
+            .await
+            .map(Socket::new_custom),
+        #[cfg(feature = "runtime")]
+        None => connect_tokio_tcp(addr, config).await.map(Socket::new_tcp),
+        #[cfg(not(feature = "runtime"))]
+        None => Err(rt::no_runtime_error()),
+    }
+}
+
+#[cfg(unix)]
+async fn connect_unix(path: &Path, config: &SocketConfig) -> io::Result<Socket> {
+    match &config.runtime {
+        Some(runtime) => runtime.0.connect_unix(path).await.map(Socket::new_custom),
+        #[cfg(feature = "runtime")]
+        None => UnixStream::connect(path).await.map(Socket::new_unix),
+        #[cfg(not(feature = "runtime"))]
+        None => Err(rt::no_runtime_error()),
+    }
+}
+
+/// Opens a TCP connection to `addr` with tokio, bound to the local address and interface of the configuration.
+#[cfg(feature = "runtime")]
+async fn connect_tokio_tcp(addr: SocketAddr, config: &SocketConfig) -> io::Result<TcpStream> {
+    let socket = if addr.is_ipv4() {
+        TcpSocket::new_v4()
+    } else {
+        TcpSocket::new_v6()
+    }?;
+
+    if let Some(local_address) = config.local_address {
+        socket.bind(SocketAddr::new(local_address, 0))?;
+    }
+
+    if let Some(bind_interface) = &config.bind_interface {
+        bind_device(&socket, bind_interface)?;
+    }
+
+    let stream = socket.connect(addr).await?;
+
+    stream.set_nodelay(true)?;
+
+    let sock_ref = SockRef::from(&stream);
+
+    #[cfg(target_os = "linux")]
+    if let Some(tcp_user_timeout) = config.tcp_user_timeout {
+        sock_ref.set_tcp_user_timeout(Some(tcp_user_timeout))?;
+    }
+
+    if let Some(keepalive_config) = &config.keepalive {
+        sock_ref.set_tcp_keepalive(&TcpKeepalive::from(keepalive_config))?;
+    }
+
+    Ok(stream)
+}
+
+#[cfg(all(
+    feature = "runtime",
+    any(target_os = "android", target_os = "fuchsia", target_os = "linux")
+))]
+fn bind_device(socket: &TcpSocket, interface: &str) -> io::Result<()> {
+    SockRef::from(socket).bind_device(Some(interface.as_bytes()))
+}
+
+#[cfg(all(
+    feature = "runtime",
+    not(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))
+))]
+fn bind_device(_: &TcpSocket, _: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "binding to a network interface is not supported on this platform",
+    ))
+}
+
+async fn connect_with_timeout<F, T>(connect: F, config: &SocketConfig) -> Result<T, Error>
 where
     F: Future<Output = io::Result<T>>,
 {
-    match timeout {
-        Some(timeout) => match time::timeout(timeout, connect).await {
-            Ok(Ok(socket)) => Ok(socket),
-            Ok(Err(e)) => Err(Error::connect(e)),
-            Err(_) => Err(Error::connect(io::Error::new(
+    match config.connect_timeout {
+        Some(timeout) => match rt::timeout(config.runtime.as_ref(), timeout, connect).await {
+            Some(Ok(socket)) => Ok(socket),
+            Some(Err(e)) => Err(Error::connect(e)),
+            None => Err(Error::connect(io::Error::new(
                 io::ErrorKind::TimedOut,
                 "connection timed out",
             ))),
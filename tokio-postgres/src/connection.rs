@@ -1,7 +1,11 @@
 use crate::codec::{BackendMessage, BackendMessages, FrontendMessage, PostgresCodec};
+use crate::copy_both::CopyBothReceiver;
 use crate::copy_in::CopyInReceiver;
+use crate::cursor::CursorReceiver;
 use crate::error::DbError;
+use crate::listen::Listeners;
 use crate::maybe_tls_stream::MaybeTlsStream;
+use crate::time::Instant;
 use crate::{AsyncMessage, Error, Notification};
 use bytes::BytesMut;
 use fallible_iterator::FallibleIterator;
@@ -13,6 +17,7 @@ use postgres_protocol::message::frontend;
 use std::collections::{HashMap, VecDeque};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_util::codec::Framed;
@@ -20,15 +25,22 @@ use tokio_util::codec::Framed;
 pub enum RequestMessages {
     Single(FrontendMessage),
     CopyIn(CopyInReceiver),
+    CopyBoth(CopyBothReceiver),
+    Cursor(CursorReceiver),
+    Close,
 }
 
 pub struct Request {
     pub messages: RequestMessages,
     pub sender: mpsc::Sender<BackendMessages>,
+    /// Whether notices raised while processing the request should be forwarded along with its responses.
+    pub notices: bool,
 }
 
 pub struct Response {
     sender: mpsc::Sender<BackendMessages>,
+    sent_at: Instant,
+    notices: bool,
 }
 
 #[derive(PartialEq, Debug)]
@@ -54,6 +66,8 @@ pub struct Connection<S, T> {
     pending_responses: VecDeque<BackendMessage>,
     responses: VecDeque<Response>,
     state: State,
+    listeners: Arc<Listeners>,
+    close_waiters: Vec<mpsc::Sender<BackendMessages>>,
 }
 
 impl<S, T> Connection<S, T>
@@ -66,6 +80,7 @@ where
         pending_responses: VecDeque<BackendMessage>,
         parameters: HashMap<String, String>,
         receiver: mpsc::UnboundedReceiver<Request>,
+        listeners: Arc<Listeners>,
     ) -> Connection<S, T> {
         Connection {
             stream,
@@ -75,6 +90,8 @@ where
             pending_responses,
             responses: VecDeque::new(),
             state: State::Active,
+            listeners,
+            close_waiters: vec![],
         }
     }
 
@@ -109,8 +126,26 @@ where
             };
 
             let (mut messages, request_complete) = match message {
+                BackendMessage::Notice(messages) => {
+                    let error = match messages.clone().next().map_err(Error::parse)? {
+                        Some(Message::NoticeResponse(body)) => {
+                            DbError::parse(&mut body.fields()).map_err(Error::parse)?
+                        }
+                        _ => return Err(Error::unexpected_message()),
+                    };
+                    self.listeners.dispatch_notice(&error);
+                    if self.responses.front().is_some_and(|r| r.notices) {
+                        self.pending_responses.push_back(BackendMessage::Normal {
+                            messages,
+                            request_complete: false,
+                        });
+                    }
+                    return Ok(Some(AsyncMessage::Notice(error)));
+                }
+                // notices raised during startup, which are delayed until the connection is first polled
                 BackendMessage::Async(Message::NoticeResponse(body)) => {
                     let error = DbError::parse(&mut body.fields()).map_err(Error::parse)?;
+                    self.listeners.dispatch_notice(&error);
                     return Ok(Some(AsyncMessage::Notice(error)));
                 }
                 BackendMessage::Async(Message::NotificationResponse(body)) => {
@@ -119,13 +154,14 @@ where
                         channel: body.channel().map_err(Error::parse)?.to_string(),
                         payload: body.message().map_err(Error::parse)?.to_string(),
                     };
+                    self.listeners.dispatch(&notification);
                     return Ok(Some(AsyncMessage::Notification(notification)));
                 }
                 BackendMessage::Async(Message::ParameterStatus(body)) => {
-                    self.parameters.insert(
-                        body.name().map_err(Error::parse)?.to_string(),
-                        body.value().map_err(Error::parse)?.to_string(),
-                    );
+                    let name = body.name().map_err(Error::parse)?;
+                    let value = body.value().map_err(Error::parse)?;
+                    self.listeners.set_parameter(name, value);
+                    self.parameters.insert(name.to_string(), value.to_string());
                     continue;
                 }
                 BackendMessage::Async(_) => unreachable!(),
@@ -146,15 +182,11 @@ where
             match response.sender.poll_ready(cx) {
                 Poll::Ready(Ok(())) => {
                     let _ = response.sender.start_send(messages);
-                    if !request_complete {
-                        self.responses.push_front(response);
-                    }
+                    self.finish_response(response, request_complete);
                 }
                 Poll::Ready(Err(_)) => {
                     // we need to keep paging through the rest of the messages even if the receiver's hung up
-                    if !request_complete {
-                        self.responses.push_front(response);
-                    }
+                    self.finish_response(response, request_complete);
                 }
                 Poll::Pending => {
                     self.responses.push_front(response);
@@ -169,6 +201,17 @@ where
         }
     }
 
+    fn finish_response(&mut self, response: Response, request_complete: bool) {
+        if request_complete {
+            self.stream
+                .codec()
+                .stats()
+                .add_latency(response.sent_at.elapsed());
+        } else {
+            self.responses.push_front(response);
+        }
+    }
+
     fn poll_request(&mut self, cx: &mut Context<'_>) -> Poll<Option<RequestMessages>> {
         if let Some(messages) = self.pending_request.take() {
             trace!("retrying pending request");
@@ -179,16 +222,30 @@ where
             return Poll::Ready(None);
         }
 
-        match self.receiver.poll_next_unpin(cx) {
-            Poll::Ready(Some(request)) => {
-                trace!("polled new request");
-                self.responses.push_back(Response {
-                    sender: request.sender,
-                });
-                Poll::Ready(Some(request.messages))
+        loop {
+            match self.receiver.poll_next_unpin(cx) {
+                Poll::Ready(Some(Request {
+                    messages: RequestMessages::Close,
+                    sender,
+                    ..
+                })) => {
+                    // stop accepting new requests, but finish those already queued before terminating
+                    trace!("polled close request");
+                    self.receiver.close();
+                    self.close_waiters.push(sender);
+                }
+                Poll::Ready(Some(request)) => {
+                    trace!("polled new request");
+                    self.responses.push_back(Response {
+                        sender: request.sender,
+                        sent_at: Instant::now(),
+                        notices: request.notices,
+                    });
+                    return Poll::Ready(Some(request.messages));
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
             }
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => Poll::Pending,
         }
     }
 
@@ -258,6 +315,43 @@ where
                         .map_err(Error::io)?;
                     self.pending_request = Some(RequestMessages::CopyIn(receiver));
                 }
+                RequestMessages::CopyBoth(mut receiver) => {
+                    let message = match receiver.poll_next_unpin(cx) {
+                        Poll::Ready(Some(message)) => message,
+                        Poll::Ready(None) => {
+                            trace!("poll_write: finished copy_both request");
+                            continue;
+                        }
+                        Poll::Pending => {
+                            trace!("poll_write: waiting on copy_both stream");
+                            self.pending_request = Some(RequestMessages::CopyBoth(receiver));
+                            return Ok(true);
+                        }
+                    };
+                    Pin::new(&mut self.stream)
+                        .start_send(message)
+                        .map_err(Error::io)?;
+                    self.pending_request = Some(RequestMessages::CopyBoth(receiver));
+                }
+                RequestMessages::Cursor(mut receiver) => {
+                    let message = match receiver.poll_next_unpin(cx) {
+                        Poll::Ready(Some(message)) => message,
+                        Poll::Ready(None) => {
+                            trace!("poll_write: finished cursor request");
+                            continue;
+                        }
+                        Poll::Pending => {
+                            trace!("poll_write: waiting on cursor stream");
+                            self.pending_request = Some(RequestMessages::Cursor(receiver));
+                            return Ok(true);
+                        }
+                    };
+                    Pin::new(&mut self.stream)
+                        .start_send(message)
+                        .map_err(Error::io)?;
+                    self.pending_request = Some(RequestMessages::Cursor(receiver));
+                }
+                RequestMessages::Close => unreachable!(),
             }
         }
     }
@@ -284,6 +378,7 @@ where
         {
             Poll::Ready(()) => {
                 trace!("poll_shutdown: complete");
+                self.close_waiters.clear();
                 Poll::Ready(Ok(()))
             }
             Poll::Pending => {
@@ -341,3 +436,9 @@ where
         Poll::Ready(Ok(()))
     }
 }
+
+impl<S, T> Drop for Connection<S, T> {
+    fn drop(&mut self) {
+        self.listeners.close();
+    }
+}
@@ -0,0 +1,200 @@
+use crate::client::{InnerClient, Responses};
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::{simple_query, Error};
+use bytes::{Buf, Bytes, BytesMut};
+use futures_channel::mpsc;
+use futures_util::{ready, Sink, SinkExt, Stream, StreamExt};
+use log::debug;
+use pin_project_lite::pin_project;
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+use postgres_protocol::message::frontend::CopyData;
+use std::marker::{PhantomData, PhantomPinned};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+enum CopyBothMessage {
+    Message(FrontendMessage),
+    Done,
+}
+
+pub struct CopyBothReceiver {
+    receiver: mpsc::Receiver<CopyBothMessage>,
+    done: bool,
+}
+
+impl CopyBothReceiver {
+    fn new(receiver: mpsc::Receiver<CopyBothMessage>) -> CopyBothReceiver {
+        CopyBothReceiver {
+            receiver,
+            done: false,
+        }
+    }
+}
+
+impl Stream for CopyBothReceiver {
+    type Item = FrontendMessage;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<FrontendMessage>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        match ready!(self.receiver.poll_next_unpin(cx)) {
+            Some(CopyBothMessage::Message(message)) => Poll::Ready(Some(message)),
+            // The copy was started by a simple query, so unlike COPY FROM STDIN no Sync is needed to finish it.
+            Some(CopyBothMessage::Done) | None => {
+                self.done = true;
+                let mut buf = BytesMut::new();
+                frontend::copy_done(&mut buf);
+                Poll::Ready(Some(FrontendMessage::Raw(buf.freeze())))
+            }
+        }
+    }
+}
+
+enum SinkState {
+    Active,
+    Closing,
+    Reading,
+}
+
+pin_project! {
+    /// A duplex stream of `CopyData` messages, used by the streaming replication protocol.
+    ///
+    /// The stream half yields the data sent by the server, and ends when the server finishes its side of the copy. The
+    /// sink half sends each item to the server as a separate `CopyData` message.
+    ///
+    /// The client's side of the copy *must* be finished via the `Sink::close` method, which waits for the server to
+    /// complete the command. If the duplex is dropped instead, the client's side is finished without waiting.
+    pub struct CopyBothDuplex<T> {
+        #[pin]
+        sender: mpsc::Sender<CopyBothMessage>,
+        responses: Responses,
+        state: SinkState,
+        stream_done: bool,
+        #[pin]
+        _p: PhantomPinned,
+        _p2: PhantomData<T>,
+    }
+}
+
+impl<T> Stream for CopyBothDuplex<T> {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        if *this.stream_done {
+            return Poll::Ready(None);
+        }
+
+        match ready!(this.responses.poll_next(cx)?) {
+            Message::CopyData(body) => Poll::Ready(Some(Ok(body.into_bytes()))),
+            Message::CopyDone => {
+                *this.stream_done = true;
+                Poll::Ready(None)
+            }
+            _ => Poll::Ready(Some(Err(Error::unexpected_message()))),
+        }
+    }
+}
+
+impl<T> Sink<T> for CopyBothDuplex<T>
+where
+    T: Buf + 'static + Send,
+{
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.project()
+            .sender
+            .poll_ready(cx)
+            .map_err(|_| Error::closed())
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Error> {
+        let data = CopyData::new(item).map_err(Error::encode)?.into_buf();
+        self.project()
+            .sender
+            .start_send(CopyBothMessage::Message(FrontendMessage::Buf(Box::new(
+                data,
+            ))))
+            .map_err(|_| Error::closed())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.project()
+            .sender
+            .poll_flush(cx)
+            .map_err(|_| Error::closed())
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        loop {
+            match self.state {
+                SinkState::Active => {
+                    let mut this = self.as_mut().project();
+                    ready!(this.sender.as_mut().poll_ready(cx)).map_err(|_| Error::closed())?;
+                    this.sender
+                        .start_send(CopyBothMessage::Done)
+                        .map_err(|_| Error::closed())?;
+                    *this.state = SinkState::Closing;
+                }
+                SinkState::Closing => {
+                    let this = self.as_mut().project();
+                    ready!(this.sender.poll_close(cx)).map_err(|_| Error::closed())?;
+                    *this.state = SinkState::Reading;
+                }
+                SinkState::Reading => {
+                    let this = self.as_mut().project();
+                    match ready!(this.responses.poll_next(cx))? {
+                        Message::CopyData(_) => {}
+                        Message::CopyDone => *this.stream_done = true,
+                        Message::CommandComplete(_)
+                        | Message::RowDescription(_)
+                        | Message::DataRow(_) => {}
+                        Message::ReadyForQuery(_) => return Poll::Ready(Ok(())),
+                        _ => return Poll::Ready(Err(Error::unexpected_message())),
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub async fn copy_both_simple<T>(
+    client: &InnerClient,
+    query: &str,
+) -> Result<CopyBothDuplex<T>, Error>
+where
+    T: Buf + 'static + Send,
+{
+    debug!("executing copy both query {}", query);
+
+    let buf = simple_query::encode(client, query)?;
+
+    let (mut sender, receiver) = mpsc::channel(1);
+    let receiver = CopyBothReceiver::new(receiver);
+    let mut responses = client.send(RequestMessages::CopyBoth(receiver))?;
+
+    sender
+        .send(CopyBothMessage::Message(FrontendMessage::Raw(buf)))
+        .await
+        .map_err(|_| Error::closed())?;
+
+    match responses.next().await? {
+        Message::CopyBothResponse(_) => {}
+        _ => return Err(Error::unexpected_message()),
+    }
+
+    Ok(CopyBothDuplex {
+        sender,
+        responses,
+        state: SinkState::Active,
+        stream_done: false,
+        _p: PhantomPinned,
+        _p2: PhantomData,
+    })
+}
@@ -1,19 +1,24 @@
 use crate::client::{InnerClient, Responses};
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
+use crate::copy_progress::{CopyProgress, Progress};
 use crate::query::extract_row_affected;
 use crate::{query, slice_iter, Error, Statement};
-use bytes::{Buf, BufMut, BytesMut};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use futures_channel::mpsc;
-use futures_util::{future, ready, Sink, SinkExt, Stream, StreamExt};
+use futures_util::{future, pin_mut, ready, Sink, SinkExt, Stream, StreamExt};
 use log::debug;
 use pin_project_lite::pin_project;
 use postgres_protocol::message::backend::Message;
 use postgres_protocol::message::frontend;
 use postgres_protocol::message::frontend::CopyData;
+use std::io;
 use std::marker::{PhantomData, PhantomPinned};
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const READ_CHUNK_SIZE: usize = 8 * 1024;
 
 enum CopyInMessage {
     Message(FrontendMessage),
@@ -79,6 +84,7 @@ pin_project! {
         responses: Responses,
         buf: BytesMut,
         state: SinkState,
+        progress: Progress,
         #[pin]
         _p: PhantomPinned,
         _p2: PhantomData<T>,
@@ -89,6 +95,17 @@ impl<T> CopyInSink<T>
 where
     T: Buf + 'static + Send,
 {
+    /// Registers a callback which is invoked with the progress of the copy each time a chunk of data is sent to the
+    /// connection.
+    ///
+    /// Data is buffered and sent in chunks of a few kilobytes, so the callback is not invoked for every write.
+    pub fn set_progress_callback<F>(self: Pin<&mut Self>, callback: F)
+    where
+        F: FnMut(CopyProgress) + Send + Sync + 'static,
+    {
+        self.project().progress.set_callback(callback);
+    }
+
     /// A poll-based version of `finish`.
     pub fn poll_finish(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<u64, Error>> {
         loop {
@@ -145,26 +162,35 @@ where
 
     fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Error> {
         let this = self.project();
+        this.progress.add_bytes(item.remaining());
 
-        let data: Box<dyn Buf + Send> = if item.remaining() > 4096 {
+        // small chunks are encoded straight into the pending buffer, which is then handed to the connection as is,
+        // while large ones are chained after their header without being copied
+        let message = if item.remaining() > 4096 {
+            let data = CopyData::new(item).map_err(Error::encode)?.into_buf();
             if this.buf.is_empty() {
-                Box::new(item)
+                FrontendMessage::Buf(Box::new(data))
             } else {
-                Box::new(this.buf.split().freeze().chain(item))
+                FrontendMessage::Buf(Box::new(this.buf.split().freeze().chain(data)))
             }
         } else {
-            this.buf.put(item);
+            frontend::copy_data(this.buf, |buf| {
+                buf.put(item);
+                Ok::<_, io::Error>(())
+            })
+            .map_err(Error::encode)?;
             if this.buf.len() > 4096 {
-                Box::new(this.buf.split().freeze())
+                FrontendMessage::Buffer(this.buf.split())
             } else {
                 return Ok(());
             }
         };
 
-        let data = CopyData::new(data).map_err(Error::encode)?;
         this.sender
-            .start_send(CopyInMessage::Message(FrontendMessage::CopyData(data)))
-            .map_err(|_| Error::closed())
+            .start_send(CopyInMessage::Message(message))
+            .map_err(|_| Error::closed())?;
+        this.progress.report();
+        Ok(())
     }
 
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
@@ -172,12 +198,13 @@ where
 
         if !this.buf.is_empty() {
             ready!(this.sender.as_mut().poll_ready(cx)).map_err(|_| Error::closed())?;
-            let data: Box<dyn Buf + Send> = Box::new(this.buf.split().freeze());
-            let data = CopyData::new(data).map_err(Error::encode)?;
             this.sender
                 .as_mut()
-                .start_send(CopyInMessage::Message(FrontendMessage::CopyData(data)))
+                .start_send(CopyInMessage::Message(FrontendMessage::Buffer(
+                    this.buf.split(),
+                )))
                 .map_err(|_| Error::closed())?;
+            this.progress.report();
         }
 
         this.sender.poll_flush(cx).map_err(|_| Error::closed())
@@ -220,7 +247,32 @@ where
         responses,
         buf: BytesMut::new(),
         state: SinkState::Active,
+        progress: Progress::bytes(),
         _p: PhantomPinned,
         _p2: PhantomData,
     })
 }
+
+pub async fn copy_in_from<R>(
+    client: &InnerClient,
+    statement: Statement,
+    mut reader: R,
+) -> Result<u64, Error>
+where
+    R: AsyncRead + Unpin,
+{
+    let sink = copy_in::<Bytes>(client, statement).await?;
+    pin_mut!(sink);
+
+    let mut buf = BytesMut::new();
+    loop {
+        buf.reserve(READ_CHUNK_SIZE);
+        // If reading fails, the sink is dropped and the copy is aborted.
+        if reader.read_buf(&mut buf).await.map_err(Error::io)? == 0 {
+            break;
+        }
+        sink.as_mut().send(buf.split().freeze()).await?;
+    }
+
+    sink.finish().await
+}
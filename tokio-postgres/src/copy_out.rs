@@ -1,6 +1,8 @@
 use crate::client::{InnerClient, Responses};
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
+use crate::copy_progress::{CopyProgress, Progress};
+use crate::query::extract_row_affected;
 use crate::{query, slice_iter, Error, Statement};
 use bytes::Bytes;
 use futures_util::{ready, Stream};
@@ -10,6 +12,7 @@ use postgres_protocol::message::backend::Message;
 use std::marker::PhantomPinned;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 pub async fn copy_out(client: &InnerClient, statement: Statement) -> Result<CopyOutStream, Error> {
     debug!("executing copy out statement {}", statement.name());
@@ -18,10 +21,39 @@ pub async fn copy_out(client: &InnerClient, statement: Statement) -> Result<Copy
     let responses = start(client, buf).await?;
     Ok(CopyOutStream {
         responses,
+        progress: Progress::bytes(),
         _p: PhantomPinned,
     })
 }
 
+pub async fn copy_out_to<W>(
+    client: &InnerClient,
+    statement: Statement,
+    mut writer: W,
+) -> Result<u64, Error>
+where
+    W: AsyncWrite + Unpin,
+{
+    debug!("executing copy out statement {}", statement.name());
+
+    let buf = query::encode(client, &statement, slice_iter(&[]))?;
+    let mut responses = start(client, buf).await?;
+
+    loop {
+        match responses.next().await? {
+            Message::CopyData(body) => writer.write_all(body.data()).await.map_err(Error::io)?,
+            Message::CopyDone => break,
+            _ => return Err(Error::unexpected_message()),
+        }
+    }
+    writer.flush().await.map_err(Error::io)?;
+
+    match responses.next().await? {
+        Message::CommandComplete(body) => extract_row_affected(&body),
+        _ => Err(Error::unexpected_message()),
+    }
+}
+
 async fn start(client: &InnerClient, buf: Bytes) -> Result<Responses, Error> {
     let mut responses = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)))?;
 
@@ -42,11 +74,22 @@ pin_project! {
     /// A stream of `COPY ... TO STDOUT` query data.
     pub struct CopyOutStream {
         responses: Responses,
+        progress: Progress,
         #[pin]
         _p: PhantomPinned,
     }
 }
 
+impl CopyOutStream {
+    /// Registers a callback which is invoked with the progress of the copy each time a chunk of data is received.
+    pub fn set_progress_callback<F>(self: Pin<&mut Self>, callback: F)
+    where
+        F: FnMut(CopyProgress) + Send + Sync + 'static,
+    {
+        self.project().progress.set_callback(callback);
+    }
+}
+
 impl Stream for CopyOutStream {
     type Item = Result<Bytes, Error>;
 
@@ -54,7 +97,12 @@ impl Stream for CopyOutStream {
         let this = self.project();
 
         match ready!(this.responses.poll_next(cx)?) {
-            Message::CopyData(body) => Poll::Ready(Some(Ok(body.into_bytes()))),
+            Message::CopyData(body) => {
+                let data = body.into_bytes();
+                this.progress.add_bytes(data.len());
+                this.progress.report();
+                Poll::Ready(Some(Ok(data)))
+            }
             Message::CopyDone => Poll::Ready(None),
             _ => Poll::Ready(Some(Err(Error::unexpected_message()))),
         }
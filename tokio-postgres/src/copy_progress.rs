@@ -0,0 +1,80 @@
+/// The progress of a `COPY` operation, passed to the callbacks registered with the `set_progress_callback` methods of
+/// the copy types.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CopyProgress {
+    bytes: u64,
+    rows: Option<u64>,
+}
+
+impl CopyProgress {
+    #[doc(hidden)]
+    pub fn __private_api_new(bytes: u64, rows: Option<u64>) -> CopyProgress {
+        CopyProgress { bytes, rows }
+    }
+
+    /// Returns the number of bytes of copy data transferred so far.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns the number of rows transferred so far.
+    ///
+    /// This is only known for the binary copy types, which parse the copy data into rows; it is `None` for raw copy
+    /// data.
+    pub fn rows(&self) -> Option<u64> {
+        self.rows
+    }
+}
+
+type Callback = Box<dyn FnMut(CopyProgress) + Send + Sync>;
+
+/// Tracks the progress of a copy, reporting it to a callback if one is registered.
+pub(crate) struct Progress {
+    progress: CopyProgress,
+    callback: Option<Callback>,
+}
+
+impl Progress {
+    pub fn bytes() -> Progress {
+        Progress {
+            progress: CopyProgress {
+                bytes: 0,
+                rows: None,
+            },
+            callback: None,
+        }
+    }
+
+    pub fn rows() -> Progress {
+        Progress {
+            progress: CopyProgress {
+                bytes: 0,
+                rows: Some(0),
+            },
+            callback: None,
+        }
+    }
+
+    pub fn set_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(CopyProgress) + Send + Sync + 'static,
+    {
+        self.callback = Some(Box::new(callback));
+    }
+
+    pub fn add_bytes(&mut self, bytes: usize) {
+        self.progress.bytes += bytes as u64;
+    }
+
+    pub fn add_row(&mut self) {
+        if let Some(rows) = &mut self.progress.rows {
+            *rows += 1;
+        }
+    }
+
+    pub fn report(&mut self) {
+        if let Some(callback) = &mut self.callback {
+            callback(self.progress);
+        }
+    }
+}
@@ -0,0 +1,1117 @@
+//! Utilities for working with the PostgreSQL CSV copy format via serde.
+//!
+//! Rows are serialized from, and deserialized into, types implementing serde's `Serialize` and `Deserialize` traits.
+//! Each row must be a struct, tuple, or sequence whose fields are scalar values: booleans, numbers, strings, bytes,
+//! unit enum variants, or `Option`s of those. `None` is written as the `NULL` marker, and a `NULL` marker is read as
+//! `None`.
+//!
+//! The options used by the writer and stream must match those of the `COPY` statement. Only the default delimiter
+//! (`,`) and quote (`"`) characters are supported.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use serde_1 as serde;
+//! use futures_util::{pin_mut, stream, TryStreamExt};
+//! use serde::{Deserialize, Serialize};
+//! use tokio_postgres::csv_copy::{CsvCopyInWriter, CsvCopyOutStream};
+//! use tokio_postgres::{Client, Error};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! # #[serde(crate = "serde_1")]
+//! struct Person {
+//!     id: i32,
+//!     name: Option<String>,
+//! }
+//!
+//! async fn copy(client: &Client, people: Vec<Person>) -> Result<Vec<Person>, Error> {
+//!     let sink = client
+//!         .copy_in("COPY people (id, name) FROM STDIN (FORMAT csv, HEADER true)")
+//!         .await?;
+//!     let writer = CsvCopyInWriter::new(sink).header(true);
+//!     pin_mut!(writer);
+//!     writer.as_mut().write_all(stream::iter(people)).await?;
+//!     writer.finish().await?;
+//!
+//!     let stream = client
+//!         .copy_out("COPY people (id, name) TO STDOUT (FORMAT csv, HEADER true)")
+//!         .await?;
+//!     CsvCopyOutStream::new(stream).header(true).try_collect().await
+//! }
+//! ```
+
+use crate::{CopyInSink, CopyOutStream, Error};
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_util::{ready, SinkExt, Stream, StreamExt};
+use pin_project_lite::pin_project;
+use serde_1::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde_1::ser::{self, Serialize};
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::str;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+const DELIMITER: u8 = b',';
+const QUOTE: u8 = b'"';
+
+#[derive(Debug)]
+struct CsvError(String);
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+impl ser::Error for CsvError {
+    fn custom<T>(msg: T) -> CsvError
+    where
+        T: fmt::Display,
+    {
+        CsvError(msg.to_string())
+    }
+}
+
+impl de::Error for CsvError {
+    fn custom<T>(msg: T) -> CsvError
+    where
+        T: fmt::Display,
+    {
+        CsvError(msg.to_string())
+    }
+}
+
+fn unsupported(what: &str) -> CsvError {
+    CsvError(format!("{} cannot be represented as a CSV field", what))
+}
+
+pin_project! {
+    /// A type which serializes rows into the PostgreSQL CSV copy format.
+    ///
+    /// The copy *must* be explicitly completed via the `finish` method. If it is not, the copy will be aborted.
+    pub struct CsvCopyInWriter<T> {
+        #[pin]
+        sink: CopyInSink<Bytes>,
+        buf: BytesMut,
+        null: String,
+        header: bool,
+        _p: PhantomData<fn(&T)>,
+    }
+}
+
+impl<T> CsvCopyInWriter<T>
+where
+    T: Serialize,
+{
+    /// Creates a new writer which will write rows to the provided sink.
+    pub fn new(sink: CopyInSink<Bytes>) -> CsvCopyInWriter<T> {
+        CsvCopyInWriter {
+            sink,
+            buf: BytesMut::new(),
+            null: String::new(),
+            header: false,
+            _p: PhantomData,
+        }
+    }
+
+    /// Sets whether a header row of field names is written before the first row.
+    ///
+    /// This should match the `HEADER` option of the `COPY` statement. Defaults to `false`.
+    pub fn header(mut self, header: bool) -> CsvCopyInWriter<T> {
+        self.header = header;
+        self
+    }
+
+    /// Sets the string written for `NULL` values.
+    ///
+    /// This should match the `NULL` option of the `COPY` statement. Defaults to an unquoted empty string.
+    pub fn null(mut self, null: &str) -> CsvCopyInWriter<T> {
+        self.null = null.to_string();
+        self
+    }
+
+    /// Writes a single row.
+    pub async fn write(self: Pin<&mut Self>, value: &T) -> Result<(), Error> {
+        let mut this = self.project();
+
+        if *this.header {
+            *this.header = false;
+            let mut names = vec![];
+            value
+                .serialize(RecordSerializer::names(&mut names))
+                .map_err(|e| Error::encode(io::Error::new(io::ErrorKind::InvalidInput, e)))?;
+            let mut first = true;
+            for name in names {
+                if !first {
+                    this.buf.put_u8(DELIMITER);
+                }
+                first = false;
+                write_field(this.buf, &name, this.null);
+            }
+            this.buf.put_u8(b'\n');
+        }
+
+        let base = this.buf.len();
+        let r = value.serialize(RecordSerializer::values(this.buf, this.null));
+        if let Err(e) = r {
+            this.buf.truncate(base);
+            return Err(Error::encode(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                e,
+            )));
+        }
+        this.buf.put_u8(b'\n');
+
+        if this.buf.len() > 4096 {
+            this.sink.send(this.buf.split().freeze()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes every row produced by a stream.
+    ///
+    /// Rows from an iterator can be written by wrapping it in `futures_util::stream::iter`.
+    pub async fn write_all<S>(mut self: Pin<&mut Self>, stream: S) -> Result<(), Error>
+    where
+        S: Stream<Item = T>,
+    {
+        futures_util::pin_mut!(stream);
+        while let Some(value) = stream.next().await {
+            self.as_mut().write(&value).await?;
+        }
+        Ok(())
+    }
+
+    /// Completes the copy, returning the number of rows added.
+    ///
+    /// This method *must* be used to complete the copy process. If it is not, the copy will be aborted.
+    pub async fn finish(self: Pin<&mut Self>) -> Result<u64, Error> {
+        let mut this = self.project();
+
+        if !this.buf.is_empty() {
+            this.sink.send(this.buf.split().freeze()).await?;
+        }
+        this.sink.finish().await
+    }
+}
+
+fn write_field(buf: &mut BytesMut, value: &str, null: &str) {
+    let needs_quotes = value.is_empty()
+        || value == null
+        || value == "\\."
+        || value
+            .bytes()
+            .any(|b| matches!(b, DELIMITER | QUOTE | b'\n' | b'\r'));
+
+    if !needs_quotes {
+        buf.put_slice(value.as_bytes());
+        return;
+    }
+
+    buf.put_u8(QUOTE);
+    for b in value.bytes() {
+        if b == QUOTE {
+            buf.put_u8(QUOTE);
+        }
+        buf.put_u8(b);
+    }
+    buf.put_u8(QUOTE);
+}
+
+enum RecordTarget<'a> {
+    Names(&'a mut Vec<String>),
+    Values {
+        buf: &'a mut BytesMut,
+        null: &'a str,
+        first: bool,
+    },
+}
+
+struct RecordSerializer<'a> {
+    target: RecordTarget<'a>,
+}
+
+impl<'a> RecordSerializer<'a> {
+    fn names(names: &'a mut Vec<String>) -> RecordSerializer<'a> {
+        RecordSerializer {
+            target: RecordTarget::Names(names),
+        }
+    }
+
+    fn values(buf: &'a mut BytesMut, null: &'a str) -> RecordSerializer<'a> {
+        RecordSerializer {
+            target: RecordTarget::Values {
+                buf,
+                null,
+                first: true,
+            },
+        }
+    }
+
+    fn field<T>(&mut self, name: Option<&str>, value: &T) -> Result<(), CsvError>
+    where
+        T: ?Sized + Serialize,
+    {
+        match &mut self.target {
+            RecordTarget::Names(names) => {
+                let idx = names.len();
+                names.push(name.map_or_else(|| idx.to_string(), str::to_string));
+            }
+            RecordTarget::Values { buf, null, first } => {
+                if !*first {
+                    buf.put_u8(DELIMITER);
+                }
+                *first = false;
+                match value.serialize(FieldSerializer)? {
+                    Some(value) => write_field(buf, &value, null),
+                    None => buf.put_slice(null.as_bytes()),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+macro_rules! unsupported_record {
+    ($($method:ident($($arg:ty),*),)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<(), CsvError> {
+                Err(CsvError("rows must be structs, tuples, or sequences".to_string()))
+            }
+        )*
+    };
+}
+
+impl<'a> ser::Serializer for RecordSerializer<'a> {
+    type Ok = ();
+    type Error = CsvError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = ser::Impossible<(), CsvError>;
+    type SerializeMap = ser::Impossible<(), CsvError>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = ser::Impossible<(), CsvError>;
+
+    unsupported_record! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), CsvError>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T>(self, _: &'static str, value: &T) -> Result<(), CsvError>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), CsvError>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(unsupported("an enum row"))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self, CsvError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, CsvError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, CsvError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, CsvError> {
+        Err(unsupported("an enum row"))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, CsvError> {
+        Err(unsupported("a map row"))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, CsvError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, CsvError> {
+        Err(unsupported("an enum row"))
+    }
+}
+
+impl ser::SerializeSeq for RecordSerializer<'_> {
+    type Ok = ();
+    type Error = CsvError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), CsvError>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(None, value)
+    }
+
+    fn end(self) -> Result<(), CsvError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for RecordSerializer<'_> {
+    type Ok = ();
+    type Error = CsvError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), CsvError>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(None, value)
+    }
+
+    fn end(self) -> Result<(), CsvError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for RecordSerializer<'_> {
+    type Ok = ();
+    type Error = CsvError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), CsvError>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(None, value)
+    }
+
+    fn end(self) -> Result<(), CsvError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for RecordSerializer<'_> {
+    type Ok = ();
+    type Error = CsvError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), CsvError>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.field(Some(key), value)
+    }
+
+    fn end(self) -> Result<(), CsvError> {
+        Ok(())
+    }
+}
+
+// Serializes a single field to its text representation, or `None` for `NULL`.
+struct FieldSerializer;
+
+macro_rules! display_field {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Option<String>, CsvError> {
+                Ok(Some(v.to_string()))
+            }
+        )*
+    };
+}
+
+macro_rules! float_field {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Option<String>, CsvError> {
+                let s = if v.is_nan() {
+                    "NaN".to_string()
+                } else if v == <$ty>::INFINITY {
+                    "Infinity".to_string()
+                } else if v == <$ty>::NEG_INFINITY {
+                    "-Infinity".to_string()
+                } else {
+                    v.to_string()
+                };
+                Ok(Some(s))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for FieldSerializer {
+    type Ok = Option<String>;
+    type Error = CsvError;
+    type SerializeSeq = ser::Impossible<Option<String>, CsvError>;
+    type SerializeTuple = ser::Impossible<Option<String>, CsvError>;
+    type SerializeTupleStruct = ser::Impossible<Option<String>, CsvError>;
+    type SerializeTupleVariant = ser::Impossible<Option<String>, CsvError>;
+    type SerializeMap = ser::Impossible<Option<String>, CsvError>;
+    type SerializeStruct = ser::Impossible<Option<String>, CsvError>;
+    type SerializeStructVariant = ser::Impossible<Option<String>, CsvError>;
+
+    display_field! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_char(char),
+        serialize_str(&str),
+    }
+
+    float_field! {
+        serialize_f32(f32),
+        serialize_f64(f64),
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Option<String>, CsvError> {
+        let mut s = String::with_capacity(2 + v.len() * 2);
+        s.push_str("\\x");
+        for b in v {
+            s.push_str(&format!("{:02x}", b));
+        }
+        Ok(Some(s))
+    }
+
+    fn serialize_none(self) -> Result<Option<String>, CsvError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Option<String>, CsvError>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Option<String>, CsvError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Option<String>, CsvError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Option<String>, CsvError> {
+        Ok(Some(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Option<String>, CsvError>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Option<String>, CsvError>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(unsupported("a newtype variant"))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, CsvError> {
+        Err(unsupported("a sequence"))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, CsvError> {
+        Err(unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, CsvError> {
+        Err(unsupported("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, CsvError> {
+        Err(unsupported("a tuple variant"))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, CsvError> {
+        Err(unsupported("a map"))
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, CsvError> {
+        Err(unsupported("a struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, CsvError> {
+        Err(unsupported("a struct variant"))
+    }
+}
+
+pin_project! {
+    /// A stream of rows deserialized from the PostgreSQL CSV copy format.
+    pub struct CsvCopyOutStream<T> {
+        #[pin]
+        stream: CopyOutStream,
+        buf: BytesMut,
+        null: String,
+        header: bool,
+        names: Option<Arc<[String]>>,
+        done: bool,
+        _p: PhantomData<fn() -> T>,
+    }
+}
+
+impl<T> CsvCopyOutStream<T>
+where
+    T: DeserializeOwned,
+{
+    /// Creates a stream from a raw copy out stream.
+    pub fn new(stream: CopyOutStream) -> CsvCopyOutStream<T> {
+        CsvCopyOutStream {
+            stream,
+            buf: BytesMut::new(),
+            null: String::new(),
+            header: false,
+            names: None,
+            done: false,
+            _p: PhantomData,
+        }
+    }
+
+    /// Sets whether the data starts with a header row of column names.
+    ///
+    /// This should match the `HEADER` option of the `COPY` statement. If set, struct fields are matched to columns by
+    /// name rather than by position. Defaults to `false`.
+    pub fn header(mut self, header: bool) -> CsvCopyOutStream<T> {
+        self.header = header;
+        self
+    }
+
+    /// Sets the string which represents `NULL` values.
+    ///
+    /// This should match the `NULL` option of the `COPY` statement. Defaults to an unquoted empty string.
+    pub fn null(mut self, null: &str) -> CsvCopyOutStream<T> {
+        self.null = null.to_string();
+        self
+    }
+}
+
+impl<T> Stream for CsvCopyOutStream<T>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<T, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        loop {
+            let record = match parse_record(this.buf, *this.done) {
+                Ok(record) => record,
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            };
+
+            let fields = match record {
+                Some(fields) => fields,
+                None if *this.done => return Poll::Ready(None),
+                None => {
+                    match ready!(this.stream.as_mut().poll_next(cx)) {
+                        Some(Ok(chunk)) => this.buf.extend_from_slice(&chunk),
+                        Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                        None => *this.done = true,
+                    }
+                    continue;
+                }
+            };
+
+            if *this.header {
+                *this.header = false;
+                let names = fields.into_iter().map(|f| f.value).collect::<Vec<_>>();
+                *this.names = Some(names.into());
+                continue;
+            }
+
+            let values = fields
+                .iter()
+                .map(|f| {
+                    if !f.quoted && f.value == **this.null {
+                        None
+                    } else {
+                        Some(&*f.value)
+                    }
+                })
+                .collect::<Vec<_>>();
+            let deserializer = RecordDeserializer {
+                values: &values,
+                names: this.names.as_deref(),
+            };
+            let row = T::deserialize(deserializer)
+                .map_err(|e| Error::parse(io::Error::new(io::ErrorKind::InvalidData, e)));
+            return Poll::Ready(Some(row));
+        }
+    }
+}
+
+struct Field {
+    value: String,
+    quoted: bool,
+}
+
+// Parses a complete record from the front of the buffer, returning `None` if more data is needed.
+fn parse_record(buf: &mut BytesMut, eof: bool) -> Result<Option<Vec<Field>>, Error> {
+    if buf.is_empty() {
+        return Ok(None);
+    }
+
+    let mut fields = vec![];
+    let mut value = vec![];
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut i = 0;
+
+    let end = loop {
+        let b = match buf.get(i) {
+            Some(b) => *b,
+            None if eof && !in_quotes => break i,
+            None if eof => {
+                return Err(Error::parse(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "unterminated quoted CSV field",
+                )))
+            }
+            None => return Ok(None),
+        };
+
+        if in_quotes {
+            if b == QUOTE {
+                match buf.get(i + 1) {
+                    Some(&QUOTE) => {
+                        value.push(QUOTE);
+                        i += 1;
+                    }
+                    Some(_) => in_quotes = false,
+                    None if eof => in_quotes = false,
+                    None => return Ok(None),
+                }
+            } else {
+                value.push(b);
+            }
+        } else {
+            match b {
+                QUOTE => {
+                    in_quotes = true;
+                    quoted = true;
+                }
+                DELIMITER => {
+                    fields.push(finish_field(&mut value, quoted)?);
+                    quoted = false;
+                }
+                b'\n' => break i + 1,
+                b'\r' if buf.get(i + 1) == Some(&b'\n') => break i + 2,
+                b'\r' if buf.get(i + 1).is_none() && !eof => return Ok(None),
+                b'\r' => break i + 1,
+                b => value.push(b),
+            }
+        }
+        i += 1;
+    };
+
+    fields.push(finish_field(&mut value, quoted)?);
+    let _ = buf.split_to(end);
+    Ok(Some(fields))
+}
+
+fn finish_field(value: &mut Vec<u8>, quoted: bool) -> Result<Field, Error> {
+    let value = String::from_utf8(std::mem::take(value))
+        .map_err(|e| Error::parse(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+    Ok(Field { value, quoted })
+}
+
+struct RecordDeserializer<'a> {
+    values: &'a [Option<&'a str>],
+    names: Option<&'a [String]>,
+}
+
+impl<'de> de::Deserializer<'de> for RecordDeserializer<'_> {
+    type Error = CsvError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        let values = self.values.iter().map(|v| FieldDeserializer(*v));
+        visitor.visit_seq(de::value::SeqDeserializer::new(values))
+    }
+
+    fn deserialize_tuple<V>(self, _: usize, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _: &'static str,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        let names = self
+            .names
+            .ok_or_else(|| CsvError("deserializing a map requires a header row".to_string()))?;
+        if names.len() != self.values.len() {
+            return Err(CsvError(format!(
+                "expected {} fields but got {}",
+                names.len(),
+                self.values.len()
+            )));
+        }
+        let entries = names
+            .iter()
+            .map(|n| &**n)
+            .zip(self.values.iter().map(|v| FieldDeserializer(*v)));
+        visitor.visit_map(de::value::MapDeserializer::new(entries))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        if self.names.is_some() {
+            self.deserialize_map(visitor)
+        } else {
+            self.deserialize_seq(visitor)
+        }
+    }
+
+    serde_1::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+        unit_struct enum identifier ignored_any
+    }
+}
+
+// Deserializes a single field from its text representation, or `None` for `NULL`.
+#[derive(Copy, Clone)]
+struct FieldDeserializer<'a>(Option<&'a str>);
+
+impl<'a> FieldDeserializer<'a> {
+    fn value(self) -> Result<&'a str, CsvError> {
+        self.0
+            .ok_or_else(|| CsvError("unexpected NULL value".to_string()))
+    }
+
+    fn parse<T>(self) -> Result<T, CsvError>
+    where
+        T: str::FromStr,
+        T::Err: fmt::Display,
+    {
+        let value = self.value()?;
+        value
+            .parse()
+            .map_err(|e| CsvError(format!("invalid value `{}`: {}", value, e)))
+    }
+
+    fn parse_float<T>(self) -> Result<T, CsvError>
+    where
+        T: str::FromStr,
+        T::Err: fmt::Display,
+    {
+        match self.value()? {
+            "Infinity" => "inf".parse(),
+            "-Infinity" => "-inf".parse(),
+            v => v.parse(),
+        }
+        .map_err(|e| CsvError(format!("invalid value: {}", e)))
+    }
+}
+
+impl<'de> IntoDeserializer<'de, CsvError> for FieldDeserializer<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! parse_field {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, CsvError>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for FieldDeserializer<'_> {
+    type Error = CsvError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Some(value) => visitor.visit_str(value),
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value()? {
+            "t" | "true" | "TRUE" | "on" | "yes" | "1" => visitor.visit_bool(true),
+            "f" | "false" | "FALSE" | "off" | "no" | "0" => visitor.visit_bool(false),
+            v => Err(CsvError(format!("invalid boolean `{}`", v))),
+        }
+    }
+
+    parse_field! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_f32(self.parse_float()?)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_f64(self.parse_float()?)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_str(self.value()?)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        let value = self.value()?;
+        let hex = value
+            .strip_prefix("\\x")
+            .ok_or_else(|| CsvError(format!("invalid bytea `{}`", value)))?;
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .and_then(|b| u8::from_str_radix(b, 16).ok())
+                    .ok_or_else(|| CsvError(format!("invalid bytea `{}`", value)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        visitor.visit_byte_buf(bytes)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Some(_) => visitor.visit_some(self),
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _: &'static str, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(self.value()?.into_deserializer())
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, CsvError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    serde_1::forward_to_deserialize_any! {
+        seq tuple tuple_struct map struct
+    }
+}
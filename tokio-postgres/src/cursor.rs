@@ -0,0 +1,193 @@
+use crate::client::{InnerClient, Responses};
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::query::{encode_bind, extract_row_affected};
+use crate::types::BorrowToSql;
+use crate::{Error, Row, Statement};
+use bytes::{Bytes, BytesMut};
+use futures_channel::mpsc;
+use futures_util::{ready, Stream, StreamExt};
+use log::debug;
+use pin_project_lite::pin_project;
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+enum CursorMessage {
+    Message(FrontendMessage),
+    Done,
+}
+
+pub struct CursorReceiver {
+    receiver: mpsc::UnboundedReceiver<CursorMessage>,
+    done: bool,
+}
+
+impl CursorReceiver {
+    fn new(receiver: mpsc::UnboundedReceiver<CursorMessage>) -> CursorReceiver {
+        CursorReceiver {
+            receiver,
+            done: false,
+        }
+    }
+}
+
+impl Stream for CursorReceiver {
+    type Item = FrontendMessage;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<FrontendMessage>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        match ready!(self.receiver.poll_next_unpin(cx)) {
+            Some(CursorMessage::Message(message)) => Poll::Ready(Some(message)),
+            Some(CursorMessage::Done) => {
+                self.done = true;
+                let mut buf = BytesMut::new();
+                frontend::sync(&mut buf);
+                Poll::Ready(Some(FrontendMessage::Raw(buf.freeze())))
+            }
+            // The stream was dropped before the portal was exhausted, so close it to release its resources.
+            None => {
+                self.done = true;
+                let mut buf = BytesMut::new();
+                frontend::close(b'P', "", &mut buf).unwrap();
+                frontend::sync(&mut buf);
+                Poll::Ready(Some(FrontendMessage::Raw(buf.freeze())))
+            }
+        }
+    }
+}
+
+fn encode_execute(fetch_size: i32) -> Result<Bytes, Error> {
+    let mut buf = BytesMut::new();
+    frontend::execute("", fetch_size, &mut buf).map_err(Error::encode)?;
+    frontend::flush(&mut buf);
+    Ok(buf.freeze())
+}
+
+pub async fn query_cursor<P, I>(
+    client: &InnerClient,
+    statement: Statement,
+    params: I,
+    fetch_size: i32,
+) -> Result<CursorStream, Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    assert!(fetch_size > 0, "fetch size must be positive");
+    debug!(
+        "executing statement {} with a fetch size of {}",
+        statement.name(),
+        fetch_size
+    );
+
+    let buf = client.with_buf(|buf| {
+        encode_bind(&statement, params, "", buf)?;
+        frontend::execute("", fetch_size, buf).map_err(Error::encode)?;
+        frontend::flush(buf);
+        Ok(buf.split().freeze())
+    })?;
+
+    let (sender, receiver) = mpsc::unbounded();
+    let receiver = CursorReceiver::new(receiver);
+    let mut responses = client.send(RequestMessages::Cursor(receiver))?;
+
+    sender
+        .unbounded_send(CursorMessage::Message(FrontendMessage::Raw(buf)))
+        .map_err(|_| Error::closed())?;
+
+    match responses.next().await? {
+        Message::BindComplete => {}
+        _ => return Err(Error::unexpected_message()),
+    }
+
+    Ok(CursorStream {
+        statement,
+        sender: Some(sender),
+        responses,
+        fetch_size,
+        rows: 0,
+        rows_affected: None,
+        _p: PhantomPinned,
+    })
+}
+
+pin_project! {
+    /// A stream of the rows of a query, fetched from the server in batches.
+    ///
+    /// The connection is reserved for the stream until it has been exhausted or dropped; other requests made on the
+    /// client in the meantime are not sent until then.
+    pub struct CursorStream {
+        statement: Statement,
+        sender: Option<mpsc::UnboundedSender<CursorMessage>>,
+        responses: Responses,
+        fetch_size: i32,
+        rows: u64,
+        rows_affected: Option<u64>,
+        #[pin]
+        _p: PhantomPinned,
+    }
+}
+
+impl Stream for CursorStream {
+    type Item = Result<Row, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        loop {
+            let message = match ready!(this.responses.poll_next(cx)) {
+                Ok(message) => message,
+                Err(e) => {
+                    // Dropping the sender closes the portal and syncs the connection.
+                    *this.sender = None;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            };
+
+            match message {
+                Message::DataRow(body) => {
+                    *this.rows += 1;
+                    return Poll::Ready(Some(Ok(Row::new(this.statement.clone(), body)?)));
+                }
+                Message::PortalSuspended => {
+                    let buf = encode_execute(*this.fetch_size)?;
+                    this.sender
+                        .as_ref()
+                        .ok_or_else(Error::closed)?
+                        .unbounded_send(CursorMessage::Message(FrontendMessage::Raw(buf)))
+                        .map_err(|_| Error::closed())?;
+                }
+                Message::CommandComplete(body) => {
+                    // The command tag of a suspended query only counts the rows of the final batch.
+                    let rows = extract_row_affected(&body)?;
+                    *this.rows_affected = Some(rows.max(*this.rows));
+                    if let Some(sender) = this.sender.take() {
+                        let _ = sender.unbounded_send(CursorMessage::Done);
+                    }
+                }
+                Message::EmptyQueryResponse => {
+                    if let Some(sender) = this.sender.take() {
+                        let _ = sender.unbounded_send(CursorMessage::Done);
+                    }
+                }
+                Message::ReadyForQuery(_) => return Poll::Ready(None),
+                _ => return Poll::Ready(Some(Err(Error::unexpected_message()))),
+            }
+        }
+    }
+}
+
+impl CursorStream {
+    /// Returns the number of rows affected by the query.
+    ///
+    /// This function will return `None` until the stream has been exhausted.
+    pub fn rows_affected(&self) -> Option<u64> {
+        self.rows_affected
+    }
+}
@@ -83,6 +83,7 @@ pub struct DbError {
     file: Option<String>,
     line: Option<u32>,
     routine: Option<String>,
+    fields: Vec<(u8, String)>,
 }
 
 impl DbError {
@@ -105,9 +106,11 @@ impl DbError {
         let mut file = None;
         let mut line = None;
         let mut routine = None;
+        let mut raw_fields = vec![];
 
         while let Some(field) = fields.next()? {
             let value = String::from_utf8_lossy(field.value_bytes());
+            raw_fields.push((field.type_(), value.to_string()));
             match field.type_() {
                 b'S' => severity = Some(value.into_owned()),
                 b'C' => code = Some(SqlState::from_code(&value)),
@@ -193,6 +196,7 @@ impl DbError {
             file,
             line,
             routine,
+            fields: raw_fields,
         })
     }
 
@@ -306,6 +310,14 @@ impl DbError {
     pub fn routine(&self) -> Option<&str> {
         self.routine.as_deref()
     }
+
+    /// All of the fields of the message, in the order they were sent by the server.
+    ///
+    /// Each field is a pair of its single-byte type code (e.g. `b'M'` for the message) and its value. This includes
+    /// fields without a dedicated accessor, such as those added by newer server versions.
+    pub fn fields(&self) -> &[(u8, String)] {
+        &self.fields
+    }
 }
 
 impl fmt::Display for DbError {
@@ -354,7 +366,8 @@ enum Kind {
     ConfigParse,
     Config,
     RowCount,
-    #[cfg(feature = "runtime")]
+    PipelineAborted,
+    #[cfg(feature = "runtime-core")]
     Connect,
     Timeout,
 }
@@ -396,7 +409,10 @@ impl fmt::Display for Error {
             Kind::ConfigParse => fmt.write_str("invalid connection string")?,
             Kind::Config => fmt.write_str("invalid configuration")?,
             Kind::RowCount => fmt.write_str("query returned an unexpected number of rows")?,
-            #[cfg(feature = "runtime")]
+            Kind::PipelineAborted => {
+                fmt.write_str("statement skipped due to an earlier error in the pipeline")?
+            }
+            #[cfg(feature = "runtime-core")]
             Kind::Connect => fmt.write_str("error connecting to server")?,
             Kind::Timeout => fmt.write_str("timeout waiting for server")?,
         };
@@ -431,6 +447,16 @@ impl Error {
         self.0.kind == Kind::Closed
     }
 
+    /// Determines if the error was caused by a timeout.
+    pub fn is_timeout(&self) -> bool {
+        self.0.kind == Kind::Timeout
+    }
+
+    /// Determines if the error was returned for a pipelined statement which was skipped due to an earlier error.
+    pub fn is_pipeline_aborted(&self) -> bool {
+        self.0.kind == Kind::PipelineAborted
+    }
+
     /// Returns the SQLSTATE error code associated with the error.
     ///
     /// This is a convenience method that downcasts the cause to a `DbError` and returns its code.
@@ -507,13 +533,35 @@ impl Error {
         Error::new(Kind::RowCount, None)
     }
 
-    #[cfg(feature = "runtime")]
+    pub(crate) fn pipeline_aborted() -> Error {
+        Error::new(Kind::PipelineAborted, None)
+    }
+
+    #[cfg(feature = "runtime-core")]
     pub(crate) fn connect(e: io::Error) -> Error {
         Error::new(Kind::Connect, Some(Box::new(e)))
     }
 
+    #[cfg(feature = "runtime-core")]
+    pub(crate) fn timeout() -> Error {
+        Error::new(Kind::Timeout, None)
+    }
+
+    #[cfg(feature = "runtime-core")]
+    pub(crate) fn pool_timeout() -> Error {
+        Error::new(
+            Kind::Timeout,
+            Some("no pooled connection became available".into()),
+        )
+    }
+
     #[doc(hidden)]
     pub fn __private_api_timeout() -> Error {
         Error::new(Kind::Timeout, None)
     }
+
+    #[doc(hidden)]
+    pub fn __private_api_config(e: Box<dyn error::Error + Sync + Send>) -> Error {
+        Error::config(e)
+    }
 }
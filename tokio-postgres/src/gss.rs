@@ -0,0 +1,239 @@
+//! GSSAPI support.
+//!
+//! The library implements the Postgres side of GSSAPI encryption and authentication, but leaves the security context
+//! itself to an implementation of [`MakeGssContext`] supplied with [`Config::gss`](crate::Config::gss). This is
+//! typically a thin wrapper over a binding to the system's Kerberos library, such as the `libgssapi` crate.
+//!
+//! Requests to cancel queries are sent without GSSAPI encryption.
+use bytes::{Buf, BufMut, BytesMut};
+use futures_util::ready;
+use std::error::Error;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::{cmp, fmt, io};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+// The largest packet, including its length prefix, which libpq and the server send or accept.
+pub(crate) const MAX_PACKET_SIZE: usize = 16 * 1024;
+
+/// A constructor of GSSAPI security contexts.
+pub trait MakeGssContext: Send + Sync {
+    /// Creates a client context for authenticating to the server at `host`.
+    ///
+    /// `service` is the Kerberos service name configured with `krbsrvname`, so that the target is the host-based
+    /// service `service@host`. If the context cannot be created, for example because no credentials are available,
+    /// connections with `gssencmode=prefer` continue without GSSAPI encryption.
+    fn make_gss_context(
+        &self,
+        service: &str,
+        host: &str,
+    ) -> Result<Box<dyn GssContext>, Box<dyn Error + Sync + Send>>;
+}
+
+/// A client-side GSSAPI security context.
+pub trait GssContext: Send + Sync {
+    /// Processes a token received from the server, and returns the token to send to it in response, if any.
+    ///
+    /// This corresponds to `gss_init_sec_context`. The first call is made with no token.
+    fn step(
+        &mut self,
+        token: Option<&[u8]>,
+    ) -> Result<Option<Vec<u8>>, Box<dyn Error + Sync + Send>>;
+
+    /// Returns whether the context has been fully established.
+    fn is_complete(&self) -> bool;
+
+    /// Returns the largest message which `wrap` encrypts into at most `output_size` bytes.
+    ///
+    /// This corresponds to `gss_wrap_size_limit` with confidentiality requested.
+    fn wrap_size_limit(
+        &mut self,
+        output_size: usize,
+    ) -> Result<usize, Box<dyn Error + Sync + Send>>;
+
+    /// Encrypts and integrity protects a message.
+    ///
+    /// This corresponds to `gss_wrap` with confidentiality requested.
+    fn wrap(&mut self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Sync + Send>>;
+
+    /// Decrypts and verifies a message wrapped by the server.
+    ///
+    /// This corresponds to `gss_unwrap`.
+    fn unwrap(&mut self, message: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Sync + Send>>;
+}
+
+/// A shared reference to a GSSAPI implementation, compared by identity so that `Config` can remain `Eq`.
+#[derive(Clone)]
+pub(crate) struct GssHandle(pub(crate) Arc<dyn MakeGssContext>);
+
+impl PartialEq for GssHandle {
+    fn eq(&self, other: &GssHandle) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for GssHandle {}
+
+impl fmt::Debug for GssHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Gss")
+    }
+}
+
+fn other(e: Box<dyn Error + Sync + Send>) -> io::Error {
+    io::Error::other(e)
+}
+
+/// A stream encrypted with an established GSSAPI context.
+///
+/// Each direction is a sequence of packets made up of a big-endian 4 byte length followed by that many bytes of a
+/// wrapped message.
+pub(crate) struct GssStream<S> {
+    stream: S,
+    context: Box<dyn GssContext>,
+    max_message_size: usize,
+    read_buf: BytesMut,
+    plain: BytesMut,
+    write_buf: BytesMut,
+}
+
+impl<S> GssStream<S> {
+    pub(crate) fn new(
+        stream: S,
+        mut context: Box<dyn GssContext>,
+        read_buf: BytesMut,
+    ) -> io::Result<GssStream<S>> {
+        let max_message_size = context
+            .wrap_size_limit(MAX_PACKET_SIZE - 4)
+            .map_err(other)?;
+        if max_message_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "GSSAPI context cannot wrap any data in a packet",
+            ));
+        }
+
+        Ok(GssStream {
+            stream,
+            context,
+            max_message_size,
+            read_buf,
+            plain: BytesMut::new(),
+            write_buf: BytesMut::new(),
+        })
+    }
+
+    // Decrypts the next complete packet in the read buffer, returning whether there was one.
+    fn decrypt_packet(&mut self) -> io::Result<bool> {
+        if self.read_buf.len() < 4 {
+            return Ok(false);
+        }
+
+        let len = (&self.read_buf[..4]).get_u32() as usize;
+        if len > MAX_PACKET_SIZE - 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("oversize GSSAPI packet sent by the server ({} bytes)", len),
+            ));
+        }
+        if self.read_buf.len() < len + 4 {
+            return Ok(false);
+        }
+
+        self.read_buf.advance(4);
+        let packet = self.read_buf.split_to(len);
+        let message = self.context.unwrap(&packet).map_err(other)?;
+        self.plain.extend_from_slice(&message);
+        Ok(true)
+    }
+}
+
+impl<S> GssStream<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn poll_write_buf(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while !self.write_buf.is_empty() {
+            let n = ready!(Pin::new(&mut self.stream).poll_write(cx, &self.write_buf))?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.write_buf.advance(n);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S> AsyncRead for GssStream<S>
+where
+    S: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+
+        while this.plain.is_empty() {
+            if this.decrypt_packet()? {
+                continue;
+            }
+
+            let mut chunk = [0; 8 * 1024];
+            let mut chunk = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.stream).poll_read(cx, &mut chunk))?;
+            if chunk.filled().is_empty() {
+                if this.read_buf.is_empty() {
+                    return Poll::Ready(Ok(()));
+                }
+                return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+            }
+            this.read_buf.extend_from_slice(chunk.filled());
+        }
+
+        let len = cmp::min(buf.remaining(), this.plain.len());
+        buf.put_slice(&this.plain.split_to(len));
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S> AsyncWrite for GssStream<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+
+        ready!(this.poll_write_buf(cx))?;
+
+        let len = cmp::min(buf.len(), this.max_message_size);
+        let packet = this.context.wrap(&buf[..len]).map_err(other)?;
+        if packet.len() > MAX_PACKET_SIZE - 4 {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "GSSAPI context wrapped a message beyond its size limit",
+            )));
+        }
+        this.write_buf.put_u32(packet.len() as u32);
+        this.write_buf.extend_from_slice(&packet);
+
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.poll_write_buf(cx))?;
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.poll_write_buf(cx))?;
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
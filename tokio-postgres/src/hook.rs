@@ -0,0 +1,131 @@
+use crate::time::Instant;
+use crate::Error;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A hook invoked by a [`Client`](crate::Client) around the execution of each statement.
+///
+/// Hooks are registered with [`Client::set_statement_hook`](crate::Client::set_statement_hook), and are called for
+/// statements executed through the `query`, `execute`, `simple_query`, and `batch_execute` families of methods,
+/// including those issued by transactions. Queries the client issues internally, such as those used to look up type
+/// information, are not reported.
+///
+/// Both methods are called synchronously from the task executing the statement, so they should not block.
+pub trait StatementHook: Send + Sync {
+    /// Called before a statement is executed.
+    ///
+    /// When a query string is passed to a method which prepares it first, this is called once the statement has been
+    /// prepared, or along with `after_statement` if preparing it fails.
+    fn before_statement(&self, _statement: &StatementInfo<'_>) {}
+
+    /// Called after a statement has completed, successfully or not.
+    ///
+    /// For methods returning a stream of rows, this is called once the stream has been exhausted or has returned an
+    /// error. It is not called if the stream is dropped before then.
+    fn after_statement(&self, _statement: &StatementInfo<'_>, _result: &StatementResult<'_>) {}
+}
+
+/// Information about a statement passed to a [`StatementHook`].
+#[derive(Debug)]
+pub struct StatementInfo<'a> {
+    query: &'a str,
+    param_count: usize,
+}
+
+impl StatementInfo<'_> {
+    /// Returns the SQL of the statement.
+    pub fn query(&self) -> &str {
+        self.query
+    }
+
+    /// Returns the number of parameters of the statement.
+    pub fn param_count(&self) -> usize {
+        self.param_count
+    }
+}
+
+/// The outcome of a statement passed to [`StatementHook::after_statement`].
+#[derive(Debug)]
+pub struct StatementResult<'a> {
+    duration: Duration,
+    rows: u64,
+    rows_affected: Option<u64>,
+    error: Option<&'a Error>,
+}
+
+impl StatementResult<'_> {
+    /// Returns the time taken by the statement, including the time spent preparing it.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the number of rows returned by the statement.
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// Returns the number of rows affected by the statement, as reported by the server.
+    ///
+    /// For batches of statements, this is the count reported for the last one.
+    pub fn rows_affected(&self) -> Option<u64> {
+        self.rows_affected
+    }
+
+    /// Returns the error the statement failed with, if any.
+    pub fn error(&self) -> Option<&Error> {
+        self.error
+    }
+}
+
+/// Tracks an in-progress statement, reporting its outcome to a hook.
+pub(crate) struct HookGuard {
+    hook: Arc<dyn StatementHook>,
+    query: String,
+    param_count: usize,
+    start: Instant,
+    rows: u64,
+}
+
+impl HookGuard {
+    pub fn start(
+        hook: &Arc<dyn StatementHook>,
+        query: &str,
+        param_count: usize,
+        start: Instant,
+    ) -> HookGuard {
+        hook.before_statement(&StatementInfo { query, param_count });
+        HookGuard {
+            hook: hook.clone(),
+            query: query.to_string(),
+            param_count,
+            start,
+            rows: 0,
+        }
+    }
+
+    pub fn add_row(&mut self) {
+        self.rows += 1;
+    }
+
+    pub fn finish(self, rows_affected: Option<u64>) {
+        self.report(rows_affected, None);
+    }
+
+    pub fn fail(self, error: &Error) {
+        self.report(None, Some(error));
+    }
+
+    fn report(self, rows_affected: Option<u64>, error: Option<&Error>) {
+        let statement = StatementInfo {
+            query: &self.query,
+            param_count: self.param_count,
+        };
+        let result = StatementResult {
+            duration: self.start.elapsed(),
+            rows: self.rows,
+            rows_affected,
+            error,
+        };
+        self.hook.after_statement(&statement, &result);
+    }
+}
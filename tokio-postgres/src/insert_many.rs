@@ -0,0 +1,182 @@
+use crate::types::{to_sql_checked, IsNull, Kind, ToSql, Type};
+use crate::{Client, Error};
+use bytes::BytesMut;
+use postgres_protocol::escape::escape_identifier;
+use postgres_protocol::types::{self, ArrayDimension};
+use std::convert::TryFrom;
+use std::error;
+use std::fmt::Write;
+
+// The number of parameters a single statement can bind.
+const MAX_PARAMETERS: usize = u16::MAX as usize;
+
+// Above this many rows, the per-parameter overhead of a VALUES list outweighs the cost of looking up column types.
+const UNNEST_THRESHOLD: usize = 100;
+
+pub async fn insert_many(
+    client: &Client,
+    table: &str,
+    columns: &[&str],
+    rows: &[&[&(dyn ToSql + Sync)]],
+) -> Result<u64, Error> {
+    if columns.is_empty() {
+        return Err(Error::config("at least one column is required".into()));
+    }
+    if let Some(row) = rows.iter().find(|row| row.len() != columns.len()) {
+        return Err(Error::parameters(row.len(), columns.len()));
+    }
+
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let mut target = "INSERT INTO ".to_string();
+    for (i, part) in table.split('.').enumerate() {
+        if i > 0 {
+            target.push('.');
+        }
+        target.push_str(&escape_identifier(part));
+    }
+    target.push_str(" (");
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            target.push_str(", ");
+        }
+        target.push_str(&escape_identifier(column));
+    }
+    target.push(')');
+
+    if rows.len() > UNNEST_THRESHOLD {
+        let types = column_types(client, &target, columns.len()).await?;
+        // UNNEST flattens multidimensional arrays, so array columns can't be passed through it.
+        if !types.iter().any(|t| matches!(t.kind(), Kind::Array(_))) {
+            return insert_unnest(client, &target, &types, rows).await;
+        }
+    }
+
+    insert_values(client, &target, columns.len(), rows).await
+}
+
+async fn column_types(client: &Client, target: &str, columns: usize) -> Result<Vec<Type>, Error> {
+    let mut query = format!("{} VALUES (", target);
+    push_placeholders(&mut query, 0, columns);
+    query.push(')');
+
+    let statement = client.prepare(&query).await?;
+    Ok(statement.params().to_vec())
+}
+
+async fn insert_unnest(
+    client: &Client,
+    target: &str,
+    types: &[Type],
+    rows: &[&[&(dyn ToSql + Sync)]],
+) -> Result<u64, Error> {
+    let mut query = format!("{} SELECT * FROM UNNEST(", target);
+    for (i, type_) in types.iter().enumerate() {
+        if i > 0 {
+            query.push_str(", ");
+        }
+        write!(
+            query,
+            "${}::{}.{}[]",
+            i + 1,
+            escape_identifier(type_.schema()),
+            escape_identifier(type_.name())
+        )
+        .unwrap();
+    }
+    query.push(')');
+
+    let arrays = (0..types.len())
+        .map(|i| ColumnArray {
+            values: rows.iter().map(|row| row[i]).collect(),
+        })
+        .collect::<Vec<_>>();
+    let params = arrays
+        .iter()
+        .map(|a| a as &(dyn ToSql + Sync))
+        .collect::<Vec<_>>();
+
+    client.execute(&query, &params).await
+}
+
+async fn insert_values(
+    client: &Client,
+    target: &str,
+    columns: usize,
+    rows: &[&[&(dyn ToSql + Sync)]],
+) -> Result<u64, Error> {
+    let mut inserted = 0;
+
+    for chunk in rows.chunks(MAX_PARAMETERS / columns) {
+        let mut query = format!("{} VALUES ", target);
+        for i in 0..chunk.len() {
+            if i > 0 {
+                query.push_str(", ");
+            }
+            query.push('(');
+            push_placeholders(&mut query, i * columns, columns);
+            query.push(')');
+        }
+
+        let params = chunk
+            .iter()
+            .flat_map(|row| row.iter().copied())
+            .collect::<Vec<_>>();
+        inserted += client.execute(&query, &params).await?;
+    }
+
+    Ok(inserted)
+}
+
+fn push_placeholders(query: &mut String, start: usize, count: usize) {
+    for i in 0..count {
+        if i > 0 {
+            query.push_str(", ");
+        }
+        write!(query, "${}", start + i + 1).unwrap();
+    }
+}
+
+// A one-dimensional array built from the values of a single column.
+#[derive(Debug)]
+struct ColumnArray<'a> {
+    values: Vec<&'a (dyn ToSql + Sync)>,
+}
+
+impl ToSql for ColumnArray<'_> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn error::Error + Sync + Send>> {
+        let member = match ty.kind() {
+            Kind::Array(member) => member,
+            _ => panic!("expected array type"),
+        };
+
+        let dimension = ArrayDimension {
+            len: i32::try_from(self.values.len()).map_err(|_| "array too large")?,
+            lower_bound: 1,
+        };
+
+        types::array_to_sql(
+            Some(dimension),
+            member.oid(),
+            self.values.iter(),
+            |v, buf| match v.to_sql_checked(member, buf)? {
+                IsNull::No => Ok(postgres_protocol::IsNull::No),
+                IsNull::Yes => Ok(postgres_protocol::IsNull::Yes),
+            },
+            out,
+        )?;
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(ty.kind(), Kind::Array(_))
+    }
+
+    to_sql_checked!();
+}
@@ -1,7 +1,12 @@
 use socket2::TcpKeepalive;
 use std::time::Duration;
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "with-serde-1",
+    derive(serde_1::Serialize, serde_1::Deserialize),
+    serde(crate = "serde_1")
+)]
 pub(crate) struct KeepaliveConfig {
     pub idle: Duration,
     pub interval: Option<Duration>,
@@ -0,0 +1,92 @@
+//! Connection latency estimates used by `LoadBalanceHosts::LeastLatency`.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// The latency recorded for a failed connection attempt, so that unreachable hosts sort behind reachable ones.
+const FAILURE_PENALTY: Duration = Duration::from_secs(10);
+
+// How long an estimate is used for after it was last updated. Once it expires the host is treated as unknown, and so
+// is tried first, which lets hosts that have recovered from a failure be measured again.
+const MAX_AGE: Duration = Duration::from_secs(60);
+
+// The most hosts which estimates are kept for.
+const MAX_ENTRIES: usize = 256;
+
+struct Estimate {
+    latency: Duration,
+    updated: Instant,
+}
+
+/// Latency estimates for the hosts connected to with a configuration, shared by its clones.
+///
+/// The estimates are state rather than configuration, so they are ignored when comparing configurations.
+#[derive(Clone, Default)]
+pub(crate) struct LatencyEstimates(Arc<Mutex<HashMap<(String, u16), Estimate>>>);
+
+impl PartialEq for LatencyEstimates {
+    fn eq(&self, _: &LatencyEstimates) -> bool {
+        true
+    }
+}
+
+impl Eq for LatencyEstimates {}
+
+impl fmt::Debug for LatencyEstimates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LatencyEstimates")
+    }
+}
+
+#[cfg_attr(not(feature = "runtime-core"), allow(dead_code))]
+impl LatencyEstimates {
+    /// Returns the current latency estimate for a host, if a connection to it has been attempted recently.
+    pub(crate) fn estimate(&self, host: &str, port: u16) -> Option<Duration> {
+        self.0
+            .lock()
+            .get(&(host.to_string(), port))
+            .filter(|estimate| estimate.updated.elapsed() < MAX_AGE)
+            .map(|estimate| estimate.latency)
+    }
+
+    /// Folds the time taken by a successful connection into the host's estimate.
+    pub(crate) fn record(&self, host: &str, port: u16, latency: Duration) {
+        let now = Instant::now();
+        let mut estimates = self.0.lock();
+
+        let key = (host.to_string(), port);
+        if !estimates.contains_key(&key) && estimates.len() >= MAX_ENTRIES {
+            estimates.retain(|_, estimate| now.duration_since(estimate.updated) < MAX_AGE);
+            if estimates.len() >= MAX_ENTRIES {
+                let oldest = estimates
+                    .iter()
+                    .min_by_key(|(_, estimate)| estimate.updated)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    estimates.remove(&oldest);
+                }
+            }
+        }
+
+        let estimate = estimates.entry(key).or_insert(Estimate {
+            latency,
+            updated: now,
+        });
+        if now.duration_since(estimate.updated) >= MAX_AGE {
+            // an expired estimate says nothing about the host's current latency
+            estimate.latency = latency;
+        } else {
+            // an exponentially weighted moving average, so that a single slow connection doesn't outweigh the history
+            estimate.latency = (estimate.latency * 3 + latency) / 4;
+        }
+        estimate.updated = now;
+    }
+
+    /// Records a failed connection attempt to a host.
+    pub(crate) fn record_failure(&self, host: &str, port: u16) {
+        self.record(host, port, FAILURE_PENALTY);
+    }
+}
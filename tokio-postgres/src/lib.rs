@@ -86,9 +86,27 @@
 //!
 //! # Runtime
 //!
-//! The client works with arbitrary `AsyncRead + AsyncWrite` streams. Convenience APIs are provided to handle the
-//! connection process, but these are gated by the `runtime` Cargo feature, which is enabled by default. If disabled,
-//! all dependence on the tokio runtime is removed.
+//! The client works with arbitrary `AsyncRead + AsyncWrite` streams, which can be connected with the
+//! [`connect_stream`] function. Convenience APIs are provided to handle the connection process, but these are gated
+//! by the `runtime` Cargo feature, which is enabled by default. If disabled, all dependence on the tokio runtime is
+//! removed.
+//!
+//! Without the `runtime` feature the client can be driven by any executor, such as those of `async-std` or `smol`.
+//! Only tokio's `AsyncRead` and `AsyncWrite` traits are used, and sockets from other runtimes can be adapted to them
+//! with the `compat` module of the `tokio-util` crate. The [`Connection`] returned by [`connect_stream`] is an ordinary
+//! future which can be spawned on the executor of choice.
+//!
+//! The APIs which open sockets, wait on timers, or spawn tasks themselves, such as [`Config::connect`], connection
+//! pooling, reconnection, and query timeouts, use tokio by default. They can be run on another executor by
+//! implementing the [`rt::Runtime`] trait and registering it with [`Config::runtime`]. These APIs only require the
+//! `runtime-core` Cargo feature, so tokio's reactor and timers need not be built at all.
+//!
+//! # WebAssembly
+//!
+//! With the `runtime` feature disabled, the client can be built for `wasm32` targets and connected over any stream the
+//! host provides, such as a WebSocket tunnel, with [`connect_stream`]. On `wasm32-unknown-unknown` the `js` feature
+//! must be enabled so that random numbers can be obtained from the JavaScript environment. That target has no clock,
+//! so query durations reported to hooks and the latency statistics of [`Client::stats`] are always zero there.
 //!
 //! # SSL/TLS support
 //!
@@ -103,15 +121,23 @@
 //!
 //! | Feature | Description | Extra dependencies | Default |
 //! | ------- | ----------- | ------------------ | ------- |
-//! | `runtime` | Enable convenience API for the connection process based on the `tokio` crate. | [tokio](https://crates.io/crates/tokio) 1.0 with the features `net` and `time` | yes |
+//! | `runtime` | Enable convenience API for the connection process and connection pooling based on the `tokio` crate. | [tokio](https://crates.io/crates/tokio) 1.0 with the features `net`, `rt`, `sync` and `time` | yes |
+//! | `runtime-core` | Enable convenience API for the connection process and connection pooling, run on a runtime registered with `Config::runtime`. Enabled by `runtime`. | [tokio](https://crates.io/crates/tokio) 1.0 with the feature `sync` | yes |
+//! | `mock` | Enable a scriptable mock backend for tests. | - | no |
+//! | `js` | Enable support for the `wasm32-unknown-unknown` target in JavaScript environments. | [getrandom](https://crates.io/crates/getrandom) 0.3 with the `wasm_js` feature | no |
 //! | `array-impls` | Enables `ToSql` and `FromSql` trait impls for arrays | - | no |
+//! | `with-bigdecimal-0_4` | Enable support for the 0.4 version of the `bigdecimal` crate. | [bigdecimal](https://crates.io/crates/bigdecimal/0.4.0) 0.4 | no |
 //! | `with-bit-vec-0_6` | Enable support for the `bit-vec` crate. | [bit-vec](https://crates.io/crates/bit-vec) 0.6 | no |
 //! | `with-chrono-0_4` | Enable support for the `chrono` crate. | [chrono](https://crates.io/crates/chrono) 0.4 | no |
 //! | `with-eui48-0_4` | Enable support for the 0.4 version of the `eui48` crate. This is deprecated and will be removed. | [eui48](https://crates.io/crates/eui48) 0.4 | no |
 //! | `with-eui48-1` | Enable support for the 1.0 version of the `eui48` crate. | [eui48](https://crates.io/crates/eui48) 1.0 | no |
 //! | `with-geo-types-0_6` | Enable support for the 0.6 version of the `geo-types` crate. | [geo-types](https://crates.io/crates/geo-types/0.6.0) 0.6 | no |
 //! | `with-geo-types-0_7` | Enable support for the 0.7 version of the `geo-types` crate. | [geo-types](https://crates.io/crates/geo-types/0.7.0) 0.7 | no |
+//! | `with-ipnetwork-0_21` | Enable support for the 0.21 version of the `ipnetwork` crate. | [ipnetwork](https://crates.io/crates/ipnetwork/0.21.0) 0.21 | no |
 //! | `with-jiff-0_1` | Enable support for the 0.1 version of the `jiff` crate. | [jiff](https://crates.io/crates/jiff/0.1.0) 0.1 | no |
+//! | `with-macaddr-1` | Enable support for the `macaddr` crate. | [macaddr](https://crates.io/crates/macaddr) 1.0 | no |
+//! | `with-rust_decimal-1` | Enable support for the `rust_decimal` crate. | [rust_decimal](https://crates.io/crates/rust_decimal) 1.0 | no |
+//! | `with-serde-1` | Enable CSV copy helpers and serialization of `Config` and `CancelToken` based on the `serde` crate. | [serde](https://crates.io/crates/serde) 1.0 | no |
 //! | `with-serde_json-1` | Enable support for the `serde_json` crate. | [serde_json](https://crates.io/crates/serde_json) 1.0 | no |
 //! | `with-uuid-0_8` | Enable support for the `uuid` crate. | [uuid](https://crates.io/crates/uuid) 0.8 | no |
 //! | `with-uuid-1` | Enable support for the `uuid` crate. | [uuid](https://crates.io/crates/uuid) 1.0 | no |
@@ -123,58 +149,110 @@ pub use crate::cancel_token::CancelToken;
 pub use crate::client::Client;
 pub use crate::config::Config;
 pub use crate::connection::Connection;
+pub use crate::copy_both::CopyBothDuplex;
 pub use crate::copy_in::CopyInSink;
 pub use crate::copy_out::CopyOutStream;
+pub use crate::copy_progress::CopyProgress;
+pub use crate::cursor::CursorStream;
 use crate::error::DbError;
 pub use crate::error::Error;
 pub use crate::generic_client::GenericClient;
+pub use crate::hook::{StatementHook, StatementInfo, StatementResult};
+#[cfg(feature = "with-serde_json-1")]
+pub use crate::listen::JsonNotificationStream;
+pub use crate::listen::{NoticeStream, NotificationStream, ParameterStream};
+pub use crate::pipeline::{Pipeline, PipelineResult};
 pub use crate::portal::Portal;
-pub use crate::query::RowStream;
+pub use crate::query::{RowStream, RowStreamAs, TextRowStream};
+pub use crate::query_builder::QueryBuilder;
+pub use crate::query_options::QueryOptions;
+pub use crate::raw_connection::RawConnection;
+pub use crate::resolver::Resolver;
 pub use crate::row::{Row, SimpleQueryRow};
-pub use crate::simple_query::{SimpleColumn, SimpleQueryStream};
-#[cfg(feature = "runtime")]
+pub use crate::simple_query::{BatchResult, SimpleColumn, SimpleQueryStream};
+#[cfg(feature = "runtime-core")]
 pub use crate::socket::Socket;
+#[cfg(feature = "runtime-core")]
+pub use crate::socket_config::{Addr, SocketConfig, SocketConfigBuilder};
 pub use crate::statement::{Column, Statement};
-#[cfg(feature = "runtime")]
+pub use crate::stats::ClientStats;
+#[cfg(feature = "runtime-core")]
 use crate::tls::MakeTlsConnect;
 pub use crate::tls::NoTls;
+use crate::tls::TlsConnect;
 pub use crate::to_statement::ToStatement;
 pub use crate::transaction::Transaction;
 pub use crate::transaction_builder::{IsolationLevel, TransactionBuilder};
 use crate::types::ToSql;
 use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncWrite};
 
 pub mod binary_copy;
 mod bind;
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 mod cancel_query;
 mod cancel_query_raw;
 mod cancel_token;
 mod client;
 mod codec;
 pub mod config;
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 mod connect;
+mod connect_gss;
 mod connect_raw;
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 mod connect_socket;
 mod connect_tls;
 mod connection;
+mod copy_both;
 mod copy_in;
 mod copy_out;
+mod copy_progress;
+#[cfg(feature = "with-serde-1")]
+pub mod csv_copy;
+mod cursor;
 pub mod error;
 mod generic_client;
+pub mod gss;
+mod hook;
+mod insert_many;
 #[cfg(not(target_arch = "wasm32"))]
 mod keepalive;
+mod latency;
+mod listen;
 mod maybe_tls_stream;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod pipeline;
+#[cfg(feature = "runtime-core")]
+pub mod pool;
 mod portal;
 mod prepare;
+#[cfg(feature = "runtime-core")]
+mod proxy;
 mod query;
+mod query_builder;
+mod query_options;
+mod raw_connection;
+#[cfg(feature = "runtime-core")]
+pub mod reconnect;
+pub mod replication;
+mod resolver;
 pub mod row;
+#[cfg(feature = "with-serde_json-1")]
+mod row_json;
+#[cfg(feature = "runtime-core")]
+pub mod rt;
+mod secret;
+mod service_file;
 mod simple_query;
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 mod socket;
+#[cfg(feature = "runtime-core")]
+mod socket_config;
 mod statement;
+mod stats;
+mod time;
 pub mod tls;
 mod to_statement;
 mod transaction;
@@ -185,10 +263,10 @@ pub mod types;
 ///
 /// See the documentation for [`Config`] for details on the connection string format.
 ///
-/// Requires the `runtime` Cargo feature (enabled by default).
+/// Requires the `runtime-core` Cargo feature (enabled by default).
 ///
 /// [`Config`]: config/struct.Config.html
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 pub async fn connect<T>(
     config: &str,
     tls: T,
@@ -200,6 +278,44 @@ where
     config.connect(tls).await
 }
 
+/// Connects to the database over a user-supplied stream.
+///
+/// Any `AsyncRead + AsyncWrite` stream can be used as a transport, such as an in-memory duplex pipe, a tunnel over a
+/// WebSocket, or a socket opened by a custom connector. The startup process, including TLS negotiation and
+/// authentication, is run over the stream as configured by `config`; see [`Config::connect_raw`] for the settings
+/// which do not apply. Unlike [`connect`], this does not require the `runtime-core` Cargo feature.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn async_main() -> Result<(), tokio_postgres::Error> {
+/// use tokio::net::UnixStream;
+/// use tokio_postgres::{Config, NoTls};
+///
+/// let stream = UnixStream::connect("/run/postgresql/.s.PGSQL.5432").await.unwrap();
+/// let config = "user=postgres".parse::<Config>()?;
+/// let (client, connection) = tokio_postgres::connect_stream(stream, NoTls, &config).await?;
+///
+/// tokio::spawn(async move {
+///     if let Err(e) = connection.await {
+///         eprintln!("connection error: {}", e);
+///     }
+/// });
+/// # Ok(())
+/// # }
+/// ```
+pub async fn connect_stream<S, T>(
+    stream: S,
+    tls: T,
+    config: &Config,
+) -> Result<(Client, Connection<S, T::Stream>), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    T: TlsConnect<S>,
+{
+    config.connect_raw(stream, tls).await
+}
+
 /// An asynchronous notification.
 #[derive(Clone, Debug)]
 pub struct Notification {
@@ -223,6 +339,36 @@ impl Notification {
     pub fn payload(&self) -> &str {
         &self.payload
     }
+
+    /// Deserializes the payload as JSON.
+    ///
+    /// Requires the `with-serde_json-1` Cargo feature.
+    #[cfg(feature = "with-serde_json-1")]
+    pub fn payload_as<T>(&self) -> Result<T, serde_json_1::Error>
+    where
+        T: serde_1::de::DeserializeOwned,
+    {
+        serde_json_1::from_str(&self.payload)
+    }
+}
+
+/// A change to the value of a runtime parameter reported by the server.
+#[derive(Clone, Debug)]
+pub struct ParameterStatus {
+    name: String,
+    value: String,
+}
+
+impl ParameterStatus {
+    /// The name of the parameter.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The new value of the parameter.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
 }
 
 /// An asynchronous message from the server.
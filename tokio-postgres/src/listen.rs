@@ -0,0 +1,334 @@
+use crate::client::InnerClient;
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::error::DbError;
+use crate::{Notification, ParameterStatus};
+use futures_channel::mpsc;
+use futures_util::{Stream, StreamExt};
+use log::{debug, warn};
+use parking_lot::Mutex;
+use postgres_protocol::escape::escape_identifier;
+use postgres_protocol::message::frontend;
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "with-serde_json-1")]
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::{Arc, Weak};
+use std::task::{Context, Poll};
+
+// The number of items buffered for each subscription before further items are dropped.
+const BUFFER_SIZE: usize = 1024;
+
+// Sends an item to a subscriber, returning `false` if the subscriber has gone away. The connection cannot wait for a
+// subscriber which has stopped reading, so an item which does not fit in its buffer is dropped.
+fn send<T>(sender: &mut mpsc::Sender<T>, item: T, kind: &str) -> bool {
+    match sender.try_send(item) {
+        Ok(()) => true,
+        Err(e) if e.is_full() => {
+            warn!("{} subscriber is not keeping up, dropping a {}", kind, kind);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[derive(Default)]
+struct State {
+    channels: HashMap<String, Vec<mpsc::Sender<Notification>>>,
+    notices: Vec<mpsc::Sender<DbError>>,
+    parameters: HashMap<String, String>,
+    parameter_changes: Vec<mpsc::Sender<ParameterStatus>>,
+    closed: bool,
+}
+
+/// The notification, notice, and runtime parameter subscriptions of a connection, shared between the client and the
+/// connection.
+#[derive(Default)]
+pub struct Listeners {
+    state: Mutex<State>,
+}
+
+impl Listeners {
+    pub fn dispatch(&self, notification: &Notification) {
+        let mut state = self.state.lock();
+        if let Some(senders) = state.channels.get_mut(notification.channel()) {
+            senders.retain_mut(|s| send(s, notification.clone(), "notification"));
+        }
+    }
+
+    pub fn dispatch_notice(&self, notice: &DbError) {
+        let mut state = self.state.lock();
+        state
+            .notices
+            .retain_mut(|s| send(s, notice.clone(), "notice"));
+    }
+
+    /// Records the current value of a runtime parameter, notifying subscribers if it has changed.
+    pub fn set_parameter(&self, name: &str, value: &str) {
+        let mut state = self.state.lock();
+        if state.parameters.get(name).map(|v| &**v) == Some(value) {
+            return;
+        }
+        state.parameters.insert(name.to_string(), value.to_string());
+
+        let status = ParameterStatus {
+            name: name.to_string(),
+            value: value.to_string(),
+        };
+        state
+            .parameter_changes
+            .retain_mut(|s| send(s, status.clone(), "parameter change"));
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<String> {
+        self.state.lock().parameters.get(name).cloned()
+    }
+
+    pub fn close(&self) {
+        let mut state = self.state.lock();
+        state.closed = true;
+        state.channels.clear();
+        state.notices.clear();
+        state.parameter_changes.clear();
+    }
+
+    fn subscribe_notices(&self) -> mpsc::Receiver<DbError> {
+        let (sender, receiver) = mpsc::channel(BUFFER_SIZE);
+        let mut state = self.state.lock();
+        if !state.closed {
+            state.notices.push(sender);
+        }
+        receiver
+    }
+
+    fn subscribe(&self, channel: &str) -> mpsc::Receiver<Notification> {
+        let (sender, receiver) = mpsc::channel(BUFFER_SIZE);
+        let mut state = self.state.lock();
+        if !state.closed {
+            state
+                .channels
+                .entry(channel.to_string())
+                .or_default()
+                .push(sender);
+        }
+        receiver
+    }
+
+    fn subscribe_parameter_changes(&self) -> mpsc::Receiver<ParameterStatus> {
+        let (sender, receiver) = mpsc::channel(BUFFER_SIZE);
+        let mut state = self.state.lock();
+        if !state.closed {
+            state.parameter_changes.push(sender);
+        }
+        receiver
+    }
+
+    /// Removes closed subscriptions to the channel, returning `true` if none remain.
+    fn unsubscribe(&self, channel: &str) -> bool {
+        let mut state = self.state.lock();
+        let senders = match state.channels.get_mut(channel) {
+            Some(senders) => senders,
+            None => return false,
+        };
+        senders.retain(|s| !s.is_closed());
+        if senders.is_empty() {
+            state.channels.remove(channel);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// A stream of notifications sent to a channel.
+///
+/// The client stops listening to the channel once every stream subscribed to it has been dropped.
+///
+/// The stream ends when the connection is closed.
+///
+/// At least 1024 notifications are buffered for a stream which is not being read. Any more are dropped, and a warning
+/// is logged, so that the connection does not buffer them without bound.
+pub struct NotificationStream {
+    receiver: mpsc::Receiver<Notification>,
+    client: Weak<InnerClient>,
+    channel: String,
+}
+
+impl NotificationStream {
+    pub(crate) fn new(client: &Arc<InnerClient>, channel: &str) -> NotificationStream {
+        NotificationStream {
+            receiver: client.listeners().subscribe(channel),
+            client: Arc::downgrade(client),
+            channel: channel.to_string(),
+        }
+    }
+
+    /// Returns the name of the channel.
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+
+    /// Converts the stream into one which deserializes the payload of each notification as JSON.
+    ///
+    /// Requires the `with-serde_json-1` Cargo feature.
+    #[cfg(feature = "with-serde_json-1")]
+    pub fn json<T>(self) -> JsonNotificationStream<T>
+    where
+        T: serde_1::de::DeserializeOwned,
+    {
+        JsonNotificationStream {
+            stream: self,
+            _p: PhantomData,
+        }
+    }
+}
+
+impl fmt::Debug for NotificationStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NotificationStream")
+            .field("channel", &self.channel)
+            .finish()
+    }
+}
+
+impl Stream for NotificationStream {
+    type Item = Notification;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Notification>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for NotificationStream {
+    fn drop(&mut self) {
+        self.receiver.close();
+
+        let client = match self.client.upgrade() {
+            Some(client) => client,
+            None => return,
+        };
+        if !client.listeners().unsubscribe(&self.channel) {
+            return;
+        }
+
+        debug!("unlistening to channel {}", self.channel);
+        let query = format!("UNLISTEN {}", escape_identifier(&self.channel));
+        let buf =
+            client.with_buf(|buf| frontend::query(&query, buf).map(|()| buf.split().freeze()));
+        if let Ok(buf) = buf {
+            let _ = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)));
+        }
+    }
+}
+
+/// A stream of notifications sent to a channel, with payloads deserialized from JSON.
+///
+/// A notification whose payload fails to deserialize is yielded as an error, and does not end the stream.
+///
+/// Requires the `with-serde_json-1` Cargo feature.
+#[cfg(feature = "with-serde_json-1")]
+pub struct JsonNotificationStream<T> {
+    stream: NotificationStream,
+    _p: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "with-serde_json-1")]
+impl<T> JsonNotificationStream<T> {
+    /// Returns the name of the channel.
+    pub fn channel(&self) -> &str {
+        self.stream.channel()
+    }
+
+    /// Returns the underlying stream of raw notifications.
+    pub fn into_inner(self) -> NotificationStream {
+        self.stream
+    }
+}
+
+#[cfg(feature = "with-serde_json-1")]
+impl<T> fmt::Debug for JsonNotificationStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonNotificationStream")
+            .field("channel", &self.stream.channel)
+            .finish()
+    }
+}
+
+#[cfg(feature = "with-serde_json-1")]
+impl<T> Stream for JsonNotificationStream<T>
+where
+    T: serde_1::de::DeserializeOwned,
+{
+    type Item = Result<T, serde_json_1::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream
+            .poll_next_unpin(cx)
+            .map(|n| n.map(|n| n.payload_as()))
+    }
+}
+
+/// A stream of the notices sent by the server.
+///
+/// The stream ends when the connection is closed.
+///
+/// At least 1024 notices are buffered for a stream which is not being read. Any more are dropped, and a warning is
+/// logged, so that the connection does not buffer them without bound.
+pub struct NoticeStream {
+    receiver: mpsc::Receiver<DbError>,
+}
+
+impl NoticeStream {
+    pub(crate) fn new(client: &InnerClient) -> NoticeStream {
+        NoticeStream {
+            receiver: client.listeners().subscribe_notices(),
+        }
+    }
+}
+
+impl fmt::Debug for NoticeStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NoticeStream").finish()
+    }
+}
+
+impl Stream for NoticeStream {
+    type Item = DbError;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<DbError>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+/// A stream of changes to the server's runtime parameters.
+///
+/// The stream ends when the connection is closed.
+///
+/// At least 1024 parameter changes are buffered for a stream which is not being read. Any more are dropped, and a
+/// warning is logged, so that the connection does not buffer them without bound.
+pub struct ParameterStream {
+    receiver: mpsc::Receiver<ParameterStatus>,
+}
+
+impl ParameterStream {
+    pub(crate) fn new(client: &InnerClient) -> ParameterStream {
+        ParameterStream {
+            receiver: client.listeners().subscribe_parameter_changes(),
+        }
+    }
+}
+
+impl fmt::Debug for ParameterStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParameterStream").finish()
+    }
+}
+
+impl Stream for ParameterStream {
+    type Item = ParameterStatus;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<ParameterStatus>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
@@ -1,3 +1,4 @@
+use crate::gss::GssStream;
 use crate::tls::{ChannelBinding, TlsStream};
 use std::io;
 use std::pin::Pin;
@@ -7,6 +8,7 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 pub enum MaybeTlsStream<S, T> {
     Raw(S),
     Tls(T),
+    Gss(GssStream<S>),
 }
 
 impl<S, T> AsyncRead for MaybeTlsStream<S, T>
@@ -22,6 +24,7 @@ where
         match &mut *self {
             MaybeTlsStream::Raw(s) => Pin::new(s).poll_read(cx, buf),
             MaybeTlsStream::Tls(s) => Pin::new(s).poll_read(cx, buf),
+            MaybeTlsStream::Gss(s) => Pin::new(s).poll_read(cx, buf),
         }
     }
 }
@@ -39,6 +42,7 @@ where
         match &mut *self {
             MaybeTlsStream::Raw(s) => Pin::new(s).poll_write(cx, buf),
             MaybeTlsStream::Tls(s) => Pin::new(s).poll_write(cx, buf),
+            MaybeTlsStream::Gss(s) => Pin::new(s).poll_write(cx, buf),
         }
     }
 
@@ -46,6 +50,7 @@ where
         match &mut *self {
             MaybeTlsStream::Raw(s) => Pin::new(s).poll_flush(cx),
             MaybeTlsStream::Tls(s) => Pin::new(s).poll_flush(cx),
+            MaybeTlsStream::Gss(s) => Pin::new(s).poll_flush(cx),
         }
     }
 
@@ -53,6 +58,7 @@ where
         match &mut *self {
             MaybeTlsStream::Raw(s) => Pin::new(s).poll_shutdown(cx),
             MaybeTlsStream::Tls(s) => Pin::new(s).poll_shutdown(cx),
+            MaybeTlsStream::Gss(s) => Pin::new(s).poll_shutdown(cx),
         }
     }
 }
@@ -64,7 +70,7 @@ where
 {
     fn channel_binding(&self) -> ChannelBinding {
         match self {
-            MaybeTlsStream::Raw(_) => ChannelBinding::none(),
+            MaybeTlsStream::Raw(_) | MaybeTlsStream::Gss(_) => ChannelBinding::none(),
             MaybeTlsStream::Tls(s) => s.channel_binding(),
         }
     }
@@ -0,0 +1,542 @@
+//! A scriptable mock backend for tests.
+//!
+//! Requires the `mock` Cargo feature.
+//!
+//! A [`Backend`] speaks the server side of the PostgreSQL protocol over any stream. It accepts the startup message,
+//! performs the configured authentication method, and then answers each statement it receives with the next entry of
+//! a script of expected queries and canned responses. This allows tests of code built on top of the client, or of
+//! proxies sitting between a client and a server, to run deterministically without a database.
+//!
+//! Both the simple and extended query protocols are supported, so statements can be run with any of the client's
+//! query methods. Rows are given in their text format; when the client requests the binary format, values of the
+//! built-in boolean, integer, floating point, `OID`, and string types are converted, and other types are rejected with
+//! an error.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use tokio_postgres::mock::{Auth, Backend, Response};
+//! use tokio_postgres::types::Type;
+//! use tokio_postgres::{Config, NoTls};
+//!
+//! # async fn async_main() -> Result<(), tokio_postgres::Error> {
+//! let mut backend = Backend::new();
+//! backend
+//!     .auth(Auth::ScramSha256("password".to_string()))
+//!     .query(
+//!         "SELECT name FROM users WHERE id = $1",
+//!         Response::rows(&[("name", Type::TEXT)])
+//!             .params(&[Type::INT4])
+//!             .row(&[Some("ferris")]),
+//!     );
+//!
+//! let (client_stream, server_stream) = tokio::io::duplex(4096);
+//! let server = tokio::spawn(async move { backend.serve(server_stream).await });
+//!
+//! let (client, connection) = Config::new()
+//!     .user("postgres")
+//!     .password("password")
+//!     .connect_raw(client_stream, NoTls)
+//!     .await?;
+//! tokio::spawn(connection);
+//!
+//! let row = client
+//!     .query_one("SELECT name FROM users WHERE id = $1", &[&1i32])
+//!     .await?;
+//! assert_eq!(row.get::<_, &str>(0), "ferris");
+//!
+//! drop(client);
+//! server.await.unwrap().unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::SqlState;
+use crate::types::Type;
+use async_trait::async_trait;
+use bytes::BytesMut;
+use postgres_protocol::types;
+use postgres_server::{Bind, ErrorResponse, Field, Handler, Oid, Responder, Startup};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error;
+use std::io;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+#[doc(inline)]
+pub use postgres_server::Auth;
+
+const PROCESS_ID: i32 = 1;
+const SECRET_KEY: i32 = 0x6d6f636b;
+
+const DEFAULT_PARAMETERS: &[(&str, &str)] = &[
+    ("server_version", "16.0"),
+    ("server_encoding", "UTF8"),
+    ("client_encoding", "UTF8"),
+    ("DateStyle", "ISO, MDY"),
+    ("TimeZone", "UTC"),
+    ("integer_datetimes", "on"),
+    ("standard_conforming_strings", "on"),
+];
+
+/// The canned response to a statement in the script of a [`Backend`].
+#[derive(Debug, Clone)]
+pub struct Response {
+    params: Vec<Type>,
+    columns: Option<Vec<(String, Type)>>,
+    rows: Vec<Vec<Option<String>>>,
+    tag: Option<String>,
+    error: Option<(SqlState, String)>,
+}
+
+impl Response {
+    fn new() -> Response {
+        Response {
+            params: vec![],
+            columns: None,
+            rows: vec![],
+            tag: None,
+            error: None,
+        }
+    }
+
+    /// Creates a response for a statement which returns no rows, completing with the provided command tag, such as
+    /// `INSERT 0 1` or `BEGIN`.
+    pub fn command(tag: &str) -> Response {
+        Response {
+            tag: Some(tag.to_string()),
+            ..Response::new()
+        }
+    }
+
+    /// Creates a response for a statement which returns rows with the provided column names and types.
+    ///
+    /// Rows are added with the `row` method, and the statement completes with a `SELECT` command tag.
+    pub fn rows(columns: &[(&str, Type)]) -> Response {
+        Response {
+            columns: Some(
+                columns
+                    .iter()
+                    .map(|(name, ty)| (name.to_string(), ty.clone()))
+                    .collect(),
+            ),
+            ..Response::new()
+        }
+    }
+
+    /// Creates a response for a statement which fails with the provided error.
+    pub fn error(code: SqlState, message: &str) -> Response {
+        Response {
+            error: Some((code, message.to_string())),
+            ..Response::new()
+        }
+    }
+
+    /// Sets the types of the statement's parameters, which are reported to clients preparing it.
+    ///
+    /// Defaults to the types given by the client, if any.
+    pub fn params(mut self, types: &[Type]) -> Response {
+        self.params = types.to_vec();
+        self
+    }
+
+    /// Adds a row to the response, given the text format of each of its values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the response does not return rows, or if the number of values does not match the number of columns.
+    pub fn row(mut self, values: &[Option<&str>]) -> Response {
+        let columns = self
+            .columns
+            .as_ref()
+            .expect("row added to a response without columns");
+        assert_eq!(
+            values.len(),
+            columns.len(),
+            "expected {} values but got {}",
+            columns.len(),
+            values.len()
+        );
+        self.rows
+            .push(values.iter().map(|v| v.map(str::to_string)).collect());
+        self
+    }
+
+    fn tag(&self) -> String {
+        match &self.tag {
+            Some(tag) => tag.clone(),
+            None => format!("SELECT {}", self.rows.len()),
+        }
+    }
+}
+
+/// A scriptable mock PostgreSQL backend.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Clone)]
+pub struct Backend {
+    auth: Auth,
+    parameters: Vec<(String, String)>,
+    script: Vec<(String, Response)>,
+}
+
+impl Default for Backend {
+    fn default() -> Backend {
+        Backend::new()
+    }
+}
+
+impl Backend {
+    /// Creates a new backend which accepts clients without a password and expects no statements.
+    pub fn new() -> Backend {
+        Backend {
+            auth: Auth::Trust,
+            parameters: DEFAULT_PARAMETERS
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            script: vec![],
+        }
+    }
+
+    /// Sets the authentication method required of clients.
+    ///
+    /// Defaults to `Auth::Trust`.
+    pub fn auth(&mut self, auth: Auth) -> &mut Backend {
+        self.auth = auth;
+        self
+    }
+
+    /// Sets a runtime parameter reported to clients once they have authenticated.
+    ///
+    /// Defaults are reported for `server_version`, `server_encoding`, `client_encoding`, `DateStyle`, `TimeZone`,
+    /// `integer_datetimes`, and `standard_conforming_strings`.
+    pub fn parameter(&mut self, name: &str, value: &str) -> &mut Backend {
+        match self.parameters.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.parameters.push((name.to_string(), value.to_string())),
+        }
+        self
+    }
+
+    /// Appends a statement to the script.
+    ///
+    /// Statements must be received in the order they are scripted, and their text must match exactly, ignoring
+    /// leading and trailing whitespace. A statement which does not match the next entry of the script fails with an
+    /// `INTERNAL_ERROR` error, and is reported by `serve`. A statement prepared once may be executed any number of
+    /// times.
+    pub fn query(&mut self, query: &str, response: Response) -> &mut Backend {
+        self.script.push((query.trim().to_string(), response));
+        self
+    }
+
+    /// Serves a single client connection over the provided stream, running the script from its start.
+    ///
+    /// Returns once the client terminates the connection or closes the stream. An error is returned if the client
+    /// violates the protocol, sends a statement which does not match the script, or disconnects before all of the
+    /// scripted statements have been run. A client which fails to authenticate is sent an error, after which this
+    /// returns `Ok(())`.
+    pub async fn serve<S>(&self, stream: S) -> io::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut handler = MockHandler {
+            backend: self,
+            script: self.script.iter(),
+            statements: HashMap::new(),
+            portals: HashMap::new(),
+            authenticated: false,
+            failures: vec![],
+        };
+        postgres_server::serve(stream, &mut handler).await?;
+
+        let remaining = handler.script.len();
+        if handler.authenticated && remaining > 0 {
+            handler.failures.push(format!(
+                "{} scripted statement(s) were not run, starting with `{}`",
+                remaining,
+                handler.script.as_slice()[0].0
+            ));
+        }
+        if handler.failures.is_empty() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "mock backend script failed: {}",
+                handler.failures.join("; ")
+            )))
+        }
+    }
+}
+
+struct Statement<'a> {
+    response: &'a Response,
+    params: Vec<Oid>,
+}
+
+struct Portal<'a> {
+    response: &'a Response,
+    formats: Vec<i16>,
+    sent: usize,
+}
+
+struct MockHandler<'a> {
+    backend: &'a Backend,
+    script: std::slice::Iter<'a, (String, Response)>,
+    statements: HashMap<String, Statement<'a>>,
+    portals: HashMap<String, Portal<'a>>,
+    authenticated: bool,
+    failures: Vec<String>,
+}
+
+impl<'a> MockHandler<'a> {
+    // Matches a statement against the script, returning an error to report to the client if it does not match.
+    fn next_statement(&mut self, query: &str) -> Result<&'a Response, ErrorResponse> {
+        let query = query.trim();
+        let message = match self.script.as_slice().first() {
+            Some((expected, response)) if expected == query => {
+                self.script.next();
+                return Ok(response);
+            }
+            Some((expected, _)) => format!(
+                "mock backend expected statement `{}` but received `{}`",
+                expected, query
+            ),
+            None => format!("mock backend received unscripted statement `{}`", query),
+        };
+
+        let error = ErrorResponse::new(SqlState::INTERNAL_ERROR.code(), &message);
+        self.failures.push(message);
+        Err(error)
+    }
+}
+
+#[async_trait]
+impl Handler for MockHandler<'_> {
+    async fn on_startup(&mut self, _: &Startup) -> Result<Auth, ErrorResponse> {
+        Ok(self.backend.auth.clone())
+    }
+
+    async fn on_authenticated(&mut self, out: &mut Responder) -> Result<(), ErrorResponse> {
+        self.authenticated = true;
+        for (name, value) in &self.backend.parameters {
+            out.parameter_status(name, value)?;
+        }
+        out.backend_key_data(PROCESS_ID, SECRET_KEY);
+        Ok(())
+    }
+
+    async fn on_query(&mut self, query: &str, out: &mut Responder) -> Result<(), ErrorResponse> {
+        let response = self.next_statement(query)?;
+        let mut portal = Portal {
+            response,
+            formats: vec![],
+            sent: 0,
+        };
+        if response.columns.is_some() && response.error.is_none() {
+            write_row_description(response, &[], out)?;
+        }
+        execute(&mut portal, 0, out)
+    }
+
+    async fn on_parse(
+        &mut self,
+        name: &str,
+        query: &str,
+        param_types: &[Oid],
+    ) -> Result<(), ErrorResponse> {
+        let response = self.next_statement(query)?;
+        let params = if response.params.is_empty() {
+            param_types.to_vec()
+        } else {
+            response.params.iter().map(Type::oid).collect()
+        };
+        self.statements
+            .insert(name.to_string(), Statement { response, params });
+        Ok(())
+    }
+
+    async fn on_bind(&mut self, bind: &Bind<'_>) -> Result<(), ErrorResponse> {
+        let statement = self
+            .statements
+            .get(bind.statement())
+            .ok_or_else(|| missing_statement(bind.statement()))?;
+        let portal = Portal {
+            response: statement.response,
+            formats: bind.result_formats().to_vec(),
+            sent: 0,
+        };
+        self.portals.insert(bind.portal().to_string(), portal);
+        Ok(())
+    }
+
+    async fn on_describe_statement(
+        &mut self,
+        name: &str,
+        out: &mut Responder,
+    ) -> Result<(), ErrorResponse> {
+        let statement = self
+            .statements
+            .get(name)
+            .ok_or_else(|| missing_statement(name))?;
+        out.parameter_description(statement.params.iter().copied())?;
+        write_row_description(statement.response, &[], out)
+    }
+
+    async fn on_describe_portal(
+        &mut self,
+        name: &str,
+        out: &mut Responder,
+    ) -> Result<(), ErrorResponse> {
+        let portal = self.portals.get(name).ok_or_else(|| missing_portal(name))?;
+        write_row_description(portal.response, &portal.formats, out)
+    }
+
+    async fn on_execute(
+        &mut self,
+        portal: &str,
+        max_rows: i32,
+        out: &mut Responder,
+    ) -> Result<(), ErrorResponse> {
+        let portal = self
+            .portals
+            .get_mut(portal)
+            .ok_or_else(|| missing_portal(portal))?;
+        execute(portal, usize::try_from(max_rows).unwrap_or(0), out)
+    }
+
+    async fn on_close_statement(&mut self, name: &str) -> Result<(), ErrorResponse> {
+        self.statements.remove(name);
+        Ok(())
+    }
+
+    async fn on_close_portal(&mut self, name: &str) -> Result<(), ErrorResponse> {
+        self.portals.remove(name);
+        Ok(())
+    }
+}
+
+fn missing_statement(name: &str) -> ErrorResponse {
+    ErrorResponse::new(
+        SqlState::INVALID_SQL_STATEMENT_NAME.code(),
+        &format!("prepared statement \"{}\" does not exist", name),
+    )
+}
+
+fn missing_portal(name: &str) -> ErrorResponse {
+    ErrorResponse::new(
+        SqlState::INVALID_CURSOR_NAME.code(),
+        &format!("portal \"{}\" does not exist", name),
+    )
+}
+
+fn column_format(formats: &[i16], column: usize) -> i16 {
+    match formats {
+        [] => 0,
+        [format] => *format,
+        formats => formats.get(column).copied().unwrap_or(0),
+    }
+}
+
+fn write_row_description(
+    response: &Response,
+    formats: &[i16],
+    out: &mut Responder,
+) -> Result<(), ErrorResponse> {
+    match &response.columns {
+        Some(columns) if response.error.is_none() => {
+            out.row_description(columns.iter().enumerate().map(|(i, (name, ty))| {
+                Field::new(name, 0, 0, ty.oid(), -1, -1, column_format(formats, i))
+            }))?;
+        }
+        _ => out.no_data(),
+    }
+    Ok(())
+}
+
+// Writes the next batch of rows of a portal, followed by either its command tag or, if rows remain, a suspension.
+fn execute(
+    portal: &mut Portal<'_>,
+    max_rows: usize,
+    out: &mut Responder,
+) -> Result<(), ErrorResponse> {
+    let response = portal.response;
+    if let Some((code, message)) = &response.error {
+        return Err(ErrorResponse::new(code.code(), message));
+    }
+
+    let columns = response.columns.as_deref().unwrap_or(&[]);
+    let rows = &response.rows[portal.sent..];
+    let count = if max_rows == 0 {
+        rows.len()
+    } else {
+        rows.len().min(max_rows)
+    };
+
+    let mut buf = BytesMut::new();
+    for row in &rows[..count] {
+        let mut values = vec![];
+        for (i, value) in row.iter().enumerate() {
+            let value = match value {
+                Some(value) => {
+                    let start = buf.len();
+                    if column_format(&portal.formats, i) == 1 {
+                        to_binary(&columns[i].1, value, &mut buf).map_err(|e| {
+                            ErrorResponse::new(
+                                SqlState::FEATURE_NOT_SUPPORTED.code(),
+                                &e.to_string(),
+                            )
+                        })?;
+                    } else {
+                        buf.extend_from_slice(value.as_bytes());
+                    }
+                    Some(start..buf.len())
+                }
+                None => None,
+            };
+            values.push(value);
+        }
+        out.data_row(values.iter().map(|range| range.clone().map(|r| &buf[r])))?;
+        buf.clear();
+    }
+    portal.sent += count;
+
+    if portal.sent < response.rows.len() {
+        out.portal_suspended();
+    } else {
+        out.command_complete(&response.tag())?;
+    }
+    Ok(())
+}
+
+fn to_binary(
+    ty: &Type,
+    value: &str,
+    buf: &mut BytesMut,
+) -> Result<(), Box<dyn error::Error + Sync + Send>> {
+    match *ty {
+        Type::BOOL => match value {
+            "t" | "true" => types::bool_to_sql(true, buf),
+            "f" | "false" => types::bool_to_sql(false, buf),
+            _ => return Err(format!("invalid boolean value `{}`", value).into()),
+        },
+        Type::INT2 => types::int2_to_sql(value.parse()?, buf),
+        Type::INT4 => types::int4_to_sql(value.parse()?, buf),
+        Type::INT8 => types::int8_to_sql(value.parse()?, buf),
+        Type::OID => types::oid_to_sql(value.parse()?, buf),
+        Type::FLOAT4 => types::float4_to_sql(value.parse()?, buf),
+        Type::FLOAT8 => types::float8_to_sql(value.parse()?, buf),
+        Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME | Type::UNKNOWN => {
+            types::text_to_sql(value, buf)
+        }
+        _ => {
+            return Err(format!(
+                "the mock backend cannot send values of type {} in binary format",
+                ty
+            )
+            .into())
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use crate::client::Responses;
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::query::{encode_bind, extract_row_affected};
+use crate::types::ToSql;
+use crate::{slice_iter, Client, Error, Row, Statement, ToStatement};
+use bytes::BytesMut;
+use log::debug;
+use postgres_protocol::message::backend::Message;
+use postgres_protocol::message::frontend;
+use std::fmt;
+
+/// A batch of statements sent to the server together.
+///
+/// Statements are queued with [`Pipeline::add`], and then sent to the server in a single round trip followed by a single
+/// `Sync` message when [`Pipeline::run`] is called.
+///
+/// Because the statements share a `Sync`, they run in a single implicit transaction unless the pipeline contains its
+/// own transaction control statements or is run inside of a [`Transaction`](crate::Transaction). If a statement fails,
+/// the server skips every statement after it, and the effects of the statements before it are rolled back along with
+/// the rest of the transaction.
+pub struct Pipeline<'a> {
+    client: &'a Client,
+    buf: BytesMut,
+    statements: Vec<Statement>,
+}
+
+impl fmt::Debug for Pipeline<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("statements", &self.statements)
+            .finish()
+    }
+}
+
+impl<'a> Pipeline<'a> {
+    pub(crate) fn new(client: &'a Client) -> Pipeline<'a> {
+        Pipeline {
+            client,
+            buf: BytesMut::new(),
+            statements: vec![],
+        }
+    }
+
+    /// Queues a statement, returning its index in the results of [`Pipeline::run`].
+    ///
+    /// The `statement` argument can either be a `Statement`, or a raw query string. Raw query strings are prepared
+    /// immediately, which requires a round trip to the server; prepare statements up front to avoid this.
+    ///
+    /// Parameters are serialized immediately, so errors converting them are reported here rather than by `run`.
+    pub async fn add<T>(
+        &mut self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<usize, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        let statement = statement.__convert().into_statement(self.client).await?;
+
+        let base = self.buf.len();
+        let r = encode_bind(&statement, slice_iter(params), "", &mut self.buf)
+            .and_then(|()| frontend::execute("", 0, &mut self.buf).map_err(Error::encode));
+        if let Err(e) = r {
+            self.buf.truncate(base);
+            return Err(e);
+        }
+
+        self.statements.push(statement);
+        Ok(self.statements.len() - 1)
+    }
+
+    /// Returns the number of statements queued.
+    pub fn len(&self) -> usize {
+        self.statements.len()
+    }
+
+    /// Determines if no statements have been queued.
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    /// Sends the queued statements to the server and waits for all of their results.
+    ///
+    /// The returned vector contains one entry per queued statement, in order. Once a statement fails, every statement
+    /// after it returns an error for which [`Error::is_pipeline_aborted`] returns `true`.
+    ///
+    /// An error is returned directly only if the connection fails or the server's response could not be processed.
+    pub async fn run(mut self) -> Result<Vec<Result<PipelineResult, Error>>, Error> {
+        if self.statements.is_empty() {
+            return Ok(vec![]);
+        }
+
+        debug!("executing pipeline of {} statements", self.statements.len());
+
+        frontend::sync(&mut self.buf);
+        let mut responses =
+            self.client
+                .inner()
+                .send(RequestMessages::Single(FrontendMessage::Raw(
+                    self.buf.split().freeze(),
+                )))?;
+
+        let mut results = Vec::with_capacity(self.statements.len());
+        for statement in &self.statements {
+            match read_result(&mut responses, statement).await {
+                Ok(result) => results.push(Ok(result)),
+                Err(e) if e.as_db_error().is_some() => {
+                    results.push(Err(e));
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        while results.len() < self.statements.len() {
+            results.push(Err(Error::pipeline_aborted()));
+        }
+
+        match responses.next().await? {
+            Message::ReadyForQuery(_) => Ok(results),
+            _ => Err(Error::unexpected_message()),
+        }
+    }
+}
+
+async fn read_result(
+    responses: &mut Responses,
+    statement: &Statement,
+) -> Result<PipelineResult, Error> {
+    match responses.next().await? {
+        Message::BindComplete => {}
+        _ => return Err(Error::unexpected_message()),
+    }
+
+    let mut rows = vec![];
+    loop {
+        match responses.next().await? {
+            Message::DataRow(body) => rows.push(Row::new(statement.clone(), body)?),
+            Message::CommandComplete(body) => {
+                let rows_affected = extract_row_affected(&body)?;
+                return Ok(PipelineResult {
+                    rows,
+                    rows_affected,
+                });
+            }
+            Message::EmptyQueryResponse => {
+                return Ok(PipelineResult {
+                    rows,
+                    rows_affected: 0,
+                })
+            }
+            _ => return Err(Error::unexpected_message()),
+        }
+    }
+}
+
+/// The result of a statement executed as part of a [`Pipeline`].
+#[derive(Debug)]
+pub struct PipelineResult {
+    rows: Vec<Row>,
+    rows_affected: u64,
+}
+
+impl PipelineResult {
+    /// Returns the rows returned by the statement.
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    /// Consumes the result, returning the rows returned by the statement.
+    pub fn into_rows(self) -> Vec<Row> {
+        self.rows
+    }
+
+    /// Returns the number of rows affected by the statement.
+    pub fn rows_affected(&self) -> u64 {
+        self.rows_affected
+    }
+}
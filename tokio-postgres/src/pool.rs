@@ -0,0 +1,446 @@
+//! A connection pool.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use tokio_postgres::pool::Pool;
+//! use tokio_postgres::NoTls;
+//!
+//! # async fn example() -> Result<(), tokio_postgres::Error> {
+//! let config = "host=localhost user=postgres".parse()?;
+//! let pool = Pool::builder(config, NoTls).max_size(16).build();
+//!
+//! let client = pool.get().await?;
+//! client.execute("UPDATE foo SET bar = 1", &[]).await?;
+//! // The connection is returned to the pool when `client` is dropped.
+//! # Ok(())
+//! # }
+//! ```
+use crate::rt::{self, RuntimeHandle};
+use crate::tls::{MakeTlsConnect, TlsConnect};
+use crate::{Client, Config, Error, Socket};
+use log::info;
+use parking_lot::Mutex;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// A builder for [`Pool`]s.
+pub struct PoolBuilder<T> {
+    config: Config,
+    tls: T,
+    max_size: usize,
+    idle_timeout: Option<Duration>,
+    test_on_checkout: bool,
+    concurrency_limit: Option<ConcurrencyLimit>,
+    wait_timeout: Option<Duration>,
+}
+
+impl<T> PoolBuilder<T> {
+    /// Sets the maximum number of connections managed by the pool.
+    ///
+    /// Defaults to 10.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_size` is 0.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        assert!(max_size > 0, "max_size must be positive");
+        self.max_size = max_size;
+        self
+    }
+
+    /// Sets the amount of time a connection may sit idle in the pool before it is closed.
+    ///
+    /// Defaults to no timeout.
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Determines if idle connections are checked with an empty query before they are handed out.
+    ///
+    /// Connections which fail the check are discarded and replaced. Defaults to `true`.
+    pub fn test_on_checkout(mut self, test_on_checkout: bool) -> Self {
+        self.test_on_checkout = test_on_checkout;
+        self
+    }
+
+    /// Sets a limit on the number of connections checked out at once, shared with any other pools using it.
+    ///
+    /// Connections are only checked out of the pool when both the pool and the limit have room for them. Defaults to
+    /// no limit beyond the pool's own `max_size`.
+    pub fn concurrency_limit(mut self, concurrency_limit: ConcurrencyLimit) -> Self {
+        self.concurrency_limit = Some(concurrency_limit);
+        self
+    }
+
+    /// Sets the maximum amount of time [`Pool::get`] waits for a connection when the pool is at its `max_size` or its
+    /// concurrency limit has been reached.
+    ///
+    /// Once it elapses, `get` returns an error for which [`Error::is_timeout`] returns `true`. A timeout of zero rejects
+    /// requests which cannot be served immediately rather than queueing them. Defaults to no timeout.
+    pub fn wait_timeout(mut self, wait_timeout: Duration) -> Self {
+        self.wait_timeout = Some(wait_timeout);
+        self
+    }
+
+    /// Creates the pool.
+    ///
+    /// No connections are opened until the first call to [`Pool::get`].
+    pub fn build(self) -> Pool<T> {
+        Pool {
+            inner: Arc::new(Inner {
+                config: self.config,
+                tls: self.tls,
+                max_size: self.max_size,
+                idle_timeout: self.idle_timeout,
+                test_on_checkout: self.test_on_checkout,
+                concurrency_limit: self.concurrency_limit,
+                wait_timeout: self.wait_timeout,
+                semaphore: Arc::new(Semaphore::new(self.max_size)),
+                idle: Mutex::new(vec![]),
+                size: AtomicUsize::new(0),
+                reaper_started: AtomicBool::new(false),
+            }),
+        }
+    }
+}
+
+/// A limit on the number of connections checked out at once across one or more [`Pool`]s.
+///
+/// A checked out connection executes its statements one at a time, so sharing a limit between the pools of a process
+/// bounds the number of statements running against the database at once, even when each pool is within its own
+/// `max_size`. Requests over the limit wait for a connection to be returned, or fail once the pool's
+/// [`wait_timeout`](PoolBuilder::wait_timeout) elapses.
+///
+/// Clones refer to the same limit.
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    semaphore: Arc<Semaphore>,
+    max: usize,
+}
+
+impl fmt::Debug for ConcurrencyLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConcurrencyLimit")
+            .field("max", &self.max)
+            .field("available", &self.available())
+            .finish()
+    }
+}
+
+impl ConcurrencyLimit {
+    /// Creates a limit allowing `max` connections to be checked out at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is 0.
+    pub fn new(max: usize) -> ConcurrencyLimit {
+        assert!(max > 0, "max must be positive");
+        ConcurrencyLimit {
+            semaphore: Arc::new(Semaphore::new(max)),
+            max,
+        }
+    }
+
+    /// Returns the maximum number of connections which can be checked out at once.
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Returns the number of connections which can currently be checked out before the limit is reached.
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+}
+
+/// Information about the state of a [`Pool`].
+#[derive(Debug, Copy, Clone)]
+#[non_exhaustive]
+pub struct Status {
+    /// The maximum number of connections managed by the pool.
+    pub max_size: usize,
+    /// The number of open connections, both idle and checked out.
+    pub size: usize,
+    /// The number of idle connections.
+    pub idle: usize,
+}
+
+/// An asynchronous pool of database connections.
+///
+/// Connections are opened on demand up to the configured maximum size. Once that limit is reached, [`Pool::get`] waits
+/// for a connection to be returned.
+///
+/// The pool is reference counted; clones refer to the same set of connections.
+pub struct Pool<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Pool<T> {
+        Pool {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("status", &self.status())
+            .finish()
+    }
+}
+
+impl<T> Pool<T> {
+    /// Returns a builder for a pool which connects with `config` and `tls`.
+    pub fn builder(config: Config, tls: T) -> PoolBuilder<T> {
+        PoolBuilder {
+            config,
+            tls,
+            max_size: 10,
+            idle_timeout: None,
+            test_on_checkout: true,
+            concurrency_limit: None,
+            wait_timeout: None,
+        }
+    }
+
+    /// Returns information about the current state of the pool.
+    pub fn status(&self) -> Status {
+        Status {
+            max_size: self.inner.max_size,
+            size: self.inner.size.load(Ordering::SeqCst),
+            idle: self.inner.idle.lock().len(),
+        }
+    }
+}
+
+impl<T> Pool<T>
+where
+    T: MakeTlsConnect<Socket> + Clone + 'static + Send + Sync,
+    T::TlsConnect: Send,
+    T::Stream: Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    /// Checks out a connection from the pool, opening a new one if none are idle.
+    ///
+    /// The connection is returned to the pool when the [`PooledClient`] is dropped.
+    pub async fn get(&self) -> Result<PooledClient<T>, Error> {
+        self.start_reaper();
+
+        let acquire = async {
+            let permit = self
+                .inner
+                .semaphore
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("pool semaphore closed");
+            let limit_permit = match &self.inner.concurrency_limit {
+                Some(limit) => Some(
+                    limit
+                        .semaphore
+                        .clone()
+                        .acquire_owned()
+                        .await
+                        .expect("limit semaphore closed"),
+                ),
+                None => None,
+            };
+            (permit, limit_permit)
+        };
+        let (permit, limit_permit) = match self.inner.wait_timeout {
+            Some(wait_timeout) => {
+                rt::timeout(self.inner.config.runtime.as_ref(), wait_timeout, acquire)
+                    .await
+                    .ok_or_else(Error::pool_timeout)?
+            }
+            None => acquire.await,
+        };
+
+        loop {
+            let idle = self.inner.idle.lock().pop();
+            let idle = match idle {
+                Some(idle) => idle,
+                None => break,
+            };
+
+            // the connection's slot is given back if it's discarded, including if this future is dropped during the check
+            let slot = SlotGuard(Some(&self.inner.size));
+
+            if self.inner.is_expired(&idle) || idle.client.is_closed() {
+                continue;
+            }
+
+            if self.inner.test_on_checkout && idle.client.simple_query("").await.is_err() {
+                continue;
+            }
+
+            slot.keep();
+            return Ok(PooledClient {
+                client: Some(idle.client),
+                pool: self.inner.clone(),
+                _permit: permit,
+                _limit_permit: limit_permit,
+            });
+        }
+
+        let (client, connection) = self.inner.config.connect(self.inner.tls.clone()).await?;
+        rt::spawn(self.inner.config.runtime.as_ref(), async move {
+            if let Err(e) = connection.await {
+                info!("pooled connection closed with error: {}", e);
+            }
+        });
+        self.inner.size.fetch_add(1, Ordering::SeqCst);
+
+        Ok(PooledClient {
+            client: Some(client),
+            pool: self.inner.clone(),
+            _permit: permit,
+            _limit_permit: limit_permit,
+        })
+    }
+
+    fn start_reaper(&self) {
+        let idle_timeout = match self.inner.idle_timeout {
+            Some(idle_timeout) => idle_timeout,
+            None => return,
+        };
+        if self.inner.reaper_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let runtime = self.inner.config.runtime.clone();
+        let inner = Arc::downgrade(&self.inner);
+        rt::spawn(
+            self.inner.config.runtime.as_ref(),
+            reap(inner, runtime, idle_timeout),
+        );
+    }
+}
+
+async fn reap<T>(inner: Weak<Inner<T>>, runtime: Option<RuntimeHandle>, idle_timeout: Duration) {
+    loop {
+        rt::sleep(runtime.as_ref(), idle_timeout).await;
+        let inner = match inner.upgrade() {
+            Some(inner) => inner,
+            None => break,
+        };
+
+        let mut idle = inner.idle.lock();
+        let before = idle.len();
+        idle.retain(|c| !inner.is_expired(c) && !c.client.is_closed());
+        inner.size.fetch_sub(before - idle.len(), Ordering::SeqCst);
+    }
+}
+
+// Releases a connection's slot in the size of the pool when dropped, unless `keep` is called.
+struct SlotGuard<'a>(Option<&'a AtomicUsize>);
+
+impl SlotGuard<'_> {
+    fn keep(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for SlotGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(size) = self.0 {
+            size.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+struct IdleClient {
+    client: Client,
+    since: Instant,
+}
+
+struct Inner<T> {
+    config: Config,
+    tls: T,
+    max_size: usize,
+    idle_timeout: Option<Duration>,
+    test_on_checkout: bool,
+    concurrency_limit: Option<ConcurrencyLimit>,
+    wait_timeout: Option<Duration>,
+    semaphore: Arc<Semaphore>,
+    idle: Mutex<Vec<IdleClient>>,
+    size: AtomicUsize,
+    reaper_started: AtomicBool,
+}
+
+impl<T> Inner<T> {
+    fn is_expired(&self, idle: &IdleClient) -> bool {
+        match self.idle_timeout {
+            Some(idle_timeout) => idle.since.elapsed() >= idle_timeout,
+            None => false,
+        }
+    }
+}
+
+/// A connection checked out from a [`Pool`].
+///
+/// It dereferences to a [`Client`], and returns the connection to the pool when dropped.
+pub struct PooledClient<T> {
+    client: Option<Client>,
+    pool: Arc<Inner<T>>,
+    _permit: OwnedSemaphorePermit,
+    _limit_permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<T> PooledClient<T> {
+    /// Removes the connection from the pool.
+    ///
+    /// The pool will open a new connection in its place when needed.
+    pub fn detach(mut self) -> Client {
+        self.pool.size.fetch_sub(1, Ordering::SeqCst);
+        self.client.take().unwrap()
+    }
+}
+
+impl<T> Deref for PooledClient<T> {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for PooledClient<T> {
+    fn deref_mut(&mut self) -> &mut Client {
+        self.client.as_mut().unwrap()
+    }
+}
+
+impl<T> fmt::Debug for PooledClient<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledClient")
+            .field("client", &self.client)
+            .finish()
+    }
+}
+
+impl<T> Drop for PooledClient<T> {
+    fn drop(&mut self) {
+        let client = match self.client.take() {
+            Some(client) => client,
+            None => return,
+        };
+
+        if client.is_closed() {
+            self.pool.size.fetch_sub(1, Ordering::SeqCst);
+            return;
+        }
+
+        self.pool.idle.lock().push(IdleClient {
+            client,
+            since: Instant::now(),
+        });
+    }
+}
@@ -56,6 +56,13 @@ AND attnum > 0
 ORDER BY attnum
 ";
 
+// Multirange types weren't added until Postgres 14, but are only looked up for types with typtype 'm'
+const TYPEINFO_MULTIRANGE_QUERY: &str = "\
+SELECT rngsubtype
+FROM pg_catalog.pg_range
+WHERE rngmultitypid = $1
+";
+
 static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
 
 pub async fn prepare(
@@ -105,7 +112,13 @@ pub async fn prepare(
         }
     }
 
-    Ok(Statement::new(client, name, parameters, columns))
+    Ok(Statement::new(
+        client,
+        name,
+        query.to_string(),
+        parameters,
+        columns,
+    ))
 }
 
 fn prepare_rec<'a>(
@@ -163,6 +176,10 @@ pub(crate) async fn get_type(client: &Arc<InnerClient>, oid: Oid) -> Result<Type
         Kind::Enum(variants)
     } else if type_ == b'p' as i8 {
         Kind::Pseudo
+    } else if type_ == b'm' as i8 {
+        let subtype = get_multirange_subtype(client, oid).await?;
+        let type_ = get_type_rec(client, subtype).await?;
+        Kind::Multirange(type_)
     } else if basetype != 0 {
         let type_ = get_type_rec(client, basetype).await?;
         Kind::Domain(type_)
@@ -265,3 +282,26 @@ async fn typeinfo_composite_statement(client: &Arc<InnerClient>) -> Result<State
     client.set_typeinfo_composite(&stmt);
     Ok(stmt)
 }
+
+async fn get_multirange_subtype(client: &Arc<InnerClient>, oid: Oid) -> Result<Oid, Error> {
+    let stmt = typeinfo_multirange_statement(client).await?;
+
+    let rows = query::query(client, stmt, slice_iter(&[&oid])).await?;
+    pin_mut!(rows);
+
+    match rows.try_next().await? {
+        Some(row) => row.try_get(0),
+        None => Err(Error::unexpected_message()),
+    }
+}
+
+async fn typeinfo_multirange_statement(client: &Arc<InnerClient>) -> Result<Statement, Error> {
+    if let Some(stmt) = client.typeinfo_multirange() {
+        return Ok(stmt);
+    }
+
+    let stmt = prepare_rec(client, TYPEINFO_MULTIRANGE_QUERY, &[]).await?;
+
+    client.set_typeinfo_multirange(&stmt);
+    Ok(stmt)
+}
@@ -0,0 +1,239 @@
+use crate::config::{Proxy, ProxyKind};
+use crate::connect_socket::connect_tcp;
+use crate::resolver;
+use crate::socket_config::{Addr, SocketConfig};
+use crate::Socket;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::convert::TryFrom;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const SOCKS_VERSION: u8 = 5;
+const SOCKS_NO_AUTH: u8 = 0;
+const SOCKS_PASSWORD_AUTH: u8 = 2;
+const SOCKS_NO_ACCEPTABLE_AUTH: u8 = 0xff;
+const SOCKS_CONNECT: u8 = 1;
+const SOCKS_IPV4: u8 = 1;
+const SOCKS_DOMAIN: u8 = 3;
+const SOCKS_IPV6: u8 = 4;
+
+const MAX_HTTP_RESPONSE_LEN: usize = 8192;
+
+/// Opens a TCP connection to the server through a proxy.
+pub(crate) async fn connect(proxy: &Proxy, config: &SocketConfig) -> io::Result<Socket> {
+    let mut stream = connect_proxy(proxy, config).await?;
+
+    match proxy.kind() {
+        ProxyKind::Socks5 => {
+            socks5_handshake(&mut stream, proxy, &config.addr, config.port).await?
+        }
+        ProxyKind::Http => http_handshake(&mut stream, proxy, &config.addr, config.port).await?,
+    }
+
+    Ok(stream)
+}
+
+async fn connect_proxy(proxy: &Proxy, config: &SocketConfig) -> io::Result<Socket> {
+    let mut last_err = None;
+    let addrs = resolver::resolve(
+        config.resolver.as_ref(),
+        config.runtime.as_ref(),
+        proxy.host(),
+        proxy.port(),
+    )
+    .await?;
+    for addr in addrs {
+        let addr = SocketAddr::new(addr.ip(), proxy.port());
+        match connect_tcp(addr, config).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_err = Some(e),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "could not resolve any proxy addresses",
+        )
+    }))
+}
+
+async fn socks5_handshake(
+    stream: &mut Socket,
+    proxy: &Proxy,
+    addr: &Addr,
+    port: u16,
+) -> io::Result<()> {
+    let credentials = proxy.username().zip(proxy.password());
+
+    let mut buf = vec![SOCKS_VERSION, 1, SOCKS_NO_AUTH];
+    if credentials.is_some() {
+        buf[1] = 2;
+        buf.push(SOCKS_PASSWORD_AUTH);
+    }
+    stream.write_all(&buf).await?;
+
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != SOCKS_VERSION {
+        return Err(invalid_data("invalid SOCKS proxy response"));
+    }
+    match (reply[1], credentials) {
+        (SOCKS_NO_AUTH, _) => {}
+        (SOCKS_PASSWORD_AUTH, Some((username, password))) => {
+            let mut buf = vec![1];
+            write_socks_string(&mut buf, username)?;
+            write_socks_string(&mut buf, password)?;
+            stream.write_all(&buf).await?;
+
+            let mut reply = [0; 2];
+            stream.read_exact(&mut reply).await?;
+            if reply[1] != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "SOCKS proxy authentication failed",
+                ));
+            }
+        }
+        (SOCKS_NO_ACCEPTABLE_AUTH, _) => {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "SOCKS proxy requires an unsupported authentication method",
+            ))
+        }
+        _ => return Err(invalid_data("invalid SOCKS proxy response")),
+    }
+
+    let mut buf = vec![SOCKS_VERSION, SOCKS_CONNECT, 0];
+    match addr {
+        Addr::Tcp(IpAddr::V4(ip)) => {
+            buf.push(SOCKS_IPV4);
+            buf.extend_from_slice(&ip.octets());
+        }
+        Addr::Tcp(IpAddr::V6(ip)) => {
+            buf.push(SOCKS_IPV6);
+            buf.extend_from_slice(&ip.octets());
+        }
+        Addr::Proxied(host) => {
+            buf.push(SOCKS_DOMAIN);
+            write_socks_string(&mut buf, host)?;
+        }
+        #[cfg(unix)]
+        Addr::Unix(_) => unreachable!(),
+    }
+    buf.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&buf).await?;
+
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != SOCKS_VERSION {
+        return Err(invalid_data("invalid SOCKS proxy response"));
+    }
+    if reply[1] != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("SOCKS proxy failed to connect: {}", socks_reply(reply[1])),
+        ));
+    }
+
+    // skip the address the proxy bound to
+    let len = match reply[3] {
+        SOCKS_IPV4 => 4,
+        SOCKS_IPV6 => 16,
+        SOCKS_DOMAIN => stream.read_u8().await? as usize,
+        _ => return Err(invalid_data("invalid SOCKS proxy response")),
+    };
+    let mut bound = vec![0; len + 2];
+    stream.read_exact(&mut bound).await?;
+
+    Ok(())
+}
+
+async fn http_handshake(
+    stream: &mut Socket,
+    proxy: &Proxy,
+    addr: &Addr,
+    port: u16,
+) -> io::Result<()> {
+    let target = match addr {
+        Addr::Tcp(IpAddr::V4(ip)) => format!("{}:{}", ip, port),
+        Addr::Tcp(IpAddr::V6(ip)) => format!("[{}]:{}", ip, port),
+        Addr::Proxied(host) => format!("{}:{}", host, port),
+        #[cfg(unix)]
+        Addr::Unix(_) => unreachable!(),
+    };
+
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+    if let Some((username, password)) = proxy.username().zip(proxy.password()) {
+        let credentials = STANDARD.encode(format!("{}:{}", username, password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", credentials));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // read the response a byte at a time so that nothing past its end is consumed
+    let mut response = vec![];
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() == MAX_HTTP_RESPONSE_LEN {
+            return Err(invalid_data("HTTP proxy response too long"));
+        }
+        response.push(stream.read_u8().await?);
+    }
+
+    let status_line = response
+        .split(|&b| b == b'\r')
+        .next()
+        .and_then(|line| std::str::from_utf8(line).ok())
+        .ok_or_else(|| invalid_data("invalid HTTP proxy response"))?;
+    let mut parts = status_line.splitn(3, ' ');
+    let status = match (parts.next(), parts.next()) {
+        (Some(version), Some(status)) if version.starts_with("HTTP/1.") => status
+            .parse::<u16>()
+            .map_err(|_| invalid_data("invalid HTTP proxy response"))?,
+        _ => return Err(invalid_data("invalid HTTP proxy response")),
+    };
+
+    match status {
+        200..=299 => Ok(()),
+        407 => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "HTTP proxy authentication failed",
+        )),
+        _ => Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("HTTP proxy failed to connect: {}", status_line),
+        )),
+    }
+}
+
+fn write_socks_string(buf: &mut Vec<u8>, s: &str) -> io::Result<()> {
+    let len = u8::try_from(s.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "SOCKS values must be at most 255 bytes",
+        )
+    })?;
+    buf.push(len);
+    buf.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+fn socks_reply(code: u8) -> &'static str {
+    match code {
+        1 => "general failure",
+        2 => "connection not allowed by ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "TTL expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -1,19 +1,23 @@
 use crate::client::{InnerClient, Responses};
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
+use crate::hook::HookGuard;
 use crate::prepare::get_type;
-use crate::types::{BorrowToSql, IsNull};
-use crate::{Column, Error, Portal, Row, Statement};
+use crate::types::{BorrowToSql, FromRowOwned, IsNull};
+use crate::{
+    simple_query, Column, Error, Portal, QueryOptions, Row, SimpleColumn, SimpleQueryRow, Statement,
+};
 use bytes::{Bytes, BytesMut};
 use fallible_iterator::FallibleIterator;
 use futures_util::{ready, Stream};
 use log::{debug, log_enabled, Level};
 use pin_project_lite::pin_project;
+use postgres_protocol::escape::escape_literal;
 use postgres_protocol::message::backend::{CommandCompleteBody, Message};
 use postgres_protocol::message::frontend;
 use postgres_types::Type;
 use std::fmt;
-use std::marker::PhantomPinned;
+use std::marker::{PhantomData, PhantomPinned};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
@@ -57,34 +61,71 @@ where
         statement,
         responses,
         rows_affected: None,
+        hook: None,
         _p: PhantomPinned,
     })
 }
 
-pub async fn query_typed<P, I>(
-    client: &Arc<InnerClient>,
-    query: &str,
+pub async fn query_text<P, I>(
+    client: &InnerClient,
+    statement: Statement,
     params: I,
-) -> Result<RowStream, Error>
+) -> Result<TextRowStream, Error>
 where
     P: BorrowToSql,
-    I: IntoIterator<Item = (P, Type)>,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
 {
-    let buf = {
+    let buf = if log_enabled!(Level::Debug) {
         let params = params.into_iter().collect::<Vec<_>>();
-        let param_oids = params.iter().map(|(_, t)| t.oid()).collect::<Vec<_>>();
+        debug!(
+            "executing statement {} with parameters: {:?}",
+            statement.name(),
+            BorrowToSqlParamsDebug(params.as_slice()),
+        );
+        encode_text(client, &statement, params)?
+    } else {
+        encode_text(client, &statement, params)?
+    };
+    let responses = start(client, buf).await?;
+    let columns = statement
+        .columns()
+        .iter()
+        .map(|c| SimpleColumn::new(c.name().to_string(), c.type_().oid(), 0))
+        .collect();
+    Ok(TextRowStream {
+        columns,
+        responses,
+        rows_affected: None,
+        hook: None,
+        _p: PhantomPinned,
+    })
+}
 
-        client.with_buf(|buf| {
-            frontend::parse("", query, param_oids.into_iter(), buf).map_err(Error::parse)?;
-            encode_bind_raw("", params, "", buf)?;
-            frontend::describe(b'S', "", buf).map_err(Error::encode)?;
-            frontend::execute("", 0, buf).map_err(Error::encode)?;
-            frontend::sync(buf);
+pub fn encode_typed<P, I>(client: &InnerClient, query: &str, params: I) -> Result<Bytes, Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = (P, Type)>,
+{
+    let params = params.into_iter().collect::<Vec<_>>();
+    let param_oids = params.iter().map(|(_, t)| t.oid()).collect::<Vec<_>>();
 
-            Ok(buf.split().freeze())
-        })?
-    };
+    client.with_buf(|buf| {
+        frontend::parse("", query, param_oids, buf).map_err(Error::parse)?;
+        encode_bind_raw("", params, "", 1, buf)?;
+        frontend::describe(b'S', "", buf).map_err(Error::encode)?;
+        frontend::execute("", 0, buf).map_err(Error::encode)?;
+        frontend::sync(buf);
+
+        Ok(buf.split().freeze())
+    })
+}
 
+pub async fn query_typed(
+    client: &Arc<InnerClient>,
+    query: &str,
+    buf: Bytes,
+) -> Result<RowStream, Error> {
     let mut responses = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)))?;
 
     loop {
@@ -92,9 +133,10 @@ where
             Message::ParseComplete | Message::BindComplete | Message::ParameterDescription(_) => {}
             Message::NoData => {
                 return Ok(RowStream {
-                    statement: Statement::unnamed(vec![], vec![]),
+                    statement: Statement::unnamed(query.to_string(), vec![], vec![]),
                     responses,
                     rows_affected: None,
+                    hook: None,
                     _p: PhantomPinned,
                 });
             }
@@ -112,9 +154,10 @@ where
                     columns.push(column);
                 }
                 return Ok(RowStream {
-                    statement: Statement::unnamed(vec![], columns),
+                    statement: Statement::unnamed(query.to_string(), vec![], columns),
                     responses,
                     rows_affected: None,
+                    hook: None,
                     _p: PhantomPinned,
                 });
             }
@@ -140,6 +183,7 @@ pub async fn query_portal(
         statement: portal.statement().clone(),
         responses,
         rows_affected: None,
+        hook: None,
         _p: PhantomPinned,
     })
 }
@@ -194,6 +238,109 @@ where
     }
 }
 
+pub async fn query_with_options<P, I>(
+    client: &InnerClient,
+    statement: Statement,
+    params: I,
+    options: &QueryOptions,
+) -> Result<(Vec<Row>, u64), Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let params = params.into_iter().collect::<Vec<_>>();
+    debug!(
+        "executing statement {} with parameters: {:?} and options: {:?}",
+        statement.name(),
+        BorrowToSqlParamsDebug(params.as_slice()),
+        options,
+    );
+
+    let buf = client.with_buf(|buf| {
+        if let Some(timeout) = options.get_statement_timeout() {
+            // the previous value is selected alongside the new one so that it can be restored if the statement runs
+            // inside of an explicit transaction, where the local setting would otherwise outlive it
+            let millis = timeout.as_nanos().div_ceil(1_000_000);
+            let query = format!(
+                "SELECT current_setting('statement_timeout'), \
+                 set_config('statement_timeout', '{}', true)",
+                millis.clamp(1, i32::MAX as u128),
+            );
+            frontend::parse("", &query, None, buf).map_err(Error::parse)?;
+            encode_bind_raw("", std::iter::empty::<(&str, Type)>(), "", 0, buf)?;
+            frontend::execute("", 0, buf).map_err(Error::encode)?;
+        }
+        encode_bind(&statement, params, "", buf)?;
+        frontend::execute("", 0, buf).map_err(Error::encode)?;
+        frontend::sync(buf);
+        Ok(buf.split().freeze())
+    })?;
+    let mut responses = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)))?;
+
+    let previous_timeout = match options.get_statement_timeout() {
+        Some(_) => Some(read_previous_setting(&mut responses).await?),
+        None => None,
+    };
+
+    match responses.next().await? {
+        Message::BindComplete => {}
+        _ => return Err(Error::unexpected_message()),
+    }
+
+    let mut rows = vec![];
+    let mut rows_affected = 0;
+    let status = loop {
+        match responses.next().await? {
+            Message::DataRow(body) => rows.push(Row::new(statement.clone(), body)?),
+            Message::CommandComplete(body) => rows_affected = extract_row_affected(&body)?,
+            Message::EmptyQueryResponse => rows_affected = 0,
+            Message::ReadyForQuery(body) => break body.status(),
+            _ => return Err(Error::unexpected_message()),
+        }
+    };
+
+    if let (Some(previous_timeout), b'T') = (previous_timeout, status) {
+        let query = format!(
+            "SELECT set_config('statement_timeout', {}, true)",
+            escape_literal(&previous_timeout)
+        );
+        simple_query::batch_execute(client, &query).await?;
+    }
+
+    Ok((rows, rows_affected))
+}
+
+async fn read_previous_setting(responses: &mut Responses) -> Result<String, Error> {
+    match responses.next().await? {
+        Message::ParseComplete => {}
+        _ => return Err(Error::unexpected_message()),
+    }
+    match responses.next().await? {
+        Message::BindComplete => {}
+        _ => return Err(Error::unexpected_message()),
+    }
+
+    let setting = match responses.next().await? {
+        Message::DataRow(body) => {
+            let range = body
+                .ranges()
+                .next()
+                .map_err(Error::parse)?
+                .flatten()
+                .ok_or_else(Error::unexpected_message)?;
+            String::from_utf8(body.buffer()[range].to_vec())
+                .map_err(|_| Error::unexpected_message())?
+        }
+        _ => return Err(Error::unexpected_message()),
+    };
+
+    match responses.next().await? {
+        Message::CommandComplete(_) => Ok(setting),
+        _ => Err(Error::unexpected_message()),
+    }
+}
+
 async fn start(client: &InnerClient, buf: Bytes) -> Result<Responses, Error> {
     let mut responses = client.send(RequestMessages::Single(FrontendMessage::Raw(buf)))?;
 
@@ -219,12 +366,41 @@ where
     })
 }
 
+fn encode_text<P, I>(client: &InnerClient, statement: &Statement, params: I) -> Result<Bytes, Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    client.with_buf(|buf| {
+        encode_bind_format(statement, params, "", 0, buf)?;
+        frontend::execute("", 0, buf).map_err(Error::encode)?;
+        frontend::sync(buf);
+        Ok(buf.split().freeze())
+    })
+}
+
 pub fn encode_bind<P, I>(
     statement: &Statement,
     params: I,
     portal: &str,
     buf: &mut BytesMut,
 ) -> Result<(), Error>
+where
+    P: BorrowToSql,
+    I: IntoIterator<Item = P>,
+    I::IntoIter: ExactSizeIterator,
+{
+    encode_bind_format(statement, params, portal, 1, buf)
+}
+
+fn encode_bind_format<P, I>(
+    statement: &Statement,
+    params: I,
+    portal: &str,
+    result_format: i16,
+    buf: &mut BytesMut,
+) -> Result<(), Error>
 where
     P: BorrowToSql,
     I: IntoIterator<Item = P>,
@@ -239,6 +415,7 @@ where
         statement.name(),
         params.zip(statement.params().iter().cloned()),
         portal,
+        result_format,
         buf,
     )
 }
@@ -247,6 +424,7 @@ fn encode_bind_raw<P, I>(
     statement_name: &str,
     params: I,
     portal: &str,
+    result_format: i16,
     buf: &mut BytesMut,
 ) -> Result<(), Error>
 where
@@ -273,7 +451,7 @@ where
                 Err(e)
             }
         },
-        Some(1),
+        Some(result_format),
         buf,
     );
     match r {
@@ -289,6 +467,7 @@ pin_project! {
         statement: Statement,
         responses: Responses,
         rows_affected: Option<u64>,
+        hook: Option<HookGuard>,
         #[pin]
         _p: PhantomPinned,
     }
@@ -299,23 +478,148 @@ impl Stream for RowStream {
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.project();
-        loop {
+        let result = ready!(poll_next_row(
+            this.statement,
+            this.responses,
+            this.rows_affected,
+            cx
+        ));
+        if let Some(hook) = this.hook {
+            match &result {
+                Some(Ok(_)) => hook.add_row(),
+                Some(Err(e)) => this.hook.take().unwrap().fail(e),
+                None => this.hook.take().unwrap().finish(*this.rows_affected),
+            }
+        }
+        Poll::Ready(result)
+    }
+}
+
+fn poll_next_row(
+    statement: &Statement,
+    responses: &mut Responses,
+    rows_affected: &mut Option<u64>,
+    cx: &mut Context<'_>,
+) -> Poll<Option<Result<Row, Error>>> {
+    loop {
+        match ready!(responses.poll_next(cx)?) {
+            Message::DataRow(body) => {
+                return Poll::Ready(Some(Ok(Row::new(statement.clone(), body)?)))
+            }
+            Message::CommandComplete(body) => {
+                *rows_affected = Some(extract_row_affected(&body)?);
+            }
+            Message::EmptyQueryResponse | Message::PortalSuspended => {}
+            Message::ReadyForQuery(_) => return Poll::Ready(None),
+            _ => return Poll::Ready(Some(Err(Error::unexpected_message()))),
+        }
+    }
+}
+
+impl RowStream {
+    pub(crate) fn set_hook(&mut self, hook: HookGuard) {
+        self.hook = Some(hook);
+    }
+
+    /// Returns the number of rows affected by the query.
+    ///
+    /// This function will return `None` until the stream has been exhausted.
+    pub fn rows_affected(&self) -> Option<u64> {
+        self.rows_affected
+    }
+}
+
+pin_project! {
+    /// A stream of table rows, each deserialized into a value via its [`FromRow`](crate::types::FromRow)
+    /// implementation.
+    pub struct RowStreamAs<R> {
+        #[pin]
+        rows: RowStream,
+        _r: PhantomData<fn() -> R>,
+    }
+}
+
+impl<R> RowStreamAs<R> {
+    pub(crate) fn new(rows: RowStream) -> RowStreamAs<R> {
+        RowStreamAs {
+            rows,
+            _r: PhantomData,
+        }
+    }
+
+    /// Returns the number of rows affected by the query.
+    ///
+    /// This function will return `None` until the stream has been exhausted.
+    pub fn rows_affected(&self) -> Option<u64> {
+        self.rows.rows_affected()
+    }
+}
+
+impl<R> Stream for RowStreamAs<R>
+where
+    R: FromRowOwned,
+{
+    type Item = Result<R, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let row = ready!(self.project().rows.poll_next(cx));
+        Poll::Ready(row.map(|row| row.and_then(|row| R::from_row(&row))))
+    }
+}
+
+pin_project! {
+    /// A stream of table rows in text format.
+    ///
+    /// Rows are returned as [`SimpleQueryRow`]s, whose values are the server's text representations.
+    pub struct TextRowStream {
+        columns: Arc<[SimpleColumn]>,
+        responses: Responses,
+        rows_affected: Option<u64>,
+        hook: Option<HookGuard>,
+        #[pin]
+        _p: PhantomPinned,
+    }
+}
+
+impl Stream for TextRowStream {
+    type Item = Result<SimpleQueryRow, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let result = loop {
             match ready!(this.responses.poll_next(cx)?) {
                 Message::DataRow(body) => {
-                    return Poll::Ready(Some(Ok(Row::new(this.statement.clone(), body)?)))
+                    break Some(SimpleQueryRow::new(this.columns.clone(), body));
                 }
                 Message::CommandComplete(body) => {
                     *this.rows_affected = Some(extract_row_affected(&body)?);
                 }
                 Message::EmptyQueryResponse | Message::PortalSuspended => {}
-                Message::ReadyForQuery(_) => return Poll::Ready(None),
-                _ => return Poll::Ready(Some(Err(Error::unexpected_message()))),
+                Message::ReadyForQuery(_) => break None,
+                _ => break Some(Err(Error::unexpected_message())),
+            }
+        };
+        if let Some(hook) = this.hook {
+            match &result {
+                Some(Ok(_)) => hook.add_row(),
+                Some(Err(e)) => this.hook.take().unwrap().fail(e),
+                None => this.hook.take().unwrap().finish(*this.rows_affected),
             }
         }
+        Poll::Ready(result)
     }
 }
 
-impl RowStream {
+impl TextRowStream {
+    pub(crate) fn set_hook(&mut self, hook: HookGuard) {
+        self.hook = Some(hook);
+    }
+
+    /// Returns information about the columns of the rows in the stream.
+    pub fn columns(&self) -> &[SimpleColumn] {
+        &self.columns
+    }
+
     /// Returns the number of rows affected by the query.
     ///
     /// This function will return `None` until the stream has been exhausted.
@@ -0,0 +1,103 @@
+use crate::types::ToSql;
+use std::fmt;
+use std::fmt::Write;
+
+/// A builder for SQL queries assembled at runtime.
+///
+/// Fragments of SQL are appended with `push`, and parameter values with `push_bind`, which appends the matching `$n`
+/// placeholder. Values are never interpolated into the query text itself.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tokio_postgres::{Client, Error, QueryBuilder, Row};
+///
+/// async fn search(client: &Client, name: Option<&str>, ids: &[i32]) -> Result<Vec<Row>, Error> {
+///     let mut builder = QueryBuilder::new("SELECT id, name FROM people WHERE true");
+///     if let Some(name) = name {
+///         builder.push(" AND name = ").push_bind(name.to_string());
+///     }
+///     if !ids.is_empty() {
+///         builder.push(" AND id IN (").push_bind_list(ids.iter().copied()).push(")");
+///     }
+///
+///     client.query(builder.sql(), &builder.params()).await
+/// }
+/// ```
+pub struct QueryBuilder<'a> {
+    sql: String,
+    params: Vec<Box<dyn ToSql + Sync + Send + 'a>>,
+}
+
+impl fmt::Debug for QueryBuilder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueryBuilder")
+            .field("sql", &self.sql)
+            .field("params", &self.params)
+            .finish()
+    }
+}
+
+impl<'a> QueryBuilder<'a> {
+    /// Creates a new builder starting with the provided SQL.
+    pub fn new(sql: &str) -> QueryBuilder<'a> {
+        QueryBuilder {
+            sql: sql.to_string(),
+            params: vec![],
+        }
+    }
+
+    /// Appends a fragment of SQL to the query.
+    ///
+    /// The fragment is included verbatim, so it must not contain untrusted input.
+    pub fn push(&mut self, sql: &str) -> &mut QueryBuilder<'a> {
+        self.sql.push_str(sql);
+        self
+    }
+
+    /// Appends a parameter placeholder to the query, binding the provided value to it.
+    pub fn push_bind<T>(&mut self, value: T) -> &mut QueryBuilder<'a>
+    where
+        T: ToSql + Sync + Send + 'a,
+    {
+        self.params.push(Box::new(value));
+        write!(self.sql, "${}", self.params.len()).unwrap();
+        self
+    }
+
+    /// Appends a comma-separated list of parameter placeholders to the query, binding each of the provided values to
+    /// one of them.
+    ///
+    /// Nothing is appended if `values` is empty. Note that an empty `IN ()` list is not valid SQL.
+    pub fn push_bind_list<I>(&mut self, values: I) -> &mut QueryBuilder<'a>
+    where
+        I: IntoIterator,
+        I::Item: ToSql + Sync + Send + 'a,
+    {
+        for (i, value) in values.into_iter().enumerate() {
+            if i > 0 {
+                self.sql.push_str(", ");
+            }
+            self.push_bind(value);
+        }
+        self
+    }
+
+    /// Returns the SQL of the query.
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Returns the parameters bound so far, in placeholder order.
+    pub fn params(&self) -> Vec<&(dyn ToSql + Sync)> {
+        self.params
+            .iter()
+            .map(|p| &**p as &(dyn ToSql + Sync))
+            .collect()
+    }
+
+    /// Returns the number of parameters bound so far.
+    pub fn param_count(&self) -> usize {
+        self.params.len()
+    }
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+/// Per-statement settings for [`Client::query_with_options`](crate::Client::query_with_options) and
+/// [`Client::execute_with_options`](crate::Client::execute_with_options).
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn async_main(client: &tokio_postgres::Client) -> Result<(), tokio_postgres::Error> {
+/// use std::time::Duration;
+/// use tokio_postgres::QueryOptions;
+///
+/// let mut options = QueryOptions::new();
+/// options.statement_timeout(Duration::from_secs(5));
+///
+/// let rows = client
+///     .query_with_options("SELECT * FROM reports", &[], &options)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryOptions {
+    statement_timeout: Option<Duration>,
+}
+
+impl QueryOptions {
+    /// Creates a new set of options, none of which are set.
+    pub fn new() -> QueryOptions {
+        QueryOptions::default()
+    }
+
+    /// Sets a server-side timeout for the statement.
+    ///
+    /// The server's `statement_timeout` parameter is set for the statement alone, and restored to its previous value
+    /// once it completes. If the statement runs for longer than the timeout, the server cancels it and it fails with an
+    /// error whose code is [`SqlState::QUERY_CANCELED`](crate::error::SqlState::QUERY_CANCELED). Unlike
+    /// [`Client::query_with_timeout`](crate::Client::query_with_timeout), this does not require a separate connection
+    /// to cancel the statement.
+    ///
+    /// The timeout has a resolution of one millisecond, and is rounded up to the nearest millisecond.
+    pub fn statement_timeout(&mut self, statement_timeout: Duration) -> &mut QueryOptions {
+        self.statement_timeout = Some(statement_timeout);
+        self
+    }
+
+    /// Gets the statement timeout, if one has been set with the `statement_timeout` method.
+    pub fn get_statement_timeout(&self) -> Option<Duration> {
+        self.statement_timeout
+    }
+}
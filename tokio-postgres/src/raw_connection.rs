@@ -0,0 +1,117 @@
+use crate::config::{SslMode, SslNegotiation};
+use crate::error::DbError;
+use crate::maybe_tls_stream::MaybeTlsStream;
+use crate::CancelToken;
+use bytes::BytesMut;
+use std::collections::HashMap;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::{cmp, fmt};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// A connection to the server which has completed startup and authentication, returned by
+/// [`Config::connect_raw_stream`](crate::Config::connect_raw_stream).
+///
+/// Unlike a [`Client`](crate::Client), this leaves the frontend/backend protocol to the user. Reads and writes go
+/// straight to the underlying stream (which may be wrapped in TLS), with the server ready for its first query. This
+/// makes it a building block for proxies and connection poolers which relay messages between their own clients and
+/// the server.
+pub struct RawConnection<S, T> {
+    pub(crate) stream: MaybeTlsStream<S, T>,
+    pub(crate) buf: BytesMut,
+    pub(crate) process_id: i32,
+    pub(crate) secret_key: i32,
+    pub(crate) parameters: HashMap<String, String>,
+    pub(crate) notices: Vec<DbError>,
+    pub(crate) ssl_mode: SslMode,
+    pub(crate) ssl_negotiation: SslNegotiation,
+}
+
+impl<S, T> RawConnection<S, T> {
+    /// Returns the process ID of the backend serving the connection.
+    pub fn process_id(&self) -> i32 {
+        self.process_id
+    }
+
+    /// Returns the secret key used to authenticate cancellation requests for the connection.
+    pub fn secret_key(&self) -> i32 {
+        self.secret_key
+    }
+
+    /// Returns the runtime parameters reported by the server during startup.
+    pub fn parameters(&self) -> &HashMap<String, String> {
+        &self.parameters
+    }
+
+    /// Returns the notices sent by the server during startup.
+    pub fn notices(&self) -> &[DbError] {
+        &self.notices
+    }
+
+    /// Returns a token which can be used to cancel queries running on the connection.
+    ///
+    /// The token does not know the address of the server, so `cancel_query_raw` must be used to cancel queries with
+    /// it.
+    pub fn cancel_token(&self) -> CancelToken {
+        CancelToken::from_parts(
+            self.process_id,
+            self.secret_key,
+            self.ssl_mode,
+            self.ssl_negotiation,
+        )
+    }
+}
+
+impl<S, T> fmt::Debug for RawConnection<S, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawConnection")
+            .field("process_id", &self.process_id)
+            .field("parameters", &self.parameters)
+            .field("notices", &self.notices)
+            .finish()
+    }
+}
+
+impl<S, T> AsyncRead for RawConnection<S, T>
+where
+    S: AsyncRead + Unpin,
+    T: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        // hand out anything read from the stream during startup before reading from it again
+        if !self.buf.is_empty() {
+            let len = cmp::min(self.buf.len(), buf.remaining());
+            buf.put_slice(&self.buf.split_to(len));
+            return Poll::Ready(Ok(()));
+        }
+
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl<S, T> AsyncWrite for RawConnection<S, T>
+where
+    S: AsyncWrite + Unpin,
+    T: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
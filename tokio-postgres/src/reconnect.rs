@@ -0,0 +1,451 @@
+//! A client which automatically reconnects.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use tokio_postgres::reconnect::{ReconnectEvent, ReconnectingClient};
+//! use tokio_postgres::NoTls;
+//!
+//! # async fn example() -> Result<(), tokio_postgres::Error> {
+//! let config = "host=localhost user=postgres".parse()?;
+//! let client = ReconnectingClient::builder(config, NoTls)
+//!     .max_backoff(Duration::from_secs(30))
+//!     .on_reconnect(|event| {
+//!         if let ReconnectEvent::Failed { attempt, error } = event {
+//!             eprintln!("reconnect attempt {} failed: {}", attempt, error);
+//!         }
+//!     })
+//!     .connect()
+//!     .await?;
+//!
+//! let rows = client.query("SELECT name FROM users WHERE id = $1", &[&1i32]).await?;
+//! # Ok(())
+//! # }
+//! ```
+use crate::rt;
+use crate::tls::{MakeTlsConnect, TlsConnect};
+use crate::types::ToSql;
+use crate::{Client, Config, Error, Row, Socket, Statement};
+use log::info;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+type Hook = Box<dyn Fn(ReconnectEvent<'_>) + Sync + Send>;
+
+/// An event reported to the hook registered with [`ReconnectingClientBuilder::on_reconnect`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ReconnectEvent<'a> {
+    /// The connection to the server was lost and a reconnect is starting.
+    Disconnected,
+    /// An attempt to reconnect failed.
+    Failed {
+        /// The number of the attempt, starting at 1.
+        attempt: u32,
+        /// The error encountered.
+        error: &'a Error,
+    },
+    /// The connection was re-established.
+    Reconnected {
+        /// The number of attempts needed.
+        attempts: u32,
+    },
+}
+
+/// A builder for [`ReconnectingClient`]s.
+pub struct ReconnectingClientBuilder<T> {
+    config: Config,
+    tls: T,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_attempts: u32,
+    statement_cache_size: usize,
+    hook: Option<Hook>,
+}
+
+impl<T> ReconnectingClientBuilder<T> {
+    /// Sets the delay after the first failed reconnect attempt.
+    ///
+    /// The delay doubles after each subsequent failure, up to the maximum backoff. Defaults to 100 milliseconds.
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Sets the maximum delay between reconnect attempts.
+    ///
+    /// Defaults to 10 seconds.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the number of reconnect attempts made before giving up and returning the last error.
+    ///
+    /// The next request will start a fresh series of attempts. Defaults to 10.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_attempts` is 0.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        assert!(max_attempts > 0, "max_attempts must be positive");
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the maximum number of prepared statements cached by the client.
+    ///
+    /// Once the cache is full, the least recently used statement is evicted to make room for a new one. A size of 0
+    /// disables the cache, so statements are prepared every time they are executed. Defaults to 100.
+    pub fn statement_cache_size(mut self, statement_cache_size: usize) -> Self {
+        self.statement_cache_size = statement_cache_size;
+        self
+    }
+
+    /// Registers a function to be called as the client reconnects.
+    pub fn on_reconnect<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(ReconnectEvent<'_>) + Sync + Send + 'static,
+    {
+        self.hook = Some(Box::new(hook));
+        self
+    }
+}
+
+impl<T> ReconnectingClientBuilder<T>
+where
+    T: MakeTlsConnect<Socket> + Clone + 'static + Send + Sync,
+    T::TlsConnect: Send,
+    T::Stream: Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    /// Opens the initial connection.
+    pub async fn connect(self) -> Result<ReconnectingClient<T>, Error> {
+        let client = connect(&self.config, &self.tls).await?;
+
+        Ok(ReconnectingClient {
+            inner: Arc::new(Inner {
+                config: self.config,
+                tls: self.tls,
+                initial_backoff: self.initial_backoff,
+                max_backoff: self.max_backoff,
+                max_attempts: self.max_attempts,
+                statement_cache_size: self.statement_cache_size,
+                hook: self.hook,
+                state: Mutex::new(State {
+                    client: Arc::new(client),
+                    statements: StatementCache::new(self.statement_cache_size),
+                }),
+                reconnect: tokio::sync::Mutex::new(()),
+            }),
+        })
+    }
+}
+
+async fn connect<T>(config: &Config, tls: &T) -> Result<Client, Error>
+where
+    T: MakeTlsConnect<Socket> + Clone + 'static + Send + Sync,
+    T::TlsConnect: Send,
+    T::Stream: Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    let (client, connection) = config.connect(tls.clone()).await?;
+    rt::spawn(config.runtime.as_ref(), async move {
+        if let Err(e) = connection.await {
+            info!("connection closed with error: {}", e);
+        }
+    });
+    Ok(client)
+}
+
+struct State {
+    client: Arc<Client>,
+    statements: StatementCache,
+}
+
+// A least recently used cache of prepared statements, keyed by query string.
+struct StatementCache {
+    capacity: usize,
+    tick: u64,
+    statements: HashMap<String, (Statement, u64)>,
+}
+
+impl StatementCache {
+    fn new(capacity: usize) -> StatementCache {
+        StatementCache {
+            capacity,
+            tick: 0,
+            statements: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, query: &str) -> Option<Statement> {
+        let (statement, last_used) = self.statements.get_mut(query)?;
+        self.tick += 1;
+        *last_used = self.tick;
+        Some(statement.clone())
+    }
+
+    fn insert(&mut self, query: String, statement: Statement) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.tick += 1;
+        self.statements.insert(query, (statement, self.tick));
+        if self.statements.len() > self.capacity {
+            let oldest = self
+                .statements
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(query, _)| query.clone());
+            if let Some(oldest) = oldest {
+                self.statements.remove(&oldest);
+            }
+        }
+    }
+
+    // Returns the cached queries, from least to most recently used.
+    fn queries(&self) -> Vec<String> {
+        let mut queries = self
+            .statements
+            .iter()
+            .map(|(query, (_, last_used))| (*last_used, query.clone()))
+            .collect::<Vec<_>>();
+        queries.sort_unstable();
+        queries.into_iter().map(|(_, query)| query).collect()
+    }
+
+    fn clear(&mut self) {
+        self.statements.clear();
+    }
+}
+
+struct Inner<T> {
+    config: Config,
+    tls: T,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_attempts: u32,
+    statement_cache_size: usize,
+    hook: Option<Hook>,
+    state: Mutex<State>,
+    reconnect: tokio::sync::Mutex<()>,
+}
+
+/// A client which transparently re-establishes its connection to the server when it is lost.
+///
+/// Statements executed through this type are prepared once and cached by their query string, up to the
+/// [size](ReconnectingClientBuilder::statement_cache_size) of the cache. When the client reconnects, the cached
+/// statements are prepared again on the new connection.
+///
+/// The connection is checked before each request. Requests which fail because the connection was lost while they were
+/// in flight are *not* retried, since the server may already have executed them; the error is returned and the next
+/// request reconnects.
+///
+/// The client is reference counted; clones share the same connection.
+pub struct ReconnectingClient<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Clone for ReconnectingClient<T> {
+    fn clone(&self) -> ReconnectingClient<T> {
+        ReconnectingClient {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for ReconnectingClient<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReconnectingClient")
+            .field("config", &self.inner.config)
+            .finish()
+    }
+}
+
+impl<T> ReconnectingClient<T> {
+    /// Returns a builder for a client which connects with `config` and `tls`.
+    pub fn builder(config: Config, tls: T) -> ReconnectingClientBuilder<T> {
+        ReconnectingClientBuilder {
+            config,
+            tls,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            max_attempts: 10,
+            statement_cache_size: 100,
+            hook: None,
+        }
+    }
+
+    fn emit(&self, event: ReconnectEvent<'_>) {
+        if let Some(hook) = &self.inner.hook {
+            hook(event);
+        }
+    }
+}
+
+impl<T> ReconnectingClient<T>
+where
+    T: MakeTlsConnect<Socket> + Clone + 'static + Send + Sync,
+    T::TlsConnect: Send,
+    T::Stream: Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    /// Returns the underlying client, reconnecting first if the connection has been lost.
+    ///
+    /// Statements prepared directly on the returned client are not re-prepared after a reconnect.
+    pub async fn client(&self) -> Result<Arc<Client>, Error> {
+        let client = self.inner.state.lock().client.clone();
+        if !client.is_closed() {
+            return Ok(client);
+        }
+
+        self.reconnect(&client).await
+    }
+
+    /// Removes all statements from the cache.
+    ///
+    /// They are prepared again when next executed, and are not prepared on the new connection after a reconnect.
+    pub fn clear_statement_cache(&self) {
+        self.inner.state.lock().statements.clear();
+    }
+
+    /// Like [`Client::query`], but caches the prepared statement.
+    pub async fn query(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        let (client, statement) = self.prepare(query).await?;
+        client.query(&statement, params).await
+    }
+
+    /// Like [`Client::query_one`], but caches the prepared statement.
+    pub async fn query_one(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Row, Error> {
+        let (client, statement) = self.prepare(query).await?;
+        client.query_one(&statement, params).await
+    }
+
+    /// Like [`Client::query_opt`], but caches the prepared statement.
+    pub async fn query_opt(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error> {
+        let (client, statement) = self.prepare(query).await?;
+        client.query_opt(&statement, params).await
+    }
+
+    /// Like [`Client::execute`], but caches the prepared statement.
+    pub async fn execute(&self, query: &str, params: &[&(dyn ToSql + Sync)]) -> Result<u64, Error> {
+        let (client, statement) = self.prepare(query).await?;
+        client.execute(&statement, params).await
+    }
+
+    /// Like [`Client::batch_execute`].
+    pub async fn batch_execute(&self, query: &str) -> Result<(), Error> {
+        self.client().await?.batch_execute(query).await
+    }
+
+    async fn prepare(&self, query: &str) -> Result<(Arc<Client>, Statement), Error> {
+        let client = self.client().await?;
+
+        let cached = {
+            let mut state = self.inner.state.lock();
+            if Arc::ptr_eq(&state.client, &client) {
+                state.statements.get(query)
+            } else {
+                None
+            }
+        };
+        if let Some(statement) = cached {
+            return Ok((client, statement));
+        }
+
+        let statement = client.prepare(query).await?;
+        let mut state = self.inner.state.lock();
+        if Arc::ptr_eq(&state.client, &client) {
+            state
+                .statements
+                .insert(query.to_string(), statement.clone());
+        }
+
+        Ok((client, statement))
+    }
+
+    async fn reconnect(&self, stale: &Arc<Client>) -> Result<Arc<Client>, Error> {
+        let _guard = self.inner.reconnect.lock().await;
+
+        let queries = {
+            let state = self.inner.state.lock();
+            if !Arc::ptr_eq(&state.client, stale) && !state.client.is_closed() {
+                return Ok(state.client.clone());
+            }
+            state.statements.queries()
+        };
+
+        self.emit(ReconnectEvent::Disconnected);
+
+        let mut backoff = self.inner.initial_backoff;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            match self.connect_and_prepare(&queries).await {
+                Ok((client, statements)) => {
+                    let client = Arc::new(client);
+                    *self.inner.state.lock() = State {
+                        client: client.clone(),
+                        statements,
+                    };
+                    self.emit(ReconnectEvent::Reconnected { attempts: attempt });
+                    return Ok(client);
+                }
+                Err(error) => {
+                    self.emit(ReconnectEvent::Failed {
+                        attempt,
+                        error: &error,
+                    });
+                    if attempt >= self.inner.max_attempts {
+                        return Err(error);
+                    }
+                }
+            }
+
+            rt::sleep(self.inner.config.runtime.as_ref(), backoff).await;
+            backoff = (backoff * 2).min(self.inner.max_backoff);
+        }
+    }
+
+    async fn connect_and_prepare(
+        &self,
+        queries: &[String],
+    ) -> Result<(Client, StatementCache), Error> {
+        let client = connect(&self.inner.config, &self.inner.tls).await?;
+
+        // queries are ordered from least to most recently used, which preserves their order in the new cache
+        let mut statements = StatementCache::new(self.inner.statement_cache_size);
+        for query in queries {
+            match client.prepare(query).await {
+                Ok(statement) => {
+                    statements.insert(query.clone(), statement);
+                }
+                // the statement may no longer be valid (e.g. a referenced table was dropped), in which case it'll be
+                // prepared again on next use and report the error then.
+                Err(_) if !client.is_closed() => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok((client, statements))
+    }
+}
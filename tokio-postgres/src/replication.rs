@@ -0,0 +1,508 @@
+//! Utilities for working with the PostgreSQL replication copy both format.
+//!
+//! Replication connections are opened by setting a [`ReplicationMode`] on the connection's configuration. Once the
+//! client has issued `START_REPLICATION` with [`Client::copy_both_simple`], the resulting duplex stream can be wrapped
+//! in a [`ReplicationStream`] to exchange replication messages with the server, or a [`LogicalReplicationStream`] to
+//! additionally decode the changes produced by the built-in `pgoutput` plugin.
+//!
+//! The server does not advance a replication slot until the client reports its progress, so clients should
+//! periodically send standby status updates, and must respond promptly to keepalive messages which request a reply.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use futures_util::{pin_mut, StreamExt};
+//! use postgres_protocol::message::replication::{LogicalReplicationMessage, ReplicationMessage};
+//! use std::time::SystemTime;
+//! use tokio_postgres::config::{Config, ReplicationMode};
+//! use tokio_postgres::replication::LogicalReplicationStream;
+//! use tokio_postgres::types::PgLsn;
+//! use tokio_postgres::{Error, NoTls};
+//!
+//! # #[cfg(feature = "runtime")]
+//! async fn replicate() -> Result<(), Error> {
+//!     let mut config = "host=localhost user=postgres dbname=postgres".parse::<Config>()?;
+//!     config.replication_mode(ReplicationMode::Logical);
+//!     let (client, connection) = config.connect(NoTls).await?;
+//!     tokio::spawn(connection);
+//!
+//!     client
+//!         .simple_query("CREATE_REPLICATION_SLOT my_slot TEMPORARY LOGICAL pgoutput")
+//!         .await?;
+//!     let duplex = client
+//!         .copy_both_simple(
+//!             "START_REPLICATION SLOT my_slot LOGICAL 0/0 \
+//!              (proto_version '1', publication_names 'my_publication')",
+//!         )
+//!         .await?;
+//!
+//!     let stream = LogicalReplicationStream::new(duplex);
+//!     pin_mut!(stream);
+//!     while let Some(message) = stream.next().await {
+//!         match message? {
+//!             ReplicationMessage::XLogData(body) => {
+//!                 if let LogicalReplicationMessage::Commit(commit) = body.data() {
+//!                     let lsn = PgLsn::from(commit.end_lsn());
+//!                     stream
+//!                         .as_mut()
+//!                         .standby_status_update(lsn, lsn, lsn, SystemTime::now(), false)
+//!                         .await?;
+//!                 }
+//!             }
+//!             ReplicationMessage::PrimaryKeepAlive(body) => {
+//!                 if body.reply() == 1 {
+//!                     let lsn = PgLsn::from(body.wal_end());
+//!                     stream
+//!                         .as_mut()
+//!                         .standby_status_update(lsn, lsn, lsn, SystemTime::now(), false)
+//!                         .await?;
+//!                 }
+//!             }
+//!             _ => {}
+//!         }
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Physical replication connections additionally support [`Client::identify_system`],
+//! [`Client::timeline_history`], [`Client::start_physical_replication`], and [`Client::base_backup`], which can be
+//! used to build backup and standby bootstrapping tools.
+//!
+//! [`ReplicationMode`]: crate::config::ReplicationMode
+//! [`Client::copy_both_simple`]: crate::Client::copy_both_simple
+//! [`Client::identify_system`]: crate::Client::identify_system
+//! [`Client::timeline_history`]: crate::Client::timeline_history
+//! [`Client::start_physical_replication`]: crate::Client::start_physical_replication
+//! [`Client::base_backup`]: crate::Client::base_backup
+
+use crate::codec::FrontendMessage;
+use crate::connection::RequestMessages;
+use crate::types::PgLsn;
+use crate::{simple_query, Client, CopyBothDuplex, Error, SimpleQueryMessage, SimpleQueryRow};
+use bytes::{Bytes, BytesMut};
+use fallible_iterator::FallibleIterator;
+use futures_util::{ready, SinkExt, Stream};
+use log::debug;
+use pin_project_lite::pin_project;
+use postgres_protocol::escape::escape_identifier;
+use postgres_protocol::message::backend::{DataRowBody, Message};
+use postgres_protocol::message::replication::{
+    self, LogicalReplicationMessage, ReplicationMessage,
+};
+use std::io;
+use std::pin::Pin;
+use std::str;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+// Seconds from the Unix epoch to the Postgres epoch of 2000-01-01.
+const PG_EPOCH_OFFSET_SECS: u64 = 946_684_800;
+
+fn pg_timestamp(time: SystemTime) -> i64 {
+    let epoch = UNIX_EPOCH + Duration::from_secs(PG_EPOCH_OFFSET_SECS);
+    match time.duration_since(epoch) {
+        Ok(d) => d.as_micros() as i64,
+        Err(e) => -(e.duration().as_micros() as i64),
+    }
+}
+
+pin_project! {
+    /// A stream of streaming replication messages.
+    ///
+    /// The data of `XLogData` messages is returned undecoded.
+    pub struct ReplicationStream {
+        #[pin]
+        stream: CopyBothDuplex<Bytes>,
+    }
+}
+
+impl ReplicationStream {
+    /// Creates a new `ReplicationStream` from a duplex returned by `Client::copy_both_simple`.
+    pub fn new(stream: CopyBothDuplex<Bytes>) -> ReplicationStream {
+        ReplicationStream { stream }
+    }
+
+    /// Sends a standby status update to the server, reporting the client's progress.
+    ///
+    /// The server may discard WAL up to the flushed location. If `reply` is set, the server will immediately respond
+    /// with a keepalive message.
+    pub async fn standby_status_update(
+        self: Pin<&mut Self>,
+        write_lsn: PgLsn,
+        flush_lsn: PgLsn,
+        apply_lsn: PgLsn,
+        ts: SystemTime,
+        reply: bool,
+    ) -> Result<(), Error> {
+        let mut buf = BytesMut::new();
+        replication::standby_status_update(
+            write_lsn.into(),
+            flush_lsn.into(),
+            apply_lsn.into(),
+            pg_timestamp(ts),
+            reply as u8,
+            &mut buf,
+        );
+        self.project().stream.send(buf.freeze()).await
+    }
+
+    /// Sends a hot standby feedback message to the server.
+    ///
+    /// A transaction ID of 0 indicates that the corresponding xmin is not being reported.
+    pub async fn hot_standby_feedback(
+        self: Pin<&mut Self>,
+        ts: SystemTime,
+        global_xmin: u32,
+        global_xmin_epoch: u32,
+        catalog_xmin: u32,
+        catalog_xmin_epoch: u32,
+    ) -> Result<(), Error> {
+        let mut buf = BytesMut::new();
+        replication::hot_standby_feedback(
+            pg_timestamp(ts),
+            global_xmin,
+            global_xmin_epoch,
+            catalog_xmin,
+            catalog_xmin_epoch,
+            &mut buf,
+        );
+        self.project().stream.send(buf.freeze()).await
+    }
+
+    /// Finishes the client's side of the replication stream, waiting for the server to complete the command.
+    pub async fn close(self: Pin<&mut Self>) -> Result<(), Error> {
+        self.project().stream.close().await
+    }
+}
+
+impl Stream for ReplicationStream {
+    type Item = Result<ReplicationMessage<Bytes>, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match ready!(self.project().stream.poll_next(cx)) {
+            Some(Ok(buf)) => {
+                Poll::Ready(Some(ReplicationMessage::parse(&buf).map_err(Error::parse)))
+            }
+            Some(Err(e)) => Poll::Ready(Some(Err(e))),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+pin_project! {
+    /// A stream of logical replication messages produced by the `pgoutput` plugin.
+    ///
+    /// The data of `XLogData` messages is decoded into `LogicalReplicationMessage`s.
+    pub struct LogicalReplicationStream {
+        #[pin]
+        stream: ReplicationStream,
+    }
+}
+
+impl LogicalReplicationStream {
+    /// Creates a new `LogicalReplicationStream` from a duplex returned by `Client::copy_both_simple`.
+    pub fn new(stream: CopyBothDuplex<Bytes>) -> LogicalReplicationStream {
+        LogicalReplicationStream {
+            stream: ReplicationStream::new(stream),
+        }
+    }
+
+    /// Like `ReplicationStream::standby_status_update`.
+    pub async fn standby_status_update(
+        self: Pin<&mut Self>,
+        write_lsn: PgLsn,
+        flush_lsn: PgLsn,
+        apply_lsn: PgLsn,
+        ts: SystemTime,
+        reply: bool,
+    ) -> Result<(), Error> {
+        self.project()
+            .stream
+            .standby_status_update(write_lsn, flush_lsn, apply_lsn, ts, reply)
+            .await
+    }
+
+    /// Like `ReplicationStream::hot_standby_feedback`.
+    pub async fn hot_standby_feedback(
+        self: Pin<&mut Self>,
+        ts: SystemTime,
+        global_xmin: u32,
+        global_xmin_epoch: u32,
+        catalog_xmin: u32,
+        catalog_xmin_epoch: u32,
+    ) -> Result<(), Error> {
+        self.project()
+            .stream
+            .hot_standby_feedback(
+                ts,
+                global_xmin,
+                global_xmin_epoch,
+                catalog_xmin,
+                catalog_xmin_epoch,
+            )
+            .await
+    }
+
+    /// Like `ReplicationStream::close`.
+    pub async fn close(self: Pin<&mut Self>) -> Result<(), Error> {
+        self.project().stream.close().await
+    }
+}
+
+impl Stream for LogicalReplicationStream {
+    type Item = Result<ReplicationMessage<LogicalReplicationMessage>, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let message = match ready!(self.project().stream.poll_next(cx)?) {
+            Some(message) => message,
+            None => return Poll::Ready(None),
+        };
+
+        let message = match message {
+            ReplicationMessage::XLogData(body) => body
+                .map_data(|buf| LogicalReplicationMessage::parse(&buf))
+                .map(ReplicationMessage::XLogData)
+                .map_err(Error::parse),
+            ReplicationMessage::PrimaryKeepAlive(body) => {
+                Ok(ReplicationMessage::PrimaryKeepAlive(body))
+            }
+            _ => Err(Error::unexpected_message()),
+        };
+        Poll::Ready(Some(message))
+    }
+}
+
+/// The server's response to an `IDENTIFY_SYSTEM` command.
+#[derive(Debug, Clone)]
+pub struct IdentifySystem {
+    systemid: String,
+    timeline: u32,
+    xlogpos: PgLsn,
+    dbname: Option<String>,
+}
+
+impl IdentifySystem {
+    /// Returns the unique system identifier of the cluster.
+    pub fn systemid(&self) -> &str {
+        &self.systemid
+    }
+
+    /// Returns the current timeline ID.
+    pub fn timeline(&self) -> u32 {
+        self.timeline
+    }
+
+    /// Returns the current WAL flush location.
+    pub fn xlogpos(&self) -> PgLsn {
+        self.xlogpos
+    }
+
+    /// Returns the database connected to, or `None` for a physical replication connection.
+    pub fn dbname(&self) -> Option<&str> {
+        self.dbname.as_deref()
+    }
+}
+
+/// A timeline history file returned by a `TIMELINE_HISTORY` command.
+#[derive(Debug, Clone)]
+pub struct TimelineHistory {
+    filename: String,
+    content: Vec<u8>,
+}
+
+impl TimelineHistory {
+    /// Returns the file name of the timeline history file, such as `00000002.history`.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Returns the contents of the timeline history file.
+    pub fn content(&self) -> &[u8] {
+        &self.content
+    }
+}
+
+/// The WAL locations reported by the server for a base backup.
+#[derive(Debug, Clone)]
+pub struct BaseBackup {
+    start_lsn: PgLsn,
+    start_timeline: u32,
+    end_lsn: PgLsn,
+    end_timeline: u32,
+}
+
+impl BaseBackup {
+    /// Returns the WAL location at which the backup started.
+    pub fn start_lsn(&self) -> PgLsn {
+        self.start_lsn
+    }
+
+    /// Returns the timeline on which the backup started.
+    pub fn start_timeline(&self) -> u32 {
+        self.start_timeline
+    }
+
+    /// Returns the WAL location at which the backup ended.
+    ///
+    /// A standby restored from the backup must replay WAL up to this location before it is consistent.
+    pub fn end_lsn(&self) -> PgLsn {
+        self.end_lsn
+    }
+
+    /// Returns the timeline on which the backup ended.
+    pub fn end_timeline(&self) -> u32 {
+        self.end_timeline
+    }
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::parse(io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+fn parse_lsn(s: &str) -> Result<PgLsn, Error> {
+    s.parse()
+        .map_err(|_| invalid_data(format!("invalid WAL location `{}`", s)))
+}
+
+fn parse_timeline(s: &str) -> Result<u32, Error> {
+    s.parse()
+        .map_err(|_| invalid_data(format!("invalid timeline `{}`", s)))
+}
+
+fn required(row: &SimpleQueryRow, idx: usize) -> Result<&str, Error> {
+    row.try_get(idx)?.ok_or_else(Error::unexpected_message)
+}
+
+async fn query_row(client: &Client, query: &str) -> Result<SimpleQueryRow, Error> {
+    client
+        .simple_query(query)
+        .await?
+        .into_iter()
+        .find_map(|m| match m {
+            SimpleQueryMessage::Row(row) => Some(row),
+            _ => None,
+        })
+        .ok_or_else(Error::unexpected_message)
+}
+
+pub(crate) async fn identify_system(client: &Client) -> Result<IdentifySystem, Error> {
+    let row = query_row(client, "IDENTIFY_SYSTEM").await?;
+    Ok(IdentifySystem {
+        systemid: required(&row, 0)?.to_string(),
+        timeline: parse_timeline(required(&row, 1)?)?,
+        xlogpos: parse_lsn(required(&row, 2)?)?,
+        dbname: row.try_get(3)?.map(str::to_string),
+    })
+}
+
+pub(crate) async fn timeline_history(
+    client: &Client,
+    timeline: u32,
+) -> Result<TimelineHistory, Error> {
+    let row = query_row(client, &format!("TIMELINE_HISTORY {}", timeline)).await?;
+    Ok(TimelineHistory {
+        filename: required(&row, 0)?.to_string(),
+        content: required(&row, 1)?.as_bytes().to_vec(),
+    })
+}
+
+pub(crate) async fn start_physical_replication(
+    client: &Client,
+    slot: Option<&str>,
+    lsn: PgLsn,
+    timeline: Option<u32>,
+) -> Result<ReplicationStream, Error> {
+    let mut query = "START_REPLICATION".to_string();
+    if let Some(slot) = slot {
+        query.push_str(" SLOT ");
+        query.push_str(&escape_identifier(slot));
+    }
+    query.push_str(&format!(" PHYSICAL {}", lsn));
+    if let Some(timeline) = timeline {
+        query.push_str(&format!(" TIMELINE {}", timeline));
+    }
+
+    let duplex = client.copy_both_simple(&query).await?;
+    Ok(ReplicationStream::new(duplex))
+}
+
+// Parses the WAL location and timeline result sets sent before and after a base backup's data.
+fn parse_position(body: &DataRowBody) -> Result<(PgLsn, u32), Error> {
+    let values: Vec<_> = body.values().collect().map_err(Error::parse)?;
+    let value = |idx: usize| match values.get(idx) {
+        Some(Some(value)) => str::from_utf8(value)
+            .map_err(|e| Error::parse(io::Error::new(io::ErrorKind::InvalidData, e))),
+        _ => Err(Error::unexpected_message()),
+    };
+    Ok((parse_lsn(value(0)?)?, parse_timeline(value(1)?)?))
+}
+
+pub(crate) async fn base_backup<W>(
+    client: &Client,
+    options: &str,
+    mut writer: W,
+) -> Result<BaseBackup, Error>
+where
+    W: AsyncWrite + Unpin,
+{
+    let query = if options.is_empty() {
+        "BASE_BACKUP".to_string()
+    } else {
+        format!("BASE_BACKUP ({})", options)
+    };
+    debug!("executing base backup: {}", query);
+
+    let buf = simple_query::encode(client.inner(), &query)?;
+    let mut responses = client
+        .inner()
+        .send(RequestMessages::Single(FrontendMessage::Raw(buf)))?;
+
+    let mut start = None;
+    loop {
+        match responses.next().await? {
+            Message::DataRow(body) if start.is_none() => start = Some(parse_position(&body)?),
+            Message::RowDescription(_) | Message::DataRow(_) | Message::CommandComplete(_) => {}
+            Message::CopyOutResponse(_) => break,
+            _ => return Err(Error::unexpected_message()),
+        }
+    }
+    let (start_lsn, start_timeline) = start.ok_or_else(Error::unexpected_message)?;
+
+    let mut manifest = false;
+    loop {
+        let data = match responses.next().await? {
+            Message::CopyData(body) => body.into_bytes(),
+            Message::CopyDone => break,
+            _ => return Err(Error::unexpected_message()),
+        };
+        match data.first() {
+            Some(b'n') => manifest = false,
+            Some(b'm') => manifest = true,
+            Some(b'd') if !manifest => writer.write_all(&data[1..]).await.map_err(Error::io)?,
+            Some(b'd') | Some(b'p') => {}
+            _ => return Err(Error::unexpected_message()),
+        }
+    }
+    writer.flush().await.map_err(Error::io)?;
+
+    let mut end = None;
+    loop {
+        match responses.next().await? {
+            Message::DataRow(body) if end.is_none() => end = Some(parse_position(&body)?),
+            Message::RowDescription(_) | Message::DataRow(_) | Message::CommandComplete(_) => {}
+            Message::ReadyForQuery(_) => break,
+            _ => return Err(Error::unexpected_message()),
+        }
+    }
+    let (end_lsn, end_timeline) = end.ok_or_else(Error::unexpected_message)?;
+
+    Ok(BaseBackup {
+        start_lsn,
+        start_timeline,
+        end_lsn,
+        end_timeline,
+    })
+}
@@ -0,0 +1,82 @@
+#[cfg(feature = "runtime-core")]
+use crate::rt::RuntimeHandle;
+use futures_util::future::BoxFuture;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// A resolver of host names to socket addresses.
+///
+/// By default, host names are resolved with the system resolver. A custom resolver can be registered with
+/// [`Config::resolver`](crate::Config::resolver) to, for example, cache lookups, integrate with a service discovery
+/// system, or map names to fixed addresses. It is used both for the hosts of the configuration and for the host of
+/// its proxy, if one is set.
+///
+/// # Examples
+///
+/// ```
+/// use futures_util::future::{self, BoxFuture};
+/// use std::collections::HashMap;
+/// use std::io;
+/// use std::net::SocketAddr;
+/// use tokio_postgres::Resolver;
+///
+/// struct StaticResolver(HashMap<String, SocketAddr>);
+///
+/// impl Resolver for StaticResolver {
+///     fn resolve<'a>(&'a self, host: &'a str, _port: u16) -> BoxFuture<'a, io::Result<Vec<SocketAddr>>> {
+///         let result = self.0.get(host).map(|addr| vec![*addr]).ok_or_else(|| {
+///             io::Error::new(io::ErrorKind::NotFound, format!("unknown host {}", host))
+///         });
+///         Box::pin(future::ready(result))
+///     }
+/// }
+/// ```
+pub trait Resolver: Send + Sync {
+    /// Resolves `host` to the addresses to attempt connections to, in order.
+    ///
+    /// `port` is the port the connection will be made to, which is provided for resolvers that look up services
+    /// rather than hosts. The ports of the returned addresses are ignored.
+    fn resolve<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> BoxFuture<'a, io::Result<Vec<SocketAddr>>>;
+}
+
+/// A shared reference to a resolver, compared by identity so that `Config` can remain `Eq`.
+#[derive(Clone)]
+pub(crate) struct ResolverHandle(pub(crate) Arc<dyn Resolver>);
+
+impl PartialEq for ResolverHandle {
+    fn eq(&self, other: &ResolverHandle) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ResolverHandle {}
+
+impl fmt::Debug for ResolverHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Resolver")
+    }
+}
+
+/// Resolves a host with the given resolver, or the runtime's resolver if there is none.
+#[cfg(feature = "runtime-core")]
+pub(crate) async fn resolve(
+    resolver: Option<&ResolverHandle>,
+    runtime: Option<&RuntimeHandle>,
+    host: &str,
+    port: u16,
+) -> io::Result<Vec<SocketAddr>> {
+    match (resolver, runtime) {
+        (Some(resolver), _) => resolver.0.resolve(host, port).await,
+        (None, Some(runtime)) => runtime.0.resolve(host, port).await,
+        #[cfg(feature = "runtime")]
+        (None, None) => Ok(tokio::net::lookup_host((host, port)).await?.collect()),
+        #[cfg(not(feature = "runtime"))]
+        (None, None) => Err(crate::rt::no_runtime_error()),
+    }
+}
@@ -1,9 +1,11 @@
 //! Rows.
 
 use crate::row::sealed::{AsName, Sealed};
+#[cfg(feature = "with-serde_json-1")]
+use crate::row_json::JsonValue;
 use crate::simple_query::SimpleColumn;
 use crate::statement::Column;
-use crate::types::{FromSql, Type, WrongType};
+use crate::types::{FromSql, NamedSqlRow, SqlRow, Type, WrongType};
 use crate::{Error, Statement};
 use fallible_iterator::FallibleIterator;
 use postgres_protocol::message::backend::DataRowBody;
@@ -163,6 +165,20 @@ impl Row {
         self.get_inner(&idx)
     }
 
+    /// Returns the raw bytes of a value in the row, or `None` if it is `NULL`.
+    ///
+    /// The bytes are borrowed from the buffer the row was received in, and are in the binary wire format of the
+    /// column's type.
+    pub fn try_get_raw<I>(&self, idx: I) -> Result<Option<&[u8]>, Error>
+    where
+        I: RowIndex + fmt::Display,
+    {
+        match idx.__idx(self.columns()) {
+            Some(idx) => Ok(self.col_buffer(idx)),
+            None => Err(Error::column(idx.to_string())),
+        }
+    }
+
     fn get_inner<'a, I, T>(&'a self, idx: &I) -> Result<T, Error>
     where
         I: RowIndex + fmt::Display,
@@ -184,6 +200,30 @@ impl Row {
         FromSql::from_sql_nullable(ty, self.col_buffer(idx)).map_err(|e| Error::from_sql(e, idx))
     }
 
+    /// Converts the row into a JSON object keyed by column name.
+    ///
+    /// Booleans, integers, and floats are converted to their JSON equivalents, `NULL` to `null`, `JSON` and `JSONB`
+    /// values are embedded directly, and arrays are converted to (possibly nested) JSON arrays. `NUMERIC` values are
+    /// converted to strings to avoid a loss of precision, as are non-finite floats which JSON cannot represent.
+    /// Composite values are converted to JSON objects keyed by field name. Other supported types such as text, dates,
+    /// timestamps, intervals (in ISO 8601 format), ranges, and UUIDs are converted to their string representations.
+    /// Values of types with no JSON representation, such as geometric types, are converted to `null`.
+    ///
+    /// If multiple columns share a name, the last one wins.
+    ///
+    /// Requires the `with-serde_json-1` Cargo feature.
+    #[cfg(feature = "with-serde_json-1")]
+    pub fn to_json(&self) -> Result<serde_json_1::Map<String, serde_json_1::Value>, Error> {
+        self.columns()
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let value = self.get_inner::<_, JsonValue>(&i)?;
+                Ok((column.name().to_string(), value.0))
+            })
+            .collect()
+    }
+
     /// Get the raw bytes for the column at the given index.
     fn col_buffer(&self, idx: usize) -> Option<&[u8]> {
         let range = self.ranges[idx].to_owned()?;
@@ -191,13 +231,37 @@ impl Row {
     }
 }
 
+impl SqlRow for Row {
+    type Error = Error;
+
+    fn try_get<'a, T>(&'a self, idx: usize) -> Result<T, Error>
+    where
+        T: FromSql<'a>,
+    {
+        self.get_inner(&idx)
+    }
+}
+
+impl NamedSqlRow for Row {
+    fn contains(&self, name: &str) -> bool {
+        name.__idx(self.columns()).is_some()
+    }
+
+    fn try_get_named<'a, T>(&'a self, name: &str) -> Result<T, Error>
+    where
+        T: FromSql<'a>,
+    {
+        self.get_inner(&name)
+    }
+}
+
 impl AsName for SimpleColumn {
     fn as_name(&self) -> &str {
         self.name()
     }
 }
 
-/// A row of data returned from the database by a simple query.
+/// A row of data returned from the database by a simple query, or by a query returning text format results.
 #[derive(Debug)]
 pub struct SimpleQueryRow {
     columns: Arc<[SimpleColumn]>,
@@ -260,6 +324,25 @@ impl SimpleQueryRow {
         self.get_inner(&idx)
     }
 
+    /// Converts the row into a JSON object keyed by column name.
+    ///
+    /// As the simple query protocol does not report column types, every non-`NULL` value is converted to a string.
+    ///
+    /// Requires the `with-serde_json-1` Cargo feature.
+    #[cfg(feature = "with-serde_json-1")]
+    pub fn to_json(&self) -> Result<serde_json_1::Map<String, serde_json_1::Value>, Error> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let value = self
+                    .get_inner(&i)?
+                    .map_or(serde_json_1::Value::Null, |v| v.into());
+                Ok((column.name().to_string(), value))
+            })
+            .collect()
+    }
+
     fn get_inner<I>(&self, idx: &I) -> Result<Option<&str>, Error>
     where
         I: RowIndex + fmt::Display,
@@ -0,0 +1,459 @@
+use crate::types::{FromSql, Kind, Type};
+use byteorder::{BigEndian, ReadBytesExt};
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::types::{self, Interval, Range, RangeBound};
+use serde_json_1::{Number, Value};
+use std::error::Error;
+use std::fmt::Write;
+use std::net::IpAddr;
+
+// Number of days from 0000-03-01 to 2000-01-01, the Postgres epoch.
+const PG_EPOCH_DAYS: i64 = 730_425;
+const USECS_PER_DAY: i64 = 86_400_000_000;
+
+/// A JSON representation of an arbitrary Postgres value.
+pub(crate) struct JsonValue(pub Value);
+
+impl<'a> FromSql<'a> for JsonValue {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<JsonValue, Box<dyn Error + Sync + Send>> {
+        to_json(ty, raw).map(JsonValue)
+    }
+
+    fn from_sql_null(_: &Type) -> Result<JsonValue, Box<dyn Error + Sync + Send>> {
+        Ok(JsonValue(Value::Null))
+    }
+
+    // Types without a JSON representation of their own are converted to `null` rather than rejected.
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+fn to_json(ty: &Type, raw: &[u8]) -> Result<Value, Box<dyn Error + Sync + Send>> {
+    match ty.kind() {
+        Kind::Enum(_) => return Ok(Value::String(types::text_from_sql(raw)?.to_string())),
+        Kind::Domain(member) => return to_json(member, raw),
+        Kind::Array(member) => return array_to_json(member, raw),
+        Kind::Range(member) => {
+            let range = types::range_from_sql(raw)?;
+            return Ok(Value::String(range_to_string(member, range)?));
+        }
+        Kind::Multirange(member) => {
+            let ranges = types::multirange_from_sql(raw)?
+                .ranges()
+                .map(|range| range_to_string(member, range))
+                .collect::<Vec<_>>()?;
+            return Ok(Value::String(format!("{{{}}}", ranges.join(","))));
+        }
+        Kind::Composite(fields) => {
+            let mut values = types::record_from_sql(raw)?;
+            let mut map = serde_json_1::Map::new();
+            for field in fields {
+                let value = match values.next()? {
+                    Some(value) => value.value(),
+                    None => return Err("invalid composite field count".into()),
+                };
+                map.insert(
+                    field.name().to_string(),
+                    nullable_to_json(field.type_(), value)?,
+                );
+            }
+            if values.next()?.is_some() {
+                return Err("invalid composite field count".into());
+            }
+            return Ok(Value::Object(map));
+        }
+        _ => {}
+    }
+
+    let value = match *ty {
+        Type::BOOL => Value::Bool(types::bool_from_sql(raw)?),
+        Type::INT2 => Value::from(types::int2_from_sql(raw)?),
+        Type::INT4 => Value::from(types::int4_from_sql(raw)?),
+        Type::INT8 => Value::from(types::int8_from_sql(raw)?),
+        Type::OID => Value::from(types::oid_from_sql(raw)?),
+        Type::FLOAT4 => float_to_json(f64::from(types::float4_from_sql(raw)?)),
+        Type::FLOAT8 => float_to_json(types::float8_from_sql(raw)?),
+        Type::NUMERIC => Value::String(numeric_to_string(raw)?),
+        Type::CHAR => Value::String(char::from(types::char_from_sql(raw)? as u8).to_string()),
+        Type::JSON | Type::JSONB => {
+            let mut raw = raw;
+            if *ty == Type::JSONB {
+                let version = raw.first().copied();
+                if version != Some(1) {
+                    return Err("unsupported JSONB encoding version".into());
+                }
+                raw = &raw[1..];
+            }
+            serde_json_1::from_slice(raw)?
+        }
+        Type::BYTEA => {
+            let mut s = String::from("\\x");
+            for b in types::bytea_from_sql(raw) {
+                write!(s, "{:02x}", b).unwrap();
+            }
+            Value::String(s)
+        }
+        Type::UUID => {
+            let bytes = types::uuid_from_sql(raw)?;
+            let mut s = String::with_capacity(36);
+            for (i, b) in bytes.iter().enumerate() {
+                if matches!(i, 4 | 6 | 8 | 10) {
+                    s.push('-');
+                }
+                write!(s, "{:02x}", b).unwrap();
+            }
+            Value::String(s)
+        }
+        Type::DATE => Value::String(date_to_string(types::date_from_sql(raw)?)),
+        Type::TIME => Value::String(time_to_string(types::time_from_sql(raw)?)),
+        Type::TIMETZ => {
+            let mut buf = raw;
+            let usecs = buf.read_i64::<BigEndian>()?;
+            let zone = buf.read_i32::<BigEndian>()?;
+            if !buf.is_empty() {
+                return Err("invalid message length: timetz not drained".into());
+            }
+            // the zone is stored in seconds west of UTC
+            Value::String(time_to_string(usecs) + &offset_to_string(-zone))
+        }
+        Type::INTERVAL => Value::String(interval_to_string(&types::interval_from_sql(raw)?)),
+        Type::MONEY => {
+            let cents = types::int8_from_sql(raw)?;
+            let sign = if cents < 0 { "-" } else { "" };
+            let cents = cents.unsigned_abs();
+            Value::String(format!("{}{}.{:02}", sign, cents / 100, cents % 100))
+        }
+        Type::TIMESTAMP => Value::String(timestamp_to_string(types::timestamp_from_sql(raw)?)),
+        Type::TIMESTAMPTZ => {
+            let mut s = timestamp_to_string(types::timestamp_from_sql(raw)?);
+            if !s.ends_with("infinity") {
+                s.push('Z');
+            }
+            Value::String(s)
+        }
+        Type::INET | Type::CIDR => {
+            let inet = types::inet_from_sql(raw)?;
+            let full = match inet.addr() {
+                IpAddr::V4(_) => 32,
+                IpAddr::V6(_) => 128,
+            };
+            // like the server, INET omits the netmask of a single host
+            if *ty == Type::INET && inet.netmask() == full {
+                Value::String(inet.addr().to_string())
+            } else {
+                Value::String(format!("{}/{}", inet.addr(), inet.netmask()))
+            }
+        }
+        Type::MACADDR => {
+            let bytes = types::macaddr_from_sql(raw)?;
+            let parts = bytes
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>();
+            Value::String(parts.join(":"))
+        }
+        _ if <&str as FromSql>::accepts(ty) => {
+            Value::String(types::text_from_sql(raw)?.to_string())
+        }
+        _ => Value::Null,
+    };
+
+    Ok(value)
+}
+
+fn nullable_to_json(ty: &Type, raw: Option<&[u8]>) -> Result<Value, Box<dyn Error + Sync + Send>> {
+    match raw {
+        Some(raw) => to_json(ty, raw),
+        None => Ok(Value::Null),
+    }
+}
+
+// Formats a range the way the server does, such as `[1,10)`.
+fn range_to_string(
+    member: &Type,
+    range: Range<'_>,
+) -> Result<String, Box<dyn Error + Sync + Send>> {
+    let (lower, upper) = match range {
+        Range::Empty => return Ok("empty".to_string()),
+        Range::Nonempty(lower, upper) => (lower, upper),
+    };
+
+    let mut s = String::new();
+    let lower = match lower {
+        RangeBound::Inclusive(v) => {
+            s.push('[');
+            Some(v)
+        }
+        RangeBound::Exclusive(v) => {
+            s.push('(');
+            Some(v)
+        }
+        RangeBound::Unbounded => {
+            s.push('(');
+            None
+        }
+    };
+    if let Some(v) = lower {
+        push_range_bound(&mut s, member, v)?;
+    }
+    s.push(',');
+    let close = match upper {
+        RangeBound::Inclusive(v) => {
+            push_range_bound(&mut s, member, v)?;
+            ']'
+        }
+        RangeBound::Exclusive(v) => {
+            push_range_bound(&mut s, member, v)?;
+            ')'
+        }
+        RangeBound::Unbounded => ')',
+    };
+    s.push(close);
+
+    Ok(s)
+}
+
+fn push_range_bound(
+    s: &mut String,
+    member: &Type,
+    raw: Option<&[u8]>,
+) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let value = match nullable_to_json(member, raw)? {
+        Value::String(v) => v,
+        Value::Null => return Ok(()),
+        v => v.to_string(),
+    };
+
+    let quote = value.is_empty()
+        || value
+            .chars()
+            .any(|c| matches!(c, '"' | '\\' | ',' | '(' | ')' | '[' | ']') || c.is_whitespace());
+    if !quote {
+        s.push_str(&value);
+        return Ok(());
+    }
+
+    s.push('"');
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            s.push(c);
+        }
+        s.push(c);
+    }
+    s.push('"');
+    Ok(())
+}
+
+fn array_to_json(member: &Type, raw: &[u8]) -> Result<Value, Box<dyn Error + Sync + Send>> {
+    let array = types::array_from_sql(raw)?;
+    let dimensions = array
+        .dimensions()
+        .map(|d| Ok(d.len as usize))
+        .collect::<Vec<_>>()?;
+    let values = array
+        .values()
+        .map(|v| nullable_to_json(member, v))
+        .collect::<Vec<_>>()?;
+
+    if dimensions.is_empty() {
+        return Ok(Value::Array(vec![]));
+    }
+
+    Ok(nest(&dimensions, &mut values.into_iter()))
+}
+
+fn nest(dimensions: &[usize], values: &mut impl Iterator<Item = Value>) -> Value {
+    match dimensions.split_first() {
+        Some((len, [])) => Value::Array(values.take(*len).collect()),
+        Some((len, rest)) => Value::Array((0..*len).map(|_| nest(rest, values)).collect()),
+        None => Value::Array(vec![]),
+    }
+}
+
+fn float_to_json(v: f64) -> Value {
+    match Number::from_f64(v) {
+        Some(n) => Value::Number(n),
+        None if v.is_nan() => Value::String("NaN".to_string()),
+        None if v > 0. => Value::String("Infinity".to_string()),
+        None => Value::String("-Infinity".to_string()),
+    }
+}
+
+fn numeric_to_string(mut raw: &[u8]) -> Result<String, Box<dyn Error + Sync + Send>> {
+    let ndigits = raw.read_i16::<BigEndian>()?;
+    let weight = raw.read_i16::<BigEndian>()?;
+    let sign = raw.read_u16::<BigEndian>()?;
+    let dscale = raw.read_i16::<BigEndian>()?;
+    let digits = (0..ndigits)
+        .map(|_| raw.read_i16::<BigEndian>())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match sign {
+        0x0000 | 0x4000 => {}
+        0xC000 => return Ok("NaN".to_string()),
+        0xD000 => return Ok("Infinity".to_string()),
+        0xF000 => return Ok("-Infinity".to_string()),
+        _ => return Err("invalid numeric sign".into()),
+    }
+
+    let mut s = String::new();
+    if sign == 0x4000 {
+        s.push('-');
+    }
+
+    // each digit is a base 10000 group; groups at or below `weight` make up the integer part
+    if weight < 0 {
+        s.push('0');
+    } else {
+        for i in 0..=weight {
+            let digit = digits.get(i as usize).copied().unwrap_or(0);
+            if i == 0 {
+                write!(s, "{}", digit).unwrap();
+            } else {
+                write!(s, "{:04}", digit).unwrap();
+            }
+        }
+    }
+
+    if dscale > 0 {
+        s.push('.');
+        let mut fraction = String::new();
+        let mut i = i32::from(weight) + 1;
+        while (fraction.len() as i16) < dscale {
+            let digit = if i < 0 {
+                0
+            } else {
+                digits.get(i as usize).copied().unwrap_or(0)
+            };
+            write!(fraction, "{:04}", digit).unwrap();
+            i += 1;
+        }
+        fraction.truncate(dscale as usize);
+        s.push_str(&fraction);
+    }
+
+    Ok(s)
+}
+
+fn date_to_string(days: i32) -> String {
+    match days {
+        i32::MAX => "infinity".to_string(),
+        i32::MIN => "-infinity".to_string(),
+        days => {
+            let (year, month, day) = civil_from_days(i64::from(days));
+            format_date(year, month, day)
+        }
+    }
+}
+
+fn time_to_string(usecs: i64) -> String {
+    let secs = usecs / 1_000_000;
+    let mut s = format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+    let fraction = usecs % 1_000_000;
+    if fraction != 0 {
+        let fraction = format!(".{:06}", fraction);
+        s.push_str(fraction.trim_end_matches('0'));
+    }
+    s
+}
+
+// Formats a UTC offset in seconds the way the server does, such as `+05:30`.
+fn offset_to_string(offset: i32) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.unsigned_abs();
+    let (minutes, seconds) = (offset / 60 % 60, offset % 60);
+    let mut s = format!("{}{:02}", sign, offset / 3600);
+    if minutes != 0 || seconds != 0 {
+        write!(s, ":{:02}", minutes).unwrap();
+    }
+    if seconds != 0 {
+        write!(s, ":{:02}", seconds).unwrap();
+    }
+    s
+}
+
+// Formats an interval in ISO 8601 format, such as `P1Y2M3DT4H5M6.5S`.
+fn interval_to_string(interval: &Interval) -> String {
+    let months = interval.months();
+    let days = interval.days();
+    let usecs = interval.microseconds();
+    if months == 0 && days == 0 && usecs == 0 {
+        return "PT0S".to_string();
+    }
+
+    let mut s = String::from("P");
+    if months / 12 != 0 {
+        write!(s, "{}Y", months / 12).unwrap();
+    }
+    if months % 12 != 0 {
+        write!(s, "{}M", months % 12).unwrap();
+    }
+    if days != 0 {
+        write!(s, "{}D", days).unwrap();
+    }
+    if usecs != 0 {
+        s.push('T');
+        let hours = usecs / 3_600_000_000;
+        let minutes = usecs / 60_000_000 % 60;
+        let secs = usecs % 60_000_000;
+        if hours != 0 {
+            write!(s, "{}H", hours).unwrap();
+        }
+        if minutes != 0 {
+            write!(s, "{}M", minutes).unwrap();
+        }
+        if secs != 0 {
+            if secs < 0 {
+                s.push('-');
+            }
+            let secs = secs.unsigned_abs();
+            let fraction = secs % 1_000_000;
+            write!(s, "{}", secs / 1_000_000).unwrap();
+            if fraction != 0 {
+                let fraction = format!(".{:06}", fraction);
+                s.push_str(fraction.trim_end_matches('0'));
+            }
+            s.push('S');
+        }
+    }
+    s
+}
+
+fn timestamp_to_string(usecs: i64) -> String {
+    match usecs {
+        i64::MAX => "infinity".to_string(),
+        i64::MIN => "-infinity".to_string(),
+        usecs => {
+            let days = usecs.div_euclid(USECS_PER_DAY);
+            let (year, month, day) = civil_from_days(days);
+            format!(
+                "{}T{}",
+                format_date(year, month, day),
+                time_to_string(usecs.rem_euclid(USECS_PER_DAY))
+            )
+        }
+    }
+}
+
+fn format_date(year: i64, month: i64, day: i64) -> String {
+    if year > 0 {
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    } else {
+        format!("{:04}-{:02}-{:02} BC", 1 - year, month, day)
+    }
+}
+
+// Converts days since 2000-01-01 into a proleptic Gregorian (year, month, day).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + PG_EPOCH_DAYS;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,210 @@
+//! Runtime support.
+//!
+//! Opening connections with [`Config::connect`](crate::Config::connect), and the APIs built on it such as connection
+//! pooling, reconnection and query timeouts, need to open sockets, wait on timers and spawn background tasks. By
+//! default these are provided by tokio, which requires the `runtime` Cargo feature (enabled by default).
+//!
+//! Other executors, such as those of `async-std` or `smol`, or executors for embedded systems, can be used instead by
+//! implementing [`Runtime`] and registering it with [`Config::runtime`](crate::Config::runtime). Those APIs are
+//! available with just the `runtime-core` Cargo feature, which does not depend on tokio's reactor or timers. Without
+//! the `runtime` feature, a runtime must be registered before connecting. Connection attempts fail without one, and the
+//! other APIs which need one, such as query timeouts, panic.
+//!
+//! Requires the `runtime-core` Cargo feature (enabled by default).
+use crate::socket_config::SocketConfig;
+use futures_util::future::{self, BoxFuture, Either};
+use futures_util::pin_mut;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// A stream opened by a [`Runtime`].
+///
+/// This is implemented for all types which implement tokio's `AsyncRead` and `AsyncWrite` traits. Sockets of other
+/// runtimes can be adapted to them with the `compat` module of the `tokio-util` crate.
+pub trait AsyncStream: AsyncRead + AsyncWrite + Send + Sync + Unpin {}
+
+impl<T> AsyncStream for T where T: AsyncRead + AsyncWrite + Send + Sync + Unpin {}
+
+/// An asynchronous runtime, which provides the tasks, timers and sockets used when connecting to the server.
+///
+/// # Examples
+///
+/// A runtime which defers to tokio, much as the default one does:
+///
+/// ```
+/// use futures_util::future::BoxFuture;
+/// use std::io;
+/// use std::net::SocketAddr;
+/// use std::time::Duration;
+/// use tokio_postgres::rt::{AsyncStream, Runtime};
+/// use tokio_postgres::SocketConfig;
+///
+/// struct Tokio;
+///
+/// impl Runtime for Tokio {
+///     fn spawn(&self, future: BoxFuture<'static, ()>) {
+///         tokio::spawn(future);
+///     }
+///
+///     fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+///         Box::pin(tokio::time::sleep(duration))
+///     }
+///
+///     fn connect_tcp<'a>(
+///         &'a self,
+///         addr: SocketAddr,
+///         _config: &'a SocketConfig,
+///     ) -> BoxFuture<'a, io::Result<Box<dyn AsyncStream>>> {
+///         Box::pin(async move {
+///             let stream = tokio::net::TcpStream::connect(addr).await?;
+///             stream.set_nodelay(true)?;
+///             Ok(Box::new(stream) as Box<dyn AsyncStream>)
+///         })
+///     }
+/// }
+/// ```
+pub trait Runtime: Send + Sync {
+    /// Spawns a task which runs in the background until it completes.
+    ///
+    /// This is used to drive the connections opened by connection pools and reconnecting clients.
+    fn spawn(&self, future: BoxFuture<'static, ()>);
+
+    /// Returns a future which completes once `duration` has elapsed.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+
+    /// Opens a TCP connection to `addr`.
+    ///
+    /// This is used to connect both to servers and to proxies. `config` is the configuration of the socket being
+    /// opened, and implementations should apply the options it sets where they are able to, such as the local address
+    /// and TCP keepalives. The connect timeout is applied by the caller. Nagle's algorithm should be disabled.
+    fn connect_tcp<'a>(
+        &'a self,
+        addr: SocketAddr,
+        config: &'a SocketConfig,
+    ) -> BoxFuture<'a, io::Result<Box<dyn AsyncStream>>>;
+
+    /// Opens a connection to the Unix socket at `path`.
+    ///
+    /// The default implementation returns an error.
+    #[cfg(unix)]
+    fn connect_unix<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxFuture<'a, io::Result<Box<dyn AsyncStream>>> {
+        let _ = path;
+        Box::pin(future::ready(Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the runtime does not support Unix sockets",
+        ))))
+    }
+
+    /// Resolves `host` to the addresses to attempt connections to, in order.
+    ///
+    /// This is used for hosts when no [`Resolver`](crate::Resolver) is configured. The default implementation returns
+    /// an error, so that only IP addresses can be connected to.
+    fn resolve<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> BoxFuture<'a, io::Result<Vec<SocketAddr>>> {
+        let _ = port;
+        Box::pin(future::ready(Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "the runtime does not support resolving host names, so {} cannot be resolved",
+                host
+            ),
+        ))))
+    }
+}
+
+/// A shared reference to a runtime, compared by identity so that `Config` can remain `Eq`.
+#[derive(Clone)]
+pub(crate) struct RuntimeHandle(pub(crate) Arc<dyn Runtime>);
+
+impl PartialEq for RuntimeHandle {
+    fn eq(&self, other: &RuntimeHandle) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for RuntimeHandle {}
+
+impl fmt::Debug for RuntimeHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Runtime")
+    }
+}
+
+#[cfg(not(feature = "runtime"))]
+const NO_RUNTIME: &str =
+    "no runtime is configured; register one with `Config::runtime` or enable the `runtime` Cargo feature";
+
+#[cfg(not(feature = "runtime"))]
+fn no_runtime() -> ! {
+    panic!("{}", NO_RUNTIME)
+}
+
+/// Returns the error reported when a socket is to be opened without a runtime.
+#[cfg(not(feature = "runtime"))]
+pub(crate) fn no_runtime_error() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, NO_RUNTIME)
+}
+
+/// Spawns a task on the given runtime, or on tokio if there is none.
+pub(crate) fn spawn<F>(runtime: Option<&RuntimeHandle>, future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    match runtime {
+        Some(runtime) => runtime.0.spawn(Box::pin(future)),
+        #[cfg(feature = "runtime")]
+        None => {
+            tokio::spawn(future);
+        }
+        #[cfg(not(feature = "runtime"))]
+        None => no_runtime(),
+    }
+}
+
+/// Sleeps for `duration` on the given runtime, or on tokio if there is none.
+pub(crate) async fn sleep(runtime: Option<&RuntimeHandle>, duration: Duration) {
+    match runtime {
+        Some(runtime) => runtime.0.sleep(duration).await,
+        #[cfg(feature = "runtime")]
+        None => tokio::time::sleep(duration).await,
+        #[cfg(not(feature = "runtime"))]
+        None => no_runtime(),
+    }
+}
+
+/// Runs `future` to completion, unless `duration` elapses first, in which case `None` is returned.
+pub(crate) async fn timeout<F>(
+    runtime: Option<&RuntimeHandle>,
+    duration: Duration,
+    future: F,
+) -> Option<F::Output>
+where
+    F: Future,
+{
+    match runtime {
+        Some(runtime) => {
+            pin_mut!(future);
+            match future::select(future, runtime.0.sleep(duration)).await {
+                Either::Left((output, _)) => Some(output),
+                Either::Right(((), _)) => None,
+            }
+        }
+        #[cfg(feature = "runtime")]
+        None => tokio::time::timeout(duration, future).await.ok(),
+        #[cfg(not(feature = "runtime"))]
+        None => no_runtime(),
+    }
+}
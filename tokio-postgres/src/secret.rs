@@ -0,0 +1,70 @@
+use std::ops::Deref;
+use std::{fmt, hint, mem};
+
+// A password or other secret value, which is redacted from debug output and overwritten with zeros when dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    pub(crate) fn new(value: T) -> Secret<T> {
+        Secret(value)
+    }
+}
+
+impl<T: Zeroize + Deref> Deref for Secret<T> {
+    type Target = T::Target;
+
+    fn deref(&self) -> &T::Target {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("_")
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(feature = "with-serde-1")]
+impl<T: Zeroize + serde_1::Serialize> serde_1::Serialize for Secret<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde_1::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "with-serde-1")]
+impl<'de, T: Zeroize + serde_1::Deserialize<'de>> serde_1::Deserialize<'de> for Secret<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Secret<T>, D::Error>
+    where
+        D: serde_1::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Secret)
+    }
+}
+
+pub(crate) trait Zeroize {
+    fn zeroize(&mut self);
+}
+
+impl Zeroize for Vec<u8> {
+    fn zeroize(&mut self) {
+        self.fill(0);
+        // keep the writes from being optimized away as dead stores
+        hint::black_box(self.as_mut_slice());
+    }
+}
+
+impl Zeroize for String {
+    fn zeroize(&mut self) {
+        mem::take(self).into_bytes().zeroize();
+    }
+}
@@ -0,0 +1,142 @@
+use crate::Error;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Looks up the parameters of a connection service.
+///
+/// As with libpq, the per-user service file is searched first, followed by `pg_service.conf` in the directory named by
+/// the `PGSYSCONFDIR` environment variable. The per-user file is `file` if given, and is otherwise found from the
+/// environment.
+pub(crate) fn load(service: &str, file: Option<&Path>) -> Result<Vec<(String, String)>, Error> {
+    let user_file = match file {
+        Some(file) => Some(file.to_path_buf()),
+        None => user_file(),
+    };
+    let files = user_file.into_iter().chain(system_file());
+    for file in files {
+        let contents = match fs::read_to_string(&file) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(Error::config_parse(Box::new(e))),
+        };
+
+        if let Some(params) = parse(&contents, service, &file)? {
+            return Ok(params);
+        }
+    }
+
+    Err(Error::config_parse(
+        format!("definition of service `{}` not found", service).into(),
+    ))
+}
+
+fn user_file() -> Option<PathBuf> {
+    if let Some(file) = env::var_os("PGSERVICEFILE") {
+        return Some(PathBuf::from(file));
+    }
+
+    #[cfg(windows)]
+    let file =
+        env::var_os("APPDATA").map(|dir| Path::new(&dir).join("postgresql/.pg_service.conf"));
+    #[cfg(not(windows))]
+    let file = env::var_os("HOME").map(|dir| Path::new(&dir).join(".pg_service.conf"));
+    file
+}
+
+fn system_file() -> Option<PathBuf> {
+    env::var_os("PGSYSCONFDIR").map(|dir| Path::new(&dir).join("pg_service.conf"))
+}
+
+fn parse(
+    contents: &str,
+    service: &str,
+    file: &Path,
+) -> Result<Option<Vec<(String, String)>>, Error> {
+    let mut params = None;
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(section) = line.strip_prefix('[') {
+            if params.is_some() {
+                break;
+            }
+            if section.strip_suffix(']') == Some(service) {
+                params = Some(vec![]);
+            }
+            continue;
+        }
+
+        let params = match &mut params {
+            Some(params) => params,
+            None => continue,
+        };
+
+        let invalid = |message: &str| {
+            Error::config_parse(
+                format!(
+                    "{} in service file {}, line {}",
+                    message,
+                    file.display(),
+                    i + 1
+                )
+                .into(),
+            )
+        };
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| invalid("syntax error"))?;
+        let key = key.trim_end();
+        if key == "service" {
+            return Err(invalid("nested service specifications not supported"));
+        }
+        params.push((key.to_string(), value.trim_start().to_string()));
+    }
+
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use std::path::Path;
+
+    #[test]
+    fn parse_service() {
+        let contents = "
+# comment
+[other]
+host=other
+
+[mydb]
+host = db.example.com
+port=5433
+  dbname=app
+
+[later]
+host=later
+";
+        let params = parse(contents, "mydb", Path::new("pg_service.conf"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            params,
+            [
+                ("host".to_string(), "db.example.com".to_string()),
+                ("port".to_string(), "5433".to_string()),
+                ("dbname".to_string(), "app".to_string()),
+            ]
+        );
+
+        assert!(parse(contents, "missing", Path::new("pg_service.conf"))
+            .unwrap()
+            .is_none());
+        parse("[a]\nservice=b", "a", Path::new("pg_service.conf")).unwrap_err();
+        parse("[a]\nhost", "a", Path::new("pg_service.conf")).unwrap_err();
+    }
+}
@@ -1,7 +1,9 @@
 use crate::client::{InnerClient, Responses};
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
+use crate::error::DbError;
 use crate::query::extract_row_affected;
+use crate::types::Oid;
 use crate::{Error, SimpleQueryMessage, SimpleQueryRow};
 use bytes::Bytes;
 use fallible_iterator::FallibleIterator;
@@ -11,6 +13,7 @@ use pin_project_lite::pin_project;
 use postgres_protocol::message::backend::Message;
 use postgres_protocol::message::frontend;
 use std::marker::PhantomPinned;
+use std::mem;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
@@ -19,17 +22,37 @@ use std::task::{Context, Poll};
 #[derive(Debug)]
 pub struct SimpleColumn {
     name: String,
+    type_oid: Oid,
+    format: i16,
 }
 
 impl SimpleColumn {
-    pub(crate) fn new(name: String) -> SimpleColumn {
-        SimpleColumn { name }
+    pub(crate) fn new(name: String, type_oid: Oid, format: i16) -> SimpleColumn {
+        SimpleColumn {
+            name,
+            type_oid,
+            format,
+        }
     }
 
     /// Returns the name of the column.
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Returns the OID of the column's type.
+    ///
+    /// [`Type::from_oid`](crate::types::Type::from_oid) can be used to look up built-in types.
+    pub fn type_oid(&self) -> Oid {
+        self.type_oid
+    }
+
+    /// Returns the format code of the column's values: `0` for text and `1` for binary.
+    ///
+    /// The simple query protocol always returns values in text format, except for those fetched from a binary cursor.
+    pub fn format(&self) -> i16 {
+        self.format
+    }
 }
 
 pub async fn simple_query(client: &InnerClient, query: &str) -> Result<SimpleQueryStream, Error> {
@@ -63,7 +86,75 @@ pub async fn batch_execute(client: &InnerClient, query: &str) -> Result<(), Erro
     }
 }
 
-fn encode(client: &InnerClient, query: &str) -> Result<Bytes, Error> {
+pub async fn batch_execute_results(
+    client: &InnerClient,
+    query: &str,
+) -> Result<Vec<BatchResult>, Error> {
+    debug!("executing statement batch: {}", query);
+
+    let buf = encode(client, query)?;
+    let mut responses =
+        client.send_with_notices(RequestMessages::Single(FrontendMessage::Raw(buf)))?;
+
+    let mut results = vec![];
+    let mut notices = vec![];
+    loop {
+        match responses.next().await? {
+            Message::ReadyForQuery(_) => break,
+            Message::CommandComplete(body) => results.push(BatchResult {
+                tag: body.tag().map_err(Error::parse)?.to_string(),
+                rows_affected: extract_row_affected(&body)?,
+                notices: mem::take(&mut notices),
+            }),
+            Message::EmptyQueryResponse => results.push(BatchResult {
+                tag: String::new(),
+                rows_affected: 0,
+                notices: mem::take(&mut notices),
+            }),
+            Message::NoticeResponse(body) => {
+                notices.push(DbError::parse(&mut body.fields()).map_err(Error::parse)?)
+            }
+            Message::RowDescription(_) | Message::DataRow(_) => {}
+            _ => return Err(Error::unexpected_message()),
+        }
+    }
+
+    // notices raised after the last statement completed, for example while committing, are attributed to it
+    if let Some(last) = results.last_mut() {
+        last.notices.append(&mut notices);
+    }
+
+    Ok(results)
+}
+
+/// The outcome of a single statement executed by [`Client::batch_execute_results`](crate::Client::batch_execute_results).
+#[derive(Debug)]
+pub struct BatchResult {
+    tag: String,
+    rows_affected: u64,
+    notices: Vec<DbError>,
+}
+
+impl BatchResult {
+    /// Returns the command tag reported by the server, such as `INSERT 0 3` or `CREATE TABLE`.
+    ///
+    /// The tag is empty for empty statements.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Returns the number of rows affected by the statement, or 0 if the statement does not report one.
+    pub fn rows_affected(&self) -> u64 {
+        self.rows_affected
+    }
+
+    /// Returns the notices raised by the server while executing the statement.
+    pub fn notices(&self) -> &[DbError] {
+        &self.notices
+    }
+}
+
+pub fn encode(client: &InnerClient, query: &str) -> Result<Bytes, Error> {
     client.with_buf(|buf| {
         frontend::query(query, buf).map_err(Error::encode)?;
         Ok(buf.split().freeze())
@@ -96,7 +187,13 @@ impl Stream for SimpleQueryStream {
             Message::RowDescription(body) => {
                 let columns: Arc<[SimpleColumn]> = body
                     .fields()
-                    .map(|f| Ok(SimpleColumn::new(f.name().to_string())))
+                    .map(|f| {
+                        Ok(SimpleColumn::new(
+                            f.name().to_string(),
+                            f.type_oid(),
+                            f.format(),
+                        ))
+                    })
                     .collect::<Vec<_>>()
                     .map_err(Error::parse)?
                     .into();
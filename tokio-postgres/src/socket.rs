@@ -1,33 +1,55 @@
-use std::io;
+use crate::rt::AsyncStream;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::{fmt, io};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+#[cfg(feature = "runtime")]
 use tokio::net::TcpStream;
-#[cfg(unix)]
+#[cfg(all(unix, feature = "runtime"))]
 use tokio::net::UnixStream;
 
-#[derive(Debug)]
 enum Inner {
+    #[cfg(feature = "runtime")]
     Tcp(TcpStream),
-    #[cfg(unix)]
+    #[cfg(all(unix, feature = "runtime"))]
     Unix(UnixStream),
+    Custom(Box<dyn AsyncStream>),
+}
+
+impl fmt::Debug for Inner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "runtime")]
+            Inner::Tcp(s) => f.debug_tuple("Tcp").field(s).finish(),
+            #[cfg(all(unix, feature = "runtime"))]
+            Inner::Unix(s) => f.debug_tuple("Unix").field(s).finish(),
+            Inner::Custom(_) => f.write_str("Custom"),
+        }
+    }
 }
 
 /// The standard stream type used by the crate.
 ///
-/// Requires the `runtime` Cargo feature (enabled by default).
+/// This is a socket opened by tokio, or by the runtime registered with [`Config::runtime`](crate::Config::runtime).
+///
+/// Requires the `runtime-core` Cargo feature (enabled by default).
 #[derive(Debug)]
 pub struct Socket(Inner);
 
 impl Socket {
+    #[cfg(feature = "runtime")]
     pub(crate) fn new_tcp(stream: TcpStream) -> Socket {
         Socket(Inner::Tcp(stream))
     }
 
-    #[cfg(unix)]
+    #[cfg(all(unix, feature = "runtime"))]
     pub(crate) fn new_unix(stream: UnixStream) -> Socket {
         Socket(Inner::Unix(stream))
     }
+
+    pub(crate) fn new_custom(stream: Box<dyn AsyncStream>) -> Socket {
+        Socket(Inner::Custom(stream))
+    }
 }
 
 impl AsyncRead for Socket {
@@ -37,9 +59,11 @@ impl AsyncRead for Socket {
         buf: &mut ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
         match &mut self.0 {
+            #[cfg(feature = "runtime")]
             Inner::Tcp(s) => Pin::new(s).poll_read(cx, buf),
-            #[cfg(unix)]
+            #[cfg(all(unix, feature = "runtime"))]
             Inner::Unix(s) => Pin::new(s).poll_read(cx, buf),
+            Inner::Custom(s) => Pin::new(s).poll_read(cx, buf),
         }
     }
 }
@@ -51,25 +75,31 @@ impl AsyncWrite for Socket {
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
         match &mut self.0 {
+            #[cfg(feature = "runtime")]
             Inner::Tcp(s) => Pin::new(s).poll_write(cx, buf),
-            #[cfg(unix)]
+            #[cfg(all(unix, feature = "runtime"))]
             Inner::Unix(s) => Pin::new(s).poll_write(cx, buf),
+            Inner::Custom(s) => Pin::new(s).poll_write(cx, buf),
         }
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
         match &mut self.0 {
+            #[cfg(feature = "runtime")]
             Inner::Tcp(s) => Pin::new(s).poll_flush(cx),
-            #[cfg(unix)]
+            #[cfg(all(unix, feature = "runtime"))]
             Inner::Unix(s) => Pin::new(s).poll_flush(cx),
+            Inner::Custom(s) => Pin::new(s).poll_flush(cx),
         }
     }
 
     fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
         match &mut self.0 {
+            #[cfg(feature = "runtime")]
             Inner::Tcp(s) => Pin::new(s).poll_shutdown(cx),
-            #[cfg(unix)]
+            #[cfg(all(unix, feature = "runtime"))]
             Inner::Unix(s) => Pin::new(s).poll_shutdown(cx),
+            Inner::Custom(s) => Pin::new(s).poll_shutdown(cx),
         }
     }
 }
@@ -0,0 +1,304 @@
+use crate::config::Proxy;
+use crate::connect_socket::connect_socket;
+use crate::keepalive::KeepaliveConfig;
+use crate::resolver::ResolverHandle;
+use crate::rt::{Runtime, RuntimeHandle};
+use crate::{Error, Resolver, Socket};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The address of a server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "with-serde-1",
+    derive(serde_1::Serialize, serde_1::Deserialize),
+    serde(crate = "serde_1")
+)]
+#[non_exhaustive]
+pub enum Addr {
+    /// An IP address.
+    Tcp(IpAddr),
+    /// A host name, to be resolved by the configured proxy.
+    Proxied(String),
+    /// A path to a directory containing the server's Unix socket.
+    ///
+    /// This variant is only available on Unix platforms.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// The configuration of the socket a [`Client`](crate::Client) is connected over.
+///
+/// This is available from [`Client::socket_config`](crate::Client::socket_config) for clients created with
+/// [`Config::connect`](crate::Config::connect), and can be used to open another socket to the same server with the
+/// same settings, for example to implement custom cancellation logic. Configurations can also be created with a
+/// [`SocketConfigBuilder`].
+///
+/// Requires the `runtime-core` Cargo feature (enabled by default).
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "with-serde-1",
+    derive(serde_1::Serialize, serde_1::Deserialize),
+    serde(crate = "serde_1")
+)]
+pub struct SocketConfig {
+    pub(crate) addr: Addr,
+    pub(crate) hostname: Option<String>,
+    pub(crate) port: u16,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) tcp_user_timeout: Option<Duration>,
+    pub(crate) keepalive: Option<KeepaliveConfig>,
+    pub(crate) proxy: Option<Proxy>,
+    pub(crate) local_address: Option<IpAddr>,
+    pub(crate) bind_interface: Option<String>,
+    pub(crate) ssl_root_cert: Option<PathBuf>,
+    pub(crate) ssl_cert: Option<PathBuf>,
+    pub(crate) ssl_key: Option<PathBuf>,
+    #[cfg_attr(feature = "with-serde-1", serde(skip))]
+    pub(crate) resolver: Option<ResolverHandle>,
+    #[cfg_attr(feature = "with-serde-1", serde(skip))]
+    pub(crate) runtime: Option<RuntimeHandle>,
+}
+
+impl SocketConfig {
+    /// Returns a builder for a configuration connecting to the server at `addr` and `port`.
+    pub fn builder(addr: Addr, port: u16) -> SocketConfigBuilder {
+        SocketConfigBuilder {
+            config: SocketConfig {
+                addr,
+                hostname: None,
+                port,
+                connect_timeout: None,
+                tcp_user_timeout: None,
+                keepalive: None,
+                proxy: None,
+                local_address: None,
+                bind_interface: None,
+                ssl_root_cert: None,
+                ssl_cert: None,
+                ssl_key: None,
+                resolver: None,
+                runtime: None,
+            },
+            keepalives: true,
+            keepalive_config: KeepaliveConfig {
+                idle: Duration::from_secs(2 * 60 * 60),
+                interval: None,
+                retries: None,
+            },
+        }
+    }
+
+    /// Returns the address of the server.
+    pub fn addr(&self) -> &Addr {
+        &self.addr
+    }
+
+    /// Returns the host name of the server, used to verify its TLS certificate.
+    pub fn hostname(&self) -> Option<&str> {
+        self.hostname.as_deref()
+    }
+
+    /// Returns the port of the server.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Returns the connection timeout.
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// Returns the TCP user timeout.
+    pub fn tcp_user_timeout(&self) -> Option<Duration> {
+        self.tcp_user_timeout
+    }
+
+    /// Returns the amount of idle time before a keepalive packet is sent, or `None` if TCP keepalive is disabled.
+    pub fn keepalives_idle(&self) -> Option<Duration> {
+        self.keepalive.as_ref().map(|k| k.idle)
+    }
+
+    /// Returns the time interval between TCP keepalive probes.
+    pub fn keepalives_interval(&self) -> Option<Duration> {
+        self.keepalive.as_ref().and_then(|k| k.interval)
+    }
+
+    /// Returns the maximum number of TCP keepalive probes that will be sent before dropping a connection.
+    pub fn keepalives_retries(&self) -> Option<u32> {
+        self.keepalive.as_ref().and_then(|k| k.retries)
+    }
+
+    /// Returns the proxy through which the connection is established.
+    pub fn proxy(&self) -> Option<&Proxy> {
+        self.proxy.as_ref()
+    }
+
+    /// Returns the local IP address the connection is bound to.
+    pub fn local_address(&self) -> Option<IpAddr> {
+        self.local_address
+    }
+
+    /// Returns the name of the network interface the connection is bound to.
+    pub fn bind_interface(&self) -> Option<&str> {
+        self.bind_interface.as_deref()
+    }
+
+    /// Returns the path to the file containing the certificate authorities used to verify the server's certificate.
+    pub fn ssl_root_cert(&self) -> Option<&Path> {
+        self.ssl_root_cert.as_deref()
+    }
+
+    /// Returns the path to the file containing the client certificate to present to the server.
+    pub fn ssl_cert(&self) -> Option<&Path> {
+        self.ssl_cert.as_deref()
+    }
+
+    /// Returns the path to the file containing the private key of the client certificate.
+    pub fn ssl_key(&self) -> Option<&Path> {
+        self.ssl_key.as_deref()
+    }
+
+    /// Returns the resolver used to look up the address of the proxy.
+    ///
+    /// The resolver is not included when the configuration is serialized.
+    pub fn resolver(&self) -> Option<&dyn Resolver> {
+        self.resolver.as_ref().map(|r| &*r.0)
+    }
+
+    /// Returns the runtime used to open the socket, if one has been set.
+    ///
+    /// The runtime is not included when the configuration is serialized.
+    pub fn runtime(&self) -> Option<&dyn Runtime> {
+        self.runtime.as_ref().map(|r| &*r.0)
+    }
+
+    /// Opens a socket to the server.
+    ///
+    /// The socket can be passed to [`Config::connect_raw`](crate::Config::connect_raw) to establish a connection over
+    /// it.
+    pub async fn connect(&self) -> Result<Socket, Error> {
+        connect_socket(self).await
+    }
+}
+
+/// A builder for [`SocketConfig`]s.
+pub struct SocketConfigBuilder {
+    config: SocketConfig,
+    keepalives: bool,
+    keepalive_config: KeepaliveConfig,
+}
+
+impl SocketConfigBuilder {
+    /// Sets the host name of the server, used to verify its TLS certificate.
+    pub fn hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.config.hostname = Some(hostname.into());
+        self
+    }
+
+    /// Sets the timeout applied to socket-level connection attempts.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.config.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Sets the TCP user timeout.
+    pub fn tcp_user_timeout(mut self, tcp_user_timeout: Duration) -> Self {
+        self.config.tcp_user_timeout = Some(tcp_user_timeout);
+        self
+    }
+
+    /// Controls the use of TCP keepalive.
+    ///
+    /// Defaults to `true`.
+    pub fn keepalives(mut self, keepalives: bool) -> Self {
+        self.keepalives = keepalives;
+        self
+    }
+
+    /// Sets the amount of idle time before a keepalive packet is sent on the connection.
+    ///
+    /// Defaults to 2 hours.
+    pub fn keepalives_idle(mut self, keepalives_idle: Duration) -> Self {
+        self.keepalive_config.idle = keepalives_idle;
+        self
+    }
+
+    /// Sets the time interval between TCP keepalive probes.
+    pub fn keepalives_interval(mut self, keepalives_interval: Duration) -> Self {
+        self.keepalive_config.interval = Some(keepalives_interval);
+        self
+    }
+
+    /// Sets the maximum number of TCP keepalive probes that will be sent before dropping a connection.
+    pub fn keepalives_retries(mut self, keepalives_retries: u32) -> Self {
+        self.keepalive_config.retries = Some(keepalives_retries);
+        self
+    }
+
+    /// Sets a proxy through which the connection is established.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.config.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets the local IP address the connection is bound to.
+    pub fn local_address(mut self, local_address: IpAddr) -> Self {
+        self.config.local_address = Some(local_address);
+        self
+    }
+
+    /// Sets the name of the network interface the connection is bound to.
+    pub fn bind_interface(mut self, bind_interface: impl Into<String>) -> Self {
+        self.config.bind_interface = Some(bind_interface.into());
+        self
+    }
+
+    /// Sets the path to the file containing the certificate authorities used to verify the server's certificate.
+    pub fn ssl_root_cert(mut self, ssl_root_cert: impl Into<PathBuf>) -> Self {
+        self.config.ssl_root_cert = Some(ssl_root_cert.into());
+        self
+    }
+
+    /// Sets the path to the file containing the client certificate to present to the server.
+    pub fn ssl_cert(mut self, ssl_cert: impl Into<PathBuf>) -> Self {
+        self.config.ssl_cert = Some(ssl_cert.into());
+        self
+    }
+
+    /// Sets the path to the file containing the private key of the client certificate.
+    pub fn ssl_key(mut self, ssl_key: impl Into<PathBuf>) -> Self {
+        self.config.ssl_key = Some(ssl_key.into());
+        self
+    }
+
+    /// Sets the resolver used to look up the address of the proxy.
+    pub fn resolver<R>(mut self, resolver: R) -> Self
+    where
+        R: Resolver + 'static,
+    {
+        self.config.resolver = Some(ResolverHandle(Arc::new(resolver)));
+        self
+    }
+
+    /// Sets the runtime used to open the socket.
+    ///
+    /// Defaults to tokio if the `runtime` Cargo feature is enabled.
+    pub fn runtime<R>(mut self, runtime: R) -> Self
+    where
+        R: Runtime + 'static,
+    {
+        self.config.runtime = Some(RuntimeHandle(Arc::new(runtime)));
+        self
+    }
+
+    /// Builds the configuration.
+    pub fn build(mut self) -> SocketConfig {
+        if self.keepalives {
+            self.config.keepalive = Some(self.keepalive_config);
+        }
+        self.config
+    }
+}
@@ -8,6 +8,7 @@ use std::sync::{Arc, Weak};
 struct StatementInner {
     client: Weak<InnerClient>,
     name: String,
+    query: String,
     params: Vec<Type>,
     columns: Vec<Column>,
 }
@@ -39,21 +40,24 @@ impl Statement {
     pub(crate) fn new(
         inner: &Arc<InnerClient>,
         name: String,
+        query: String,
         params: Vec<Type>,
         columns: Vec<Column>,
     ) -> Statement {
         Statement(Arc::new(StatementInner {
             client: Arc::downgrade(inner),
             name,
+            query,
             params,
             columns,
         }))
     }
 
-    pub(crate) fn unnamed(params: Vec<Type>, columns: Vec<Column>) -> Statement {
+    pub(crate) fn unnamed(query: String, params: Vec<Type>, columns: Vec<Column>) -> Statement {
         Statement(Arc::new(StatementInner {
             client: Weak::new(),
             name: String::new(),
+            query,
             params,
             columns,
         }))
@@ -63,6 +67,10 @@ impl Statement {
         &self.0.name
     }
 
+    pub(crate) fn query(&self) -> &str {
+        &self.0.query
+    }
+
     /// Returns the expected types of the statement's parameters.
     pub fn params(&self) -> &[Type] {
         &self.0.params
@@ -0,0 +1,116 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Counters updated by the connection as it exchanges messages with the server.
+#[derive(Default)]
+pub(crate) struct Stats {
+    statements: AtomicU64,
+    rows: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    prepares: AtomicU64,
+    errors: AtomicU64,
+    latency_micros: AtomicU64,
+}
+
+impl Stats {
+    pub fn add_statement(&self) {
+        self.statements.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_row(&self) {
+        self.rows.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_bytes_sent(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_bytes_received(&self, bytes: usize) {
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_prepare(&self) {
+        self.prepares.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_latency(&self, latency: Duration) {
+        self.latency_micros
+            .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> ClientStats {
+        ClientStats {
+            statements: self.statements.load(Ordering::Relaxed),
+            rows: self.rows.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            prepares: self.prepares.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            latency: Duration::from_micros(self.latency_micros.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// A snapshot of the execution statistics of a connection.
+///
+/// The counters include the queries the client issues internally, such as those used to look up type information,
+/// and cover the whole lifetime of the connection, starting with the startup handshake.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientStats {
+    statements: u64,
+    rows: u64,
+    bytes_sent: u64,
+    bytes_received: u64,
+    prepares: u64,
+    errors: u64,
+    latency: Duration,
+}
+
+impl ClientStats {
+    /// Returns the number of statements which have completed successfully.
+    pub fn statements(&self) -> u64 {
+        self.statements
+    }
+
+    /// Returns the number of rows returned by the server.
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// Returns the number of bytes sent to the server.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    /// Returns the number of bytes received from the server.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// Returns the number of statements which have been parsed by the server.
+    ///
+    /// This includes the unnamed statements used by methods like `query_typed` as well as explicitly prepared ones.
+    pub fn prepares(&self) -> u64 {
+        self.prepares
+    }
+
+    /// Returns the number of errors reported by the server.
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// Returns the total time spent waiting on requests, measured from when each request is sent until the server
+    /// has finished responding to it.
+    ///
+    /// Pipelined requests are timed individually, so this can exceed the wall-clock time the connection has been
+    /// open.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+}
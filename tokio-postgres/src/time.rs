@@ -0,0 +1,25 @@
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) use std::time::Instant;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) use self::wasm::Instant;
+
+// `std::time::Instant::now` panics on wasm32-unknown-unknown, which has no clock, so elapsed times are reported as zero
+// there instead.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+mod wasm {
+    use std::time::Duration;
+
+    #[derive(Debug, Copy, Clone)]
+    pub(crate) struct Instant;
+
+    impl Instant {
+        pub(crate) fn now() -> Instant {
+            Instant
+        }
+
+        pub(crate) fn elapsed(&self) -> Duration {
+            Duration::ZERO
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! TLS support.
 
+#[cfg(feature = "runtime-core")]
+use crate::SocketConfig;
 use std::error::Error;
 use std::future::Future;
 use std::pin::Pin;
@@ -34,8 +36,8 @@ impl ChannelBinding {
 
 /// A constructor of `TlsConnect`ors.
 ///
-/// Requires the `runtime` Cargo feature (enabled by default).
-#[cfg(feature = "runtime")]
+/// Requires the `runtime-core` Cargo feature (enabled by default).
+#[cfg(feature = "runtime-core")]
 pub trait MakeTlsConnect<S> {
     /// The stream type created by the `TlsConnect` implementation.
     type Stream: TlsStream + Unpin;
@@ -48,6 +50,18 @@ pub trait MakeTlsConnect<S> {
     ///
     /// The domain name is provided for certificate verification and SNI.
     fn make_tls_connect(&mut self, domain: &str) -> Result<Self::TlsConnect, Self::Error>;
+
+    /// Creates a new `TlsConnect`or for a connection over a socket with the specified configuration.
+    ///
+    /// This is what the crate calls when connecting. Implementations can override it to use the certificate and key
+    /// files set with the `sslrootcert`, `sslcert`, and `sslkey` options, which are available from `config`. The
+    /// default implementation ignores them and calls `make_tls_connect` with the host name of the server.
+    fn make_tls_connect_for(
+        &mut self,
+        config: &SocketConfig,
+    ) -> Result<Self::TlsConnect, Self::Error> {
+        self.make_tls_connect(config.hostname().unwrap_or(""))
+    }
 }
 
 /// An asynchronous function wrapping a stream in a TLS session.
@@ -80,7 +94,7 @@ pub trait TlsStream: AsyncRead + AsyncWrite {
 #[derive(Debug, Copy, Clone)]
 pub struct NoTls;
 
-#[cfg(feature = "runtime")]
+#[cfg(feature = "runtime-core")]
 impl<S> MakeTlsConnect<S> for NoTls {
     type Stream = NoTlsStream;
     type TlsConnect = NoTls;
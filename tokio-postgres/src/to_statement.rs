@@ -2,6 +2,7 @@ use crate::to_statement::private::{Sealed, ToStatementType};
 use crate::Statement;
 
 mod private {
+    use crate::types::Type;
     use crate::{Client, Error, Statement};
 
     pub trait Sealed {}
@@ -11,7 +12,22 @@ mod private {
         Query(&'a str),
     }
 
-    impl ToStatementType<'_> {
+    impl<'a> ToStatementType<'a> {
+        pub fn query(&self) -> &'a str {
+            match self {
+                ToStatementType::Statement(s) => s.query(),
+                ToStatementType::Query(s) => s,
+            }
+        }
+
+        /// Returns the parameter types the statement was prepared with, which are empty for a query string.
+        pub fn param_types(&self) -> &'a [Type] {
+            match self {
+                ToStatementType::Statement(s) => s.params(),
+                ToStatementType::Query(_) => &[],
+            }
+        }
+
         pub async fn into_statement(self, client: &Client) -> Result<Statement, Error> {
             match self {
                 ToStatementType::Statement(s) => Ok(s.clone()),
@@ -1,16 +1,16 @@
 use crate::codec::FrontendMessage;
 use crate::connection::RequestMessages;
 use crate::copy_out::CopyOutStream;
-use crate::query::RowStream;
-#[cfg(feature = "runtime")]
+use crate::query::{RowStream, TextRowStream};
+#[cfg(feature = "runtime-core")]
 use crate::tls::MakeTlsConnect;
 use crate::tls::TlsConnect;
-use crate::types::{BorrowToSql, ToSql, Type};
-#[cfg(feature = "runtime")]
+use crate::types::{BorrowToSql, FromRowOwned, ToSql, Type};
+#[cfg(feature = "runtime-core")]
 use crate::Socket;
 use crate::{
-    bind, query, slice_iter, CancelToken, Client, CopyInSink, Error, Portal, Row,
-    SimpleQueryMessage, Statement, ToStatement,
+    bind, query, slice_iter, BatchResult, CancelToken, Client, CopyInSink, CursorStream, Error,
+    Pipeline, Portal, QueryOptions, Row, RowStreamAs, SimpleQueryMessage, Statement, ToStatement,
 };
 use bytes::Buf;
 use futures_util::TryStreamExt;
@@ -114,6 +114,40 @@ impl<'a> Transaction<'a> {
         self.client.query(statement, params).await
     }
 
+    /// Like `Client::query_as`.
+    pub async fn query_as<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, Error>
+    where
+        R: FromRowOwned,
+    {
+        self.client.query_as(statement, params).await
+    }
+
+    /// Like `Client::insert_many`.
+    pub async fn insert_many(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[&[&(dyn ToSql + Sync)]],
+    ) -> Result<u64, Error> {
+        self.client.insert_many(table, columns, rows).await
+    }
+
+    /// Like `Client::query_stream_as`.
+    pub async fn query_stream_as<R>(
+        &self,
+        statement: &(impl ?Sized + ToStatement),
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<RowStreamAs<R>, Error>
+    where
+        R: FromRowOwned,
+    {
+        self.client.query_stream_as(statement, params).await
+    }
+
     /// Like `Client::query_one`.
     pub async fn query_one<T>(
         &self,
@@ -138,6 +172,21 @@ impl<'a> Transaction<'a> {
         self.client.query_opt(statement, params).await
     }
 
+    /// Like `Client::query_with_options`.
+    pub async fn query_with_options<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        options: &QueryOptions,
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.client
+            .query_with_options(statement, params, options)
+            .await
+    }
+
     /// Like `Client::query_raw`.
     pub async fn query_raw<T, P, I>(&self, statement: &T, params: I) -> Result<RowStream, Error>
     where
@@ -149,6 +198,36 @@ impl<'a> Transaction<'a> {
         self.client.query_raw(statement, params).await
     }
 
+    /// Like `Client::query_raw_text`.
+    pub async fn query_raw_text<T, P, I>(
+        &self,
+        statement: &T,
+        params: I,
+    ) -> Result<TextRowStream, Error>
+    where
+        T: ?Sized + ToStatement,
+        P: BorrowToSql,
+        I: IntoIterator<Item = P>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.client.query_raw_text(statement, params).await
+    }
+
+    /// Like `Client::query_cursor`.
+    pub async fn query_cursor<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        fetch_size: i32,
+    ) -> Result<CursorStream, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.client
+            .query_cursor(statement, params, fetch_size)
+            .await
+    }
+
     /// Like `Client::query_typed`.
     pub async fn query_typed(
         &self,
@@ -179,6 +258,21 @@ impl<'a> Transaction<'a> {
         self.client.execute(statement, params).await
     }
 
+    /// Like `Client::execute_with_options`.
+    pub async fn execute_with_options<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+        options: &QueryOptions,
+    ) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+    {
+        self.client
+            .execute_with_options(statement, params, options)
+            .await
+    }
+
     /// Like `Client::execute_iter`.
     pub async fn execute_raw<P, I, T>(&self, statement: &T, params: I) -> Result<u64, Error>
     where
@@ -262,6 +356,24 @@ impl<'a> Transaction<'a> {
         self.client.copy_out(statement).await
     }
 
+    /// Like `Client::copy_in_from`.
+    pub async fn copy_in_from<T, R>(&self, statement: &T, reader: R) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+        R: AsyncRead + Unpin,
+    {
+        self.client.copy_in_from(statement, reader).await
+    }
+
+    /// Like `Client::copy_out_to`.
+    pub async fn copy_out_to<T, W>(&self, statement: &T, writer: W) -> Result<u64, Error>
+    where
+        T: ?Sized + ToStatement,
+        W: AsyncWrite + Unpin,
+    {
+        self.client.copy_out_to(statement, writer).await
+    }
+
     /// Like `Client::simple_query`.
     pub async fn simple_query(&self, query: &str) -> Result<Vec<SimpleQueryMessage>, Error> {
         self.client.simple_query(query).await
@@ -272,13 +384,18 @@ impl<'a> Transaction<'a> {
         self.client.batch_execute(query).await
     }
 
+    /// Like `Client::batch_execute_results`.
+    pub async fn batch_execute_results(&self, query: &str) -> Result<Vec<BatchResult>, Error> {
+        self.client.batch_execute_results(query).await
+    }
+
     /// Like `Client::cancel_token`.
     pub fn cancel_token(&self) -> CancelToken {
         self.client.cancel_token()
     }
 
     /// Like `Client::cancel_query`.
-    #[cfg(feature = "runtime")]
+    #[cfg(feature = "runtime-core")]
     #[deprecated(since = "0.6.0", note = "use Transaction::cancel_token() instead")]
     pub async fn cancel_query<T>(&self, tls: T) -> Result<(), Error>
     where
@@ -299,6 +416,11 @@ impl<'a> Transaction<'a> {
         self.client.cancel_query_raw(stream, tls).await
     }
 
+    /// Like `Client::pipeline`.
+    pub fn pipeline(&self) -> Pipeline<'_> {
+        self.client.pipeline()
+    }
+
     /// Like `Client::transaction`, but creates a nested transaction via a savepoint.
     pub async fn transaction(&mut self) -> Result<Transaction<'_>, Error> {
         self._savepoint(None).await
@@ -1,7 +1,8 @@
 use crate::connect;
 use futures_util::{pin_mut, TryStreamExt};
+use std::sync::{Arc, Mutex};
 use tokio_postgres::binary_copy::{BinaryCopyInWriter, BinaryCopyOutStream};
-use tokio_postgres::types::Type;
+use tokio_postgres::types::{FromSqlRow, ToSqlRow, Type};
 
 #[tokio::test]
 async fn write_basic() {
@@ -201,3 +202,110 @@ async fn read_big_rows() {
         assert_eq!(row.get::<&[u8]>(1), &vec![i as u8; 128 * 1024][..]);
     }
 }
+
+#[derive(Debug, PartialEq, ToSqlRow, FromSqlRow)]
+struct Foo {
+    id: i32,
+    bar: Option<String>,
+}
+
+#[tokio::test]
+async fn write_read_struct() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT, bar TEXT)")
+        .await
+        .unwrap();
+
+    let expected = vec![
+        Foo {
+            id: 1,
+            bar: Some("foobar".to_string()),
+        },
+        Foo { id: 2, bar: None },
+    ];
+
+    let sink = client
+        .copy_in("COPY foo (id, bar) FROM STDIN BINARY")
+        .await
+        .unwrap();
+    let writer = BinaryCopyInWriter::new(sink, &[Type::INT4, Type::TEXT]);
+    pin_mut!(writer);
+    for foo in &expected {
+        writer.as_mut().write_struct(foo).await.unwrap();
+    }
+    writer.finish().await.unwrap();
+
+    let stream = client
+        .copy_out("COPY (SELECT id, bar FROM foo ORDER BY id) TO STDOUT BINARY")
+        .await
+        .unwrap();
+    let rows = BinaryCopyOutStream::new(stream, &[Type::INT4, Type::TEXT])
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    let actual = rows
+        .iter()
+        .map(|row| row.get_struct())
+        .collect::<Vec<Foo>>();
+    assert_eq!(actual, expected);
+
+    #[allow(dead_code)]
+    #[derive(Debug, FromSqlRow)]
+    struct TooWide(i32, Option<String>, i32);
+
+    assert!(rows[0].try_get_struct::<TooWide>().is_err());
+}
+
+#[tokio::test]
+async fn progress() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT, bar TEXT)")
+        .await
+        .unwrap();
+
+    let progress = Arc::new(Mutex::new(vec![]));
+
+    let sink = client
+        .copy_in("COPY foo (id, bar) FROM STDIN BINARY")
+        .await
+        .unwrap();
+    let writer = BinaryCopyInWriter::new(sink, &[Type::INT4, Type::TEXT]);
+    pin_mut!(writer);
+    writer.as_mut().set_progress_callback({
+        let progress = progress.clone();
+        move |p| progress.lock().unwrap().push(p)
+    });
+    for i in 0..1000 {
+        writer
+            .as_mut()
+            .write(&[&i, &format!("the value for {}", i)])
+            .await
+            .unwrap();
+    }
+    writer.finish().await.unwrap();
+
+    let reports = progress.lock().unwrap().split_off(0);
+    assert!(reports.len() > 1);
+    assert!(reports.windows(2).all(|w| w[0].rows() < w[1].rows()));
+    assert_eq!(reports.last().unwrap().rows(), Some(1000));
+
+    let stream = client
+        .copy_out("COPY foo (id, bar) TO STDOUT BINARY")
+        .await
+        .unwrap();
+    let stream = BinaryCopyOutStream::new(stream, &[Type::INT4, Type::TEXT]);
+    pin_mut!(stream);
+    stream.as_mut().set_progress_callback({
+        let progress = progress.clone();
+        move |p| progress.lock().unwrap().push(p)
+    });
+    while stream.try_next().await.unwrap().is_some() {}
+
+    let reports = progress.lock().unwrap().split_off(0);
+    assert_eq!(reports.len(), 1000);
+    assert_eq!(reports.last().unwrap().rows(), Some(1000));
+}
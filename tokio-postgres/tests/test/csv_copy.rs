@@ -0,0 +1,135 @@
+use crate::connect;
+use futures_util::{pin_mut, stream, TryStreamExt};
+use serde_1::{Deserialize, Serialize};
+use tokio_postgres::csv_copy::{CsvCopyInWriter, CsvCopyOutStream};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "serde_1")]
+struct Foo {
+    id: i32,
+    bar: Option<String>,
+    baz: Option<f64>,
+}
+
+#[tokio::test]
+async fn write_basic() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT, bar TEXT, baz FLOAT8)")
+        .await
+        .unwrap();
+
+    let sink = client
+        .copy_in("COPY foo (id, bar, baz) FROM STDIN (FORMAT csv)")
+        .await
+        .unwrap();
+    let writer = CsvCopyInWriter::new(sink);
+    pin_mut!(writer);
+    writer
+        .as_mut()
+        .write(&Foo {
+            id: 1,
+            bar: Some("a \"quoted\", multiline\nvalue".to_string()),
+            baz: Some(1.5),
+        })
+        .await
+        .unwrap();
+    writer
+        .as_mut()
+        .write(&Foo {
+            id: 2,
+            bar: Some(String::new()),
+            baz: None,
+        })
+        .await
+        .unwrap();
+    writer
+        .as_mut()
+        .write(&Foo {
+            id: 3,
+            bar: None,
+            baz: Some(f64::INFINITY),
+        })
+        .await
+        .unwrap();
+    assert_eq!(writer.finish().await.unwrap(), 3);
+
+    let rows = client
+        .query("SELECT id, bar, baz FROM foo ORDER BY id", &[])
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(
+        rows[0].get::<_, Option<&str>>(1),
+        Some("a \"quoted\", multiline\nvalue")
+    );
+    assert_eq!(rows[0].get::<_, Option<f64>>(2), Some(1.5));
+    assert_eq!(rows[1].get::<_, Option<&str>>(1), Some(""));
+    assert_eq!(rows[1].get::<_, Option<f64>>(2), None);
+    assert_eq!(rows[2].get::<_, Option<&str>>(1), None);
+    assert_eq!(rows[2].get::<_, Option<f64>>(2), Some(f64::INFINITY));
+}
+
+#[tokio::test]
+async fn round_trip_header() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT, bar TEXT, baz FLOAT8)")
+        .await
+        .unwrap();
+
+    let expected = (0..1_000)
+        .map(|i| Foo {
+            id: i,
+            bar: if i % 2 == 0 {
+                Some(format!("the value, \"for\"\r\n{}", i))
+            } else {
+                None
+            },
+            baz: Some(f64::from(i) / 4.),
+        })
+        .collect::<Vec<_>>();
+
+    let sink = client
+        .copy_in("COPY foo (id, bar, baz) FROM STDIN (FORMAT csv, HEADER true, NULL 'NULL')")
+        .await
+        .unwrap();
+    let writer = CsvCopyInWriter::new(sink).header(true).null("NULL");
+    pin_mut!(writer);
+    writer
+        .as_mut()
+        .write_all(stream::iter(expected.clone()))
+        .await
+        .unwrap();
+    writer.finish().await.unwrap();
+
+    // columns are deliberately reordered to check that fields are matched by name
+    let stream = client
+        .copy_out("COPY (SELECT baz, bar, id FROM foo ORDER BY id) TO STDOUT (FORMAT csv, HEADER true, NULL 'NULL')")
+        .await
+        .unwrap();
+    let actual = CsvCopyOutStream::<Foo>::new(stream)
+        .header(true)
+        .null("NULL")
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[tokio::test]
+async fn read_tuples() {
+    let client = connect("user=postgres").await;
+
+    let stream = client
+        .copy_out("COPY (VALUES (1, NULL, true), (2, '', false)) TO STDOUT (FORMAT csv)")
+        .await
+        .unwrap();
+    let rows = CsvCopyOutStream::<(i32, Option<String>, bool)>::new(stream)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(rows, vec![(1, None, true), (2, Some(String::new()), false)]);
+}
@@ -6,22 +6,35 @@ use futures_util::{
     future, join, pin_mut, stream, try_join, Future, FutureExt, SinkExt, StreamExt, TryStreamExt,
 };
 use pin_project_lite::pin_project;
+use postgres_protocol::message::{backend, frontend};
 use std::fmt::Write;
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tokio::time;
 use tokio_postgres::error::SqlState;
 use tokio_postgres::tls::{NoTls, NoTlsStream};
-use tokio_postgres::types::{Kind, Type};
+use tokio_postgres::types::{FromRow, Kind, ToSql, Type};
 use tokio_postgres::{
-    AsyncMessage, Client, Config, Connection, Error, IsolationLevel, SimpleQueryMessage,
+    AsyncMessage, CancelToken, Client, Config, Connection, Error, IsolationLevel, QueryBuilder,
+    QueryOptions, SimpleQueryMessage, StatementHook, StatementInfo, StatementResult,
 };
 
 mod binary_copy;
+#[cfg(feature = "with-serde-1")]
+mod csv_copy;
+#[cfg(feature = "mock")]
+mod mock;
 mod parse;
 #[cfg(feature = "runtime")]
+mod pool;
+#[cfg(feature = "runtime")]
+mod reconnect;
+mod replication;
+#[cfg(feature = "runtime")]
 mod runtime;
 mod types;
 
@@ -147,6 +160,87 @@ async fn scram_password_ok() {
     connect("user=scram_user password=password dbname=postgres").await;
 }
 
+#[tokio::test]
+async fn raw_connection() {
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let config = "user=postgres application_name=raw"
+        .parse::<Config>()
+        .unwrap();
+    let mut conn = config.connect_raw_stream(socket, NoTls).await.unwrap();
+    assert_ne!(conn.process_id(), 0);
+    assert_eq!(conn.parameters()["application_name"], "raw");
+    assert_eq!(conn.cancel_token().process_id(), conn.process_id());
+
+    let mut buf = BytesMut::new();
+    frontend::query("SELECT 1", &mut buf).unwrap();
+    conn.write_all(&buf).await.unwrap();
+
+    let mut buf = BytesMut::new();
+    let mut tags = vec![];
+    while tags.last() != Some(&b'Z') {
+        conn.read_buf(&mut buf).await.unwrap();
+        while let Some(header) = backend::Header::parse(&buf).unwrap() {
+            let len = header.len() as usize + 1;
+            if buf.len() < len {
+                break;
+            }
+            tags.push(header.tag());
+            let _ = buf.split_to(len);
+        }
+    }
+    assert_eq!(tags, b"TDCZ");
+}
+
+#[tokio::test]
+async fn connect_stream() {
+    // relay an in-memory pipe to the server to stand in for a custom transport
+    let (stream, mut relay) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let mut socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+        let _ = tokio::io::copy_bidirectional(&mut relay, &mut socket).await;
+    });
+
+    let config = "user=postgres".parse::<Config>().unwrap();
+    let (client, connection) = tokio_postgres::connect_stream(stream, NoTls, &config)
+        .await
+        .unwrap();
+    let connection = tokio::spawn(connection);
+
+    let rows = client.query("SELECT 1::INT4", &[]).await.unwrap();
+    assert_eq!(rows[0].get::<_, i32>(0), 1);
+
+    client.close().await;
+    connection.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn require_auth() {
+    connect("user=scram_user password=password dbname=postgres require_auth=scram-sha-256").await;
+    connect("user=pass_user password=password dbname=postgres require_auth=!md5,!none").await;
+
+    for s in [
+        "user=pass_user password=password dbname=postgres require_auth=!password",
+        "user=scram_user password=password dbname=postgres require_auth=password,md5",
+        "user=postgres require_auth=scram-sha-256",
+    ] {
+        let e = connect_raw(s).await.err().unwrap();
+        assert!(e.to_string().contains("require_auth"), "{}", e);
+    }
+}
+
+#[tokio::test]
+async fn close() {
+    let (client, connection) = connect_raw("user=postgres").await.unwrap();
+    let connection = tokio::spawn(connection);
+
+    let stmt = client.prepare("SELECT 1").await.unwrap();
+    client.close().await;
+    connection.await.unwrap().unwrap();
+
+    // statements outliving the client are cleaned up along with the connection
+    drop(stmt);
+}
+
 #[tokio::test]
 async fn pipelined_prepare() {
     let client = connect("user=postgres").await;
@@ -331,6 +425,9 @@ async fn simple_query() {
         SimpleQueryMessage::RowDescription(columns) => {
             assert_eq!(columns.get(0).map(|c| c.name()), Some("id"));
             assert_eq!(columns.get(1).map(|c| c.name()), Some("name"));
+            assert_eq!(columns.get(0).map(|c| c.type_oid()), Some(Type::INT4.oid()));
+            assert_eq!(columns.get(1).map(|c| c.type_oid()), Some(Type::TEXT.oid()));
+            assert_eq!(columns.get(0).map(|c| c.format()), Some(0));
         }
         _ => panic!("unexpected message"),
     }
@@ -359,6 +456,127 @@ async fn simple_query() {
     assert_eq!(messages.len(), 6);
 }
 
+#[tokio::test]
+async fn query_raw_text() {
+    let client = connect("user=postgres").await;
+
+    let stream = client
+        .query_raw_text(
+            "SELECT $1::INT4 AS a, 'hello'::TEXT AS b, NULL::FLOAT8 AS c, '{1,2}'::INT4[] AS d",
+            &[&7i32],
+        )
+        .await
+        .unwrap();
+    pin_mut!(stream);
+
+    let columns = stream.columns();
+    assert_eq!(columns[0].name(), "a");
+    assert_eq!(columns[0].type_oid(), Type::INT4.oid());
+    assert_eq!(columns[3].type_oid(), Type::INT4_ARRAY.oid());
+    assert_eq!(columns[0].format(), 0);
+
+    let rows = stream.as_mut().try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get("a"), Some("7"));
+    assert_eq!(rows[0].get(1), Some("hello"));
+    assert_eq!(rows[0].get(2), None);
+    assert_eq!(rows[0].get(3), Some("{1,2}"));
+    assert_eq!(stream.rows_affected(), Some(1));
+}
+
+#[tokio::test]
+async fn row_buffer_size() {
+    "row_buffer_size=0".parse::<Config>().unwrap_err();
+
+    let client = connect("user=postgres row_buffer_size=1").await;
+
+    let stream = client
+        .query_raw("SELECT generate_series(1, 1000)", std::iter::empty::<i32>())
+        .await
+        .unwrap();
+    pin_mut!(stream);
+
+    let mut expected = 1;
+    while let Some(row) = stream.try_next().await.unwrap() {
+        assert_eq!(row.get::<_, i32>(0), expected);
+        expected += 1;
+        if expected % 100 == 0 {
+            time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+    assert_eq!(expected, 1001);
+    assert_eq!(stream.rows_affected(), Some(1000));
+
+    client.batch_execute("SELECT 1").await.unwrap();
+}
+
+#[tokio::test]
+async fn io_buffer_sizes() {
+    "read_buffer_size=0".parse::<Config>().unwrap_err();
+    "write_buffer_size=x".parse::<Config>().unwrap_err();
+
+    let client = connect("user=postgres read_buffer_size=16 write_buffer_size=16").await;
+
+    let rows = client
+        .query(
+            "SELECT repeat('x', 100000) FROM generate_series(1, 10)",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 10);
+    assert_eq!(rows[0].get::<_, &str>(0).len(), 100000);
+
+    let value = "y".repeat(100000);
+    let row = client
+        .query_one("SELECT length($1::TEXT)", &[&value])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i32>(0), 100000);
+}
+
+#[tokio::test]
+async fn statement_timeout_option() {
+    let mut client = connect("user=postgres").await;
+
+    let mut options = QueryOptions::new();
+    options.statement_timeout(Duration::from_millis(50));
+
+    let rows = client
+        .query_with_options("SELECT $1::INT", &[&1i32], &options)
+        .await
+        .unwrap();
+    assert_eq!(rows[0].get::<_, i32>(0), 1);
+
+    let err = client
+        .execute_with_options("SELECT pg_sleep(10)", &[], &options)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::QUERY_CANCELED));
+
+    let setting = client
+        .query_one("SHOW statement_timeout", &[])
+        .await
+        .unwrap();
+    assert_eq!(setting.get::<_, &str>(0), "0");
+
+    client
+        .batch_execute("SET statement_timeout = '10s'")
+        .await
+        .unwrap();
+    let transaction = client.transaction().await.unwrap();
+    transaction
+        .execute_with_options("SELECT 1", &[], &options)
+        .await
+        .unwrap();
+    let setting = transaction
+        .query_one("SHOW statement_timeout", &[])
+        .await
+        .unwrap();
+    assert_eq!(setting.get::<_, &str>(0), "10s");
+    transaction.commit().await.unwrap();
+}
+
 #[tokio::test]
 async fn cancel_query_raw() {
     let client = connect("user=postgres").await;
@@ -376,6 +594,29 @@ async fn cancel_query_raw() {
     }
 }
 
+#[tokio::test]
+async fn cancel_query_from_parts() {
+    let client = connect("user=postgres").await;
+
+    let token = client.cancel_token();
+    let cancel_token = CancelToken::from_parts(
+        token.process_id(),
+        token.secret_key(),
+        token.ssl_mode(),
+        token.ssl_negotiation(),
+    );
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let cancel = cancel_token.cancel_query_raw(socket, NoTls);
+    let cancel = time::sleep(Duration::from_millis(100)).then(|()| cancel);
+
+    let sleep = client.batch_execute("SELECT pg_sleep(100)");
+
+    match join!(sleep, cancel) {
+        (Err(ref e), Ok(())) if e.code() == Some(&SqlState::QUERY_CANCELED) => {}
+        t => panic!("unexpected return: {:?}", t),
+    }
+}
+
 #[tokio::test]
 async fn transaction_commit() {
     let mut client = connect("user=postgres").await;
@@ -643,6 +884,50 @@ async fn copy_in_large() {
     assert_eq!(rows, 10_000);
 }
 
+#[tokio::test]
+async fn copy_progress() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INTEGER, name TEXT)")
+        .await
+        .unwrap();
+
+    let mut data = String::new();
+    for i in 0..10_000 {
+        writeln!(data, "{0}\tname{0}", i).unwrap();
+    }
+
+    let progress = Arc::new(Mutex::new(vec![]));
+    let sink = client.copy_in("COPY foo FROM STDIN").await.unwrap();
+    pin_mut!(sink);
+    sink.as_mut().set_progress_callback({
+        let progress = progress.clone();
+        move |p| progress.lock().unwrap().push(p)
+    });
+    for line in data.lines() {
+        sink.send(Bytes::from(format!("{}\n", line))).await.unwrap();
+    }
+    sink.finish().await.unwrap();
+
+    let reports = progress.lock().unwrap().split_off(0);
+    assert!(reports.len() > 1);
+    assert!(reports.windows(2).all(|w| w[0].bytes() < w[1].bytes()));
+    assert_eq!(reports.last().unwrap().bytes(), data.len() as u64);
+    assert_eq!(reports.last().unwrap().rows(), None);
+
+    let stream = client.copy_out("COPY foo TO STDOUT").await.unwrap();
+    pin_mut!(stream);
+    stream.as_mut().set_progress_callback({
+        let progress = progress.clone();
+        move |p| progress.lock().unwrap().push(p)
+    });
+    while stream.try_next().await.unwrap().is_some() {}
+
+    let last = *progress.lock().unwrap().last().unwrap();
+    assert_eq!(last.bytes(), data.len() as u64);
+}
+
 #[tokio::test]
 async fn copy_in_error() {
     let client = connect("user=postgres").await;
@@ -700,6 +985,99 @@ async fn copy_out() {
     assert_eq!(&data[..], b"1\tjim\n2\tjoe\n");
 }
 
+#[tokio::test]
+async fn copy_in_from() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (
+                id INTEGER,
+                name TEXT
+            )",
+        )
+        .await
+        .unwrap();
+
+    let mut data = String::new();
+    for i in 0..10_000 {
+        writeln!(data, "{0}\tname{0}", i).unwrap();
+    }
+    let rows = client
+        .copy_in_from("COPY foo FROM STDIN", data.as_bytes())
+        .await
+        .unwrap();
+    assert_eq!(rows, 10_000);
+
+    let mut out = vec![];
+    let rows = client
+        .copy_out_to("COPY foo TO STDOUT", &mut out)
+        .await
+        .unwrap();
+    assert_eq!(rows, 10_000);
+    assert_eq!(out, data.as_bytes());
+}
+
+#[tokio::test]
+async fn copy_in_from_error() {
+    struct FailingReader;
+
+    impl tokio::io::AsyncRead for FailingReader {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            _: &mut tokio::io::ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Err(std::io::Error::other("boom")))
+        }
+    }
+
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (
+                id INTEGER,
+                name TEXT
+            )",
+        )
+        .await
+        .unwrap();
+
+    let reader = tokio::io::AsyncReadExt::chain(&b"1\tjim\n"[..], FailingReader);
+    client
+        .copy_in_from("COPY foo FROM STDIN", reader)
+        .await
+        .unwrap_err();
+
+    let rows = client.query("SELECT * FROM foo", &[]).await.unwrap();
+    assert_eq!(rows.len(), 0);
+}
+
+#[tokio::test]
+async fn startup_params() {
+    let mut config = "user=postgres".parse::<Config>().unwrap();
+    config
+        .param("search_path", "pg_catalog")
+        .param("statement_timeout", "1s")
+        .param("statement_timeout", "2s");
+    assert_eq!(config.get_params().len(), 2);
+
+    let socket = TcpStream::connect("127.0.0.1:5433").await.unwrap();
+    let (client, connection) = config.connect_raw(socket, NoTls).await.unwrap();
+    tokio::spawn(connection.map(|r| r.unwrap()));
+
+    let row = client
+        .query_one(
+            "SELECT current_setting('search_path'), current_setting('statement_timeout')",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, &str>(0), "pg_catalog");
+    assert_eq!(row.get::<_, &str>(1), "2s");
+}
+
 #[tokio::test]
 async fn notices() {
     let long_name = "x".repeat(65);
@@ -776,77 +1154,822 @@ async fn notifications() {
 }
 
 #[tokio::test]
-async fn query_portal() {
-    let mut client = connect("user=postgres").await;
+async fn listen() {
+    let client = connect("user=postgres").await;
+
+    let mut foo = client.listen("test_listen_foo").await.unwrap();
+    let mut bar = client.listen("Test_Listen_Bar").await.unwrap();
 
     client
         .batch_execute(
-            "CREATE TEMPORARY TABLE foo (
-                id SERIAL,
-                name TEXT
-            );
-
-            INSERT INTO foo (name) VALUES ('alice'), ('bob'), ('charlie');",
+            "NOTIFY test_listen_foo, 'hello';
+             NOTIFY \"Test_Listen_Bar\", 'world';
+             NOTIFY test_listen_foo, 'again';",
         )
         .await
         .unwrap();
 
-    let stmt = client
-        .prepare("SELECT id, name FROM foo ORDER BY id")
+    let notification = foo.next().await.unwrap();
+    assert_eq!(notification.channel(), "test_listen_foo");
+    assert_eq!(notification.payload(), "hello");
+    assert_eq!(foo.next().await.unwrap().payload(), "again");
+    assert_eq!(bar.next().await.unwrap().payload(), "world");
+
+    drop(foo);
+    let rows = client
+        .query("SELECT pg_listening_channels()", &[])
         .await
         .unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get::<_, &str>(0), "Test_Listen_Bar");
 
-    let transaction = client.transaction().await.unwrap();
-
-    let portal = transaction.bind(&stmt, &[]).await.unwrap();
-    let f1 = transaction.query_portal(&portal, 2);
-    let f2 = transaction.query_portal(&portal, 2);
-    let f3 = transaction.query_portal(&portal, 2);
-
-    let (r1, r2, r3) = try_join!(f1, f2, f3).unwrap();
+    drop(client);
+    assert!(bar.next().await.is_none());
+}
 
-    assert_eq!(r1.len(), 2);
-    assert_eq!(r1[0].get::<_, i32>(0), 1);
-    assert_eq!(r1[0].get::<_, &str>(1), "alice");
-    assert_eq!(r1[1].get::<_, i32>(0), 2);
-    assert_eq!(r1[1].get::<_, &str>(1), "bob");
+#[tokio::test]
+async fn listen_full() {
+    let client = connect("user=postgres").await;
 
-    assert_eq!(r2.len(), 1);
-    assert_eq!(r2[0].get::<_, i32>(0), 3);
-    assert_eq!(r2[0].get::<_, &str>(1), "charlie");
+    let stream = client.listen("test_listen_full").await.unwrap();
+    client
+        .batch_execute(
+            "SELECT pg_notify('test_listen_full', i::text) FROM generate_series(1, 2000) i",
+        )
+        .await
+        .unwrap();
 
-    assert_eq!(r3.len(), 0);
+    drop(client);
+    let payloads = stream
+        .map(|n| n.payload().to_string())
+        .collect::<Vec<_>>()
+        .await;
+    assert!(
+        payloads.len() >= 1024 && payloads.len() < 2000,
+        "{}",
+        payloads.len()
+    );
+    assert_eq!(payloads[0], "1");
 }
 
+#[cfg(feature = "with-serde_json-1")]
 #[tokio::test]
-async fn require_channel_binding() {
-    connect_raw("user=postgres channel_binding=require")
+async fn listen_json() {
+    #[derive(serde_1::Deserialize, Debug, PartialEq)]
+    #[serde(crate = "serde_1")]
+    struct Event {
+        id: i32,
+        name: String,
+    }
+
+    let client = connect("user=postgres").await;
+
+    let mut events = client
+        .listen_json::<Event>("test_listen_json")
         .await
-        .err()
         .unwrap();
-}
+    let mut raw = client.listen("test_listen_json").await.unwrap();
 
-#[tokio::test]
-async fn prefer_channel_binding() {
-    connect("user=postgres channel_binding=prefer").await;
-}
+    client
+        .batch_execute(
+            "NOTIFY test_listen_json, '{\"id\": 1, \"name\": \"foo\"}';
+             NOTIFY test_listen_json, 'bogus';",
+        )
+        .await
+        .unwrap();
 
-#[tokio::test]
-async fn disable_channel_binding() {
-    connect("user=postgres channel_binding=disable").await;
+    assert_eq!(
+        events.next().await.unwrap().unwrap(),
+        Event {
+            id: 1,
+            name: "foo".to_string(),
+        }
+    );
+    assert!(events.next().await.unwrap().is_err());
+
+    let notification = raw.next().await.unwrap();
+    assert_eq!(
+        notification.payload_as::<serde_json_1::Value>().unwrap(),
+        serde_json_1::json!({"id": 1, "name": "foo"})
+    );
+    assert!(raw.next().await.unwrap().payload_as::<Event>().is_err());
 }
 
 #[tokio::test]
-async fn check_send() {
-    fn is_send<T: Send>(_: &T) {}
+async fn batch_execute_results() {
+    let client = connect("user=postgres").await;
 
-    let f = connect("user=postgres");
-    is_send(&f);
-    let mut client = f.await;
+    let results = client
+        .batch_execute_results(
+            "CREATE TEMPORARY TABLE foo (id INT);
+             INSERT INTO foo VALUES (1), (2), (3);
+             DO $$ BEGIN RAISE NOTICE 'hello'; RAISE NOTICE 'world'; END $$;
+             ;
+             UPDATE foo SET id = id + 1 WHERE id > 1",
+        )
+        .await
+        .unwrap();
 
-    let f = client.prepare("SELECT $1::TEXT");
-    is_send(&f);
-    let stmt = f.await.unwrap();
+    let summary = results
+        .iter()
+        .map(|r| (r.tag(), r.rows_affected(), r.notices().len()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            ("CREATE TABLE", 0, 0),
+            ("INSERT 0 3", 3, 0),
+            ("DO", 0, 2),
+            ("UPDATE 2", 2, 0),
+        ]
+    );
+    assert_eq!(results[2].notices()[0].message(), "hello");
+    assert_eq!(results[2].notices()[1].message(), "world");
+
+    let err = client
+        .batch_execute_results("SELECT 1; SELECT * FROM bogus")
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::UNDEFINED_TABLE));
+}
+
+#[tokio::test]
+async fn notice_stream() {
+    let client = connect("user=postgres").await;
+
+    let mut notices = client.notices();
+
+    client
+        .batch_execute(
+            "DO $$BEGIN RAISE NOTICE 'hello'; END$$;
+             DO $$BEGIN RAISE WARNING 'world'; END$$;",
+        )
+        .await
+        .unwrap();
+
+    let notice = notices.next().await.unwrap();
+    assert_eq!(notice.severity(), "NOTICE");
+    assert_eq!(notice.message(), "hello");
+    assert_eq!(notice.fields()[0], (b'S', "NOTICE".to_string()));
+    assert!(notice.fields().contains(&(b'M', "hello".to_string())));
+    assert!(notice.fields().iter().any(|(tag, _)| *tag == b'R'));
+    let notice = notices.next().await.unwrap();
+    assert_eq!(notice.severity(), "WARNING");
+    assert_eq!(notice.message(), "world");
+
+    drop(client);
+    assert!(notices.next().await.is_none());
+}
+
+#[tokio::test]
+async fn parameter_changes() {
+    let client = connect("user=postgres").await;
+
+    assert!(client.parameter("server_version").is_some());
+
+    let mut changes = client.parameter_changes();
+    client
+        .batch_execute("SET TimeZone = 'America/New_York'")
+        .await
+        .unwrap();
+
+    let change = changes.next().await.unwrap();
+    assert_eq!(change.name(), "TimeZone");
+    assert_eq!(change.value(), "America/New_York");
+    assert_eq!(
+        client.parameter("TimeZone").as_deref(),
+        Some("America/New_York")
+    );
+
+    drop(client);
+    assert!(changes.next().await.is_none());
+}
+
+#[tokio::test]
+async fn stats() {
+    let client = connect("user=postgres").await;
+
+    let before = client.stats();
+    assert!(before.bytes_sent() > 0);
+    assert!(before.bytes_received() > 0);
+
+    let stmt = client
+        .prepare("SELECT generate_series(1, 3)")
+        .await
+        .unwrap();
+    client.query(&stmt, &[]).await.unwrap();
+    client.batch_execute("SELECT 1; SELECT 2").await.unwrap();
+    client
+        .batch_execute("SELECT * FROM nonexistent")
+        .await
+        .unwrap_err();
+
+    let after = client.stats();
+    assert_eq!(after.statements() - before.statements(), 3);
+    assert_eq!(after.rows() - before.rows(), 5);
+    assert_eq!(after.prepares() - before.prepares(), 1);
+    assert_eq!(after.errors() - before.errors(), 1);
+    assert!(after.bytes_sent() > before.bytes_sent());
+    assert!(after.bytes_received() > before.bytes_received());
+    assert!(after.latency() > before.latency());
+}
+
+#[tokio::test]
+async fn statement_hook() {
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl StatementHook for Recorder {
+        fn before_statement(&self, statement: &StatementInfo<'_>) {
+            self.0.lock().unwrap().push(format!(
+                "before {} ({})",
+                statement.query(),
+                statement.param_count()
+            ));
+        }
+
+        fn after_statement(&self, statement: &StatementInfo<'_>, result: &StatementResult<'_>) {
+            self.0.lock().unwrap().push(format!(
+                "after {} rows={} affected={:?} error={}",
+                statement.query(),
+                result.rows(),
+                result.rows_affected(),
+                result.error().is_some()
+            ));
+        }
+    }
+
+    let mut client = connect("user=postgres").await;
+    let events = Arc::new(Mutex::new(vec![]));
+    client.set_statement_hook(Recorder(events.clone()));
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT)")
+        .await
+        .unwrap();
+    client
+        .execute("INSERT INTO foo VALUES ($1), ($2)", &[&1i32, &2i32])
+        .await
+        .unwrap();
+    client.query("SELECT id FROM foo", &[]).await.unwrap();
+    client.query("SELECT nonexistent", &[]).await.unwrap_err();
+
+    client.clear_statement_hook();
+    client.simple_query("SELECT 1").await.unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "before CREATE TEMPORARY TABLE foo (id INT) (0)",
+            "after CREATE TEMPORARY TABLE foo (id INT) rows=0 affected=None error=false",
+            "before INSERT INTO foo VALUES ($1), ($2) (2)",
+            "after INSERT INTO foo VALUES ($1), ($2) rows=0 affected=Some(2) error=false",
+            "before SELECT id FROM foo (0)",
+            "after SELECT id FROM foo rows=2 affected=Some(2) error=false",
+            "before SELECT nonexistent (0)",
+            "after SELECT nonexistent rows=0 affected=None error=true",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn retry_invalidated_plans() {
+    let mut client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT); INSERT INTO foo VALUES (1)")
+        .await
+        .unwrap();
+    let stmt = client.prepare("SELECT * FROM foo").await.unwrap();
+    client
+        .batch_execute("ALTER TABLE foo ADD COLUMN name TEXT")
+        .await
+        .unwrap();
+
+    let err = client.query(&stmt, &[]).await.unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::FEATURE_NOT_SUPPORTED));
+
+    client.set_retry_invalidated_plans(true);
+    let rows = client.query(&stmt, &[]).await.unwrap();
+    assert_eq!(rows[0].columns().len(), 2);
+
+    let transaction = client.transaction().await.unwrap();
+    let err = transaction.query(&stmt, &[]).await.unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::FEATURE_NOT_SUPPORTED));
+}
+
+#[tokio::test]
+async fn retry_invalidated_typed_plans() {
+    let mut client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT); INSERT INTO foo VALUES (1)")
+        .await
+        .unwrap();
+    let stmt = client
+        .prepare_typed("SELECT * FROM foo WHERE id = $1", &[Type::INT8])
+        .await
+        .unwrap();
+    client
+        .batch_execute("ALTER TABLE foo ADD COLUMN name TEXT")
+        .await
+        .unwrap();
+
+    client.set_retry_invalidated_plans(true);
+    let rows = client.query(&stmt, &[&1i64]).await.unwrap();
+    assert_eq!(rows[0].columns().len(), 2);
+}
+
+#[tokio::test]
+async fn query_portal() {
+    let mut client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (
+                id SERIAL,
+                name TEXT
+            );
+
+            INSERT INTO foo (name) VALUES ('alice'), ('bob'), ('charlie');",
+        )
+        .await
+        .unwrap();
+
+    let stmt = client
+        .prepare("SELECT id, name FROM foo ORDER BY id")
+        .await
+        .unwrap();
+
+    let transaction = client.transaction().await.unwrap();
+
+    let portal = transaction.bind(&stmt, &[]).await.unwrap();
+    let f1 = transaction.query_portal(&portal, 2);
+    let f2 = transaction.query_portal(&portal, 2);
+    let f3 = transaction.query_portal(&portal, 2);
+
+    let (r1, r2, r3) = try_join!(f1, f2, f3).unwrap();
+
+    assert_eq!(r1.len(), 2);
+    assert_eq!(r1[0].get::<_, i32>(0), 1);
+    assert_eq!(r1[0].get::<_, &str>(1), "alice");
+    assert_eq!(r1[1].get::<_, i32>(0), 2);
+    assert_eq!(r1[1].get::<_, &str>(1), "bob");
+
+    assert_eq!(r2.len(), 1);
+    assert_eq!(r2[0].get::<_, i32>(0), 3);
+    assert_eq!(r2[0].get::<_, &str>(1), "charlie");
+
+    assert_eq!(r3.len(), 0);
+}
+
+#[tokio::test]
+async fn query_as() {
+    #[derive(Debug, PartialEq, FromRow)]
+    struct Address {
+        city: String,
+    }
+
+    #[derive(Debug, PartialEq, FromRow)]
+    #[postgres(rename_all = "camelCase")]
+    struct Person {
+        #[postgres(name = "person_id")]
+        id: i32,
+        display_name: Option<String>,
+        #[postgres(default)]
+        nickname: Option<String>,
+        #[postgres(flatten)]
+        address: Address,
+    }
+
+    let client = connect("user=postgres").await;
+
+    let people = client
+        .query_as::<Person>(
+            "SELECT 1 AS person_id, 'Steven' AS \"displayName\", 'Springfield' AS city \
+             UNION ALL SELECT 2, NULL, 'Shelbyville'",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        people,
+        vec![
+            Person {
+                id: 1,
+                display_name: Some("Steven".to_string()),
+                nickname: None,
+                address: Address {
+                    city: "Springfield".to_string(),
+                },
+            },
+            Person {
+                id: 2,
+                display_name: None,
+                nickname: None,
+                address: Address {
+                    city: "Shelbyville".to_string(),
+                },
+            },
+        ]
+    );
+
+    let err = client
+        .query_as::<Address>("SELECT 1 AS person_id", &[])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("city"), "{}", err);
+}
+
+#[tokio::test]
+async fn from_row_borrowed() {
+    #[derive(Debug, PartialEq, FromRow)]
+    struct Item<'a> {
+        id: i32,
+        name: &'a str,
+        tags: Vec<&'a str>,
+    }
+
+    let client = connect("user=postgres").await;
+
+    let row = client
+        .query_one(
+            "SELECT 1 AS id, 'foo' AS name, ARRAY['a', 'b'] AS tags",
+            &[],
+        )
+        .await
+        .unwrap();
+    let item = Item::from_row(&row).unwrap();
+    assert_eq!(
+        item,
+        Item {
+            id: 1,
+            name: "foo",
+            tags: vec!["a", "b"],
+        }
+    );
+}
+
+#[tokio::test]
+async fn query_stream_as() {
+    #[derive(Debug, PartialEq, FromRow)]
+    struct Item {
+        id: i32,
+        name: String,
+    }
+
+    let client = connect("user=postgres").await;
+
+    let stream = client
+        .query_stream_as::<Item>(
+            "SELECT i AS id, 'item ' || i AS name FROM generate_series(1, 1000) i",
+            &[],
+        )
+        .await
+        .unwrap();
+    pin_mut!(stream);
+    let mut expected = 0;
+    while let Some(item) = stream.try_next().await.unwrap() {
+        expected += 1;
+        assert_eq!(
+            item,
+            Item {
+                id: expected,
+                name: format!("item {}", expected),
+            }
+        );
+    }
+    assert_eq!(expected, 1000);
+    assert_eq!(stream.rows_affected(), Some(1000));
+
+    let stream = client
+        .query_stream_as::<Item>("SELECT 1 AS id", &[])
+        .await
+        .unwrap();
+    let err = stream.try_collect::<Vec<_>>().await.unwrap_err();
+    assert!(err.to_string().contains("name"), "{}", err);
+}
+
+#[tokio::test]
+async fn row_raw_values() {
+    let client = connect("user=postgres").await;
+
+    let row = client
+        .query_one("SELECT 1::INT4 AS a, NULL::TEXT AS b, 'hi'::TEXT AS c", &[])
+        .await
+        .unwrap();
+    assert_eq!(row.try_get_raw(0).unwrap(), Some(&[0, 0, 0, 1][..]));
+    assert_eq!(row.try_get_raw("b").unwrap(), None);
+    assert_eq!(row.try_get_raw("c").unwrap(), Some(&b"hi"[..]));
+    assert!(row.try_get_raw(3).is_err());
+}
+
+#[cfg(feature = "with-serde_json-1")]
+#[tokio::test]
+async fn row_to_json() {
+    let client = connect("user=postgres").await;
+
+    let row = client
+        .query_one(
+            "SELECT 1::INT4 AS a, true AS b, NULL::TEXT AS c, 'hi' AS d, 1.5::FLOAT8 AS e, \
+             'NaN'::FLOAT8 AS f, -12345.0670::NUMERIC AS g, '{\"x\": [1]}'::JSONB AS h, \
+             ARRAY[[1, 2], [3, NULL]]::INT4[] AS i, '\\xdead'::BYTEA AS j, \
+             '1999-12-31 23:59:59.5'::TIMESTAMP AS k, '0.001'::NUMERIC AS l, \
+             'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11'::UUID AS m, '2024-02-29'::DATE AS n",
+            &[],
+        )
+        .await
+        .unwrap();
+    let json = serde_json_1::Value::Object(row.to_json().unwrap());
+    assert_eq!(
+        json,
+        serde_json_1::json!({
+            "a": 1,
+            "b": true,
+            "c": null,
+            "d": "hi",
+            "e": 1.5,
+            "f": "NaN",
+            "g": "-12345.0670",
+            "h": {"x": [1]},
+            "i": [[1, 2], [3, null]],
+            "j": "\\xdead",
+            "k": "1999-12-31T23:59:59.5",
+            "l": "0.001",
+            "m": "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11",
+            "n": "2024-02-29",
+        })
+    );
+
+    client
+        .batch_execute("CREATE TYPE pg_temp.pair AS (a INT4, b TEXT)")
+        .await
+        .unwrap();
+    let row = client
+        .query_one(
+            "SELECT '1 year 2 mons 3 days 04:05:06.5'::INTERVAL AS a, '-1 day -00:00:01'::INTERVAL AS b, \
+             '12:00:00+05:30'::TIMETZ AS c, '-12.34'::MONEY AS d, '[1,10)'::INT4RANGE AS e, \
+             'empty'::INT4RANGE AS f, '(,\"2020-01-01\"]'::DATERANGE AS g, '{[1,3), [5,7)}'::INT4MULTIRANGE AS h, \
+             ROW(1, 'x')::pg_temp.pair AS i, '127.0.0.1'::INET AS j, '::1'::INET AS k, \
+             '10.0.0.1/8'::INET AS l, '10.0.0.0/8'::CIDR AS m, '(1,2)'::POINT AS n",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json_1::Value::Object(row.to_json().unwrap()),
+        serde_json_1::json!({
+            "a": "P1Y2M3DT4H5M6.5S",
+            "b": "P-1DT-1S",
+            "c": "12:00:00+05:30",
+            "d": "-12.34",
+            "e": "[1,10)",
+            "f": "empty",
+            "g": "(,2020-01-02)",
+            "h": "{[1,3),[5,7)}",
+            "i": {"a": 1, "b": "x"},
+            "j": "127.0.0.1",
+            "k": "::1",
+            "l": "10.0.0.1/8",
+            "m": "10.0.0.0/8",
+            "n": null,
+        })
+    );
+
+    let messages = client
+        .simple_query("SELECT 1 AS a, NULL AS b")
+        .await
+        .unwrap();
+    let row = match &messages[1] {
+        SimpleQueryMessage::Row(row) => row,
+        _ => panic!("unexpected message"),
+    };
+    assert_eq!(
+        serde_json_1::Value::Object(row.to_json().unwrap()),
+        serde_json_1::json!({"a": "1", "b": null})
+    );
+}
+
+#[tokio::test]
+async fn insert_many() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT, name TEXT, tags TEXT[])")
+        .await
+        .unwrap();
+
+    let names = (0..1000).map(|i| format!("name {}", i)).collect::<Vec<_>>();
+    let tags = vec!["a".to_string(), "b".to_string()];
+    let ids = (0..1000).collect::<Vec<i32>>();
+
+    // a small batch, inserted with a VALUES list
+    let rows = (0..3)
+        .map(|i| [&ids[i] as _, &names[i] as _])
+        .collect::<Vec<[&(dyn ToSql + Sync); 2]>>();
+    let rows = rows.iter().map(|r| &r[..]).collect::<Vec<_>>();
+    assert_eq!(
+        client
+            .insert_many("foo", &["id", "name"], &rows)
+            .await
+            .unwrap(),
+        3
+    );
+
+    // a large batch, inserted with UNNEST
+    let rows = (3..1000)
+        .map(|i| [&ids[i] as _, &names[i] as _])
+        .collect::<Vec<[&(dyn ToSql + Sync); 2]>>();
+    let rows = rows.iter().map(|r| &r[..]).collect::<Vec<_>>();
+    assert_eq!(
+        client
+            .insert_many("foo", &["id", "name"], &rows)
+            .await
+            .unwrap(),
+        997
+    );
+
+    // a large batch with an array column, inserted with a VALUES list
+    let rows = (0..200)
+        .map(|i| [&ids[i] as _, &None::<&str> as _, &tags as _])
+        .collect::<Vec<[&(dyn ToSql + Sync); 3]>>();
+    let rows = rows.iter().map(|r| &r[..]).collect::<Vec<_>>();
+    assert_eq!(
+        client
+            .insert_many("foo", &["id", "name", "tags"], &rows)
+            .await
+            .unwrap(),
+        200
+    );
+
+    let row = client
+        .query_one(
+            "SELECT count(*), count(DISTINCT name), count(tags), sum(id) FROM foo",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i64>(0), 1200);
+    assert_eq!(row.get::<_, i64>(1), 1000);
+    assert_eq!(row.get::<_, i64>(2), 200);
+    assert_eq!(row.get::<_, i64>(3), 499_500 + 19_900);
+}
+
+#[tokio::test]
+async fn insert_many_qualified() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute("CREATE TEMPORARY TABLE foo (id INT)")
+        .await
+        .unwrap();
+
+    let rows: &[&[&(dyn ToSql + Sync)]] = &[&[&1i32], &[&2i32]];
+    assert_eq!(
+        client
+            .insert_many("pg_temp.foo", &["id"], rows)
+            .await
+            .unwrap(),
+        2
+    );
+
+    let err = client.insert_many("foo", &[], rows).await.unwrap_err();
+    assert!(err.to_string().contains("invalid configuration"));
+
+    let rows: &[&[&(dyn ToSql + Sync)]] = &[&[&1i32], &[&2i32, &3i32]];
+    let err = client.insert_many("foo", &["id"], rows).await.unwrap_err();
+    assert_eq!(err.to_string(), "expected 1 parameters but got 2");
+}
+
+#[tokio::test]
+async fn query_builder() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TEMPORARY TABLE foo (id INT, name TEXT);
+             INSERT INTO foo (id, name) VALUES (1, 'alice'), (2, 'bob'), (3, 'carol');",
+        )
+        .await
+        .unwrap();
+
+    let name = "bob'; DROP TABLE foo; --";
+    let mut builder = QueryBuilder::new("SELECT id FROM foo WHERE id IN (");
+    builder
+        .push_bind_list(vec![1i32, 3])
+        .push(") OR name = ")
+        .push_bind(name)
+        .push(" ORDER BY id");
+    assert_eq!(
+        builder.sql(),
+        "SELECT id FROM foo WHERE id IN ($1, $2) OR name = $3 ORDER BY id"
+    );
+    assert_eq!(builder.param_count(), 3);
+
+    let rows = client
+        .query(builder.sql(), &builder.params())
+        .await
+        .unwrap();
+    let ids = rows.iter().map(|r| r.get(0)).collect::<Vec<i32>>();
+    assert_eq!(ids, [1, 3]);
+}
+
+#[tokio::test]
+async fn query_cursor() {
+    let client = connect("user=postgres").await;
+
+    let stream = client
+        .query_cursor("SELECT generate_series(1, $1)", &[&10i32], 3)
+        .await
+        .unwrap();
+    pin_mut!(stream);
+    let mut values = vec![];
+    while let Some(row) = stream.next().await {
+        values.push(row.unwrap().get::<_, i32>(0));
+    }
+    assert_eq!(values, (1..=10).collect::<Vec<_>>());
+    assert_eq!(stream.rows_affected(), Some(10));
+
+    // Dropping the stream early closes the cursor and releases the connection.
+    let mut stream = Box::pin(
+        client
+            .query_cursor("SELECT generate_series(1, 1000000)", &[], 100)
+            .await
+            .unwrap(),
+    );
+    let row = stream.next().await.unwrap().unwrap();
+    assert_eq!(row.get::<_, i32>(0), 1);
+    drop(stream);
+
+    let row = client.query_one("SELECT 1", &[]).await.unwrap();
+    assert_eq!(row.get::<_, i32>(0), 1);
+}
+
+#[tokio::test]
+async fn query_cursor_error() {
+    let mut client = connect("user=postgres").await;
+
+    let stream = client
+        .query_cursor("SELECT 1 / (5 - i) FROM generate_series(1, 10) i", &[], 2)
+        .await
+        .unwrap();
+    let rows = stream.collect::<Vec<_>>().await;
+    assert_eq!(rows.len(), 5);
+    assert!(rows[..4].iter().all(|r| r.is_ok()));
+    assert_eq!(
+        rows[4].as_ref().unwrap_err().code(),
+        Some(&SqlState::DIVISION_BY_ZERO)
+    );
+
+    let transaction = client.transaction().await.unwrap();
+    let rows = transaction
+        .query_cursor("SELECT generate_series(1, 5)", &[], 2)
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 5);
+    transaction.commit().await.unwrap();
+}
+
+#[tokio::test]
+async fn require_channel_binding() {
+    let err = connect_raw("user=postgres channel_binding=require")
+        .await
+        .err()
+        .unwrap();
+    assert!(err
+        .to_string()
+        .contains("the server did not request authentication"));
+
+    let err = connect_raw("user=scram_user password=password channel_binding=require")
+        .await
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("the connection does not use TLS"));
+}
+
+#[tokio::test]
+async fn prefer_channel_binding() {
+    connect("user=postgres channel_binding=prefer").await;
+}
+
+#[tokio::test]
+async fn disable_channel_binding() {
+    connect("user=postgres channel_binding=disable").await;
+}
+
+#[tokio::test]
+async fn check_send() {
+    fn is_send<T: Send>(_: &T) {}
+
+    let f = connect("user=postgres");
+    is_send(&f);
+    let mut client = f.await;
+
+    let f = client.prepare("SELECT $1::TEXT");
+    is_send(&f);
+    let stmt = f.await.unwrap();
 
     let f = client.query(&stmt, &[&"hello"]);
     is_send(&f);
@@ -1079,3 +2202,85 @@ async fn query_typed_with_transaction() {
         .unwrap();
     assert_eq!(updated_rows.len(), 0);
 }
+
+#[tokio::test]
+async fn pipeline() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "
+                CREATE TEMPORARY TABLE foo (
+                    id SERIAL,
+                    name TEXT
+                );
+            ",
+        )
+        .await
+        .unwrap();
+
+    let insert = client
+        .prepare("INSERT INTO foo (name) VALUES ($1)")
+        .await
+        .unwrap();
+
+    let mut pipeline = client.pipeline();
+    pipeline.add(&insert, &[&"alice"]).await.unwrap();
+    pipeline.add(&insert, &[&"bob"]).await.unwrap();
+    let select = pipeline
+        .add("SELECT name FROM foo ORDER BY id", &[])
+        .await
+        .unwrap();
+    assert_eq!(pipeline.len(), 3);
+
+    let results = pipeline.run().await.unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap().rows_affected(), 1);
+    let rows = results[select].as_ref().unwrap().rows();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get::<_, &str>(0), "alice");
+    assert_eq!(rows[1].get::<_, &str>(0), "bob");
+}
+
+#[tokio::test]
+async fn pipeline_error() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "
+                CREATE TEMPORARY TABLE foo (
+                    id INT PRIMARY KEY
+                );
+            ",
+        )
+        .await
+        .unwrap();
+
+    let insert = client
+        .prepare("INSERT INTO foo (id) VALUES ($1)")
+        .await
+        .unwrap();
+
+    let mut pipeline = client.pipeline();
+    pipeline.add(&insert, &[&1i32]).await.unwrap();
+    pipeline.add(&insert, &[&1i32]).await.unwrap();
+    pipeline.add(&insert, &[&2i32]).await.unwrap();
+    pipeline.add(&insert, &[&"x"]).await.err().unwrap();
+
+    let results = pipeline.run().await.unwrap();
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_ok());
+    assert_eq!(
+        results[1].as_ref().unwrap_err().code(),
+        Some(&SqlState::UNIQUE_VIOLATION)
+    );
+    assert!(results[2].as_ref().unwrap_err().is_pipeline_aborted());
+
+    // the implicit transaction was rolled back
+    let rows = client.query("SELECT id FROM foo", &[]).await.unwrap();
+    assert!(rows.is_empty());
+
+    // the connection is still usable
+    client.batch_execute("SELECT 1").await.unwrap();
+}
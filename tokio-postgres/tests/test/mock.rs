@@ -0,0 +1,411 @@
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream};
+use tokio::task::JoinHandle;
+use tokio_postgres::config::GssEncMode;
+use tokio_postgres::error::SqlState;
+use tokio_postgres::gss::{GssContext, MakeGssContext};
+use tokio_postgres::mock::{Auth, Backend, Response};
+use tokio_postgres::types::Type;
+use tokio_postgres::{Client, Config, Connection, Error, NoTls, SimpleQueryMessage};
+
+type MockConnection = Connection<DuplexStream, tokio_postgres::tls::NoTlsStream>;
+
+async fn connect(
+    backend: Backend,
+    config: &str,
+) -> (
+    Result<(Client, MockConnection), Error>,
+    JoinHandle<std::io::Result<()>>,
+) {
+    let (client_stream, server_stream) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move { backend.serve(server_stream).await });
+    let config = config.parse::<Config>().unwrap();
+    (config.connect_raw(client_stream, NoTls).await, server)
+}
+
+#[tokio::test]
+async fn scripted_queries() {
+    let mut backend = Backend::new();
+    backend
+        .auth(Auth::ScramSha256("password".to_string()))
+        .parameter("server_version", "15.4")
+        .query(
+            "SELECT id, name FROM users WHERE id > $1",
+            Response::rows(&[("id", Type::INT4), ("name", Type::TEXT)])
+                .params(&[Type::INT4])
+                .row(&[Some("1"), Some("ferris")])
+                .row(&[Some("2"), None]),
+        )
+        .query("START TRANSACTION", Response::command("BEGIN"))
+        .query(
+            "INSERT INTO users (name) VALUES ('ferris')",
+            Response::command("INSERT 0 1"),
+        )
+        .query("COMMIT", Response::command("COMMIT"))
+        .query(
+            "SELECT 'hello'",
+            Response::rows(&[("?column?", Type::TEXT)]).row(&[Some("hello")]),
+        );
+
+    let (connection, server) = connect(backend, "user=postgres password=password").await;
+    let (mut client, connection) = connection.unwrap();
+    let connection = tokio::spawn(connection);
+
+    let rows = client
+        .query("SELECT id, name FROM users WHERE id > $1", &[&0i32])
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get::<_, i32>(0), 1);
+    assert_eq!(rows[0].get::<_, Option<&str>>(1), Some("ferris"));
+    assert_eq!(rows[1].get::<_, i32>(0), 2);
+    assert_eq!(rows[1].get::<_, Option<&str>>(1), None);
+
+    let transaction = client.transaction().await.unwrap();
+    let inserted = transaction
+        .execute("INSERT INTO users (name) VALUES ('ferris')", &[])
+        .await
+        .unwrap();
+    assert_eq!(inserted, 1);
+    transaction.commit().await.unwrap();
+
+    let messages = client.simple_query("SELECT 'hello'").await.unwrap();
+    match &messages[1] {
+        SimpleQueryMessage::Row(row) => assert_eq!(row.get(0), Some("hello")),
+        _ => panic!("expected a row"),
+    }
+
+    drop(client);
+    connection.await.unwrap().unwrap();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn scripted_error() {
+    let mut backend = Backend::new();
+    backend.auth(Auth::Md5("password".to_string())).query(
+        "SELECT * FROM missing",
+        Response::error(
+            SqlState::UNDEFINED_TABLE,
+            "relation \"missing\" does not exist",
+        ),
+    );
+
+    let (connection, server) = connect(backend, "user=postgres password=password").await;
+    let (client, connection) = connection.unwrap();
+    let connection = tokio::spawn(connection);
+
+    let err = client
+        .query("SELECT * FROM missing", &[])
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::UNDEFINED_TABLE));
+
+    drop(client);
+    connection.await.unwrap().unwrap();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn unscripted_query() {
+    let mut backend = Backend::new();
+    backend.query("SELECT 1", Response::command("SELECT 0"));
+
+    let (connection, server) = connect(backend, "user=postgres").await;
+    let (client, connection) = connection.unwrap();
+    let connection = tokio::spawn(connection);
+
+    let err = client.batch_execute("SELECT 2").await.unwrap_err();
+    assert_eq!(err.code(), Some(&SqlState::INTERNAL_ERROR));
+
+    drop(client);
+    connection.await.unwrap().unwrap();
+    server.await.unwrap().unwrap_err();
+}
+
+#[tokio::test]
+async fn wrong_password() {
+    let mut backend = Backend::new();
+    backend.auth(Auth::Cleartext("password".to_string()));
+
+    let (connection, server) = connect(backend, "user=postgres password=wrong").await;
+    let err = connection.err().unwrap();
+    assert_eq!(err.code(), Some(&SqlState::INVALID_PASSWORD));
+
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn scram_verifier() {
+    let verifier = postgres_protocol::password::scram_sha_256(b"password");
+
+    let mut backend = Backend::new();
+    backend.auth(Auth::ScramSha256Verifier(verifier.clone()));
+    let (connection, server) = connect(backend, "user=postgres password=password").await;
+    let (client, connection) = connection.unwrap();
+    drop(client);
+    connection.await.unwrap();
+    server.await.unwrap().unwrap();
+
+    let mut backend = Backend::new();
+    backend.auth(Auth::ScramSha256Verifier(verifier));
+    let (connection, server) = connect(backend, "user=postgres password=wrong").await;
+    let err = connection.err().unwrap();
+    assert_eq!(err.code(), Some(&SqlState::INVALID_PASSWORD));
+    server.await.unwrap().unwrap();
+}
+
+// A stand-in for a Kerberos context which trades a fixed pair of tokens and "encrypts" by flipping bits.
+struct XorGss;
+
+impl MakeGssContext for XorGss {
+    fn make_gss_context(
+        &self,
+        service: &str,
+        host: &str,
+    ) -> Result<Box<dyn GssContext>, Box<dyn std::error::Error + Sync + Send>> {
+        assert_eq!(service, "postgres");
+        assert_eq!(host, "db.example.com");
+        Ok(Box::new(XorContext { complete: false }))
+    }
+}
+
+struct XorContext {
+    complete: bool,
+}
+
+impl GssContext for XorContext {
+    fn step(
+        &mut self,
+        token: Option<&[u8]>,
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error + Sync + Send>> {
+        match token {
+            None => Ok(Some(b"hello".to_vec())),
+            Some(b"world") => {
+                self.complete = true;
+                Ok(None)
+            }
+            Some(_) => Err("bad token".into()),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    fn wrap_size_limit(
+        &mut self,
+        _: usize,
+    ) -> Result<usize, Box<dyn std::error::Error + Sync + Send>> {
+        // small enough that messages are split across packets
+        Ok(16)
+    }
+
+    fn wrap(
+        &mut self,
+        message: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(message.iter().map(|b| b ^ 0xff).collect())
+    }
+
+    fn unwrap(
+        &mut self,
+        message: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Sync + Send>> {
+        self.wrap(message)
+    }
+}
+
+async fn read_packet<R>(stream: &mut R) -> Option<Vec<u8>>
+where
+    R: AsyncRead + Unpin,
+{
+    let len = stream.read_u32().await.ok()?;
+    let mut packet = vec![0; len as usize];
+    stream.read_exact(&mut packet).await.unwrap();
+    Some(packet)
+}
+
+async fn write_packet<W>(stream: &mut W, packet: &[u8])
+where
+    W: AsyncWrite + Unpin,
+{
+    stream.write_u32(packet.len() as u32).await.unwrap();
+    stream.write_all(packet).await.unwrap();
+}
+
+// Accepts GSSAPI encryption on `stream`, and relays the decrypted traffic to `backend`.
+async fn serve_gss(mut stream: DuplexStream, backend: Backend) -> std::io::Result<()> {
+    let mut request = [0; 8];
+    stream.read_exact(&mut request).await.unwrap();
+    assert_eq!(request, [0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x30]);
+    stream.write_all(b"G").await.unwrap();
+
+    assert_eq!(read_packet(&mut stream).await.unwrap(), b"hello");
+    write_packet(&mut stream, b"world").await;
+
+    let (client_stream, server_stream) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move { backend.serve(server_stream).await });
+    let (mut client_read, mut client_write) = tokio::io::split(stream);
+    let (mut server_read, mut server_write) = tokio::io::split(client_stream);
+
+    let inbound = async move {
+        while let Some(packet) = read_packet(&mut client_read).await {
+            assert!(packet.len() <= 16);
+            let message = packet.iter().map(|b| b ^ 0xff).collect::<Vec<_>>();
+            server_write.write_all(&message).await.unwrap();
+        }
+        server_write.shutdown().await.unwrap();
+    };
+    let outbound = async move {
+        let mut buf = [0; 1024];
+        loop {
+            let n = server_read.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            let packet = buf[..n].iter().map(|b| b ^ 0xff).collect::<Vec<_>>();
+            write_packet(&mut client_write, &packet).await;
+        }
+    };
+    futures_util::join!(inbound, outbound);
+
+    server.await.unwrap()
+}
+
+#[tokio::test]
+async fn gss_encryption() {
+    let mut backend = Backend::new();
+    backend.query(
+        "SELECT 'a fairly long value which spans several packets'",
+        Response::rows(&[("?column?", Type::TEXT)])
+            .row(&[Some("a fairly long value which spans several packets")]),
+    );
+
+    let (client_stream, server_stream) = tokio::io::duplex(4096);
+    let server = tokio::spawn(serve_gss(server_stream, backend));
+    let mut config = "host=db.example.com user=postgres gssencmode=require"
+        .parse::<Config>()
+        .unwrap();
+    config.gss(XorGss);
+    let (client, connection) = config.connect_raw(client_stream, NoTls).await.unwrap();
+    let connection = tokio::spawn(connection);
+
+    let messages = client
+        .simple_query("SELECT 'a fairly long value which spans several packets'")
+        .await
+        .unwrap();
+    match &messages[1] {
+        SimpleQueryMessage::Row(row) => assert_eq!(
+            row.get(0),
+            Some("a fairly long value which spans several packets")
+        ),
+        _ => panic!("expected a row"),
+    }
+
+    drop(client);
+    connection.await.unwrap().unwrap();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn gss_encryption_unsupported() {
+    // the mock backend declines GSSAPI encryption
+    let (connection, server) = connect(Backend::new(), "user=postgres gssencmode=require").await;
+    let err = connection.err().unwrap();
+    assert!(
+        err.to_string().contains("no GSSAPI implementation"),
+        "{}",
+        err
+    );
+    drop(server);
+
+    let (client_stream, server_stream) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move { Backend::new().serve(server_stream).await });
+    let mut config = "host=db.example.com user=postgres gssencmode=require"
+        .parse::<Config>()
+        .unwrap();
+    config.gss(XorGss);
+    let err = config
+        .connect_raw(client_stream, NoTls)
+        .await
+        .err()
+        .unwrap();
+    assert!(
+        err.to_string()
+            .contains("does not support GSSAPI encryption"),
+        "{}",
+        err
+    );
+    drop(server);
+
+    let (client_stream, server_stream) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move { Backend::new().serve(server_stream).await });
+    config.gss_enc_mode(GssEncMode::Prefer);
+    let (client, connection) = config.connect_raw(client_stream, NoTls).await.unwrap();
+    drop(client);
+    connection.await.unwrap();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn gss_encryption_oversize_error() {
+    let (client_stream, mut server_stream) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move {
+        let mut request = [0; 8];
+        server_stream.read_exact(&mut request).await.unwrap();
+        server_stream.write_all(b"G").await.unwrap();
+        assert_eq!(read_packet(&mut server_stream).await.unwrap(), b"hello");
+
+        // an error response which never ends
+        server_stream.write_all(b"E\x7f\xff\xff\xff").await.unwrap();
+        while server_stream.write_all(&[b'x'; 1024]).await.is_ok() {}
+    });
+    let mut config = "host=db.example.com user=postgres gssencmode=require"
+        .parse::<Config>()
+        .unwrap();
+    config.gss(XorGss);
+    let err = config
+        .connect_raw(client_stream, NoTls)
+        .await
+        .err()
+        .unwrap();
+    assert!(
+        err.to_string().contains("oversize error response"),
+        "{}",
+        err
+    );
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn gss_authentication() {
+    let (client_stream, mut server_stream) = tokio::io::duplex(4096);
+    let server = tokio::spawn(async move {
+        let len = server_stream.read_u32().await.unwrap();
+        let mut startup = vec![0; len as usize - 4];
+        server_stream.read_exact(&mut startup).await.unwrap();
+
+        // AuthenticationGSS
+        server_stream
+            .write_all(b"R\0\0\0\x08\0\0\0\x07")
+            .await
+            .unwrap();
+
+        let mut response = [0; 10];
+        server_stream.read_exact(&mut response).await.unwrap();
+        assert_eq!(&response, b"p\0\0\0\x09hello");
+
+        // AuthenticationGSSContinue, AuthenticationOk, and ReadyForQuery
+        server_stream
+            .write_all(b"R\0\0\0\x0d\0\0\0\x08worldR\0\0\0\x08\0\0\0\0Z\0\0\0\x05I")
+            .await
+            .unwrap();
+    });
+
+    let mut config = "host=db.example.com user=postgres gssencmode=disable"
+        .parse::<Config>()
+        .unwrap();
+    config.gss(XorGss);
+    let (_client, _connection) = config.connect_raw(client_stream, NoTls).await.unwrap();
+    server.await.unwrap();
+}
@@ -0,0 +1,164 @@
+use futures_util::FutureExt;
+use std::time::Duration;
+use tokio::time;
+use tokio_postgres::pool::{ConcurrencyLimit, Pool};
+use tokio_postgres::NoTls;
+
+fn pool(max_size: usize) -> Pool<NoTls> {
+    let config = "host=localhost port=5433 user=postgres".parse().unwrap();
+    Pool::builder(config, NoTls).max_size(max_size).build()
+}
+
+#[tokio::test]
+async fn reuse() {
+    let pool = pool(2);
+
+    let client = pool.get().await.unwrap();
+    let pid = client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .await
+        .unwrap()
+        .get::<_, i32>(0);
+    drop(client);
+
+    let status = pool.status();
+    assert_eq!(status.size, 1);
+    assert_eq!(status.idle, 1);
+
+    let client = pool.get().await.unwrap();
+    let row = client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, i32>(0), pid);
+}
+
+#[tokio::test]
+async fn max_size() {
+    let pool = pool(1);
+
+    let client = pool.get().await.unwrap();
+    assert!(time::timeout(Duration::from_millis(100), pool.get())
+        .await
+        .is_err());
+
+    drop(client);
+    time::timeout(Duration::from_secs(1), pool.get())
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn discard_broken() {
+    let pool = pool(1);
+
+    let client = pool.get().await.unwrap();
+    let pid = client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .await
+        .unwrap()
+        .get::<_, i32>(0);
+    drop(client);
+
+    let (other, connection) =
+        tokio_postgres::connect("host=localhost port=5433 user=postgres", NoTls)
+            .await
+            .unwrap();
+    tokio::spawn(connection);
+    other
+        .execute("SELECT pg_terminate_backend($1)", &[&pid])
+        .await
+        .unwrap();
+
+    let client = pool.get().await.unwrap();
+    let row = client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .await
+        .unwrap();
+    assert_ne!(row.get::<_, i32>(0), pid);
+    assert_eq!(pool.status().size, 1);
+}
+
+#[tokio::test]
+async fn cancel_checkout() {
+    let pool = pool(1);
+
+    drop(pool.get().await.unwrap());
+    assert_eq!(pool.status().size, 1);
+
+    // dropped while the idle connection is being checked
+    assert!(pool.get().now_or_never().is_none());
+    let status = pool.status();
+    assert_eq!(status.size, 0);
+    assert_eq!(status.idle, 0);
+
+    pool.get().await.unwrap();
+    assert_eq!(pool.status().size, 1);
+}
+
+#[tokio::test]
+async fn detach() {
+    let pool = pool(1);
+
+    let client = pool.get().await.unwrap().detach();
+    assert_eq!(pool.status().size, 0);
+    client.batch_execute("SELECT 1").await.unwrap();
+
+    pool.get().await.unwrap();
+    assert_eq!(pool.status().size, 1);
+}
+
+#[tokio::test]
+async fn idle_timeout() {
+    let config = "host=localhost port=5433 user=postgres".parse().unwrap();
+    let pool = Pool::builder(config, NoTls)
+        .idle_timeout(Duration::from_millis(100))
+        .build();
+
+    drop(pool.get().await.unwrap());
+    assert_eq!(pool.status().idle, 1);
+
+    time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(pool.status().idle, 0);
+    assert_eq!(pool.status().size, 0);
+}
+
+#[tokio::test]
+async fn concurrency_limit() {
+    let limit = ConcurrencyLimit::new(1);
+    let config = "host=localhost port=5433 user=postgres"
+        .parse::<tokio_postgres::Config>()
+        .unwrap();
+    let a = Pool::builder(config.clone(), NoTls)
+        .concurrency_limit(limit.clone())
+        .build();
+    let b = Pool::builder(config, NoTls)
+        .concurrency_limit(limit.clone())
+        .wait_timeout(Duration::from_millis(100))
+        .build();
+
+    let client = a.get().await.unwrap();
+    assert_eq!(limit.available(), 0);
+    let err = b.get().await.unwrap_err();
+    assert!(err.is_timeout());
+
+    drop(client);
+    assert_eq!(limit.available(), 1);
+    b.get().await.unwrap();
+}
+
+#[tokio::test]
+async fn wait_timeout_zero() {
+    let config = "host=localhost port=5433 user=postgres".parse().unwrap();
+    let pool = Pool::builder(config, NoTls)
+        .max_size(1)
+        .wait_timeout(Duration::ZERO)
+        .build();
+
+    let client = pool.get().await.unwrap();
+    assert!(pool.get().await.unwrap_err().is_timeout());
+
+    drop(client);
+    pool.get().await.unwrap();
+}
@@ -0,0 +1,141 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time;
+use tokio_postgres::reconnect::{ReconnectEvent, ReconnectingClient};
+use tokio_postgres::NoTls;
+
+async fn terminate(pid: i32) {
+    let (client, connection) =
+        tokio_postgres::connect("host=localhost port=5433 user=postgres", NoTls)
+            .await
+            .unwrap();
+    tokio::spawn(connection);
+    client
+        .execute("SELECT pg_terminate_backend($1)", &[&pid])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn reconnect() {
+    let events = Arc::new(Mutex::new(vec![]));
+
+    let config = "host=localhost port=5433 user=postgres".parse().unwrap();
+    let client = ReconnectingClient::builder(config, NoTls)
+        .on_reconnect({
+            let events = events.clone();
+            move |event| {
+                let event = match event {
+                    ReconnectEvent::Disconnected => "disconnected".to_string(),
+                    ReconnectEvent::Failed { attempt, .. } => format!("failed {}", attempt),
+                    ReconnectEvent::Reconnected { attempts } => format!("reconnected {}", attempts),
+                    _ => unreachable!(),
+                };
+                events.lock().unwrap().push(event);
+            }
+        })
+        .connect()
+        .await
+        .unwrap();
+
+    let pid = client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .await
+        .unwrap()
+        .get::<_, i32>(0);
+
+    terminate(pid).await;
+    time::sleep(Duration::from_millis(100)).await;
+
+    let new_pid = client
+        .query_one("SELECT pg_backend_pid()", &[])
+        .await
+        .unwrap()
+        .get::<_, i32>(0);
+    assert_ne!(pid, new_pid);
+    assert_eq!(*events.lock().unwrap(), ["disconnected", "reconnected 1"]);
+}
+
+#[tokio::test]
+async fn give_up() {
+    let (admin, connection) =
+        tokio_postgres::connect("host=localhost port=5433 user=postgres", NoTls)
+            .await
+            .unwrap();
+    tokio::spawn(connection);
+    admin
+        .batch_execute("DROP DATABASE IF EXISTS reconnect_give_up")
+        .await
+        .unwrap();
+    admin
+        .batch_execute("CREATE DATABASE reconnect_give_up")
+        .await
+        .unwrap();
+
+    let failures = Arc::new(Mutex::new(vec![]));
+
+    let config = "host=localhost port=5433 user=postgres dbname=reconnect_give_up"
+        .parse()
+        .unwrap();
+    let client = ReconnectingClient::builder(config, NoTls)
+        .initial_backoff(Duration::from_millis(1))
+        .max_attempts(2)
+        .on_reconnect({
+            let failures = failures.clone();
+            move |event| {
+                if let ReconnectEvent::Failed { attempt, .. } = event {
+                    failures.lock().unwrap().push(attempt);
+                }
+            }
+        })
+        .connect()
+        .await
+        .unwrap();
+    client.batch_execute("SELECT 1").await.unwrap();
+
+    admin
+        .batch_execute("DROP DATABASE reconnect_give_up WITH (FORCE)")
+        .await
+        .unwrap();
+    time::sleep(Duration::from_millis(100)).await;
+
+    client.batch_execute("SELECT 1").await.unwrap_err();
+    assert_eq!(*failures.lock().unwrap(), [1, 2]);
+}
+
+#[tokio::test]
+async fn statement_cache() {
+    let config = "host=localhost port=5433 user=postgres".parse().unwrap();
+    let client = ReconnectingClient::builder(config, NoTls)
+        .statement_cache_size(2)
+        .connect()
+        .await
+        .unwrap();
+
+    let prepared = || async {
+        client
+            .client()
+            .await
+            .unwrap()
+            .query(
+                "SELECT statement FROM pg_prepared_statements \
+                 WHERE statement NOT LIKE '%pg_prepared_statements%' ORDER BY statement",
+                &[],
+            )
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| row.get::<_, String>(0))
+            .collect::<Vec<_>>()
+    };
+
+    client.execute("SELECT 1", &[]).await.unwrap();
+    client.execute("SELECT 2", &[]).await.unwrap();
+    client.execute("SELECT 1", &[]).await.unwrap();
+    // evicts the least recently used statement
+    client.execute("SELECT 3", &[]).await.unwrap();
+    assert_eq!(prepared().await, ["SELECT 1", "SELECT 3"]);
+
+    client.clear_statement_cache();
+    assert!(prepared().await.is_empty());
+}
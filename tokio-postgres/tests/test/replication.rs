@@ -0,0 +1,164 @@
+use futures_util::{pin_mut, StreamExt};
+use postgres_protocol::message::replication::{
+    LogicalReplicationMessage, ReplicationMessage, TupleData,
+};
+use std::time::SystemTime;
+use tokio_postgres::replication::LogicalReplicationStream;
+use tokio_postgres::types::PgLsn;
+use tokio_postgres::SimpleQueryMessage;
+
+use crate::connect;
+
+#[tokio::test]
+async fn logical_replication() {
+    let client = connect("user=postgres").await;
+    client
+        .batch_execute(
+            "DROP TABLE IF EXISTS replication_test;
+             DROP PUBLICATION IF EXISTS replication_test_pub;
+             CREATE TABLE replication_test (id SERIAL PRIMARY KEY, name TEXT);
+             CREATE PUBLICATION replication_test_pub FOR TABLE replication_test;",
+        )
+        .await
+        .unwrap();
+
+    let repl_client = connect("user=postgres replication=database").await;
+    let slot = repl_client
+        .simple_query("CREATE_REPLICATION_SLOT replication_test_slot TEMPORARY LOGICAL pgoutput")
+        .await
+        .unwrap();
+    let lsn = match &slot[1] {
+        SimpleQueryMessage::Row(row) => row.get("consistent_point").unwrap().to_string(),
+        _ => panic!("unexpected message"),
+    };
+
+    client
+        .execute("INSERT INTO replication_test (name) VALUES ('steven')", &[])
+        .await
+        .unwrap();
+
+    let query = format!(
+        "START_REPLICATION SLOT replication_test_slot LOGICAL {} \
+         (proto_version '1', publication_names 'replication_test_pub')",
+        lsn
+    );
+    let duplex = repl_client.copy_both_simple(&query).await.unwrap();
+    let stream = LogicalReplicationStream::new(duplex);
+    pin_mut!(stream);
+
+    let mut messages = vec![];
+    while let Some(message) = stream.next().await {
+        let body = match message.unwrap() {
+            ReplicationMessage::XLogData(body) => body.into_data(),
+            _ => continue,
+        };
+        let commit = matches!(body, LogicalReplicationMessage::Commit(_));
+        messages.push(body);
+        if commit {
+            break;
+        }
+    }
+
+    match &messages[..] {
+        [LogicalReplicationMessage::Begin(_), LogicalReplicationMessage::Relation(relation), LogicalReplicationMessage::Insert(insert), LogicalReplicationMessage::Commit(commit)] =>
+        {
+            assert_eq!(relation.name().unwrap(), "replication_test");
+            assert_eq!(relation.columns().len(), 2);
+            assert_eq!(insert.rel_id(), relation.rel_id());
+            match insert.tuple().tuple_data() {
+                [TupleData::Text(id), TupleData::Text(name)] => {
+                    assert_eq!(&id[..], b"1");
+                    assert_eq!(&name[..], b"steven");
+                }
+                data => panic!("unexpected tuple {:?}", data),
+            }
+
+            let lsn = PgLsn::from(commit.end_lsn());
+            stream
+                .as_mut()
+                .standby_status_update(lsn, lsn, lsn, SystemTime::now(), false)
+                .await
+                .unwrap();
+        }
+        messages => panic!("unexpected messages {:?}", messages),
+    }
+
+    stream.close().await.unwrap();
+    repl_client.simple_query("SELECT 1").await.unwrap();
+
+    client
+        .batch_execute(
+            "DROP PUBLICATION replication_test_pub;
+             DROP TABLE replication_test;",
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn physical_replication() {
+    let repl_client = connect("user=postgres replication=true").await;
+    let system = repl_client.identify_system().await.unwrap();
+    assert_eq!(system.dbname(), None);
+
+    let stream = repl_client
+        .start_physical_replication(None, system.xlogpos(), Some(system.timeline()))
+        .await
+        .unwrap();
+    pin_mut!(stream);
+
+    let client = connect("user=postgres").await;
+    client.batch_execute("SELECT txid_current()").await.unwrap();
+
+    loop {
+        match stream.next().await.unwrap().unwrap() {
+            ReplicationMessage::XLogData(body) => {
+                assert!(u64::from(system.xlogpos()) <= body.wal_start());
+                break;
+            }
+            ReplicationMessage::PrimaryKeepAlive(body) => {
+                let lsn = PgLsn::from(body.wal_end());
+                stream
+                    .as_mut()
+                    .standby_status_update(lsn, lsn, lsn, SystemTime::now(), false)
+                    .await
+                    .unwrap();
+            }
+            _ => {}
+        }
+    }
+
+    stream.close().await.unwrap();
+    repl_client.identify_system().await.unwrap();
+}
+
+#[tokio::test]
+async fn timeline_history() {
+    let repl_client = connect("user=postgres replication=true").await;
+    // The initial timeline has no history file.
+    let err = repl_client.timeline_history(1).await.unwrap_err();
+    assert!(err.as_db_error().is_some());
+}
+
+#[tokio::test]
+async fn base_backup() {
+    let repl_client = connect("user=postgres replication=true").await;
+
+    let mut archive = vec![];
+    let backup = repl_client
+        .base_backup(
+            "LABEL 'base_backup_test', CHECKPOINT 'fast', MANIFEST 'no'",
+            &mut archive,
+        )
+        .await
+        .unwrap();
+    assert!(backup.start_lsn() <= backup.end_lsn());
+
+    // Tar headers start with the entry's file name.
+    assert_eq!(archive.len() % 512, 0);
+    assert!(archive
+        .chunks(512)
+        .any(|block| block.starts_with(b"PG_VERSION\0")));
+
+    repl_client.identify_system().await.unwrap();
+}
@@ -0,0 +1,71 @@
+use bigdecimal_04::num_bigint::BigInt;
+use bigdecimal_04::BigDecimal;
+use bytes::BytesMut;
+use std::str::FromStr;
+use tokio_postgres::types::{ToSql, Type};
+
+use crate::connect;
+use crate::types::test_type;
+
+#[tokio::test]
+async fn test_bigdecimal_params() {
+    test_type(
+        "NUMERIC",
+        &[
+            (Some(BigDecimal::from_str("0").unwrap()), "'0'"),
+            (Some(BigDecimal::from_str("-1").unwrap()), "'-1'"),
+            (Some(BigDecimal::from_str("12.5").unwrap()), "'12.5'"),
+            (Some(BigDecimal::from_str("0.00001").unwrap()), "'0.00001'"),
+            (
+                Some(BigDecimal::from_str("-123456789.123456789").unwrap()),
+                "'-123456789.123456789'",
+            ),
+            (
+                Some(
+                    BigDecimal::from_str("1234567890123456789012345678901234567890.0987654321")
+                        .unwrap(),
+                ),
+                "'1234567890123456789012345678901234567890.0987654321'",
+            ),
+            (None, "NULL"),
+        ],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_bigdecimal_scale() {
+    let client = connect("user=postgres").await;
+
+    let value = client
+        .query_one(
+            "SELECT '1.50'::NUMERIC, $1::NUMERIC::TEXT, $2::NUMERIC::TEXT",
+            &[
+                &BigDecimal::from_str("1.50").unwrap(),
+                &BigDecimal::new(BigInt::from(-12), -5),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(value.get::<_, BigDecimal>(0).to_string(), "1.50");
+    assert_eq!(value.get::<_, &str>(1), "1.50");
+    assert_eq!(value.get::<_, &str>(2), "-1200000");
+
+    let value = client
+        .query_one("SELECT 'NaN'::NUMERIC, 'Infinity'::NUMERIC", &[])
+        .await
+        .unwrap();
+    assert!(value.try_get::<_, BigDecimal>(0).is_err());
+    assert!(value.try_get::<_, BigDecimal>(1).is_err());
+}
+
+#[test]
+fn test_bigdecimal_extreme_scale() {
+    let to_sql = |value: BigDecimal| value.to_sql(&Type::NUMERIC, &mut BytesMut::new()).is_ok();
+
+    assert!(!to_sql(BigDecimal::new(BigInt::from(1), i64::MIN)));
+    assert!(!to_sql(BigDecimal::new(BigInt::from(1), -131_072)));
+    assert!(to_sql(BigDecimal::new(BigInt::from(1), -131_071)));
+    assert!(!to_sql(BigDecimal::new(BigInt::from(1), i64::MAX)));
+    assert!(to_sql(BigDecimal::new(BigInt::from(0), i64::MIN)));
+}
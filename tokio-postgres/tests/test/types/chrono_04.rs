@@ -1,6 +1,7 @@
-use chrono_04::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono_04::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use std::convert::TryFrom;
 use std::fmt;
-use tokio_postgres::types::{Date, FromSqlOwned, Timestamp};
+use tokio_postgres::types::{Date, FromSqlOwned, PgInterval, Timestamp};
 use tokio_postgres::Client;
 
 use crate::connect;
@@ -188,3 +189,29 @@ async fn test_special_params_without_wrapper() {
     assert_overflows::<NaiveDate>(&mut client, "'-infinity'", "date").await;
     assert_overflows::<NaiveDate>(&mut client, "'infinity'", "date").await;
 }
+
+#[tokio::test]
+async fn test_interval_duration() {
+    let client = connect("user=postgres").await;
+
+    let row = client
+        .query_one(
+            "SELECT '1 day 02:00:00.5'::INTERVAL, '1 month'::INTERVAL",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        Duration::try_from(row.get::<_, PgInterval>(0)).unwrap(),
+        Duration::hours(26) + Duration::milliseconds(500)
+    );
+    assert!(Duration::try_from(row.get::<_, PgInterval>(1)).is_err());
+
+    let interval = PgInterval::try_from(-Duration::minutes(90)).unwrap();
+    let row = client
+        .query_one("SELECT $1 = '-1 hour -30 minutes'::INTERVAL", &[&interval])
+        .await
+        .unwrap();
+    assert!(row.get::<_, bool>(0));
+    assert!(PgInterval::try_from(Duration::nanoseconds(1500)).is_err());
+}
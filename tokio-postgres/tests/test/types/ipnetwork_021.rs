@@ -0,0 +1,70 @@
+use ipnetwork_021::{IpNetwork, Ipv4Network, Ipv6Network};
+
+use crate::connect;
+use crate::types::test_type;
+
+#[tokio::test]
+async fn test_ipnetwork_params() {
+    test_type(
+        "INET",
+        &[
+            (
+                Some("127.0.0.1/32".parse::<IpNetwork>().unwrap()),
+                "'127.0.0.1/32'",
+            ),
+            (
+                Some("10.1.2.3/8".parse::<IpNetwork>().unwrap()),
+                "'10.1.2.3/8'",
+            ),
+            (
+                Some("2001:4f8:3:ba::/64".parse::<IpNetwork>().unwrap()),
+                "'2001:4f8:3:ba::/64'",
+            ),
+            (None, "NULL"),
+        ],
+    )
+    .await;
+
+    test_type(
+        "CIDR",
+        &[
+            (
+                Some("10.0.0.0/8".parse::<IpNetwork>().unwrap()),
+                "'10.0.0.0/8'",
+            ),
+            (
+                Some("2001:4f8:3:ba::/64".parse::<IpNetwork>().unwrap()),
+                "'2001:4f8:3:ba::/64'",
+            ),
+            (None, "NULL"),
+        ],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_ipnetwork_family() {
+    let client = connect("user=postgres").await;
+
+    let row = client
+        .query_one("SELECT '192.168.0.1/24'::INET, '::1'::INET", &[])
+        .await
+        .unwrap();
+    assert_eq!(
+        row.get::<_, Ipv4Network>(0),
+        "192.168.0.1/24".parse::<Ipv4Network>().unwrap()
+    );
+    assert!(row.try_get::<_, Ipv6Network>(0).is_err());
+    assert_eq!(
+        row.get::<_, Ipv6Network>(1),
+        "::1/128".parse::<Ipv6Network>().unwrap()
+    );
+    assert!(row.try_get::<_, Ipv4Network>(1).is_err());
+
+    // host bits are not allowed in a CIDR value
+    let network = "10.1.2.3/8".parse::<IpNetwork>().unwrap();
+    assert!(client
+        .query_one("SELECT $1::CIDR", &[&network])
+        .await
+        .is_err());
+}
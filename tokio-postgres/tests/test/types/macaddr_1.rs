@@ -0,0 +1,59 @@
+use macaddr_1::{MacAddr, MacAddr6, MacAddr8};
+
+use crate::connect;
+use crate::types::test_type;
+
+#[tokio::test]
+async fn test_macaddr_params() {
+    test_type(
+        "MACADDR",
+        &[
+            (
+                Some(MacAddr6::new(0x12, 0x34, 0x56, 0xab, 0xcd, 0xef)),
+                "'12:34:56:ab:cd:ef'",
+            ),
+            (None, "NULL"),
+        ],
+    )
+    .await;
+
+    test_type(
+        "MACADDR8",
+        &[
+            (
+                Some(MacAddr8::new(
+                    0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0,
+                )),
+                "'12:34:56:78:9a:bc:de:f0'",
+            ),
+            (None, "NULL"),
+        ],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_macaddr_conversions() {
+    let client = connect("user=postgres").await;
+
+    let addr = MacAddr6::new(0x12, 0x34, 0x56, 0xab, 0xcd, 0xef);
+    let row = client
+        .query_one(
+            "SELECT $1::MACADDR8, $2::MACADDR, $2::MACADDR8",
+            &[&addr, &MacAddr::V6(addr)],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        row.get::<_, MacAddr8>(0),
+        MacAddr8::new(0x12, 0x34, 0x56, 0xff, 0xfe, 0xab, 0xcd, 0xef)
+    );
+    assert_eq!(row.get::<_, MacAddr>(1), MacAddr::V6(addr));
+    assert!(matches!(row.get::<_, MacAddr>(2), MacAddr::V8(_)));
+
+    let addr = MacAddr::V8(MacAddr8::new(1, 2, 3, 4, 5, 6, 7, 8));
+    assert!(client
+        .query_one("SELECT $1::MACADDR", &[&addr])
+        .await
+        .is_err());
+}
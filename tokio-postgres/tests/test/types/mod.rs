@@ -1,5 +1,6 @@
 use postgres_types::to_sql_checked;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::error::Error;
 use std::f32;
 use std::f64;
@@ -7,12 +8,18 @@ use std::fmt;
 use std::net::IpAddr;
 use std::result;
 use std::str::FromStr;
-use std::time::{Duration, UNIX_EPOCH};
-use tokio_postgres::types::{FromSql, FromSqlOwned, IsNull, Kind, PgLsn, ToSql, Type, WrongType};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio_postgres::types::{
+    Circle, FromSql, FromSqlOwned, IsNull, Kind, Line, LineSegment, Multirange, Path, PgBox,
+    PgInterval, PgLsn, Point, Polygon, Range, RangeBound, Timestamp, ToSql, TsLexeme, TsPosition,
+    TsQuery, TsQueryNode, TsVector, TsWeight, Type, WrongType,
+};
 
 use crate::connect;
 use bytes::BytesMut;
 
+#[cfg(feature = "with-bigdecimal-0_4")]
+mod bigdecimal_04;
 #[cfg(feature = "with-bit-vec-0_6")]
 mod bit_vec_06;
 #[cfg(feature = "with-chrono-0_4")]
@@ -23,10 +30,16 @@ mod eui48_1;
 mod geo_types_06;
 #[cfg(feature = "with-geo-types-0_7")]
 mod geo_types_07;
+#[cfg(feature = "with-ipnetwork-0_21")]
+mod ipnetwork_021;
 #[cfg(feature = "with-jiff-0_1")]
 mod jiff_01;
 #[cfg(feature = "with-jiff-0_2")]
 mod jiff_02;
+#[cfg(feature = "with-macaddr-1")]
+mod macaddr_1;
+#[cfg(feature = "with-rust_decimal-1")]
+mod rust_decimal_1;
 #[cfg(feature = "with-serde_json-1")]
 mod serde_json_1;
 #[cfg(feature = "with-smol_str-01")]
@@ -158,6 +171,143 @@ async fn test_lsn_params() {
     .await
 }
 
+#[tokio::test]
+async fn lsn_arithmetic() {
+    let client = connect("user=postgres").await;
+
+    let lsn = PgLsn::from_str("16/B374D848").unwrap();
+    let row = client
+        .query_one(
+            "SELECT $1::PG_LSN + 4096, $1::PG_LSN - 'FFFFFFFF/0'::PG_LSN < 0",
+            &[&lsn],
+        )
+        .await
+        .unwrap();
+    assert_eq!(row.get::<_, PgLsn>(0), lsn + 4096);
+    assert!(row.get::<_, bool>(1));
+    assert_eq!(
+        lsn.checked_distance_from(PgLsn::from_str("FFFFFFFF/0").unwrap()),
+        None
+    );
+    assert_eq!(lsn - PgLsn::from_str("16/0").unwrap(), 0xB374D848);
+    assert_eq!((lsn - 0xB374D848).to_string(), "16/0");
+
+    assert!(PgLsn::from_str("100000000/0").is_err());
+}
+
+#[tokio::test]
+async fn test_range_params() {
+    test_type(
+        "INT4RANGE",
+        &[
+            (
+                Some(Range::Nonempty(
+                    RangeBound::Inclusive(1),
+                    RangeBound::Exclusive(6),
+                )),
+                "'[1,5]'",
+            ),
+            (
+                Some(Range::Nonempty(
+                    RangeBound::Unbounded,
+                    RangeBound::Exclusive(-3),
+                )),
+                "'(,-3)'",
+            ),
+            (Some(Range::Empty), "'empty'"),
+            (None, "NULL"),
+        ],
+    )
+    .await;
+
+    test_type(
+        "INT8RANGE",
+        &[(
+            Some(Range::Nonempty(
+                RangeBound::<i64>::Unbounded,
+                RangeBound::Unbounded,
+            )),
+            "'(,)'",
+        )],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_multirange_params() {
+    test_type(
+        "INT8MULTIRANGE",
+        &[
+            (
+                Some(Multirange::new(vec![
+                    Range::Nonempty(RangeBound::Inclusive(1i64), RangeBound::Exclusive(3)),
+                    Range::Nonempty(RangeBound::Inclusive(10), RangeBound::Unbounded),
+                ])),
+                "'{[1,2], [10,)}'",
+            ),
+            (Some(Multirange::default()), "'{}'"),
+            (None, "NULL"),
+        ],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn multirange_normalized() {
+    let client = connect("user=postgres").await;
+
+    let multirange = Multirange::new(vec![
+        Range::Nonempty(RangeBound::Inclusive(5), RangeBound::Exclusive(8)),
+        Range::Empty,
+        Range::Nonempty(RangeBound::Inclusive(1), RangeBound::Inclusive(5)),
+    ]);
+    let row = client
+        .query_one("SELECT $1::INT4MULTIRANGE", &[&multirange])
+        .await
+        .unwrap();
+    assert_eq!(
+        row.get::<_, Multirange<i32>>(0).ranges(),
+        [Range::Nonempty(
+            RangeBound::Inclusive(1),
+            RangeBound::Exclusive(8)
+        )]
+    );
+
+    let row = client
+        .query_one("SELECT '{[1,2]}'::INT4MULTIRANGE", &[])
+        .await
+        .unwrap();
+    assert!(row.try_get::<_, Range<i32>>(0).is_err());
+    assert!(row.try_get::<_, Multirange<i64>>(0).is_err());
+}
+
+#[tokio::test]
+async fn custom_multirange() {
+    let client = connect("user=postgres").await;
+
+    client
+        .batch_execute(
+            "CREATE TYPE pg_temp.floatrange AS RANGE (
+                subtype = float8,
+                multirange_type_name = floatmultirange
+            )",
+        )
+        .await
+        .unwrap();
+
+    let multirange = Multirange::new(vec![Range::Nonempty(
+        RangeBound::Inclusive(1.5f64),
+        RangeBound::Exclusive(2.5),
+    )]);
+    let select = client.prepare("SELECT $1::floatmultirange").await.unwrap();
+    let type_ = &select.params()[0];
+    assert_eq!("floatmultirange", type_.name());
+    assert_eq!(&Kind::Multirange(Type::FLOAT8), type_.kind());
+
+    let row = client.query_one(&select, &[&multirange]).await.unwrap();
+    assert_eq!(row.get::<_, Multirange<f64>>(0), multirange);
+}
+
 #[tokio::test]
 async fn test_f32_params() {
     test_type(
@@ -622,6 +772,27 @@ async fn system_time() {
         ],
     )
     .await;
+
+    test_type(
+        "TIMESTAMPTZ",
+        &[
+            (
+                Timestamp::Value(UNIX_EPOCH + Duration::from_millis(1_010)),
+                "'1970-01-01 00:00:01.01+00'",
+            ),
+            (Timestamp::PosInfinity, "'infinity'"),
+            (Timestamp::NegInfinity, "'-infinity'"),
+        ],
+    )
+    .await;
+
+    let client = connect("user=postgres").await;
+    let row = client
+        .query_one("SELECT 'infinity'::TIMESTAMP, '-infinity'::TIMESTAMP", &[])
+        .await
+        .unwrap();
+    assert!(row.try_get::<_, SystemTime>(0).is_err());
+    assert!(row.try_get::<_, SystemTime>(1).is_err());
 }
 
 #[tokio::test]
@@ -742,6 +913,247 @@ async fn ltxtquery_any() {
     .await;
 }
 
+#[tokio::test]
+async fn tsvector() {
+    test_type(
+        "TSVECTOR",
+        &[
+            (
+                Some(TsVector::new(vec![
+                    TsLexeme {
+                        lexeme: "a".to_string(),
+                        positions: vec![],
+                    },
+                    TsLexeme {
+                        lexeme: "cat".to_string(),
+                        positions: vec![
+                            TsPosition {
+                                position: 3,
+                                weight: TsWeight::D,
+                            },
+                            TsPosition {
+                                position: 7,
+                                weight: TsWeight::A,
+                            },
+                        ],
+                    },
+                    TsLexeme {
+                        lexeme: "fat".to_string(),
+                        positions: vec![TsPosition {
+                            position: 2,
+                            weight: TsWeight::B,
+                        }],
+                    },
+                ])),
+                "'fat:2B cat:3,7A a'",
+            ),
+            (Some(TsVector::default()), "''"),
+            (None, "NULL"),
+        ],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn tsquery() {
+    fn lexeme(lexeme: &str) -> Box<TsQueryNode> {
+        Box::new(TsQueryNode::Lexeme {
+            lexeme: lexeme.to_string(),
+            weights: vec![],
+            prefix: false,
+        })
+    }
+
+    test_type(
+        "TSQUERY",
+        &[
+            (
+                Some(TsQuery::new(TsQueryNode::And(
+                    Box::new(TsQueryNode::Or(lexeme("fat"), lexeme("rat"))),
+                    Box::new(TsQueryNode::Not(lexeme("cat"))),
+                ))),
+                "'(fat | rat) & !cat'",
+            ),
+            (
+                Some(TsQuery::new(TsQueryNode::Phrase(
+                    Box::new(TsQueryNode::Lexeme {
+                        lexeme: "super".to_string(),
+                        weights: vec![TsWeight::A, TsWeight::B],
+                        prefix: true,
+                    }),
+                    lexeme("cat"),
+                    2,
+                ))),
+                "'super:AB* <2> cat'",
+            ),
+            (None, "NULL"),
+        ],
+    )
+    .await;
+
+    let client = connect("user=postgres").await;
+    let row = client.query_one("SELECT ''::TSQUERY", &[]).await.unwrap();
+    assert_eq!(row.get::<_, TsQuery>(0), TsQuery::empty());
+    assert!(client
+        .query_one("SELECT $1::TSQUERY", &[&TsQuery::empty()])
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn interval() {
+    test_type(
+        "INTERVAL",
+        &[
+            (
+                Some(PgInterval::new(14, 3, 4_500_000)),
+                "'1 year 2 months 3 days 4.5 seconds'",
+            ),
+            (
+                Some(PgInterval::new(0, -1, -60_000_000)),
+                "'-1 day -1 minute'",
+            ),
+            (Some(PgInterval::default()), "'0'"),
+            (None, "NULL"),
+        ],
+    )
+    .await;
+
+    let interval = PgInterval::new(0, 1, 1);
+    assert_eq!(
+        Duration::try_from(interval).unwrap(),
+        Duration::from_micros(86_400_000_001)
+    );
+    assert!(Duration::try_from(PgInterval::new(1, 0, 0)).is_err());
+    assert!(Duration::try_from(PgInterval::new(0, 0, -1)).is_err());
+    assert_eq!(
+        PgInterval::try_from(Duration::from_millis(1500)).unwrap(),
+        PgInterval::new(0, 0, 1_500_000)
+    );
+    assert!(PgInterval::try_from(Duration::from_nanos(1)).is_err());
+}
+
+#[tokio::test]
+async fn geometric() {
+    test_type(
+        "POINT",
+        &[
+            (Some(Point::new(-3.2, 1.618)), "'(-3.2, 1.618)'"),
+            (None, "NULL"),
+        ],
+    )
+    .await;
+    test_type(
+        "LINE",
+        &[(
+            Some(Line {
+                a: 1.0,
+                b: -1.0,
+                c: 2.5,
+            }),
+            "'{1, -1, 2.5}'",
+        )],
+    )
+    .await;
+    test_type(
+        "LSEG",
+        &[(
+            Some(LineSegment {
+                start: Point::new(0.0, 0.0),
+                end: Point::new(1.5, -1.0),
+            }),
+            "'[(0, 0), (1.5, -1)]'",
+        )],
+    )
+    .await;
+    test_type(
+        "BOX",
+        &[(
+            Some(PgBox {
+                high: Point::new(2.0, 3.0),
+                low: Point::new(-1.0, 0.5),
+            }),
+            "'(-1, 3), (2, 0.5)'",
+        )],
+    )
+    .await;
+    test_type(
+        "PATH",
+        &[
+            (
+                Some(Path {
+                    closed: false,
+                    points: vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)],
+                }),
+                "'[(0, 0), (1, 1)]'",
+            ),
+            (
+                Some(Path {
+                    closed: true,
+                    points: vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)],
+                }),
+                "'((0, 0), (1, 1))'",
+            ),
+        ],
+    )
+    .await;
+    test_type(
+        "POLYGON",
+        &[(
+            Some(Polygon {
+                points: vec![
+                    Point::new(0.0, 0.0),
+                    Point::new(0.0, 1.0),
+                    Point::new(1.0, 0.0),
+                ],
+            }),
+            "'((0, 0), (0, 1), (1, 0))'",
+        )],
+    )
+    .await;
+    test_type(
+        "CIRCLE",
+        &[(
+            Some(Circle {
+                center: Point::new(1.0, 2.0),
+                radius: 3.5,
+            }),
+            "'<(1, 2), 3.5>'",
+        )],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn record() {
+    let client = connect("user=postgres").await;
+
+    let row = client
+        .query_one("SELECT (1, 'foo'::TEXT, NULL::INT8)", &[])
+        .await
+        .unwrap();
+    let value: (i32, String, Option<i64>) = row.get(0);
+    assert_eq!(value, (1, "foo".to_string(), None));
+
+    client
+        .batch_execute("CREATE TYPE pg_temp.record_pair AS (id INT, name TEXT)")
+        .await
+        .unwrap();
+    let row = client
+        .query_one("SELECT ROW(1, 'foo')::record_pair", &[])
+        .await
+        .unwrap();
+    let value: (i32, String) = row.get(0);
+    assert_eq!(value, (1, "foo".to_string()));
+    assert!(row.try_get::<_, (i32, String, bool)>(0).is_err());
+
+    let row = client.query_one("SELECT (1, 'foo')", &[]).await.unwrap();
+    let err = row.try_get::<_, (i32, i32)>(0).unwrap_err();
+    assert!(err.source().unwrap().is::<WrongType>());
+    let err = row.try_get::<_, (i32,)>(0).unwrap_err();
+    assert!(err.source().unwrap().to_string().contains("field count"));
+}
+
 #[tokio::test]
 async fn oidvector() {
     test_type(
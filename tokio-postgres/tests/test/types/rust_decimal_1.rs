@@ -0,0 +1,59 @@
+use rust_decimal_1::Decimal;
+use std::str::FromStr;
+
+use crate::connect;
+use crate::types::test_type;
+
+#[tokio::test]
+async fn test_decimal_params() {
+    test_type(
+        "NUMERIC",
+        &[
+            (Some(Decimal::from_str("0").unwrap()), "'0'"),
+            (Some(Decimal::from_str("1").unwrap()), "'1'"),
+            (Some(Decimal::from_str("-1").unwrap()), "'-1'"),
+            (Some(Decimal::from_str("12.5").unwrap()), "'12.5'"),
+            (Some(Decimal::from_str("10000").unwrap()), "'10000'"),
+            (Some(Decimal::from_str("0.00001").unwrap()), "'0.00001'"),
+            (
+                Some(Decimal::from_str("-123456789.123456789").unwrap()),
+                "'-123456789.123456789'",
+            ),
+            (Some(Decimal::MAX), "'79228162514264337593543950335'"),
+            (None, "NULL"),
+        ],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_decimal_scale() {
+    let client = connect("user=postgres").await;
+
+    let value = client
+        .query_one(
+            "SELECT '1.50'::NUMERIC, $1::NUMERIC::TEXT",
+            &[&Decimal::new(150, 2)],
+        )
+        .await
+        .unwrap();
+    assert_eq!(value.get::<_, Decimal>(0).to_string(), "1.50");
+    assert_eq!(value.get::<_, &str>(1), "1.50");
+
+    let value = client
+        .query_one("SELECT '1.000000000000000000000000000000000'::NUMERIC", &[])
+        .await
+        .unwrap();
+    assert_eq!(value.get::<_, Decimal>(0), Decimal::ONE);
+
+    let value = client
+        .query_one(
+            "SELECT 'NaN'::NUMERIC, '0.00000000000000000000000000001'::NUMERIC, 1e40::NUMERIC",
+            &[],
+        )
+        .await
+        .unwrap();
+    assert!(value.try_get::<_, Decimal>(0).is_err());
+    assert!(value.try_get::<_, Decimal>(1).is_err());
+    assert!(value.try_get::<_, Decimal>(2).is_err());
+}
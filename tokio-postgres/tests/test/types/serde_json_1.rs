@@ -1,5 +1,7 @@
+use serde_json_1::value::RawValue;
 use serde_json_1::Value;
 
+use crate::connect;
 use crate::types::test_type;
 
 #[tokio::test]
@@ -39,3 +41,35 @@ async fn test_jsonb_params() {
     )
     .await
 }
+
+#[tokio::test]
+async fn test_raw_value_params() {
+    let client = connect("user=postgres").await;
+
+    for ty in &["JSON", "JSONB"] {
+        let row = client
+            .query_one(&*format!("SELECT '{{\"f\": [1, 2]}}'::{}", ty), &[])
+            .await
+            .unwrap();
+        let raw: &RawValue = row.get(0);
+        let value: Value = serde_json_1::from_str(raw.get()).unwrap();
+        assert_eq!(value, serde_json_1::json!({"f": [1, 2]}));
+
+        let raw = RawValue::from_string("{\"f\": [1, 2]}".to_string()).unwrap();
+        let row = client
+            .query_one(&*format!("SELECT $1::{0}, $1::{0}->'f'", ty), &[&raw])
+            .await
+            .unwrap();
+        let echoed: Box<RawValue> = row.get(0);
+        let value: Value = serde_json_1::from_str(echoed.get()).unwrap();
+        assert_eq!(value, serde_json_1::json!({"f": [1, 2]}));
+        let field: &RawValue = row.get(1);
+        assert_eq!(field.get().replace(' ', ""), "[1,2]");
+
+        let row = client
+            .query_one(&*format!("SELECT NULL::{}", ty), &[])
+            .await
+            .unwrap();
+        assert!(row.get::<_, Option<&RawValue>>(0).is_none());
+    }
+}